@@ -0,0 +1,39 @@
+//! The client side of the agent.
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::Shutdown,
+    os::unix::net::UnixStream,
+    path::Path,
+};
+
+use zeroize::Zeroize;
+
+use crate::error::PWDuckCliError;
+
+use super::{socket_path, Request, Response};
+
+/// Connect to the agent serving the vault, if there is one.
+#[must_use]
+pub fn connect(vault: &Path) -> Option<UnixStream> {
+    UnixStream::connect(socket_path(vault).ok()?).ok()
+}
+
+/// Send the [`Request`](Request) to the agent and wait for its [`Response`](Response).
+///
+/// # Errors
+///
+/// Returns `Err` if the connection to the agent fails.
+pub fn send(stream: &UnixStream, request: &Request) -> Result<Response, PWDuckCliError> {
+    let mut writer = stream;
+    serde_json::to_writer(&mut writer, request).map_err(std::io::Error::from)?;
+    writeln!(writer)?;
+    stream.shutdown(Shutdown::Write)?;
+
+    // The response may contain revealed passwords, so it is erased before any error is returned.
+    let mut line = String::new();
+    let read = BufReader::new(stream).read_line(&mut line);
+    let response = serde_json::from_str(&line).map_err(std::io::Error::from);
+    line.zeroize();
+    let _ = read?;
+    Ok(response?)
+}
@@ -0,0 +1,113 @@
+//! The agent unlocks a vault once and serves the commands of the client over a Unix domain socket.
+//!
+//! Each connection carries a single [`Request`](Request) followed by a single [`Response`](Response),
+//! both encoded as one line of JSON. The socket lives in a directory only accessible by the current
+//! user and is derived from the location of the vault, so the client finds the agent on its own.
+use std::{
+    fs::{self, DirBuilder},
+    os::unix::{
+        fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+};
+
+use pwduck_core::SecString;
+use serde::{Deserialize, Serialize};
+
+use crate::{args::Command, error::PWDuckCliError};
+
+pub mod client;
+
+mod server;
+pub use server::Agent;
+
+/// The permissions of the directory containing the sockets.
+const DIRECTORY_MODE: u32 = 0o700;
+
+/// The permissions of the socket.
+const SOCKET_MODE: u32 = 0o600;
+
+/// A request of the client to the agent.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Request {
+    /// The command to run on the vault. [`Lock`](Command::Lock) locks the vault and stops the agent.
+    pub command: Command,
+    /// The secrets needed by the command, read by the client.
+    pub secrets: Vec<SecString>,
+    /// True, if the output should be JSON.
+    pub json: bool,
+}
+
+/// The response of the agent to a [`Request`](Request).
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Response {
+    /// The exit code of the command.
+    pub code: i32,
+    /// The output of the command.
+    pub stdout: SecString,
+    /// The error of the command.
+    pub stderr: String,
+}
+
+/// The location of the socket of the agent serving the vault.
+///
+/// The socket is placed in the runtime directory of the user, if there is one.
+/// Otherwise it is placed in a directory of the user inside of the temporary directory.
+///
+/// # Errors
+///
+/// Returns `Err` if:
+/// - The vault does not exist.
+/// - The directory of the sockets can't be created.
+/// - The directory of the sockets is owned by or accessible by other users.
+pub fn socket_path(vault: &Path) -> Result<PathBuf, PWDuckCliError> {
+    user_socket(vault, "agent")
+}
+
+/// The location of a socket of the kind serving the vault, see [`socket_path`](socket_path).
+pub(crate) fn user_socket(vault: &Path, kind: &str) -> Result<PathBuf, PWDuckCliError> {
+    let directory = pwduck_core::socket_directory();
+    if let Err(error) = DirBuilder::new().mode(DIRECTORY_MODE).create(&directory) {
+        if error.kind() != std::io::ErrorKind::AlreadyExists {
+            return Err(error.into());
+        }
+    }
+    let metadata = fs::metadata(&directory)?;
+    if metadata.uid() != rustix::process::getuid().as_raw() {
+        return Err(PWDuckCliError::Refused(format!(
+            "The directory {} is owned by another user",
+            directory.display()
+        )));
+    }
+    if metadata.permissions().mode() & 0o077 != 0 {
+        return Err(PWDuckCliError::Refused(format!(
+            "The directory {} is accessible by other users",
+            directory.display()
+        )));
+    }
+
+    Ok(pwduck_core::socket_path(vault, kind)?)
+}
+
+/// Bind the socket of the agent, only accessible by the current user.
+/// A socket left behind by a crashed agent is replaced.
+///
+/// # Errors
+///
+/// Returns `Err` if another agent is listening on the socket or the socket can't be bound.
+pub fn bind(socket: &Path) -> Result<UnixListener, PWDuckCliError> {
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            return Err(PWDuckCliError::AlreadyExists(format!(
+                "The agent listening on {}",
+                socket.display()
+            )));
+        }
+        fs::remove_file(socket)?;
+    }
+
+    let listener = UnixListener::bind(socket)?;
+    fs::set_permissions(socket, fs::Permissions::from_mode(SOCKET_MODE))?;
+    Ok(listener)
+}
@@ -0,0 +1,287 @@
+//! The server side of the agent.
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    time::{Duration, Instant},
+};
+
+use pwduck_core::{MemKey, MergeSide, SecString, Vault};
+use zeroize::Zeroize;
+
+use crate::{args::Command, commands, error::PWDuckCliError};
+
+use super::{Request, Response};
+
+/// The time to wait between looking for new connections.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The time a client may take to send its request or to receive the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An agent serving an unlocked [`Vault`](Vault).
+///
+/// The master key of the [`Vault`](Vault) stays encrypted by the [`MemKey`](MemKey) while the agent runs.
+/// Dropping the agent locks the [`Vault`](Vault).
+#[derive(Debug)]
+pub struct Agent {
+    /// The unlocked [`Vault`](Vault).
+    vault: Vault,
+    /// The [`MemKey`](MemKey) protecting the master key of the [`Vault`](Vault).
+    mem_key: MemKey,
+    /// True, if commands changing the [`Vault`](Vault) are served.
+    allow_write: bool,
+    /// The time without requests after which the [`Vault`](Vault) is locked.
+    idle_timeout: Duration,
+}
+
+impl Agent {
+    /// Create a new [`Agent`](Agent) serving the unlocked [`Vault`](Vault).
+    ///
+    /// It expects:
+    ///  - The unlocked [`Vault`](Vault)
+    ///  - The [`MemKey`](MemKey) protecting the master key of the [`Vault`](Vault)
+    ///  - True, if commands changing the [`Vault`](Vault) are served
+    ///  - The time without requests after which the [`Vault`](Vault) is locked
+    #[must_use]
+    pub const fn new(
+        vault: Vault,
+        mem_key: MemKey,
+        allow_write: bool,
+        idle_timeout: Duration,
+    ) -> Self {
+        Self {
+            vault,
+            mem_key,
+            allow_write,
+            idle_timeout,
+        }
+    }
+
+    /// Serve the requests of the clients connecting to the listener until the [`Vault`](Vault)
+    /// is locked explicitly or the idle timeout expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the listener fails. Failing clients are ignored.
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn serve(mut self, listener: &UnixListener) -> Result<(), PWDuckCliError> {
+        listener.set_nonblocking(true)?;
+        let mut last_request = Instant::now();
+
+        loop {
+            match listener.accept() {
+                Ok((stream, _address)) => {
+                    last_request = Instant::now();
+                    if matches!(self.serve_client(&stream), Ok(true)) {
+                        return Ok(());
+                    }
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                    if last_request.elapsed() >= self.idle_timeout {
+                        return Ok(());
+                    }
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+    /// Answer the single request of the client. Returns true, if the [`Vault`](Vault) should be locked.
+    fn serve_client(&mut self, stream: &UnixStream) -> Result<bool, PWDuckCliError> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+        // The request may contain the master password, so it is erased before any error is returned.
+        let mut line = String::new();
+        let read = BufReader::new(stream).read_line(&mut line);
+        let request = serde_json::from_str::<Request>(&line);
+        line.zeroize();
+        let _ = read?;
+        let request = request.map_err(std::io::Error::from)?;
+
+        let lock = matches!(request.command, Command::Lock);
+        let response = self.handle(request);
+        let mut stream = stream;
+        serde_json::to_writer(&mut stream, &response).map_err(std::io::Error::from)?;
+        writeln!(stream)?;
+        Ok(lock)
+    }
+
+    /// Run the command of the [`Request`](Request) and capture its output in the [`Response`](Response).
+    pub fn handle(&mut self, request: Request) -> Response {
+        let mut stdout = Vec::new();
+        let result = self.run(&request.command, request.secrets, request.json, &mut stdout);
+
+        let mut response = Response {
+            stdout: String::from_utf8_lossy(&stdout).into_owned().into(),
+            ..Response::default()
+        };
+        stdout.zeroize();
+
+        if let Err(error) = result {
+            let mut stderr = Vec::new();
+            drop(error.report(request.json, &mut stderr));
+            response.code = error.kind().exit_code();
+            response.stderr = String::from_utf8_lossy(&stderr).into_owned();
+        }
+        response
+    }
+
+    /// Run the command on the [`Vault`](Vault) after reloading the changes made on disk by other programs.
+    fn run(
+        &mut self,
+        command: &Command,
+        secrets: Vec<SecString>,
+        json: bool,
+        out: &mut dyn Write,
+    ) -> Result<(), PWDuckCliError> {
+        if matches!(command, Command::Lock) {
+            return Ok(());
+        }
+        if !self.allow_write && !command.is_read_only() {
+            return Err(PWDuckCliError::Refused(
+                "The agent only serves commands reading the vault, start it with --allow-write"
+                    .into(),
+            ));
+        }
+
+        let master_key = self.vault.master_key().as_unprotected(
+            &self.mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        // All changes of the agent are saved immediately, so there are no local changes to keep.
+        let _ = self
+            .vault
+            .reload_external_changes(&master_key, Some(MergeSide::Remote))?;
+        drop(master_key);
+
+        commands::run(command, &mut self.vault, &self.mem_key, secrets, json, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{os::unix::net::UnixStream, thread, time::Duration};
+
+    use pwduck_core::ReferenceField;
+    use tempfile::tempdir;
+
+    use crate::{
+        agent::{bind, client, Request},
+        args::Command,
+        commands::tests::{example_vault, reload},
+        error::ErrorKind,
+        path::find_entry,
+    };
+
+    use super::Agent;
+
+    fn request(command: Command) -> Request {
+        Request {
+            command,
+            secrets: Vec::new(),
+            json: false,
+        }
+    }
+
+    #[test]
+    fn handle() {
+        let dir = tempdir().unwrap();
+        let (vault, mem_key) = example_vault(dir.path());
+        let (mut disk, disk_mem_key) = reload(&vault);
+        let mut agent = Agent::new(vault, mem_key, false, Duration::from_secs(30));
+
+        let response = agent.handle(request(Command::Show {
+            entry: "/Bank".into(),
+            field: Some(ReferenceField::Password),
+        }));
+        assert_eq!(response.code, 0);
+        assert_eq!(response.stdout.as_str(), "bank secret\n");
+        assert!(response.stderr.is_empty());
+
+        let mkdir = Command::Mkdir {
+            group: "/Servers".into(),
+            parents: false,
+        };
+        let response = agent.handle(request(mkdir));
+        assert_eq!(response.code, ErrorKind::Refused.exit_code());
+        assert!(response.stderr.starts_with("Error: "));
+
+        // Changes made by other programs are served.
+        let mut out = Vec::new();
+        crate::commands::run(
+            &Command::Mkdir {
+                group: "/Servers".into(),
+                parents: false,
+            },
+            &mut disk,
+            &disk_mem_key,
+            Vec::new(),
+            false,
+            &mut out,
+        )
+        .unwrap();
+        let response = agent.handle(Request {
+            json: true,
+            ..request(Command::Ls {
+                group: Some("/Servers".into()),
+            })
+        });
+        assert_eq!(response.code, 0);
+        assert!(response.stdout.contains("\"path\":\"/Servers\""));
+
+        let response = agent.handle(Request {
+            json: true,
+            ..request(Command::Ls {
+                group: Some("/Missing".into()),
+            })
+        });
+        assert_eq!(response.code, ErrorKind::NotFound.exit_code());
+        assert!(response.stderr.contains("\"kind\":\"not_found\""));
+    }
+
+    #[test]
+    fn serve() {
+        let dir = tempdir().unwrap();
+        let (vault, mem_key) = example_vault(dir.path());
+        let vault_path = vault.path().clone();
+        let socket = dir.path().join("agent.sock");
+        let listener = bind(&socket).unwrap();
+        assert!(bind(&socket).is_err());
+
+        let agent = Agent::new(vault, mem_key, true, Duration::from_secs(30));
+        let server = thread::spawn(move || agent.serve(&listener));
+
+        let mut add = request(Command::Add {
+            entry: "/Shop".into(),
+            username: "bob".into(),
+            email: String::new(),
+            web_address: String::new(),
+            secret_stdin: true,
+        });
+        add.secrets.push("shop secret".into());
+        let response = client::send(&UnixStream::connect(&socket).unwrap(), &add).unwrap();
+        assert_eq!(response.code, 0);
+
+        let lock = client::send(
+            &UnixStream::connect(&socket).unwrap(),
+            &request(Command::Lock),
+        );
+        assert_eq!(lock.unwrap().code, 0);
+        server.join().unwrap().unwrap();
+        assert!(UnixStream::connect(&socket).is_err());
+
+        let mem_key = pwduck_core::MemKey::with_length(1);
+        let vault = pwduck_core::Vault::load(
+            crate::commands::tests::PASSWORD,
+            Option::<String>::None,
+            &mem_key,
+            vault_path,
+        )
+        .unwrap();
+        assert!(find_entry(&vault, "/Shop").is_ok());
+    }
+}
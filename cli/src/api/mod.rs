@@ -0,0 +1,337 @@
+//! A local API serving an unlocked vault to paired programs over a Unix domain socket.
+//!
+//! Each connection carries requests of [JSON-RPC 2.0](https://www.jsonrpc.org/specification), one per line,
+//! and receives one response per line for each request with an `id`. A connection starts by authenticating
+//! a paired client with its key, all other methods are refused until then:
+//!
+//! | Method         | Parameters                                           | Result                                   |
+//! |----------------|------------------------------------------------------|------------------------------------------|
+//! | `authenticate` | `{"id": "...", "key": "..."}`                        | The [`ApiClient`](ApiClient) without its key |
+//! | `list`         | `{"group": "/Email"}`, defaults to the root group    | [`Listing`](crate::json::Listing)        |
+//! | `search`       | `{"term": "mail"}`                                   | [`SearchResults`](crate::json::SearchResults) |
+//! | `read`         | `{"entry": "/Email/Mail"}`                           | [`EntryDetails`](crate::json::EntryDetails) with `"password"` |
+//! | `create`       | `{"entry": "/Email/New", "username": "...", "email": "...", "web_address": "...", "password": "..."}` | [`EntryItem`](crate::json::EntryItem) |
+//!
+//! Failed requests are answered with an error whose `code` is the exit code of its
+//! [`ErrorKind`](crate::error::ErrorKind) and whose `data` is `{"kind": "refused"}`,
+//! or with one of the standard errors of JSON-RPC.
+//!
+//! Clients are paired by the user with `pwduck-api <vault> pair`, which prints the key of the client once.
+//! Only the hash of the key is stored. A client may be restricted to reading and to some groups,
+//! in which case it only sees these groups, their subgroups and their entries.
+//! Entries whose fields reference entries outside of these groups can't be read or created by the client.
+//! Every request of an authenticated client is appended to the audit log, including refused requests.
+use std::{
+    fs::{self, DirBuilder, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use pwduck_core::SecString;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{agent, error::PWDuckCliError};
+
+mod server;
+pub use server::{ApiServer, Session};
+
+/// The permissions of the directory containing the configuration and the audit log.
+const DIRECTORY_MODE: u32 = 0o700;
+
+/// The permissions of the configuration and the audit log.
+const FILE_MODE: u32 = 0o600;
+
+/// The number of random bytes of a key.
+const KEY_LENGTH: usize = 32;
+
+/// A client paired with the API.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ApiClient {
+    /// The random identifier of the client.
+    pub id: String,
+    /// The name given by the user.
+    pub name: String,
+    /// The SHA-256 of the key of the client.
+    pub key_hash: String,
+    /// True, if the client may not create entries.
+    pub read_only: bool,
+    /// The paths or UUIDs of the groups the client may access. Empty, if it may access the whole vault.
+    pub groups: Vec<String>,
+}
+
+/// The clients paired with the API of a vault.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ApiConfig {
+    /// The paired clients.
+    pub clients: Vec<ApiClient>,
+}
+
+impl ApiConfig {
+    /// Load the paired clients. A missing configuration has no clients.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the configuration can't be read.
+    pub fn load(path: &Path) -> Result<Self, PWDuckCliError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content).map_err(std::io::Error::from)?),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Save the paired clients, only accessible by the current user.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the configuration can't be written.
+    pub fn save(&self, path: &Path) -> Result<(), PWDuckCliError> {
+        let mut file = open_private(path, false)?;
+        serde_json::to_writer_pretty(&mut file, self).map_err(std::io::Error::from)?;
+        writeln!(file)?;
+        Ok(())
+    }
+
+    /// Find the client by its identifier and key.
+    #[must_use]
+    pub fn authenticate(&self, id: &str, key: &str) -> Option<&ApiClient> {
+        let hash = key_hash(key);
+        self.clients
+            .iter()
+            .find(|client| client.id == id && client.key_hash == hash)
+    }
+}
+
+/// An access of a client recorded in the audit log.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AuditRecord {
+    /// The seconds since the Unix epoch.
+    pub time: u64,
+    /// The identifier of the client.
+    pub client: String,
+    /// The name of the client.
+    pub name: String,
+    /// The method called by the client.
+    pub method: String,
+    /// The accessed group or entry or the search term.
+    pub item: String,
+    /// True, if the request was answered without an error.
+    pub allowed: bool,
+}
+
+impl AuditRecord {
+    /// Create a new [`AuditRecord`](AuditRecord) of the current time.
+    ///
+    /// It expects:
+    ///  - The identifier of the client
+    ///  - The name of the client, empty if the client is unknown
+    ///  - The called method
+    ///  - The accessed group or entry or the search term
+    ///  - True, if the request was answered without an error
+    #[must_use]
+    pub fn new(client: &str, name: &str, method: &str, item: String, allowed: bool) -> Self {
+        Self {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs()),
+            client: client.into(),
+            name: name.into(),
+            method: method.into(),
+            item,
+            allowed,
+        }
+    }
+}
+
+/// Append the record to the audit log.
+///
+/// # Errors
+///
+/// Returns `Err` if the audit log can't be written.
+pub fn append_audit(path: &Path, record: &AuditRecord) -> Result<(), PWDuckCliError> {
+    let mut file = open_private(path, true)?;
+    serde_json::to_writer(&mut file, record).map_err(std::io::Error::from)?;
+    writeln!(file)?;
+    Ok(())
+}
+
+/// Read all records of the audit log, the oldest first.
+///
+/// # Errors
+///
+/// Returns `Err` if the audit log can't be read.
+pub fn read_audit(path: &Path) -> Result<Vec<AuditRecord>, PWDuckCliError> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error.into()),
+    };
+    BufReader::new(file)
+        .lines()
+        .map(|line| Ok(serde_json::from_str(&line?).map_err(std::io::Error::from)?))
+        .collect()
+}
+
+/// The location of the socket of the API serving the vault.
+/// It is placed next to the socket of the [agent](crate::agent).
+///
+/// # Errors
+///
+/// Returns `Err` if the vault does not exist or the directory of the sockets can't be used.
+pub fn socket_path(vault: &Path) -> Result<PathBuf, PWDuckCliError> {
+    agent::user_socket(vault, "api")
+}
+
+/// The locations of the configuration and of the audit log of the vault in the configuration directory of the user.
+///
+/// # Errors
+///
+/// Returns `Err` if the vault does not exist or the user has no configuration directory.
+pub fn paths(vault: &Path) -> Result<(PathBuf, PathBuf), PWDuckCliError> {
+    let id = pwduck_core::vault_id(vault)?;
+    let directory = dirs::config_dir()
+        .ok_or_else(|| PWDuckCliError::NotFound("The configuration directory".into()))?
+        .join("pwduck");
+    Ok((
+        directory.join(format!("api-{}.json", id)),
+        directory.join(format!("api-{}-audit.log", id)),
+    ))
+}
+
+/// Pair a new client and return it with its key. The key can't be recovered later.
+///
+/// It expects:
+///  - The location of the configuration
+///  - The name of the client
+///  - True, if the client may not create entries
+///  - The paths or UUIDs of the groups the client may access, all groups if empty
+///
+/// # Errors
+///
+/// Returns `Err` if the name is empty or the configuration can't be written.
+pub fn pair(
+    path: &Path,
+    name: &str,
+    read_only: bool,
+    groups: Vec<String>,
+) -> Result<(ApiClient, SecString), PWDuckCliError> {
+    if name.trim().is_empty() {
+        return Err(PWDuckCliError::Refused("The client has no name".into()));
+    }
+    let mut config = ApiConfig::load(path)?;
+
+    let mut id = [0_u8; 8];
+    OsRng.fill_bytes(&mut id);
+    let mut key = [0_u8; KEY_LENGTH];
+    OsRng.fill_bytes(&mut key);
+    let key = SecString::from(base64::encode(key));
+
+    let client = ApiClient {
+        id: id.iter().map(|byte| format!("{:02x}", byte)).collect(),
+        name: name.into(),
+        key_hash: key_hash(key.as_str()),
+        read_only,
+        groups,
+    };
+    config.clients.push(client.clone());
+    config.save(path)?;
+    Ok((client, key))
+}
+
+/// Remove the paired client.
+///
+/// # Errors
+///
+/// Returns `Err` if the client does not exist or the configuration can't be written.
+pub fn unpair(path: &Path, id: &str) -> Result<(), PWDuckCliError> {
+    let mut config = ApiConfig::load(path)?;
+    let count = config.clients.len();
+    config.clients.retain(|client| client.id != id);
+    if config.clients.len() == count {
+        return Err(PWDuckCliError::NotFound(format!("The client {}", id)));
+    }
+    config.save(path)
+}
+
+/// The SHA-256 of the key encoded as hex.
+fn key_hash(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Open the file only accessible by the current user for writing, creating its directory if needed.
+fn open_private(path: &Path, append: bool) -> Result<fs::File, PWDuckCliError> {
+    if let Some(directory) = path.parent() {
+        DirBuilder::new()
+            .recursive(true)
+            .mode(DIRECTORY_MODE)
+            .create(directory)?;
+    }
+    Ok(OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .mode(FILE_MODE)
+        .open(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::{append_audit, pair, read_audit, unpair, ApiConfig, AuditRecord};
+
+    #[test]
+    fn clients_and_audit() {
+        let dir = tempdir().unwrap();
+        let config = dir.path().join("api").join("config.json");
+        assert!(ApiConfig::load(&config).unwrap().clients.is_empty());
+        assert!(pair(&config, " ", false, Vec::new()).is_err());
+
+        let (client, key) = pair(&config, "Backup", true, vec!["/Email".into()]).unwrap();
+        let (other, other_key) = pair(&config, "Deploy", false, Vec::new()).unwrap();
+        assert_ne!(key.as_str(), other_key.as_str());
+        let loaded = ApiConfig::load(&config).unwrap();
+        assert_eq!(loaded.clients.len(), 2);
+        assert!(!std::fs::read_to_string(&config)
+            .unwrap()
+            .contains(key.as_str()));
+        let found = loaded.authenticate(&client.id, key.as_str()).unwrap();
+        assert!(found.read_only);
+        assert_eq!(found.groups, ["/Email"]);
+        assert!(loaded
+            .authenticate(&client.id, other_key.as_str())
+            .is_none());
+        assert!(loaded.authenticate(&other.id, other_key.as_str()).is_some());
+
+        assert!(unpair(&config, "unknown").is_err());
+        unpair(&config, &client.id).unwrap();
+        let loaded = ApiConfig::load(&config).unwrap();
+        assert!(loaded.authenticate(&client.id, key.as_str()).is_none());
+
+        let audit = dir.path().join("api").join("audit.log");
+        assert!(read_audit(&audit).unwrap().is_empty());
+        append_audit(
+            &audit,
+            &AuditRecord::new(&client.id, &client.name, "read", "/Bank".into(), false),
+        )
+        .unwrap();
+        append_audit(
+            &audit,
+            &AuditRecord::new(&other.id, &other.name, "read", "/Bank".into(), true),
+        )
+        .unwrap();
+        let records = read_audit(&audit).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].name, "Backup");
+        assert!(!records[0].allowed);
+        assert_eq!(records[1].client, other.id);
+    }
+}
@@ -0,0 +1,980 @@
+//! The server side of the API.
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use pwduck_core::{
+    EntryHead, FieldReference, MemKey, MergeSide, ReferenceField, SecString, Uuid, Vault,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use zeroize::Zeroize;
+
+use crate::{
+    args::Command,
+    commands::{self, field_value},
+    error::PWDuckCliError,
+    json::{EntryItem, GroupItem, SearchResults},
+    path::{entry_path, find_entry, find_group, group_path, split},
+};
+
+use super::{append_audit, ApiClient, ApiConfig, AuditRecord};
+
+/// The time to wait between looking for new connections.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The time a client may wait between its requests before the connection is closed.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The error of JSON-RPC for requests that aren't valid JSON.
+const PARSE_ERROR: i32 = -32700;
+
+/// The error of JSON-RPC for requests that aren't valid requests.
+const INVALID_REQUEST: i32 = -32600;
+
+/// The error of JSON-RPC for unknown methods.
+const METHOD_NOT_FOUND: i32 = -32601;
+
+/// The error of JSON-RPC for invalid parameters.
+const INVALID_PARAMS: i32 = -32602;
+
+/// A request of JSON-RPC 2.0.
+#[derive(Debug, Deserialize)]
+struct Call {
+    /// The version of the protocol, always `2.0`.
+    jsonrpc: String,
+    /// The called method.
+    method: String,
+    /// The parameters of the method.
+    #[serde(default)]
+    params: Value,
+    /// The identifier of the request. Requests without one are notifications and aren't answered.
+    id: Option<Value>,
+}
+
+/// The parameters of `authenticate`.
+#[derive(Deserialize)]
+#[allow(missing_debug_implementations)]
+struct AuthenticateParams {
+    /// The identifier of the client.
+    id: String,
+    /// The key of the client.
+    key: SecString,
+}
+
+/// The parameters of `list`.
+#[derive(Debug, Default, Deserialize)]
+struct ListParams {
+    /// The path or UUID of the group, the root group if missing.
+    group: Option<String>,
+}
+
+/// The parameters of `search`.
+#[derive(Debug, Deserialize)]
+struct SearchParams {
+    /// The term to search for in the titles.
+    term: String,
+}
+
+/// The parameters of `read`.
+#[derive(Debug, Deserialize)]
+struct ReadParams {
+    /// The path or UUID of the entry.
+    entry: String,
+}
+
+/// The parameters of `create`.
+#[derive(Deserialize)]
+#[allow(missing_debug_implementations)]
+struct CreateParams {
+    /// The path of the new entry.
+    entry: String,
+    /// The username of the new entry.
+    #[serde(default)]
+    username: String,
+    /// The email of the new entry.
+    #[serde(default)]
+    email: String,
+    /// The web address of the new entry.
+    #[serde(default)]
+    web_address: String,
+    /// The password of the new entry.
+    #[serde(default)]
+    password: SecString,
+}
+
+/// The error of a request before it is answered as JSON-RPC.
+#[derive(Debug)]
+enum Failure {
+    /// An error of JSON-RPC itself with its code and message.
+    Protocol(i32, String),
+    /// An error of the vault.
+    Vault(PWDuckCliError),
+}
+
+impl From<PWDuckCliError> for Failure {
+    fn from(error: PWDuckCliError) -> Self {
+        Self::Vault(error)
+    }
+}
+
+/// The state of a single connection.
+#[derive(Debug, Default)]
+pub struct Session {
+    /// The identifier of the authenticated client.
+    client: Option<String>,
+}
+
+/// A server answering the requests of paired clients on an unlocked [`Vault`](Vault).
+///
+/// The master key of the [`Vault`](Vault) stays encrypted by the [`MemKey`](MemKey) while the server runs.
+/// Dropping the server locks the [`Vault`](Vault).
+#[derive(Debug)]
+pub struct ApiServer {
+    /// The unlocked [`Vault`](Vault).
+    vault: Vault,
+    /// The [`MemKey`](MemKey) protecting the master key of the [`Vault`](Vault).
+    mem_key: MemKey,
+    /// The location of the configuration of the paired clients.
+    config_path: PathBuf,
+    /// The location of the audit log.
+    audit_path: PathBuf,
+    /// The time without requests after which the [`Vault`](Vault) is locked.
+    idle_timeout: Duration,
+}
+
+impl ApiServer {
+    /// Create a new [`ApiServer`](ApiServer) serving the unlocked [`Vault`](Vault).
+    ///
+    /// It expects:
+    ///  - The unlocked [`Vault`](Vault)
+    ///  - The [`MemKey`](MemKey) protecting the master key of the [`Vault`](Vault)
+    ///  - The location of the configuration of the paired clients, see [`paths`](super::paths)
+    ///  - The location of the audit log
+    ///  - The time without requests after which the [`Vault`](Vault) is locked
+    #[must_use]
+    pub const fn new(
+        vault: Vault,
+        mem_key: MemKey,
+        config_path: PathBuf,
+        audit_path: PathBuf,
+        idle_timeout: Duration,
+    ) -> Self {
+        Self {
+            vault,
+            mem_key,
+            config_path,
+            audit_path,
+            idle_timeout,
+        }
+    }
+
+    /// Serve the clients connecting to the listener one after another until the idle timeout expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the listener fails. Failing clients are ignored.
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn serve(mut self, listener: &UnixListener) -> Result<(), PWDuckCliError> {
+        listener.set_nonblocking(true)?;
+        let mut last_request = Instant::now();
+
+        loop {
+            match listener.accept() {
+                Ok((stream, _address)) => {
+                    drop(self.serve_client(&stream));
+                    last_request = Instant::now();
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                    if last_request.elapsed() >= self.idle_timeout {
+                        return Ok(());
+                    }
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+    /// Answer the requests of the client, one per line, until it closes the connection or stays silent.
+    fn serve_client(&mut self, stream: &UnixStream) -> Result<(), PWDuckCliError> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+        let mut session = Session::default();
+        let mut reader = BufReader::new(stream);
+        let mut writer = stream;
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 {
+            let response = self.handle(&mut session, &line);
+            line.zeroize();
+            if let Some(response) = response {
+                serde_json::to_writer(&mut writer, &response).map_err(std::io::Error::from)?;
+                writeln!(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer the request of the session, given as a single line of JSON.
+    /// Returns `None` for notifications, which aren't answered.
+    pub fn handle(&mut self, session: &mut Session, request: &str) -> Option<Value> {
+        let call = match serde_json::from_str::<Value>(request) {
+            Ok(value) => value,
+            Err(error) => {
+                return Some(response(
+                    &Value::Null,
+                    Err(Failure::Protocol(PARSE_ERROR, error.to_string())),
+                ))
+            }
+        };
+        let id = call.get("id").cloned().unwrap_or(Value::Null);
+        let call = match serde_json::from_value::<Call>(call) {
+            Ok(call) if call.jsonrpc == "2.0" => call,
+            Ok(_call) => {
+                return Some(response(
+                    &id,
+                    Err(Failure::Protocol(
+                        INVALID_REQUEST,
+                        "Only JSON-RPC 2.0 is supported".into(),
+                    )),
+                ))
+            }
+            Err(error) => {
+                return Some(response(
+                    &id,
+                    Err(Failure::Protocol(INVALID_REQUEST, error.to_string())),
+                ))
+            }
+        };
+
+        let result = self.answer(session, &call.method, call.params);
+        call.id.map(|id| response(&id, result))
+    }
+
+    /// Answer the call of the method.
+    fn answer(
+        &mut self,
+        session: &mut Session,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, Failure> {
+        if method == "authenticate" {
+            let params: AuthenticateParams = parse(params)?;
+            return self.authenticate(session, &params);
+        }
+        if !matches!(method, "list" | "search" | "read" | "create") {
+            return Err(Failure::Protocol(
+                METHOD_NOT_FOUND,
+                format!("The method {} does not exist", method),
+            ));
+        }
+
+        let client = self.client(session)?;
+        self.reload()?;
+
+        let (item, result) = match method {
+            "list" => {
+                let params: ListParams = if params.is_null() {
+                    ListParams::default()
+                } else {
+                    parse(params)?
+                };
+                let group = params.group.unwrap_or_else(|| "/".into());
+                (group.clone(), self.list(&client, group))
+            }
+            "search" => {
+                let params: SearchParams = parse(params)?;
+                (params.term.clone(), self.search(&client, &params.term))
+            }
+            "read" => {
+                let params: ReadParams = parse(params)?;
+                let item = self
+                    .find_accessible_entry(&client, &params.entry)
+                    .map_or_else(
+                        |_err| params.entry.clone(),
+                        |head| entry_path(&self.vault, head),
+                    );
+                (item, self.read(&client, &params.entry))
+            }
+            _ => {
+                let params: CreateParams = parse(params)?;
+                (params.entry.clone(), self.create(&client, params))
+            }
+        };
+
+        let record = AuditRecord::new(&client.id, &client.name, method, item, result.is_ok());
+        append_audit(&self.audit_path, &record)?;
+        Ok(result?)
+    }
+
+    /// Authenticate the client of the session by its key. Failed attempts are recorded in the audit log.
+    fn authenticate(
+        &self,
+        session: &mut Session,
+        params: &AuthenticateParams,
+    ) -> Result<Value, Failure> {
+        session.client = None;
+        let config = ApiConfig::load(&self.config_path)?;
+        let client = config.authenticate(&params.id, params.key.as_str());
+        let name = client.map_or_else(
+            || {
+                config
+                    .clients
+                    .iter()
+                    .find(|client| client.id == params.id)
+                    .map_or_else(String::new, |client| client.name.clone())
+            },
+            |client| client.name.clone(),
+        );
+        let record = AuditRecord::new(
+            &params.id,
+            &name,
+            "authenticate",
+            String::new(),
+            client.is_some(),
+        );
+        append_audit(&self.audit_path, &record)?;
+
+        let client = client.ok_or_else(|| {
+            PWDuckCliError::Refused("The identifier or the key of the client is wrong".into())
+        })?;
+        session.client = Some(client.id.clone());
+        Ok(json!({
+            "id": client.id,
+            "name": client.name,
+            "read_only": client.read_only,
+            "groups": client.groups,
+        }))
+    }
+
+    /// The authenticated client of the session with its current permissions.
+    fn client(&self, session: &Session) -> Result<ApiClient, Failure> {
+        let not_authenticated =
+            || PWDuckCliError::Refused("The client is not authenticated".into());
+        let id = session.client.as_ref().ok_or_else(not_authenticated)?;
+        // The configuration is loaded again, so unpaired clients lose their access immediately.
+        Ok(ApiConfig::load(&self.config_path)?
+            .clients
+            .into_iter()
+            .find(|client| &client.id == id)
+            .ok_or_else(not_authenticated)?)
+    }
+
+    /// Reload the changes made on disk by other programs.
+    fn reload(&mut self) -> Result<(), PWDuckCliError> {
+        let master_key = self.vault.master_key().as_unprotected(
+            &self.mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        // All changes of the server are saved immediately, so there are no local changes to keep.
+        let _ = self
+            .vault
+            .reload_external_changes(&master_key, Some(MergeSide::Remote))?;
+        Ok(())
+    }
+
+    /// List the subgroups and entries of the group.
+    fn list(&mut self, client: &ApiClient, group: String) -> Result<Value, PWDuckCliError> {
+        let uuid = find_group(&self.vault, &group)?.uuid().clone();
+        self.ensure_access(client, &uuid)?;
+        self.run(&Command::Ls { group: Some(group) }, Vec::new())
+    }
+
+    /// Search the groups and entries accessible by the client.
+    fn search(&self, client: &ApiClient, term: &str) -> Result<Value, PWDuckCliError> {
+        let root = self
+            .vault
+            .get_root_uuid()
+            .ok_or_else(|| PWDuckCliError::NotFound("The root group".into()))?;
+        let results = self.vault.get_item_list_for(&root, Some(term));
+        let allowed = self.allowed_groups(client);
+        let results = SearchResults {
+            groups: results
+                .groups()
+                .iter()
+                .filter(|group| self.within(group.uuid(), allowed.as_deref()))
+                .map(|group| GroupItem::new(&self.vault, group))
+                .collect(),
+            entries: results
+                .entries()
+                .iter()
+                .filter(|entry| self.within(entry.parent(), allowed.as_deref()))
+                .map(|entry| EntryItem::new(&self.vault, entry))
+                .collect(),
+        };
+        Ok(serde_json::to_value(results).map_err(std::io::Error::from)?)
+    }
+
+    /// Read the details of the entry including its password.
+    /// The fields of the entry may only reference entries accessible by the client.
+    fn read(&mut self, client: &ApiClient, entry: &str) -> Result<Value, PWDuckCliError> {
+        let head = self.find_accessible_entry(client, entry)?;
+        let uuid = head.uuid().to_string();
+
+        let master_key = self.vault.master_key().as_unprotected(
+            &self.mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        let body = self.vault.load_entry_body(head.body(), &master_key)?;
+        drop(master_key);
+        self.ensure_reference_access(
+            client,
+            &[
+                body.username().as_str(),
+                body.password().as_str(),
+                body.email().as_str(),
+            ],
+        )?;
+
+        let mut details = self.run(
+            &Command::Show {
+                entry: uuid.clone(),
+                field: None,
+            },
+            Vec::new(),
+        )?;
+        let password = field_value(&self.vault, &self.mem_key, &uuid, ReferenceField::Password)?;
+        details["password"] = password.as_str().into();
+        Ok(details)
+    }
+
+    /// Create the entry, if the client may write to its group.
+    /// The values may only reference entries accessible by the client.
+    fn create(
+        &mut self,
+        client: &ApiClient,
+        params: CreateParams,
+    ) -> Result<Value, PWDuckCliError> {
+        if client.read_only {
+            return Err(PWDuckCliError::Refused(format!(
+                "The client {} may only read the vault",
+                client.name
+            )));
+        }
+        let (group, _title) = split(&params.entry)?;
+        let uuid = find_group(&self.vault, group)?.uuid().clone();
+        self.ensure_access(client, &uuid)?;
+        self.ensure_reference_access(
+            client,
+            &[&params.username, &params.email, params.password.as_str()],
+        )?;
+
+        self.run(
+            &Command::Add {
+                entry: params.entry,
+                username: params.username,
+                email: params.email,
+                web_address: params.web_address,
+                secret_stdin: true,
+            },
+            vec![params.password],
+        )
+    }
+
+    /// Run the command on the [`Vault`](Vault) and return its JSON output.
+    fn run(&mut self, command: &Command, secrets: Vec<SecString>) -> Result<Value, PWDuckCliError> {
+        let mut out = Vec::new();
+        commands::run(
+            command,
+            &mut self.vault,
+            &self.mem_key,
+            secrets,
+            true,
+            &mut out,
+        )?;
+        let value = serde_json::from_slice(&out).map_err(std::io::Error::from);
+        out.zeroize();
+        Ok(value?)
+    }
+
+    /// Find the entry by its path or UUID, if it lies within the groups of the client.
+    /// Entries outside of these groups are refused like missing ones, so the client can't tell whether they exist.
+    fn find_accessible_entry(
+        &self,
+        client: &ApiClient,
+        entry: &str,
+    ) -> Result<&EntryHead, PWDuckCliError> {
+        let allowed = self.allowed_groups(client);
+        // Only a missing entry in an accessible group is reported as missing.
+        let group_accessible = split(entry)
+            .ok()
+            .and_then(|(group, _title)| find_group(&self.vault, group).ok())
+            .map_or(false, |group| self.within(group.uuid(), allowed.as_deref()));
+        match find_entry(&self.vault, entry) {
+            Ok(head) if self.within(head.parent(), allowed.as_deref()) => Ok(head),
+            Err(error) if group_accessible => Err(error),
+            _ => Err(PWDuckCliError::Refused(format!(
+                "The client {} may not access {}",
+                client.name, entry
+            ))),
+        }
+    }
+
+    /// Fail if the group is outside of the groups of the client.
+    fn ensure_access(&self, client: &ApiClient, group: &Uuid) -> Result<(), PWDuckCliError> {
+        let allowed = self.allowed_groups(client);
+        if self.within(group, allowed.as_deref()) {
+            Ok(())
+        } else {
+            Err(PWDuckCliError::Refused(format!(
+                "The client {} may not access {}",
+                client.name,
+                group_path(&self.vault, group)
+            )))
+        }
+    }
+
+    /// Fail if the values reference entries outside of the groups of the client,
+    /// either directly or through the references in the referenced fields.
+    fn ensure_reference_access(
+        &self,
+        client: &ApiClient,
+        values: &[&str],
+    ) -> Result<(), PWDuckCliError> {
+        let master_key = self.vault.master_key().as_unprotected(
+            &self.mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        let mut pending: Vec<FieldReference> = values
+            .iter()
+            .flat_map(|value| FieldReference::find_all(value))
+            .collect();
+        let mut checked = Vec::new();
+        while let Some(reference) = pending.pop() {
+            if checked.contains(&reference) {
+                continue;
+            }
+            checked.push(reference.clone());
+
+            // Missing entries are reported when the references are resolved.
+            let head = match self.vault.entries().get(reference.target()) {
+                Some(head) => head,
+                None => continue,
+            };
+            self.ensure_access(client, head.parent())?;
+
+            let body = self.vault.load_entry_body(head.body(), &master_key)?;
+            let value = match reference.field() {
+                ReferenceField::Title => continue,
+                ReferenceField::Username => body.username(),
+                ReferenceField::Password => body.password(),
+                ReferenceField::Email => body.email(),
+            };
+            pending.extend(FieldReference::find_all(value));
+        }
+        Ok(())
+    }
+
+    /// The UUIDs of the groups accessible by the client or `None`, if it may access the whole vault.
+    /// Groups of the client that no longer exist are ignored.
+    fn allowed_groups(&self, client: &ApiClient) -> Option<Vec<Uuid>> {
+        if client.groups.is_empty() {
+            return None;
+        }
+        Some(
+            client
+                .groups
+                .iter()
+                .filter_map(|group| find_group(&self.vault, group).ok())
+                .map(|group| group.uuid().clone())
+                .collect(),
+        )
+    }
+
+    /// True, if the group is one of the allowed groups or one of their subgroups.
+    fn within(&self, group: &Uuid, allowed: Option<&[Uuid]>) -> bool {
+        let allowed = match allowed {
+            Some(allowed) => allowed,
+            None => return true,
+        };
+        let mut current = Some(group);
+        while let Some(uuid) = current {
+            if allowed.contains(uuid) {
+                return true;
+            }
+            current = self
+                .vault
+                .groups()
+                .get(uuid)
+                .and_then(|group| group.parent().as_ref());
+        }
+        false
+    }
+}
+
+/// Parse the parameters of the method.
+fn parse<T: DeserializeOwned>(params: Value) -> Result<T, Failure> {
+    serde_json::from_value(params)
+        .map_err(|error| Failure::Protocol(INVALID_PARAMS, error.to_string()))
+}
+
+/// The response of JSON-RPC to the request with the identifier.
+fn response(id: &Value, result: Result<Value, Failure>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(Failure::Protocol(code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message },
+        }),
+        Err(Failure::Vault(error)) => {
+            let kind = error.kind();
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {
+                    "code": kind.exit_code(),
+                    "message": error.to_string(),
+                    "data": { "kind": kind.name() },
+                },
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixStream,
+        path::Path,
+        thread,
+        time::Duration,
+    };
+
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    use pwduck_core::{FieldReference, ReferenceField};
+
+    use crate::{
+        agent::bind,
+        api::{pair, read_audit},
+        args::Command,
+        commands::tests::{example_vault, reload},
+        error::ErrorKind,
+        path::find_entry,
+    };
+
+    use super::{ApiServer, Session, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR};
+
+    fn server(dir: &Path) -> ApiServer {
+        let (vault, mem_key) = example_vault(dir);
+        ApiServer::new(
+            vault,
+            mem_key,
+            dir.join("api.json"),
+            dir.join("audit.log"),
+            Duration::from_secs(30),
+        )
+    }
+
+    fn call(server: &mut ApiServer, session: &mut Session, method: &str, params: &Value) -> Value {
+        let request = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+        server.handle(session, &request.to_string()).unwrap()
+    }
+
+    fn error_kind(response: &Value) -> &str {
+        response["error"]["data"]["kind"].as_str().unwrap()
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn handle() {
+        let dir = tempdir().unwrap();
+        let mut server = server(dir.path());
+        let config = dir.path().join("api.json");
+        let (mail, mail_key) = pair(&config, "Mail", true, vec!["/Email".into()]).unwrap();
+        let (admin, admin_key) = pair(&config, "Admin", false, Vec::new()).unwrap();
+
+        // JSON-RPC itself.
+        let mut session = Session::default();
+        let response = server.handle(&mut session, "{").unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        let response = call(&mut server, &mut session, "delete", &json!({}));
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        let response = call(&mut server, &mut session, "authenticate", &json!({}));
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        let notification = json!({"jsonrpc": "2.0", "method": "list"});
+        assert!(server
+            .handle(&mut session, &notification.to_string())
+            .is_none());
+
+        // Nothing is served before the authentication.
+        let response = call(&mut server, &mut session, "list", &Value::Null);
+        assert_eq!(error_kind(&response), "refused");
+        assert_eq!(response["error"]["code"], ErrorKind::Refused.exit_code());
+        let wrong = json!({"id": mail.id, "key": admin_key.as_str()});
+        let response = call(&mut server, &mut session, "authenticate", &wrong);
+        assert_eq!(error_kind(&response), "refused");
+
+        // A read-only client restricted to a group.
+        let credentials = json!({"id": mail.id, "key": mail_key.as_str()});
+        let response = call(&mut server, &mut session, "authenticate", &credentials);
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["read_only"], true);
+        assert!(response["result"].get("key_hash").is_none());
+
+        let response = call(&mut server, &mut session, "list", &Value::Null);
+        assert_eq!(error_kind(&response), "refused");
+        let response = call(
+            &mut server,
+            &mut session,
+            "list",
+            &json!({"group": "/Email"}),
+        );
+        let groups = response["result"]["groups"].as_array().unwrap();
+        assert!(groups.iter().any(|group| group["path"] == "/Email/Work"));
+        let response = call(&mut server, &mut session, "search", &json!({"term": "a"}));
+        let entries = response["result"]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["path"], "/Email/Work/Mail");
+
+        let read = json!({"entry": "/Email/Work/Mail"});
+        let response = call(&mut server, &mut session, "read", &read);
+        assert_eq!(response["result"]["username"], "alice@work");
+        assert_eq!(response["result"]["password"], "mail secret");
+        let response = call(
+            &mut server,
+            &mut session,
+            "read",
+            &json!({"entry": "/Bank"}),
+        );
+        assert_eq!(error_kind(&response), "refused");
+        // Entries outside of the groups can't be told apart from missing ones.
+        let bank = find_entry(&server.vault, "/Bank")
+            .unwrap()
+            .uuid()
+            .to_string();
+        let missing = "00000000-0000-0000-0000-000000000000";
+        for entry in ["/Missing", "/Private/Bank", bank.as_str(), missing] {
+            let response = call(
+                &mut server,
+                &mut session,
+                "read",
+                &json!({ "entry": entry }),
+            );
+            assert_eq!(error_kind(&response), "refused");
+            assert_eq!(
+                response["error"]["message"],
+                format!("The client Mail may not access {}", entry)
+            );
+        }
+        let response = call(
+            &mut server,
+            &mut session,
+            "read",
+            &json!({"entry": "/Email/Missing"}),
+        );
+        assert_eq!(error_kind(&response), "not_found");
+        let create = json!({"entry": "/Email/New", "password": "new secret"});
+        let response = call(&mut server, &mut session, "create", &create);
+        assert_eq!(error_kind(&response), "refused");
+
+        // A client without restrictions.
+        let mut session = Session::default();
+        let credentials = json!({"id": admin.id, "key": admin_key.as_str()});
+        let _ = call(&mut server, &mut session, "authenticate", &credentials);
+        let response = call(
+            &mut server,
+            &mut session,
+            "read",
+            &json!({"entry": "/Bank"}),
+        );
+        assert_eq!(response["result"]["password"], "bank secret");
+        let create = json!({"entry": "/Shop", "username": "bob", "password": "shop secret"});
+        let response = call(&mut server, &mut session, "create", &create);
+        assert_eq!(response["result"]["path"], "/Shop");
+        let (disk, disk_mem_key) = reload(&server.vault);
+        assert!(find_entry(&disk, "/Shop").is_ok());
+        drop(disk_mem_key);
+        let response = call(
+            &mut server,
+            &mut session,
+            "read",
+            &json!({"entry": "/Missing"}),
+        );
+        assert_eq!(error_kind(&response), "not_found");
+
+        // Unpaired clients lose their access immediately.
+        crate::api::unpair(&config, &admin.id).unwrap();
+        let response = call(&mut server, &mut session, "list", &Value::Null);
+        assert_eq!(error_kind(&response), "refused");
+
+        let audit = read_audit(&dir.path().join("audit.log")).unwrap();
+        let summary = audit
+            .iter()
+            .map(|record| {
+                (
+                    record.name.as_str(),
+                    record.method.as_str(),
+                    record.item.as_str(),
+                    record.allowed,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("Mail", "authenticate", "", false),
+                ("Mail", "authenticate", "", true),
+                ("Mail", "list", "/", false),
+                ("Mail", "list", "/Email", true),
+                ("Mail", "search", "a", true),
+                ("Mail", "read", "/Email/Work/Mail", true),
+                ("Mail", "read", "/Bank", false),
+                ("Mail", "read", "/Missing", false),
+                ("Mail", "read", "/Private/Bank", false),
+                ("Mail", "read", bank.as_str(), false),
+                (
+                    "Mail",
+                    "read",
+                    "00000000-0000-0000-0000-000000000000",
+                    false
+                ),
+                ("Mail", "read", "/Email/Missing", false),
+                ("Mail", "create", "/Email/New", false),
+                ("Admin", "authenticate", "", true),
+                ("Admin", "read", "/Bank", true),
+                ("Admin", "create", "/Shop", true),
+                ("Admin", "read", "/Missing", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn references_outside_of_scope() {
+        let dir = tempdir().unwrap();
+        let mut server = server(dir.path());
+        let (client, key) = pair(
+            &dir.path().join("api.json"),
+            "Mail",
+            false,
+            vec!["/Email".into()],
+        )
+        .unwrap();
+        let reference = |server: &ApiServer, entry: &str, field: ReferenceField| {
+            let uuid = find_entry(&server.vault, entry).unwrap().uuid().clone();
+            FieldReference::new(field, uuid).to_string()
+        };
+        let add = |server: &mut ApiServer, entry: &str, username: String, password: String| {
+            let command = Command::Add {
+                entry: entry.into(),
+                username,
+                email: String::new(),
+                web_address: String::new(),
+                secret_stdin: true,
+            };
+            let _ = server.run(&command, vec![password.into()]).unwrap();
+        };
+
+        let bank_password = reference(&server, "/Bank", ReferenceField::Password);
+        let bank_title = reference(&server, "/Bank", ReferenceField::Title);
+        let mail_password = reference(&server, "/Email/Work/Mail", ReferenceField::Password);
+        add(
+            &mut server,
+            "/Email/Leak",
+            String::new(),
+            bank_password.clone(),
+        );
+        add(
+            &mut server,
+            "/Email/Title",
+            bank_title.clone(),
+            String::new(),
+        );
+        add(
+            &mut server,
+            "/Email/Copy",
+            String::new(),
+            mail_password.clone(),
+        );
+        let leak_password = reference(&server, "/Email/Leak", ReferenceField::Password);
+        add(&mut server, "/Email/Chain", String::new(), leak_password);
+
+        let mut session = Session::default();
+        let credentials = json!({"id": client.id, "key": key.as_str()});
+        let _ = call(&mut server, &mut session, "authenticate", &credentials);
+
+        // Reading resolves the references, directly or through other entries.
+        for entry in ["/Email/Leak", "/Email/Title", "/Email/Chain"] {
+            let response = call(
+                &mut server,
+                &mut session,
+                "read",
+                &json!({ "entry": entry }),
+            );
+            assert_eq!(error_kind(&response), "refused");
+            assert!(response.get("result").is_none());
+        }
+        let response = call(
+            &mut server,
+            &mut session,
+            "read",
+            &json!({"entry": "/Email/Copy"}),
+        );
+        assert_eq!(response["result"]["password"], "mail secret");
+
+        // Created entries may not reference entries outside of the groups of the client.
+        let create = json!({"entry": "/Email/New", "password": bank_password});
+        let response = call(&mut server, &mut session, "create", &create);
+        assert_eq!(error_kind(&response), "refused");
+        let create = json!({"entry": "/Email/New", "username": bank_title});
+        let response = call(&mut server, &mut session, "create", &create);
+        assert_eq!(error_kind(&response), "refused");
+        assert!(find_entry(&server.vault, "/Email/New").is_err());
+
+        let create = json!({"entry": "/Email/New", "password": mail_password});
+        let response = call(&mut server, &mut session, "create", &create);
+        assert_eq!(response["result"]["path"], "/Email/New");
+    }
+
+    #[test]
+    fn serve() {
+        let dir = tempdir().unwrap();
+        let server = server(dir.path());
+        let (client, key) = pair(&dir.path().join("api.json"), "Script", true, Vec::new()).unwrap();
+        let socket = dir.path().join("api.sock");
+        let listener = bind(&socket).unwrap();
+        let handle = thread::spawn(move || {
+            ApiServer {
+                idle_timeout: Duration::from_secs(1),
+                ..server
+            }
+            .serve(&listener)
+        });
+
+        // Several requests share the connection and its authentication.
+        let stream = UnixStream::connect(&socket).unwrap();
+        let mut writer = &stream;
+        let mut reader = BufReader::new(&stream);
+        let requests = [
+            json!({"jsonrpc": "2.0", "id": "a", "method": "authenticate",
+                "params": {"id": client.id, "key": key.as_str()}}),
+            json!({"jsonrpc": "2.0", "id": "b", "method": "read", "params": {"entry": "/Bank"}}),
+        ];
+        for request in &requests {
+            writeln!(writer, "{}", request).unwrap();
+        }
+        let mut line = String::new();
+        let _ = reader.read_line(&mut line).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&line).unwrap()["id"], "a");
+        line.clear();
+        let _ = reader.read_line(&mut line).unwrap();
+        let response: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["result"]["password"], "bank secret");
+        drop(reader);
+        drop(stream);
+
+        // The server stops after the idle timeout.
+        handle.join().unwrap().unwrap();
+    }
+}
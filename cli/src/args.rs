@@ -0,0 +1,556 @@
+//! The arguments of the command line client.
+use std::{path::PathBuf, str::FromStr};
+
+use pwduck_core::ReferenceField;
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+use crate::error::PWDuckCliError;
+
+/// Read and edit password vaults on the command line.
+///
+/// Groups and entries are addressed by their UUIDs or by their paths like `/Email/Work`.
+/// If a `pwduck-agent` serves the vault, the commands are sent to it instead of unlocking the vault.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "pwduck-cli")]
+pub struct Args {
+    /// The vault and how to unlock it.
+    #[structopt(flatten)]
+    pub unlock: UnlockArgs,
+
+    /// Print the output and errors as JSON for scripts.
+    #[structopt(long)]
+    pub json: bool,
+
+    /// Always unlock the vault, even if a `pwduck-agent` serves it.
+    #[structopt(long)]
+    pub no_agent: bool,
+
+    /// The command to run on the unlocked vault.
+    #[structopt(subcommand)]
+    pub command: Command,
+}
+
+/// Unlock a vault once and serve the commands of `pwduck-cli` over a Unix domain socket.
+///
+/// The socket is only accessible by the current user. The agent locks the vault and exits
+/// after the idle timeout or when `pwduck-cli <VAULT> lock` is called.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "pwduck-agent")]
+pub struct AgentArgs {
+    /// The vault and how to unlock it.
+    #[structopt(flatten)]
+    pub unlock: UnlockArgs,
+
+    /// Lock the vault after this many seconds without requests.
+    #[structopt(short, long, default_value = "900")]
+    pub idle_timeout: u64,
+
+    /// Also serve the commands changing the vault.
+    #[structopt(long)]
+    pub allow_write: bool,
+}
+
+/// A credential helper of git storing the credentials in a vault.
+///
+/// Configure it with `git config --global credential.helper "pwduck /path/to/vault"`.
+/// The password of the vault is prompted for on the terminal, unless a `pwduck-agent` serves the vault.
+/// Storing and erasing credentials through the agent requires `--allow-write`.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "git-credential-pwduck")]
+pub struct GitCredentialArgs {
+    /// The location of the vault.
+    #[structopt(parse(from_os_str))]
+    pub vault: PathBuf,
+
+    /// The key file to unlock the vault.
+    #[structopt(short, long, parse(from_os_str))]
+    pub key_file: Option<PathBuf>,
+
+    /// The path of the group new entries are stored in.
+    #[structopt(short, long, default_value = "/")]
+    pub group: String,
+
+    /// Always unlock the vault, even if a `pwduck-agent` serves it.
+    #[structopt(long)]
+    pub no_agent: bool,
+
+    /// The operation requested by git. Unknown operations are ignored.
+    pub operation: String,
+}
+
+/// Provide a group of a vault as a collection of the Secret Service API on the D-Bus session bus.
+///
+/// Applications storing their secrets with `libsecret` store them in the group. Locking the collection
+/// forgets the key of the vault, unlocking it prompts for the password on the terminal of the provider.
+/// No other Secret Service, e.g. of GNOME Keyring, may run on the same bus.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "pwduck-secret-service")]
+pub struct SecretServiceArgs {
+    /// The vault and how to unlock it.
+    #[structopt(flatten)]
+    pub unlock: UnlockArgs,
+
+    /// The path or UUID of the group provided as the collection.
+    #[structopt(short, long, default_value = "/")]
+    pub group: String,
+
+    /// The name of the collection, only ASCII letters, digits and `_` are allowed.
+    #[structopt(short, long, default_value = "pwduck")]
+    pub collection: String,
+
+    /// The address of the bus, e.g. `unix:path=/run/user/1000/bus`. Defaults to the session bus.
+    #[structopt(long)]
+    pub address: Option<String>,
+}
+
+/// An SSH agent offering the SSH keys of the entries of a vault.
+///
+/// Point `SSH_AUTH_SOCK` to the printed socket. `ssh-add -x` locks the vault and removes the keys,
+/// `ssh-add -X` unlocks it again with the password of the vault.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "pwduck-ssh-agent")]
+pub struct SshAgentArgs {
+    /// The vault and how to unlock it.
+    #[structopt(flatten)]
+    pub unlock: UnlockArgs,
+
+    /// Lock the vault after this many seconds without signing.
+    #[structopt(short, long, default_value = "900")]
+    pub idle_timeout: u64,
+
+    /// The location of the socket. Defaults to a socket next to the one of `pwduck-agent`.
+    #[structopt(short, long, parse(from_os_str))]
+    pub socket: Option<PathBuf>,
+}
+
+/// A native messaging host connecting browser extensions to the vault served by `pwduck-agent`.
+///
+/// Browsers start the host on their own. Choose the vault once with `--configure`,
+/// then unlock it with `pwduck-agent` to let paired extensions find logins.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "pwduck-native-host")]
+pub struct NativeHostArgs {
+    /// Choose the vault served to the extensions and exit.
+    #[structopt(long, parse(from_os_str))]
+    pub configure: Option<PathBuf>,
+
+    /// The program asking for confirmations while no GUI has the vault open, like `ssh-askpass`.
+    /// Chosen together with `--configure`. Defaults to the program in `SSH_ASKPASS`, which browsers usually don't pass on.
+    #[structopt(long, parse(from_os_str), requires = "configure")]
+    pub askpass: Option<PathBuf>,
+
+    /// The location of the configuration. Defaults to `pwduck/native-host.json` in the configuration directory.
+    #[structopt(long, parse(from_os_str))]
+    pub config: Option<PathBuf>,
+
+    /// The arguments passed by the browser, e.g. the origin of the extension. They are ignored.
+    pub browser_arguments: Vec<String>,
+}
+
+/// A local JSON-RPC API letting paired programs list, search, read and create entries of a vault.
+///
+/// Without a command the vault is unlocked and served over a Unix domain socket until the idle timeout.
+/// Pair programs with `pair`, which prints the key the program authenticates with.
+/// Every access of a program is recorded in the audit log.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "pwduck-api")]
+pub struct ApiArgs {
+    /// The vault and how to unlock it.
+    #[structopt(flatten)]
+    pub unlock: UnlockArgs,
+
+    /// Lock the vault after this many seconds without requests.
+    #[structopt(short, long, default_value = "900")]
+    pub idle_timeout: u64,
+
+    /// The location of the socket. Defaults to a socket next to the one of `pwduck-agent`.
+    #[structopt(short, long, parse(from_os_str))]
+    pub socket: Option<PathBuf>,
+
+    /// Manage the paired programs instead of serving the vault.
+    #[structopt(subcommand)]
+    pub command: Option<ApiCommand>,
+}
+
+/// The commands managing the programs paired with the API of a vault.
+#[derive(Debug, StructOpt)]
+pub enum ApiCommand {
+    /// Pair a program and print its identifier and key. The key is only printed once.
+    Pair {
+        /// The name of the program.
+        name: String,
+
+        /// Refuse to create entries.
+        #[structopt(short, long)]
+        read_only: bool,
+
+        /// Only allow access to the group, its subgroups and entries. Repeat it for several groups.
+        #[structopt(short, long = "group")]
+        groups: Vec<String>,
+    },
+
+    /// Remove a paired program.
+    Unpair {
+        /// The identifier of the program.
+        id: String,
+    },
+
+    /// Print the paired programs and their permissions.
+    Clients,
+
+    /// Print the audit log of the accesses of the paired programs.
+    Audit {
+        /// Only print the accesses of the program with this identifier.
+        #[structopt(short, long)]
+        client: Option<String>,
+    },
+}
+
+/// The vault to unlock and how to unlock it.
+#[derive(Debug, StructOpt)]
+pub struct UnlockArgs {
+    /// The location of the vault.
+    #[structopt(parse(from_os_str))]
+    pub vault: PathBuf,
+
+    /// The key file to unlock the vault.
+    #[structopt(short, long, parse(from_os_str))]
+    pub key_file: Option<PathBuf>,
+
+    /// Read the password from the first line of the standard input instead of prompting for it.
+    /// Secrets of the command are read from the following lines.
+    #[structopt(long)]
+    pub password_stdin: bool,
+}
+
+/// The commands of the command line client.
+#[derive(Clone, Debug, StructOpt, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    /// List the subgroups and entries of a group.
+    Ls {
+        /// The path or UUID of the group. Defaults to the root group.
+        group: Option<String>,
+    },
+
+    /// Print the subgroups and entries of a group and of all its subgroups as a tree.
+    Tree {
+        /// The path or UUID of the group. Defaults to the root group.
+        group: Option<String>,
+    },
+
+    /// Show the details of an entry. The password is hidden.
+    Show {
+        /// The path or UUID of the entry.
+        entry: String,
+
+        /// Only print the value of the given field: title, username, password or email.
+        #[structopt(short, long)]
+        field: Option<ReferenceField>,
+    },
+
+    /// Search the groups and entries whose titles contain the given term, ignoring the case.
+    Search {
+        /// The term to search for.
+        term: String,
+    },
+
+    /// Generate a new random password. The vault is not unlocked for this.
+    Generate(Generate),
+
+    /// Run a program with fields of entries in its environment.
+    ///
+    /// The secrets are only passed to the environment of the program, never to its arguments or to a file.
+    Run(RunArgs),
+
+    /// Lock the vault served by the `pwduck-agent`.
+    Lock,
+
+    /// Answer a request of the credential helper protocol of git, see `git-credential-pwduck`.
+    ///
+    /// Requests are matched against the web addresses and usernames of the entries.
+    Credential(CredentialArgs),
+
+    /// List the entries whose web addresses match the URL, the most specific first.
+    ///
+    /// The URL is matched like a request of `git-credential-pwduck`, e.g. the entry `https://example.com`
+    /// matches `https://example.com/login`.
+    Match {
+        /// The URL, e.g. `https://example.com/login`.
+        url: String,
+    },
+
+    /// Manage the SSH key of an entry. The keys of all entries are offered by `pwduck-ssh-agent`.
+    SshKey(SshKeyCommand),
+
+    /// Add a new entry. Its password is prompted for.
+    Add {
+        /// The path of the new entry, e.g. `/Email/Work/Mail`. Its group must exist.
+        entry: String,
+
+        /// The username of the entry.
+        #[structopt(short, long, default_value = "")]
+        username: String,
+
+        /// The email of the entry.
+        #[structopt(short, long, default_value = "")]
+        email: String,
+
+        /// The web address of the entry.
+        #[structopt(short, long, default_value = "")]
+        web_address: String,
+
+        /// Read the password of the entry from the next line of the standard input instead of prompting for it.
+        #[structopt(long)]
+        secret_stdin: bool,
+    },
+
+    /// Change the fields of an entry. Fields that are not given stay unchanged.
+    Edit {
+        /// The path or UUID of the entry.
+        entry: String,
+
+        /// The new title of the entry.
+        #[structopt(short, long)]
+        title: Option<String>,
+
+        /// The new username of the entry.
+        #[structopt(short, long)]
+        username: Option<String>,
+
+        /// The new email of the entry.
+        #[structopt(short, long)]
+        email: Option<String>,
+
+        /// The new web address of the entry.
+        #[structopt(short, long)]
+        web_address: Option<String>,
+
+        /// Change the password of the entry. The new password is prompted for.
+        #[structopt(short, long)]
+        password: bool,
+
+        /// Read the new password from the next line of the standard input instead of prompting for it.
+        #[structopt(long, requires = "password")]
+        secret_stdin: bool,
+    },
+
+    /// Move an entry or a group into another group.
+    Mv {
+        /// The path or UUID of the entry or group to move. Entries are preferred over groups of the same path.
+        item: String,
+
+        /// The path or UUID of the new parent group.
+        group: String,
+    },
+
+    /// Remove an entry or a group.
+    Rm {
+        /// The path or UUID of the entry or group to remove. Entries are preferred over groups of the same path.
+        item: String,
+
+        /// Also remove groups that still contain subgroups or entries.
+        #[structopt(short, long)]
+        recursive: bool,
+
+        /// Also remove entries that are referenced by other entries.
+        #[structopt(short, long)]
+        force: bool,
+    },
+
+    /// Create a new group.
+    Mkdir {
+        /// The path of the new group, e.g. `/Email/Work`.
+        group: String,
+
+        /// Also create the missing parent groups.
+        #[structopt(short, long)]
+        parents: bool,
+    },
+}
+
+impl Command {
+    /// True, if this [`Command`](Command) does not change the vault.
+    #[must_use]
+    pub const fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Self::Ls { .. }
+                | Self::Tree { .. }
+                | Self::Show { .. }
+                | Self::Search { .. }
+                | Self::Generate(_)
+                | Self::Run(_)
+                | Self::Lock
+                | Self::Match { .. }
+                | Self::SshKey(SshKeyCommand::Public { .. })
+                | Self::Credential(CredentialArgs {
+                    operation: Operation::Get,
+                    ..
+                })
+        )
+    }
+}
+
+/// The options of the password generator.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, StructOpt, Deserialize, Serialize)]
+pub struct Generate {
+    /// The length of the password.
+    #[structopt(short, long, default_value = "32")]
+    pub length: u8,
+
+    /// Exclude uppercase latin characters.
+    #[structopt(long)]
+    pub no_upper: bool,
+
+    /// Exclude lowercase latin characters.
+    #[structopt(long)]
+    pub no_lower: bool,
+
+    /// Exclude the digits 0-9.
+    #[structopt(long)]
+    pub no_numbers: bool,
+
+    /// Exclude special characters.
+    #[structopt(long)]
+    pub no_special: bool,
+}
+
+/// The options of running a program with secrets in its environment.
+#[derive(Clone, Debug, StructOpt, Deserialize, Serialize)]
+pub struct RunArgs {
+    /// Bind an environment variable to a field of an entry, e.g. `DB_PASSWORD=/Servers/Database:password`.
+    /// The field defaults to the password.
+    #[structopt(short, long = "env", number_of_values = 1)]
+    pub env: Vec<String>,
+
+    /// Read the bindings from a file with one `VARIABLE=<entry>:<field>` per line.
+    /// Empty lines and lines starting with `#` are ignored.
+    /// Defaults to `.pwduck-env` in the current directory, if no bindings are given.
+    #[structopt(long, parse(from_os_str))]
+    pub env_file: Option<PathBuf>,
+
+    /// The path of the group containing the entries of relative paths, i.e. paths without a leading `/`.
+    /// This allows to use the same bindings for different groups, e.g. `/Staging` and `/Production`.
+    #[structopt(short, long)]
+    pub group: Option<String>,
+
+    /// The program to run and its arguments, given after `--`.
+    #[structopt(required = true, last = true)]
+    pub program: Vec<String>,
+}
+
+/// The commands managing the SSH key of an entry.
+///
+/// If the key is encrypted, the password of the entry is its passphrase.
+#[derive(Clone, Debug, StructOpt, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SshKeyCommand {
+    /// Generate a new Ed25519 key for the entry and print its public key.
+    Generate {
+        /// The path or UUID of the entry.
+        entry: String,
+
+        /// The comment of the key. Defaults to the title of the entry.
+        #[structopt(short, long)]
+        comment: Option<String>,
+
+        /// Encrypt the key with the password of the entry as its passphrase.
+        #[structopt(long)]
+        encrypt: bool,
+
+        /// Ask for a confirmation before each use of the key by the agent.
+        #[structopt(long)]
+        confirm: bool,
+
+        /// Replace the existing key of the entry.
+        #[structopt(short, long)]
+        force: bool,
+    },
+
+    /// Import an OpenSSH private key, e.g. `~/.ssh/id_ed25519`, and print its public key.
+    /// The passphrase of an encrypted key must be the password of the entry.
+    Import {
+        /// The path or UUID of the entry.
+        entry: String,
+
+        /// The file of the private key. It is read by the client.
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+
+        /// Ask for a confirmation before each use of the key by the agent.
+        #[structopt(long)]
+        confirm: bool,
+
+        /// Replace the existing key of the entry.
+        #[structopt(short, long)]
+        force: bool,
+    },
+
+    /// Print the public key of the entry in the format of `authorized_keys`.
+    Public {
+        /// The path or UUID of the entry.
+        entry: String,
+    },
+}
+
+/// The request of the credential helper protocol of git.
+#[derive(Clone, Debug, StructOpt, Deserialize, Serialize)]
+pub struct CredentialArgs {
+    /// The operation: get, store or erase.
+    pub operation: Operation,
+
+    /// The protocol of the request, e.g. `https`.
+    #[structopt(long)]
+    pub protocol: Option<String>,
+
+    /// The host of the request including the port, e.g. `example.com:8080`.
+    #[structopt(long)]
+    pub host: Option<String>,
+
+    /// The path of the request, if git is configured to send it.
+    #[structopt(long)]
+    pub path: Option<String>,
+
+    /// The username of the request.
+    #[structopt(short, long)]
+    pub username: Option<String>,
+
+    /// The path of the group new entries are stored in.
+    #[structopt(short, long, default_value = "/")]
+    pub group: String,
+
+    /// Read the password of `store` and `erase` from the next line of the standard input instead of prompting for it.
+    #[structopt(long)]
+    pub secret_stdin: bool,
+}
+
+/// The operations of the credential helper protocol of git.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    /// Print the username and the password of the best matching entry.
+    Get,
+    /// Update the password of the matching entry or add a new entry.
+    Store,
+    /// Remove the matching entries with the given password.
+    Erase,
+}
+
+impl FromStr for Operation {
+    type Err = PWDuckCliError;
+
+    fn from_str(operation: &str) -> Result<Self, Self::Err> {
+        match operation {
+            "get" => Ok(Self::Get),
+            "store" => Ok(Self::Store),
+            "erase" => Ok(Self::Erase),
+            _ => Err(PWDuckCliError::NotFound(format!(
+                "The credential operation {}",
+                operation
+            ))),
+        }
+    }
+}
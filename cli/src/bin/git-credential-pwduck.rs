@@ -0,0 +1,99 @@
+//! A credential helper of git storing the credentials in a vault.
+//!
+//! Git runs the helper with the operation `get`, `store` or `erase` and writes the request to its
+//! standard input, see: [`git`](pwduck_cli::git). The password of the vault is prompted for on the
+//! terminal, unless a `pwduck-agent` serves the vault.
+#![cfg_attr(coverage, feature(no_coverage))]
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+#![deny(unused_results)]
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::pedantic,
+    clippy::nursery,
+
+    // Restriction lints
+    clippy::clone_on_ref_ptr,
+    clippy::create_dir,
+    clippy::dbg_macro,
+    clippy::decimal_literal_representation,
+    clippy::exit,
+    clippy::float_cmp_const,
+    clippy::get_unwrap,
+    clippy::let_underscore_must_use,
+    clippy::map_err_ignore,
+    clippy::mem_forget,
+    clippy::missing_docs_in_private_items,
+    clippy::multiple_inherent_impl,
+    clippy::panic,
+    clippy::panic_in_result_fn,
+    clippy::print_stderr,
+    clippy::print_stdout,
+    clippy::rest_pat_in_fully_bound_structs,
+    clippy::str_to_string,
+    clippy::string_to_string,
+    clippy::todo,
+    clippy::unimplemented,
+    clippy::unneeded_field_pattern,
+    clippy::unwrap_in_result,
+    clippy::unwrap_used,
+    clippy::use_debug,
+)]
+#![allow(
+    clippy::suboptimal_flops,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_wrap,
+    clippy::module_name_repetitions
+)]
+
+use pwduck_cli::{
+    args::{Command, GitCredentialArgs, Operation, UnlockArgs},
+    dispatch::dispatch,
+    error::PWDuckCliError,
+    git,
+};
+use structopt::StructOpt;
+
+#[cfg_attr(coverage, no_coverage)]
+fn main() {
+    let args = GitCredentialArgs::from_args();
+    let code = run(&args).unwrap_or_else(|error| {
+        drop(error.report(false, &mut std::io::stderr()));
+        error.kind().exit_code()
+    });
+    if code != 0 {
+        #[allow(clippy::exit)]
+        std::process::exit(code);
+    }
+}
+
+/// Answer the request of git and return the exit code.
+#[cfg_attr(coverage, no_coverage)]
+fn run(args: &GitCredentialArgs) -> Result<i32, PWDuckCliError> {
+    pwduck_core::try_to_prevent_core_dump()?;
+
+    // Git expects helpers to ignore operations they don't know.
+    let operation: Operation = match args.operation.parse() {
+        Ok(operation) => operation,
+        Err(_error) => return Ok(0),
+    };
+    let (request, password) =
+        git::read_request(&mut std::io::stdin().lock(), operation, &args.group)?;
+
+    // The standard input belongs to git, so the password of the vault is prompted for on the terminal.
+    let unlock_args = UnlockArgs {
+        vault: args.vault.clone(),
+        key_file: args.key_file.clone(),
+        password_stdin: false,
+    };
+    dispatch(
+        &unlock_args,
+        args.no_agent,
+        &Command::Credential(request),
+        Some(password.into_iter().collect()),
+        false,
+        &mut std::io::empty(),
+        &mut std::io::stdout().lock(),
+    )
+}
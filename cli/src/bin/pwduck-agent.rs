@@ -0,0 +1,113 @@
+//! An agent unlocking a vault of the password manager once for the command line client.
+//!
+//! It serves the commands of `pwduck-cli` over a Unix domain socket until it is locked
+//! explicitly or the idle timeout expires. See: [agent](pwduck_cli::agent).
+#![cfg_attr(coverage, feature(no_coverage))]
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+#![deny(unused_results)]
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::pedantic,
+    clippy::nursery,
+
+    // Restriction lints
+    clippy::clone_on_ref_ptr,
+    clippy::create_dir,
+    clippy::dbg_macro,
+    clippy::decimal_literal_representation,
+    clippy::exit,
+    clippy::float_cmp_const,
+    clippy::get_unwrap,
+    clippy::let_underscore_must_use,
+    clippy::map_err_ignore,
+    clippy::mem_forget,
+    clippy::missing_docs_in_private_items,
+    clippy::multiple_inherent_impl,
+    clippy::panic,
+    clippy::panic_in_result_fn,
+    clippy::print_stderr,
+    clippy::print_stdout,
+    clippy::rest_pat_in_fully_bound_structs,
+    clippy::str_to_string,
+    clippy::string_to_string,
+    clippy::todo,
+    clippy::unimplemented,
+    clippy::unneeded_field_pattern,
+    clippy::unwrap_in_result,
+    clippy::unwrap_used,
+    clippy::use_debug,
+)]
+#![allow(
+    clippy::suboptimal_flops,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_wrap,
+    clippy::module_name_repetitions
+)]
+
+#[cfg(unix)]
+use std::{io::Write, time::Duration};
+
+#[cfg(unix)]
+use pwduck_cli::{
+    agent::{self, Agent},
+    args::AgentArgs,
+    error::PWDuckCliError,
+    unlock,
+};
+#[cfg(unix)]
+use pwduck_core::MemKey;
+#[cfg(unix)]
+use structopt::StructOpt;
+
+#[cfg(unix)]
+#[cfg_attr(coverage, no_coverage)]
+fn main() {
+    let args = AgentArgs::from_args();
+    if let Err(error) = run(&args) {
+        drop(error.report(false, &mut std::io::stderr()));
+        #[allow(clippy::exit)]
+        std::process::exit(error.kind().exit_code());
+    }
+}
+
+#[cfg(not(unix))]
+#[cfg_attr(coverage, no_coverage)]
+fn main() {
+    drop(std::io::Write::write_all(
+        &mut std::io::stderr(),
+        b"Error: The agent is only available on Unix systems\n",
+    ));
+    #[allow(clippy::exit)]
+    std::process::exit(1);
+}
+
+/// Unlock the vault and serve it until it is locked.
+#[cfg(unix)]
+#[cfg_attr(coverage, no_coverage)]
+fn run(args: &AgentArgs) -> Result<(), PWDuckCliError> {
+    pwduck_core::try_to_prevent_core_dump()?;
+
+    let socket = agent::socket_path(&args.unlock.vault)?;
+    let mem_key = MemKey::new();
+    let vault = unlock::unlock(&args.unlock, &mem_key, &mut std::io::stdin().lock())?;
+
+    let listener = agent::bind(&socket)?;
+    writeln!(
+        std::io::stdout(),
+        "Serving {} on {}",
+        args.unlock.vault.display(),
+        socket.display()
+    )?;
+
+    let result = Agent::new(
+        vault,
+        mem_key,
+        args.allow_write,
+        Duration::from_secs(args.idle_timeout),
+    )
+    .serve(&listener);
+    drop(std::fs::remove_file(&socket));
+    result
+}
@@ -0,0 +1,174 @@
+//! A local JSON-RPC API serving a vault of the password manager to paired programs.
+//!
+//! It unlocks the vault once and answers the requests of the paired programs over a Unix domain socket.
+//! See: [`api`](pwduck_cli::api).
+#![cfg_attr(coverage, feature(no_coverage))]
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+#![deny(unused_results)]
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::pedantic,
+    clippy::nursery,
+
+    // Restriction lints
+    clippy::clone_on_ref_ptr,
+    clippy::create_dir,
+    clippy::dbg_macro,
+    clippy::decimal_literal_representation,
+    clippy::exit,
+    clippy::float_cmp_const,
+    clippy::get_unwrap,
+    clippy::let_underscore_must_use,
+    clippy::map_err_ignore,
+    clippy::mem_forget,
+    clippy::missing_docs_in_private_items,
+    clippy::multiple_inherent_impl,
+    clippy::panic,
+    clippy::panic_in_result_fn,
+    clippy::print_stderr,
+    clippy::print_stdout,
+    clippy::rest_pat_in_fully_bound_structs,
+    clippy::str_to_string,
+    clippy::string_to_string,
+    clippy::todo,
+    clippy::unimplemented,
+    clippy::unneeded_field_pattern,
+    clippy::unwrap_in_result,
+    clippy::unwrap_used,
+    clippy::use_debug,
+)]
+#![allow(
+    clippy::suboptimal_flops,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_wrap,
+    clippy::module_name_repetitions
+)]
+
+#[cfg(unix)]
+use std::{io::Write, time::Duration};
+
+#[cfg(unix)]
+use pwduck_cli::{
+    agent,
+    api::{self, ApiConfig, ApiServer},
+    args::{ApiArgs, ApiCommand},
+    error::PWDuckCliError,
+    unlock,
+};
+#[cfg(unix)]
+use pwduck_core::MemKey;
+#[cfg(unix)]
+use structopt::StructOpt;
+
+#[cfg(unix)]
+#[cfg_attr(coverage, no_coverage)]
+fn main() {
+    let args = ApiArgs::from_args();
+    if let Err(error) = run(&args) {
+        drop(error.report(false, &mut std::io::stderr()));
+        #[allow(clippy::exit)]
+        std::process::exit(error.kind().exit_code());
+    }
+}
+
+#[cfg(not(unix))]
+#[cfg_attr(coverage, no_coverage)]
+fn main() {
+    drop(std::io::Write::write_all(
+        &mut std::io::stderr(),
+        b"Error: The API is only available on Unix systems\n",
+    ));
+    #[allow(clippy::exit)]
+    std::process::exit(1);
+}
+
+/// Manage the paired programs or unlock the vault and serve it until the idle timeout.
+#[cfg(unix)]
+#[cfg_attr(coverage, no_coverage)]
+fn run(args: &ApiArgs) -> Result<(), PWDuckCliError> {
+    pwduck_core::try_to_prevent_core_dump()?;
+
+    let (config, audit) = api::paths(&args.unlock.vault)?;
+    let mut out = std::io::stdout();
+    match &args.command {
+        Some(ApiCommand::Pair {
+            name,
+            read_only,
+            groups,
+        }) => {
+            let (client, key) = api::pair(&config, name, *read_only, groups.clone())?;
+            writeln!(out, "ID:  {}", client.id)?;
+            writeln!(out, "Key: {}", key.as_str())?;
+            return Ok(());
+        }
+        Some(ApiCommand::Unpair { id }) => return api::unpair(&config, id),
+        Some(ApiCommand::Clients) => {
+            for client in ApiConfig::load(&config)?.clients {
+                let groups = if client.groups.is_empty() {
+                    "all groups".into()
+                } else {
+                    client.groups.join(", ")
+                };
+                let access = if client.read_only {
+                    "read"
+                } else {
+                    "read, create"
+                };
+                writeln!(
+                    out,
+                    "{}  {}  {}  {}",
+                    client.id, client.name, access, groups
+                )?;
+            }
+            return Ok(());
+        }
+        Some(ApiCommand::Audit { client }) => {
+            let records = api::read_audit(&audit)?;
+            for record in records
+                .iter()
+                .filter(|record| client.as_ref().map_or(true, |id| &record.client == id))
+            {
+                writeln!(
+                    out,
+                    "{}  {}  {}  {}  {}  {}",
+                    record.time,
+                    record.client,
+                    record.name,
+                    record.method,
+                    record.item,
+                    if record.allowed { "allowed" } else { "refused" }
+                )?;
+            }
+            return Ok(());
+        }
+        None => {}
+    }
+
+    let socket = match &args.socket {
+        Some(socket) => socket.clone(),
+        None => api::socket_path(&args.unlock.vault)?,
+    };
+    let mem_key = MemKey::new();
+    let vault = unlock::unlock(&args.unlock, &mem_key, &mut std::io::stdin().lock())?;
+
+    let listener = agent::bind(&socket)?;
+    writeln!(
+        out,
+        "Serving {} on {}",
+        args.unlock.vault.display(),
+        socket.display()
+    )?;
+
+    let result = ApiServer::new(
+        vault,
+        mem_key,
+        config,
+        audit,
+        Duration::from_secs(args.idle_timeout),
+    )
+    .serve(&listener);
+    drop(std::fs::remove_file(&socket));
+    result
+}
@@ -0,0 +1,105 @@
+//! A native messaging host connecting browser extensions to a vault of the password manager.
+//!
+//! The browser starts it and exchanges length-prefixed JSON messages with it over the standard
+//! input and output. See: [`native_host`](pwduck_cli::native_host).
+#![cfg_attr(coverage, feature(no_coverage))]
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+#![deny(unused_results)]
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::pedantic,
+    clippy::nursery,
+
+    // Restriction lints
+    clippy::clone_on_ref_ptr,
+    clippy::create_dir,
+    clippy::dbg_macro,
+    clippy::decimal_literal_representation,
+    clippy::exit,
+    clippy::float_cmp_const,
+    clippy::get_unwrap,
+    clippy::let_underscore_must_use,
+    clippy::map_err_ignore,
+    clippy::mem_forget,
+    clippy::missing_docs_in_private_items,
+    clippy::multiple_inherent_impl,
+    clippy::panic,
+    clippy::panic_in_result_fn,
+    clippy::print_stderr,
+    clippy::print_stdout,
+    clippy::rest_pat_in_fully_bound_structs,
+    clippy::str_to_string,
+    clippy::string_to_string,
+    clippy::todo,
+    clippy::unimplemented,
+    clippy::unneeded_field_pattern,
+    clippy::unwrap_in_result,
+    clippy::unwrap_used,
+    clippy::use_debug,
+)]
+#![allow(
+    clippy::suboptimal_flops,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_wrap,
+    clippy::module_name_repetitions
+)]
+
+#[cfg(unix)]
+use std::io::Write;
+
+#[cfg(unix)]
+use pwduck_cli::{
+    args::NativeHostArgs,
+    error::PWDuckCliError,
+    native_host::{self, NativeHost},
+};
+#[cfg(unix)]
+use structopt::StructOpt;
+
+#[cfg(unix)]
+#[cfg_attr(coverage, no_coverage)]
+fn main() {
+    let args = NativeHostArgs::from_args();
+    if let Err(error) = run(&args) {
+        drop(error.report(false, &mut std::io::stderr()));
+        #[allow(clippy::exit)]
+        std::process::exit(error.kind().exit_code());
+    }
+}
+
+#[cfg(not(unix))]
+#[cfg_attr(coverage, no_coverage)]
+fn main() {
+    drop(std::io::Write::write_all(
+        &mut std::io::stderr(),
+        b"Error: The native messaging host is only available on Unix systems\n",
+    ));
+    #[allow(clippy::exit)]
+    std::process::exit(1);
+}
+
+/// Choose the vault or answer the messages of the browser until it closes the connection.
+#[cfg(unix)]
+#[cfg_attr(coverage, no_coverage)]
+fn run(args: &NativeHostArgs) -> Result<(), PWDuckCliError> {
+    pwduck_core::try_to_prevent_core_dump()?;
+
+    let config = match &args.config {
+        Some(config) => config.clone(),
+        None => native_host::config_path()?,
+    };
+    if let Some(vault) = &args.configure {
+        native_host::configure(&config, vault, args.askpass.as_deref())?;
+        writeln!(
+            std::io::stdout(),
+            "Serving {} to browser extensions",
+            vault.display()
+        )?;
+        return Ok(());
+    }
+
+    // The standard output belongs to the browser, errors are reported on the standard error.
+    NativeHost::new(config)?.serve(&mut std::io::stdin().lock(), &mut std::io::stdout().lock())
+}
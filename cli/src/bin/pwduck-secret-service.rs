@@ -0,0 +1,115 @@
+//! A provider of the Secret Service API storing the secrets of desktop applications in a vault.
+//!
+//! It provides a group of the vault as a collection on the D-Bus session bus until the bus is closed.
+//! See: [`secret_service`](pwduck_cli::secret_service).
+#![cfg_attr(coverage, feature(no_coverage))]
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+#![deny(unused_results)]
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::pedantic,
+    clippy::nursery,
+
+    // Restriction lints
+    clippy::clone_on_ref_ptr,
+    clippy::create_dir,
+    clippy::dbg_macro,
+    clippy::decimal_literal_representation,
+    clippy::exit,
+    clippy::float_cmp_const,
+    clippy::get_unwrap,
+    clippy::let_underscore_must_use,
+    clippy::map_err_ignore,
+    clippy::mem_forget,
+    clippy::missing_docs_in_private_items,
+    clippy::multiple_inherent_impl,
+    clippy::panic,
+    clippy::panic_in_result_fn,
+    clippy::print_stderr,
+    clippy::print_stdout,
+    clippy::rest_pat_in_fully_bound_structs,
+    clippy::str_to_string,
+    clippy::string_to_string,
+    clippy::todo,
+    clippy::unimplemented,
+    clippy::unneeded_field_pattern,
+    clippy::unwrap_in_result,
+    clippy::unwrap_used,
+    clippy::use_debug,
+)]
+
+#[cfg(unix)]
+use std::io::Write;
+
+#[cfg(unix)]
+use pwduck_cli::{
+    args::SecretServiceArgs,
+    dbus::{Connection, DO_NOT_QUEUE, PRIMARY_OWNER},
+    error::PWDuckCliError,
+    secret_service::{SecretService, SERVICE_NAME},
+    unlock,
+};
+#[cfg(unix)]
+use pwduck_core::MemKey;
+#[cfg(unix)]
+use structopt::StructOpt;
+
+#[cfg(unix)]
+#[cfg_attr(coverage, no_coverage)]
+fn main() {
+    let args = SecretServiceArgs::from_args();
+    if let Err(error) = run(&args) {
+        drop(error.report(false, &mut std::io::stderr()));
+        #[allow(clippy::exit)]
+        std::process::exit(error.kind().exit_code());
+    }
+}
+
+#[cfg(not(unix))]
+#[cfg_attr(coverage, no_coverage)]
+fn main() {
+    drop(std::io::Write::write_all(
+        &mut std::io::stderr(),
+        b"Error: The Secret Service is only available on Unix systems\n",
+    ));
+    #[allow(clippy::exit)]
+    std::process::exit(1);
+}
+
+/// Unlock the vault and provide the group until the bus is closed.
+#[cfg(unix)]
+#[cfg_attr(coverage, no_coverage)]
+fn run(args: &SecretServiceArgs) -> Result<(), PWDuckCliError> {
+    pwduck_core::try_to_prevent_core_dump()?;
+
+    let mem_key = MemKey::new();
+    let vault = unlock::unlock(&args.unlock, &mem_key, &mut std::io::stdin().lock())?;
+    let service = SecretService::new(
+        vault,
+        mem_key,
+        args.unlock.key_file.clone(),
+        &args.group,
+        &args.collection,
+    )?;
+
+    let mut connection = match &args.address {
+        Some(address) => Connection::open(address)?,
+        None => Connection::session()?,
+    };
+    if connection.request_name(SERVICE_NAME, DO_NOT_QUEUE)? != PRIMARY_OWNER {
+        return Err(PWDuckCliError::AlreadyExists(format!(
+            "A Secret Service on the bus ({})",
+            SERVICE_NAME
+        )));
+    }
+    writeln!(
+        std::io::stdout(),
+        "Serving {} of {} as the collection {}",
+        args.group,
+        args.unlock.vault.display(),
+        args.collection
+    )?;
+
+    service.serve(&mut connection)
+}
@@ -0,0 +1,142 @@
+//! Add a new entry.
+use std::io::Write;
+
+use pwduck_core::{EntryBody, EntryHead, MemKey, Uuid, Vault};
+
+use crate::{
+    error::PWDuckCliError,
+    json::{self, EntryItem},
+    path::{find_group, split},
+};
+
+use super::{edit::Changes, ensure_no_entry};
+
+/// Add a new entry with the given fields, save the [`Vault`](Vault) and print the UUID of the entry.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The path of the new entry
+///  - The fields of the new entry as [`Changes`](Changes)
+///  - True, if the new entry should be printed as JSON
+///  - The output to print to
+pub fn run(
+    vault: &mut Vault,
+    mem_key: &MemKey,
+    entry: &str,
+    fields: &Changes,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<(), PWDuckCliError> {
+    let (group, title) = split(entry)?;
+    let parent = find_group(vault, group)?.uuid().clone();
+    ensure_no_entry(vault, &parent, title)?;
+
+    let mut body = EntryBody::new(
+        Uuid::new(vault.path()),
+        fields.username.unwrap_or_default().into(),
+        fields
+            .password
+            .as_ref()
+            .map_or_else(String::new, |password| password.as_str().into()),
+    );
+    let _ = body.set_email(fields.email.unwrap_or_default().into());
+    let mut head = EntryHead::new(
+        Uuid::new(vault.path()),
+        parent,
+        title.into(),
+        body.uuid().clone(),
+    );
+    let _ = head.set_web_address(fields.web_address.unwrap_or_default().into());
+    let uuid = head.uuid().clone();
+
+    let master_key = vault
+        .master_key()
+        .as_unprotected(mem_key, vault.salt(), vault.nonce())?;
+    vault.insert_entry(head, body, &master_key)?;
+    vault.save(mem_key)?;
+
+    if json {
+        if let Some(entry) = vault.entries().get(&uuid) {
+            return json::write(out, &EntryItem::new(vault, entry));
+        }
+    }
+    writeln!(out, "{}", uuid)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pwduck_core::ReferenceField;
+    use tempfile::tempdir;
+
+    use crate::{
+        args::Command,
+        commands::tests::{example_vault, json_output, output, output_with_input, reload},
+        error::PWDuckCliError,
+        path::find_entry,
+    };
+
+    fn add(entry: &str) -> Command {
+        Command::Add {
+            entry: entry.into(),
+            username: "bob".into(),
+            email: "bob@example.com".into(),
+            web_address: "https://shop.example.com".into(),
+            secret_stdin: true,
+        }
+    }
+
+    #[test]
+    fn add_entry() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+
+        let uuid = output_with_input(
+            &add("/Email/Private/Shop"),
+            &mut vault,
+            &mem_key,
+            "shop secret\n",
+        )
+        .unwrap();
+
+        let (mut vault, mem_key) = reload(&vault);
+        let entry = find_entry(&vault, "/Email/Private/Shop").unwrap();
+        assert_eq!(format!("{}\n", entry.uuid()), uuid);
+        assert_eq!(entry.web_address(), "https://shop.example.com");
+        let show = |field| Command::Show {
+            entry: "/Email/Private/Shop".into(),
+            field: Some(field),
+        };
+        assert_eq!(
+            output(&show(ReferenceField::Username), &mut vault, &mem_key).unwrap(),
+            "bob\n"
+        );
+        assert_eq!(
+            output(&show(ReferenceField::Email), &mut vault, &mem_key).unwrap(),
+            "bob@example.com\n"
+        );
+        assert_eq!(
+            output(&show(ReferenceField::Password), &mut vault, &mem_key).unwrap(),
+            "shop secret\n"
+        );
+
+        let json = json_output(&add("/Email/Work/Shop"), &mut vault, &mem_key);
+        assert_eq!(json["title"], "Shop");
+        assert_eq!(json["path"], "/Email/Work/Shop");
+        assert_eq!(json["web_address"], "https://shop.example.com");
+
+        assert!(matches!(
+            output_with_input(&add("/Email/Private/Shop"), &mut vault, &mem_key, "\n"),
+            Err(PWDuckCliError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            output_with_input(&add("/Missing/Shop"), &mut vault, &mem_key, "\n"),
+            Err(PWDuckCliError::NotFound(_))
+        ));
+        assert!(matches!(
+            output_with_input(&add("/"), &mut vault, &mem_key, "\n"),
+            Err(PWDuckCliError::Refused(_))
+        ));
+    }
+}
@@ -0,0 +1,538 @@
+//! Answer the requests of the credential helper protocol of git.
+use std::io::Write;
+
+use pwduck_core::{EntryHead, MemKey, SecString, Uuid, Vault};
+
+use crate::{
+    args::{CredentialArgs, Operation},
+    error::PWDuckCliError,
+    json::{self, Credential, EntryDetails, EntryItem, Matches},
+    path::{entry_path, find_group, group_path, join, SEPARATOR},
+};
+
+use super::{add, edit};
+
+/// The parts of a web address identifying a credential.
+#[derive(Debug, PartialEq, Eq)]
+struct Address<'a> {
+    /// The protocol, e.g. `https`, if the web address has one.
+    protocol: Option<&'a str>,
+    /// The host including the port.
+    host: &'a str,
+    /// The path without leading and trailing slashes.
+    path: &'a str,
+}
+
+impl<'a> Address<'a> {
+    /// Parse the web address of an entry. Returns `None`, if it has no host.
+    fn parse(web_address: &'a str) -> Option<Self> {
+        let web_address = web_address.trim();
+        let (protocol, rest) = match web_address.split_once("://") {
+            Some((protocol, rest)) => (Some(protocol), rest),
+            None => (None, web_address),
+        };
+        let rest = rest.split(&['?', '#'][..]).next().unwrap_or_default();
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        // Drop the user information, e.g. `alice@example.com`.
+        let host = host.rsplit_once('@').map_or(host, |(_user, host)| host);
+
+        if host.is_empty() {
+            return None;
+        }
+        Some(Self {
+            protocol,
+            host,
+            path: normalize_path(path),
+        })
+    }
+
+    /// The length of the path, if the address matches the request.
+    /// Longer paths match more specifically.
+    fn matches(&self, request: &CredentialArgs) -> Option<usize> {
+        let host_matches = request
+            .host
+            .as_deref()
+            .map_or(false, |host| host.eq_ignore_ascii_case(self.host));
+        let protocol_matches = match (self.protocol, request.protocol.as_deref()) {
+            (Some(protocol), Some(requested)) => protocol.eq_ignore_ascii_case(requested),
+            _ => true,
+        };
+        // Without a path in the request (git's default), any path of the host matches.
+        let path_matches = match request.path.as_deref().map(normalize_path) {
+            Some(requested) if !self.path.is_empty() => {
+                requested == self.path
+                    || requested
+                        .strip_prefix(self.path)
+                        .map_or(false, |rest| rest.starts_with('/'))
+            }
+            _ => true,
+        };
+
+        if host_matches && protocol_matches && path_matches {
+            Some(self.path.len())
+        } else {
+            None
+        }
+    }
+}
+
+/// Remove the slashes around the path and the suffix `.git` of repositories.
+fn normalize_path(path: &str) -> &str {
+    let path = path.trim_matches('/');
+    path.strip_suffix(".git").unwrap_or(path)
+}
+
+/// An entry matching the request.
+#[derive(Debug)]
+struct Match {
+    /// The UUID of the entry.
+    uuid: Uuid,
+    /// The username of the entry.
+    username: SecString,
+    /// The password of the entry.
+    password: SecString,
+}
+
+/// Answer the request of git and save the [`Vault`](Vault), if it was changed.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The request of git
+///  - The password of the request, if it contains one
+///  - True, if the credential should be printed as JSON instead of git's format
+///  - The output to print to
+pub fn run(
+    vault: &mut Vault,
+    mem_key: &MemKey,
+    request: &CredentialArgs,
+    password: Option<SecString>,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<(), PWDuckCliError> {
+    let matches = find_matches(vault, mem_key, request)?;
+
+    match request.operation {
+        Operation::Get => {
+            if let Some(found) = matches.first() {
+                if json {
+                    return json::write(
+                        out,
+                        &Credential {
+                            username: found.username.as_str().into(),
+                            password: found.password.as_str().into(),
+                        },
+                    );
+                }
+                writeln!(out, "username={}", found.username.as_str())?;
+                writeln!(out, "password={}", found.password.as_str())?;
+            }
+            Ok(())
+        }
+        Operation::Store => {
+            let password = password.ok_or_else(|| {
+                PWDuckCliError::Refused("The credential to store has no password".into())
+            })?;
+            match matches.first() {
+                Some(found) if found.password.as_str() == password.as_str() => Ok(()),
+                Some(found) => edit::run(
+                    vault,
+                    mem_key,
+                    &found.uuid.to_string(),
+                    &edit::Changes {
+                        title: None,
+                        username: None,
+                        email: None,
+                        web_address: None,
+                        password: Some(password),
+                    },
+                ),
+                None => store_new(vault, mem_key, request, password),
+            }
+        }
+        Operation::Erase => {
+            let password = match password {
+                Some(password) => password,
+                None => return Ok(()),
+            };
+            let mut erased = false;
+            for found in matches
+                .iter()
+                .filter(|found| found.password.as_str() == password.as_str())
+            {
+                // Entries used by other entries are kept, their fields would become unresolvable.
+                if vault.delete_entry(&found.uuid).is_ok() {
+                    erased = true;
+                }
+            }
+            if erased {
+                vault.save(mem_key)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Find the entries matching the request, the most specific first.
+/// If the request contains a username, only entries with this username match.
+fn find_matches(
+    vault: &Vault,
+    mem_key: &MemKey,
+    request: &CredentialArgs,
+) -> Result<Vec<Match>, PWDuckCliError> {
+    let candidates = candidates(vault, request);
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let master_key = vault
+        .master_key()
+        .as_unprotected(mem_key, vault.salt(), vault.nonce())?;
+    let mut matches = Vec::new();
+    for entry in candidates {
+        let body = vault.load_entry_body(entry.body(), &master_key)?;
+        let body = vault.resolve_entry_body(&body, &master_key)?;
+        if request
+            .username
+            .as_deref()
+            .map_or(true, |username| username == body.username().as_str())
+        {
+            matches.push(Match {
+                uuid: entry.uuid().clone(),
+                username: body.username().clone(),
+                password: body.password().clone(),
+            });
+        }
+    }
+    Ok(matches)
+}
+
+/// The entries whose web addresses match the request, the most specific first.
+fn candidates<'a>(vault: &'a Vault, request: &CredentialArgs) -> Vec<&'a EntryHead> {
+    let mut candidates: Vec<_> = vault
+        .entries()
+        .values()
+        .filter_map(|entry| {
+            let length = Address::parse(entry.web_address())?.matches(request)?;
+            Some((length, entry_path(vault, entry), entry))
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    candidates
+        .into_iter()
+        .map(|(_length, _path, entry)| entry)
+        .collect()
+}
+
+/// Print the paths of the entries whose web addresses match the URL, the most specific first.
+/// The URL is matched like a request of git without a username.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The URL, e.g. `https://example.com/login`
+///  - True, if the entries should be printed as JSON including their usernames
+///  - The output to print to
+pub fn run_match(
+    vault: &Vault,
+    mem_key: &MemKey,
+    url: &str,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<(), PWDuckCliError> {
+    let address = Address::parse(url)
+        .ok_or_else(|| PWDuckCliError::Refused(format!("The URL {} has no host", url)))?;
+    let request = CredentialArgs {
+        operation: Operation::Get,
+        protocol: address.protocol.map(Into::into),
+        host: Some(address.host.into()),
+        path: Some(address.path.into()),
+        username: None,
+        group: SEPARATOR.to_string(),
+        secret_stdin: false,
+    };
+    let entries = candidates(vault, &request);
+
+    if json {
+        let master_key = vault
+            .master_key()
+            .as_unprotected(mem_key, vault.salt(), vault.nonce())?;
+        let mut details = Vec::new();
+        for entry in entries {
+            let body = vault.load_entry_body(entry.body(), &master_key)?;
+            let body = vault.resolve_entry_body(&body, &master_key)?;
+            details.push(EntryDetails {
+                entry: EntryItem::new(vault, entry),
+                username: body.username().as_str().into(),
+                email: body.email().as_str().into(),
+            });
+        }
+        return json::write(out, &Matches { entries: details });
+    }
+
+    for entry in entries {
+        writeln!(out, "{}", entry_path(vault, entry))?;
+    }
+    Ok(())
+}
+
+/// Add a new entry for the credential to the group of the request.
+/// It is titled by the host or by the username and the host, if the host is already taken.
+fn store_new(
+    vault: &mut Vault,
+    mem_key: &MemKey,
+    request: &CredentialArgs,
+    password: SecString,
+) -> Result<(), PWDuckCliError> {
+    let (host, username) = match (request.host.as_deref(), request.username.as_deref()) {
+        (Some(host), Some(username)) => (host, username),
+        _ => {
+            return Err(PWDuckCliError::Refused(
+                "The credential to store has no host or no username".into(),
+            ))
+        }
+    };
+
+    let group = find_group(vault, &request.group)?;
+    let path = group_path(vault, group.uuid());
+    let title = if vault
+        .get_entries_of(group.uuid())
+        .iter()
+        .any(|entry| entry.title() == host)
+    {
+        format!("{}@{}", username, host)
+    } else {
+        host.into()
+    };
+
+    let mut web_address = request
+        .protocol
+        .as_ref()
+        .map_or_else(String::new, |protocol| format!("{}://", protocol));
+    web_address.push_str(host);
+    if let Some(request_path) = request.path.as_deref().map(normalize_path) {
+        if !request_path.is_empty() {
+            web_address.push('/');
+            web_address.push_str(request_path);
+        }
+    }
+
+    add::run(
+        vault,
+        mem_key,
+        &join(&path, &title),
+        &edit::Changes {
+            title: None,
+            username: Some(username),
+            email: None,
+            web_address: Some(&web_address),
+            password: Some(password),
+        },
+        false,
+        &mut std::io::sink(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::{
+        args::{Command, CredentialArgs, Operation},
+        commands::tests::{example_vault, json_output, output, output_with_input, reload},
+        path::find_entry,
+    };
+
+    use super::Address;
+
+    fn request(operation: Operation, host: &str) -> CredentialArgs {
+        CredentialArgs {
+            operation,
+            protocol: Some("https".into()),
+            host: Some(host.into()),
+            path: None,
+            username: None,
+            group: "/".into(),
+            secret_stdin: true,
+        }
+    }
+
+    #[test]
+    fn parse_address() {
+        assert_eq!(
+            Address::parse("https://alice@git.example.com:8080/team/repo.git?tab=1"),
+            Some(Address {
+                protocol: Some("https"),
+                host: "git.example.com:8080",
+                path: "team/repo",
+            })
+        );
+        assert_eq!(
+            Address::parse("example.com"),
+            Some(Address {
+                protocol: None,
+                host: "example.com",
+                path: "",
+            })
+        );
+        assert_eq!(Address::parse(""), None);
+        assert_eq!(Address::parse("https:///path"), None);
+
+        let address = Address::parse("https://example.com/team").unwrap();
+        let mut get = request(Operation::Get, "EXAMPLE.com");
+        assert_eq!(address.matches(&get), Some(4));
+        get.path = Some("team/repo.git".into());
+        assert_eq!(address.matches(&get), Some(4));
+        get.path = Some("teams/repo.git".into());
+        assert_eq!(address.matches(&get), None);
+        get.path = None;
+        get.protocol = Some("http".into());
+        assert_eq!(address.matches(&get), None);
+        get.protocol = None;
+        assert_eq!(address.matches(&get), Some(4));
+        get.host = Some("other.com".into());
+        assert_eq!(address.matches(&get), None);
+    }
+
+    #[test]
+    fn credential() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+
+        let get = |vault: &mut pwduck_core::Vault, args: CredentialArgs| {
+            output_with_input(&Command::Credential(args), vault, &mem_key, "").unwrap()
+        };
+        assert_eq!(
+            get(&mut vault, request(Operation::Get, "mail.example.com")),
+            "username=alice@work\npassword=mail secret\n"
+        );
+        let mut other_user = request(Operation::Get, "mail.example.com");
+        other_user.username = Some("bob".into());
+        assert_eq!(get(&mut vault, other_user), "");
+        assert_eq!(get(&mut vault, request(Operation::Get, "missing.com")), "");
+
+        let json = json_output(
+            &Command::Credential(request(Operation::Get, "bank.example.com")),
+            &mut vault,
+            &mem_key,
+        );
+        assert_eq!(json["username"], "alice");
+        assert_eq!(json["password"], "bank secret");
+
+        // Storing a known credential updates the password.
+        let mut store = request(Operation::Store, "bank.example.com");
+        store.username = Some("alice".into());
+        let _ = output_with_input(
+            &Command::Credential(store.clone()),
+            &mut vault,
+            &mem_key,
+            "new bank secret\n",
+        )
+        .unwrap();
+        assert_eq!(
+            get(&mut vault, request(Operation::Get, "bank.example.com")),
+            "username=alice\npassword=new bank secret\n"
+        );
+
+        // Storing a new credential adds an entry.
+        store.host = Some("git.example.com".into());
+        store.path = Some("/team/repo.git".into());
+        store.group = "/Email".into();
+        let _ = output_with_input(
+            &Command::Credential(store.clone()),
+            &mut vault,
+            &mem_key,
+            "git secret\n",
+        )
+        .unwrap();
+        let (mut disk, disk_mem_key) = reload(&vault);
+        let entry = find_entry(&disk, "/Email/git.example.com").unwrap();
+        assert_eq!(entry.web_address(), "https://git.example.com/team/repo");
+        let mut repo = request(Operation::Get, "git.example.com");
+        repo.path = Some("team/repo.git/".into());
+        assert_eq!(
+            output_with_input(&Command::Credential(repo), &mut disk, &disk_mem_key, "").unwrap(),
+            "username=alice\npassword=git secret\n"
+        );
+
+        // A second user of the same host gets its own entry.
+        store.username = Some("bob".into());
+        store.path = None;
+        let _ = output_with_input(
+            &Command::Credential(store),
+            &mut vault,
+            &mem_key,
+            "bob secret\n",
+        )
+        .unwrap();
+        assert!(find_entry(&vault, "/Email/bob@git.example.com").is_ok());
+
+        // Only credentials with the rejected password are erased.
+        let mut erase = request(Operation::Erase, "git.example.com");
+        erase.username = Some("bob".into());
+        let _ = output_with_input(
+            &Command::Credential(erase.clone()),
+            &mut vault,
+            &mem_key,
+            "wrong secret\n",
+        )
+        .unwrap();
+        assert!(find_entry(&vault, "/Email/bob@git.example.com").is_ok());
+        let _ = output_with_input(
+            &Command::Credential(erase),
+            &mut vault,
+            &mem_key,
+            "bob secret\n",
+        )
+        .unwrap();
+        assert!(find_entry(&vault, "/Email/bob@git.example.com").is_err());
+        assert!(find_entry(&vault, "/Email/git.example.com").is_ok());
+        let (disk, _disk_mem_key) = reload(&vault);
+        assert!(find_entry(&disk, "/Email/bob@git.example.com").is_err());
+    }
+
+    #[test]
+    fn match_url() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+        let _ = output_with_input(
+            &Command::Add {
+                entry: "/Email/Login".into(),
+                username: "bob".into(),
+                email: String::new(),
+                web_address: "mail.example.com/login".into(),
+                secret_stdin: true,
+            },
+            &mut vault,
+            &mem_key,
+            "login secret\n",
+        )
+        .unwrap();
+        let url = |url: &str| Command::Match { url: url.into() };
+
+        assert_eq!(
+            output(
+                &url("https://mail.example.com/login?next=1"),
+                &mut vault,
+                &mem_key
+            )
+            .unwrap(),
+            "/Email/Login\n/Email/Work/Mail\n"
+        );
+        assert_eq!(
+            output(&url("https://mail.example.com/inbox"), &mut vault, &mem_key).unwrap(),
+            "/Email/Work/Mail\n"
+        );
+        assert_eq!(
+            output(&url("http://bank.example.com"), &mut vault, &mem_key).unwrap(),
+            ""
+        );
+        assert!(output(&url("https://"), &mut vault, &mem_key).is_err());
+
+        let json = json_output(&url("mail.example.com/login"), &mut vault, &mem_key);
+        assert_eq!(json["entries"][0]["path"], "/Email/Login");
+        assert_eq!(json["entries"][0]["username"], "bob");
+        assert_eq!(json["entries"][1]["username"], "alice@work");
+        assert!(json["entries"][0].get("password").is_none());
+    }
+}
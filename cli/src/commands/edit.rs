@@ -0,0 +1,136 @@
+//! Change the fields of an entry.
+use pwduck_core::{MemKey, SecString, Vault};
+
+use crate::{error::PWDuckCliError, path::find_entry};
+
+use super::ensure_no_entry;
+
+/// The new values of the fields of an entry. Fields set to `None` stay unchanged.
+#[derive(Debug)]
+pub struct Changes<'a> {
+    /// The new title.
+    pub title: Option<&'a str>,
+    /// The new username.
+    pub username: Option<&'a str>,
+    /// The new email.
+    pub email: Option<&'a str>,
+    /// The new web address.
+    pub web_address: Option<&'a str>,
+    /// The new password.
+    pub password: Option<SecString>,
+}
+
+/// Change the fields of the entry and save the [`Vault`](Vault).
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The path or UUID of the entry
+///  - The [`Changes`](Changes) to apply
+pub fn run(
+    vault: &mut Vault,
+    mem_key: &MemKey,
+    entry: &str,
+    changes: &Changes,
+) -> Result<(), PWDuckCliError> {
+    let mut head = find_entry(vault, entry)?.clone();
+    if let Some(title) = changes.title {
+        if title != head.title() {
+            ensure_no_entry(vault, head.parent(), title)?;
+        }
+        let _ = head.set_title(title.into());
+    }
+    if let Some(web_address) = changes.web_address {
+        let _ = head.set_web_address(web_address.into());
+    }
+
+    let master_key = vault
+        .master_key()
+        .as_unprotected(mem_key, vault.salt(), vault.nonce())?;
+    let mut body = vault.load_entry_body(head.body(), &master_key)?;
+    if let Some(username) = changes.username {
+        let _ = body.set_username(username.into());
+    }
+    if let Some(email) = changes.email {
+        let _ = body.set_email(email.into());
+    }
+    if let Some(password) = &changes.password {
+        let _ = body.set_password(password.as_str().into());
+    }
+
+    vault.insert_entry(head, body, &master_key)?;
+    vault.save(mem_key)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pwduck_core::ReferenceField;
+    use tempfile::tempdir;
+
+    use crate::{
+        args::Command,
+        commands::tests::{example_vault, output, output_with_input, reload},
+        error::PWDuckCliError,
+        path::find_entry,
+    };
+
+    #[test]
+    fn edit() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+
+        let command = Command::Edit {
+            entry: "/Email/Work/Mail".into(),
+            title: Some("Webmail".into()),
+            username: None,
+            email: Some("alice@example.com".into()),
+            web_address: None,
+            password: true,
+            secret_stdin: true,
+        };
+        assert_eq!(
+            output_with_input(&command, &mut vault, &mem_key, "new secret\n").unwrap(),
+            ""
+        );
+
+        let (mut vault, mem_key) = reload(&vault);
+        assert!(find_entry(&vault, "/Email/Work/Mail").is_err());
+        let details = output(
+            &Command::Show {
+                entry: "/Email/Work/Webmail".into(),
+                field: None,
+            },
+            &mut vault,
+            &mem_key,
+        )
+        .unwrap();
+        assert!(details.contains("Web address: https://mail.example.com\n"));
+        assert!(details.contains("Username:    alice@work\n"));
+        assert!(details.contains("Email:       alice@example.com\n"));
+        let password = output(
+            &Command::Show {
+                entry: "/Email/Work/Webmail".into(),
+                field: Some(ReferenceField::Password),
+            },
+            &mut vault,
+            &mem_key,
+        )
+        .unwrap();
+        assert_eq!(password, "new secret\n");
+
+        let missing = Command::Edit {
+            entry: "/Missing".into(),
+            title: Some("Found".into()),
+            username: None,
+            email: None,
+            web_address: None,
+            password: false,
+            secret_stdin: false,
+        };
+        assert!(matches!(
+            output(&missing, &mut vault, &mem_key),
+            Err(PWDuckCliError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,95 @@
+//! Generate a new random password.
+use std::io::Write;
+
+use pwduck_core::{generate_password, password_entropy, Symbols};
+
+use crate::{
+    args::Generate,
+    error::PWDuckCliError,
+    json::{self, GeneratedPassword},
+};
+
+/// Print a new random password generated with the given options.
+///
+/// It expects:
+///  - The [`Generate`](Generate) options
+///  - True, if the password should be printed as JSON
+///  - The output to print to
+///
+/// # Errors
+///
+/// Returns `Err` if all kinds of characters are excluded or the output can't be written.
+pub fn run(options: &Generate, json: bool, out: &mut dyn Write) -> Result<(), PWDuckCliError> {
+    let mut symbols = Symbols::new();
+    if !options.no_upper {
+        symbols.append(&Symbols::UPPER_ALPHA);
+    }
+    if !options.no_lower {
+        symbols.append(&Symbols::LOWER_ALPHA);
+    }
+    if !options.no_numbers {
+        symbols.append(&Symbols::NUMBERS);
+    }
+    if !options.no_special {
+        symbols.append(&Symbols::SPECIAL);
+    }
+    if symbols.is_empty() {
+        return Err(PWDuckCliError::Refused(
+            "At least one kind of characters must be included".into(),
+        ));
+    }
+
+    let password = generate_password(options.length, &symbols);
+    if json {
+        json::write(
+            out,
+            &GeneratedPassword {
+                length: password.chars().count(),
+                entropy: password_entropy(&password).get_entropy(),
+                password,
+            },
+        )
+    } else {
+        writeln!(out, "{}", password)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{args::Generate, error::PWDuckCliError};
+
+    use super::run;
+
+    fn generate(options: &Generate, json: bool) -> Result<String, PWDuckCliError> {
+        let mut out = Vec::new();
+        run(options, json, &mut out)?;
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    #[test]
+    fn generate_passwords() {
+        let mut options = Generate {
+            length: 16,
+            no_upper: true,
+            no_lower: false,
+            no_numbers: true,
+            no_special: true,
+        };
+        let password = generate(&options, false).unwrap();
+        assert_eq!(password.len(), 17);
+        assert!(password.trim_end().chars().all(|c| c.is_ascii_lowercase()));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&generate(&options, true).unwrap()).unwrap();
+        assert_eq!(json["length"], 16);
+        assert_eq!(json["password"].as_str().unwrap().len(), 16);
+        assert!(json["entropy"].is_number());
+
+        options.no_lower = true;
+        assert!(matches!(
+            generate(&options, false),
+            Err(PWDuckCliError::Refused(_))
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+//! List the subgroups and entries of a group.
+use std::io::Write;
+
+use pwduck_core::Vault;
+
+use crate::{
+    error::PWDuckCliError,
+    json::{self, EntryItem, GroupItem, Listing},
+    path::{find_group, SEPARATOR},
+};
+
+use super::sorted_children;
+
+/// Print the subgroups followed by the entries of the group, one title per line.
+/// The titles of the subgroups end with the separator.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The path or UUID of the group or `None` for the root group
+///  - True, if the listing should be printed as JSON
+///  - The output to print to
+pub fn run(
+    vault: &Vault,
+    group: Option<&str>,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<(), PWDuckCliError> {
+    let group = find_group(vault, group.unwrap_or("/"))?;
+    let (groups, entries) = sorted_children(vault, group.uuid());
+
+    if json {
+        return json::write(
+            out,
+            &Listing {
+                group: GroupItem::new(vault, group),
+                groups: groups
+                    .into_iter()
+                    .map(|group| GroupItem::new(vault, group))
+                    .collect(),
+                entries: entries
+                    .into_iter()
+                    .map(|entry| EntryItem::new(vault, entry))
+                    .collect(),
+            },
+        );
+    }
+
+    for group in groups {
+        writeln!(out, "{}{}", group.title(), SEPARATOR)?;
+    }
+    for entry in entries {
+        writeln!(out, "{}", entry.title())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::{
+        args::Command,
+        commands::tests::{example_vault, json_output, output},
+    };
+
+    #[test]
+    fn ls() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+
+        let root = output(&Command::Ls { group: None }, &mut vault, &mem_key).unwrap();
+        assert_eq!(root, "Email/\nBank\n");
+
+        let email = output(
+            &Command::Ls {
+                group: Some("/Email".into()),
+            },
+            &mut vault,
+            &mem_key,
+        )
+        .unwrap();
+        assert_eq!(email, "Private/\nWork/\n");
+
+        let json = json_output(
+            &Command::Ls {
+                group: Some("/Email/Work".into()),
+            },
+            &mut vault,
+            &mem_key,
+        );
+        assert_eq!(json["group"]["title"], "Work");
+        assert_eq!(json["group"]["path"], "/Email/Work");
+        assert_eq!(json["groups"].as_array().unwrap().len(), 0);
+        assert_eq!(json["entries"][0]["title"], "Mail");
+        assert_eq!(json["entries"][0]["path"], "/Email/Work/Mail");
+        assert_eq!(json["entries"][0]["group"], json["group"]["uuid"]);
+        assert_eq!(
+            json["entries"][0]["web_address"],
+            "https://mail.example.com"
+        );
+
+        assert!(output(
+            &Command::Ls {
+                group: Some("/Missing".into()),
+            },
+            &mut vault,
+            &mem_key,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,143 @@
+//! Create a new group.
+use std::io::Write;
+
+use pwduck_core::{Group, MemKey, Uuid, Vault};
+
+use crate::{
+    error::PWDuckCliError,
+    json::{self, GroupItem},
+    path::{group_path, join, SEPARATOR},
+};
+
+/// Create the group, save the [`Vault`](Vault) and print the UUID of the group.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The path of the new group
+///  - True, if missing parent groups should be created and existing groups accepted
+///  - True, if the new group should be printed as JSON
+///  - The output to print to
+pub fn run(
+    vault: &mut Vault,
+    mem_key: &MemKey,
+    group: &str,
+    parents: bool,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<(), PWDuckCliError> {
+    let mut current = vault
+        .get_root_uuid()
+        .ok_or_else(|| PWDuckCliError::NotFound("The root group".into()))?;
+    let titles: Vec<&str> = group
+        .split(SEPARATOR)
+        .filter(|title| !title.is_empty())
+        .collect();
+    if titles.is_empty() {
+        return Err(PWDuckCliError::Refused(format!(
+            "The path {} has no title",
+            group
+        )));
+    }
+
+    for (index, title) in titles.iter().enumerate() {
+        let is_last = index + 1 == titles.len();
+        let existing = vault
+            .get_groups_of(&current)
+            .into_iter()
+            .find(|child| child.title() == title)
+            .map(|child| child.uuid().clone());
+
+        let path = || join(&group_path(vault, &current), title);
+        current = match existing {
+            Some(existing) if !is_last || parents => existing,
+            Some(_) => {
+                return Err(PWDuckCliError::AlreadyExists(format!(
+                    "The group {}",
+                    path()
+                )));
+            }
+            None if !is_last && !parents => {
+                return Err(PWDuckCliError::NotFound(format!("The group {}", path())));
+            }
+            None => {
+                let new = Group::new(Uuid::new(vault.path()), current, (*title).into());
+                let uuid = new.uuid().clone();
+                vault.insert_group(new);
+                uuid
+            }
+        };
+    }
+
+    vault.save(mem_key)?;
+
+    if json {
+        if let Some(group) = vault.groups().get(&current) {
+            return json::write(out, &GroupItem::new(vault, group));
+        }
+    }
+    writeln!(out, "{}", current)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::{
+        args::Command,
+        commands::tests::{example_vault, json_output, output, reload},
+        error::PWDuckCliError,
+        path::find_group,
+    };
+
+    fn mkdir(group: &str, parents: bool) -> Command {
+        Command::Mkdir {
+            group: group.into(),
+            parents,
+        }
+    }
+
+    #[test]
+    fn make_groups() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+
+        let servers = output(&mkdir("/Servers", false), &mut vault, &mem_key).unwrap();
+        let staging = output(&mkdir("/Deploy/Staging/", true), &mut vault, &mem_key).unwrap();
+        assert_eq!(
+            output(&mkdir("/Deploy", true), &mut vault, &mem_key).unwrap(),
+            output(&mkdir("Deploy/", true), &mut vault, &mem_key).unwrap()
+        );
+
+        let (mut vault, mem_key) = reload(&vault);
+        assert_eq!(
+            format!("{}\n", find_group(&vault, "/Servers").unwrap().uuid()),
+            servers
+        );
+        assert_eq!(
+            format!(
+                "{}\n",
+                find_group(&vault, "/Deploy/Staging").unwrap().uuid()
+            ),
+            staging
+        );
+
+        let json = json_output(&mkdir("/Email/Home", false), &mut vault, &mem_key);
+        assert_eq!(json["title"], "Home");
+        assert_eq!(json["path"], "/Email/Home");
+
+        assert!(matches!(
+            output(&mkdir("/Email/Work", false), &mut vault, &mem_key),
+            Err(PWDuckCliError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            output(&mkdir("/Missing/Group", false), &mut vault, &mem_key),
+            Err(PWDuckCliError::NotFound(_))
+        ));
+        assert!(matches!(
+            output(&mkdir("/", true), &mut vault, &mem_key),
+            Err(PWDuckCliError::Refused(_))
+        ));
+    }
+}
@@ -0,0 +1,294 @@
+//! The commands of the command line client.
+use std::io::{BufRead, Write};
+
+use pwduck_core::{EntryHead, Group, MemKey, SecString, Uuid, Vault};
+
+use crate::{
+    args::{Command, Operation, SshKeyCommand},
+    error::PWDuckCliError,
+    input::read_secret,
+    path::{group_path, join},
+};
+
+mod add;
+mod credential;
+mod edit;
+pub mod generate;
+mod ls;
+mod mkdir;
+mod mv;
+mod rm;
+mod search;
+mod show;
+mod ssh;
+mod tree;
+
+pub use show::field_value;
+pub use ssh::private_key;
+
+/// Read the secrets needed by the command from the input or prompt for them on the terminal.
+///
+/// It expects:
+///  - The [`Command`](Command) needing the secrets
+///  - The input to read secrets from, if they are not read from the terminal
+///
+/// # Errors
+///
+/// Returns `Err` if the input or the terminal can't be read.
+pub fn read_secrets(
+    command: &Command,
+    input: &mut dyn BufRead,
+) -> Result<Vec<SecString>, PWDuckCliError> {
+    let secret = match command {
+        Command::Add { secret_stdin, .. } => {
+            Some(read_secret("Entry password: ", *secret_stdin, input)?)
+        }
+        Command::Edit {
+            password: true,
+            secret_stdin,
+            ..
+        } => Some(read_secret("New entry password: ", *secret_stdin, input)?),
+        Command::Credential(request) if request.operation != Operation::Get => Some(read_secret(
+            "Credential password: ",
+            request.secret_stdin,
+            input,
+        )?),
+        // The key file is read by the client, so the agent never reads files of its own.
+        Command::SshKey(SshKeyCommand::Import { file, .. }) => Some(std::fs::read_to_string(file)?),
+        _ => None,
+    };
+    Ok(secret.into_iter().map(SecString::from).collect())
+}
+
+/// Run the command on the unlocked [`Vault`](Vault).
+/// Commands changing the [`Vault`](Vault) save it afterwards.
+///
+/// It expects:
+///  - The [`Command`](Command) to run
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The secrets needed by the command, see: [`read_secrets`](read_secrets)
+///  - True, if the result should be printed as JSON
+///  - The output to print the result to
+///
+/// # Errors
+///
+/// Returns `Err` if the command fails. See: [`ErrorKind`](crate::error::ErrorKind).
+pub fn run(
+    command: &Command,
+    vault: &mut Vault,
+    mem_key: &MemKey,
+    secrets: Vec<SecString>,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<(), PWDuckCliError> {
+    let mut secrets = secrets.into_iter();
+    match command {
+        Command::Ls { group } => ls::run(vault, group.as_deref(), json, out),
+        Command::Tree { group } => tree::run(vault, group.as_deref(), json, out),
+        Command::Show { entry, field } => show::run(vault, mem_key, entry, *field, json, out),
+        Command::Search { term } => search::run(vault, term, json, out),
+        Command::Generate(options) => generate::run(options, json, out),
+        Command::Add {
+            entry,
+            username,
+            email,
+            web_address,
+            ..
+        } => add::run(
+            vault,
+            mem_key,
+            entry,
+            &edit::Changes {
+                title: None,
+                username: Some(username.as_str()),
+                email: Some(email.as_str()),
+                web_address: Some(web_address.as_str()),
+                password: secrets.next(),
+            },
+            json,
+            out,
+        ),
+        Command::Edit {
+            entry,
+            title,
+            username,
+            email,
+            web_address,
+            password,
+            ..
+        } => edit::run(
+            vault,
+            mem_key,
+            entry,
+            &edit::Changes {
+                title: title.as_deref(),
+                username: username.as_deref(),
+                email: email.as_deref(),
+                web_address: web_address.as_deref(),
+                password: if *password { secrets.next() } else { None },
+            },
+        ),
+        Command::Mv { item, group } => mv::run(vault, mem_key, item, group),
+        Command::Rm {
+            item,
+            recursive,
+            force,
+        } => rm::run(vault, mem_key, item, *recursive, *force),
+        Command::Mkdir { group, parents } => mkdir::run(vault, mem_key, group, *parents, json, out),
+        Command::Lock => Err(PWDuckCliError::NotFound(
+            "The agent serving the vault".into(),
+        )),
+        Command::Credential(request) => {
+            credential::run(vault, mem_key, request, secrets.next(), json, out)
+        }
+        Command::Match { url } => credential::run_match(vault, mem_key, url, json, out),
+        Command::SshKey(command) => ssh::run(vault, mem_key, command, secrets.next(), json, out),
+        Command::Run(_) => Err(PWDuckCliError::Refused(
+            "Programs are only run by the client, not by the agent".into(),
+        )),
+    }
+}
+
+/// The subgroups and entries of the [`Group`](Group) in the sort order of the [`Vault`](Vault).
+pub(crate) fn sorted_children<'a>(
+    vault: &'a Vault,
+    uuid: &Uuid,
+) -> (Vec<&'a Group>, Vec<&'a EntryHead>) {
+    let sort_order = vault.settings().sort_order();
+    let mut groups = vault.get_groups_of(uuid);
+    groups.sort_by(|&a, &b| sort_order.compare_groups(a, b));
+    let mut entries = vault.get_entries_of(uuid);
+    entries.sort_by(|&a, &b| sort_order.compare_entries(a, b));
+    (groups, entries)
+}
+
+/// Fail if the [`Group`](pwduck_core::Group) already contains an entry with the title.
+fn ensure_no_entry(vault: &Vault, group: &Uuid, title: &str) -> Result<(), PWDuckCliError> {
+    if vault
+        .get_entries_of(group)
+        .iter()
+        .any(|entry| entry.title() == title)
+    {
+        return Err(PWDuckCliError::AlreadyExists(format!(
+            "The entry {}",
+            join(&group_path(vault, group), title)
+        )));
+    }
+    Ok(())
+}
+
+/// Fail if the [`Group`](pwduck_core::Group) already contains a subgroup with the title.
+fn ensure_no_group(vault: &Vault, group: &Uuid, title: &str) -> Result<(), PWDuckCliError> {
+    if vault
+        .get_groups_of(group)
+        .iter()
+        .any(|child| child.title() == title)
+    {
+        return Err(PWDuckCliError::AlreadyExists(format!(
+            "The group {}",
+            join(&group_path(vault, group), title)
+        )));
+    }
+    Ok(())
+}
+
+/// Helpers shared by the tests of the commands.
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::path::Path;
+
+    use pwduck_core::{EntryBody, EntryHead, Group, MemKey, Uuid, Vault};
+
+    /// The password of the example vault.
+    pub const PASSWORD: &str = "this is a totally secret password";
+
+    /// Create a saved vault containing:
+    /// ```text
+    /// /
+    /// ├── Email/
+    /// │   ├── Private/
+    /// │   └── Work/
+    /// │       └── Mail
+    /// └── Bank
+    /// ```
+    pub fn example_vault(dir: &Path) -> (Vault, MemKey) {
+        let mem_key = MemKey::with_length(1);
+        let path = dir.join("Vault");
+        let mut vault = Vault::generate(PASSWORD, Option::<String>::None, &mem_key, &path).unwrap();
+        let master_key = vault
+            .master_key()
+            .as_unprotected(&mem_key, vault.salt(), vault.nonce())
+            .unwrap();
+        let root = vault.get_root_uuid().unwrap();
+
+        let email = Group::new(Uuid::new(&path), root.clone(), "Email".into());
+        let private = Group::new(Uuid::new(&path), email.uuid().clone(), "Private".into());
+        let work = Group::new(Uuid::new(&path), email.uuid().clone(), "Work".into());
+        let work_uuid = work.uuid().clone();
+        vault.insert_group(email);
+        vault.insert_group(private);
+        vault.insert_group(work);
+
+        let mut entry = |parent: Uuid, title: &str, username: &str, password: &str| {
+            let body = EntryBody::new(Uuid::new(&path), username.into(), password.into());
+            let mut head =
+                EntryHead::new(Uuid::new(&path), parent, title.into(), body.uuid().clone());
+            let _ = head.set_web_address(format!("https://{}.example.com", title.to_lowercase()));
+            vault.insert_entry(head, body, &master_key).unwrap();
+        };
+        entry(work_uuid, "Mail", "alice@work", "mail secret");
+        entry(root, "Bank", "alice", "bank secret");
+
+        vault.save(&mem_key).unwrap();
+        (vault, mem_key)
+    }
+
+    /// Run the command and return its output.
+    pub fn output(
+        command: &super::Command,
+        vault: &mut Vault,
+        mem_key: &MemKey,
+    ) -> Result<String, crate::error::PWDuckCliError> {
+        output_with_input(command, vault, mem_key, "")
+    }
+
+    /// Run the command reading secrets from the given input and return its output.
+    pub fn output_with_input(
+        command: &super::Command,
+        vault: &mut Vault,
+        mem_key: &MemKey,
+        input: &str,
+    ) -> Result<String, crate::error::PWDuckCliError> {
+        let secrets = super::read_secrets(command, &mut input.as_bytes())?;
+        let mut out = Vec::new();
+        super::run(command, vault, mem_key, secrets, false, &mut out)?;
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    /// Run the command with JSON output and parse its output.
+    pub fn json_output(
+        command: &super::Command,
+        vault: &mut Vault,
+        mem_key: &MemKey,
+    ) -> serde_json::Value {
+        let secrets = super::read_secrets(command, &mut &b"\n"[..]).unwrap();
+        let mut out = Vec::new();
+        super::run(command, vault, mem_key, secrets, true, &mut out).unwrap();
+        assert!(out.ends_with(b"\n") && !out[..out.len() - 1].contains(&b'\n'));
+        serde_json::from_slice(&out).unwrap()
+    }
+
+    /// Reload the saved vault from disk.
+    pub fn reload(vault: &Vault) -> (Vault, MemKey) {
+        let mem_key = MemKey::with_length(1);
+        let vault = Vault::load(
+            PASSWORD,
+            Option::<String>::None,
+            &mem_key,
+            vault.path().clone(),
+        )
+        .unwrap();
+        (vault, mem_key)
+    }
+}
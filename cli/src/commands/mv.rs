@@ -0,0 +1,111 @@
+//! Move an entry or a group into another group.
+use pwduck_core::{MemKey, Vault};
+
+use crate::{
+    error::PWDuckCliError,
+    path::{find_entry, find_group},
+};
+
+use super::{ensure_no_entry, ensure_no_group};
+
+/// Move the entry or group into the other group and save the [`Vault`](Vault).
+/// Entries are preferred over groups of the same path.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The path or UUID of the entry or group to move
+///  - The path or UUID of the new parent group
+pub fn run(
+    vault: &mut Vault,
+    mem_key: &MemKey,
+    item: &str,
+    group: &str,
+) -> Result<(), PWDuckCliError> {
+    let parent = find_group(vault, group)?.uuid().clone();
+
+    if let Ok(entry) = find_entry(vault, item) {
+        let (uuid, title) = (entry.uuid().clone(), entry.title().clone());
+        if entry.parent() != &parent {
+            ensure_no_entry(vault, &parent, &title)?;
+        }
+        vault.move_entry(&uuid, &parent)?;
+    } else {
+        let group = find_group(vault, item)
+            .map_err(|_err| PWDuckCliError::NotFound(format!("The entry or group {}", item)))?;
+        let (uuid, title) = (group.uuid().clone(), group.title().clone());
+        if group.parent().as_ref() != Some(&parent) {
+            ensure_no_group(vault, &parent, &title)?;
+        }
+        vault.move_group(&uuid, &parent)?;
+    }
+
+    vault.save(mem_key)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::{
+        args::Command,
+        commands::tests::{example_vault, output, reload},
+        error::PWDuckCliError,
+        path::{find_entry, find_group},
+    };
+
+    fn mv(item: &str, group: &str) -> Command {
+        Command::Mv {
+            item: item.into(),
+            group: group.into(),
+        }
+    }
+
+    #[test]
+    fn move_items() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+
+        assert_eq!(
+            output(&mv("/Bank", "/Email/Private"), &mut vault, &mem_key).unwrap(),
+            ""
+        );
+        assert_eq!(
+            output(&mv("/Email/Work", "/"), &mut vault, &mem_key).unwrap(),
+            ""
+        );
+
+        let (mut vault, mem_key) = reload(&vault);
+        assert!(find_entry(&vault, "/Email/Private/Bank").is_ok());
+        assert!(find_entry(&vault, "/Work/Mail").is_ok());
+        assert!(find_group(&vault, "/Email/Work").is_err());
+
+        assert!(matches!(
+            output(&mv("/Work", "/Work/"), &mut vault, &mem_key),
+            Err(PWDuckCliError::PWDuckCoreError(_))
+        ));
+        assert!(matches!(
+            output(&mv("/Missing", "/Work"), &mut vault, &mem_key),
+            Err(PWDuckCliError::NotFound(_))
+        ));
+        assert!(matches!(
+            output(&mv("/Work/Mail", "/Missing"), &mut vault, &mem_key),
+            Err(PWDuckCliError::NotFound(_))
+        ));
+
+        let _ = output(
+            &Command::Mkdir {
+                group: "/Email/Work".into(),
+                parents: false,
+            },
+            &mut vault,
+            &mem_key,
+        )
+        .unwrap();
+        assert!(matches!(
+            output(&mv("/Work", "/Email"), &mut vault, &mem_key),
+            Err(PWDuckCliError::AlreadyExists(_))
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+//! Remove an entry or a group.
+use pwduck_core::{MemKey, Vault};
+
+use crate::{
+    error::PWDuckCliError,
+    path::{entry_path, find_entry, find_group},
+};
+
+/// Remove the entry or group and save the [`Vault`](Vault).
+/// Entries are preferred over groups of the same path.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The path or UUID of the entry or group to remove
+///  - True, if groups containing subgroups or entries should be removed
+///  - True, if entries referenced by other entries should be removed
+pub fn run(
+    vault: &mut Vault,
+    mem_key: &MemKey,
+    item: &str,
+    recursive: bool,
+    force: bool,
+) -> Result<(), PWDuckCliError> {
+    if let Ok(entry) = find_entry(vault, item) {
+        let uuid = entry.uuid().clone();
+        if force {
+            vault.delete_entry_breaking_references(&uuid);
+        } else if vault.delete_entry(&uuid).is_err() {
+            let referencing: Vec<String> = vault
+                .get_referencing_entries(&uuid)
+                .into_iter()
+                .map(|entry| entry_path(vault, entry))
+                .collect();
+            return Err(PWDuckCliError::Refused(format!(
+                "The entry {} is referenced by {}, use --force to remove it anyway",
+                item,
+                referencing.join(", ")
+            )));
+        }
+    } else {
+        let group = find_group(vault, item)
+            .map_err(|_err| PWDuckCliError::NotFound(format!("The entry or group {}", item)))?;
+        let uuid = group.uuid().clone();
+        if group.is_root() {
+            return Err(PWDuckCliError::Refused(
+                "The root group can't be removed".into(),
+            ));
+        }
+        let is_empty =
+            vault.get_groups_of(&uuid).is_empty() && vault.get_entries_of(&uuid).is_empty();
+        if !recursive && !is_empty {
+            return Err(PWDuckCliError::Refused(format!(
+                "The group {} is not empty, use --recursive to remove it anyway",
+                item
+            )));
+        }
+        vault.delete_group(&uuid);
+    }
+
+    vault.save(mem_key)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pwduck_core::{EntryBody, EntryHead, Uuid};
+    use tempfile::tempdir;
+
+    use crate::{
+        args::Command,
+        commands::tests::{example_vault, output, reload},
+        error::PWDuckCliError,
+        path::{find_entry, find_group},
+    };
+
+    fn rm(item: &str, recursive: bool, force: bool) -> Command {
+        Command::Rm {
+            item: item.into(),
+            recursive,
+            force,
+        }
+    }
+
+    #[test]
+    fn remove_items() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+
+        // Reference the password of the bank.
+        let master_key = vault
+            .master_key()
+            .as_unprotected(&mem_key, vault.salt(), vault.nonce())
+            .unwrap();
+        let bank = find_entry(&vault, "/Bank").unwrap().uuid().clone();
+        let root = vault.get_root_uuid().unwrap();
+        let body = EntryBody::new(
+            Uuid::new(vault.path()),
+            "alice".into(),
+            format!("{{REF:password@{}}}", bank),
+        );
+        let head = EntryHead::new(
+            Uuid::new(vault.path()),
+            root,
+            "Online banking".into(),
+            body.uuid().clone(),
+        );
+        vault.insert_entry(head, body, &master_key).unwrap();
+
+        assert!(matches!(
+            output(&rm("/Bank", false, false), &mut vault, &mem_key),
+            Err(PWDuckCliError::Refused(_))
+        ));
+        assert!(matches!(
+            output(&rm("/Email", false, false), &mut vault, &mem_key),
+            Err(PWDuckCliError::Refused(_))
+        ));
+        assert!(matches!(
+            output(&rm("/", true, true), &mut vault, &mem_key),
+            Err(PWDuckCliError::Refused(_))
+        ));
+        assert!(matches!(
+            output(&rm("/Missing", true, true), &mut vault, &mem_key),
+            Err(PWDuckCliError::NotFound(_))
+        ));
+
+        assert_eq!(
+            output(&rm("/Email/Private", false, false), &mut vault, &mem_key).unwrap(),
+            ""
+        );
+        assert_eq!(
+            output(&rm("/Bank", false, true), &mut vault, &mem_key).unwrap(),
+            ""
+        );
+        assert_eq!(
+            output(&rm("/Email", true, false), &mut vault, &mem_key).unwrap(),
+            ""
+        );
+
+        let (vault, _mem_key) = reload(&vault);
+        assert!(find_entry(&vault, "/Bank").is_err());
+        assert!(find_entry(&vault, "/Email/Work/Mail").is_err());
+        assert!(find_group(&vault, "/Email").is_err());
+        assert!(find_entry(&vault, "/Online banking").is_ok());
+    }
+}
@@ -0,0 +1,97 @@
+//! Search groups and entries by their titles.
+use std::io::Write;
+
+use pwduck_core::Vault;
+
+use crate::{
+    error::PWDuckCliError,
+    json::{self, EntryItem, GroupItem, SearchResults},
+    path::{entry_path, group_path, SEPARATOR},
+};
+
+/// Print the paths of the groups and entries whose titles contain the term, ignoring the case.
+/// The paths of the groups end with the separator.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The term to search for
+///  - True, if the results should be printed as JSON
+///  - The output to print to
+pub fn run(
+    vault: &Vault,
+    term: &str,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<(), PWDuckCliError> {
+    let root = vault
+        .get_root_uuid()
+        .ok_or_else(|| PWDuckCliError::NotFound("The root group".into()))?;
+    let results = vault.get_item_list_for(&root, Some(term));
+
+    if json {
+        return json::write(
+            out,
+            &SearchResults {
+                groups: results
+                    .groups()
+                    .iter()
+                    .map(|group| GroupItem::new(vault, group))
+                    .collect(),
+                entries: results
+                    .entries()
+                    .iter()
+                    .map(|entry| EntryItem::new(vault, entry))
+                    .collect(),
+            },
+        );
+    }
+
+    for group in results.groups() {
+        let path = group_path(vault, group.uuid());
+        if path.ends_with(SEPARATOR) {
+            writeln!(out, "{}", path)?;
+        } else {
+            writeln!(out, "{}{}", path, SEPARATOR)?;
+        }
+    }
+    for entry in results.entries() {
+        writeln!(out, "{}", entry_path(vault, entry))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::{
+        args::Command,
+        commands::tests::{example_vault, json_output, output},
+    };
+
+    #[test]
+    fn search() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+
+        let mut search = |term: &str| {
+            output(&Command::Search { term: term.into() }, &mut vault, &mem_key).unwrap()
+        };
+
+        assert_eq!(search("MAIL"), "/Email/\n/Email/Work/Mail\n");
+        assert_eq!(search("an"), "/Bank\n");
+        assert_eq!(search("nothing"), "");
+
+        let json = json_output(
+            &Command::Search {
+                term: "mail".into(),
+            },
+            &mut vault,
+            &mem_key,
+        );
+        assert_eq!(json["groups"][0]["path"], "/Email");
+        assert_eq!(json["entries"][0]["path"], "/Email/Work/Mail");
+        assert_eq!(json["entries"].as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,202 @@
+//! Show the details of an entry.
+use std::io::Write;
+
+use pwduck_core::{MemKey, ReferenceField, SecString, Vault};
+
+use crate::{
+    error::PWDuckCliError,
+    json::{self, EntryDetails, EntryItem, FieldValue},
+    path::{find_entry, group_path},
+};
+
+/// The placeholder printed instead of the password.
+const HIDDEN: &str = "********";
+
+/// Print the details of the entry without the password.
+/// If a field is given, only its value is printed, including the password.
+/// Field references are resolved.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The path or UUID of the entry
+///  - The field to print or `None` to print the details
+///  - True, if the details or the field should be printed as JSON
+///  - The output to print to
+pub fn run(
+    vault: &Vault,
+    mem_key: &MemKey,
+    entry: &str,
+    field: Option<ReferenceField>,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<(), PWDuckCliError> {
+    if let Some(field) = field {
+        let value = field_value(vault, mem_key, entry, field)?;
+        return if json {
+            json::write(
+                out,
+                &FieldValue {
+                    field: field.name(),
+                    value: value.as_str().into(),
+                },
+            )
+        } else {
+            writeln!(out, "{}", value.as_str())?;
+            Ok(())
+        };
+    }
+
+    let head = find_entry(vault, entry)?;
+    let master_key = vault
+        .master_key()
+        .as_unprotected(mem_key, vault.salt(), vault.nonce())?;
+    let body = vault.load_entry_body(head.body(), &master_key)?;
+    let body = vault.resolve_entry_body(&body, &master_key)?;
+
+    if json {
+        return json::write(
+            out,
+            &EntryDetails {
+                entry: EntryItem::new(vault, head),
+                username: body.username().as_str().into(),
+                email: body.email().as_str().into(),
+            },
+        );
+    }
+
+    writeln!(out, "Title:       {}", head.title())?;
+    writeln!(out, "UUID:        {}", head.uuid())?;
+    writeln!(out, "Group:       {}", group_path(vault, head.parent()))?;
+    writeln!(out, "Web address: {}", head.web_address())?;
+    writeln!(out, "Username:    {}", body.username().as_str())?;
+    writeln!(out, "Email:       {}", body.email().as_str())?;
+    writeln!(out, "Password:    {}", HIDDEN)?;
+    Ok(())
+}
+
+/// The value of the field of the entry with its references resolved.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The path or UUID of the entry
+///  - The field to return
+///
+/// # Errors
+///
+/// Returns `Err` if the entry does not exist or can't be decrypted.
+pub fn field_value(
+    vault: &Vault,
+    mem_key: &MemKey,
+    entry: &str,
+    field: ReferenceField,
+) -> Result<SecString, PWDuckCliError> {
+    let head = find_entry(vault, entry)?;
+    let master_key = vault
+        .master_key()
+        .as_unprotected(mem_key, vault.salt(), vault.nonce())?;
+    let body = vault.load_entry_body(head.body(), &master_key)?;
+    let body = vault.resolve_entry_body(&body, &master_key)?;
+    Ok(match field {
+        ReferenceField::Title => head.title().as_str().into(),
+        ReferenceField::Username => body.username().as_str().into(),
+        ReferenceField::Password => body.password().as_str().into(),
+        ReferenceField::Email => body.email().as_str().into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pwduck_core::ReferenceField;
+    use tempfile::tempdir;
+
+    use crate::{
+        args::Command,
+        commands::tests::{example_vault, json_output, output},
+        path::find_entry,
+    };
+
+    #[test]
+    fn show() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+        let uuid = find_entry(&vault, "/Email/Work/Mail")
+            .unwrap()
+            .uuid()
+            .clone();
+
+        let details = output(
+            &Command::Show {
+                entry: "/Email/Work/Mail".into(),
+                field: None,
+            },
+            &mut vault,
+            &mem_key,
+        )
+        .unwrap();
+        assert_eq!(
+            details,
+            format!(
+                "Title:       Mail\n\
+                UUID:        {}\n\
+                Group:       /Email/Work\n\
+                Web address: https://mail.example.com\n\
+                Username:    alice@work\n\
+                Email:       \n\
+                Password:    ********\n",
+                uuid
+            )
+        );
+        assert!(!details.contains("mail secret"));
+
+        let password = output(
+            &Command::Show {
+                entry: uuid.to_string(),
+                field: Some(ReferenceField::Password),
+            },
+            &mut vault,
+            &mem_key,
+        )
+        .unwrap();
+        assert_eq!(password, "mail secret\n");
+
+        let json = json_output(
+            &Command::Show {
+                entry: "/Email/Work/Mail".into(),
+                field: None,
+            },
+            &mut vault,
+            &mem_key,
+        );
+        assert_eq!(json["uuid"], uuid.to_string());
+        assert_eq!(json["title"], "Mail");
+        assert_eq!(json["path"], "/Email/Work/Mail");
+        assert_eq!(json["username"], "alice@work");
+        assert_eq!(json["email"], "");
+        assert!(json.get("password").is_none());
+
+        let json = json_output(
+            &Command::Show {
+                entry: "/Bank".into(),
+                field: Some(ReferenceField::Password),
+            },
+            &mut vault,
+            &mem_key,
+        );
+        assert_eq!(
+            json,
+            serde_json::json!({"field": "password", "value": "bank secret"})
+        );
+
+        assert!(output(
+            &Command::Show {
+                entry: "/Email/Work".into(),
+                field: None,
+            },
+            &mut vault,
+            &mem_key,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,302 @@
+//! Generate, import and print the SSH keys of entries.
+use std::io::Write;
+
+use pwduck_core::{EntryHead, MemKey, ReferenceField, SecString, Vault};
+use rand_core::OsRng;
+use ssh_key::{Algorithm, HashAlg, LineEnding, PrivateKey};
+
+use crate::{
+    args::SshKeyCommand,
+    error::PWDuckCliError,
+    json::{self, SshPublicKey},
+    path::{entry_path, find_entry},
+};
+
+use super::field_value;
+
+/// Run the [`SshKeyCommand`](SshKeyCommand) on the entry.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The [`SshKeyCommand`](SshKeyCommand) to run
+///  - The content of the key file to import
+///  - True, if the public key should be printed as JSON
+///  - The output to print the public key to
+pub fn run(
+    vault: &mut Vault,
+    mem_key: &MemKey,
+    command: &SshKeyCommand,
+    key_file: Option<SecString>,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<(), PWDuckCliError> {
+    let key = match command {
+        SshKeyCommand::Generate {
+            entry,
+            comment,
+            encrypt,
+            confirm,
+            force,
+        } => {
+            let head = find_entry(vault, entry)?.clone();
+            let mut key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).map_err(invalid)?;
+            key.set_comment(comment.as_deref().unwrap_or_else(|| head.title()));
+            if *encrypt {
+                let passphrase = passphrase(vault, mem_key, &head)?;
+                let encrypted = key
+                    .encrypt(&mut OsRng, passphrase.as_str())
+                    .map_err(invalid)?;
+                store(vault, mem_key, head, &encrypted, *confirm, *force)?;
+            } else {
+                store(vault, mem_key, head, &key, *confirm, *force)?;
+            }
+            key
+        }
+        SshKeyCommand::Import {
+            entry,
+            confirm,
+            force,
+            ..
+        } => {
+            let head = find_entry(vault, entry)?.clone();
+            let content = key_file.ok_or_else(|| {
+                PWDuckCliError::Refused("The content of the key file is missing".into())
+            })?;
+            let key = PrivateKey::from_openssh(content.as_str()).map_err(invalid)?;
+            let decrypted = if key.is_encrypted() {
+                let passphrase = passphrase(vault, mem_key, &head)?;
+                key.decrypt(passphrase.as_str()).map_err(|_err| {
+                    PWDuckCliError::Refused(format!(
+                        "The passphrase of the key is not the password of {}, change it with edit --password",
+                        entry_path(vault, &head)
+                    ))
+                })?
+            } else {
+                key.clone()
+            };
+            store(vault, mem_key, head, &key, *confirm, *force)?;
+            decrypted
+        }
+        SshKeyCommand::Public { entry } => {
+            let head = find_entry(vault, entry)?;
+            private_key(vault, mem_key, head)?.ok_or_else(|| {
+                PWDuckCliError::NotFound(format!("The SSH key of {}", entry_path(vault, head)))
+            })?
+        }
+    };
+
+    let public_key = key.public_key().to_openssh().map_err(invalid)?;
+    if json {
+        return json::write(
+            out,
+            &SshPublicKey {
+                public_key,
+                fingerprint: key.fingerprint(HashAlg::Sha256).to_string(),
+            },
+        );
+    }
+    writeln!(out, "{}", public_key)?;
+    Ok(())
+}
+
+/// Load the SSH key of the entry and decrypt it with the password of the entry, if it is encrypted.
+/// Returns `None`, if the entry has no SSH key.
+///
+/// # Errors
+///
+/// Returns `Err` if the key can't be loaded or decrypted.
+pub fn private_key(
+    vault: &Vault,
+    mem_key: &MemKey,
+    entry: &EntryHead,
+) -> Result<Option<PrivateKey>, PWDuckCliError> {
+    match stored_key(vault, mem_key, entry)? {
+        Some(key) if key.is_encrypted() => {
+            let passphrase = passphrase(vault, mem_key, entry)?;
+            key.decrypt(passphrase.as_str()).map(Some).map_err(invalid)
+        }
+        key => Ok(key),
+    }
+}
+
+/// Load the SSH key of the entry as it is stored, i.e. possibly encrypted.
+fn stored_key(
+    vault: &Vault,
+    mem_key: &MemKey,
+    entry: &EntryHead,
+) -> Result<Option<PrivateKey>, PWDuckCliError> {
+    let master_key = vault
+        .master_key()
+        .as_unprotected(mem_key, vault.salt(), vault.nonce())?;
+    let body = vault.load_entry_body(entry.body(), &master_key)?;
+    if body.ssh_key().is_empty() {
+        return Ok(None);
+    }
+    PrivateKey::from_openssh(body.ssh_key().as_str())
+        .map(Some)
+        .map_err(invalid)
+}
+
+/// The password of the entry used as the passphrase of its SSH key.
+fn passphrase(
+    vault: &Vault,
+    mem_key: &MemKey,
+    entry: &EntryHead,
+) -> Result<SecString, PWDuckCliError> {
+    let passphrase = field_value(
+        vault,
+        mem_key,
+        &entry.uuid().to_string(),
+        ReferenceField::Password,
+    )?;
+    if passphrase.is_empty() {
+        return Err(PWDuckCliError::Refused(format!(
+            "The entry {} has no password to use as the passphrase",
+            entry_path(vault, entry)
+        )));
+    }
+    Ok(passphrase)
+}
+
+/// Store the SSH key in the entry and save the [`Vault`](Vault).
+fn store(
+    vault: &mut Vault,
+    mem_key: &MemKey,
+    mut head: EntryHead,
+    key: &PrivateKey,
+    confirm: bool,
+    force: bool,
+) -> Result<(), PWDuckCliError> {
+    let master_key = vault
+        .master_key()
+        .as_unprotected(mem_key, vault.salt(), vault.nonce())?;
+    let mut body = vault.load_entry_body(head.body(), &master_key)?;
+    if !force && !body.ssh_key().is_empty() {
+        return Err(PWDuckCliError::AlreadyExists(format!(
+            "The SSH key of {}",
+            entry_path(vault, &head)
+        )));
+    }
+
+    let openssh = key.to_openssh(LineEnding::LF).map_err(invalid)?;
+    let _ = body.set_ssh_key(openssh.as_str().into());
+    let _ = head.set_ssh_key_confirmation(confirm);
+    vault.insert_entry(head, body, &master_key)?;
+    vault.save(mem_key)?;
+    Ok(())
+}
+
+/// The error of keys that can't be read, written or decrypted.
+#[allow(clippy::needless_pass_by_value)]
+fn invalid(error: ssh_key::Error) -> PWDuckCliError {
+    PWDuckCliError::Refused(format!("The SSH key can't be used ({})", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        args::{Command, SshKeyCommand},
+        commands::tests::{example_vault, json_output, output, output_with_input},
+        error::PWDuckCliError,
+        path::find_entry,
+    };
+
+    use super::private_key;
+
+    fn generate(entry: &str, encrypt: bool, force: bool) -> Command {
+        Command::SshKey(SshKeyCommand::Generate {
+            entry: entry.into(),
+            comment: None,
+            encrypt,
+            confirm: true,
+            force,
+        })
+    }
+
+    fn public(entry: &str) -> Command {
+        Command::SshKey(SshKeyCommand::Public {
+            entry: entry.into(),
+        })
+    }
+
+    #[test]
+    fn ssh_keys() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+
+        assert!(matches!(
+            output(&public("/Bank"), &mut vault, &mem_key),
+            Err(PWDuckCliError::NotFound(_))
+        ));
+
+        // Generate an encrypted key.
+        let generated = output(&generate("/Bank", true, false), &mut vault, &mem_key).unwrap();
+        assert!(generated.starts_with("ssh-ed25519 "));
+        assert!(generated.ends_with(" Bank\n"));
+        assert_eq!(
+            output(&public("/Bank"), &mut vault, &mem_key).unwrap(),
+            generated
+        );
+        assert!(matches!(
+            output(&generate("/Bank", false, false), &mut vault, &mem_key),
+            Err(PWDuckCliError::AlreadyExists(_))
+        ));
+
+        let bank = find_entry(&vault, "/Bank").unwrap().clone();
+        assert!(bank.ssh_key_confirmation());
+        let key = private_key(&vault, &mem_key, &bank).unwrap().unwrap();
+        assert!(!key.is_encrypted());
+        let mail = find_entry(&vault, "/Email/Work/Mail").unwrap().clone();
+        assert!(private_key(&vault, &mem_key, &mail).unwrap().is_none());
+
+        let json = json_output(&public("/Bank"), &mut vault, &mem_key);
+        assert!(json["fingerprint"].as_str().unwrap().starts_with("SHA256:"));
+
+        // Import the key into another entry, it is encrypted with the same password.
+        let file = dir.path().join("id_ed25519");
+        let master_key = vault
+            .master_key()
+            .as_unprotected(&mem_key, vault.salt(), vault.nonce())
+            .unwrap();
+        let body = vault.load_entry_body(bank.body(), &master_key).unwrap();
+        std::fs::write(&file, body.ssh_key().as_str()).unwrap();
+        let import = |file: &Path| {
+            Command::SshKey(SshKeyCommand::Import {
+                entry: "/Email/Work/Mail".into(),
+                file: file.into(),
+                confirm: false,
+                force: false,
+            })
+        };
+        assert!(matches!(
+            output(&import(&file), &mut vault, &mem_key),
+            Err(PWDuckCliError::Refused(_))
+        ));
+        std::fs::write(dir.path().join("invalid"), "no key").unwrap();
+        assert!(output(&import(&dir.path().join("invalid")), &mut vault, &mem_key).is_err());
+        assert!(output(&import(&dir.path().join("missing")), &mut vault, &mem_key).is_err());
+
+        let _ = output_with_input(
+            &Command::Edit {
+                entry: "/Email/Work/Mail".into(),
+                title: None,
+                username: None,
+                email: None,
+                web_address: None,
+                password: true,
+                secret_stdin: true,
+            },
+            &mut vault,
+            &mem_key,
+            "bank secret\n",
+        )
+        .unwrap();
+        let imported = output(&import(&file), &mut vault, &mem_key).unwrap();
+        assert_eq!(imported, generated);
+    }
+}
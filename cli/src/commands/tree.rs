@@ -0,0 +1,143 @@
+//! Print a group with all its subgroups and entries as a tree.
+use std::io::Write;
+
+use pwduck_core::{Group, Uuid, Vault};
+
+use crate::{
+    error::PWDuckCliError,
+    json::{self, EntryItem, GroupItem, TreeGroup},
+    path::{find_group, group_path, SEPARATOR},
+};
+
+use super::sorted_children;
+
+/// Print the path of the group followed by its subgroups and entries as a tree.
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The path or UUID of the group or `None` for the root group
+///  - True, if the tree should be printed as JSON
+///  - The output to print to
+pub fn run(
+    vault: &Vault,
+    group: Option<&str>,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<(), PWDuckCliError> {
+    let group = find_group(vault, group.unwrap_or("/"))?;
+    if json {
+        return json::write(out, &tree_group(vault, group));
+    }
+    writeln!(out, "{}", group_path(vault, group.uuid()))?;
+    write_children(vault, group.uuid(), "", out)
+}
+
+/// Describe the group with all its subgroups and entries.
+fn tree_group(vault: &Vault, group: &Group) -> TreeGroup {
+    let (groups, entries) = sorted_children(vault, group.uuid());
+    TreeGroup {
+        group: GroupItem::new(vault, group),
+        groups: groups
+            .into_iter()
+            .map(|group| tree_group(vault, group))
+            .collect(),
+        entries: entries
+            .into_iter()
+            .map(|entry| EntryItem::new(vault, entry))
+            .collect(),
+    }
+}
+
+/// Print the subgroups and entries of the group below the given prefix.
+fn write_children(
+    vault: &Vault,
+    uuid: &Uuid,
+    prefix: &str,
+    out: &mut dyn Write,
+) -> Result<(), PWDuckCliError> {
+    let (groups, entries) = sorted_children(vault, uuid);
+
+    let count = groups.len() + entries.len();
+    for (index, group) in groups.into_iter().enumerate() {
+        let last = index + 1 == count;
+        writeln!(
+            out,
+            "{}{}{}{}",
+            prefix,
+            branch(last),
+            group.title(),
+            SEPARATOR
+        )?;
+        let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        write_children(vault, group.uuid(), &prefix, out)?;
+    }
+
+    let offset = count - entries.len();
+    for (index, entry) in entries.into_iter().enumerate() {
+        writeln!(
+            out,
+            "{}{}{}",
+            prefix,
+            branch(offset + index + 1 == count),
+            entry.title()
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The branch in front of an item of the tree.
+const fn branch(last: bool) -> &'static str {
+    if last {
+        "└── "
+    } else {
+        "├── "
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::{
+        args::Command,
+        commands::tests::{example_vault, json_output, output},
+    };
+
+    #[test]
+    fn tree() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+
+        let root = output(&Command::Tree { group: None }, &mut vault, &mem_key).unwrap();
+        assert_eq!(
+            root,
+            "/\n\
+            ├── Email/\n\
+            │   ├── Private/\n\
+            │   └── Work/\n\
+            │       └── Mail\n\
+            └── Bank\n"
+        );
+
+        let work = output(
+            &Command::Tree {
+                group: Some("/Email/Work".into()),
+            },
+            &mut vault,
+            &mem_key,
+        )
+        .unwrap();
+        assert_eq!(work, "/Email/Work\n└── Mail\n");
+
+        let json = json_output(&Command::Tree { group: None }, &mut vault, &mem_key);
+        assert_eq!(json["path"], "/");
+        assert_eq!(json["groups"][0]["title"], "Email");
+        assert_eq!(json["groups"][0]["groups"][1]["path"], "/Email/Work");
+        assert_eq!(
+            json["groups"][0]["groups"][1]["entries"][0]["path"],
+            "/Email/Work/Mail"
+        );
+        assert_eq!(json["entries"][0]["title"], "Bank");
+    }
+}
@@ -0,0 +1,861 @@
+//! The messages of the D-Bus wire protocol and the marshalling of their values.
+//!
+//! See: <https://dbus.freedesktop.org/doc/dbus-specification.html#message-protocol>
+use std::convert::TryInto;
+
+use zeroize::Zeroize;
+
+use crate::error::PWDuckCliError;
+
+/// The maximal length of a message accepted from the bus.
+pub const MAX_MESSAGE_LENGTH: usize = 64 * 1024 * 1024;
+
+/// The maximal depth of nested containers accepted from the bus.
+const MAX_DEPTH: usize = 64;
+
+/// A value of the D-Bus type system.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// `y`: An unsigned byte.
+    Byte(u8),
+    /// `b`: A boolean.
+    Bool(bool),
+    /// `n`: A signed 16 bit integer.
+    Int16(i16),
+    /// `q`: An unsigned 16 bit integer.
+    UInt16(u16),
+    /// `i`: A signed 32 bit integer.
+    Int32(i32),
+    /// `u`: An unsigned 32 bit integer.
+    UInt32(u32),
+    /// `x`: A signed 64 bit integer.
+    Int64(i64),
+    /// `t`: An unsigned 64 bit integer.
+    UInt64(u64),
+    /// `d`: A double precision floating point number.
+    Double(f64),
+    /// `s`: A string.
+    String(String),
+    /// `o`: The path of an object.
+    ObjectPath(String),
+    /// `g`: A type signature.
+    Signature(String),
+    /// `ay`: An array of bytes. It is zeroized when dropped, since secrets are passed as bytes.
+    Bytes(Vec<u8>),
+    /// `a`: An array with the signature of its elements.
+    Array(String, Vec<Self>),
+    /// `()`: A struct.
+    Struct(Vec<Self>),
+    /// `{}`: An entry of a dictionary, only allowed as the element of an array.
+    DictEntry(Box<Self>, Box<Self>),
+    /// `v`: A value of any type.
+    Variant(Box<Self>),
+}
+
+impl Drop for Value {
+    fn drop(&mut self) {
+        if let Self::Bytes(bytes) = self {
+            bytes.zeroize();
+        }
+    }
+}
+
+impl Value {
+    /// The type signature of this value.
+    #[must_use]
+    pub fn signature(&self) -> String {
+        match self {
+            Self::Byte(_) => "y".into(),
+            Self::Bool(_) => "b".into(),
+            Self::Int16(_) => "n".into(),
+            Self::UInt16(_) => "q".into(),
+            Self::Int32(_) => "i".into(),
+            Self::UInt32(_) => "u".into(),
+            Self::Int64(_) => "x".into(),
+            Self::UInt64(_) => "t".into(),
+            Self::Double(_) => "d".into(),
+            Self::String(_) => "s".into(),
+            Self::ObjectPath(_) => "o".into(),
+            Self::Signature(_) => "g".into(),
+            Self::Bytes(_) => "ay".into(),
+            Self::Array(element, _) => format!("a{}", element),
+            Self::Struct(fields) => {
+                let fields: Vec<String> = fields.iter().map(Self::signature).collect();
+                format!("({})", fields.concat())
+            }
+            Self::DictEntry(key, value) => format!("{{{}{}}}", key.signature(), value.signature()),
+            Self::Variant(_) => "v".into(),
+        }
+    }
+
+    /// A dictionary of the entries with the given signatures of the keys and the values.
+    #[must_use]
+    pub fn dict(key: &str, value: &str, entries: Vec<(Self, Self)>) -> Self {
+        Self::Array(
+            format!("{{{}{}}}", key, value),
+            entries
+                .into_iter()
+                .map(|(key, value)| Self::DictEntry(Box::new(key), Box::new(value)))
+                .collect(),
+        )
+    }
+
+    /// An array of object paths.
+    #[must_use]
+    pub fn object_paths(paths: Vec<String>) -> Self {
+        Self::Array(
+            "o".into(),
+            paths.into_iter().map(Self::ObjectPath).collect(),
+        )
+    }
+
+    /// The value as a string, if it is a string, an object path or a signature.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) | Self::ObjectPath(value) | Self::Signature(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as a boolean, if it is one.
+    #[must_use]
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The elements of the value, if it is an array.
+    #[must_use]
+    pub fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(_, elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// The fields of the value, if it is a struct.
+    #[must_use]
+    pub fn as_struct(&self) -> Option<&[Self]> {
+        match self {
+            Self::Struct(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// The bytes of the value, if it is an array of bytes.
+    #[must_use]
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// The value contained in the variant or the value itself, if it is no variant.
+    #[must_use]
+    pub fn unwrap_variant(&self) -> &Self {
+        match self {
+            Self::Variant(value) => value.unwrap_variant(),
+            _ => self,
+        }
+    }
+
+    /// The key value pairs of the value, if it is a dictionary.
+    #[must_use]
+    pub fn as_dict(&self) -> Option<Vec<(&Self, &Self)>> {
+        self.as_array()?
+            .iter()
+            .map(|entry| match entry {
+                Self::DictEntry(key, value) => Some((key.as_ref(), value.as_ref())),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// The alignment of the first character of the signature.
+const fn alignment(signature: &str) -> usize {
+    match signature.as_bytes().first() {
+        Some(b'n' | b'q') => 2,
+        Some(b'b' | b'i' | b'u' | b's' | b'o' | b'a') => 4,
+        Some(b'x' | b't' | b'd' | b'(' | b'{') => 8,
+        _ => 1,
+    }
+}
+
+/// An error for malformed messages.
+fn invalid(reason: &str) -> PWDuckCliError {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("Malformed D-Bus message: {}", reason),
+    )
+    .into()
+}
+
+/// Split the first single complete type from the signature.
+///
+/// # Errors
+///
+/// Returns `Err` if the signature is empty or malformed.
+pub fn split_type(signature: &str) -> Result<(&str, &str), PWDuckCliError> {
+    let bytes = signature.as_bytes();
+    let mut index = 0;
+    while bytes.get(index) == Some(&b'a') {
+        index += 1;
+    }
+    let length = match bytes.get(index) {
+        Some(b'(' | b'{') => {
+            let mut depth = 0_usize;
+            let mut end = None;
+            for (offset, character) in bytes[index..].iter().enumerate() {
+                match character {
+                    b'(' | b'{' => depth += 1,
+                    b')' | b'}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = Some(index + offset + 1);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            end.ok_or_else(|| invalid("unbalanced signature"))?
+        }
+        Some(
+            b'y' | b'b' | b'n' | b'q' | b'i' | b'u' | b'x' | b't' | b'd' | b's' | b'o' | b'g'
+            | b'v',
+        ) => index + 1,
+        _ => return Err(invalid("unknown type in signature")),
+    };
+    Ok(signature.split_at(length))
+}
+
+/// The single complete types of the signature.
+///
+/// # Errors
+///
+/// Returns `Err` if the signature is malformed.
+pub fn split_types(mut signature: &str) -> Result<Vec<&str>, PWDuckCliError> {
+    let mut types = Vec::new();
+    while !signature.is_empty() {
+        let (first, rest) = split_type(signature)?;
+        types.push(first);
+        signature = rest;
+    }
+    Ok(types)
+}
+
+/// Marshals values in little endian byte order.
+#[derive(Debug, Default)]
+struct Writer {
+    /// The marshalled data.
+    data: Vec<u8>,
+}
+
+impl Writer {
+    /// Pad the data to the alignment.
+    fn align(&mut self, alignment: usize) {
+        while self.data.len() % alignment != 0 {
+            self.data.push(0);
+        }
+    }
+
+    /// Write an unsigned 32 bit integer.
+    fn write_u32(&mut self, value: u32) {
+        self.align(4);
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Write the length of a string or an array.
+    fn write_length(&mut self, length: usize) -> Result<(), PWDuckCliError> {
+        self.write_u32(length.try_into().map_err(|_err| invalid("too long"))?);
+        Ok(())
+    }
+
+    /// Write the value.
+    fn write(&mut self, value: &Value) -> Result<(), PWDuckCliError> {
+        match value {
+            Value::Byte(byte) => self.data.push(*byte),
+            Value::Bool(boolean) => self.write_u32(u32::from(*boolean)),
+            Value::Int16(number) => {
+                self.align(2);
+                self.data.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::UInt16(number) => {
+                self.align(2);
+                self.data.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::Int32(number) => {
+                self.align(4);
+                self.data.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::UInt32(number) => self.write_u32(*number),
+            Value::Int64(number) => {
+                self.align(8);
+                self.data.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::UInt64(number) => {
+                self.align(8);
+                self.data.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::Double(number) => {
+                self.align(8);
+                self.data.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::String(string) | Value::ObjectPath(string) => {
+                self.write_length(string.len())?;
+                self.data.extend_from_slice(string.as_bytes());
+                self.data.push(0);
+            }
+            Value::Signature(signature) => {
+                self.data.push(
+                    signature
+                        .len()
+                        .try_into()
+                        .map_err(|_err| invalid("too long"))?,
+                );
+                self.data.extend_from_slice(signature.as_bytes());
+                self.data.push(0);
+            }
+            Value::Bytes(bytes) => {
+                self.write_length(bytes.len())?;
+                self.data.extend_from_slice(bytes);
+            }
+            Value::Array(element, elements) => {
+                self.write_u32(0);
+                let length_position = self.data.len() - 4;
+                self.align(alignment(element));
+                let start = self.data.len();
+                for element in elements {
+                    self.write(element)?;
+                }
+                let length: u32 = (self.data.len() - start)
+                    .try_into()
+                    .map_err(|_err| invalid("too long"))?;
+                self.data[length_position..length_position + 4]
+                    .copy_from_slice(&length.to_le_bytes());
+            }
+            Value::Struct(fields) => {
+                self.align(8);
+                for field in fields {
+                    self.write(field)?;
+                }
+            }
+            Value::DictEntry(key, value) => {
+                self.align(8);
+                self.write(key)?;
+                self.write(value)?;
+            }
+            Value::Variant(value) => {
+                self.write(&Value::Signature(value.signature()))?;
+                self.write(value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Unmarshals values in the byte order of the message.
+#[derive(Debug)]
+struct Reader<'a> {
+    /// The whole message.
+    data: &'a [u8],
+    /// The position of the next value.
+    position: usize,
+    /// True, if the message is in big endian byte order.
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    /// Skip the padding up to the alignment.
+    fn align(&mut self, alignment: usize) -> Result<(), PWDuckCliError> {
+        let padding = (alignment - self.position % alignment) % alignment;
+        let _ = self.take(padding)?;
+        Ok(())
+    }
+
+    /// Take the next bytes.
+    fn take(&mut self, length: usize) -> Result<&'a [u8], PWDuckCliError> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| invalid("unexpected end"))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Take the next `N` bytes aligned to `N` in the byte order of the system independent order.
+    fn take_number<const N: usize>(&mut self) -> Result<[u8; N], PWDuckCliError> {
+        self.align(N)?;
+        let mut bytes: [u8; N] = self
+            .take(N)?
+            .try_into()
+            .map_err(|_err| invalid("unexpected end"))?;
+        if self.big_endian {
+            bytes.reverse();
+        }
+        Ok(bytes)
+    }
+
+    /// Read an unsigned 32 bit integer.
+    fn read_u32(&mut self) -> Result<u32, PWDuckCliError> {
+        Ok(u32::from_le_bytes(self.take_number()?))
+    }
+
+    /// Read a string of the given length followed by a nul byte.
+    fn read_string(&mut self, length: usize) -> Result<String, PWDuckCliError> {
+        let bytes = self.take(length)?;
+        if self.take(1)? != [0] {
+            return Err(invalid("string without nul byte"));
+        }
+        String::from_utf8(bytes.to_vec()).map_err(|_err| invalid("string is not UTF-8"))
+    }
+
+    /// Read the value of the single complete type.
+    fn read(&mut self, signature: &str, depth: usize) -> Result<Value, PWDuckCliError> {
+        if depth > MAX_DEPTH {
+            return Err(invalid("too deeply nested"));
+        }
+        Ok(match signature.as_bytes().first() {
+            Some(b'y') => Value::Byte(self.take(1)?[0]),
+            Some(b'b') => match self.read_u32()? {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                _ => return Err(invalid("boolean out of range")),
+            },
+            Some(b'n') => Value::Int16(i16::from_le_bytes(self.take_number()?)),
+            Some(b'q') => Value::UInt16(u16::from_le_bytes(self.take_number()?)),
+            Some(b'i') => Value::Int32(i32::from_le_bytes(self.take_number()?)),
+            Some(b'u') => Value::UInt32(self.read_u32()?),
+            Some(b'x') => Value::Int64(i64::from_le_bytes(self.take_number()?)),
+            Some(b't') => Value::UInt64(u64::from_le_bytes(self.take_number()?)),
+            Some(b'd') => Value::Double(f64::from_le_bytes(self.take_number()?)),
+            Some(b's') => {
+                let length = self.read_u32()? as usize;
+                Value::String(self.read_string(length)?)
+            }
+            Some(b'o') => {
+                let length = self.read_u32()? as usize;
+                Value::ObjectPath(self.read_string(length)?)
+            }
+            Some(b'g') => {
+                let length = usize::from(self.take(1)?[0]);
+                Value::Signature(self.read_string(length)?)
+            }
+            Some(b'v') => {
+                let length = usize::from(self.take(1)?[0]);
+                let signature = self.read_string(length)?;
+                let (inner, rest) = split_type(&signature)?;
+                if !rest.is_empty() {
+                    return Err(invalid("variant with more than one type"));
+                }
+                Value::Variant(Box::new(self.read(inner, depth + 1)?))
+            }
+            Some(b'a') => {
+                let length = self.read_u32()? as usize;
+                let element = &signature[1..];
+                self.align(alignment(element))?;
+                if element == "y" {
+                    return Ok(Value::Bytes(self.take(length)?.to_vec()));
+                }
+                let end = self
+                    .position
+                    .checked_add(length)
+                    .filter(|&end| end <= self.data.len())
+                    .ok_or_else(|| invalid("array longer than the message"))?;
+                let mut elements = Vec::new();
+                while self.position < end {
+                    elements.push(self.read(element, depth + 1)?);
+                }
+                Value::Array(element.into(), elements)
+            }
+            Some(b'(') => {
+                self.align(8)?;
+                let fields = split_types(&signature[1..signature.len() - 1])?
+                    .into_iter()
+                    .map(|field| self.read(field, depth + 1))
+                    .collect::<Result<_, _>>()?;
+                Value::Struct(fields)
+            }
+            Some(b'{') => {
+                self.align(8)?;
+                let (key, value) = split_type(&signature[1..signature.len() - 1])?;
+                let key = self.read(key, depth + 1)?;
+                let value = self.read(value, depth + 1)?;
+                Value::DictEntry(Box::new(key), Box::new(value))
+            }
+            _ => return Err(invalid("unknown type")),
+        })
+    }
+}
+
+/// The type of a [`Message`](Message).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    /// A call of a method.
+    MethodCall = 1,
+    /// The successful reply to a call.
+    MethodReturn = 2,
+    /// The failed reply to a call.
+    Error = 3,
+    /// A broadcast signal.
+    Signal = 4,
+}
+
+/// The flag of calls not expecting a reply.
+pub const NO_REPLY_EXPECTED: u8 = 0x1;
+
+/// A message of the D-Bus wire protocol.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    /// The type of the message.
+    pub message_type: MessageType,
+    /// The flags of the message.
+    pub flags: u8,
+    /// The serial number assigned by the sender.
+    pub serial: u32,
+    /// The path of the object the call or signal belongs to.
+    pub path: Option<String>,
+    /// The interface of the member.
+    pub interface: Option<String>,
+    /// The name of the method or signal.
+    pub member: Option<String>,
+    /// The name of the error.
+    pub error_name: Option<String>,
+    /// The serial number of the call this message replies to.
+    pub reply_serial: Option<u32>,
+    /// The bus name of the receiver.
+    pub destination: Option<String>,
+    /// The unique bus name of the sender, set by the bus.
+    pub sender: Option<String>,
+    /// The arguments of the message.
+    pub body: Vec<Value>,
+}
+
+impl Message {
+    /// A message of the type without any header fields.
+    const fn new(message_type: MessageType, body: Vec<Value>) -> Self {
+        Self {
+            message_type,
+            flags: 0,
+            serial: 0,
+            path: None,
+            interface: None,
+            member: None,
+            error_name: None,
+            reply_serial: None,
+            destination: None,
+            sender: None,
+            body,
+        }
+    }
+
+    /// A call of the method of the object.
+    #[must_use]
+    pub fn method_call(
+        destination: &str,
+        path: &str,
+        interface: &str,
+        member: &str,
+        body: Vec<Value>,
+    ) -> Self {
+        Self {
+            destination: Some(destination.into()),
+            path: Some(path.into()),
+            interface: Some(interface.into()),
+            member: Some(member.into()),
+            ..Self::new(MessageType::MethodCall, body)
+        }
+    }
+
+    /// A signal emitted by the object.
+    #[must_use]
+    pub fn signal(path: &str, interface: &str, member: &str, body: Vec<Value>) -> Self {
+        Self {
+            path: Some(path.into()),
+            interface: Some(interface.into()),
+            member: Some(member.into()),
+            ..Self::new(MessageType::Signal, body)
+        }
+    }
+
+    /// The successful reply to this call.
+    #[must_use]
+    pub fn method_return(&self, body: Vec<Value>) -> Self {
+        Self {
+            reply_serial: Some(self.serial),
+            destination: self.sender.clone(),
+            ..Self::new(MessageType::MethodReturn, body)
+        }
+    }
+
+    /// The failed reply to this call.
+    #[must_use]
+    pub fn error(&self, name: &str, text: &str) -> Self {
+        Self {
+            error_name: Some(name.into()),
+            reply_serial: Some(self.serial),
+            destination: self.sender.clone(),
+            ..Self::new(MessageType::Error, vec![Value::String(text.into())])
+        }
+    }
+
+    /// The signature of the body.
+    #[must_use]
+    pub fn signature(&self) -> String {
+        self.body.iter().map(Value::signature).collect()
+    }
+
+    /// Marshal the message with the serial number.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the message is too long.
+    pub fn encode(&self, serial: u32) -> Result<Vec<u8>, PWDuckCliError> {
+        let mut body = Writer::default();
+        for value in &self.body {
+            body.write(value)?;
+        }
+
+        let mut fields = Vec::new();
+        let mut field = |code: u8, value: Value| {
+            fields.push(Value::Struct(vec![
+                Value::Byte(code),
+                Value::Variant(Box::new(value)),
+            ]));
+        };
+        if let Some(path) = &self.path {
+            field(1, Value::ObjectPath(path.clone()));
+        }
+        if let Some(interface) = &self.interface {
+            field(2, Value::String(interface.clone()));
+        }
+        if let Some(member) = &self.member {
+            field(3, Value::String(member.clone()));
+        }
+        if let Some(error_name) = &self.error_name {
+            field(4, Value::String(error_name.clone()));
+        }
+        if let Some(reply_serial) = self.reply_serial {
+            field(5, Value::UInt32(reply_serial));
+        }
+        if let Some(destination) = &self.destination {
+            field(6, Value::String(destination.clone()));
+        }
+        if !self.body.is_empty() {
+            field(8, Value::Signature(self.signature()));
+        }
+
+        let mut header = Writer::default();
+        header
+            .data
+            .extend_from_slice(&[b'l', self.message_type as u8, self.flags, 1]);
+        header.write_length(body.data.len())?;
+        header.write_u32(serial);
+        header.write(&Value::Array("(yv)".into(), fields))?;
+        header.align(8);
+
+        let mut data = std::mem::take(&mut header.data);
+        data.extend_from_slice(&body.data);
+        body.data.zeroize();
+        Ok(data)
+    }
+
+    /// The total length of the message starting with the first 16 bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the message is longer than [`MAX_MESSAGE_LENGTH`](MAX_MESSAGE_LENGTH).
+    pub fn length(start: &[u8; 16]) -> Result<usize, PWDuckCliError> {
+        let mut reader = Reader {
+            data: start,
+            position: 4,
+            big_endian: start[0] == b'B',
+        };
+        let body_length = reader.read_u32()? as usize;
+        reader.position = 12;
+        let fields_length = reader.read_u32()? as usize;
+        let header_length = (16 + fields_length + 7) / 8 * 8;
+        let length = header_length.saturating_add(body_length);
+        if length > MAX_MESSAGE_LENGTH {
+            return Err(invalid("too long"));
+        }
+        Ok(length)
+    }
+
+    /// Unmarshal a complete message.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the message is malformed.
+    pub fn decode(data: &[u8]) -> Result<Self, PWDuckCliError> {
+        let big_endian = match data.first() {
+            Some(b'l') => false,
+            Some(b'B') => true,
+            _ => return Err(invalid("unknown byte order")),
+        };
+        let message_type = match data.get(1) {
+            Some(1) => MessageType::MethodCall,
+            Some(2) => MessageType::MethodReturn,
+            Some(3) => MessageType::Error,
+            Some(4) => MessageType::Signal,
+            _ => return Err(invalid("unknown message type")),
+        };
+        let mut reader = Reader {
+            data,
+            position: 4,
+            big_endian,
+        };
+        let _body_length = reader.read_u32()?;
+        let mut message = Self::new(message_type, Vec::new());
+        message.flags = data[2];
+        message.serial = reader.read_u32()?;
+
+        let mut signature = String::new();
+        let fields = reader.read("a(yv)", 0)?;
+        if let Some(fields) = fields.as_array() {
+            for field in fields {
+                let (code, value) = match field.as_struct() {
+                    Some([Value::Byte(code), value]) => (*code, value.unwrap_variant()),
+                    _ => continue,
+                };
+                let text = value.as_str().map(String::from);
+                match code {
+                    1 => message.path = text,
+                    2 => message.interface = text,
+                    3 => message.member = text,
+                    4 => message.error_name = text,
+                    5 => {
+                        if let Value::UInt32(serial) = value {
+                            message.reply_serial = Some(*serial);
+                        }
+                    }
+                    6 => message.destination = text,
+                    7 => message.sender = text,
+                    8 => signature = text.unwrap_or_default(),
+                    _ => {}
+                }
+            }
+        }
+        reader.align(8)?;
+
+        for value_type in split_types(&signature)? {
+            message.body.push(reader.read(value_type, 0)?);
+        }
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_types, Message, MessageType, Value};
+
+    #[test]
+    fn signatures() {
+        let secret = Value::Struct(vec![
+            Value::ObjectPath("/session".into()),
+            Value::Bytes(Vec::new()),
+            Value::Bytes(b"secret".to_vec()),
+            Value::String("text/plain".into()),
+        ]);
+        assert_eq!(secret.signature(), "(oayays)");
+        let attributes = Value::dict(
+            "s",
+            "s",
+            vec![(Value::String("a".into()), Value::String("b".into()))],
+        );
+        assert_eq!(attributes.signature(), "a{ss}");
+        assert_eq!(
+            split_types("a{sv}(oayays)ob").unwrap(),
+            vec!["a{sv}", "(oayays)", "o", "b"]
+        );
+        assert!(split_types("a{sv").is_err());
+        assert!(split_types("z").is_err());
+    }
+
+    #[test]
+    fn encode_and_decode() {
+        let mut call = Message::method_call(
+            "org.freedesktop.secrets",
+            "/org/freedesktop/secrets",
+            "org.freedesktop.Secret.Service",
+            "OpenSession",
+            vec![
+                Value::String("plain".into()),
+                Value::Variant(Box::new(Value::String(String::new()))),
+                Value::dict(
+                    "s",
+                    "v",
+                    vec![(
+                        Value::String("label".into()),
+                        Value::Variant(Box::new(Value::UInt64(42))),
+                    )],
+                ),
+                Value::Array("(yi)".into(), Vec::new()),
+                Value::Struct(vec![
+                    Value::Byte(1),
+                    Value::Int16(-2),
+                    Value::UInt16(3),
+                    Value::Int64(-4),
+                    Value::Double(0.5),
+                    Value::Bool(true),
+                    Value::Signature("a{sv}".into()),
+                    Value::Bytes(vec![1, 2, 3]),
+                ]),
+            ],
+        );
+        let data = call.encode(7).unwrap();
+        let mut start = [0_u8; 16];
+        start.copy_from_slice(&data[..16]);
+        assert_eq!(Message::length(&start).unwrap(), data.len());
+
+        let decoded = Message::decode(&data).unwrap();
+        call.serial = 7;
+        assert_eq!(decoded, call);
+        assert_eq!(decoded.signature(), "sva{sv}a(yi)(ynqxdbgay)");
+
+        let reply = decoded.method_return(vec![Value::object_paths(vec!["/a".into()])]);
+        let decoded_reply = Message::decode(&reply.encode(8).unwrap()).unwrap();
+        assert_eq!(decoded_reply.message_type, MessageType::MethodReturn);
+        assert_eq!(decoded_reply.reply_serial, Some(7));
+        assert_eq!(decoded_reply.body[0].as_array().unwrap().len(), 1);
+
+        assert!(Message::decode(&data[..data.len() - 1]).is_err());
+        assert!(Message::decode(b"x").is_err());
+    }
+
+    #[test]
+    fn decode_big_endian() {
+        // A signal without arguments, as sent by a big endian machine.
+        let mut data = vec![b'B', 4, 0, 1, 0, 0, 0, 0, 0, 0, 0, 9];
+        let mut fields = Vec::new();
+        for (code, value) in [(1_u8, "/a"), (2, "b.c"), (3, "D")] {
+            while fields.len() % 8 != 0 {
+                fields.push(0);
+            }
+            let signature = if code == 1 { b'o' } else { b's' };
+            fields.extend_from_slice(&[code, 1, signature, 0]);
+            fields.extend_from_slice(&(value.len() as u32).to_be_bytes());
+            fields.extend_from_slice(value.as_bytes());
+            fields.push(0);
+        }
+        data.extend_from_slice(&(fields.len() as u32).to_be_bytes());
+        data.extend_from_slice(&fields);
+        while data.len() % 8 != 0 {
+            data.push(0);
+        }
+
+        let message = Message::decode(&data).unwrap();
+        assert_eq!(message.message_type, MessageType::Signal);
+        assert_eq!(message.serial, 9);
+        assert_eq!(message.path.as_deref(), Some("/a"));
+        assert_eq!(message.interface.as_deref(), Some("b.c"));
+        assert_eq!(message.member.as_deref(), Some("D"));
+    }
+}
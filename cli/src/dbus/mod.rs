@@ -0,0 +1,281 @@
+//! A minimal connection to a D-Bus message bus.
+//!
+//! It supports buses listening on Unix domain socket paths, e.g. `unix:path=/run/user/1000/bus`,
+//! and authenticates with the credentials of the process (`EXTERNAL`).
+//! Only the messages needed to provide a service are implemented.
+//!
+//! See: <https://dbus.freedesktop.org/doc/dbus-specification.html>
+use std::{
+    collections::VecDeque,
+    io::{Read, Write},
+    os::unix::net::UnixStream,
+};
+
+use zeroize::Zeroize;
+
+use crate::error::PWDuckCliError;
+
+pub mod message;
+
+pub use message::{Message, MessageType, Value, NO_REPLY_EXPECTED};
+
+/// The bus name of the message bus itself.
+const BUS_NAME: &str = "org.freedesktop.DBus";
+
+/// The object path of the message bus itself.
+const BUS_PATH: &str = "/org/freedesktop/DBus";
+
+/// The flag of `RequestName` to fail instead of waiting in the queue for the name.
+pub const DO_NOT_QUEUE: u32 = 0x4;
+
+/// The reply of `RequestName`, if the connection owns the name now.
+pub const PRIMARY_OWNER: u32 = 1;
+
+/// A connection to a message bus.
+#[derive(Debug)]
+pub struct Connection {
+    /// The socket of the bus.
+    stream: UnixStream,
+    /// The serial number of the last sent message.
+    serial: u32,
+    /// The unique name assigned by the bus.
+    unique_name: String,
+    /// Messages received while waiting for a reply.
+    queue: VecDeque<Message>,
+}
+
+impl Connection {
+    /// Connect to the session bus given by the environment variable `DBUS_SESSION_BUS_ADDRESS`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the variable is not set or the bus can't be reached.
+    pub fn session() -> Result<Self, PWDuckCliError> {
+        let address = std::env::var("DBUS_SESSION_BUS_ADDRESS").map_err(|_err| {
+            PWDuckCliError::NotFound("The session bus (DBUS_SESSION_BUS_ADDRESS)".into())
+        })?;
+        Self::open(&address)
+    }
+
+    /// Connect to the bus at the address, e.g. `unix:path=/run/user/1000/bus`.
+    /// Of multiple addresses separated by `;` the first reachable one is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if none of the addresses can be reached or the authentication fails.
+    pub fn open(address: &str) -> Result<Self, PWDuckCliError> {
+        let mut last_error = PWDuckCliError::Refused(format!(
+            "The bus address {} contains no supported unix:path= address",
+            address
+        ));
+        for path in address.split(';').filter_map(socket_path) {
+            match UnixStream::connect(&path) {
+                Ok(stream) => return Self::start(stream),
+                Err(error) => last_error = error.into(),
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Authenticate on the connected socket and register at the bus.
+    fn start(mut stream: UnixStream) -> Result<Self, PWDuckCliError> {
+        // The credentials are taken from the socket, so no data has to be sent.
+        stream.write_all(b"\0AUTH EXTERNAL\r\n")?;
+        let mut line = read_auth_line(&mut stream)?;
+        if line.starts_with("DATA") {
+            stream.write_all(b"DATA\r\n")?;
+            line = read_auth_line(&mut stream)?;
+        }
+        if !line.starts_with("OK ") {
+            return Err(PWDuckCliError::Refused(format!(
+                "The bus refused the authentication ({})",
+                line.trim_end()
+            )));
+        }
+        stream.write_all(b"BEGIN\r\n")?;
+
+        let mut connection = Self {
+            stream,
+            serial: 0,
+            unique_name: String::new(),
+            queue: VecDeque::new(),
+        };
+        let reply = connection.call(&Message::method_call(
+            BUS_NAME,
+            BUS_PATH,
+            BUS_NAME,
+            "Hello",
+            vec![],
+        ))?;
+        connection.unique_name = reply
+            .body
+            .first()
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .into();
+        Ok(connection)
+    }
+
+    /// The unique name assigned by the bus, e.g. `:1.42`.
+    #[must_use]
+    pub fn unique_name(&self) -> &str {
+        &self.unique_name
+    }
+
+    /// Request the well-known name on the bus. Returns the reply code, e.g. [`PRIMARY_OWNER`](PRIMARY_OWNER).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the bus fails.
+    pub fn request_name(&mut self, name: &str, flags: u32) -> Result<u32, PWDuckCliError> {
+        let reply = self.call(&Message::method_call(
+            BUS_NAME,
+            BUS_PATH,
+            BUS_NAME,
+            "RequestName",
+            vec![Value::String(name.into()), Value::UInt32(flags)],
+        ))?;
+        match reply.body.first() {
+            Some(Value::UInt32(code)) => Ok(*code),
+            _ => Err(PWDuckCliError::Refused(
+                "The bus sent an unexpected reply to RequestName".into(),
+            )),
+        }
+    }
+
+    /// Send the message. Returns the serial number assigned to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the message can't be written.
+    pub fn send(&mut self, message: &Message) -> Result<u32, PWDuckCliError> {
+        self.serial = self.serial.wrapping_add(1).max(1);
+        let mut data = message.encode(self.serial)?;
+        let result = self.stream.write_all(&data);
+        data.zeroize();
+        result?;
+        Ok(self.serial)
+    }
+
+    /// Receive the next message.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the connection is closed or the message is malformed.
+    pub fn receive(&mut self) -> Result<Message, PWDuckCliError> {
+        if let Some(message) = self.queue.pop_front() {
+            return Ok(message);
+        }
+
+        let mut start = [0_u8; 16];
+        self.stream.read_exact(&mut start)?;
+        let mut data = vec![0_u8; Message::length(&start)?];
+        data[..16].copy_from_slice(&start);
+        let result = self
+            .stream
+            .read_exact(&mut data[16..])
+            .map_err(PWDuckCliError::from)
+            .and_then(|()| Message::decode(&data));
+        data.zeroize();
+        result
+    }
+
+    /// Call a method and wait for its reply. Other messages received meanwhile are queued.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the connection fails or the method replies with an error.
+    pub fn call(&mut self, message: &Message) -> Result<Message, PWDuckCliError> {
+        let serial = self.send(message)?;
+        let mut received = Vec::new();
+        let reply = loop {
+            let reply = match self.receive() {
+                Ok(reply) => reply,
+                Err(error) => {
+                    self.queue.extend(received);
+                    return Err(error);
+                }
+            };
+            let is_reply = matches!(
+                reply.message_type,
+                MessageType::MethodReturn | MessageType::Error
+            ) && reply.reply_serial == Some(serial);
+            if is_reply {
+                break reply;
+            }
+            received.push(reply);
+        };
+        self.queue.extend(received);
+
+        if reply.message_type == MessageType::Error {
+            return Err(PWDuckCliError::Refused(format!(
+                "{}: {}",
+                reply.error_name.as_deref().unwrap_or_default(),
+                reply
+                    .body
+                    .first()
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+            )));
+        }
+        Ok(reply)
+    }
+}
+
+/// Read a line of the authentication protocol byte by byte, so no message data is consumed.
+fn read_auth_line(stream: &mut UnixStream) -> Result<String, PWDuckCliError> {
+    let mut line = Vec::new();
+    let mut byte = [0_u8; 1];
+    while !line.ends_with(b"\r\n") {
+        stream.read_exact(&mut byte)?;
+        line.push(byte[0]);
+        if line.len() > 4096 {
+            return Err(PWDuckCliError::Refused(
+                "The bus sent an overlong authentication line".into(),
+            ));
+        }
+    }
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+/// The socket path of a single `unix:path=` address with its escaped bytes decoded.
+fn socket_path(address: &str) -> Option<String> {
+    let parameters = address.strip_prefix("unix:")?;
+    let path = parameters
+        .split(',')
+        .find_map(|parameter| parameter.strip_prefix("path="))?;
+
+    let mut bytes = Vec::new();
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::socket_path;
+
+    #[test]
+    fn parse_address() {
+        assert_eq!(
+            socket_path("unix:path=/run/user/1000/bus").as_deref(),
+            Some("/run/user/1000/bus")
+        );
+        assert_eq!(
+            socket_path("unix:guid=1234,path=/tmp/my%20bus").as_deref(),
+            Some("/tmp/my bus")
+        );
+        assert_eq!(socket_path("unix:abstract=/tmp/dbus-1234"), None);
+        assert_eq!(socket_path("tcp:host=localhost,port=1234"), None);
+        assert_eq!(socket_path("unix:path=/tmp/%2"), None);
+    }
+}
@@ -0,0 +1,163 @@
+//! Run commands on the vault served by the agent or unlocked by the client itself.
+use std::io::{BufRead, Write};
+
+use pwduck_core::{MemKey, SecString};
+
+use crate::{
+    args::{Command, UnlockArgs},
+    commands,
+    error::PWDuckCliError,
+    unlock,
+};
+
+/// Run the command on the vault and return the exit code.
+///
+/// If a `pwduck-agent` serves the vault, the command is sent to the agent.
+/// Otherwise the vault is unlocked and locked again afterwards.
+///
+/// It expects:
+///  - The [`UnlockArgs`](UnlockArgs) of the vault
+///  - True, if the vault should be unlocked even if an agent serves it
+///  - The [`Command`](Command) to run
+///  - The secrets of the command or `None` to read them, see: [`read_secrets`](commands::read_secrets)
+///  - True, if the result should be printed as JSON
+///  - The input to read the password and the secrets from, if they are not read from the terminal
+///  - The output to print the result to
+///
+/// # Errors
+///
+/// Returns `Err` if the vault can't be unlocked or the command fails.
+#[allow(clippy::fn_params_excessive_bools)]
+pub fn dispatch(
+    unlock_args: &UnlockArgs,
+    no_agent: bool,
+    command: &Command,
+    secrets: Option<Vec<SecString>>,
+    json: bool,
+    input: &mut dyn BufRead,
+    out: &mut dyn Write,
+) -> Result<i32, PWDuckCliError> {
+    #[cfg(unix)]
+    if !no_agent {
+        if let Some(stream) = crate::agent::client::connect(&unlock_args.vault) {
+            let request = crate::agent::Request {
+                command: command.clone(),
+                secrets: match secrets {
+                    Some(secrets) => secrets,
+                    None => commands::read_secrets(command, input)?,
+                },
+                json,
+            };
+            let response = crate::agent::client::send(&stream, &request)?;
+            out.write_all(response.stdout.as_bytes())?;
+            std::io::stderr().write_all(response.stderr.as_bytes())?;
+            return Ok(response.code);
+        }
+    }
+    if matches!(command, Command::Lock) {
+        return Err(PWDuckCliError::NotFound(
+            "The agent serving the vault".into(),
+        ));
+    }
+
+    let mem_key = MemKey::new();
+    let mut vault = unlock::unlock(unlock_args, &mem_key, input)?;
+    let secrets = match secrets {
+        Some(secrets) => secrets,
+        None => commands::read_secrets(command, input)?,
+    };
+
+    commands::run(command, &mut vault, &mem_key, secrets, json, out)?;
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use pwduck_core::ReferenceField;
+    use tempfile::tempdir;
+
+    use crate::{
+        args::{Command, UnlockArgs},
+        commands::tests::{example_vault, PASSWORD},
+        error::PWDuckCliError,
+    };
+
+    use super::dispatch;
+
+    #[test]
+    fn unlock_and_run() {
+        let dir = tempdir().unwrap();
+        let (vault, _mem_key) = example_vault(dir.path());
+        let unlock_args = UnlockArgs {
+            vault: vault.path().clone(),
+            key_file: None,
+            password_stdin: true,
+        };
+        drop(vault);
+
+        let show = Command::Show {
+            entry: "/Bank".into(),
+            field: Some(ReferenceField::Password),
+        };
+        let mut out = Vec::new();
+        let input = format!("{}\n", PASSWORD);
+        let code = dispatch(
+            &unlock_args,
+            true,
+            &show,
+            None,
+            false,
+            &mut input.as_bytes(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(code, 0);
+        assert_eq!(out, b"bank secret\n");
+
+        let add = Command::Add {
+            entry: "/Shop".into(),
+            username: String::new(),
+            email: String::new(),
+            web_address: String::new(),
+            secret_stdin: true,
+        };
+        let input = format!("{}\nshop secret\n", PASSWORD);
+        let _ = dispatch(
+            &unlock_args,
+            true,
+            &add,
+            None,
+            false,
+            &mut input.as_bytes(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        let show = Command::Show {
+            entry: "/Shop".into(),
+            field: Some(ReferenceField::Password),
+        };
+        let mut out = Vec::new();
+        let _ = dispatch(
+            &unlock_args,
+            true,
+            &show,
+            Some(Vec::new()),
+            false,
+            &mut input.as_bytes(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(out, b"shop secret\n");
+
+        let lock = dispatch(
+            &unlock_args,
+            true,
+            &Command::Lock,
+            None,
+            false,
+            &mut &b""[..],
+            &mut Vec::new(),
+        );
+        assert!(matches!(lock, Err(PWDuckCliError::NotFound(_))));
+    }
+}
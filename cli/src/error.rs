@@ -0,0 +1,230 @@
+//! This module contains everything related to errors occurring in the command line client.
+
+use std::{fmt::Display, io::Write};
+
+use pwduck_core::PWDuckCoreError;
+
+/// An error thrown in the command line client.
+#[derive(Debug)]
+pub enum PWDuckCliError {
+    /// Reading the input or writing the output failed.
+    IO(std::io::Error),
+    /// The addressed group or entry does not exist.
+    NotFound(String),
+    /// A group or entry with the same path already exists.
+    AlreadyExists(String),
+    /// The command was refused to protect the vault, e.g. removing a non-empty group without `--recursive`.
+    Refused(String),
+    /// An error bubbled up from the core.
+    PWDuckCoreError(PWDuckCoreError),
+}
+
+impl From<std::io::Error> for PWDuckCliError {
+    #[cfg_attr(coverage, no_coverage)]
+    fn from(error: std::io::Error) -> Self {
+        Self::IO(error)
+    }
+}
+
+impl From<PWDuckCoreError> for PWDuckCliError {
+    #[cfg_attr(coverage, no_coverage)]
+    fn from(error: PWDuckCoreError) -> Self {
+        Self::PWDuckCoreError(error)
+    }
+}
+
+impl Display for PWDuckCliError {
+    #[cfg_attr(coverage, no_coverage)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PWDuckCliError::IO(error) => write!(f, "Could not read or write ({})", error),
+            PWDuckCliError::NotFound(path) => write!(f, "{} does not exist", path),
+            PWDuckCliError::AlreadyExists(path) => write!(f, "{} already exists", path),
+            PWDuckCliError::Refused(reason) => write!(f, "{}", reason),
+            PWDuckCliError::PWDuckCoreError(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// The kind of a [`PWDuckCliError`](PWDuckCliError).
+///
+/// Each kind exits the command line client with its own exit code. The exit codes and names are
+/// part of the stable interface for scripts and will not change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// `1 error`: Any error not covered by another kind.
+    Error,
+    /// `2 not_found`: The addressed group or entry does not exist.
+    NotFound,
+    /// `3 already_exists`: A group or entry with the same path already exists.
+    AlreadyExists,
+    /// `4 refused`: The command was refused to protect the vault.
+    Refused,
+    /// `10 wrong_password`: The password or the key file is wrong.
+    WrongPassword,
+    /// `11 missing_vault`: The vault or one of its files does not exist.
+    MissingVault,
+    /// `12 corrupt_file`: A file of the vault can't be decoded.
+    CorruptFile,
+    /// `13 conflict`: The vault was changed on disk by another program.
+    Conflict,
+    /// `14 broken_reference`: A field reference can't be resolved.
+    BrokenReference,
+    /// `15 io`: Reading or writing a file, the input or the output failed.
+    IO,
+}
+
+impl ErrorKind {
+    /// The exit code of this [`ErrorKind`](ErrorKind).
+    #[must_use]
+    pub const fn exit_code(self) -> i32 {
+        match self {
+            Self::Error => 1,
+            Self::NotFound => 2,
+            Self::AlreadyExists => 3,
+            Self::Refused => 4,
+            Self::WrongPassword => 10,
+            Self::MissingVault => 11,
+            Self::CorruptFile => 12,
+            Self::Conflict => 13,
+            Self::BrokenReference => 14,
+            Self::IO => 15,
+        }
+    }
+
+    /// The name of this [`ErrorKind`](ErrorKind) used in the JSON output.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::NotFound => "not_found",
+            Self::AlreadyExists => "already_exists",
+            Self::Refused => "refused",
+            Self::WrongPassword => "wrong_password",
+            Self::MissingVault => "missing_vault",
+            Self::CorruptFile => "corrupt_file",
+            Self::Conflict => "conflict",
+            Self::BrokenReference => "broken_reference",
+            Self::IO => "io",
+        }
+    }
+}
+
+impl PWDuckCliError {
+    /// Print this error as a message or as JSON.
+    ///
+    /// It expects:
+    ///  - True, if the error should be printed as JSON
+    ///  - The output to print to, usually the standard error
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the output can't be written.
+    pub fn report(&self, json: bool, out: &mut dyn Write) -> Result<(), PWDuckCliError> {
+        if json {
+            crate::json::write(out, &crate::json::ErrorOutput::from(self))
+        } else {
+            writeln!(out, "Error: {}", self)?;
+            Ok(())
+        }
+    }
+
+    /// The [`ErrorKind`](ErrorKind) of this error.
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::IO(_) => ErrorKind::IO,
+            Self::NotFound(_) => ErrorKind::NotFound,
+            Self::AlreadyExists(_) => ErrorKind::AlreadyExists,
+            Self::Refused(_) => ErrorKind::Refused,
+            Self::PWDuckCoreError(error) => match error {
+                PWDuckCoreError::BlockMode(_) => ErrorKind::WrongPassword,
+                PWDuckCoreError::IO(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                    ErrorKind::MissingVault
+                }
+                PWDuckCoreError::IO(_) => ErrorKind::IO,
+                PWDuckCoreError::Base64(_)
+                | PWDuckCoreError::BlockModeIV(_)
+                | PWDuckCoreError::Json(_)
+                | PWDuckCoreError::Ron(_)
+                | PWDuckCoreError::Utf8(_) => ErrorKind::CorruptFile,
+                PWDuckCoreError::Conflict(_) => ErrorKind::Conflict,
+                PWDuckCoreError::Reference(_) => ErrorKind::BrokenReference,
+                _ => ErrorKind::Error,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use pwduck_core::{MemKey, PWDuckCoreError, Vault};
+    use tempfile::tempdir;
+
+    use crate::commands::tests::{example_vault, PASSWORD};
+
+    use super::{ErrorKind, PWDuckCliError};
+
+    #[test]
+    fn kind() {
+        let dir = tempdir().unwrap();
+        let (vault, _mem_key) = example_vault(dir.path());
+        let load = |password: &str, path: &std::path::Path| {
+            let mem_key = MemKey::with_length(1);
+            PWDuckCliError::from(
+                Vault::load(password, Option::<String>::None, &mem_key, path).unwrap_err(),
+            )
+            .kind()
+        };
+
+        assert_eq!(
+            load("wrong password", vault.path()),
+            ErrorKind::WrongPassword
+        );
+        assert_eq!(
+            load(PASSWORD, &dir.path().join("Missing")),
+            ErrorKind::MissingVault
+        );
+        std::fs::write(vault.path().join("master_key.pwduck"), "corrupt").unwrap();
+        assert_eq!(load(PASSWORD, vault.path()), ErrorKind::CorruptFile);
+
+        let core = |error: PWDuckCoreError| PWDuckCliError::from(error).kind();
+        assert_eq!(
+            core(PWDuckCoreError::IO(io::ErrorKind::PermissionDenied.into())),
+            ErrorKind::IO
+        );
+        assert_eq!(
+            core(PWDuckCoreError::Conflict(Vec::new())),
+            ErrorKind::Conflict
+        );
+        assert_eq!(
+            core(PWDuckCoreError::Error(String::new())),
+            ErrorKind::Error
+        );
+        assert_eq!(
+            PWDuckCliError::NotFound(String::new()).kind(),
+            ErrorKind::NotFound
+        );
+
+        let kinds = [
+            ErrorKind::Error,
+            ErrorKind::NotFound,
+            ErrorKind::AlreadyExists,
+            ErrorKind::Refused,
+            ErrorKind::WrongPassword,
+            ErrorKind::MissingVault,
+            ErrorKind::CorruptFile,
+            ErrorKind::Conflict,
+            ErrorKind::BrokenReference,
+            ErrorKind::IO,
+        ];
+        for (index, kind) in kinds.iter().enumerate() {
+            assert!(kind.exit_code() > 0);
+            assert!(kinds[index + 1..].iter().all(|other| {
+                other.exit_code() != kind.exit_code() && other.name() != kind.name()
+            }));
+        }
+    }
+}
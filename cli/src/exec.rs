@@ -0,0 +1,364 @@
+//! Run programs with fields of entries in their environment.
+//!
+//! Environment variables are bound to fields by `--env VARIABLE=<entry>:<field>` or by a file like `.pwduck-env`:
+//! ```text
+//! # The database of the deployment
+//! DB_USER=Database:username
+//! DB_PASSWORD=Database:password
+//! ```
+//! The field defaults to the password. Relative paths, i.e. paths without a leading `/`, are resolved in the
+//! group given by `--group`, so the same file can be used for different groups like `/Staging` and `/Production`.
+//!
+//! The secrets are only passed to the environment of the program. They never appear in its arguments,
+//! in the shell history or in a file.
+use std::{fs, path::Path, process, str::FromStr};
+
+use pwduck_core::{MemKey, ReferenceField, SecString, Uuid, Vault};
+use serde::Deserialize;
+
+use crate::{
+    args::{Command, RunArgs},
+    commands::field_value,
+    error::PWDuckCliError,
+    path::{join, SEPARATOR},
+};
+
+/// The file containing the bindings, if none are given on the command line.
+pub const DEFAULT_ENV_FILE: &str = ".pwduck-env";
+
+/// An environment variable bound to a field of an entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Binding {
+    /// The name of the environment variable.
+    pub variable: String,
+    /// The path or UUID of the entry.
+    pub entry: String,
+    /// The field of the entry.
+    pub field: ReferenceField,
+}
+
+impl Binding {
+    /// The `show` [`Command`](Command) printing the value of the bound field.
+    #[must_use]
+    pub fn show(&self) -> Command {
+        Command::Show {
+            entry: self.entry.clone(),
+            field: Some(self.field),
+        }
+    }
+
+    /// Resolve a relative path of the entry in the group.
+    fn relative_to(mut self, group: &str) -> Self {
+        if !self.entry.starts_with(SEPARATOR) && self.entry.parse::<Uuid>().is_err() {
+            self.entry = join(group, &self.entry);
+        }
+        self
+    }
+}
+
+impl FromStr for Binding {
+    type Err = PWDuckCliError;
+
+    fn from_str(binding: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            PWDuckCliError::Refused(format!(
+                "The binding {} is not of the form VARIABLE=<entry>:<field>",
+                binding
+            ))
+        };
+
+        let (variable, address) = binding.split_once('=').ok_or_else(invalid)?;
+        let (variable, address) = (variable.trim(), address.trim());
+        if !is_variable(variable) || address.is_empty() {
+            return Err(invalid());
+        }
+
+        // Titles may contain colons, so the suffix is only a field if it names one.
+        let (entry, field) = address
+            .rsplit_once(':')
+            .and_then(|(entry, field)| Some((entry, field.parse().ok()?)))
+            .unwrap_or((address, ReferenceField::Password));
+
+        Ok(Self {
+            variable: variable.into(),
+            entry: entry.into(),
+            field,
+        })
+    }
+}
+
+/// True, if the name is a portable name of an environment variable.
+fn is_variable(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|first: char| first.is_ascii_digit())
+        && name
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
+
+/// Parse the bindings of a file with one binding per line.
+/// Empty lines and lines starting with `#` are ignored.
+///
+/// # Errors
+///
+/// Returns `Err` if a line is not a valid binding.
+pub fn parse_bindings(content: &str) -> Result<Vec<Binding>, PWDuckCliError> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::parse)
+        .collect()
+}
+
+/// Collect the bindings of the file and of the command line.
+/// Bindings of the command line override the bindings of the file.
+///
+/// # Errors
+///
+/// Returns `Err` if the file can't be read, a binding is invalid or no variable is bound.
+pub fn bindings(options: &RunArgs) -> Result<Vec<Binding>, PWDuckCliError> {
+    let default_file = Path::new(DEFAULT_ENV_FILE);
+    let file = match &options.env_file {
+        Some(file) => Some(file.as_path()),
+        None if options.env.is_empty() && default_file.is_file() => Some(default_file),
+        None => None,
+    };
+
+    let mut bindings = match file {
+        Some(file) => parse_bindings(&fs::read_to_string(file)?)?,
+        None => Vec::new(),
+    };
+    for binding in &options.env {
+        bindings.push(binding.parse()?);
+    }
+    if bindings.is_empty() {
+        return Err(PWDuckCliError::Refused(format!(
+            "No environment variable is bound, use --env, --env-file or {}",
+            DEFAULT_ENV_FILE
+        )));
+    }
+
+    Ok(match &options.group {
+        Some(group) => bindings
+            .into_iter()
+            .map(|binding| binding.relative_to(group))
+            .collect(),
+        None => bindings,
+    })
+}
+
+/// Resolve the values of the bound fields in the unlocked [`Vault`](Vault).
+///
+/// It expects:
+///  - The unlocked [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to decrypt the master key of the [`Vault`](Vault)
+///  - The bindings to resolve
+///
+/// # Errors
+///
+/// Returns `Err` if an entry does not exist or can't be decrypted.
+pub fn resolve(
+    vault: &Vault,
+    mem_key: &MemKey,
+    bindings: &[Binding],
+) -> Result<Vec<(String, SecString)>, PWDuckCliError> {
+    bindings
+        .iter()
+        .map(|binding| {
+            let value = field_value(vault, mem_key, &binding.entry, binding.field)?;
+            Ok((binding.variable.clone(), value))
+        })
+        .collect()
+}
+
+/// The output of `show --field` as JSON.
+#[derive(Deserialize)]
+struct PrintedValue {
+    /// The value of the field.
+    value: SecString,
+}
+
+/// The value of a field printed by `show --field`, e.g. by the agent.
+///
+/// # Errors
+///
+/// Returns `Err` if the JSON output can't be parsed.
+pub fn printed_value(output: &str, json: bool) -> Result<SecString, PWDuckCliError> {
+    if json {
+        let printed: PrintedValue = serde_json::from_str(output).map_err(std::io::Error::from)?;
+        return Ok(printed.value);
+    }
+    Ok(output.strip_suffix('\n').unwrap_or(output).into())
+}
+
+/// Run the program with the variables added to its environment and wait for it to exit.
+/// The program inherits the standard input and output.
+///
+/// Returns the exit code of the program or `128` plus the number of the signal that killed it.
+///
+/// # Errors
+///
+/// Returns `Err` if the program can't be started.
+pub fn spawn(program: &[String], variables: &[(String, SecString)]) -> Result<i32, PWDuckCliError> {
+    let (name, arguments) = program
+        .split_first()
+        .ok_or_else(|| PWDuckCliError::Refused("No program to run is given".into()))?;
+
+    let status = process::Command::new(name)
+        .args(arguments)
+        .envs(
+            variables
+                .iter()
+                .map(|(variable, value)| (variable, value.as_str())),
+        )
+        .status()
+        .map_err(|error| {
+            if error.kind() == std::io::ErrorKind::NotFound {
+                PWDuckCliError::NotFound(format!("The program {}", name))
+            } else {
+                error.into()
+            }
+        })?;
+
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return Ok(128 + signal);
+    }
+    Ok(status.code().unwrap_or(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use pwduck_core::ReferenceField;
+    use tempfile::tempdir;
+
+    use crate::{
+        args::{Command, RunArgs},
+        commands::tests::{example_vault, output},
+        path::find_entry,
+    };
+
+    use super::{bindings, parse_bindings, printed_value, resolve, spawn, Binding};
+
+    fn options(env: &[&str], group: Option<&str>) -> RunArgs {
+        RunArgs {
+            env: env.iter().map(|&binding| binding.into()).collect(),
+            env_file: None,
+            group: group.map(Into::into),
+            program: vec!["true".into()],
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "DB_USER=/Servers/Database:username"
+                .parse::<Binding>()
+                .unwrap(),
+            Binding {
+                variable: "DB_USER".into(),
+                entry: "/Servers/Database".into(),
+                field: ReferenceField::Username,
+            }
+        );
+        let binding: Binding = " TOKEN = Server: prod ".parse().unwrap();
+        assert_eq!(binding.entry, "Server: prod");
+        assert_eq!(binding.field, ReferenceField::Password);
+
+        assert!("TOKEN".parse::<Binding>().is_err());
+        assert!("TOKEN=".parse::<Binding>().is_err());
+        assert!("1TOKEN=Bank".parse::<Binding>().is_err());
+        assert!("MY-TOKEN=Bank".parse::<Binding>().is_err());
+
+        let file = "# Deployment\n\nDB_USER=Database:username\n  DB_PASSWORD=Database\n";
+        let parsed = parse_bindings(file).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].variable, "DB_PASSWORD");
+        assert!(parse_bindings("DB_USER=Database\ninvalid\n").is_err());
+    }
+
+    #[test]
+    fn collect_bindings() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("env");
+        std::fs::write(&file, "USER=Mail:username\nPASSWORD=Mail\n").unwrap();
+
+        let mut run = options(&["PASSWORD=/Bank"], Some("/Email/Work"));
+        run.env_file = Some(file);
+        let collected = bindings(&run).unwrap();
+        assert_eq!(
+            collected
+                .iter()
+                .map(|binding| binding.entry.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/Email/Work/Mail", "/Email/Work/Mail", "/Bank"]
+        );
+
+        run.env_file = Some(dir.path().join("missing"));
+        assert!(bindings(&run).is_err());
+    }
+
+    #[test]
+    fn resolve_fields() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+        let bank = find_entry(&vault, "/Bank").unwrap().uuid().to_string();
+
+        let run = options(
+            &[
+                "USER=Mail:username",
+                "PASSWORD=Mail",
+                &format!("BANK={}", bank),
+            ],
+            Some("/Email/Work/"),
+        );
+        let variables = resolve(&vault, &mem_key, &bindings(&run).unwrap()).unwrap();
+        let variables: Vec<_> = variables
+            .iter()
+            .map(|(variable, value)| (variable.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            variables,
+            vec![
+                ("USER", "alice@work"),
+                ("PASSWORD", "mail secret"),
+                ("BANK", "bank secret")
+            ]
+        );
+
+        let missing = options(&["PASSWORD=Missing"], None);
+        assert!(resolve(&vault, &mem_key, &bindings(&missing).unwrap()).is_err());
+
+        // The printed values of the agent are the same.
+        let binding: Binding = "PASSWORD=/Email/Work/Mail".parse().unwrap();
+        let text = output(&binding.show(), &mut vault, &mem_key).unwrap();
+        assert_eq!(printed_value(&text, false).unwrap().as_str(), "mail secret");
+        let mut json = Vec::new();
+        crate::commands::run(
+            &binding.show(),
+            &mut vault,
+            &mem_key,
+            Vec::new(),
+            true,
+            &mut json,
+        )
+        .unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(printed_value(&json, true).unwrap().as_str(), "mail secret");
+        assert!(matches!(binding.show(), Command::Show { .. }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn spawn_program() {
+        let variables = vec![("PWDUCK_TEST_SECRET".into(), "mail secret".into())];
+        let check =
+            |script: &str| spawn(&["sh".into(), "-c".into(), script.into()], &variables).unwrap();
+        assert_eq!(check("test \"$PWDUCK_TEST_SECRET\" = 'mail secret'"), 0);
+        assert_eq!(check("exit 3"), 3);
+        assert_eq!(check("kill -TERM $$"), 128 + 15);
+        assert!(spawn(&["pwduck-missing-program".into()], &variables).is_err());
+        assert!(spawn(&[], &variables).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! The credential helper protocol of git.
+//!
+//! Git writes the attributes of a request as `key=value` lines to the standard input of the helper,
+//! terminated by an empty line or the end of the input. The answer of `get` uses the same format.
+//! See: <https://git-scm.com/docs/git-credential#IOFMT>
+use std::io::BufRead;
+
+use pwduck_core::SecString;
+use zeroize::Zeroize;
+
+use crate::{
+    args::{CredentialArgs, Operation},
+    error::PWDuckCliError,
+    input::read_line,
+};
+
+/// Read the attributes of a request of git. Unknown attributes are ignored.
+///
+/// It expects:
+///  - The input containing the request
+///  - The requested [`Operation`](Operation)
+///  - The path of the group new entries are stored in
+///
+/// Returns the request and its password, if it contains one.
+///
+/// # Errors
+///
+/// Returns `Err` if the input can't be read.
+pub fn read_request(
+    input: &mut dyn BufRead,
+    operation: Operation,
+    group: &str,
+) -> Result<(CredentialArgs, Option<SecString>), PWDuckCliError> {
+    let mut request = CredentialArgs {
+        operation,
+        protocol: None,
+        host: None,
+        path: None,
+        username: None,
+        group: group.into(),
+        secret_stdin: false,
+    };
+    let mut password = None;
+
+    loop {
+        let mut line = read_line(input)?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            match key {
+                "protocol" => request.protocol = Some(value.into()),
+                "host" => request.host = Some(value.into()),
+                "path" => request.path = Some(value.into()),
+                "username" => request.username = Some(value.into()),
+                "password" => password = Some(SecString::from(value)),
+                _ => {}
+            }
+        }
+        line.zeroize();
+    }
+
+    Ok((request, password))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::args::Operation;
+
+    use super::read_request;
+
+    #[test]
+    fn read() {
+        let mut input = &b"protocol=https\nhost=example.com:8080\ncapability[]=authtype\n\
+            username=alice\npassword=a=b\n\nhost=ignored.com\n"[..];
+        let (request, password) = read_request(&mut input, Operation::Store, "/Git").unwrap();
+        assert_eq!(request.operation, Operation::Store);
+        assert_eq!(request.protocol.as_deref(), Some("https"));
+        assert_eq!(request.host.as_deref(), Some("example.com:8080"));
+        assert_eq!(request.path, None);
+        assert_eq!(request.username.as_deref(), Some("alice"));
+        assert_eq!(request.group, "/Git");
+        assert_eq!(password.unwrap().as_str(), "a=b");
+
+        let mut input = &b"host=example.com"[..];
+        let (request, password) = read_request(&mut input, Operation::Get, "/").unwrap();
+        assert_eq!(request.host.as_deref(), Some("example.com"));
+        assert!(password.is_none());
+    }
+}
@@ -0,0 +1,101 @@
+//! Read secrets from the standard input or from the terminal without echoing them
+//! and ask the user for confirmations.
+use std::{
+    ffi::{OsStr, OsString},
+    io::BufRead,
+    process::Stdio,
+};
+
+use crate::error::PWDuckCliError;
+
+/// Read a secret from the next line of the input or prompt for it on the terminal.
+///
+/// It expects:
+///  - The prompt shown on the terminal
+///  - True, if the secret should be read from the input instead of the terminal
+///  - The input to read the secret from
+///
+/// # Errors
+///
+/// Returns `Err` if the input or the terminal can't be read.
+#[cfg_attr(coverage, no_coverage)]
+pub fn read_secret(
+    prompt: &str,
+    from_input: bool,
+    input: &mut dyn BufRead,
+) -> Result<String, PWDuckCliError> {
+    if from_input {
+        read_line(input)
+    } else {
+        Ok(rpassword::read_password_from_tty(Some(prompt))?)
+    }
+}
+
+/// Read the next line of the input without the line break.
+///
+/// # Errors
+///
+/// Returns `Err` if the input can't be read.
+pub fn read_line(input: &mut dyn BufRead) -> Result<String, PWDuckCliError> {
+    let mut line = String::new();
+    let _ = input.read_line(&mut line)?;
+
+    let length = line.trim_end_matches(&['\r', '\n'][..]).len();
+    line.truncate(length);
+    Ok(line)
+}
+
+/// Ask the user for a confirmation with the program in `SSH_ASKPASS`, see: [`confirm_with`](confirm_with).
+/// Refuses, if there is no such program or it can't be started.
+#[must_use]
+#[cfg_attr(coverage, no_coverage)]
+pub fn confirm_with_askpass(prompt: &str) -> bool {
+    askpass().map_or(false, |askpass| {
+        confirm_with(&askpass, prompt).unwrap_or(false)
+    })
+}
+
+/// The program in `SSH_ASKPASS`, if there is one.
+#[must_use]
+pub fn askpass() -> Option<OsString> {
+    std::env::var_os("SSH_ASKPASS").filter(|askpass| !askpass.is_empty())
+}
+
+/// Ask the user for a confirmation with the program, usually a graphical dialog like `ssh-askpass`.
+///
+/// The program gets the prompt as its argument and confirms by exiting successfully, like for `ssh-agent`.
+///
+/// # Errors
+///
+/// Returns `Err` if the program can't be started.
+#[cfg_attr(coverage, no_coverage)]
+pub fn confirm_with(program: &OsStr, prompt: &str) -> Result<bool, PWDuckCliError> {
+    Ok(std::process::Command::new(program)
+        .arg(prompt)
+        .env("SSH_ASKPASS_PROMPT", "confirm")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()?
+        .success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_line, read_secret};
+
+    #[test]
+    fn read_line_from_input() {
+        let mut input = &b"secret password\nsecond line\n"[..];
+        assert_eq!(read_line(&mut input).unwrap(), "secret password");
+        assert_eq!(
+            read_secret("Password: ", true, &mut input).unwrap(),
+            "second line"
+        );
+
+        let mut input = &b"windows\r\n"[..];
+        assert_eq!(read_line(&mut input).unwrap(), "windows");
+
+        let mut input = &b""[..];
+        assert_eq!(read_line(&mut input).unwrap(), "");
+    }
+}
@@ -0,0 +1,219 @@
+//! The machine-readable output of the command line client enabled by `--json`.
+//!
+//! Every command prints exactly one JSON object on a single line to the standard output.
+//! Groups and entries are always described by their UUID, their title and their path.
+//! Fields may be added in the future, but existing fields are neither renamed nor removed.
+//!
+//! | Command    | Output                                                                  |
+//! |------------|-------------------------------------------------------------------------|
+//! | `ls`       | [`Listing`](Listing): `{"group": Group, "groups": [Group], "entries": [Entry]}` |
+//! | `tree`     | [`TreeGroup`](TreeGroup): `Group` with `"groups": [TreeGroup], "entries": [Entry]` |
+//! | `show`     | [`EntryDetails`](EntryDetails): `Entry` with `"username"` and `"email"`  |
+//! | `show -f`  | [`FieldValue`](FieldValue): `{"field": "password", "value": "..."}`      |
+//! | `search`   | [`SearchResults`](SearchResults): `{"groups": [Group], "entries": [Entry]}` |
+//! | `generate` | [`GeneratedPassword`](GeneratedPassword): `{"password": "...", "length": 32, "entropy": 1.0}` |
+//! | `add`      | `Entry` of the new entry                                                |
+//! | `mkdir`    | `Group` of the new group                                                |
+//! | `credential get` | [`Credential`](Credential): `{"username": "...", "password": "..."}` |
+//! | `match`    | [`Matches`](Matches): `{"entries": [EntryDetails]}`                      |
+//! | `ssh-key`  | [`SshPublicKey`](SshPublicKey): `{"public_key": "ssh-ed25519 ...", "fingerprint": "SHA256:..."}` |
+//!
+//! With `Group` being [`GroupItem`](GroupItem): `{"uuid": "...", "title": "Work", "path": "/Email/Work"}`
+//! and `Entry` being [`EntryItem`](EntryItem):
+//! `{"uuid": "...", "title": "Mail", "path": "/Email/Work/Mail", "group": "<uuid>", "web_address": "..."}`.
+//!
+//! Errors are printed to the standard error as [`ErrorOutput`](ErrorOutput):
+//! `{"error": {"kind": "wrong_password", "code": 10, "message": "..."}}`.
+//! The process exits with the same code. See: [`ErrorKind`](crate::error::ErrorKind).
+use std::io::Write;
+
+use pwduck_core::{EntryHead, Group, Vault};
+use serde::Serialize;
+
+use crate::{
+    error::PWDuckCliError,
+    path::{entry_path, group_path},
+};
+
+/// A group of the vault.
+#[derive(Debug, Serialize)]
+pub struct GroupItem {
+    /// The UUID of the group.
+    pub uuid: String,
+    /// The title of the group.
+    pub title: String,
+    /// The path of the group.
+    pub path: String,
+}
+
+impl GroupItem {
+    /// Describe the [`Group`](Group) of the [`Vault`](Vault).
+    #[must_use]
+    pub fn new(vault: &Vault, group: &Group) -> Self {
+        Self {
+            uuid: group.uuid().to_string(),
+            title: group.title().clone(),
+            path: group_path(vault, group.uuid()),
+        }
+    }
+}
+
+/// An entry of the vault without its secret fields.
+#[derive(Debug, Serialize)]
+pub struct EntryItem {
+    /// The UUID of the entry.
+    pub uuid: String,
+    /// The title of the entry.
+    pub title: String,
+    /// The path of the entry.
+    pub path: String,
+    /// The UUID of the group containing the entry.
+    pub group: String,
+    /// The web address of the entry.
+    pub web_address: String,
+}
+
+impl EntryItem {
+    /// Describe the [`EntryHead`](EntryHead) of the [`Vault`](Vault).
+    #[must_use]
+    pub fn new(vault: &Vault, entry: &EntryHead) -> Self {
+        Self {
+            uuid: entry.uuid().to_string(),
+            title: entry.title().clone(),
+            path: entry_path(vault, entry),
+            group: entry.parent().to_string(),
+            web_address: entry.web_address().clone(),
+        }
+    }
+}
+
+/// The subgroups and entries of a group.
+#[derive(Debug, Serialize)]
+pub struct Listing {
+    /// The listed group.
+    pub group: GroupItem,
+    /// The subgroups of the group.
+    pub groups: Vec<GroupItem>,
+    /// The entries of the group.
+    pub entries: Vec<EntryItem>,
+}
+
+/// A group with all its subgroups and entries.
+#[derive(Debug, Serialize)]
+pub struct TreeGroup {
+    /// The group itself.
+    #[serde(flatten)]
+    pub group: GroupItem,
+    /// The subgroups of the group with their subgroups and entries.
+    pub groups: Vec<TreeGroup>,
+    /// The entries of the group.
+    pub entries: Vec<EntryItem>,
+}
+
+/// The details of an entry without its password.
+#[derive(Debug, Serialize)]
+pub struct EntryDetails {
+    /// The entry itself.
+    #[serde(flatten)]
+    pub entry: EntryItem,
+    /// The username of the entry.
+    pub username: String,
+    /// The email of the entry.
+    pub email: String,
+}
+
+/// The value of a single field of an entry.
+#[derive(Debug, Serialize)]
+pub struct FieldValue {
+    /// The name of the field.
+    pub field: &'static str,
+    /// The resolved value of the field.
+    pub value: String,
+}
+
+/// The groups and entries found by a search.
+#[derive(Debug, Serialize)]
+pub struct SearchResults {
+    /// The found groups.
+    pub groups: Vec<GroupItem>,
+    /// The found entries.
+    pub entries: Vec<EntryItem>,
+}
+
+/// A generated password.
+#[derive(Debug, Serialize)]
+pub struct GeneratedPassword {
+    /// The password.
+    pub password: String,
+    /// The number of characters of the password.
+    pub length: usize,
+    /// The estimated entropy of the password in bits.
+    pub entropy: f64,
+}
+
+/// The entries whose web addresses match a URL, the most specific first.
+#[derive(Debug, Serialize)]
+pub struct Matches {
+    /// The matching entries.
+    pub entries: Vec<EntryDetails>,
+}
+
+/// The credential found for a request of git.
+#[derive(Debug, Serialize)]
+pub struct Credential {
+    /// The username of the credential.
+    pub username: String,
+    /// The password of the credential.
+    pub password: String,
+}
+
+/// The public key of the SSH key of an entry.
+#[derive(Debug, Serialize)]
+pub struct SshPublicKey {
+    /// The public key in the format of `authorized_keys`.
+    pub public_key: String,
+    /// The SHA-256 fingerprint of the key.
+    pub fingerprint: String,
+}
+
+/// An error that aborted the command.
+#[derive(Debug, Serialize)]
+pub struct ErrorOutput {
+    /// The error.
+    pub error: ErrorDetails,
+}
+
+/// The details of an error.
+#[derive(Debug, Serialize)]
+pub struct ErrorDetails {
+    /// The name of the [`ErrorKind`](crate::error::ErrorKind).
+    pub kind: &'static str,
+    /// The exit code of the process.
+    pub code: i32,
+    /// The human readable message.
+    pub message: String,
+}
+
+impl From<&PWDuckCliError> for ErrorOutput {
+    fn from(error: &PWDuckCliError) -> Self {
+        let kind = error.kind();
+        Self {
+            error: ErrorDetails {
+                kind: kind.name(),
+                code: kind.exit_code(),
+                message: error.to_string(),
+            },
+        }
+    }
+}
+
+/// Print the value as a single line of JSON.
+///
+/// # Errors
+///
+/// Returns `Err` if the output can't be written.
+pub fn write<T: Serialize>(out: &mut dyn Write, value: &T) -> Result<(), PWDuckCliError> {
+    serde_json::to_writer(&mut *out, value).map_err(std::io::Error::from)?;
+    writeln!(out)?;
+    Ok(())
+}
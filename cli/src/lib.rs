@@ -0,0 +1,95 @@
+//! A command line client of the password manager.
+//!
+//! It opens the vaults with the [core](pwduck_core) on machines without a graphical user interface,
+//! e.g. servers or scripts. Secrets are only printed if they are explicitly requested and they are
+//! only read from the standard input or from the terminal, never from the arguments.
+//!
+//! The library is shared by the binaries `pwduck-cli`, `pwduck-agent`, `git-credential-pwduck`,
+//! `pwduck-secret-service`, `pwduck-ssh-agent`, `pwduck-native-host` and `pwduck-api`.
+//! The agent unlocks a vault once and serves the commands of the client over a Unix domain socket.
+//! The credential helper answers the requests of git with the entries of a vault.
+//! The Secret Service provider stores the secrets of desktop applications in a group of a vault.
+//! The SSH agent offers the SSH keys of the entries of a vault to the SSH client.
+//! The native messaging host connects browser extensions to the vault served by the agent.
+//! The API serves a vault to paired programs over JSON-RPC with scoped permissions and an audit log.
+#![cfg_attr(coverage, feature(no_coverage))]
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+#![deny(unused_results)]
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::pedantic,
+    clippy::nursery,
+
+    // Restriction lints
+    clippy::clone_on_ref_ptr,
+    clippy::create_dir,
+    clippy::dbg_macro,
+    clippy::decimal_literal_representation,
+    clippy::exit,
+    clippy::float_cmp_const,
+    clippy::get_unwrap,
+    clippy::let_underscore_must_use,
+    clippy::map_err_ignore,
+    clippy::mem_forget,
+    clippy::missing_docs_in_private_items,
+    clippy::multiple_inherent_impl,
+    clippy::panic,
+    clippy::panic_in_result_fn,
+    clippy::print_stderr,
+    clippy::print_stdout,
+    clippy::rest_pat_in_fully_bound_structs,
+    clippy::str_to_string,
+    clippy::string_to_string,
+    clippy::todo,
+    clippy::unimplemented,
+    clippy::unneeded_field_pattern,
+    clippy::unwrap_in_result,
+    clippy::unwrap_used,
+    clippy::use_debug,
+)]
+#![allow(
+    clippy::suboptimal_flops,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_wrap,
+    clippy::module_name_repetitions
+)]
+
+#[cfg(unix)]
+pub mod agent;
+
+#[cfg(unix)]
+pub mod api;
+
+pub mod args;
+
+pub mod commands;
+
+#[cfg(unix)]
+pub mod dbus;
+
+pub mod dispatch;
+
+pub mod error;
+
+pub mod exec;
+
+pub mod git;
+
+pub mod input;
+
+pub mod json;
+
+#[cfg(unix)]
+pub mod native_host;
+
+pub mod path;
+
+#[cfg(unix)]
+pub mod secret_service;
+
+#[cfg(unix)]
+pub mod ssh_agent;
+
+pub mod unlock;
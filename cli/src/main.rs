@@ -0,0 +1,148 @@
+//! A command line client of the password manager.
+//!
+//! It runs the commands of the [library](pwduck_cli) on the vault. If a `pwduck-agent` serves the
+//! vault, the commands are sent to the agent, otherwise the vault is unlocked by the client itself.
+#![cfg_attr(coverage, feature(no_coverage))]
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+#![deny(unused_results)]
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::pedantic,
+    clippy::nursery,
+
+    // Restriction lints
+    clippy::clone_on_ref_ptr,
+    clippy::create_dir,
+    clippy::dbg_macro,
+    clippy::decimal_literal_representation,
+    clippy::exit,
+    clippy::float_cmp_const,
+    clippy::get_unwrap,
+    clippy::let_underscore_must_use,
+    clippy::map_err_ignore,
+    clippy::mem_forget,
+    clippy::missing_docs_in_private_items,
+    clippy::multiple_inherent_impl,
+    clippy::panic,
+    clippy::panic_in_result_fn,
+    clippy::print_stderr,
+    clippy::print_stdout,
+    clippy::rest_pat_in_fully_bound_structs,
+    clippy::str_to_string,
+    clippy::string_to_string,
+    clippy::todo,
+    clippy::unimplemented,
+    clippy::unneeded_field_pattern,
+    clippy::unwrap_in_result,
+    clippy::unwrap_used,
+    clippy::use_debug,
+)]
+#![allow(
+    clippy::suboptimal_flops,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_wrap,
+    clippy::module_name_repetitions
+)]
+
+use std::io::{BufRead, Write};
+
+use pwduck_cli::{
+    args::{Args, Command, RunArgs},
+    commands,
+    dispatch::dispatch,
+    error::PWDuckCliError,
+    exec, unlock,
+};
+use pwduck_core::MemKey;
+use structopt::StructOpt;
+
+#[cfg_attr(coverage, no_coverage)]
+fn main() {
+    let args = Args::from_args();
+    let code = run(&args).unwrap_or_else(|error| {
+        drop(error.report(args.json, &mut std::io::stderr()));
+        error.kind().exit_code()
+    });
+    if code != 0 {
+        #[allow(clippy::exit)]
+        std::process::exit(code);
+    }
+}
+
+/// Run the command given by the arguments and return the exit code.
+#[cfg_attr(coverage, no_coverage)]
+fn run(args: &Args) -> Result<i32, PWDuckCliError> {
+    pwduck_core::try_to_prevent_core_dump()?;
+
+    let mut input = std::io::stdin().lock();
+    let mut out = std::io::stdout().lock();
+
+    if let Command::Generate(options) = &args.command {
+        commands::generate::run(options, args.json, &mut out)?;
+        return Ok(0);
+    }
+    if let Command::Run(options) = &args.command {
+        return run_program(args, options, &mut input);
+    }
+
+    dispatch(
+        &args.unlock,
+        args.no_agent,
+        &args.command,
+        None,
+        args.json,
+        &mut input,
+        &mut out,
+    )
+}
+
+/// Resolve the bindings of the environment variables and run the program.
+/// The vault is locked again before the program starts.
+#[cfg_attr(coverage, no_coverage)]
+fn run_program(
+    args: &Args,
+    options: &RunArgs,
+    input: &mut dyn BufRead,
+) -> Result<i32, PWDuckCliError> {
+    let bindings = exec::bindings(options)?;
+
+    #[cfg(unix)]
+    if !args.no_agent {
+        let mut stream = pwduck_cli::agent::client::connect(&args.unlock.vault);
+        if stream.is_some() {
+            let mut variables = Vec::new();
+            for binding in &bindings {
+                let stream = stream
+                    .take()
+                    .or_else(|| pwduck_cli::agent::client::connect(&args.unlock.vault))
+                    .ok_or_else(|| {
+                        PWDuckCliError::NotFound("The agent serving the vault".into())
+                    })?;
+                let request = pwduck_cli::agent::Request {
+                    command: binding.show(),
+                    secrets: Vec::new(),
+                    json: args.json,
+                };
+                let response = pwduck_cli::agent::client::send(&stream, &request)?;
+                if response.code != 0 {
+                    std::io::stderr().write_all(response.stderr.as_bytes())?;
+                    return Ok(response.code);
+                }
+                variables.push((
+                    binding.variable.clone(),
+                    exec::printed_value(&response.stdout, args.json)?,
+                ));
+            }
+            return exec::spawn(&options.program, &variables);
+        }
+    }
+
+    let mem_key = MemKey::new();
+    let vault = unlock::unlock(&args.unlock, &mem_key, input)?;
+    let variables = exec::resolve(&vault, &mem_key, &bindings)?;
+    drop(vault);
+    drop(mem_key);
+    exec::spawn(&options.program, &variables)
+}
@@ -0,0 +1,759 @@
+//! A native messaging host connecting browser extensions to the vault served by the [agent](crate::agent).
+//!
+//! The browser starts the host for an extension and exchanges messages over its standard input and output.
+//! Each message is a JSON object prefixed by its length as a 32-bit unsigned integer in native byte order.
+//! The host never unlocks the vault on its own, it sends the requests to the `pwduck-agent` serving the vault.
+//!
+//! Requests are selected by their `action`:
+//!
+//! | Action         | Request                                  | Response                                     |
+//! |----------------|------------------------------------------|----------------------------------------------|
+//! | `pair`         | `{"action": "pair", "name": "Firefox"}`  | `{"id": "...", "key": "<base64>"}`           |
+//! | `hello`        | `{"action": "hello", "id": "..."}`       | `{"challenge": "<base64>"}`                  |
+//! | `authenticate` | `{"action": "authenticate", "proof": "<base64>"}` | `{"authenticated": true}`           |
+//! | `match`        | `{"action": "match", "url": "https://..."}` | [`Matches`](crate::json::Matches)         |
+//! | `fetch`        | `{"action": "fetch", "uuid": "..."}`     | [`Credential`](crate::json::Credential)      |
+//!
+//! Pairing asks the user for a confirmation and returns the key of the extension, which is stored
+//! in the configuration of the host. An extension starts each connection with `hello` and
+//! proves its key by answering the challenge with its HMAC-SHA-256 under the key.
+//! Only authenticated extensions may search for entries or fetch their credentials,
+//! and fetching a credential asks the user for a confirmation again.
+//! The confirmations are shown by the GUI, if it has the vault open, see: [`request_confirmation`](pwduck_core::request_confirmation).
+//! Otherwise they are shown by the program chosen with `--askpass`, see: [`confirm_with`](confirm_with).
+//! Browsers usually don't pass on `SSH_ASKPASS`, which is used otherwise.
+//! Without the GUI or such a program the requests needing a confirmation are refused.
+//!
+//! Errors are answered like the errors of `--json`: `{"error": {"kind": "refused", "code": 4, "message": "..."}}`.
+//!
+//! The manifest installed for the browser, e.g. `~/.mozilla/native-messaging-hosts/pwduck.json`, looks like:
+//! ```json
+//! {
+//!   "name": "pwduck",
+//!   "description": "PWDuck",
+//!   "path": "/usr/bin/pwduck-native-host",
+//!   "type": "stdio",
+//!   "allowed_extensions": ["pwduck@example.com"]
+//! }
+//! ```
+use std::{
+    ffi::{OsStr, OsString},
+    fs::{self, DirBuilder, OpenOptions},
+    io::{Read, Write},
+    os::unix::{
+        fs::{DirBuilderExt, OpenOptionsExt},
+        net::UnixStream,
+    },
+    path::{Path, PathBuf},
+};
+
+use hmac::{Hmac, Mac, NewMac};
+use pwduck_core::{
+    request_confirmation, PWDuckCoreError, ReferenceField, SecString, CONFIRMATION_SOCKET,
+};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::{
+    agent::{self, client, Request},
+    args::Command,
+    error::PWDuckCliError,
+    input::{askpass, confirm_with},
+    json::ErrorOutput,
+};
+
+/// The permissions of the directory containing the configuration.
+const DIRECTORY_MODE: u32 = 0o700;
+
+/// The permissions of the configuration, it contains the keys of the extensions.
+const CONFIG_MODE: u32 = 0o600;
+
+/// The maximum length of a message, as sent by browsers to native messaging hosts.
+const MAX_MESSAGE_LENGTH: usize = 1024 * 1024;
+
+/// The number of random bytes of keys and challenges.
+const KEY_LENGTH: usize = 32;
+
+/// The configuration of the host.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// The vault served to the extensions.
+    pub vault: PathBuf,
+    /// The paired extensions.
+    #[serde(default)]
+    pub clients: Vec<Client>,
+    /// The program asking the user for confirmations, defaults to the program in `SSH_ASKPASS`.
+    #[serde(default)]
+    pub askpass: Option<PathBuf>,
+}
+
+/// An extension paired with the host.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Client {
+    /// The random identifier of the extension.
+    pub id: String,
+    /// The name given by the extension, shown in confirmations.
+    pub name: String,
+    /// The key of the extension encoded as Base64.
+    pub key: SecString,
+}
+
+impl Config {
+    /// Load the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the configuration does not exist or can't be read.
+    pub fn load(path: &Path) -> Result<Self, PWDuckCliError> {
+        let content = fs::read_to_string(path).map_err(|error| {
+            if error.kind() == std::io::ErrorKind::NotFound {
+                PWDuckCliError::NotFound(format!(
+                    "The configuration {}, choose the vault with pwduck-native-host --configure",
+                    path.display()
+                ))
+            } else {
+                error.into()
+            }
+        })?;
+        Ok(serde_json::from_str(&content).map_err(std::io::Error::from)?)
+    }
+
+    /// Save the configuration, only accessible by the current user.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the configuration can't be written.
+    pub fn save(&self, path: &Path) -> Result<(), PWDuckCliError> {
+        if let Some(directory) = path.parent() {
+            DirBuilder::new()
+                .recursive(true)
+                .mode(DIRECTORY_MODE)
+                .create(directory)?;
+        }
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(CONFIG_MODE)
+            .open(path)?;
+        serde_json::to_writer_pretty(&mut file, self).map_err(std::io::Error::from)?;
+        writeln!(file)?;
+        Ok(())
+    }
+}
+
+/// The default location of the configuration in the configuration directory of the user.
+///
+/// # Errors
+///
+/// Returns `Err` if the user has no configuration directory.
+pub fn config_path() -> Result<PathBuf, PWDuckCliError> {
+    dirs::config_dir()
+        .map(|directory| directory.join("pwduck").join("native-host.json"))
+        .ok_or_else(|| PWDuckCliError::NotFound("The configuration directory".into()))
+}
+
+/// Choose the vault served to the extensions and the program asking for confirmations.
+/// The paired extensions are kept, unless the vault changes. The program is kept, unless a new one is given.
+///
+/// # Errors
+///
+/// Returns `Err` if the vault does not exist or the configuration can't be written.
+pub fn configure(path: &Path, vault: &Path, askpass: Option<&Path>) -> Result<(), PWDuckCliError> {
+    let vault = fs::canonicalize(vault)?;
+    let previous = Config::load(path).ok();
+    let askpass = askpass
+        .map(Path::to_path_buf)
+        .or_else(|| previous.as_ref().and_then(|config| config.askpass.clone()));
+    let clients = match previous {
+        Some(config) if config.vault == vault => config.clients,
+        _ => Vec::new(),
+    };
+    Config {
+        vault,
+        clients,
+        askpass,
+    }
+    .save(path)
+}
+
+/// A request of an extension.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum Action {
+    /// Pair a new extension.
+    Pair {
+        /// The name of the extension.
+        name: String,
+    },
+    /// Start the authentication of a paired extension.
+    Hello {
+        /// The identifier of the extension.
+        id: String,
+    },
+    /// Answer the challenge of [`Hello`](Action::Hello).
+    Authenticate {
+        /// The HMAC-SHA-256 of the challenge encoded as Base64.
+        proof: String,
+    },
+    /// Find the entries matching the URL.
+    Match {
+        /// The URL of the page.
+        url: String,
+    },
+    /// Fetch the username and the password of an entry.
+    Fetch {
+        /// The UUID of the entry.
+        uuid: String,
+    },
+}
+
+/// The native messaging host serving a single extension.
+#[derive(Debug)]
+pub struct NativeHost {
+    /// The location of the configuration.
+    config_path: PathBuf,
+    /// The configuration.
+    config: Config,
+    /// The socket of the agent serving the vault.
+    socket: PathBuf,
+    /// The socket of the GUI showing the vault.
+    gui_socket: PathBuf,
+    /// The identifier of the extension and the challenge it has to answer.
+    challenge: Option<(String, Vec<u8>)>,
+    /// The identifier of the authenticated extension.
+    client: Option<String>,
+    /// The program asking the user for confirmations.
+    askpass: Option<OsString>,
+    /// Ask the user with the program whether the extension may proceed.
+    confirmation: fn(&OsStr, &str) -> Result<bool, PWDuckCliError>,
+}
+
+impl NativeHost {
+    /// Create a new [`NativeHost`](NativeHost) with the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the configuration can't be loaded or the vault does not exist.
+    pub fn new(config_path: PathBuf) -> Result<Self, PWDuckCliError> {
+        let config = Config::load(&config_path)?;
+        let socket = agent::socket_path(&config.vault)?;
+        let gui_socket = agent::user_socket(&config.vault, CONFIRMATION_SOCKET)?;
+        let askpass = config
+            .askpass
+            .clone()
+            .map(PathBuf::into_os_string)
+            .or_else(askpass);
+        Ok(Self {
+            config_path,
+            config,
+            socket,
+            gui_socket,
+            challenge: None,
+            client: None,
+            askpass,
+            confirmation: confirm_with,
+        })
+    }
+
+    /// Answer the messages of the extension until the browser closes the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if a message can't be read or written.
+    pub fn serve(
+        &mut self,
+        input: &mut dyn Read,
+        out: &mut dyn Write,
+    ) -> Result<(), PWDuckCliError> {
+        while let Some(message) = read_message(input)? {
+            let response = self.handle(&message);
+            write_message(out, &response)?;
+        }
+        Ok(())
+    }
+
+    /// Answer the message, given without its length.
+    pub fn handle(&mut self, message: &[u8]) -> Value {
+        let result = serde_json::from_slice(message)
+            .map_err(|error| {
+                failure(&PWDuckCliError::Refused(format!(
+                    "The request is invalid ({})",
+                    error
+                )))
+            })
+            .and_then(|action| self.answer(action));
+        result.unwrap_or_else(|error| error)
+    }
+
+    /// Answer the request. Errors are already formatted as JSON.
+    fn answer(&mut self, action: Action) -> Result<Value, Value> {
+        match action {
+            Action::Pair { name } => self.pair(name).map_err(|error| failure(&error)),
+            Action::Hello { id } => self.hello(id).map_err(|error| failure(&error)),
+            Action::Authenticate { proof } => {
+                self.authenticate(&proof).map_err(|error| failure(&error))
+            }
+            Action::Match { url } => {
+                let _ = self.authenticated()?;
+                self.send(Command::Match { url })
+            }
+            Action::Fetch { uuid } => self.fetch(uuid),
+        }
+    }
+
+    /// Pair the extension after the user confirmed it and authenticate it.
+    fn pair(&mut self, name: String) -> Result<Value, PWDuckCliError> {
+        if name.trim().is_empty() {
+            return Err(PWDuckCliError::Refused("The extension has no name".into()));
+        }
+        if !self.confirm(&format!(
+            "Pair the browser extension {} with {}?",
+            name,
+            self.config.vault.display()
+        ))? {
+            return Err(PWDuckCliError::Refused(
+                "The user refused to pair the extension".into(),
+            ));
+        }
+
+        let mut id = [0_u8; 16];
+        OsRng.fill_bytes(&mut id);
+        let id = id
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        let mut key = [0_u8; KEY_LENGTH];
+        OsRng.fill_bytes(&mut key);
+        let key = SecString::from(base64::encode(key));
+
+        self.config.clients.push(Client {
+            id: id.clone(),
+            name,
+            key: key.clone(),
+        });
+        self.config.save(&self.config_path)?;
+        self.client = Some(id.clone());
+        Ok(json!({ "id": id, "key": key.as_str() }))
+    }
+
+    /// Send a new challenge to the paired extension.
+    fn hello(&mut self, id: String) -> Result<Value, PWDuckCliError> {
+        if !self.config.clients.iter().any(|client| client.id == id) {
+            return Err(PWDuckCliError::NotFound(format!(
+                "The paired extension {}",
+                id
+            )));
+        }
+        let mut challenge = vec![0_u8; KEY_LENGTH];
+        OsRng.fill_bytes(&mut challenge);
+        let encoded = base64::encode(&challenge);
+        self.client = None;
+        self.challenge = Some((id, challenge));
+        Ok(json!({ "challenge": encoded }))
+    }
+
+    /// Authenticate the extension by the answer to its challenge. Each challenge is answered only once.
+    fn authenticate(&mut self, proof: &str) -> Result<Value, PWDuckCliError> {
+        let (id, challenge) = self.challenge.take().ok_or_else(|| {
+            PWDuckCliError::Refused("The extension has to say hello first".into())
+        })?;
+        let refused = || PWDuckCliError::Refused("The proof of the extension is wrong".into());
+        let client = self
+            .config
+            .clients
+            .iter()
+            .find(|client| client.id == id)
+            .ok_or_else(refused)?;
+        let key = base64::decode(client.key.as_str()).map_err(|_err| refused())?;
+        let proof = base64::decode(proof).map_err(|_err| refused())?;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&key).map_err(|_err| refused())?;
+        mac.update(&challenge);
+        mac.verify(&proof).map_err(|_err| refused())?;
+
+        self.client = Some(id);
+        Ok(json!({ "authenticated": true }))
+    }
+
+    /// Fetch the username and the password of the entry after the user confirmed it.
+    fn fetch(&self, uuid: String) -> Result<Value, Value> {
+        let client = self.authenticated()?;
+        let entry = self.send(Command::Show {
+            entry: uuid.clone(),
+            field: None,
+        })?;
+        let path = entry["path"].as_str().unwrap_or(&uuid);
+        if !self
+            .confirm(&format!(
+                "Allow the browser extension {} to fill in the login of {}?",
+                client.name, path
+            ))
+            .map_err(|error| failure(&error))?
+        {
+            return Err(failure(&PWDuckCliError::Refused(
+                "The user refused to fill in the login".into(),
+            )));
+        }
+
+        let password = self.send(Command::Show {
+            entry: uuid,
+            field: Some(ReferenceField::Password),
+        })?;
+        Ok(json!({
+            "username": entry["username"],
+            "password": password["value"],
+        }))
+    }
+
+    /// Ask the user for a confirmation in the GUI showing the vault.
+    /// Without such a GUI the program asking for confirmations is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if there is neither a GUI nor such a program or the confirmation failed.
+    fn confirm(&self, prompt: &str) -> Result<bool, PWDuckCliError> {
+        match request_confirmation(&self.gui_socket, prompt) {
+            Err(PWDuckCoreError::IO(error))
+                if matches!(
+                    error.kind(),
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+                ) => {}
+            result => return Ok(result?),
+        }
+        let askpass = self.askpass.as_ref().ok_or_else(|| {
+            PWDuckCliError::Refused(
+                "No GUI shows the vault and no confirmation program is configured, open the vault in PWDuck or choose a program with pwduck-native-host --configure <vault> --askpass <program>".into(),
+            )
+        })?;
+        (self.confirmation)(askpass, prompt)
+    }
+
+    /// The authenticated extension.
+    fn authenticated(&self) -> Result<&Client, Value> {
+        self.client
+            .as_ref()
+            .and_then(|id| self.config.clients.iter().find(|client| &client.id == id))
+            .ok_or_else(|| {
+                failure(&PWDuckCliError::Refused(
+                    "The extension is not authenticated".into(),
+                ))
+            })
+    }
+
+    /// Run the read-only command on the vault served by the agent and return its JSON output.
+    fn send(&self, command: Command) -> Result<Value, Value> {
+        let stream = UnixStream::connect(&self.socket).map_err(|_err| {
+            failure(&PWDuckCliError::NotFound(format!(
+                "The agent serving {}, unlock the vault with pwduck-agent",
+                self.config.vault.display()
+            )))
+        })?;
+        let response = client::send(
+            &stream,
+            &Request {
+                command,
+                secrets: Vec::new(),
+                json: true,
+            },
+        )
+        .map_err(|error| failure(&error))?;
+
+        let invalid =
+            |error: serde_json::Error| failure(&PWDuckCliError::IO(std::io::Error::from(error)));
+        if response.code == 0 {
+            serde_json::from_str(response.stdout.as_str()).map_err(invalid)
+        } else {
+            Err(serde_json::from_str(&response.stderr).map_err(invalid)?)
+        }
+    }
+}
+
+/// The error formatted like the errors of `--json`.
+fn failure(error: &PWDuckCliError) -> Value {
+    serde_json::to_value(ErrorOutput::from(error)).unwrap_or(Value::Null)
+}
+
+/// Read the next message without its length. Returns `None`, if the input is closed.
+///
+/// # Errors
+///
+/// Returns `Err` if the input can't be read or the message is too long.
+pub fn read_message(input: &mut dyn Read) -> Result<Option<Vec<u8>>, PWDuckCliError> {
+    let mut length = [0_u8; 4];
+    match input.read_exact(&mut length) {
+        Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        result => result?,
+    }
+    let length = u32::from_ne_bytes(length) as usize;
+    if length > MAX_MESSAGE_LENGTH {
+        return Err(PWDuckCliError::Refused(format!(
+            "The message is longer than {} bytes",
+            MAX_MESSAGE_LENGTH
+        )));
+    }
+    let mut message = vec![0_u8; length];
+    input.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+/// Write the message prefixed by its length.
+///
+/// # Errors
+///
+/// Returns `Err` if the output can't be written.
+pub fn write_message(out: &mut dyn Write, message: &Value) -> Result<(), PWDuckCliError> {
+    let message = serde_json::to_vec(message).map_err(std::io::Error::from)?;
+    out.write_all(&(message.len() as u32).to_ne_bytes())?;
+    out.write_all(&message)?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        os::unix::{
+            fs::PermissionsExt,
+            net::{UnixListener, UnixStream},
+        },
+        path::Path,
+        time::Duration,
+    };
+
+    use hmac::{Hmac, Mac, NewMac};
+    use serde_json::{json, Value};
+    use sha2::Sha256;
+    use tempfile::tempdir;
+
+    use crate::{
+        agent::{self, client, Agent, Request},
+        args::Command,
+        commands::tests::example_vault,
+    };
+
+    use super::{configure, read_message, write_message, Config, NativeHost};
+
+    fn call(host: &mut NativeHost, request: &Value) -> Value {
+        host.handle(&serde_json::to_vec(request).unwrap())
+    }
+
+    fn error_kind(response: &Value) -> &str {
+        response["error"]["kind"].as_str().unwrap()
+    }
+
+    fn host(config: &Path, socket: &Path) -> NativeHost {
+        let mut host = NativeHost::new(config.into()).unwrap();
+        host.socket = socket.into();
+        host.gui_socket = socket.with_extension("gui");
+        host
+    }
+
+    fn authenticate(host: &mut NativeHost, id: &Value, key: &Value) -> Value {
+        let challenge = call(host, &json!({"action": "hello", "id": id}));
+        let challenge = base64::decode(challenge["challenge"].as_str().unwrap()).unwrap();
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&base64::decode(key.as_str().unwrap()).unwrap())
+                .unwrap();
+        mac.update(&challenge);
+        let proof = base64::encode(mac.finalize().into_bytes());
+        call(host, &json!({"action": "authenticate", "proof": proof}))
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn fake_extension(config: &Path, socket: &Path) {
+        let mut host = host(config, socket);
+        let search = json!({"action": "match", "url": "https://mail.example.com/inbox"});
+        assert_eq!(error_kind(&call(&mut host, &search)), "refused");
+        assert_eq!(error_kind(&host.handle(b"{")), "refused");
+        assert_eq!(
+            error_kind(&call(&mut host, &json!({"action": "delete"}))),
+            "refused"
+        );
+
+        // Pairing needs the confirmation of the user, shown by the configured program.
+        assert_eq!(host.askpass, Some("/usr/bin/ssh-askpass".into()));
+        let askpass = host.askpass.take();
+        host.confirmation = |_program, _prompt| Ok(true);
+        let pair = json!({"action": "pair", "name": "Firefox"});
+        let missing = call(&mut host, &pair);
+        assert_eq!(error_kind(&missing), "refused");
+        assert!(missing["error"]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("No GUI shows the vault and no confirmation program is configured"));
+        host.askpass = askpass;
+        host.confirmation = |_program, _prompt| Ok(false);
+        assert_eq!(error_kind(&call(&mut host, &pair)), "refused");
+        host.confirmation = |program, prompt| {
+            Ok(program == "/usr/bin/ssh-askpass"
+                && prompt.starts_with("Pair the browser extension Firefox"))
+        };
+        let paired = call(&mut host, &pair);
+        let (id, key) = (&paired["id"], &paired["key"]);
+        let saved = Config::load(config).unwrap();
+        assert_eq!(saved.clients.len(), 1);
+        assert_eq!(saved.clients[0].name, "Firefox");
+        assert_eq!(
+            std::fs::metadata(config).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        let found = call(&mut host, &search);
+        assert_eq!(found["entries"][0]["path"], "/Email/Work/Mail");
+        assert_eq!(found["entries"][0]["username"], "alice@work");
+        let mail = found["entries"][0]["uuid"].clone();
+
+        // A new connection has to authenticate with the key.
+        let mut host = self::host(config, socket);
+        assert_eq!(error_kind(&call(&mut host, &search)), "refused");
+        assert_eq!(
+            error_kind(&call(
+                &mut host,
+                &json!({"action": "hello", "id": "unknown"})
+            )),
+            "not_found"
+        );
+        let wrong = json!({"action": "authenticate", "proof": base64::encode([0_u8; 32])});
+        assert_eq!(error_kind(&call(&mut host, &wrong)), "refused");
+        let _ = call(&mut host, &json!({"action": "hello", "id": id}));
+        assert_eq!(error_kind(&call(&mut host, &wrong)), "refused");
+        let other_key = json!(base64::encode([1_u8; 32]));
+        assert_eq!(
+            error_kind(&authenticate(&mut host, id, &other_key)),
+            "refused"
+        );
+        assert_eq!(
+            authenticate(&mut host, id, key),
+            json!({"authenticated": true})
+        );
+        assert_eq!(call(&mut host, &search)["entries"][0]["uuid"], mail);
+
+        // Fetching a login needs the confirmation of the user.
+        host.confirmation =
+            |_program, prompt| Ok(prompt.ends_with("the login of /Email/Work/Mail?"));
+        assert_eq!(
+            call(&mut host, &json!({"action": "fetch", "uuid": mail})),
+            json!({"username": "alice@work", "password": "mail secret"})
+        );
+        let bank = call(
+            &mut host,
+            &json!({"action": "match", "url": "https://bank.example.com"}),
+        )["entries"][0]["uuid"]
+            .clone();
+        assert_eq!(
+            error_kind(&call(&mut host, &json!({"action": "fetch", "uuid": bank}))),
+            "refused"
+        );
+        assert_eq!(
+            error_kind(&call(
+                &mut host,
+                &json!({"action": "fetch", "uuid": "/Missing"})
+            )),
+            "not_found"
+        );
+
+        // The GUI showing the vault asks the user instead of the program.
+        host.confirmation = |_program, _prompt| Ok(false);
+        let gui = UnixListener::bind(&host.gui_socket).unwrap();
+        let fake_gui = std::thread::spawn(move || {
+            let prompts: Vec<Value> = (0..2)
+                .map(|index| {
+                    let (mut stream, _address) = gui.accept().unwrap();
+                    let mut line = String::new();
+                    let _ = BufReader::new(&stream).read_line(&mut line).unwrap();
+                    stream
+                        .write_all(format!("{{\"confirmed\": {}}}\n", index == 0).as_bytes())
+                        .unwrap();
+                    serde_json::from_str(&line).unwrap()
+                })
+                .collect();
+            prompts
+        });
+        assert_eq!(
+            call(&mut host, &json!({"action": "fetch", "uuid": mail}))["password"],
+            "mail secret"
+        );
+        assert_eq!(
+            error_kind(&call(&mut host, &json!({"action": "fetch", "uuid": mail}))),
+            "refused"
+        );
+        let prompts = fake_gui.join().unwrap();
+        assert_eq!(
+            prompts[0]["prompt"],
+            "Allow the browser extension Firefox to fill in the login of /Email/Work/Mail?"
+        );
+        std::fs::remove_file(&host.gui_socket).unwrap();
+
+        let agent_socket = std::mem::replace(&mut host.socket, socket.with_extension("missing"));
+        assert_eq!(error_kind(&call(&mut host, &search)), "not_found");
+        host.socket = agent_socket;
+
+        // The messages are prefixed by their lengths.
+        let mut input = Vec::new();
+        write_message(&mut input, &search).unwrap();
+        write_message(&mut input, &json!({"action": "hello", "id": id})).unwrap();
+        let mut output = Vec::new();
+        host.serve(&mut input.as_slice(), &mut output).unwrap();
+        let mut output = output.as_slice();
+        let first: Value =
+            serde_json::from_slice(&read_message(&mut output).unwrap().unwrap()).unwrap();
+        assert_eq!(first["entries"][0]["uuid"], mail);
+        assert!(read_message(&mut output).unwrap().is_some());
+        assert!(read_message(&mut output).unwrap().is_none());
+        assert!(read_message(&mut &u32::MAX.to_ne_bytes()[..]).is_err());
+    }
+
+    #[test]
+    fn native_host() {
+        let dir = tempdir().unwrap();
+        let (vault, mem_key) = example_vault(dir.path());
+        let config = dir.path().join("config").join("native-host.json");
+        assert!(NativeHost::new(config.clone()).is_err());
+        configure(
+            &config,
+            vault.path(),
+            Some(Path::new("/usr/bin/ssh-askpass")),
+        )
+        .unwrap();
+        let socket = dir.path().join("agent.sock");
+        let listener = agent::bind(&socket).unwrap();
+
+        let extension = {
+            let config = config.clone();
+            std::thread::spawn(move || {
+                let result = std::panic::catch_unwind(|| fake_extension(&config, &socket));
+                let stream = UnixStream::connect(&socket).unwrap();
+                let _ = client::send(
+                    &stream,
+                    &Request {
+                        command: Command::Lock,
+                        secrets: Vec::new(),
+                        json: false,
+                    },
+                )
+                .unwrap();
+                result
+            })
+        };
+        Agent::new(vault, mem_key, false, Duration::from_secs(30))
+            .serve(&listener)
+            .unwrap();
+        assert!(extension.join().unwrap().is_ok());
+
+        // The pairings are kept for the same vault only, the confirmation program until another one is chosen.
+        configure(&config, &dir.path().join("Vault"), None).unwrap();
+        assert_eq!(Config::load(&config).unwrap().clients.len(), 1);
+        configure(&config, dir.path(), None).unwrap();
+        let loaded = Config::load(&config).unwrap();
+        assert!(loaded.clients.is_empty());
+        assert_eq!(loaded.askpass, Some("/usr/bin/ssh-askpass".into()));
+        configure(&config, dir.path(), Some(Path::new("zenity-askpass"))).unwrap();
+        assert_eq!(
+            Config::load(&config).unwrap().askpass,
+            Some("zenity-askpass".into())
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Address the groups and entries of a vault by their paths or by their UUIDs.
+//!
+//! A path lists the titles of the groups starting at the root group, separated by slashes, e.g. `/Email/Work`.
+//! The root group itself is addressed by `/`.
+use pwduck_core::{EntryHead, Group, Uuid, Vault};
+
+use crate::error::PWDuckCliError;
+
+/// The separator of the titles in a path.
+pub const SEPARATOR: char = '/';
+
+/// The path of the [`Group`](Group) with the given UUID.
+#[must_use]
+pub fn group_path(vault: &Vault, uuid: &Uuid) -> String {
+    let mut titles = Vec::new();
+    let mut current = vault.groups().get(uuid);
+    while let Some(group) = current {
+        // A broken parent chain must not loop forever.
+        if group.is_root() || titles.len() > vault.groups().len() {
+            break;
+        }
+        titles.push(group.title().as_str());
+        current = group
+            .parent()
+            .as_ref()
+            .and_then(|parent| vault.groups().get(parent));
+    }
+    titles.reverse();
+
+    format!("{}{}", SEPARATOR, titles.join(&SEPARATOR.to_string()))
+}
+
+/// The path of the [`EntryHead`](EntryHead).
+#[must_use]
+pub fn entry_path(vault: &Vault, entry: &EntryHead) -> String {
+    join(&group_path(vault, entry.parent()), entry.title())
+}
+
+/// Append the title to the path of a group.
+#[must_use]
+pub fn join(group: &str, title: &str) -> String {
+    if group.ends_with(SEPARATOR) {
+        format!("{}{}", group, title)
+    } else {
+        format!("{}{}{}", group, SEPARATOR, title)
+    }
+}
+
+/// Split the path into the path of its parent group and its title.
+/// A trailing separator is ignored.
+///
+/// # Errors
+///
+/// Returns `Err` if the title is empty, e.g. for the root group.
+pub fn split(path: &str) -> Result<(&str, &str), PWDuckCliError> {
+    let path = path.strip_suffix(SEPARATOR).unwrap_or(path);
+    let (group, title) = path.rsplit_once(SEPARATOR).unwrap_or(("", path));
+    if title.is_empty() {
+        return Err(PWDuckCliError::Refused(format!(
+            "The path {} has no title",
+            path
+        )));
+    }
+    Ok((group, title))
+}
+
+/// Find the [`Group`](Group) by its path or UUID.
+///
+/// It expects:
+///  - The [`Vault`](Vault) containing the [`Group`](Group)
+///  - The path or UUID of the [`Group`](Group)
+///
+/// # Errors
+///
+/// Returns `Err` if the [`Group`](Group) does not exist.
+pub fn find_group<'a>(vault: &'a Vault, address: &str) -> Result<&'a Group, PWDuckCliError> {
+    let not_found = || PWDuckCliError::NotFound(format!("The group {}", address));
+
+    if let Some(group) = parse_uuid(address).and_then(|uuid| vault.groups().get(&uuid)) {
+        return Ok(group);
+    }
+
+    let root = vault
+        .get_root_uuid()
+        .and_then(|root| vault.groups().get(&root))
+        .ok_or_else(not_found)?;
+    address
+        .split(SEPARATOR)
+        .filter(|title| !title.is_empty())
+        .try_fold(root, |group, title| {
+            vault
+                .get_groups_of(group.uuid())
+                .into_iter()
+                .find(|child| child.title() == title)
+                .ok_or_else(not_found)
+        })
+}
+
+/// Find the [`EntryHead`](EntryHead) by its path or UUID.
+///
+/// It expects:
+///  - The [`Vault`](Vault) containing the [`EntryHead`](EntryHead)
+///  - The path or UUID of the [`EntryHead`](EntryHead)
+///
+/// # Errors
+///
+/// Returns `Err` if the [`EntryHead`](EntryHead) does not exist.
+pub fn find_entry<'a>(vault: &'a Vault, address: &str) -> Result<&'a EntryHead, PWDuckCliError> {
+    let not_found = || PWDuckCliError::NotFound(format!("The entry {}", address));
+
+    if let Some(entry) = parse_uuid(address).and_then(|uuid| vault.entries().get(&uuid)) {
+        return Ok(entry);
+    }
+
+    let (group, title) = split(address).map_err(|_err| not_found())?;
+    let group = find_group(vault, group).map_err(|_err| not_found())?;
+    vault
+        .get_entries_of(group.uuid())
+        .into_iter()
+        .find(|entry| entry.title() == title)
+        .ok_or_else(not_found)
+}
+
+/// Parse the address as a UUID, if it is one.
+fn parse_uuid(address: &str) -> Option<Uuid> {
+    address.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::commands::tests::example_vault;
+
+    use super::{entry_path, find_entry, find_group, group_path, join, split};
+
+    #[test]
+    fn paths() {
+        let dir = tempdir().unwrap();
+        let (vault, _mem_key) = example_vault(dir.path());
+
+        let root = vault.get_root_uuid().unwrap();
+        assert_eq!(group_path(&vault, &root), "/");
+
+        let work = find_group(&vault, "/Email/Work").unwrap();
+        assert_eq!(work.title(), "Work");
+        assert_eq!(group_path(&vault, work.uuid()), "/Email/Work");
+        assert_eq!(
+            find_group(&vault, "Email/Work/").unwrap().uuid(),
+            work.uuid()
+        );
+        assert_eq!(
+            find_group(&vault, &work.uuid().to_string()).unwrap().uuid(),
+            work.uuid()
+        );
+        assert_eq!(find_group(&vault, "/").unwrap().uuid(), &root);
+        assert!(find_group(&vault, "/Email/Missing").is_err());
+
+        let mail = find_entry(&vault, "/Email/Work/Mail").unwrap();
+        assert_eq!(entry_path(&vault, mail), "/Email/Work/Mail");
+        assert_eq!(
+            find_entry(&vault, &mail.uuid().to_string()).unwrap().uuid(),
+            mail.uuid()
+        );
+        let bank = find_entry(&vault, "Bank").unwrap();
+        assert_eq!(entry_path(&vault, bank), "/Bank");
+        assert!(find_entry(&vault, "/Email/Bank").is_err());
+        assert!(find_entry(&vault, "/Missing/Mail").is_err());
+        assert!(find_entry(&vault, "/").is_err());
+
+        assert_eq!(split("/Email/Work/").unwrap(), ("/Email", "Work"));
+        assert_eq!(split("Bank").unwrap(), ("", "Bank"));
+        assert!(split("/").is_err());
+        assert_eq!(join("/", "Bank"), "/Bank");
+        assert_eq!(join("/Email", "Work"), "/Email/Work");
+    }
+}
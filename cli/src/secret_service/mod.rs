@@ -0,0 +1,1407 @@
+//! Provide a group of a vault as a collection of the Secret Service API on the D-Bus session bus.
+//!
+//! Desktop applications store their secrets with libsecret through the Secret Service API.
+//! The entries of the group are the items of the collection: The title of an entry is the label of the item,
+//! its password is the secret and its additional attributes are the lookup attributes.
+//! The collection is also available as the alias `default`.
+//!
+//! Sessions only support the algorithm `plain`, the secrets are only exchanged over the local bus.
+//! Locking the collection forgets the key of the vault. Unlocking it prompts for the password on the
+//! terminal the provider was started from.
+//!
+//! See: <https://specifications.freedesktop.org/secret-service/latest/>
+use std::path::{Path, PathBuf};
+
+use pwduck_core::{
+    Attribute, EntryBody, EntryHead, Group, MemKey, MergeSide, PWDuckCoreError, ReferenceField,
+    SecString, Uuid, Vault,
+};
+use zeroize::Zeroize;
+
+use crate::{
+    commands::field_value,
+    dbus::{Connection, Message, MessageType, Value, NO_REPLY_EXPECTED},
+    error::PWDuckCliError,
+    input::read_secret,
+    path::find_group,
+};
+
+mod introspection;
+
+/// The well-known bus name of the Secret Service.
+pub const SERVICE_NAME: &str = "org.freedesktop.secrets";
+
+/// The object path of the service.
+const SERVICE_PATH: &str = "/org/freedesktop/secrets";
+
+/// The object path of the `default` alias.
+const DEFAULT_ALIAS_PATH: &str = "/org/freedesktop/secrets/aliases/default";
+
+/// The object path meaning no object, e.g. if no prompt is needed.
+const NO_OBJECT: &str = "/";
+
+/// The interface of the service.
+const SERVICE_INTERFACE: &str = "org.freedesktop.Secret.Service";
+/// The interface of collections.
+const COLLECTION_INTERFACE: &str = "org.freedesktop.Secret.Collection";
+/// The interface of items.
+const ITEM_INTERFACE: &str = "org.freedesktop.Secret.Item";
+/// The interface of sessions.
+const SESSION_INTERFACE: &str = "org.freedesktop.Secret.Session";
+/// The interface of prompts.
+const PROMPT_INTERFACE: &str = "org.freedesktop.Secret.Prompt";
+/// The standard interface of properties.
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+/// The standard interface of introspection.
+const INTROSPECTABLE_INTERFACE: &str = "org.freedesktop.DBus.Introspectable";
+/// The standard interface of pings.
+const PEER_INTERFACE: &str = "org.freedesktop.DBus.Peer";
+
+/// The property of the label of new items.
+const LABEL_PROPERTY: &str = "org.freedesktop.Secret.Item.Label";
+/// The property of the attributes of new items.
+const ATTRIBUTES_PROPERTY: &str = "org.freedesktop.Secret.Item.Attributes";
+
+/// The content type of the secrets.
+const CONTENT_TYPE: &str = "text/plain; charset=utf8";
+
+/// The error of calls needing an unlocked collection.
+const IS_LOCKED: &str = "org.freedesktop.Secret.Error.IsLocked";
+/// The error of calls with an unknown session.
+const NO_SESSION: &str = "org.freedesktop.Secret.Error.NoSession";
+/// The error of calls to unknown objects.
+const NO_SUCH_OBJECT: &str = "org.freedesktop.Secret.Error.NoSuchObject";
+/// The error of unsupported calls.
+const NOT_SUPPORTED: &str = "org.freedesktop.DBus.Error.NotSupported";
+/// The error of calls with wrong arguments.
+const INVALID_ARGS: &str = "org.freedesktop.DBus.Error.InvalidArgs";
+/// The error of unknown methods.
+const UNKNOWN_METHOD: &str = "org.freedesktop.DBus.Error.UnknownMethod";
+/// The error of unknown properties.
+const UNKNOWN_PROPERTY: &str = "org.freedesktop.DBus.Error.UnknownProperty";
+/// The error of properties that can't be set.
+const PROPERTY_READ_ONLY: &str = "org.freedesktop.DBus.Error.PropertyReadOnly";
+/// The error of failed calls.
+const FAILED: &str = "org.freedesktop.DBus.Error.Failed";
+
+/// An error replied to a call.
+#[derive(Debug)]
+struct CallError {
+    /// The D-Bus name of the error.
+    name: &'static str,
+    /// The human readable message.
+    message: String,
+}
+
+impl CallError {
+    /// Create a new [`CallError`](CallError).
+    fn new(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            message: message.into(),
+        }
+    }
+
+    /// The error of calls changing the collections.
+    fn not_supported() -> Self {
+        Self::new(
+            NOT_SUPPORTED,
+            "Only the collection of the group is provided, change the groups with pwduck",
+        )
+    }
+
+    /// The error of calls needing an unlocked collection.
+    fn locked() -> Self {
+        Self::new(IS_LOCKED, "The collection is locked")
+    }
+}
+
+impl From<PWDuckCliError> for CallError {
+    fn from(error: PWDuckCliError) -> Self {
+        Self::new(FAILED, error.to_string())
+    }
+}
+
+impl From<PWDuckCoreError> for CallError {
+    fn from(error: PWDuckCoreError) -> Self {
+        PWDuckCliError::from(error).into()
+    }
+}
+
+/// The result of a call: the arguments of the reply or the error.
+type CallResult = Result<Vec<Value>, CallError>;
+
+/// An object provided on the bus.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Object {
+    /// The service itself.
+    Service,
+    /// The collection of the group.
+    Collection,
+    /// An item of the collection with the UUID of its entry.
+    Item(Uuid),
+    /// An open session.
+    Session(String),
+    /// A prompt to unlock the collection.
+    Prompt(String),
+}
+
+impl Object {
+    /// The interface of the methods of this object.
+    const fn interface(&self) -> &'static str {
+        match self {
+            Self::Service => SERVICE_INTERFACE,
+            Self::Collection => COLLECTION_INTERFACE,
+            Self::Item(_) => ITEM_INTERFACE,
+            Self::Session(_) => SESSION_INTERFACE,
+            Self::Prompt(_) => PROMPT_INTERFACE,
+        }
+    }
+}
+
+/// The provider of the Secret Service API for a group of an unlocked [`Vault`](Vault).
+#[derive(Debug)]
+pub struct SecretService {
+    /// The [`Vault`](Vault) containing the group.
+    vault: Vault,
+    /// The [`MemKey`](MemKey) protecting the master key of the [`Vault`](Vault) or `None`, if it is locked.
+    mem_key: Option<MemKey>,
+    /// The key file to unlock the [`Vault`](Vault) again.
+    key_file: Option<PathBuf>,
+    /// The UUID of the group provided as the collection.
+    group: Uuid,
+    /// The object path of the collection.
+    collection: String,
+    /// The object paths of the open sessions.
+    sessions: Vec<String>,
+    /// The object paths of the prompts not completed yet.
+    prompts: Vec<String>,
+    /// The prompts requested by clients, they are shown after the reply was sent.
+    requested_prompts: Vec<String>,
+    /// The number of the last created session or prompt.
+    last_id: u64,
+    /// Ask the user for the password of the [`Vault`](Vault) to unlock it.
+    password_prompt: fn(&Path) -> Result<String, PWDuckCliError>,
+}
+
+impl SecretService {
+    /// Create a new [`SecretService`](SecretService) providing the group of the unlocked [`Vault`](Vault).
+    ///
+    /// It expects:
+    ///  - The unlocked [`Vault`](Vault)
+    ///  - The [`MemKey`](MemKey) protecting the master key of the [`Vault`](Vault)
+    ///  - The key file needed to unlock the [`Vault`](Vault) again
+    ///  - The path or UUID of the group to provide
+    ///  - The name of the collection, only ASCII letters, digits and `_` are allowed
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the group does not exist or the name is invalid.
+    pub fn new(
+        vault: Vault,
+        mem_key: MemKey,
+        key_file: Option<PathBuf>,
+        group: &str,
+        name: &str,
+    ) -> Result<Self, PWDuckCliError> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|character| character.is_ascii_alphanumeric() || character == '_')
+        {
+            return Err(PWDuckCliError::Refused(format!(
+                "The collection name {} may only contain ASCII letters, digits and _",
+                name
+            )));
+        }
+        let group = find_group(&vault, group)?.uuid().clone();
+
+        Ok(Self {
+            vault,
+            mem_key: Some(mem_key),
+            key_file,
+            group,
+            collection: format!("{}/collection/{}", SERVICE_PATH, name),
+            sessions: Vec::new(),
+            prompts: Vec::new(),
+            requested_prompts: Vec::new(),
+            last_id: 0,
+            password_prompt: prompt_on_terminal,
+        })
+    }
+
+    /// Answer the calls received on the connection until it is closed.
+    /// The connection must own the name [`SERVICE_NAME`](SERVICE_NAME).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the connection fails.
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn serve(mut self, connection: &mut Connection) -> Result<(), PWDuckCliError> {
+        loop {
+            let call = connection.receive()?;
+            if call.message_type != MessageType::MethodCall {
+                continue;
+            }
+            let mut messages = self.handle(&call);
+            if call.flags & NO_REPLY_EXPECTED != 0 {
+                messages.retain(|message| message.message_type == MessageType::Signal);
+            }
+            for message in &messages {
+                let _ = connection.send(message)?;
+            }
+            for message in &self.show_prompts() {
+                let _ = connection.send(message)?;
+            }
+        }
+    }
+
+    /// Answer the call. Returns the reply followed by the signals to emit.
+    pub fn handle(&mut self, call: &Message) -> Vec<Message> {
+        let mut signals = Vec::new();
+        let result = self
+            .reload()
+            .map_err(CallError::from)
+            .and_then(|()| self.dispatch(call, &mut signals));
+        let reply = match result {
+            Ok(body) => call.method_return(body),
+            Err(error) => call.error(error.name, &error.message),
+        };
+        std::iter::once(reply).chain(signals).collect()
+    }
+
+    /// Show the prompts requested by the last calls and return the signals of their completion.
+    pub fn show_prompts(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.requested_prompts)
+            .into_iter()
+            .map(|prompt| {
+                let unlocked = self.mem_key.is_some() || self.unlock_with_prompt().is_ok();
+                self.complete_prompt(&prompt, !unlocked)
+            })
+            .collect()
+    }
+
+    /// Take over the changes made on disk by other programs, if the collection is unlocked.
+    fn reload(&mut self) -> Result<(), PWDuckCliError> {
+        if let Some(mem_key) = &self.mem_key {
+            let master_key = self.vault.master_key().as_unprotected(
+                mem_key,
+                self.vault.salt(),
+                self.vault.nonce(),
+            )?;
+            // All changes are saved immediately, so there are no local changes to keep.
+            let _ = self
+                .vault
+                .reload_external_changes(&master_key, Some(MergeSide::Remote))?;
+        }
+        Ok(())
+    }
+
+    /// Find the object of the path.
+    fn object(&self, path: &str) -> Option<Object> {
+        if path == SERVICE_PATH {
+            return Some(Object::Service);
+        }
+        if path == self.collection || path == DEFAULT_ALIAS_PATH {
+            return Some(Object::Collection);
+        }
+        if self.sessions.iter().any(|session| session == path) {
+            return Some(Object::Session(path.into()));
+        }
+        if self.prompts.iter().any(|prompt| prompt == path) {
+            return Some(Object::Prompt(path.into()));
+        }
+
+        let id = [self.collection.as_str(), DEFAULT_ALIAS_PATH]
+            .iter()
+            .find_map(|collection| path.strip_prefix(collection)?.strip_prefix('/'))?;
+        let uuid: Uuid = id.parse().ok()?;
+        self.vault
+            .entries()
+            .get(&uuid)
+            .filter(|entry| entry.parent() == &self.group)
+            .map(|_entry| Object::Item(uuid))
+    }
+
+    /// The object path of the item of the entry.
+    fn item_path(&self, uuid: &Uuid) -> String {
+        format!("{}/{}", self.collection, uuid.to_string().replace('-', ""))
+    }
+
+    /// The entries of the group sorted by their titles.
+    fn items(&self) -> Vec<&EntryHead> {
+        let mut entries = self.vault.get_entries_of(&self.group);
+        entries.sort_by(|a, b| a.title().cmp(b.title()));
+        entries
+    }
+
+    /// The object paths of the items matching all attributes.
+    fn search(&self, attributes: &[Attribute]) -> Vec<String> {
+        self.items()
+            .into_iter()
+            .filter(|entry| {
+                attributes
+                    .iter()
+                    .all(|attribute| entry.attribute(attribute.key()) == Some(attribute.value()))
+            })
+            .map(|entry| self.item_path(entry.uuid()))
+            .collect()
+    }
+
+    /// Call the method of the object.
+    fn dispatch(&mut self, call: &Message, signals: &mut Vec<Message>) -> CallResult {
+        let path = call.path.as_deref().unwrap_or_default();
+        let member = call.member.as_deref().unwrap_or_default();
+        let object = self
+            .object(path)
+            .ok_or_else(|| CallError::new(NO_SUCH_OBJECT, format!("No such object {}", path)))?;
+
+        match call.interface.as_deref() {
+            Some(PROPERTIES_INTERFACE) => return self.call_properties(call, &object, signals),
+            Some(INTROSPECTABLE_INTERFACE) if member == "Introspect" => {
+                return Ok(vec![Value::String(introspection::xml(&object))]);
+            }
+            Some(PEER_INTERFACE) if member == "Ping" => return Ok(Vec::new()),
+            Some(interface) if interface != object.interface() => {
+                return Err(CallError::new(
+                    UNKNOWN_METHOD,
+                    format!("No such interface {}", interface),
+                ));
+            }
+            _ => {}
+        }
+
+        match &object {
+            Object::Service => self.call_service(call, member),
+            Object::Collection => self.call_collection(call, member, signals),
+            Object::Item(uuid) => self.call_item(call, member, uuid, signals),
+            Object::Session(session) if member == "Close" => {
+                self.sessions.retain(|other| other != session);
+                Ok(Vec::new())
+            }
+            Object::Prompt(prompt) if member == "Prompt" => {
+                let _window_id = arguments(call, "s")?;
+                self.requested_prompts.push(prompt.clone());
+                Ok(Vec::new())
+            }
+            Object::Prompt(prompt) if member == "Dismiss" => {
+                signals.push(self.complete_prompt(prompt, true));
+                Ok(Vec::new())
+            }
+            Object::Session(_) | Object::Prompt(_) => Err(unknown_method(member)),
+        }
+    }
+
+    /// Call a method of the service.
+    fn call_service(&mut self, call: &Message, member: &str) -> CallResult {
+        match member {
+            "OpenSession" => {
+                let arguments = arguments(call, "sv")?;
+                if arguments[0].as_str() != Some("plain") {
+                    return Err(CallError::new(
+                        NOT_SUPPORTED,
+                        "Only the algorithm plain is supported",
+                    ));
+                }
+                let session = self.next_path("session");
+                self.sessions.push(session.clone());
+                Ok(vec![
+                    Value::Variant(Box::new(Value::String(String::new()))),
+                    Value::ObjectPath(session),
+                ])
+            }
+            "SearchItems" => {
+                let found = self.search(&parse_attributes(&arguments(call, "a{ss}")?[0])?);
+                let (unlocked, locked) = if self.mem_key.is_some() {
+                    (found, Vec::new())
+                } else {
+                    (Vec::new(), found)
+                };
+                Ok(vec![
+                    Value::object_paths(unlocked),
+                    Value::object_paths(locked),
+                ])
+            }
+            "Unlock" => {
+                let objects = self.own_objects(&arguments(call, "ao")?[0]);
+                if self.mem_key.is_some() || objects.is_empty() {
+                    return Ok(vec![
+                        Value::object_paths(objects),
+                        Value::ObjectPath(NO_OBJECT.into()),
+                    ]);
+                }
+                let prompt = self.next_path("prompt");
+                self.prompts.push(prompt.clone());
+                Ok(vec![
+                    Value::object_paths(Vec::new()),
+                    Value::ObjectPath(prompt),
+                ])
+            }
+            "Lock" => {
+                let objects = self.own_objects(&arguments(call, "ao")?[0]);
+                if !objects.is_empty() {
+                    self.mem_key = None;
+                }
+                Ok(vec![
+                    Value::object_paths(objects),
+                    Value::ObjectPath(NO_OBJECT.into()),
+                ])
+            }
+            "GetSecrets" => {
+                let arguments = arguments(call, "aoo")?;
+                let session = self.session(&arguments[1])?;
+                let mut secrets = Vec::new();
+                for item in arguments[0].as_array().unwrap_or_default() {
+                    if let Some(Object::Item(uuid)) =
+                        item.as_str().and_then(|path| self.object(path))
+                    {
+                        secrets.push((
+                            Value::ObjectPath(self.item_path(&uuid)),
+                            self.secret(&uuid, session)?,
+                        ));
+                    }
+                }
+                Ok(vec![Value::dict("o", "(oayays)", secrets)])
+            }
+            "ReadAlias" => {
+                let name = arguments(call, "s")?[0].as_str().unwrap_or_default();
+                let collection_name = self.collection.rsplit('/').next().unwrap_or_default();
+                let path = if name == "default" || name == collection_name {
+                    self.collection.clone()
+                } else {
+                    NO_OBJECT.into()
+                };
+                Ok(vec![Value::ObjectPath(path)])
+            }
+            "CreateCollection" | "SetAlias" => Err(CallError::not_supported()),
+            _ => Err(unknown_method(member)),
+        }
+    }
+
+    /// Call a method of the collection.
+    fn call_collection(
+        &mut self,
+        call: &Message,
+        member: &str,
+        signals: &mut Vec<Message>,
+    ) -> CallResult {
+        match member {
+            "SearchItems" => {
+                let found = self.search(&parse_attributes(&arguments(call, "a{ss}")?[0])?);
+                Ok(vec![Value::object_paths(found)])
+            }
+            "CreateItem" => {
+                let arguments = arguments(call, "a{sv}(oayays)b")?;
+                let replace = arguments[2].as_bool().unwrap_or_default();
+                let item = self.create_item(&arguments[0], &arguments[1], replace, signals)?;
+                Ok(vec![
+                    Value::ObjectPath(item),
+                    Value::ObjectPath(NO_OBJECT.into()),
+                ])
+            }
+            "Delete" => Err(CallError::not_supported()),
+            _ => Err(unknown_method(member)),
+        }
+    }
+
+    /// Call a method of the item of the entry.
+    fn call_item(
+        &mut self,
+        call: &Message,
+        member: &str,
+        uuid: &Uuid,
+        signals: &mut Vec<Message>,
+    ) -> CallResult {
+        match member {
+            "GetSecret" => {
+                let session = self.session(&arguments(call, "o")?[0])?;
+                Ok(vec![self.secret(uuid, session)?])
+            }
+            "SetSecret" => {
+                let secret = self.parse_secret(&arguments(call, "(oayays)")?[0])?;
+                self.update_item(uuid, signals, |_head, body| {
+                    let _ = body.set_password(secret.as_str().into());
+                })?;
+                Ok(Vec::new())
+            }
+            "Delete" => {
+                let mem_key = self.mem_key.as_ref().ok_or_else(CallError::locked)?;
+                let path = self.item_path(uuid);
+                self.vault.delete_entry(uuid).map_err(|_err| {
+                    CallError::new(
+                        FAILED,
+                        "The entry is referenced by other entries, remove it with pwduck",
+                    )
+                })?;
+                self.vault.save(mem_key)?;
+                signals.push(self.item_signal("ItemDeleted", path));
+                Ok(vec![Value::ObjectPath(NO_OBJECT.into())])
+            }
+            _ => Err(unknown_method(member)),
+        }
+    }
+
+    /// Call a method of the standard interface of properties.
+    fn call_properties(
+        &mut self,
+        call: &Message,
+        object: &Object,
+        signals: &mut Vec<Message>,
+    ) -> CallResult {
+        let member = call.member.as_deref().unwrap_or_default();
+        let interface_matches = |interface: &Value| {
+            interface
+                .as_str()
+                .map_or(false, |name| name.is_empty() || name == object.interface())
+        };
+
+        match member {
+            "Get" => {
+                let arguments = arguments(call, "ss")?;
+                let name = arguments[1].as_str().unwrap_or_default();
+                self.properties(object)
+                    .into_iter()
+                    .find(|(property, _value)| *property == name)
+                    .filter(|_property| interface_matches(&arguments[0]))
+                    .map(|(_property, value)| vec![Value::Variant(Box::new(value))])
+                    .ok_or_else(|| {
+                        CallError::new(UNKNOWN_PROPERTY, format!("No such property {}", name))
+                    })
+            }
+            "GetAll" => {
+                let arguments = arguments(call, "s")?;
+                let properties = if interface_matches(&arguments[0]) {
+                    self.properties(object)
+                } else {
+                    Vec::new()
+                };
+                Ok(vec![Value::dict(
+                    "s",
+                    "v",
+                    properties
+                        .into_iter()
+                        .map(|(name, value)| {
+                            (Value::String(name.into()), Value::Variant(Box::new(value)))
+                        })
+                        .collect(),
+                )])
+            }
+            "Set" => {
+                let arguments = arguments(call, "ssv")?;
+                let name = arguments[1].as_str().unwrap_or_default();
+                let value = arguments[2].unwrap_variant();
+                match (object, name) {
+                    (Object::Item(uuid), "Label") => {
+                        let label = value.as_str().ok_or_else(|| {
+                            CallError::new(INVALID_ARGS, "The label must be a string")
+                        })?;
+                        let title = self.unique_title(label, Some(uuid));
+                        self.update_item(uuid, signals, |head, _body| {
+                            let _ = head.set_title(title);
+                        })?;
+                    }
+                    (Object::Item(uuid), "Attributes") => {
+                        let attributes = parse_attributes(value)?;
+                        self.update_item(uuid, signals, |head, _body| {
+                            let _ = head.set_attributes(attributes);
+                        })?;
+                    }
+                    _ => {
+                        return Err(CallError::new(
+                            PROPERTY_READ_ONLY,
+                            format!("The property {} can't be set", name),
+                        ))
+                    }
+                }
+                Ok(Vec::new())
+            }
+            _ => Err(unknown_method(member)),
+        }
+    }
+
+    /// The properties of the object.
+    fn properties(&self, object: &Object) -> Vec<(&'static str, Value)> {
+        let locked = Value::Bool(self.mem_key.is_none());
+        match object {
+            Object::Service => vec![(
+                "Collections",
+                Value::object_paths(vec![self.collection.clone()]),
+            )],
+            Object::Collection => {
+                let group = self.vault.groups().get(&self.group);
+                let items = self
+                    .items()
+                    .into_iter()
+                    .map(|entry| self.item_path(entry.uuid()))
+                    .collect();
+                vec![
+                    ("Items", Value::object_paths(items)),
+                    (
+                        "Label",
+                        Value::String(group.map(|group| group.title().clone()).unwrap_or_default()),
+                    ),
+                    ("Locked", locked),
+                    ("Created", Value::UInt64(group.map_or(0, Group::created))),
+                    (
+                        "Modified",
+                        Value::UInt64(group.map_or(0, Group::last_modified)),
+                    ),
+                ]
+            }
+            Object::Item(uuid) => self
+                .vault
+                .entries()
+                .get(uuid)
+                .map_or_else(Vec::new, |entry| item_properties(entry, locked)),
+            Object::Session(_) | Object::Prompt(_) => Vec::new(),
+        }
+    }
+
+    /// The object path of a new session or prompt.
+    fn next_path(&mut self, kind: &str) -> String {
+        self.last_id += 1;
+        format!("{}/{}/{}{}", SERVICE_PATH, kind, &kind[..1], self.last_id)
+    }
+
+    /// The paths of the objects provided by this service, i.e. of the collection and its items.
+    fn own_objects(&self, objects: &Value) -> Vec<String> {
+        objects
+            .as_array()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(Value::as_str)
+            .filter(|path| {
+                matches!(
+                    self.object(path),
+                    Some(Object::Collection | Object::Item(_))
+                )
+            })
+            .map(String::from)
+            .collect()
+    }
+
+    /// The path of the open session.
+    fn session<'a>(&self, session: &'a Value) -> Result<&'a str, CallError> {
+        session
+            .as_str()
+            .filter(|path| self.sessions.iter().any(|other| other == path))
+            .ok_or_else(|| CallError::new(NO_SESSION, "The session does not exist"))
+    }
+
+    /// The secret of the item encoded for the session.
+    fn secret(&self, uuid: &Uuid, session: &str) -> Result<Value, CallError> {
+        let mem_key = self.mem_key.as_ref().ok_or_else(CallError::locked)?;
+        let password = field_value(
+            &self.vault,
+            mem_key,
+            &uuid.to_string(),
+            ReferenceField::Password,
+        )?;
+        Ok(Value::Struct(vec![
+            Value::ObjectPath(session.into()),
+            Value::Bytes(Vec::new()),
+            Value::Bytes(password.as_str().as_bytes().to_vec()),
+            Value::String(CONTENT_TYPE.into()),
+        ]))
+    }
+
+    /// Decode the secret sent in the session.
+    fn parse_secret(&self, secret: &Value) -> Result<SecString, CallError> {
+        let invalid = || CallError::new(INVALID_ARGS, "The secret must be a (oayays)");
+        let fields = secret.as_struct().ok_or_else(invalid)?;
+        let _session = self.session(fields.first().ok_or_else(invalid)?)?;
+        let bytes = fields
+            .get(2)
+            .and_then(Value::as_bytes)
+            .ok_or_else(invalid)?;
+        String::from_utf8(bytes.to_vec())
+            .map(SecString::from)
+            .map_err(|error| {
+                error.into_bytes().zeroize();
+                CallError::new(INVALID_ARGS, "Only UTF-8 secrets can be stored")
+            })
+    }
+
+    /// A title for the label that is not used by another entry of the group yet.
+    fn unique_title(&self, label: &str, except: Option<&Uuid>) -> String {
+        let label = if label.is_empty() { "Unnamed" } else { label };
+        let is_taken = |title: &str| {
+            self.vault
+                .get_entries_of(&self.group)
+                .iter()
+                .any(|entry| entry.title() == title && Some(entry.uuid()) != except)
+        };
+        if !is_taken(label) {
+            return label.into();
+        }
+        let mut number = 2_u32;
+        loop {
+            let title = format!("{} ({})", label, number);
+            if !is_taken(&title) {
+                return title;
+            }
+            number += 1;
+        }
+    }
+
+    /// Create a new item or replace the secret of the item with the same attributes.
+    /// Returns the path of the item.
+    fn create_item(
+        &mut self,
+        properties: &Value,
+        secret: &Value,
+        replace: bool,
+        signals: &mut Vec<Message>,
+    ) -> Result<String, CallError> {
+        if self.mem_key.is_none() {
+            return Err(CallError::locked());
+        }
+        let secret = self.parse_secret(secret)?;
+        let mut label = String::new();
+        let mut attributes = Vec::new();
+        for (key, value) in properties
+            .as_dict()
+            .ok_or_else(|| CallError::new(INVALID_ARGS, "The properties must be a a{sv}"))?
+        {
+            match key.as_str() {
+                Some(LABEL_PROPERTY) => {
+                    label = value.unwrap_variant().as_str().unwrap_or_default().into();
+                }
+                Some(ATTRIBUTES_PROPERTY) => attributes = parse_attributes(value.unwrap_variant())?,
+                _ => {}
+            }
+        }
+
+        let existing = if replace {
+            self.items()
+                .into_iter()
+                .find(|entry| {
+                    entry.attributes().len() == attributes.len()
+                        && attributes.iter().all(|attribute| {
+                            entry.attribute(attribute.key()) == Some(attribute.value())
+                        })
+                })
+                .map(|entry| entry.uuid().clone())
+        } else {
+            None
+        };
+        if let Some(uuid) = existing {
+            let title = self.unique_title(&label, Some(&uuid));
+            self.update_item(&uuid, signals, |head, body| {
+                let _ = head.set_title(title);
+                let _ = body.set_password(secret.as_str().into());
+            })?;
+            return Ok(self.item_path(&uuid));
+        }
+
+        let title = self.unique_title(&label, None);
+        let mem_key = self.mem_key.as_ref().ok_or_else(CallError::locked)?;
+        let body = EntryBody::new(
+            Uuid::new(self.vault.path()),
+            String::new(),
+            secret.as_str().into(),
+        );
+        let mut head = EntryHead::new(
+            Uuid::new(self.vault.path()),
+            self.group.clone(),
+            title,
+            body.uuid().clone(),
+        );
+        let _ = head.set_attributes(attributes);
+        let uuid = head.uuid().clone();
+
+        let master_key = self.vault.master_key().as_unprotected(
+            mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        self.vault.insert_entry(head, body, &master_key)?;
+        self.vault.save(mem_key)?;
+
+        let path = self.item_path(&uuid);
+        signals.push(self.item_signal("ItemCreated", path.clone()));
+        Ok(path)
+    }
+
+    /// Change the head and the body of the entry and save the [`Vault`](Vault).
+    fn update_item<F>(
+        &mut self,
+        uuid: &Uuid,
+        signals: &mut Vec<Message>,
+        change: F,
+    ) -> Result<(), CallError>
+    where
+        F: FnOnce(&mut EntryHead, &mut EntryBody),
+    {
+        let mem_key = self.mem_key.as_ref().ok_or_else(CallError::locked)?;
+        let mut head = self
+            .vault
+            .entries()
+            .get(uuid)
+            .ok_or_else(|| CallError::new(NO_SUCH_OBJECT, "The item does not exist"))?
+            .clone();
+        let master_key = self.vault.master_key().as_unprotected(
+            mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        let mut body = self.vault.load_entry_body(head.body(), &master_key)?;
+        change(&mut head, &mut body);
+        self.vault.insert_entry(head, body, &master_key)?;
+        self.vault.save(mem_key)?;
+
+        signals.push(self.item_signal("ItemChanged", self.item_path(uuid)));
+        Ok(())
+    }
+
+    /// A signal of the collection about the item.
+    fn item_signal(&self, member: &str, item: String) -> Message {
+        Message::signal(
+            &self.collection,
+            COLLECTION_INTERFACE,
+            member,
+            vec![Value::ObjectPath(item)],
+        )
+    }
+
+    /// Ask for the password and unlock the [`Vault`](Vault) again.
+    fn unlock_with_prompt(&mut self) -> Result<(), PWDuckCliError> {
+        let mut password = (self.password_prompt)(self.vault.path())?;
+        let mem_key = MemKey::new();
+        let vault = Vault::load(
+            &password,
+            self.key_file.clone(),
+            &mem_key,
+            self.vault.path().clone(),
+        );
+        password.zeroize();
+
+        self.vault = vault?;
+        self.mem_key = Some(mem_key);
+        Ok(())
+    }
+
+    /// Remove the prompt and return its signal of completion.
+    fn complete_prompt(&mut self, prompt: &str, dismissed: bool) -> Message {
+        self.prompts.retain(|other| other != prompt);
+        let unlocked = if dismissed {
+            Vec::new()
+        } else {
+            vec![self.collection.clone()]
+        };
+        Message::signal(
+            prompt,
+            PROMPT_INTERFACE,
+            "Completed",
+            vec![
+                Value::Bool(dismissed),
+                Value::Variant(Box::new(Value::object_paths(unlocked))),
+            ],
+        )
+    }
+}
+
+/// Ask for the password of the vault on the terminal of the provider.
+#[cfg_attr(coverage, no_coverage)]
+fn prompt_on_terminal(vault: &Path) -> Result<String, PWDuckCliError> {
+    read_secret(
+        &format!("Password to unlock {}: ", vault.display()),
+        false,
+        &mut std::io::empty(),
+    )
+}
+
+/// The arguments of the call, if they are of the signature.
+fn arguments<'a>(call: &'a Message, signature: &str) -> Result<&'a [Value], CallError> {
+    if call.signature() == signature {
+        Ok(&call.body)
+    } else {
+        Err(CallError::new(
+            INVALID_ARGS,
+            format!("Expected arguments of the type {}", signature),
+        ))
+    }
+}
+
+/// The properties of the item of the entry.
+fn item_properties(entry: &EntryHead, locked: Value) -> Vec<(&'static str, Value)> {
+    let attributes = entry
+        .attributes()
+        .iter()
+        .map(|attribute| {
+            (
+                Value::String(attribute.key().clone()),
+                Value::String(attribute.value().clone()),
+            )
+        })
+        .collect();
+    vec![
+        ("Locked", locked),
+        ("Attributes", Value::dict("s", "s", attributes)),
+        ("Label", Value::String(entry.title().clone())),
+        ("Created", Value::UInt64(entry.created())),
+        ("Modified", Value::UInt64(entry.last_modified())),
+    ]
+}
+
+/// The error of calls to unknown methods.
+fn unknown_method(member: &str) -> CallError {
+    CallError::new(UNKNOWN_METHOD, format!("No such method {}", member))
+}
+
+/// Decode the lookup attributes of a `a{ss}`.
+fn parse_attributes(attributes: &Value) -> Result<Vec<Attribute>, CallError> {
+    let invalid = || CallError::new(INVALID_ARGS, "The attributes must be a a{ss}");
+    attributes
+        .as_dict()
+        .ok_or_else(invalid)?
+        .into_iter()
+        .map(|(key, value)| {
+            Ok(Attribute::new(
+                key.as_str().ok_or_else(invalid)?.into(),
+                value.as_str().ok_or_else(invalid)?.into(),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        commands::tests::{example_vault, PASSWORD},
+        dbus::{Message, MessageType, Value},
+        error::PWDuckCliError,
+    };
+
+    use super::{
+        SecretService, COLLECTION_INTERFACE, INTROSPECTABLE_INTERFACE, IS_LOCKED, ITEM_INTERFACE,
+        NOT_SUPPORTED, NO_SESSION, PROMPT_INTERFACE, PROPERTIES_INTERFACE, SERVICE_INTERFACE,
+        SERVICE_NAME, SERVICE_PATH,
+    };
+
+    const COLLECTION: &str = "/org/freedesktop/secrets/collection/pwduck";
+
+    fn call(
+        service: &mut SecretService,
+        path: &str,
+        interface: &str,
+        member: &str,
+        body: Vec<Value>,
+    ) -> Vec<Message> {
+        service.handle(&Message::method_call(
+            SERVICE_NAME,
+            path,
+            interface,
+            member,
+            body,
+        ))
+    }
+
+    fn reply(
+        service: &mut SecretService,
+        path: &str,
+        interface: &str,
+        member: &str,
+        body: Vec<Value>,
+    ) -> Vec<Value> {
+        let mut messages = call(service, path, interface, member, body);
+        let reply = messages.remove(0);
+        assert_eq!(
+            reply.message_type,
+            MessageType::MethodReturn,
+            "{:?}",
+            reply.body
+        );
+        reply.body
+    }
+
+    fn error(
+        service: &mut SecretService,
+        path: &str,
+        interface: &str,
+        member: &str,
+        body: Vec<Value>,
+    ) -> String {
+        let reply = call(service, path, interface, member, body).remove(0);
+        assert_eq!(reply.message_type, MessageType::Error);
+        reply.error_name.unwrap()
+    }
+
+    fn attributes(attributes: &[(&str, &str)]) -> Value {
+        Value::dict(
+            "s",
+            "s",
+            attributes
+                .iter()
+                .map(|&(key, value)| (Value::String(key.into()), Value::String(value.into())))
+                .collect(),
+        )
+    }
+
+    fn secret(session: &str, secret: &str) -> Value {
+        Value::Struct(vec![
+            Value::ObjectPath(session.into()),
+            Value::Bytes(Vec::new()),
+            Value::Bytes(secret.as_bytes().to_vec()),
+            Value::String("text/plain".into()),
+        ])
+    }
+
+    fn paths(value: &Value) -> Vec<String> {
+        value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|path| path.as_str().unwrap().to_owned())
+            .collect()
+    }
+
+    fn secret_of(service: &mut SecretService, item: &str, session: &str) -> String {
+        let secret = reply(
+            service,
+            item,
+            ITEM_INTERFACE,
+            "GetSecret",
+            vec![Value::ObjectPath(session.into())],
+        );
+        String::from_utf8(
+            secret[0].as_struct().unwrap()[2]
+                .as_bytes()
+                .unwrap()
+                .to_vec(),
+        )
+        .unwrap()
+    }
+
+    #[allow(clippy::unnecessary_wraps)]
+    fn correct_password(_vault: &Path) -> Result<String, PWDuckCliError> {
+        Ok(PASSWORD.into())
+    }
+
+    fn wrong_password(_vault: &Path) -> Result<String, PWDuckCliError> {
+        Err(PWDuckCliError::Refused("No terminal".into()))
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn items() {
+        let dir = tempdir().unwrap();
+        let (vault, mem_key) = example_vault(&dir.path().join("name"));
+        assert!(SecretService::new(vault, mem_key, None, "/Email/Work", "my-keyring").is_err());
+        let (vault, mem_key) = example_vault(&dir.path().join("group"));
+        assert!(SecretService::new(vault, mem_key, None, "/Missing", "pwduck").is_err());
+        let (vault, mem_key) = example_vault(dir.path());
+        let mut service =
+            SecretService::new(vault, mem_key, None, "/Email/Work", "pwduck").unwrap();
+
+        assert_eq!(
+            error(
+                &mut service,
+                SERVICE_PATH,
+                SERVICE_INTERFACE,
+                "OpenSession",
+                vec![
+                    Value::String("dh-ietf1024-sha256-aes128-cbc-pkcs7".into()),
+                    Value::Variant(Box::new(Value::Bytes(vec![1, 2]))),
+                ],
+            ),
+            NOT_SUPPORTED
+        );
+        let session = reply(
+            &mut service,
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "OpenSession",
+            vec![
+                Value::String("plain".into()),
+                Value::Variant(Box::new(Value::String(String::new()))),
+            ],
+        )[1]
+        .as_str()
+        .unwrap()
+        .to_owned();
+
+        // The existing entry of the group is an item.
+        let found = reply(
+            &mut service,
+            "/org/freedesktop/secrets/aliases/default",
+            COLLECTION_INTERFACE,
+            "SearchItems",
+            vec![attributes(&[])],
+        );
+        let mail = paths(&found[0]).remove(0);
+        assert_eq!(secret_of(&mut service, &mail, &session), "mail secret");
+
+        // Create an item and replace its secret.
+        let properties = |label: &str| {
+            Value::dict(
+                "s",
+                "v",
+                vec![
+                    (
+                        Value::String("org.freedesktop.Secret.Item.Label".into()),
+                        Value::Variant(Box::new(Value::String(label.into()))),
+                    ),
+                    (
+                        Value::String("org.freedesktop.Secret.Item.Attributes".into()),
+                        Value::Variant(Box::new(attributes(&[
+                            ("service", "chat"),
+                            ("user", "alice"),
+                        ]))),
+                    ),
+                ],
+            )
+        };
+        let messages = call(
+            &mut service,
+            COLLECTION,
+            COLLECTION_INTERFACE,
+            "CreateItem",
+            vec![
+                properties("Mail"),
+                secret(&session, "chat secret"),
+                Value::Bool(true),
+            ],
+        );
+        let chat = messages[0].body[0].as_str().unwrap().to_owned();
+        assert_eq!(messages[1].member.as_deref(), Some("ItemCreated"));
+        let replaced = reply(
+            &mut service,
+            COLLECTION,
+            COLLECTION_INTERFACE,
+            "CreateItem",
+            vec![
+                properties("Chat"),
+                secret(&session, "new chat secret"),
+                Value::Bool(true),
+            ],
+        );
+        assert_eq!(replaced[0].as_str(), Some(chat.as_str()));
+        assert_eq!(secret_of(&mut service, &chat, &session), "new chat secret");
+
+        // Search with a subset of the attributes.
+        let found = reply(
+            &mut service,
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "SearchItems",
+            vec![attributes(&[("service", "chat")])],
+        );
+        assert_eq!(paths(&found[0]), vec![chat.clone()]);
+        assert!(paths(&found[1]).is_empty());
+        let secrets = reply(
+            &mut service,
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "GetSecrets",
+            vec![
+                Value::object_paths(vec![chat.clone(), mail.clone()]),
+                Value::ObjectPath(session.clone()),
+            ],
+        );
+        assert_eq!(secrets[0].as_dict().unwrap().len(), 2);
+
+        // Properties
+        let label = reply(
+            &mut service,
+            &chat,
+            PROPERTIES_INTERFACE,
+            "Get",
+            vec![
+                Value::String(ITEM_INTERFACE.into()),
+                Value::String("Label".into()),
+            ],
+        );
+        assert_eq!(label[0].unwrap_variant().as_str(), Some("Chat"));
+        let _ = reply(
+            &mut service,
+            &chat,
+            PROPERTIES_INTERFACE,
+            "Set",
+            vec![
+                Value::String(ITEM_INTERFACE.into()),
+                Value::String("Label".into()),
+                Value::Variant(Box::new(Value::String("Mail".into()))),
+            ],
+        );
+        let all = reply(
+            &mut service,
+            &chat,
+            PROPERTIES_INTERFACE,
+            "GetAll",
+            vec![Value::String(ITEM_INTERFACE.into())],
+        );
+        let all = all[0].as_dict().unwrap();
+        assert!(all
+            .iter()
+            .any(|(name, value)| name.as_str() == Some("Label")
+                && value.unwrap_variant().as_str() == Some("Mail (2)")));
+        let collection = reply(
+            &mut service,
+            COLLECTION,
+            PROPERTIES_INTERFACE,
+            "Get",
+            vec![
+                Value::String(COLLECTION_INTERFACE.into()),
+                Value::String("Items".into()),
+            ],
+        );
+        assert_eq!(paths(collection[0].unwrap_variant()).len(), 2);
+
+        // Items are removed, unless they are referenced.
+        let _ = reply(&mut service, &chat, ITEM_INTERFACE, "Delete", Vec::new());
+        assert_eq!(
+            error(&mut service, &chat, ITEM_INTERFACE, "Delete", Vec::new()),
+            "org.freedesktop.Secret.Error.NoSuchObject"
+        );
+        assert_eq!(service.vault.get_entries_of(&service.group).len(), 1);
+
+        // Closed sessions can't be used anymore.
+        let _ = reply(
+            &mut service,
+            &session,
+            "org.freedesktop.Secret.Session",
+            "Close",
+            Vec::new(),
+        );
+        assert_eq!(
+            error(
+                &mut service,
+                &mail,
+                ITEM_INTERFACE,
+                "GetSecret",
+                vec![Value::ObjectPath(session)]
+            ),
+            NO_SESSION
+        );
+
+        let xml = reply(
+            &mut service,
+            &mail,
+            INTROSPECTABLE_INTERFACE,
+            "Introspect",
+            Vec::new(),
+        );
+        assert!(xml[0].as_str().unwrap().contains(ITEM_INTERFACE));
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn lock_and_unlock() {
+        let dir = tempdir().unwrap();
+        let (vault, mem_key) = example_vault(dir.path());
+        let mut service =
+            SecretService::new(vault, mem_key, None, "/Email/Work", "pwduck").unwrap();
+        let session = reply(
+            &mut service,
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "OpenSession",
+            vec![
+                Value::String("plain".into()),
+                Value::Variant(Box::new(Value::String(String::new()))),
+            ],
+        )[1]
+        .as_str()
+        .unwrap()
+        .to_owned();
+
+        let locked = reply(
+            &mut service,
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "Lock",
+            vec![Value::object_paths(vec![COLLECTION.into()])],
+        );
+        assert_eq!(paths(&locked[0]), vec![COLLECTION.to_owned()]);
+        let found = reply(
+            &mut service,
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "SearchItems",
+            vec![attributes(&[])],
+        );
+        assert!(paths(&found[0]).is_empty());
+        let mail = paths(&found[1]).remove(0);
+        assert_eq!(
+            error(
+                &mut service,
+                &mail,
+                ITEM_INTERFACE,
+                "GetSecret",
+                vec![Value::ObjectPath(session.clone())]
+            ),
+            IS_LOCKED
+        );
+
+        // A dismissed prompt keeps the collection locked.
+        let unlock = |service: &mut SecretService| {
+            let unlocked = reply(
+                service,
+                SERVICE_PATH,
+                SERVICE_INTERFACE,
+                "Unlock",
+                vec![Value::object_paths(vec![COLLECTION.into()])],
+            );
+            assert!(paths(&unlocked[0]).is_empty());
+            unlocked[1].as_str().unwrap().to_owned()
+        };
+        let prompt = unlock(&mut service);
+        let messages = call(
+            &mut service,
+            &prompt,
+            PROMPT_INTERFACE,
+            "Dismiss",
+            Vec::new(),
+        );
+        assert_eq!(messages[1].member.as_deref(), Some("Completed"));
+        assert_eq!(messages[1].body[0].as_bool(), Some(true));
+
+        service.password_prompt = wrong_password;
+        let prompt = unlock(&mut service);
+        let _ = reply(
+            &mut service,
+            &prompt,
+            PROMPT_INTERFACE,
+            "Prompt",
+            vec![Value::String(String::new())],
+        );
+        let completed = service.show_prompts();
+        assert_eq!(completed[0].body[0].as_bool(), Some(true));
+        assert!(service.mem_key.is_none());
+
+        service.password_prompt = correct_password;
+        let prompt = unlock(&mut service);
+        let _ = reply(
+            &mut service,
+            &prompt,
+            PROMPT_INTERFACE,
+            "Prompt",
+            vec![Value::String(String::new())],
+        );
+        let completed = service.show_prompts();
+        assert_eq!(completed[0].path.as_deref(), Some(prompt.as_str()));
+        assert_eq!(completed[0].body[0].as_bool(), Some(false));
+        assert_eq!(
+            paths(completed[0].body[1].unwrap_variant()),
+            vec![COLLECTION.to_owned()]
+        );
+        assert_eq!(secret_of(&mut service, &mail, &session), "mail secret");
+
+        // The prompt is gone after its completion.
+        assert_eq!(
+            error(
+                &mut service,
+                &prompt,
+                PROMPT_INTERFACE,
+                "Dismiss",
+                Vec::new()
+            ),
+            "org.freedesktop.Secret.Error.NoSuchObject"
+        );
+    }
+}
@@ -0,0 +1,577 @@
+//! An agent offering the SSH keys of the entries of a vault to the SSH client.
+//!
+//! It speaks the protocol of `ssh-agent` over a Unix domain socket, so `ssh`, `ssh-add` and `git`
+//! use it through `SSH_AUTH_SOCK`. The keys are read from the entries when the vault is unlocked
+//! and again whenever it is changed on disk, and are only offered while the vault is unlocked.
+//! `ssh-add -x` and the idle timeout lock the vault, which removes all keys,
+//! and `ssh-add -X` unlocks it again with the password of the vault.
+//!
+//! RSA keys sign with SHA-256 or SHA-512 as requested by the client, the deprecated SHA-1 signatures are refused.
+//!
+//! Signing with a key of an entry requiring a confirmation asks the user with the program
+//! in `SSH_ASKPASS`, like `ssh-agent` does for keys added with `ssh-add -c`.
+use std::{
+    io::{Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use pwduck_core::{MemKey, MergeSide, Vault, VaultWatcher};
+use rsa::{
+    pkcs1v15::SigningKey, pkcs8::AssociatedOid, signature::digest::Digest, BigUint, RsaPrivateKey,
+};
+use signature::{SignatureEncoding, Signer};
+use ssh_key::{
+    private::{KeypairData, RsaKeypair},
+    sha2::{Sha256, Sha512},
+    Algorithm, HashAlg, Mpint, PrivateKey, Signature,
+};
+use zeroize::Zeroize;
+
+use crate::{
+    agent, commands::private_key, error::PWDuckCliError, input::confirm_with_askpass,
+    path::entry_path,
+};
+
+/// The reply to requests that failed or are not supported.
+const FAILURE: u8 = 5;
+/// The reply to successful requests without a result.
+const SUCCESS: u8 = 6;
+/// The request of the public keys.
+const REQUEST_IDENTITIES: u8 = 11;
+/// The reply to [`REQUEST_IDENTITIES`](REQUEST_IDENTITIES).
+const IDENTITIES_ANSWER: u8 = 12;
+/// The request of a signature.
+const SIGN_REQUEST: u8 = 13;
+/// The reply to [`SIGN_REQUEST`](SIGN_REQUEST).
+const SIGN_RESPONSE: u8 = 14;
+/// The request to lock the agent.
+const LOCK: u8 = 22;
+/// The request to unlock the agent.
+const UNLOCK: u8 = 23;
+
+/// The flag of sign requests asking for a `rsa-sha2-256` signature.
+const RSA_SHA2_256: u32 = 2;
+/// The flag of sign requests asking for a `rsa-sha2-512` signature.
+const RSA_SHA2_512: u32 = 4;
+
+/// The maximum length of a message, as accepted by `ssh-agent`.
+const MAX_MESSAGE_LENGTH: usize = 256 * 1024;
+
+/// The time to wait between looking for new connections.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The time a client may take to send its next request or to receive the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The location of the socket of the SSH agent serving the vault.
+/// It is placed next to the socket of the [agent](crate::agent).
+///
+/// # Errors
+///
+/// Returns `Err` if the vault does not exist or the directory of the sockets can't be used.
+pub fn socket_path(vault: &Path) -> Result<PathBuf, PWDuckCliError> {
+    agent::user_socket(vault, "ssh-agent")
+}
+
+/// A decrypted key of an entry with the path of the entry and whether its use must be confirmed.
+type AgentKey = (String, bool, PrivateKey);
+
+/// An SSH agent offering the SSH keys of the entries of a [`Vault`](Vault).
+#[derive(Debug)]
+pub struct SshAgent {
+    /// The [`Vault`](Vault) containing the keys.
+    vault: Vault,
+    /// The [`MemKey`](MemKey) protecting the master key of the [`Vault`](Vault) or `None`, if it is locked.
+    mem_key: Option<MemKey>,
+    /// The key file to unlock the [`Vault`](Vault) again.
+    key_file: Option<PathBuf>,
+    /// The time without sign requests after which the [`Vault`](Vault) is locked.
+    idle_timeout: Duration,
+    /// Ask the user whether the signature may be created.
+    confirmation: fn(&str) -> bool,
+    /// The watcher of the [`Vault`](Vault) on disk or `None`, if it can't be watched and is reloaded for every request.
+    watcher: Option<VaultWatcher>,
+    /// The decrypted keys sorted by the paths of their entries or `None`, if they must be loaded again.
+    keys: Option<Vec<AgentKey>>,
+}
+
+impl SshAgent {
+    /// Create a new [`SshAgent`](SshAgent) offering the keys of the unlocked [`Vault`](Vault).
+    ///
+    /// It expects:
+    ///  - The unlocked [`Vault`](Vault)
+    ///  - The [`MemKey`](MemKey) protecting the master key of the [`Vault`](Vault)
+    ///  - The key file needed to unlock the [`Vault`](Vault) again
+    ///  - The time without sign requests after which the [`Vault`](Vault) is locked
+    #[must_use]
+    pub fn new(
+        vault: Vault,
+        mem_key: MemKey,
+        key_file: Option<PathBuf>,
+        idle_timeout: Duration,
+    ) -> Self {
+        Self {
+            watcher: VaultWatcher::new(vault.path()).ok(),
+            vault,
+            mem_key: Some(mem_key),
+            key_file,
+            idle_timeout,
+            confirmation: confirm_with_askpass,
+            keys: None,
+        }
+    }
+
+    /// Serve the requests of the clients connecting to the listener.
+    /// The [`Vault`](Vault) is locked when the idle timeout expires, but the agent keeps running.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the listener fails. Failing clients are ignored.
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn serve(mut self, listener: &UnixListener) -> Result<(), PWDuckCliError> {
+        listener.set_nonblocking(true)?;
+        let mut last_use = Instant::now();
+
+        loop {
+            match listener.accept() {
+                Ok((stream, _address)) => {
+                    if matches!(self.serve_client(&stream), Ok(true)) {
+                        last_use = Instant::now();
+                    }
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                    if self.mem_key.is_some() && last_use.elapsed() >= self.idle_timeout {
+                        let _ = self.lock();
+                    }
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+    /// Answer the requests of the client until it closes the connection.
+    /// Returns true, if the client requested a signature.
+    #[cfg_attr(coverage, no_coverage)]
+    fn serve_client(&mut self, stream: &UnixStream) -> Result<bool, PWDuckCliError> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        let mut stream = stream;
+        let mut signed = false;
+
+        loop {
+            let mut length = [0_u8; 4];
+            match stream.read_exact(&mut length) {
+                Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    return Ok(signed)
+                }
+                result => result?,
+            }
+            let length = u32::from_be_bytes(length) as usize;
+            if length > MAX_MESSAGE_LENGTH {
+                return Ok(signed);
+            }
+
+            let mut request = vec![0_u8; length];
+            stream.read_exact(&mut request)?;
+            signed |= request.first() == Some(&SIGN_REQUEST);
+            let response = self.handle(&request);
+            request.zeroize();
+
+            stream.write_all(&(response.len() as u32).to_be_bytes())?;
+            stream.write_all(&response)?;
+        }
+    }
+
+    /// Answer the request, given without its length.
+    pub fn handle(&mut self, request: &[u8]) -> Vec<u8> {
+        if self.refresh().is_err() {
+            return vec![FAILURE];
+        }
+        let (message_type, payload) = match request.split_first() {
+            Some((message_type, payload)) => (*message_type, payload),
+            None => return vec![FAILURE],
+        };
+        let response = match message_type {
+            REQUEST_IDENTITIES => Some(self.identities()),
+            SIGN_REQUEST => self.sign(payload),
+            LOCK => self.lock(),
+            UNLOCK => self.unlock(payload),
+            _ => None,
+        };
+        response.unwrap_or_else(|| vec![FAILURE])
+    }
+
+    /// Take over the changes made on disk by other programs and load the keys again,
+    /// if the [`Vault`](Vault) is unlocked and was changed since the keys were loaded.
+    fn refresh(&mut self) -> Result<(), PWDuckCliError> {
+        let mem_key = match &self.mem_key {
+            Some(mem_key) => mem_key,
+            None => return Ok(()),
+        };
+        // The changes are consumed even if the keys are loaded anyway.
+        let changed = self.watcher.as_ref().map_or(true, |watcher| {
+            watcher.wait_for_change(Duration::ZERO).unwrap_or(true)
+        });
+        if self.keys.is_some() && !changed {
+            return Ok(());
+        }
+        self.keys = None;
+
+        let master_key = self.vault.master_key().as_unprotected(
+            mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        // The agent never changes the vault, so there are no local changes to keep.
+        let _ = self
+            .vault
+            .reload_external_changes(&master_key, Some(MergeSide::Remote))?;
+        drop(master_key);
+
+        self.keys = Some(load_keys(&self.vault, mem_key));
+        Ok(())
+    }
+
+    /// The decrypted keys of all entries sorted by their paths, none if the [`Vault`](Vault) is locked.
+    fn keys(&self) -> &[AgentKey] {
+        self.keys.as_deref().unwrap_or_default()
+    }
+
+    /// The public keys and their comments. Keys without a comment are named by their entries.
+    fn identities(&self) -> Vec<u8> {
+        let identities: Vec<_> = self
+            .keys()
+            .iter()
+            .filter_map(|(path, _confirmation, key)| {
+                let comment = if key.comment().is_empty() {
+                    path.clone()
+                } else {
+                    key.comment().to_owned()
+                };
+                key.public_key().to_bytes().ok().map(|blob| (blob, comment))
+            })
+            .collect();
+
+        let mut response = vec![IDENTITIES_ANSWER];
+        response.extend_from_slice(&(identities.len() as u32).to_be_bytes());
+        for (blob, comment) in identities {
+            put_string(&mut response, &blob);
+            put_string(&mut response, comment.as_bytes());
+        }
+        response
+    }
+
+    /// Sign the data with the key of the public key blob, after the user confirmed it if the entry requires it.
+    fn sign(&self, mut payload: &[u8]) -> Option<Vec<u8>> {
+        let blob = read_string(&mut payload)?;
+        let data = read_string(&mut payload)?;
+        let flags = read_u32(&mut payload)?;
+
+        let (path, confirmation, key) =
+            self.keys().iter().find(|(_path, _confirmation, key)| {
+                key.public_key()
+                    .to_bytes()
+                    .map_or(false, |other| other == blob)
+            })?;
+        if *confirmation
+            && !(self.confirmation)(&format!("Allow to sign with the SSH key of {}?", path))
+        {
+            return None;
+        }
+
+        let signature = match key.key_data() {
+            // The deprecated SHA-1 signatures requested without a flag are refused.
+            KeypairData::Rsa(keypair) if flags & RSA_SHA2_512 != 0 => {
+                sign_rsa::<Sha512>(keypair, data, HashAlg::Sha512)?
+            }
+            KeypairData::Rsa(keypair) if flags & RSA_SHA2_256 != 0 => {
+                sign_rsa::<Sha256>(keypair, data, HashAlg::Sha256)?
+            }
+            KeypairData::Rsa(_keypair) => return None,
+            _key_data => key.try_sign(data).ok()?,
+        };
+        let signature = Vec::<u8>::try_from(signature).ok()?;
+        let mut response = vec![SIGN_RESPONSE];
+        put_string(&mut response, &signature);
+        Some(response)
+    }
+
+    /// Lock the [`Vault`](Vault), which removes all keys. The passphrase is ignored.
+    fn lock(&mut self) -> Option<Vec<u8>> {
+        self.keys = None;
+        self.mem_key.take().map(|_mem_key| vec![SUCCESS])
+    }
+
+    /// Unlock the [`Vault`](Vault) with its password.
+    fn unlock(&mut self, mut payload: &[u8]) -> Option<Vec<u8>> {
+        if self.mem_key.is_some() {
+            return None;
+        }
+        let mut password = String::from_utf8(read_string(&mut payload)?.to_vec()).ok()?;
+        let mem_key = MemKey::new();
+        let vault = Vault::load(
+            &password,
+            self.key_file.clone(),
+            &mem_key,
+            self.vault.path().clone(),
+        );
+        password.zeroize();
+
+        self.vault = vault.ok()?;
+        self.mem_key = Some(mem_key);
+        self.keys = None;
+        Some(vec![SUCCESS])
+    }
+}
+
+/// Decrypt the keys of all entries and sort them by the paths of their entries.
+/// Keys that can't be decrypted are left out.
+fn load_keys(vault: &Vault, mem_key: &MemKey) -> Vec<AgentKey> {
+    let mut keys: Vec<_> = vault
+        .entries()
+        .values()
+        .filter_map(|entry| {
+            private_key(vault, mem_key, entry)
+                .ok()
+                .flatten()
+                .map(|key| (entry_path(vault, entry), entry.ssh_key_confirmation(), key))
+        })
+        .collect();
+    keys.sort_by(|a, b| a.0.cmp(&b.0));
+    keys
+}
+
+/// Sign the data with the RSA key using the hash requested by the client.
+/// The key is converted here, because the conversion of `ssh-key` passes the first prime twice.
+fn sign_rsa<D>(keypair: &RsaKeypair, data: &[u8], hash: HashAlg) -> Option<Signature>
+where
+    D: Digest + AssociatedOid,
+{
+    let number = |mpint: &Mpint| mpint.as_positive_bytes().map(BigUint::from_bytes_be);
+    let key = RsaPrivateKey::from_components(
+        number(&keypair.public.n)?,
+        number(&keypair.public.e)?,
+        number(&keypair.private.d)?,
+        vec![number(&keypair.private.p)?, number(&keypair.private.q)?],
+    )
+    .ok()?;
+    let signature = SigningKey::<D>::new(key).try_sign(data).ok()?;
+    Signature::new(Algorithm::Rsa { hash: Some(hash) }, signature.to_vec()).ok()
+}
+
+/// Read a big-endian `uint32` from the front of the input.
+fn read_u32(input: &mut &[u8]) -> Option<u32> {
+    if input.len() < 4 {
+        return None;
+    }
+    let (value, rest) = input.split_at(4);
+    *input = rest;
+    let mut bytes = [0_u8; 4];
+    bytes.copy_from_slice(value);
+    Some(u32::from_be_bytes(bytes))
+}
+
+/// Read a `string`, i.e. bytes prefixed by their length, from the front of the input.
+fn read_string<'a>(input: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let length = read_u32(input)? as usize;
+    if input.len() < length {
+        return None;
+    }
+    let (value, rest) = input.split_at(length);
+    *input = rest;
+    Some(value)
+}
+
+/// Append the bytes as a `string` to the output.
+fn put_string(output: &mut Vec<u8>, value: &[u8]) {
+    output.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    output.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, thread, time::Duration};
+
+    use signature::Verifier;
+    use ssh_key::{Algorithm, HashAlg, PublicKey, Signature};
+    use tempfile::tempdir;
+
+    use crate::{
+        args::{Command, SshKeyCommand},
+        commands::tests::{example_vault, output, reload, PASSWORD},
+    };
+
+    use super::{
+        put_string, read_string, read_u32, SshAgent, FAILURE, IDENTITIES_ANSWER, LOCK,
+        REQUEST_IDENTITIES, RSA_SHA2_256, RSA_SHA2_512, SIGN_REQUEST, SIGN_RESPONSE, SUCCESS,
+        UNLOCK,
+    };
+
+    fn identities(agent: &mut SshAgent) -> Vec<(Vec<u8>, String)> {
+        let response = agent.handle(&[REQUEST_IDENTITIES]);
+        assert_eq!(response[0], IDENTITIES_ANSWER);
+        let mut payload = &response[1..];
+        let count = read_u32(&mut payload).unwrap();
+        let identities = (0..count)
+            .map(|_| {
+                let blob = read_string(&mut payload).unwrap().to_vec();
+                let comment = read_string(&mut payload).unwrap();
+                (blob, String::from_utf8(comment.to_vec()).unwrap())
+            })
+            .collect();
+        assert!(payload.is_empty());
+        identities
+    }
+
+    fn sign_request(blob: &[u8], data: &[u8], flags: u32) -> Vec<u8> {
+        let mut request = vec![SIGN_REQUEST];
+        put_string(&mut request, blob);
+        put_string(&mut request, data);
+        request.extend_from_slice(&flags.to_be_bytes());
+        request
+    }
+
+    fn message(message_type: u8, value: &str) -> Vec<u8> {
+        let mut request = vec![message_type];
+        put_string(&mut request, value.as_bytes());
+        request
+    }
+
+    #[test]
+    fn ssh_agent() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+        for (entry, confirm) in [("/Bank", true), ("/Email/Work/Mail", false)] {
+            let _ = output(
+                &Command::SshKey(SshKeyCommand::Generate {
+                    entry: entry.into(),
+                    comment: Some(String::new()),
+                    encrypt: entry == "/Bank",
+                    confirm,
+                    force: false,
+                }),
+                &mut vault,
+                &mem_key,
+            )
+            .unwrap();
+        }
+        let mut agent = SshAgent::new(vault, mem_key, None, Duration::from_secs(30));
+        agent.confirmation = |prompt| {
+            assert_eq!(prompt, "Allow to sign with the SSH key of /Bank?");
+            false
+        };
+
+        let keys = identities(&mut agent);
+        let comments: Vec<_> = keys
+            .iter()
+            .map(|(_blob, comment)| comment.as_str())
+            .collect();
+        assert_eq!(comments, ["/Bank", "/Email/Work/Mail"]);
+
+        // The key of the mail entry needs no confirmation.
+        let response = agent.handle(&sign_request(&keys[1].0, b"data", 0));
+        assert_eq!(response[0], SIGN_RESPONSE);
+        let signature = Signature::try_from(read_string(&mut &response[1..]).unwrap()).unwrap();
+        let public_key = PublicKey::from_bytes(&keys[1].0).unwrap();
+        assert!(Verifier::verify(&public_key, b"data", &signature).is_ok());
+        assert!(Verifier::verify(&public_key, b"other", &signature).is_err());
+
+        // The user refuses the key of the bank entry, unknown keys and malformed requests fail.
+        assert_eq!(
+            agent.handle(&sign_request(&keys[0].0, b"data", 0)),
+            [FAILURE]
+        );
+        assert_eq!(
+            agent.handle(&sign_request(b"unknown", b"data", 0)),
+            [FAILURE]
+        );
+        assert_eq!(agent.handle(&[SIGN_REQUEST, 0, 0]), [FAILURE]);
+        assert_eq!(agent.handle(&[]), [FAILURE]);
+        assert_eq!(agent.handle(&[17]), [FAILURE]);
+        agent.confirmation = |_prompt| true;
+        assert_eq!(
+            agent.handle(&sign_request(&keys[0].0, b"data", 0))[0],
+            SIGN_RESPONSE
+        );
+
+        // Locking removes all keys until the vault is unlocked with its password.
+        assert_eq!(agent.handle(&message(UNLOCK, PASSWORD)), [FAILURE]);
+        assert_eq!(agent.handle(&message(LOCK, "ignored")), [SUCCESS]);
+        assert_eq!(agent.handle(&message(LOCK, "ignored")), [FAILURE]);
+        assert!(identities(&mut agent).is_empty());
+        assert_eq!(
+            agent.handle(&sign_request(&keys[1].0, b"data", 0)),
+            [FAILURE]
+        );
+        assert_eq!(agent.handle(&message(UNLOCK, "wrong")), [FAILURE]);
+        assert_eq!(agent.handle(&message(UNLOCK, PASSWORD)), [SUCCESS]);
+        assert_eq!(identities(&mut agent), keys);
+    }
+
+    #[test]
+    fn rsa_and_changes_on_disk() {
+        let dir = tempdir().unwrap();
+        let (mut vault, mem_key) = example_vault(dir.path());
+        let _ = output(
+            &Command::SshKey(SshKeyCommand::Import {
+                entry: "/Bank".into(),
+                file: Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources/id_rsa"),
+                confirm: false,
+                force: false,
+            }),
+            &mut vault,
+            &mem_key,
+        )
+        .unwrap();
+        let (mut other, other_mem_key) = reload(&vault);
+        let mut agent = SshAgent::new(vault, mem_key, None, Duration::from_secs(30));
+
+        let keys = identities(&mut agent);
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].1, "rsa@example.com");
+        let public_key = PublicKey::from_bytes(&keys[0].0).unwrap();
+
+        // The hash requested by the client is used, the deprecated SHA-1 signatures are refused.
+        for (flags, hash) in [
+            (RSA_SHA2_256, HashAlg::Sha256),
+            (RSA_SHA2_512, HashAlg::Sha512),
+        ] {
+            let response = agent.handle(&sign_request(&keys[0].0, b"data", flags));
+            assert_eq!(response[0], SIGN_RESPONSE);
+            let signature = Signature::try_from(read_string(&mut &response[1..]).unwrap()).unwrap();
+            assert_eq!(signature.algorithm(), Algorithm::Rsa { hash: Some(hash) });
+            assert!(Verifier::verify(&public_key, b"data", &signature).is_ok());
+        }
+        assert_eq!(
+            agent.handle(&sign_request(&keys[0].0, b"data", 0)),
+            [FAILURE]
+        );
+
+        // The keys are loaded again, after another program changed the vault.
+        let _ = output(
+            &Command::SshKey(SshKeyCommand::Generate {
+                entry: "/Email/Work/Mail".into(),
+                comment: None,
+                encrypt: false,
+                confirm: false,
+                force: false,
+            }),
+            &mut other,
+            &other_mem_key,
+        )
+        .unwrap();
+        let mut keys = identities(&mut agent);
+        for _ in 0..50 {
+            if keys.len() == 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+            keys = identities(&mut agent);
+        }
+        assert_eq!(keys.len(), 2);
+    }
+}
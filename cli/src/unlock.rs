@@ -0,0 +1,36 @@
+//! Unlock the vault given by the arguments.
+use std::io::BufRead;
+
+use pwduck_core::{MemKey, Vault};
+use zeroize::Zeroize;
+
+use crate::{args::UnlockArgs, error::PWDuckCliError, input::read_secret};
+
+/// Unlock the [`Vault`](Vault) with the password and the optional key file.
+///
+/// It expects:
+///  - The [`UnlockArgs`](UnlockArgs) containing the location of the [`Vault`](Vault)
+///  - The [`MemKey`](MemKey) to encrypt the master key in memory
+///  - The input to read the password from, if it is not read from the terminal
+///
+/// # Errors
+///
+/// Returns `Err` if the password can't be read or the [`Vault`](Vault) can't be unlocked.
+#[cfg_attr(coverage, no_coverage)]
+pub fn unlock(
+    args: &UnlockArgs,
+    mem_key: &MemKey,
+    input: &mut dyn BufRead,
+) -> Result<Vault, PWDuckCliError> {
+    let mut password = read_secret("Password: ", args.password_stdin, input)?;
+
+    let vault = Vault::load(
+        &password,
+        args.key_file.clone(),
+        mem_key,
+        args.vault.clone(),
+    );
+    password.zeroize();
+
+    Ok(vault?)
+}
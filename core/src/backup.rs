@@ -0,0 +1,306 @@
+//! Pack the files of a vault into a single authenticated backup archive and unpack it again.
+//!
+//! The archive starts with a signature and the version of the format, followed by the
+//! file of the master key, the compressed other files of the vault and an HMAC-SHA-256 of everything before it.
+//! The files of the vault are already encrypted, the key of the HMAC is derived from the master key.
+//! The file of the master key is stored uncompressed to verify the archive before decompressing it.
+use std::{
+    convert::TryFrom,
+    io::{Read, Write},
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use hmac::{Hmac, Mac, NewMac};
+use sha2::{Digest, Sha256};
+
+use crate::{error::PWDuckCoreError, io::MASTER_KEY_NAME, mem_protection::SecVec};
+
+/// The file extension of backup archives.
+pub const BACKUP_EXTENSION: &str = "pwduck-backup";
+
+/// The signature at the start of every backup archive.
+const SIGNATURE: &[u8; 8] = b"PWDUCKBK";
+
+/// The version of the written archive format.
+const VERSION: u16 = 1;
+
+/// The length of the HMAC at the end of the archive.
+const HMAC_LENGTH: usize = 32;
+
+/// The context used to derive the key of the HMAC from the master key.
+const HMAC_CONTEXT: &[u8] = b"PWDuck backup archive";
+
+/// The maximum size of the decompressed files of an archive.
+const MAX_PAYLOAD_SIZE: u64 = 256 * 1024 * 1024;
+
+/// A file of a vault stored in a backup archive.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BackupFile {
+    /// The path of the file relative to the vault directory, separated by `/`.
+    pub path: String,
+    /// The content of the file.
+    pub data: Vec<u8>,
+}
+
+/// A parsed backup archive whose authenticity is not verified yet.
+#[derive(Debug)]
+pub struct Backup<'a> {
+    /// The signed part of the archive.
+    signed: &'a [u8],
+    /// The HMAC of the signed part.
+    hmac: &'a [u8],
+    /// The content of the file of the master key.
+    master_key: &'a [u8],
+    /// The compressed other files of the vault.
+    payload: &'a [u8],
+}
+
+impl<'a> Backup<'a> {
+    /// Parse the backup archive. The compressed files are not decompressed before the archive is verified.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the data is not a backup archive of a supported version or if it is corrupted.
+    pub fn parse(data: &'a [u8]) -> Result<Self, PWDuckCoreError> {
+        if data.len() < SIGNATURE.len() + 2 + HMAC_LENGTH || !data.starts_with(SIGNATURE) {
+            return Err(PWDuckCoreError::Error(
+                "The file is not a backup of a vault".into(),
+            ));
+        }
+        let (signed, hmac) = data.split_at(data.len() - HMAC_LENGTH);
+
+        let mut version = [0_u8; 2];
+        version.copy_from_slice(&signed[SIGNATURE.len()..SIGNATURE.len() + 2]);
+        let version = u16::from_le_bytes(version);
+        if version != VERSION {
+            return Err(PWDuckCoreError::Error(format!(
+                "The version {} of the backup is not supported",
+                version
+            )));
+        }
+
+        let mut position = SIGNATURE.len() + 2;
+        let master_key_length = read_u32(signed, &mut position)? as usize;
+        let master_key = read_bytes(signed, &mut position, master_key_length)?;
+
+        Ok(Self {
+            signed,
+            hmac,
+            master_key,
+            payload: &signed[position..],
+        })
+    }
+
+    /// The content of the file of the master key, needed to derive the key to verify the archive.
+    #[must_use]
+    pub const fn master_key(&self) -> &'a [u8] {
+        self.master_key
+    }
+
+    /// Verify the authenticity of the archive and return the contained files.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - The archive was not created with the given master key or if it was modified.
+    /// - The decompressed files are larger than [`MAX_PAYLOAD_SIZE`](MAX_PAYLOAD_SIZE).
+    pub fn verify(self, master_key: &[u8]) -> Result<Vec<BackupFile>, PWDuckCoreError> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&hmac_key(master_key))
+            .map_err(|error| PWDuckCoreError::Error(error.to_string()))?;
+        mac.update(self.signed);
+        mac.verify(self.hmac).map_err(|_error| {
+            PWDuckCoreError::Error("Wrong password or the backup is corrupted".into())
+        })?;
+
+        let payload = decompress(self.payload, MAX_PAYLOAD_SIZE)?;
+        let mut files = vec![BackupFile {
+            path: MASTER_KEY_NAME.into(),
+            data: self.master_key.to_vec(),
+        }];
+        files.append(&mut read_files(&payload)?);
+        Ok(files)
+    }
+}
+
+/// Pack the files into a backup archive authenticated with the given master key.
+///
+/// # Errors
+///
+/// Returns `Err` if the files contain no master key, a file is too large or the archive can't be compressed.
+pub fn write_backup(files: &[BackupFile], master_key: &[u8]) -> Result<Vec<u8>, PWDuckCoreError> {
+    let master_key_file = files
+        .iter()
+        .find(|file| file.path == MASTER_KEY_NAME)
+        .ok_or_else(|| PWDuckCoreError::Error("The vault has no master key".into()))?;
+    let others: Vec<&BackupFile> = files
+        .iter()
+        .filter(|file| file.path != MASTER_KEY_NAME)
+        .collect();
+
+    let mut payload = Vec::new();
+    write_length(&mut payload, others.len())?;
+    for file in others {
+        write_length(&mut payload, file.path.len())?;
+        payload.extend_from_slice(file.path.as_bytes());
+        payload.extend_from_slice(&(file.data.len() as u64).to_le_bytes());
+        payload.extend_from_slice(&file.data);
+    }
+
+    let mut archive = SIGNATURE.to_vec();
+    archive.extend_from_slice(&VERSION.to_le_bytes());
+    write_length(&mut archive, master_key_file.data.len())?;
+    archive.extend_from_slice(&master_key_file.data);
+    let mut encoder = GzEncoder::new(archive, Compression::default());
+    encoder.write_all(&payload)?;
+    let mut archive = encoder.finish()?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&hmac_key(master_key))
+        .map_err(|error| PWDuckCoreError::Error(error.to_string()))?;
+    mac.update(&archive);
+    archive.extend_from_slice(&mac.finalize().into_bytes());
+    Ok(archive)
+}
+
+/// Derive the key of the HMAC from the master key.
+fn hmac_key(master_key: &[u8]) -> SecVec<u8> {
+    Sha256::new()
+        .chain(HMAC_CONTEXT)
+        .chain(master_key)
+        .finalize()
+        .to_vec()
+        .into()
+}
+
+/// Decompress the data, but not more than the given number of bytes.
+fn decompress(data: &[u8], limit: u64) -> Result<Vec<u8>, PWDuckCoreError> {
+    let mut decompressed = Vec::new();
+    let _ = GzDecoder::new(data)
+        .take(limit + 1)
+        .read_to_end(&mut decompressed)?;
+    if decompressed.len() as u64 > limit {
+        return Err(PWDuckCoreError::Error("The backup is too large".into()));
+    }
+    Ok(decompressed)
+}
+
+/// Write a length as an unsigned 32 bit integer.
+fn write_length(data: &mut Vec<u8>, length: usize) -> Result<(), PWDuckCoreError> {
+    let length = u32::try_from(length)
+        .map_err(|_err| PWDuckCoreError::Error("The vault is too large for a backup".into()))?;
+    data.extend_from_slice(&length.to_le_bytes());
+    Ok(())
+}
+
+/// Read the files of the decompressed payload.
+fn read_files(payload: &[u8]) -> Result<Vec<BackupFile>, PWDuckCoreError> {
+    let mut position = 0;
+
+    let count = read_u32(payload, &mut position)?;
+    let mut files = Vec::new();
+    for _ in 0..count {
+        let path_length = read_u32(payload, &mut position)? as usize;
+        let path = String::from_utf8(read_bytes(payload, &mut position, path_length)?.to_vec())
+            .map_err(|_err| PWDuckCoreError::Error("The backup contains an invalid path".into()))?;
+        let mut data_length = [0_u8; 8];
+        data_length.copy_from_slice(read_bytes(payload, &mut position, 8)?);
+        let data_length = usize::try_from(u64::from_le_bytes(data_length))
+            .map_err(|_err| PWDuckCoreError::Error("The backup is too large".into()))?;
+        let data = read_bytes(payload, &mut position, data_length)?.to_vec();
+        files.push(BackupFile { path, data });
+    }
+    Ok(files)
+}
+
+/// Read the next bytes with the given length and advance the position.
+fn read_bytes<'a>(
+    payload: &'a [u8],
+    position: &mut usize,
+    length: usize,
+) -> Result<&'a [u8], PWDuckCoreError> {
+    let end = position
+        .checked_add(length)
+        .filter(|end| *end <= payload.len())
+        .ok_or_else(|| PWDuckCoreError::Error("The backup ends unexpectedly".into()))?;
+    let bytes = &payload[*position..end];
+    *position = end;
+    Ok(bytes)
+}
+
+/// Read the next unsigned 32 bit integer and advance the position.
+fn read_u32(payload: &[u8], position: &mut usize) -> Result<u32, PWDuckCoreError> {
+    let mut value = [0_u8; 4];
+    value.copy_from_slice(read_bytes(payload, position, 4)?);
+    Ok(u32::from_le_bytes(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::{decompress, write_backup, Backup, BackupFile, HMAC_LENGTH};
+
+    fn files() -> Vec<BackupFile> {
+        vec![
+            BackupFile {
+                path: "master_key.pwduck".into(),
+                data: b"(salt: \"SALT\")".to_vec(),
+            },
+            BackupFile {
+                path: "entries/head/aGVhZA==".into(),
+                data: vec![0, 1, 2, 3],
+            },
+            BackupFile {
+                path: "groups/empty".into(),
+                data: Vec::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn write_and_read_backup() {
+        let master_key = [42_u8; 32];
+        let archive = write_backup(&files(), &master_key).unwrap();
+        assert!(archive.starts_with(b"PWDUCKBK"));
+
+        let backup = Backup::parse(&archive).unwrap();
+        assert_eq!(backup.master_key(), b"(salt: \"SALT\")");
+        assert_eq!(backup.verify(&master_key).unwrap(), files());
+
+        let backup = Backup::parse(&archive).unwrap();
+        assert!(backup.verify(&[0_u8; 32]).is_err());
+    }
+
+    #[test]
+    fn reject_invalid_backup() {
+        let master_key = [42_u8; 32];
+        let archive = write_backup(&files(), &master_key).unwrap();
+
+        assert!(Backup::parse(b"no backup").is_err());
+        assert!(Backup::parse(&archive[..12 + HMAC_LENGTH]).is_err());
+        assert!(write_backup(&files()[1..], &master_key).is_err());
+
+        let mut version = archive.clone();
+        version[8] = 2;
+        assert!(Backup::parse(&version).is_err());
+
+        let mut modified = archive;
+        let last = modified.len() - 1;
+        modified[last] ^= 1;
+        assert!(Backup::parse(&modified)
+            .unwrap()
+            .verify(&master_key)
+            .is_err());
+    }
+
+    #[test]
+    fn limit_decompression() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[0_u8; 2048]).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(decompress(&compressed, 2048).unwrap().len(), 2048);
+        assert!(decompress(&compressed, 1024).is_err());
+    }
+}
@@ -0,0 +1,318 @@
+//! Confirmations requested by other programs, e.g. the native messaging host, and answered by the user in the GUI.
+//!
+//! The GUI listens on a Unix domain socket for each open vault. A program connects to the socket of the vault,
+//! sends the prompt as one line of JSON and receives the answer of the user as one line of JSON.
+//! A connection closed without an answer counts as a refusal.
+//!
+//! The sockets live in a directory only accessible by the current user and are derived from the location of the vault,
+//! like the sockets of the programs of the CLI.
+use std::{
+    fs::{self, DirBuilder},
+    io::{BufRead, BufReader, Read, Write},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{DirBuilderExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::PWDuckCoreError;
+
+/// The kind of the socket of the GUI answering confirmations.
+pub const CONFIRMATION_SOCKET: &str = "gui";
+
+/// The permissions of the directory containing the sockets.
+const DIRECTORY_MODE: u32 = 0o700;
+
+/// The permissions of the socket.
+const SOCKET_MODE: u32 = 0o600;
+
+/// The interval to check for new connections.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The time a program may take to send its prompt.
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximum length of a prompt.
+const MAX_PROMPT_LENGTH: u64 = 4096;
+
+/// The prompt sent to the GUI.
+#[derive(Debug, Deserialize, Serialize)]
+struct Prompt {
+    /// The question shown to the user.
+    prompt: String,
+}
+
+/// The answer of the user sent back by the GUI.
+#[derive(Debug, Deserialize, Serialize)]
+struct Answer {
+    /// True, if the user confirmed the prompt.
+    confirmed: bool,
+}
+
+/// The directory containing the sockets serving the vaults.
+///
+/// It is placed in the runtime directory of the user, if there is one.
+/// Otherwise it is placed in a directory of the user inside of the temporary directory.
+#[must_use]
+pub fn socket_directory() -> PathBuf {
+    dirs::runtime_dir().map_or_else(
+        || {
+            let user = std::env::var("USER").unwrap_or_else(|_err| "user".into());
+            std::env::temp_dir().join(format!("pwduck-{}", user))
+        },
+        |runtime| runtime.join("pwduck"),
+    )
+}
+
+/// A short identifier of the vault derived from its canonical location.
+///
+/// # Errors
+///
+/// Returns `Err` if the vault does not exist.
+pub fn vault_id(vault: &Path) -> Result<String, PWDuckCoreError> {
+    let vault = fs::canonicalize(vault)?;
+    let hash = Sha256::digest(vault.as_os_str().as_bytes());
+    Ok(hash[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .concat())
+}
+
+/// The location of the socket of the kind serving the vault inside of the [`socket_directory`](socket_directory).
+///
+/// # Errors
+///
+/// Returns `Err` if the vault does not exist.
+pub fn socket_path(vault: &Path, kind: &str) -> Result<PathBuf, PWDuckCoreError> {
+    Ok(socket_directory().join(format!("{}-{}.sock", kind, vault_id(vault)?)))
+}
+
+/// Ask the user in the GUI listening on the socket for a confirmation. Blocks until the user answered.
+///
+/// It expects:
+///  - The [`Path`](Path) of the socket, see: [`socket_path`](socket_path)
+///  - The prompt shown to the user
+///
+/// # Errors
+///
+/// Returns `Err` if no GUI is listening on the socket or the connection failed.
+pub fn request_confirmation(socket: &Path, prompt: &str) -> Result<bool, PWDuckCoreError> {
+    let mut stream = UnixStream::connect(socket)?;
+    let mut request = serde_json::to_vec(&Prompt {
+        prompt: prompt.to_owned(),
+    })?;
+    request.push(b'\n');
+    stream.write_all(&request)?;
+
+    let mut line = String::new();
+    let _ = BufReader::new(stream).read_line(&mut line)?;
+    if line.is_empty() {
+        return Ok(false);
+    }
+    Ok(serde_json::from_str::<Answer>(&line)?.confirmed)
+}
+
+/// The socket of the GUI answering the confirmations requested for a vault.
+/// The socket is removed when the listener is dropped.
+#[derive(Debug)]
+pub struct ConfirmationListener {
+    /// The listener of the socket.
+    listener: UnixListener,
+    /// The location of the socket.
+    path: PathBuf,
+}
+
+impl ConfirmationListener {
+    /// Listen for the confirmations requested for the [`Vault`](crate::Vault), only accessible by the current user.
+    /// A socket left behind by a crashed GUI is replaced.
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the [`Vault`](crate::Vault)
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - The vault does not exist.
+    /// - The directory of the sockets is accessible by other users.
+    /// - Another GUI is listening for the vault or the socket can't be bound.
+    pub fn bind(vault: &Path) -> Result<Self, PWDuckCoreError> {
+        let path = socket_path(vault, CONFIRMATION_SOCKET)?;
+        Self::bind_socket(path)
+    }
+
+    /// Listen on the socket at the given location, see: [`bind`](ConfirmationListener::bind).
+    fn bind_socket(path: PathBuf) -> Result<Self, PWDuckCoreError> {
+        if let Some(directory) = path.parent() {
+            if let Err(error) = DirBuilder::new().mode(DIRECTORY_MODE).create(directory) {
+                if error.kind() != std::io::ErrorKind::AlreadyExists {
+                    return Err(error.into());
+                }
+            }
+            // A directory of another user can't be written, so only the permissions are checked.
+            if fs::metadata(directory)?.permissions().mode() & 0o077 != 0 {
+                return Err(PWDuckCoreError::Error(format!(
+                    "The directory {} is accessible by other users",
+                    directory.display()
+                )));
+            }
+        }
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(PWDuckCoreError::Error(format!(
+                    "Another program is listening on {}",
+                    path.display()
+                )));
+            }
+            fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(SOCKET_MODE))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self { listener, path })
+    }
+
+    /// Wait for the next confirmation requested by another program.
+    ///
+    /// Returns `None` if nothing was requested within the timeout.
+    /// Connections not sending a valid prompt in time are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the socket failed.
+    pub fn accept(&self, timeout: Duration) -> Result<Option<Confirmation>, PWDuckCoreError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _address)) => {
+                    if let Ok(confirmation) = Confirmation::receive(stream) {
+                        return Ok(Some(confirmation));
+                    }
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Ok(None);
+                    }
+                    thread::sleep(remaining.min(ACCEPT_INTERVAL));
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+}
+
+impl Drop for ConfirmationListener {
+    fn drop(&mut self) {
+        drop(fs::remove_file(&self.path));
+    }
+}
+
+/// A confirmation requested by another program. Dropping it unanswered refuses the request.
+#[derive(Debug)]
+pub struct Confirmation {
+    /// The question shown to the user.
+    prompt: String,
+    /// The connection to the requesting program.
+    stream: UnixStream,
+}
+
+impl Confirmation {
+    /// Read the prompt of the requesting program.
+    fn receive(stream: UnixStream) -> Result<Self, PWDuckCoreError> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(PROMPT_TIMEOUT))?;
+        let mut line = String::new();
+        let _ = BufReader::new((&stream).take(MAX_PROMPT_LENGTH)).read_line(&mut line)?;
+        let prompt = serde_json::from_str::<Prompt>(&line)?.prompt;
+        Ok(Self { prompt, stream })
+    }
+
+    /// The question shown to the user.
+    #[must_use]
+    pub fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    /// Send the answer of the user to the requesting program.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the program closed the connection.
+    pub fn answer(mut self, confirmed: bool) -> Result<(), PWDuckCoreError> {
+        let mut answer = serde_json::to_vec(&Answer { confirmed })?;
+        answer.push(b'\n');
+        self.stream.write_all(&answer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, os::unix::net::UnixStream, time::Duration};
+
+    use tempfile::tempdir;
+
+    use super::{request_confirmation, socket_path, vault_id, ConfirmationListener};
+
+    #[test]
+    fn socket_location() {
+        let dir = tempdir().unwrap();
+        let id = vault_id(dir.path()).unwrap();
+        assert_eq!(id.len(), 16);
+        assert_eq!(vault_id(&dir.path().join(".")).unwrap(), id);
+        assert!(socket_path(dir.path(), "gui")
+            .unwrap()
+            .ends_with(format!("gui-{}.sock", id)));
+        assert!(vault_id(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn confirmation() {
+        let dir = tempdir().unwrap();
+        let socket = dir.path().join("sockets").join("gui.sock");
+        assert!(request_confirmation(&socket, "Allow?").is_err());
+
+        let listener = ConfirmationListener::bind_socket(socket.clone()).unwrap();
+        assert!(ConfirmationListener::bind_socket(socket.clone()).is_err());
+        assert!(listener.accept(Duration::ZERO).unwrap().is_none());
+
+        let requests = {
+            let socket = socket.clone();
+            std::thread::spawn(move || {
+                // Invalid prompts are dropped without an answer.
+                let mut invalid = UnixStream::connect(&socket).unwrap();
+                invalid.write_all(b"{\n").unwrap();
+                [
+                    request_confirmation(&socket, "Allow the first?").unwrap(),
+                    request_confirmation(&socket, "Allow the second?").unwrap(),
+                    request_confirmation(&socket, "Allow the third?").unwrap(),
+                ]
+            })
+        };
+        let timeout = Duration::from_secs(10);
+        let first = listener.accept(timeout).unwrap().unwrap();
+        assert_eq!(first.prompt(), "Allow the first?");
+        first.answer(true).unwrap();
+        let second = listener.accept(timeout).unwrap().unwrap();
+        assert_eq!(second.prompt(), "Allow the second?");
+        second.answer(false).unwrap();
+        drop(listener.accept(timeout).unwrap().unwrap());
+        assert_eq!(requests.join().unwrap(), [true, false, false]);
+
+        // The socket is removed with the listener, a socket left behind is replaced.
+        drop(listener);
+        assert!(!socket.exists());
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        assert!(socket.exists());
+        drop(ConfirmationListener::bind_socket(socket).unwrap());
+    }
+}
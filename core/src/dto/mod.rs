@@ -8,3 +8,7 @@ pub mod group;
 pub mod key_file;
 
 pub mod master_key;
+
+pub mod saved_search;
+
+pub mod vault_settings;
@@ -0,0 +1,36 @@
+//! The data-transfer-objects of the saved searches.
+//!
+//! It represents the encrypted [`SavedSearch`](crate::model::saved_search::SavedSearch) that
+//! is stored on disk.
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+/// An encrypted [`SavedSearch`] as a data-transfer-object (dto).
+#[derive(Clone, Debug, Deserialize, Serialize, Getters)]
+pub struct SavedSearch {
+    /// The iv used to encrypt this saved search.
+    #[getset(get = "pub")]
+    iv: String,
+
+    /// The encrypted content of this saved search.
+    #[getset(get = "pub")]
+    content: String,
+}
+
+impl SavedSearch {
+    /// Create a new [`SavedSearch`](SavedSearch).
+    pub const fn new(iv: String, content: String) -> Self {
+        Self { iv, content }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SavedSearch;
+
+    #[test]
+    fn new_saved_search() {
+        let saved_search = SavedSearch::new("IV".into(), "CONTENT".into());
+        assert_eq!(saved_search.iv(), "IV");
+        assert_eq!(saved_search.content(), "CONTENT");
+    }
+}
@@ -0,0 +1,36 @@
+//! The data-transfer-objects of the vault settings.
+//!
+//! It represents the encrypted [`VaultSettings`](crate::model::vault_settings::VaultSettings)
+//! that are stored on disk.
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+/// The encrypted [`VaultSettings`] as a data-transfer-object (dto).
+#[derive(Clone, Debug, Deserialize, Serialize, Getters)]
+pub struct VaultSettings {
+    /// The iv used to encrypt the vault settings.
+    #[getset(get = "pub")]
+    iv: String,
+
+    /// The encrypted content of the vault settings.
+    #[getset(get = "pub")]
+    content: String,
+}
+
+impl VaultSettings {
+    /// Create a new [`VaultSettings`](VaultSettings).
+    pub const fn new(iv: String, content: String) -> Self {
+        Self { iv, content }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VaultSettings;
+
+    #[test]
+    fn new_vault_settings() {
+        let vault_settings = VaultSettings::new("IV".into(), "CONTENT".into());
+        assert_eq!(vault_settings.iv(), "IV");
+        assert_eq!(vault_settings.content(), "CONTENT");
+    }
+}
@@ -12,18 +12,30 @@ pub enum PWDuckCoreError {
     BlockMode(block_modes::BlockModeError),
     /// Error thrown by an invalid length for the AES IV.
     BlockModeIV(block_modes::InvalidKeyIvLength),
+    /// Saving was refused, because the items with the UUIDs were changed on disk by another program.
+    Conflict(Vec<crate::Uuid>),
+    /// Reading a CSV file failed.
+    Csv(csv::Error),
     /// A generic error.
     Error(String),
     /// Reading or writing a file failed.
     IO(std::io::Error),
+    /// Serializing or deserializing with JSON failed.
+    Json(serde_json::Error),
     /// Locking a mutex failed.
     Mutex(String),
+    /// Resolving a reference to the field of another entry failed.
+    Reference(ReferenceError),
     /// Serializing or deserializing with RON failed.
     Ron(ron::Error),
     /// An error from the autotype sequence parser.
     SequenceParseError(SequenceParseError),
     /// Wrong UFT8 encoding.
     Utf8(std::string::FromUtf8Error),
+    /// Watching the vault directory for changes failed.
+    Watch(notify::Error),
+    /// Parsing a XML document failed.
+    Xml(roxmltree::Error),
 }
 
 /// An error from Argon2.
@@ -43,12 +55,18 @@ impl Clone for PWDuckCoreError {
             Self::Base64(error) => Self::Base64(error.clone()),
             Self::BlockMode(error) => Self::BlockMode(*error),
             Self::BlockModeIV(error) => Self::BlockModeIV(*error),
+            Self::Conflict(uuids) => Self::Conflict(uuids.clone()),
+            Self::Csv(error) => Self::Error(format!("{}", error)),
             Self::Error(error) => Self::Error(error.clone()),
             Self::IO(error) => Self::Error(format!("{}", error)),
+            Self::Json(error) => Self::Error(format!("{}", error)),
             Self::Mutex(error) => Self::Mutex(error.clone()),
+            Self::Reference(error) => Self::Reference(error.clone()),
             Self::Ron(error) => Self::Ron(error.clone()),
             Self::SequenceParseError(error) => Self::SequenceParseError(error.clone()),
             Self::Utf8(error) => Self::Utf8(error.clone()),
+            Self::Watch(error) => Self::Error(format!("{}", error)),
+            Self::Xml(error) => Self::Xml(error.clone()),
         }
     }
 }
@@ -88,6 +106,13 @@ impl From<block_modes::InvalidKeyIvLength> for PWDuckCoreError {
     }
 }
 
+impl From<csv::Error> for PWDuckCoreError {
+    #[cfg_attr(coverage, no_coverage)]
+    fn from(error: csv::Error) -> Self {
+        Self::Csv(error)
+    }
+}
+
 impl From<String> for PWDuckCoreError {
     #[cfg_attr(coverage, no_coverage)]
     fn from(s: String) -> Self {
@@ -102,6 +127,13 @@ impl From<std::io::Error> for PWDuckCoreError {
     }
 }
 
+impl From<serde_json::Error> for PWDuckCoreError {
+    #[cfg_attr(coverage, no_coverage)]
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
 impl<T> From<PoisonError<T>> for PWDuckCoreError {
     #[cfg_attr(coverage, no_coverage)]
     fn from(error: PoisonError<T>) -> Self {
@@ -109,6 +141,13 @@ impl<T> From<PoisonError<T>> for PWDuckCoreError {
     }
 }
 
+impl From<ReferenceError> for PWDuckCoreError {
+    #[cfg_attr(coverage, no_coverage)]
+    fn from(error: ReferenceError) -> Self {
+        Self::Reference(error)
+    }
+}
+
 impl From<ron::Error> for PWDuckCoreError {
     #[cfg_attr(coverage, no_coverage)]
     fn from(error: ron::Error) -> Self {
@@ -130,6 +169,20 @@ impl From<std::string::FromUtf8Error> for PWDuckCoreError {
     }
 }
 
+impl From<notify::Error> for PWDuckCoreError {
+    #[cfg_attr(coverage, no_coverage)]
+    fn from(error: notify::Error) -> Self {
+        Self::Watch(error)
+    }
+}
+
+impl From<roxmltree::Error> for PWDuckCoreError {
+    #[cfg_attr(coverage, no_coverage)]
+    fn from(error: roxmltree::Error) -> Self {
+        Self::Xml(error)
+    }
+}
+
 impl Display for PWDuckCoreError {
     #[cfg_attr(coverage, no_coverage)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -146,9 +199,19 @@ impl Display for PWDuckCoreError {
             PWDuckCoreError::BlockModeIV(error) => {
                 write!(f, "Got a wrong size of the IV ({})", error)
             }
+            PWDuckCoreError::Conflict(uuids) => write!(
+                f,
+                "The vault was changed on disk by another program ({} items affected)",
+                uuids.len()
+            ),
+            PWDuckCoreError::Csv(error) => write!(f, "Not a valid CSV file ({})", error),
             PWDuckCoreError::Error(error) => write!(f, "{}", error),
             PWDuckCoreError::IO(error) => write!(f, "Could not access the vault ({})", error),
+            PWDuckCoreError::Json(error) => write!(f, "Not a valid JSON document ({})", error),
             PWDuckCoreError::Mutex(error) => write!(f, "Could not lock a mutex ({})", error),
+            PWDuckCoreError::Reference(error) => {
+                write!(f, "Could not resolve a field reference ({})", error)
+            }
             PWDuckCoreError::Ron(error) => write!(f, "Not a valid RON structure ({})", error),
             PWDuckCoreError::SequenceParseError(error) => write!(
                 f,
@@ -158,6 +221,10 @@ impl Display for PWDuckCoreError {
             PWDuckCoreError::Utf8(error) => {
                 write!(f, "The given data was no valid UTF-8 ({})", error)
             }
+            PWDuckCoreError::Watch(error) => {
+                write!(f, "Could not watch the vault for changes ({})", error)
+            }
+            PWDuckCoreError::Xml(error) => write!(f, "Not a valid XML document ({})", error),
         }
     }
 }
@@ -203,3 +270,31 @@ impl Display for SequenceParseError {
         }
     }
 }
+
+/// An error from resolving a reference to the field of another entry.
+#[derive(Clone, Debug)]
+pub enum ReferenceError {
+    /// The referenced entry does not exist.
+    MissingTarget(String),
+    /// The references are cyclic or nested too deeply.
+    TooDeep,
+    /// The entry is still referenced by the entries with the given titles.
+    Referenced(String),
+}
+
+impl Display for ReferenceError {
+    #[cfg_attr(coverage, no_coverage)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceError::MissingTarget(uuid) => {
+                write!(f, "The referenced entry {} does not exist", uuid)
+            }
+            ReferenceError::TooDeep => {
+                write!(f, "The references are cyclic or nested too deeply")
+            }
+            ReferenceError::Referenced(titles) => {
+                write!(f, "The entry is still referenced by: {}", titles)
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Map an [`Export`](Export) to the XML document of a `KeePass` database.
+use std::convert::TryFrom;
+
+use zeroize::Zeroize;
+
+use crate::{
+    cryptography::fill_random_bytes,
+    kdbx::{InnerStream, SECONDS_UNTIL_UNIX_EPOCH},
+    mem_protection::SecString,
+    model::uuid,
+};
+
+use super::{Export, ExportEntry, ExportGroup};
+
+/// Write the XML document of a `KeePass` database.
+///
+/// It expects:
+///  - The [`Export`](Export) containing the groups and entries
+///  - The [`InnerStream`](InnerStream) to encrypt the protected values
+pub fn write(export: &Export, inner_stream: &mut InnerStream) -> SecString {
+    let mut writer = Writer {
+        xml: String::new(),
+        inner_stream,
+    };
+
+    writer.push("<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>");
+    writer.push("<KeePassFile><Meta><Generator>PWDuck</Generator>");
+    writer.element("DatabaseName", export.root().title());
+    writer.push("<RecycleBinEnabled>False</RecycleBinEnabled></Meta><Root>");
+    writer.group(export.root());
+    writer.push("</Root></KeePassFile>");
+
+    std::mem::take(&mut writer.xml).into()
+}
+
+/// Writes the groups and entries and encrypts the protected values in the order of their appearance.
+struct Writer<'a> {
+    /// The XML document.
+    xml: String,
+    /// The stream cipher to encrypt the protected values.
+    inner_stream: &'a mut InnerStream,
+}
+
+impl Writer<'_> {
+    /// Append the raw markup.
+    fn push(&mut self, markup: &str) {
+        self.xml.push_str(markup);
+    }
+
+    /// Append an element containing the escaped text.
+    fn element(&mut self, name: &str, text: &str) {
+        self.push("<");
+        self.push(name);
+        self.push(">");
+        escape_into(&mut self.xml, text);
+        self.push("</");
+        self.push(name);
+        self.push(">");
+    }
+
+    /// Write the group and all of its descendants.
+    fn group(&mut self, group: &ExportGroup) {
+        self.push("<Group>");
+        self.element("UUID", &random_uuid());
+        self.element("Name", group.title());
+        for entry in group.entries() {
+            self.entry(entry);
+        }
+        for child in group.groups() {
+            self.group(child);
+        }
+        self.push("</Group>");
+    }
+
+    /// Write the entry.
+    fn entry(&mut self, entry: &ExportEntry) {
+        self.push("<Entry>");
+        self.element("UUID", &random_uuid());
+        self.push("<Times>");
+        self.element("CreationTime", &format_time(entry.created()));
+        self.element("LastModificationTime", &format_time(entry.last_modified()));
+        self.element("LastAccessTime", &format_time(entry.last_modified()));
+        self.push("<Expires>False</Expires></Times>");
+        self.string("Title", entry.title(), false);
+        self.string("UserName", entry.username(), false);
+        self.string("Password", entry.password(), true);
+        self.string("URL", entry.web_address(), false);
+        self.string("Notes", "", false);
+        if !entry.email().is_empty() {
+            self.string("Email", entry.email(), false);
+        }
+        self.push("</Entry>");
+    }
+
+    /// Write a string field of an entry.
+    /// Protected values are encrypted by the [`InnerStream`](InnerStream).
+    fn string(&mut self, key: &str, value: &str, protected: bool) {
+        self.push("<String>");
+        self.element("Key", key);
+        if protected {
+            let mut bytes = value.as_bytes().to_vec();
+            self.inner_stream.apply(&mut bytes);
+            self.push("<Value Protected=\"True\">");
+            self.push(&base64::encode(&bytes));
+            self.push("</Value>");
+            bytes.zeroize();
+        } else {
+            self.element("Value", value);
+        }
+        self.push("</String>");
+    }
+}
+
+impl Drop for Writer<'_> {
+    fn drop(&mut self) {
+        self.xml.zeroize();
+    }
+}
+
+/// Append the text with all XML special characters escaped.
+fn escape_into(xml: &mut String, text: &str) {
+    for character in text.chars() {
+        match character {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            '"' => xml.push_str("&quot;"),
+            '\'' => xml.push_str("&apos;"),
+            _ => xml.push(character),
+        }
+    }
+}
+
+/// Generate a random base64 encoded UUID for a group or entry.
+fn random_uuid() -> String {
+    let mut uuid = [0_u8; uuid::SIZE];
+    fill_random_bytes(&mut uuid);
+    base64::encode(uuid)
+}
+
+/// Format the seconds since the unix epoch as base64 encoded seconds since the year 1.
+fn format_time(time: u64) -> String {
+    let seconds = i64::try_from(time)
+        .unwrap_or_default()
+        .saturating_add(SECONDS_UNTIL_UNIX_EPOCH);
+    base64::encode(seconds.to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_time, write};
+    use crate::{
+        export::{Export, ExportEntry, ExportGroup},
+        kdbx::InnerStream,
+    };
+
+    #[test]
+    fn write_xml() {
+        let entry = ExportEntry {
+            title: "Mail & Calendar".into(),
+            password: "s3cr3t <pw>".into(),
+            ..ExportEntry::default()
+        };
+        let export = Export::new(ExportGroup::new("Team".into(), Vec::new(), vec![entry]));
+
+        let (mut inner_stream, _key) = InnerStream::generate();
+        let xml = write(&export, &mut inner_stream);
+        assert!(roxmltree::Document::parse(&xml).is_ok());
+        assert!(xml.contains("<DatabaseName>Team</DatabaseName>"));
+        assert!(xml.contains("<Value>Mail &amp; Calendar</Value>"));
+        assert!(xml.contains("<Value Protected=\"True\">"));
+        assert!(!xml.contains("s3cr3t"));
+        assert!(!xml.contains("<Key>Email</Key>"));
+    }
+
+    #[test]
+    fn format_times() {
+        assert_eq!(format_time(0), "APeRdw4AAAA=");
+        assert_eq!(format_time(u64::MAX), "APeRdw4AAAA=");
+    }
+}
@@ -0,0 +1,264 @@
+//! Export groups and entries into the databases of other password managers.
+use std::path::Path;
+
+use getset::{CopyGetters, Getters};
+
+use crate::{
+    error::PWDuckCoreError,
+    io::save_export_file,
+    kdbx::{self, InnerStream},
+    mem_protection::SecString,
+    EntryBody, EntryHead,
+};
+
+mod keepass_xml;
+mod plain;
+
+/// The groups and entries of a vault prepared for the database of another password manager.
+#[derive(Clone, Debug, Default, Getters)]
+pub struct Export {
+    /// The exported group containing all exported groups and entries.
+    #[getset(get = "pub")]
+    root: ExportGroup,
+}
+
+impl Export {
+    /// Create a new [`Export`](Export) of the given group.
+    pub(crate) const fn new(root: ExportGroup) -> Self {
+        Self { root }
+    }
+
+    /// Write the groups and entries as a `KeePass` database in the format KDBX 4.
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the new `KeePass` database
+    ///  - The password to protect the `KeePass` database
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the database can't be encrypted or written.
+    pub fn to_kdbx(&self, path: &Path, password: &str) -> Result<(), PWDuckCoreError> {
+        let (mut inner_stream, inner_stream_key) = InnerStream::generate();
+        let xml = keepass_xml::write(self, &mut inner_stream);
+        let data = kdbx::write_database(&xml, &inner_stream_key, password)?;
+        save_export_file(path, &data)
+    }
+
+    /// Write the entries unencrypted as a CSV file.
+    /// The groups are flattened into a column containing the path of each entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can't be written.
+    pub fn to_csv(&self, path: &Path) -> Result<(), PWDuckCoreError> {
+        save_export_file(path, &plain::write_csv(self)?)
+    }
+
+    /// Write the groups and entries unencrypted as a JSON document.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can't be written.
+    pub fn to_json(&self, path: &Path) -> Result<(), PWDuckCoreError> {
+        save_export_file(path, &plain::write_json(self)?)
+    }
+
+    /// The number of exported groups, not counting the root.
+    #[must_use]
+    pub fn group_count(&self) -> usize {
+        self.root.group_count()
+    }
+
+    /// The number of exported entries.
+    #[must_use]
+    pub fn entry_count(&self) -> usize {
+        self.root.entry_count()
+    }
+}
+
+/// A group prepared for the database of another password manager.
+#[derive(Clone, Debug, Default, Getters)]
+pub struct ExportGroup {
+    /// The title of this group.
+    #[getset(get = "pub")]
+    title: String,
+
+    /// The subgroups of this group.
+    #[getset(get = "pub")]
+    groups: Vec<Self>,
+
+    /// The entries of this group.
+    #[getset(get = "pub")]
+    entries: Vec<ExportEntry>,
+}
+
+impl ExportGroup {
+    /// Create a new [`ExportGroup`](ExportGroup).
+    pub(crate) const fn new(title: String, groups: Vec<Self>, entries: Vec<ExportEntry>) -> Self {
+        Self {
+            title,
+            groups,
+            entries,
+        }
+    }
+
+    /// The number of all descendant groups of this group.
+    fn group_count(&self) -> usize {
+        self.groups
+            .iter()
+            .map(|group| group.group_count() + 1)
+            .sum()
+    }
+
+    /// The number of all entries of this group and its descendants.
+    fn entry_count(&self) -> usize {
+        self.entries.len() + self.groups.iter().map(Self::entry_count).sum::<usize>()
+    }
+}
+
+/// An entry prepared for the database of another password manager.
+#[derive(Clone, Debug, Default, CopyGetters, Getters)]
+pub struct ExportEntry {
+    /// The title of this entry.
+    #[getset(get = "pub")]
+    title: String,
+
+    /// The username of this entry.
+    #[getset(get = "pub")]
+    username: SecString,
+
+    /// The password of this entry.
+    #[getset(get = "pub")]
+    password: SecString,
+
+    /// The email of this entry.
+    #[getset(get = "pub")]
+    email: SecString,
+
+    /// The address of the website this entry belongs to.
+    #[getset(get = "pub")]
+    web_address: String,
+
+    /// The time this entry was created as seconds since the unix epoch.
+    #[getset(get_copy = "pub")]
+    created: u64,
+
+    /// The time this entry was modified the last time as seconds since the unix epoch.
+    #[getset(get_copy = "pub")]
+    last_modified: u64,
+}
+
+impl ExportEntry {
+    /// Create a new [`ExportEntry`](ExportEntry) from the head and the body of an entry.
+    /// The field references of the body must already be resolved.
+    pub(crate) fn new(entry_head: &EntryHead, entry_body: &EntryBody) -> Self {
+        Self {
+            title: entry_head.title().clone(),
+            username: entry_body.username().clone(),
+            password: entry_body.password().clone(),
+            email: entry_body.email().clone(),
+            web_address: entry_head.web_address().clone(),
+            created: entry_head.created(),
+            last_modified: entry_head.last_modified(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use tempfile::tempdir;
+
+    use crate::Import;
+
+    use super::{Export, ExportEntry, ExportGroup};
+
+    #[test]
+    fn to_kdbx() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("export.kdbx");
+
+        let export = Export::new(ExportGroup::new(
+            "Team".into(),
+            vec![ExportGroup::new(
+                "Servers & Databases".into(),
+                vec![ExportGroup::new("Empty".into(), Vec::new(), Vec::new())],
+                vec![ExportEntry {
+                    title: "Database".into(),
+                    username: "root".into(),
+                    password: "hunter2".into(),
+                    ..ExportEntry::default()
+                }],
+            )],
+            vec![ExportEntry {
+                title: "Mail & Calendar".into(),
+                username: "alice".into(),
+                password: "s3cr3t <pw>".into(),
+                email: "alice@example.com".into(),
+                web_address: "https://mail.example.com".into(),
+                created: 1_614_834_367,
+                last_modified: 1_623_053_350,
+            }],
+        ));
+        export.to_kdbx(&path, "export password").unwrap();
+
+        assert!(Import::from_kdbx(&path, "password").is_err());
+        let import = Import::from_kdbx(&path, "export password").unwrap();
+        assert!(import.skipped().is_empty());
+        assert_eq!(import.root().title(), "Team");
+        assert_eq!(import.group_count(), export.group_count());
+        assert_eq!(import.entry_count(), export.entry_count());
+
+        let entry = &import.root().entries()[0];
+        assert_eq!(entry.title(), "Mail & Calendar");
+        assert_eq!(entry.username().as_str(), "alice");
+        assert_eq!(entry.password().as_str(), "s3cr3t <pw>");
+        assert_eq!(entry.email().as_str(), "alice@example.com");
+        assert_eq!(entry.web_address(), "https://mail.example.com");
+        assert_eq!(entry.created(), 1_614_834_367);
+        assert_eq!(entry.last_modified(), 1_623_053_350);
+
+        let servers = &import.root().groups()[0];
+        assert_eq!(servers.title(), "Servers & Databases");
+        assert_eq!(servers.groups()[0].title(), "Empty");
+        assert_eq!(servers.entries()[0].password().as_str(), "hunter2");
+
+        assert!(export
+            .to_kdbx(Path::new("missing/directory/export.kdbx"), "password")
+            .is_err());
+    }
+
+    #[test]
+    fn to_plain() {
+        let dir = tempdir().unwrap();
+        let export = Export::new(ExportGroup::new(
+            "Team".into(),
+            Vec::new(),
+            vec![ExportEntry {
+                title: "Mail".into(),
+                password: "s3cr3t".into(),
+                ..ExportEntry::default()
+            }],
+        ));
+
+        let csv = dir.path().join("export.csv");
+        export.to_csv(&csv).unwrap();
+        assert!(std::fs::read_to_string(&csv)
+            .unwrap()
+            .contains("Mail,,s3cr3t"));
+
+        let json = dir.path().join("export.json");
+        export.to_json(&json).unwrap();
+        assert!(std::fs::read_to_string(&json)
+            .unwrap()
+            .contains("\"password\": \"s3cr3t\""));
+
+        assert!(export
+            .to_csv(Path::new("missing/directory/export.csv"))
+            .is_err());
+        assert!(export
+            .to_json(Path::new("missing/directory/export.json"))
+            .is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! Write an [`Export`](Export) as an unencrypted CSV file or JSON document.
+use serde::Serialize;
+
+use crate::{error::PWDuckCoreError, mem_protection::SecVec};
+
+use super::{Export, ExportEntry, ExportGroup};
+
+/// The header of the written CSV file.
+const CSV_HEADER: [&str; 8] = [
+    "group",
+    "title",
+    "username",
+    "password",
+    "email",
+    "web_address",
+    "created",
+    "last_modified",
+];
+
+/// Write the entries as rows of a CSV file.
+///
+/// The groups are flattened into the first column as the path of group titles separated by `/`,
+/// starting below the exported root. The file can be imported again by mapping this column as the group path.
+pub fn write_csv(export: &Export) -> Result<SecVec<u8>, PWDuckCoreError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(CSV_HEADER)?;
+    write_csv_group(&mut writer, export.root(), "")?;
+    let data = writer
+        .into_inner()
+        .map_err(|error| PWDuckCoreError::Error(error.to_string()))?;
+    Ok(data.into())
+}
+
+/// Write the entries of the group and all of its descendants as rows of the CSV file.
+fn write_csv_group(
+    writer: &mut csv::Writer<Vec<u8>>,
+    group: &ExportGroup,
+    path: &str,
+) -> Result<(), PWDuckCoreError> {
+    for entry in group.entries() {
+        writer.write_record([
+            path,
+            entry.title(),
+            entry.username(),
+            entry.password(),
+            entry.email(),
+            entry.web_address(),
+            &entry.created().to_string(),
+            &entry.last_modified().to_string(),
+        ])?;
+    }
+    for child in group.groups() {
+        let path = if path.is_empty() {
+            child.title().clone()
+        } else {
+            format!("{}/{}", path, child.title())
+        };
+        write_csv_group(writer, child, &path)?;
+    }
+    Ok(())
+}
+
+/// Write the groups and entries as a JSON document keeping the hierarchy of the groups.
+pub fn write_json(export: &Export) -> Result<SecVec<u8>, PWDuckCoreError> {
+    Ok(serde_json::to_vec_pretty(&JsonGroup::from(export.root()))?.into())
+}
+
+/// The serialized form of an [`ExportGroup`](ExportGroup).
+#[derive(Debug, Serialize)]
+struct JsonGroup<'a> {
+    /// The title of the group.
+    title: &'a str,
+    /// The subgroups of the group.
+    groups: Vec<Self>,
+    /// The entries of the group.
+    entries: Vec<JsonEntry<'a>>,
+}
+
+impl<'a> From<&'a ExportGroup> for JsonGroup<'a> {
+    fn from(group: &'a ExportGroup) -> Self {
+        Self {
+            title: group.title(),
+            groups: group.groups().iter().map(Self::from).collect(),
+            entries: group.entries().iter().map(JsonEntry::from).collect(),
+        }
+    }
+}
+
+/// The serialized form of an [`ExportEntry`](ExportEntry).
+#[derive(Debug, Serialize)]
+struct JsonEntry<'a> {
+    /// The title of the entry.
+    title: &'a str,
+    /// The username of the entry.
+    username: &'a str,
+    /// The password of the entry.
+    password: &'a str,
+    /// The email of the entry.
+    email: &'a str,
+    /// The address of the website the entry belongs to.
+    web_address: &'a str,
+    /// The time the entry was created as seconds since the unix epoch.
+    created: u64,
+    /// The time the entry was modified the last time as seconds since the unix epoch.
+    last_modified: u64,
+}
+
+impl<'a> From<&'a ExportEntry> for JsonEntry<'a> {
+    fn from(entry: &'a ExportEntry) -> Self {
+        Self {
+            title: entry.title(),
+            username: entry.username(),
+            password: entry.password(),
+            email: entry.email(),
+            web_address: entry.web_address(),
+            created: entry.created(),
+            last_modified: entry.last_modified(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_csv, write_json};
+    use crate::{
+        export::{Export, ExportEntry, ExportGroup},
+        CsvFile,
+    };
+
+    fn export() -> Export {
+        Export::new(ExportGroup::new(
+            "Team".into(),
+            vec![ExportGroup::new(
+                "Servers".into(),
+                vec![ExportGroup::new(
+                    "Databases".into(),
+                    Vec::new(),
+                    vec![ExportEntry {
+                        title: "Database".into(),
+                        username: "root".into(),
+                        password: "hunter2".into(),
+                        ..ExportEntry::default()
+                    }],
+                )],
+                Vec::new(),
+            )],
+            vec![ExportEntry {
+                title: "Mail, Calendar".into(),
+                username: "alice".into(),
+                password: "s3cr3t \"pw\"".into(),
+                email: "alice@example.com".into(),
+                web_address: "https://mail.example.com".into(),
+                created: 1_614_834_367,
+                last_modified: 1_623_053_350,
+            }],
+        ))
+    }
+
+    #[test]
+    fn csv() {
+        let data = write_csv(&export()).unwrap();
+        let file = CsvFile::parse(&data).unwrap();
+        assert_eq!(
+            file.headers(),
+            &[
+                "group",
+                "title",
+                "username",
+                "password",
+                "email",
+                "web_address",
+                "created",
+                "last_modified"
+            ]
+        );
+        assert_eq!(file.records().len(), 2);
+
+        let mail: Vec<&str> = file.records()[0]
+            .iter()
+            .map(|field| field.as_str())
+            .collect();
+        assert_eq!(
+            mail,
+            [
+                "",
+                "Mail, Calendar",
+                "alice",
+                "s3cr3t \"pw\"",
+                "alice@example.com",
+                "https://mail.example.com",
+                "1614834367",
+                "1623053350"
+            ]
+        );
+        assert_eq!(file.records()[1][0].as_str(), "Servers/Databases");
+        assert_eq!(file.records()[1][3].as_str(), "hunter2");
+    }
+
+    #[test]
+    fn json() {
+        let data = write_json(&export()).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&data).unwrap();
+
+        assert_eq!(json["title"], "Team");
+        assert_eq!(json["entries"][0]["title"], "Mail, Calendar");
+        assert_eq!(json["entries"][0]["password"], "s3cr3t \"pw\"");
+        assert_eq!(json["entries"][0]["created"], 1_614_834_367);
+        assert_eq!(json["groups"][0]["title"], "Servers");
+        assert_eq!(
+            json["groups"][0]["groups"][0]["entries"][0]["username"],
+            "root"
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Map the unencrypted JSON export of `Bitwarden` to an [`Import`](Import).
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use zeroize::Zeroize;
+
+use crate::error::PWDuckCoreError;
+
+use super::{parse_iso_8601, Import, ImportEntry, ImportGroup, SkippedField};
+
+/// The title of the imported root group.
+const ROOT_TITLE: &str = "Bitwarden";
+
+/// The type of a login item.
+const ITEM_LOGIN: u8 = 1;
+/// The type of a secure note item.
+const ITEM_SECURE_NOTE: u8 = 2;
+/// The type of a card item.
+const ITEM_CARD: u8 = 3;
+/// The type of an identity item.
+const ITEM_IDENTITY: u8 = 4;
+
+/// Parse the unencrypted JSON export of `Bitwarden`.
+///
+/// The nested folders (`Parent/Child`) are mapped to nested groups and the logins to entries.
+/// Secure notes, cards and identities can't be represented in a vault and are reported as skipped.
+pub fn parse(data: &[u8]) -> Result<Import, PWDuckCoreError> {
+    let export: Export = serde_json::from_slice(data)?;
+    if export.encrypted {
+        return Err(PWDuckCoreError::Error(
+            "Encrypted Bitwarden exports are not supported".into(),
+        ));
+    }
+
+    let mut root = ImportGroup {
+        title: ROOT_TITLE.to_owned(),
+        ..ImportGroup::default()
+    };
+    let folders: HashMap<&str, Vec<String>> = export
+        .folders
+        .iter()
+        .map(|folder| {
+            let path: Vec<String> = folder
+                .name
+                .split('/')
+                .filter(|title| !title.is_empty())
+                .map(str::to_owned)
+                .collect();
+            // Create the groups in the order of the folders, even if they are empty.
+            let _ = root.group_mut(&path);
+            (folder.id.as_str(), path)
+        })
+        .collect();
+
+    let mut skipped = Vec::new();
+    for item in &export.items {
+        let path = item
+            .folder_id
+            .as_deref()
+            .and_then(|id| folders.get(id))
+            .map_or(&[][..], Vec::as_slice);
+        let name = path
+            .iter()
+            .chain(std::iter::once(&item.name))
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("/");
+
+        match item.kind {
+            ITEM_LOGIN => root
+                .group_mut(path)
+                .entries
+                .push(entry(item, &name, &mut skipped)),
+            ITEM_SECURE_NOTE => skipped.push(SkippedField::new(&name, "Secure note")),
+            ITEM_CARD => skipped.push(SkippedField::new(&name, "Card")),
+            ITEM_IDENTITY => skipped.push(SkippedField::new(&name, "Identity")),
+            _ => skipped.push(SkippedField::new(&name, "Unknown item")),
+        }
+    }
+
+    Ok(Import { root, skipped })
+}
+
+/// Map the login item to an entry and collect the fields which can't be represented.
+///
+/// It expects:
+///  - The login item
+///  - The path of the item used to report the skipped fields
+///  - The collection of the skipped fields
+fn entry(item: &Item, name: &str, skipped: &mut Vec<SkippedField>) -> ImportEntry {
+    let mut entry = ImportEntry {
+        title: item.name.clone(),
+        created: parse_time(item.creation_date.as_deref()),
+        last_modified: parse_time(item.revision_date.as_deref()),
+        favourite: item.favorite,
+        ..ImportEntry::default()
+    };
+
+    if let Some(login) = &item.login {
+        entry.username = login.username.as_deref().unwrap_or_default().into();
+        entry.password = login.password.as_deref().unwrap_or_default().into();
+
+        let mut uris = login
+            .uris
+            .iter()
+            .flatten()
+            .filter_map(|uri| uri.uri.as_deref())
+            .filter(|uri| !uri.is_empty());
+        entry.web_address = uris.next().unwrap_or_default().to_owned();
+        for (index, _uri) in uris.enumerate() {
+            skipped.push(SkippedField::new(name, &format!("URI {}", index + 2)));
+        }
+
+        if login.totp.as_deref().map_or(false, |totp| !totp.is_empty()) {
+            skipped.push(SkippedField::new(name, "TOTP"));
+        }
+    }
+
+    if item
+        .notes
+        .as_deref()
+        .map_or(false, |notes| !notes.is_empty())
+    {
+        skipped.push(SkippedField::new(name, "Notes"));
+    }
+
+    for field in item.fields.iter().flatten() {
+        let field_name = field.name.as_deref().unwrap_or_default();
+        let value = field.value.as_deref().unwrap_or_default();
+        let is_email =
+            field_name.eq_ignore_ascii_case("email") || field_name.eq_ignore_ascii_case("e-mail");
+        if is_email && entry.email.is_empty() {
+            entry.email = value.into();
+        } else if !field_name.is_empty() || !value.is_empty() {
+            skipped.push(SkippedField::new(
+                name,
+                if field_name.is_empty() {
+                    "Custom field"
+                } else {
+                    field_name
+                },
+            ));
+        }
+    }
+
+    if item
+        .password_history
+        .as_ref()
+        .map_or(false, |history| !history.is_empty())
+    {
+        skipped.push(SkippedField::new(name, "Password history"));
+    }
+
+    entry
+}
+
+/// Parse an optional date of the export into seconds since the unix epoch or 0, if unknown.
+fn parse_time(time: Option<&str>) -> u64 {
+    time.and_then(parse_iso_8601)
+        .and_then(|seconds| u64::try_from(seconds).ok())
+        .unwrap_or_default()
+}
+
+/// The unencrypted JSON export of `Bitwarden`.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+struct Export {
+    /// True, if the export is encrypted.
+    #[serde(default)]
+    #[zeroize(skip)]
+    encrypted: bool,
+    /// The folders of the export.
+    #[serde(default)]
+    folders: Vec<Folder>,
+    /// The items of the export.
+    #[serde(default)]
+    items: Vec<Item>,
+}
+
+/// A folder of the export.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+struct Folder {
+    /// The ID of the folder referenced by the items.
+    id: String,
+    /// The name of the folder. Nested folders are separated by `/`.
+    name: String,
+}
+
+/// An item of the export.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+    /// The ID of the folder containing the item.
+    #[serde(default)]
+    folder_id: Option<String>,
+    /// The type of the item.
+    #[serde(rename = "type")]
+    kind: u8,
+    /// The name of the item.
+    #[serde(default)]
+    name: String,
+    /// The notes of the item.
+    #[serde(default)]
+    notes: Option<String>,
+    /// True, if the item is marked as a favourite.
+    #[serde(default)]
+    #[zeroize(skip)]
+    favorite: bool,
+    /// The custom fields of the item.
+    #[serde(default)]
+    fields: Option<Vec<Field>>,
+    /// The credentials of a login item.
+    #[serde(default)]
+    login: Option<Login>,
+    /// The previous passwords of the item.
+    #[serde(default)]
+    password_history: Option<Vec<PasswordHistory>>,
+    /// The time the item was created.
+    #[serde(default)]
+    creation_date: Option<String>,
+    /// The time the item was modified the last time.
+    #[serde(default)]
+    revision_date: Option<String>,
+}
+
+/// A custom field of an item.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+struct Field {
+    /// The name of the field.
+    #[serde(default)]
+    name: Option<String>,
+    /// The value of the field.
+    #[serde(default)]
+    value: Option<String>,
+}
+
+/// The credentials of a login item.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+struct Login {
+    /// The addresses of the websites the login belongs to.
+    #[serde(default)]
+    uris: Option<Vec<Uri>>,
+    /// The username of the login.
+    #[serde(default)]
+    username: Option<String>,
+    /// The password of the login.
+    #[serde(default)]
+    password: Option<String>,
+    /// The secret to generate time-based one-time passwords.
+    #[serde(default)]
+    totp: Option<String>,
+}
+
+/// The address of a website of a login item.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+struct Uri {
+    /// The address of the website.
+    #[serde(default)]
+    uri: Option<String>,
+}
+
+/// A previous password of an item.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+struct PasswordHistory {
+    /// The previous password.
+    #[serde(default)]
+    password: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, SkippedField};
+
+    #[test]
+    fn parse_export() {
+        let data = include_bytes!("../../tests/resources/bitwarden.json");
+        let import = parse(data).unwrap();
+
+        let root = import.root();
+        assert_eq!(root.title(), "Bitwarden");
+        assert_eq!(root.groups().len(), 2);
+        assert_eq!(import.group_count(), 3);
+        assert_eq!(import.entry_count(), 3);
+
+        let forum = &root.entries()[0];
+        assert_eq!(forum.title(), "Forum");
+        assert_eq!(forum.web_address(), "https://forum.example.com");
+        assert!(!forum.favourite());
+
+        let work = &root.groups()[0];
+        assert_eq!(work.title(), "Work");
+        let mail = &work.entries()[0];
+        assert_eq!(mail.title(), "Mail & Calendar");
+        assert_eq!(mail.username().as_str(), "alice");
+        assert_eq!(mail.password().as_str(), "s3cr3t <pw>");
+        assert_eq!(mail.email().as_str(), "alice@example.com");
+        assert_eq!(mail.web_address(), "https://mail.example.com");
+        assert_eq!(mail.created(), 1_614_834_367);
+        assert_eq!(mail.last_modified(), 1_623_053_350);
+        assert!(mail.favourite());
+
+        let servers = &work.groups()[0];
+        assert_eq!(servers.title(), "Servers");
+        assert_eq!(servers.entries()[0].password().as_str(), "hunter2");
+        assert_eq!(servers.entries()[0].web_address(), "");
+        assert_eq!(servers.entries()[0].created(), 0);
+
+        assert_eq!(root.groups()[1].title(), "Empty");
+
+        let skipped: Vec<String> = import
+            .skipped()
+            .iter()
+            .map(SkippedField::to_string)
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                "Work/Mail & Calendar: URI 2",
+                "Work/Mail & Calendar: TOTP",
+                "Work/Mail & Calendar: Notes",
+                "Work/Mail & Calendar: PIN",
+                "Work/Mail & Calendar: Password history",
+                "Work/Wi-Fi: Secure note",
+                "Credit card: Card",
+                "Passport: Identity",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse(br#"{"encrypted": true, "encKeyValidation_DO_NOT_EDIT": ""}"#).is_err());
+        assert!(parse(b"no json").is_err());
+
+        let import = parse(br#"{"items": [{"type": 5, "name": "Future"}]}"#).unwrap();
+        assert_eq!(import.entry_count(), 0);
+        assert_eq!(import.skipped()[0].to_string(), "Future: Unknown item");
+    }
+}
@@ -0,0 +1,451 @@
+//! Map the rows of a CSV file to an [`Import`](Import).
+use std::path::Path;
+
+use getset::Getters;
+
+use crate::{error::PWDuckCoreError, io::load_import_file, mem_protection::SecString};
+
+use super::{Import, ImportEntry, SkippedField, UTF8_BOM};
+
+/// The rows of a CSV file exported by another password manager.
+#[derive(Clone, Debug, Default, Getters)]
+pub struct CsvFile {
+    /// The names of the columns taken from the first row.
+    #[getset(get = "pub")]
+    headers: Vec<String>,
+
+    /// The values of all other rows.
+    #[getset(get = "pub")]
+    records: Vec<Vec<SecString>>,
+}
+
+impl CsvFile {
+    /// Read the CSV file located at the given [`Path`](Path).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can't be read or is no valid CSV file.
+    pub fn from_path(path: &Path) -> Result<Self, PWDuckCoreError> {
+        Self::parse(&load_import_file(path)?)
+    }
+
+    /// Parse the content of a CSV file. The first row must contain the names of the columns.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the data is no valid CSV file.
+    pub fn parse(data: &[u8]) -> Result<Self, PWDuckCoreError> {
+        let data = data.strip_prefix(UTF8_BOM).unwrap_or(data);
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(data);
+
+        let headers = reader
+            .headers()?
+            .iter()
+            .map(|header| header.trim().to_owned())
+            .collect();
+        let records = reader
+            .records()
+            .map(|record| Ok(record?.iter().map(SecString::from).collect()))
+            .collect::<Result<_, PWDuckCoreError>>()?;
+
+        Ok(Self { headers, records })
+    }
+}
+
+/// The field of an entry a column of a CSV file is mapped to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvField {
+    /// The column is not imported.
+    Ignore,
+    /// The title of the entry.
+    Title,
+    /// The username of the entry.
+    Username,
+    /// The password of the entry.
+    Password,
+    /// The email of the entry.
+    Email,
+    /// The address of the website.
+    WebAddress,
+    /// The path of the group containing the entry, e.g. `Work/Servers`.
+    GroupPath,
+}
+
+impl CsvField {
+    /// All available [`CsvField`](CsvField)s.
+    pub const ALL: [Self; 7] = [
+        Self::Ignore,
+        Self::Title,
+        Self::Username,
+        Self::Password,
+        Self::Email,
+        Self::WebAddress,
+        Self::GroupPath,
+    ];
+
+    /// The human readable name of this [`CsvField`](CsvField).
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Ignore => "Ignore",
+            Self::Title => "Title",
+            Self::Username => "Username",
+            Self::Password => "Password",
+            Self::Email => "Email",
+            Self::WebAddress => "Web address",
+            Self::GroupPath => "Group path",
+        }
+    }
+
+    /// Returns the next [`CsvField`](CsvField). The last one is followed by the first one.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Ignore => Self::Title,
+            Self::Title => Self::Username,
+            Self::Username => Self::Password,
+            Self::Password => Self::Email,
+            Self::Email => Self::WebAddress,
+            Self::WebAddress => Self::GroupPath,
+            Self::GroupPath => Self::Ignore,
+        }
+    }
+}
+
+impl Default for CsvField {
+    fn default() -> Self {
+        Self::Ignore
+    }
+}
+
+/// The CSV exports of other password managers with a known column layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvPreset {
+    /// The password export of Google Chrome.
+    Chrome,
+    /// The password export of Mozilla Firefox.
+    Firefox,
+    /// The CSV export of Bitwarden.
+    Bitwarden,
+    /// The CSV export of `LastPass`.
+    LastPass,
+}
+
+impl CsvPreset {
+    /// All available [`CsvPreset`](CsvPreset)s.
+    pub const ALL: [Self; 4] = [Self::Chrome, Self::Firefox, Self::Bitwarden, Self::LastPass];
+
+    /// The human readable name of this [`CsvPreset`](CsvPreset).
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Chrome => "Chrome",
+            Self::Firefox => "Firefox",
+            Self::Bitwarden => "Bitwarden",
+            Self::LastPass => "LastPass",
+        }
+    }
+
+    /// The names of the columns of this [`CsvPreset`](CsvPreset) and the fields they are mapped to.
+    #[must_use]
+    pub const fn columns(self) -> &'static [(&'static str, CsvField)] {
+        match self {
+            Self::Chrome => &[
+                ("name", CsvField::Title),
+                ("url", CsvField::WebAddress),
+                ("username", CsvField::Username),
+                ("password", CsvField::Password),
+            ],
+            Self::Firefox => &[
+                ("url", CsvField::WebAddress),
+                ("username", CsvField::Username),
+                ("password", CsvField::Password),
+            ],
+            Self::Bitwarden => &[
+                ("folder", CsvField::GroupPath),
+                ("name", CsvField::Title),
+                ("login_uri", CsvField::WebAddress),
+                ("login_username", CsvField::Username),
+                ("login_password", CsvField::Password),
+            ],
+            Self::LastPass => &[
+                ("url", CsvField::WebAddress),
+                ("username", CsvField::Username),
+                ("password", CsvField::Password),
+                ("name", CsvField::Title),
+                ("grouping", CsvField::GroupPath),
+            ],
+        }
+    }
+
+    /// Detect the [`CsvPreset`](CsvPreset) whose columns are all contained in the given headers.
+    #[must_use]
+    pub fn detect(headers: &[String]) -> Option<Self> {
+        // Presets with more specific columns are checked first.
+        [Self::Bitwarden, Self::LastPass, Self::Chrome, Self::Firefox]
+            .iter()
+            .copied()
+            .find(|preset| {
+                preset
+                    .columns()
+                    .iter()
+                    .all(|(name, _)| find_column(headers, name).is_some())
+            })
+    }
+}
+
+/// The mapping of the columns of a CSV file to the fields of an entry.
+#[derive(Clone, Debug, Default, Getters, PartialEq, Eq)]
+pub struct CsvMapping {
+    /// The field of each column.
+    #[getset(get = "pub")]
+    fields: Vec<CsvField>,
+}
+
+impl CsvMapping {
+    /// Create a new [`CsvMapping`](CsvMapping) ignoring all columns.
+    #[must_use]
+    pub fn new(column_count: usize) -> Self {
+        Self {
+            fields: vec![CsvField::Ignore; column_count],
+        }
+    }
+
+    /// Create the [`CsvMapping`](CsvMapping) of the [`CsvPreset`](CsvPreset) for the given headers.
+    /// Columns unknown to the preset are ignored.
+    #[must_use]
+    pub fn from_preset(preset: CsvPreset, headers: &[String]) -> Self {
+        let mut mapping = Self::new(headers.len());
+        for (name, field) in preset.columns() {
+            if let Some(column) = find_column(headers, name) {
+                mapping.set(column, *field);
+            }
+        }
+        mapping
+    }
+
+    /// Create the [`CsvMapping`](CsvMapping) of the detected [`CsvPreset`](CsvPreset)
+    /// or ignore all columns, if no preset matches.
+    #[must_use]
+    pub fn detect(headers: &[String]) -> Self {
+        CsvPreset::detect(headers).map_or_else(
+            || Self::new(headers.len()),
+            |preset| Self::from_preset(preset, headers),
+        )
+    }
+
+    /// The field the column is mapped to.
+    #[must_use]
+    pub fn field(&self, column: usize) -> CsvField {
+        self.fields.get(column).copied().unwrap_or_default()
+    }
+
+    /// The column mapped to the field.
+    #[must_use]
+    pub fn column(&self, field: CsvField) -> Option<usize> {
+        self.fields.iter().position(|mapped| *mapped == field)
+    }
+
+    /// Map the column to the field.
+    /// Every field except [`Ignore`](CsvField::Ignore) is mapped to at most one column.
+    pub fn set(&mut self, column: usize, field: CsvField) {
+        if column >= self.fields.len() {
+            return;
+        }
+        if field != CsvField::Ignore {
+            self.fields
+                .iter_mut()
+                .filter(|mapped| **mapped == field)
+                .for_each(|mapped| *mapped = CsvField::Ignore);
+        }
+        self.fields[column] = field;
+    }
+}
+
+/// Find the column with the given name ignoring the case.
+fn find_column(headers: &[String], name: &str) -> Option<usize> {
+    headers
+        .iter()
+        .position(|header| header.eq_ignore_ascii_case(name))
+}
+
+/// Map the rows of the CSV file to the entries of an [`Import`](Import).
+///
+/// Nested groups are created for the group paths. An entry without a title
+/// is named after its web address. Ignored columns containing values are reported as skipped.
+pub fn map(file: &CsvFile, mapping: &CsvMapping) -> Import {
+    let mut import = Import::default();
+
+    for (row, record) in file.records().iter().enumerate() {
+        if record.iter().all(|value| value.trim().is_empty()) {
+            continue;
+        }
+        let value = |field| {
+            mapping
+                .column(field)
+                .and_then(|column| record.get(column))
+                .map(|value| SecString::from(value.trim()))
+                .unwrap_or_default()
+        };
+
+        let web_address = value(CsvField::WebAddress).as_str().to_owned();
+        let mut title = value(CsvField::Title).as_str().to_owned();
+        if title.is_empty() {
+            title = if web_address.is_empty() {
+                // The first row contains the headers.
+                format!("Row {}", row + 2)
+            } else {
+                web_address.clone()
+            };
+        }
+
+        let entry = ImportEntry {
+            title,
+            username: value(CsvField::Username),
+            password: value(CsvField::Password),
+            email: value(CsvField::Email),
+            web_address,
+            ..ImportEntry::default()
+        };
+        import
+            .root
+            .group_mut(&split_group_path(&value(CsvField::GroupPath)))
+            .entries
+            .push(entry);
+    }
+
+    for (column, header) in file.headers().iter().enumerate() {
+        let contains_values = file.records().iter().any(|record| {
+            record
+                .get(column)
+                .map_or(false, |value| !value.trim().is_empty())
+        });
+        if mapping.field(column) == CsvField::Ignore && contains_values {
+            import
+                .skipped
+                .push(SkippedField::new(&format!("Column {}", column + 1), header));
+        }
+    }
+
+    import
+}
+
+/// Split the group path into the titles of the nested groups.
+/// Both `/` and `\` are accepted as separators.
+fn split_group_path(path: &str) -> Vec<String> {
+    path.split(['/', '\\'])
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{map, split_group_path, CsvField, CsvFile, CsvMapping, CsvPreset};
+
+    const BITWARDEN: &str = "\u{feff}folder,favorite,type,name,notes,fields,reprompt,login_uri,login_username,login_password,login_totp
+Work/Servers,,login,Database,,,0,https://db.example.com,root,hunter2,
+Work,,login,Mail,Ask Bob,,0,https://mail.example.com,alice,\"s3cr3t, \"\"quoted\"\"\",
+,,login,,,,0,https://example.com,bob,password,
+";
+
+    #[test]
+    fn parse() {
+        let file = CsvFile::parse(BITWARDEN.as_bytes()).unwrap();
+        assert_eq!(file.headers().len(), 11);
+        assert_eq!(file.headers()[0], "folder");
+        assert_eq!(file.records().len(), 3);
+        assert_eq!(file.records()[1][9].as_str(), "s3cr3t, \"quoted\"");
+
+        assert!(CsvFile::parse(b"name,password\n\xFF\xFE,x\n").is_err());
+    }
+
+    #[test]
+    fn detect_presets() {
+        let headers = |line: &str| -> Vec<String> { line.split(',').map(Into::into).collect() };
+
+        assert_eq!(
+            CsvPreset::detect(&headers("name,url,username,password")),
+            Some(CsvPreset::Chrome)
+        );
+        assert_eq!(
+            CsvPreset::detect(&headers(
+                "url,username,password,httpRealm,formActionOrigin,guid"
+            )),
+            Some(CsvPreset::Firefox)
+        );
+        assert_eq!(
+            CsvPreset::detect(&headers(
+                "url,username,password,totp,extra,name,grouping,fav"
+            )),
+            Some(CsvPreset::LastPass)
+        );
+        assert_eq!(
+            CsvPreset::detect(&CsvFile::parse(BITWARDEN.as_bytes()).unwrap().headers),
+            Some(CsvPreset::Bitwarden)
+        );
+        assert_eq!(CsvPreset::detect(&headers("title,login")), None);
+    }
+
+    #[test]
+    fn mapping() {
+        let mut mapping = CsvMapping::new(3);
+        assert_eq!(mapping.field(0), CsvField::Ignore);
+        assert_eq!(mapping.column(CsvField::Title), None);
+
+        mapping.set(0, CsvField::Title);
+        mapping.set(1, CsvField::Title);
+        assert_eq!(mapping.field(0), CsvField::Ignore);
+        assert_eq!(mapping.column(CsvField::Title), Some(1));
+
+        mapping.set(5, CsvField::Password);
+        assert_eq!(mapping.fields().len(), 3);
+        assert_eq!(mapping.field(5), CsvField::Ignore);
+
+        let mut field = CsvField::Ignore;
+        for expected in CsvField::ALL.iter().skip(1) {
+            field = field.next();
+            assert_eq!(field, *expected);
+        }
+        assert_eq!(field.next(), CsvField::Ignore);
+    }
+
+    #[test]
+    fn map_bitwarden() {
+        let file = CsvFile::parse(BITWARDEN.as_bytes()).unwrap();
+        let import = map(&file, &CsvMapping::detect(file.headers()));
+
+        assert_eq!(import.group_count(), 2);
+        assert_eq!(import.entry_count(), 3);
+
+        let untitled = &import.root().entries()[0];
+        assert_eq!(untitled.title(), "https://example.com");
+        assert_eq!(untitled.username().as_str(), "bob");
+
+        let work = &import.root().groups()[0];
+        assert_eq!(work.title(), "Work");
+        assert_eq!(work.entries()[0].title(), "Mail");
+        assert_eq!(work.entries()[0].password().as_str(), "s3cr3t, \"quoted\"");
+
+        let servers = &work.groups()[0];
+        assert_eq!(servers.title(), "Servers");
+        assert_eq!(servers.entries()[0].web_address(), "https://db.example.com");
+
+        let skipped: Vec<String> = import.skipped().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            skipped,
+            vec!["Column 3: type", "Column 5: notes", "Column 7: reprompt"]
+        );
+    }
+
+    #[test]
+    fn group_paths() {
+        assert_eq!(
+            split_group_path(" Work / Servers/"),
+            vec!["Work", "Servers"]
+        );
+        assert_eq!(split_group_path("Work\\Servers"), vec!["Work", "Servers"]);
+        assert!(split_group_path("").is_empty());
+    }
+}
@@ -0,0 +1,328 @@
+//! Map the XML document of a `KeePass` database to an [`Import`](Import).
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node};
+use zeroize::Zeroize;
+
+use crate::{
+    error::PWDuckCoreError,
+    kdbx::{InnerStream, SECONDS_UNTIL_UNIX_EPOCH},
+    mem_protection::SecString,
+};
+
+use super::{parse_iso_8601, Import, ImportEntry, ImportGroup, SkippedField};
+
+/// Parse the XML document of a `KeePass` database.
+///
+/// It expects:
+///  - The XML document
+///  - The [`InnerStream`](InnerStream) to decrypt the protected values or `None`,
+///    if the values are stored in plain text
+pub fn parse(xml: &str, inner_stream: Option<&mut InnerStream>) -> Result<Import, PWDuckCoreError> {
+    let document = Document::parse(xml)?;
+    let keepass_file = document.root_element();
+    if !keepass_file.has_tag_name("KeePassFile") {
+        return Err(PWDuckCoreError::Error(
+            "The file is not a KeePass database".into(),
+        ));
+    }
+
+    let root = child(keepass_file, "Root")
+        .and_then(|root| child(root, "Group"))
+        .ok_or_else(|| {
+            PWDuckCoreError::Error("The KeePass database contains no root group".into())
+        })?;
+
+    let mut parser = Parser {
+        protected_values: decrypt_protected_values(&document, inner_stream)?,
+        recycle_bin: child(keepass_file, "Meta")
+            .and_then(|meta| child_text(meta, "RecycleBinUUID"))
+            .filter(|uuid| !uuid.is_empty()),
+        skipped: Vec::new(),
+    };
+    let root = parser.group(root, "");
+
+    Ok(Import {
+        root,
+        skipped: std::mem::take(&mut parser.skipped),
+    })
+}
+
+/// Decrypt all protected values of the document in the order of their appearance.
+///
+/// The decrypted values are identified by the position of their node in the document.
+fn decrypt_protected_values(
+    document: &Document,
+    mut inner_stream: Option<&mut InnerStream>,
+) -> Result<HashMap<usize, SecString>, PWDuckCoreError> {
+    let mut values = HashMap::new();
+    for node in document
+        .descendants()
+        .filter(|node| node.has_tag_name("Value") && node.attribute("Protected") == Some("True"))
+    {
+        let text = node.text().unwrap_or_default();
+        let value = match inner_stream.as_mut() {
+            Some(inner_stream) => {
+                let mut bytes = base64::decode(text)?;
+                inner_stream.apply(&mut bytes);
+                SecString::from_utf8(bytes.into())?
+            }
+            None => text.into(),
+        };
+        drop(values.insert(node.range().start, value));
+    }
+    Ok(values)
+}
+
+/// Maps the groups and entries of the document and collects the skipped fields.
+struct Parser {
+    /// The decrypted protected values identified by the position of their node.
+    protected_values: HashMap<usize, SecString>,
+    /// The base64 encoded UUID of the recycle bin.
+    recycle_bin: Option<String>,
+    /// The fields which could not be represented in a vault.
+    skipped: Vec<SkippedField>,
+}
+
+impl Parser {
+    /// Map the group and all of its descendants.
+    ///
+    /// It expects:
+    ///  - The node of the group
+    ///  - The path of the group, which is empty for the root
+    fn group(&mut self, node: Node, path: &str) -> ImportGroup {
+        let mut group = ImportGroup {
+            title: child_text(node, "Name").unwrap_or_default(),
+            ..ImportGroup::default()
+        };
+        let item = if path.is_empty() { &group.title } else { path };
+
+        if child_text(node, "Notes").map_or(false, |notes| !notes.is_empty()) {
+            self.skipped.push(SkippedField::new(item, "Notes"));
+        }
+        if child(node, "CustomIconUUID").is_some() {
+            self.skipped.push(SkippedField::new(item, "Custom icon"));
+        }
+
+        for child_node in node.children().filter(Node::is_element) {
+            if child_node.has_tag_name("Group") {
+                let title = child_text(child_node, "Name").unwrap_or_default();
+                let child_path = join(path, &title);
+                if self.recycle_bin.is_some() && child_text(child_node, "UUID") == self.recycle_bin
+                {
+                    self.skipped
+                        .push(SkippedField::new(&child_path, "Recycle bin"));
+                } else {
+                    group.groups.push(self.group(child_node, &child_path));
+                }
+            } else if child_node.has_tag_name("Entry") {
+                group.entries.push(self.entry(child_node, path));
+            }
+        }
+
+        group
+    }
+
+    /// Map the entry.
+    ///
+    /// It expects:
+    ///  - The node of the entry
+    ///  - The path of the parent group, which is empty for the root
+    fn entry(&mut self, node: Node, path: &str) -> ImportEntry {
+        let mut entry = ImportEntry::default();
+        let mut custom_fields = Vec::new();
+
+        for string in node.children().filter(|node| node.has_tag_name("String")) {
+            let key = child_text(string, "Key").unwrap_or_default();
+            let value = child(string, "Value").map_or_else(SecString::new, |value| {
+                self.protected_values
+                    .get(&value.range().start)
+                    .cloned()
+                    .unwrap_or_else(|| value.text().unwrap_or_default().into())
+            });
+
+            match key.as_str() {
+                "Title" => entry.title = value.as_str().to_owned(),
+                "UserName" => entry.username = value,
+                "Password" => entry.password = value,
+                "URL" => entry.web_address = value.as_str().to_owned(),
+                _ if key.eq_ignore_ascii_case("email") || key.eq_ignore_ascii_case("e-mail") => {
+                    entry.email = value;
+                }
+                _ if !value.is_empty() => custom_fields.push(key),
+                _ => {}
+            }
+        }
+
+        if let Some(times) = child(node, "Times") {
+            entry.created = child_text(times, "CreationTime")
+                .and_then(|time| parse_time(&time))
+                .unwrap_or_default();
+            entry.last_modified = child_text(times, "LastModificationTime")
+                .and_then(|time| parse_time(&time))
+                .unwrap_or_default();
+            if child_text(times, "Expires").as_deref() == Some("True") {
+                custom_fields.push("Expiry date".into());
+            }
+        }
+
+        for binary in node.children().filter(|node| node.has_tag_name("Binary")) {
+            custom_fields.push(format!(
+                "Attachment {}",
+                child_text(binary, "Key").unwrap_or_default()
+            ));
+        }
+        if child_text(node, "Tags").map_or(false, |tags| !tags.is_empty()) {
+            custom_fields.push("Tags".into());
+        }
+        if child(node, "CustomIconUUID").is_some() {
+            custom_fields.push("Custom icon".into());
+        }
+        if child(node, "AutoType")
+            .and_then(|auto_type| child_text(auto_type, "DefaultSequence"))
+            .map_or(false, |sequence| !sequence.is_empty())
+        {
+            custom_fields.push("Auto-Type sequence".into());
+        }
+        if child(node, "History").map_or(false, |history| {
+            history.children().any(|node| node.has_tag_name("Entry"))
+        }) {
+            custom_fields.push("History".into());
+        }
+
+        let item = join(path, &entry.title);
+        for field in custom_fields {
+            self.skipped.push(SkippedField::new(&item, &field));
+        }
+
+        entry
+    }
+}
+
+impl Drop for Parser {
+    fn drop(&mut self) {
+        self.protected_values
+            .values_mut()
+            .for_each(Zeroize::zeroize);
+    }
+}
+
+/// Get the first child element of the node with the given tag name.
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+/// Get the text of the first child element of the node with the given tag name.
+fn child_text(node: Node, name: &str) -> Option<String> {
+    child(node, name).map(|child| child.text().unwrap_or_default().to_owned())
+}
+
+/// Join the path of a group with the title of one of its children.
+fn join(path: &str, title: &str) -> String {
+    if path.is_empty() {
+        title.to_owned()
+    } else {
+        format!("{}/{}", path, title)
+    }
+}
+
+/// Parse a time of a `KeePass` database into seconds since the unix epoch.
+///
+/// KDBX 4 stores the seconds since the first of January of the year 1 as base64 encoded
+/// 64 bit integer, while older versions and XML exports use ISO 8601 (`2021-03-04T05:06:07Z`).
+fn parse_time(time: &str) -> Option<u64> {
+    let seconds = if time.contains('-') {
+        parse_iso_8601(time)?
+    } else {
+        let bytes = base64::decode(time).ok()?;
+        let mut seconds = [0_u8; 8];
+        seconds.copy_from_slice(bytes.get(..8)?);
+        i64::from_le_bytes(seconds).checked_sub(SECONDS_UNTIL_UNIX_EPOCH)?
+    };
+    u64::try_from(seconds).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kdbx::read_database;
+
+    use super::{parse, parse_time, SkippedField};
+
+    #[test]
+    fn parse_kdbx() {
+        let data = include_bytes!("../../tests/resources/argon2id_chacha20.kdbx");
+        let mut database = read_database(data, "password").unwrap();
+        let import = parse(&database.xml, Some(&mut database.inner_stream)).unwrap();
+
+        let root = import.root();
+        assert_eq!(root.title(), "Team");
+        assert_eq!(root.entries().len(), 1);
+        // The recycle bin is not imported.
+        assert_eq!(root.groups().len(), 1);
+
+        let entry = &root.entries()[0];
+        assert_eq!(entry.title(), "Mail & Calendar");
+        assert_eq!(entry.username().as_str(), "alice");
+        assert_eq!(entry.password().as_str(), "s3cr3t <pw>");
+        assert_eq!(entry.email().as_str(), "alice@example.com");
+        assert_eq!(entry.web_address(), "https://mail.example.com");
+        assert_eq!(entry.created(), 1_614_834_367);
+        assert_eq!(entry.last_modified(), 1_623_053_350);
+
+        let servers = &root.groups()[0];
+        assert_eq!(servers.title(), "Servers");
+        assert_eq!(servers.groups()[0].title(), "Empty");
+        let database = &servers.entries()[0];
+        assert_eq!(database.title(), "Database");
+        assert_eq!(database.username().as_str(), "root");
+        assert_eq!(database.password().as_str(), "hunter2");
+        assert_eq!(database.web_address(), "");
+
+        let skipped: Vec<String> = import
+            .skipped()
+            .iter()
+            .map(SkippedField::to_string)
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                "Mail & Calendar: Notes",
+                "Mail & Calendar: Recovery code",
+                "Mail & Calendar: Attachment backup.txt",
+                "Mail & Calendar: Tags",
+                "Mail & Calendar: Auto-Type sequence",
+                "Mail & Calendar: History",
+                "Servers: Notes",
+                "Recycle Bin: Recycle bin",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_plain_xml() {
+        let xml = r#"<KeePassFile><Root><Group><Name>Root</Name>
+            <Entry>
+                <String><Key>Title</Key><Value>Plain</Value></String>
+                <String><Key>Password</Key><Value Protected="True">secret</Value></String>
+            </Entry>
+        </Group></Root></KeePassFile>"#;
+        let import = parse(xml, None).unwrap();
+        assert_eq!(import.entry_count(), 1);
+        assert_eq!(import.root().entries()[0].password().as_str(), "secret");
+        assert!(import.skipped().is_empty());
+
+        assert!(parse("<Database/>", None).is_err());
+        assert!(parse("<KeePassFile><Root/></KeePassFile>", None).is_err());
+        assert!(parse("no xml", None).is_err());
+    }
+
+    #[test]
+    fn parse_times() {
+        assert_eq!(parse_time("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_time("2021-03-04T05:06:07Z"), Some(1_614_834_367));
+        assert_eq!(parse_time("2000-02-29T12:00:00Z"), Some(951_825_600));
+        assert_eq!(parse_time("APeRdw4AAAA="), Some(0));
+        assert_eq!(parse_time("invalid"), None);
+        assert_eq!(parse_time("1960-01-01T00:00:00Z"), None);
+    }
+}
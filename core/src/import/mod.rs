@@ -0,0 +1,338 @@
+//! Import groups and entries from the databases of other password managers.
+use std::{fmt::Display, path::Path};
+
+use getset::{CopyGetters, Getters};
+use zeroize::Zeroize;
+
+use crate::{error::PWDuckCoreError, io::load_import_file, kdbx, mem_protection::SecString};
+
+mod bitwarden;
+
+mod csv_file;
+pub use csv_file::{CsvField, CsvFile, CsvMapping, CsvPreset};
+
+mod keepass_xml;
+
+/// The byte order mark some password managers write at the start of their exports.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// The groups and entries read from the database of another password manager.
+#[derive(Clone, Debug, Default, Getters)]
+pub struct Import {
+    /// The root group containing the imported groups and entries.
+    #[getset(get = "pub")]
+    root: ImportGroup,
+
+    /// The fields which could not be represented in a vault.
+    #[getset(get = "pub")]
+    skipped: Vec<SkippedField>,
+}
+
+impl Import {
+    /// Read the groups and entries of a `KeePass` database in the format KDBX 4.
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the `KeePass` database
+    ///  - The password of the `KeePass` database
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can't be read, the password is wrong or the database is not
+    /// supported.
+    pub fn from_kdbx(path: &Path, password: &str) -> Result<Self, PWDuckCoreError> {
+        let data = load_import_file(path)?;
+        let mut database = kdbx::read_database(&data, password)?;
+        keepass_xml::parse(&database.xml, Some(&mut database.inner_stream))
+    }
+
+    /// Read the groups and entries of an unencrypted XML export of `KeePass` 2 or `KeePassXC`.
+    /// The XML document is mapped exactly like the content of a `KeePass` database.
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the XML export
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can't be read or is no valid `KeePass` XML document.
+    pub fn from_keepass_xml(path: &Path) -> Result<Self, PWDuckCoreError> {
+        let mut data = load_import_file(path)?;
+        if data.starts_with(UTF8_BOM) {
+            drop(data.drain(..UTF8_BOM.len()));
+        }
+        let xml = SecString::from_utf8(data.into())?;
+        keepass_xml::parse(&xml, None)
+    }
+
+    /// Read the folders and items of an unencrypted `Bitwarden` export in the JSON format.
+    /// The folders are mapped to groups, the logins to entries.
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the export
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file can't be read, is no valid JSON document or is encrypted.
+    pub fn from_bitwarden(path: &Path) -> Result<Self, PWDuckCoreError> {
+        let mut data = load_import_file(path)?;
+        let import = bitwarden::parse(&data);
+        data.zeroize();
+        import
+    }
+
+    /// Map the rows of a CSV file to entries.
+    ///
+    /// It expects:
+    ///  - The [`CsvFile`](CsvFile) containing the rows
+    ///  - The [`CsvMapping`](CsvMapping) of the columns to the fields of the entries
+    #[must_use]
+    pub fn from_csv(file: &CsvFile, mapping: &CsvMapping) -> Self {
+        csv_file::map(file, mapping)
+    }
+
+    /// The number of imported groups, not counting the root.
+    #[must_use]
+    pub fn group_count(&self) -> usize {
+        self.root.group_count()
+    }
+
+    /// The number of imported entries.
+    #[must_use]
+    pub fn entry_count(&self) -> usize {
+        self.root.entry_count()
+    }
+}
+
+/// A group read from the database of another password manager.
+#[derive(Clone, Debug, Default, Getters)]
+pub struct ImportGroup {
+    /// The title of this group.
+    #[getset(get = "pub")]
+    title: String,
+
+    /// The subgroups of this group.
+    #[getset(get = "pub")]
+    groups: Vec<Self>,
+
+    /// The entries of this group.
+    #[getset(get = "pub")]
+    entries: Vec<ImportEntry>,
+}
+
+impl ImportGroup {
+    /// Get the descendant group identified by the path of titles.
+    /// Missing groups are created.
+    fn group_mut(&mut self, path: &[String]) -> &mut Self {
+        match path.split_first() {
+            None => self,
+            Some((title, rest)) => {
+                let index = if let Some(index) =
+                    self.groups.iter().position(|group| &group.title == title)
+                {
+                    index
+                } else {
+                    self.groups.push(Self {
+                        title: title.clone(),
+                        ..Self::default()
+                    });
+                    self.groups.len() - 1
+                };
+                self.groups[index].group_mut(rest)
+            }
+        }
+    }
+
+    /// The number of all descendant groups of this group.
+    fn group_count(&self) -> usize {
+        self.groups
+            .iter()
+            .map(|group| group.group_count() + 1)
+            .sum()
+    }
+
+    /// The number of all entries of this group and its descendants.
+    fn entry_count(&self) -> usize {
+        self.entries.len() + self.groups.iter().map(Self::entry_count).sum::<usize>()
+    }
+}
+
+/// An entry read from the database of another password manager.
+#[derive(Clone, Debug, Default, CopyGetters, Getters)]
+pub struct ImportEntry {
+    /// The title of this entry.
+    #[getset(get = "pub")]
+    title: String,
+
+    /// The username of this entry.
+    #[getset(get = "pub")]
+    username: SecString,
+
+    /// The password of this entry.
+    #[getset(get = "pub")]
+    password: SecString,
+
+    /// The email of this entry.
+    #[getset(get = "pub")]
+    email: SecString,
+
+    /// The address of the website this entry belongs to.
+    #[getset(get = "pub")]
+    web_address: String,
+
+    /// The time this entry was created as seconds since the unix epoch or 0, if unknown.
+    #[getset(get_copy = "pub")]
+    created: u64,
+
+    /// The time this entry was modified the last time as seconds since the unix epoch or 0, if unknown.
+    #[getset(get_copy = "pub")]
+    last_modified: u64,
+
+    /// If this entry is pinned as a favourite.
+    #[getset(get_copy = "pub")]
+    favourite: bool,
+}
+
+/// A field which could not be represented in a vault and was skipped by the import.
+#[derive(Clone, Debug, Getters, PartialEq, Eq)]
+pub struct SkippedField {
+    /// The path of the group or entry containing the field.
+    #[getset(get = "pub")]
+    item: String,
+
+    /// The name of the skipped field.
+    #[getset(get = "pub")]
+    field: String,
+}
+
+impl SkippedField {
+    /// Create a new [`SkippedField`](SkippedField).
+    fn new(item: &str, field: &str) -> Self {
+        Self {
+            item: item.to_owned(),
+            field: field.to_owned(),
+        }
+    }
+}
+
+impl Display for SkippedField {
+    #[cfg_attr(coverage, no_coverage)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.item, self.field)
+    }
+}
+
+/// Parse an UTC time in the format `YYYY-MM-DDTHH:MM:SSZ` into seconds since the unix epoch.
+/// Fractions of seconds (`YYYY-MM-DDTHH:MM:SS.sssZ`) are ignored.
+fn parse_iso_8601(time: &str) -> Option<i64> {
+    let time = time.trim_end_matches('Z');
+    let (date, clock) = time.split_once('T')?;
+    let clock = clock.split('.').next()?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut clock = clock.splitn(3, ':').map(str::parse::<i64>);
+    let (hour, minute, second) = (
+        clock.next()?.ok()?,
+        clock.next()?.ok()?,
+        clock.next()?.ok()?,
+    );
+
+    // The days since the unix epoch, see: http://howardhinnant.github.io/date_algorithms.html
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{parse_iso_8601, Import};
+
+    #[test]
+    fn from_kdbx() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources");
+
+        for file in &["argon2id_chacha20.kdbx", "aes_kdf_aes256.kdbx"] {
+            let import = Import::from_kdbx(&path.join(file), "password").unwrap();
+            assert_eq!(import.group_count(), 2);
+            assert_eq!(import.entry_count(), 2);
+            assert_eq!(import.root().title(), "Team");
+
+            let entry = &import.root().entries()[0];
+            assert_eq!(entry.title(), "Mail & Calendar");
+            assert_eq!(entry.username().as_str(), "alice");
+            assert_eq!(entry.password().as_str(), "s3cr3t <pw>");
+
+            let database = &import.root().groups()[0].entries()[0];
+            assert_eq!(database.title(), "Database");
+            assert_eq!(database.password().as_str(), "hunter2");
+
+            assert!(Import::from_kdbx(&path.join(file), "wrong password").is_err());
+        }
+
+        assert!(Import::from_kdbx(&path.join("missing.kdbx"), "password").is_err());
+    }
+
+    #[test]
+    fn from_keepass_xml() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources");
+
+        let import = Import::from_keepass_xml(&path.join("keepass.xml")).unwrap();
+        assert_eq!(import.root().title(), "Team");
+        // The recycle bin is not imported.
+        assert_eq!(import.group_count(), 2);
+        // The history is not imported as additional entries.
+        assert_eq!(import.entry_count(), 2);
+
+        let entry = &import.root().entries()[0];
+        assert_eq!(entry.title(), "Mail & Calendar");
+        assert_eq!(entry.username().as_str(), "alice");
+        assert_eq!(entry.password().as_str(), "s3cr3t <pw>");
+        assert_eq!(entry.email().as_str(), "alice@example.com");
+        assert_eq!(entry.web_address(), "https://mail.example.com");
+        assert_eq!(entry.created(), 1_614_834_367);
+        assert_eq!(entry.last_modified(), 1_623_053_350);
+
+        let databases = &import.root().groups()[0].groups()[0];
+        assert_eq!(databases.title(), "Databases");
+        assert_eq!(databases.entries()[0].password().as_str(), "hunter2");
+
+        let skipped: Vec<String> = import.skipped().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            skipped,
+            vec![
+                "Mail & Calendar: Recovery code",
+                "Mail & Calendar: History",
+                "Recycle Bin: Recycle bin",
+            ]
+        );
+
+        assert!(Import::from_keepass_xml(&path.join("bitwarden.json")).is_err());
+        assert!(Import::from_keepass_xml(&path.join("missing.xml")).is_err());
+    }
+
+    #[test]
+    fn from_bitwarden() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources");
+
+        let import = Import::from_bitwarden(&path.join("bitwarden.json")).unwrap();
+        assert_eq!(import.root().title(), "Bitwarden");
+        assert_eq!(import.entry_count(), 3);
+
+        assert!(Import::from_bitwarden(&path.join("missing.json")).is_err());
+    }
+
+    #[test]
+    fn iso_8601() {
+        assert_eq!(parse_iso_8601("2021-03-04T05:06:07Z"), Some(1_614_834_367));
+        assert_eq!(
+            parse_iso_8601("2021-03-04T05:06:07.123Z"),
+            Some(1_614_834_367)
+        );
+        assert_eq!(parse_iso_8601("2021-03-04"), None);
+    }
+}
@@ -0,0 +1,157 @@
+//! The functions to collect the files of a vault for a backup and to restore them.
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{backup::BackupFile, PWDuckCoreError};
+
+/// Load all files of the [`Vault`](Vault) to store them in a backup.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the [`Vault`](Vault)
+///
+/// The files are sorted by their path relative to the location of the [`Vault`](Vault).
+pub fn load_vault_files(path: &Path) -> Result<Vec<BackupFile>, PWDuckCoreError> {
+    let mut files = Vec::new();
+    load_files_of_dir(path, "", &mut files)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Load the files of the directory and all of its subdirectories.
+fn load_files_of_dir(
+    dir: &Path,
+    prefix: &str,
+    files: &mut Vec<BackupFile>,
+) -> Result<(), PWDuckCoreError> {
+    for item in fs::read_dir(dir)? {
+        let item = item?;
+        let name = item.file_name().into_string().map_err(|_err| {
+            PWDuckCoreError::Error("The vault contains an invalid file name".into())
+        })?;
+        let path = format!("{}{}", prefix, name);
+        if item.file_type()?.is_dir() {
+            load_files_of_dir(&item.path(), &format!("{}/", path), files)?;
+        } else {
+            files.push(BackupFile {
+                path,
+                data: fs::read(item.path())?,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Save the files of a backup as a new [`Vault`](Vault).
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the new [`Vault`](Vault). It must not exist yet.
+///  - The files of the backup
+pub fn save_vault_files(path: &Path, files: &[BackupFile]) -> Result<(), PWDuckCoreError> {
+    if path.exists() {
+        return Err(PWDuckCoreError::Error(format!(
+            "The directory {} already exists",
+            path.display()
+        )));
+    }
+
+    let targets = files
+        .iter()
+        .map(|file| backup_file_path(path, &file.path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    super::create_new_vault_dir(path)?;
+    for (file, target) in files.iter().zip(targets) {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(target, &file.data)?;
+    }
+    Ok(())
+}
+
+/// Resolve the relative path of a file of the backup below the location of the [`Vault`](Vault).
+///
+/// Paths leaving the location of the [`Vault`](Vault) are rejected.
+fn backup_file_path(path: &Path, relative: &str) -> Result<PathBuf, PWDuckCoreError> {
+    let relative = Path::new(relative);
+    if relative.as_os_str().is_empty()
+        || !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(PWDuckCoreError::Error(format!(
+            "The backup contains the invalid path {}",
+            relative.display()
+        )));
+    }
+    Ok(path.join(relative))
+}
+
+/// Save the backup archive to disk.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the archive
+///  - The content of the archive
+pub fn save_backup_file(path: &Path, data: &[u8]) -> Result<(), PWDuckCoreError> {
+    Ok(fs::write(path, data)?)
+}
+
+/// Load the backup archive from disk.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the archive
+pub fn load_backup_file(path: &Path) -> Result<Vec<u8>, PWDuckCoreError> {
+    Ok(fs::read(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use crate::{backup::BackupFile, io::create_new_vault_dir};
+
+    use super::{load_vault_files, save_vault_files};
+
+    #[test]
+    fn load_and_save_vault_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("Vault");
+        create_new_vault_dir(&path).unwrap();
+        fs::write(path.join("master_key.pwduck"), "KEY").unwrap();
+        fs::write(path.join("groups").join("Z3JvdXA="), "GROUP").unwrap();
+
+        let files = load_vault_files(&path).unwrap();
+        let paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, ["groups/Z3JvdXA=", "master_key.pwduck"]);
+
+        let restored = dir.path().join("Restored");
+        save_vault_files(&restored, &files).unwrap();
+        assert_eq!(
+            fs::read_to_string(restored.join("groups").join("Z3JvdXA=")).unwrap(),
+            "GROUP"
+        );
+        assert!(restored.join("entries").join("head").exists());
+
+        // The target must not exist yet.
+        assert!(save_vault_files(&restored, &files).is_err());
+    }
+
+    #[test]
+    fn reject_invalid_paths() {
+        let dir = tempdir().unwrap();
+
+        for path in ["../escape", "/absolute", "groups/../../escape", ""] {
+            let files = vec![BackupFile {
+                path: path.into(),
+                data: Vec::new(),
+            }];
+            let target = dir.path().join("Vault");
+            assert!(save_vault_files(&target, &files).is_err());
+            assert!(!target.exists());
+        }
+    }
+}
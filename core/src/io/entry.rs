@@ -37,6 +37,40 @@ pub fn load_entry_head(path: &Path, uuid: &Uuid) -> Result<EntryHead, PWDuckCore
     Ok(ron::from_str(&content)?)
 }
 
+/// Hash the files of the [`EntryHead`](EntryHead) and its [`EntryBody`](EntryBody) on disk.
+/// Returns `None` if the [`EntryHead`](EntryHead) does not exist on disk.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the [`Vault`](Vault)
+///  - The UUID as the identifier of the [`EntryHead`](EntryHead)
+///  - The UUID as the identifier of the [`EntryBody`](EntryBody)
+pub fn hash_entry(
+    path: &Path,
+    head_uuid: &Uuid,
+    body_uuid: &Uuid,
+) -> Result<Option<String>, PWDuckCoreError> {
+    let head_hash = match super::hash_file(
+        &path
+            .join(ENTRIES_DIR)
+            .join(HEAD)
+            .join(head_uuid.base64_hash()),
+    )? {
+        Some(hash) => hash,
+        None => return Ok(None),
+    };
+    let body_hash = super::hash_file(
+        &path
+            .join(ENTRIES_DIR)
+            .join(BODY)
+            .join(body_uuid.base64_hash()),
+    )?;
+    Ok(Some(format!(
+        "{}:{}",
+        head_hash,
+        body_hash.unwrap_or_default()
+    )))
+}
+
 /// Load all [`EntryHead`](EntryHead)s of a vault.
 ///
 /// It expects:
@@ -140,7 +174,8 @@ mod tests {
     };
 
     use super::{
-        load_all_entry_heads, load_entry_body, load_entry_head, save_entry_body, save_entry_head,
+        hash_entry, load_all_entry_heads, load_entry_body, load_entry_head, save_entry_body,
+        save_entry_head,
     };
 
     #[test]
@@ -208,6 +243,49 @@ mod tests {
         assert_eq!(body.content(), loaded.content());
     }
 
+    #[test]
+    fn hash_entry_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        create_new_vault_dir(&path).unwrap();
+
+        let head_uuid: Uuid = [42_u8; uuid::SIZE].into();
+        let body_uuid: Uuid = [21_u8; uuid::SIZE].into();
+        assert!(hash_entry(&path, &head_uuid, &body_uuid).unwrap().is_none());
+
+        save_entry_head(
+            &path,
+            &head_uuid,
+            &EntryHead::new("IV".into(), "HEAD".into()),
+        )
+        .unwrap();
+        save_entry_body(
+            &path,
+            &body_uuid,
+            &EntryBody::new("IV".into(), "BODY".into()),
+        )
+        .unwrap();
+        let hash = hash_entry(&path, &head_uuid, &body_uuid)
+            .unwrap()
+            .expect("Entry should exist.");
+        assert_eq!(
+            hash_entry(&path, &head_uuid, &body_uuid).unwrap(),
+            Some(hash.clone())
+        );
+
+        // Changing only the body changes the hash.
+        save_entry_body(
+            &path,
+            &body_uuid,
+            &EntryBody::new("IV".into(), "CHANGED".into()),
+        )
+        .unwrap();
+        assert_ne!(
+            hash_entry(&path, &head_uuid, &body_uuid).unwrap(),
+            Some(hash)
+        );
+    }
+
     #[test]
     fn delete_entry() {
         let dir = tempdir().unwrap();
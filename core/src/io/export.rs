@@ -0,0 +1,13 @@
+//! The functions to save the files of other password managers.
+use std::{fs, path::Path};
+
+use crate::PWDuckCoreError;
+
+/// Save the exported file to disk.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the file
+///  - The content of the file
+pub fn save_export_file(path: &Path, data: &[u8]) -> Result<(), PWDuckCoreError> {
+    Ok(fs::write(path, data)?)
+}
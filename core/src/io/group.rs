@@ -44,6 +44,16 @@ pub fn load_group(path: &Path, uuid: &Uuid) -> Result<Group, PWDuckCoreError> {
     Ok(ron::from_str(&content)?)
 }
 
+/// Hash the file of the [`Group`](Group) on disk.
+/// Returns `None` if the [`Group`](Group) does not exist on disk.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the [`Vault`](Vault)
+///  - The UUID as the identifier of the [`Group`](Group)
+pub fn hash_group(path: &Path, uuid: &Uuid) -> Result<Option<String>, PWDuckCoreError> {
+    super::hash_file(&path.join(GROUPS_DIR).join(uuid.base64_hash()))
+}
+
 /// Load all [`Group`](Group)s of a vault.
 ///
 /// It expects:
@@ -73,7 +83,7 @@ mod tests {
         Uuid,
     };
 
-    use super::{load_all_groups, load_group, save_group};
+    use super::{hash_group, load_all_groups, load_group, save_group};
 
     #[test]
     fn save_and_load_group() {
@@ -122,6 +132,25 @@ mod tests {
         })
     }
 
+    #[test]
+    fn hash_group_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        create_new_vault_dir(&path).unwrap();
+
+        let uuid: Uuid = [42_u8; uuid::SIZE].into();
+        assert!(hash_group(&path, &uuid).unwrap().is_none());
+
+        save_group(&path, &uuid, &Group::new("IV".into(), "CONTENT".into())).unwrap();
+        let hash = hash_group(&path, &uuid)
+            .unwrap()
+            .expect("Group should exist.");
+        assert_eq!(hash_group(&path, &uuid).unwrap(), Some(hash.clone()));
+
+        save_group(&path, &uuid, &Group::new("IV".into(), "CHANGED".into())).unwrap();
+        assert_ne!(hash_group(&path, &uuid).unwrap(), Some(hash));
+    }
+
     #[test]
     fn delete_group() {
         let dir = tempdir().unwrap();
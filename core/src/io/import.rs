@@ -0,0 +1,12 @@
+//! The functions to load the files of other password managers.
+use std::{fs, path::Path};
+
+use crate::PWDuckCoreError;
+
+/// Load the file to import from disk.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the file
+pub fn load_import_file(path: &Path) -> Result<Vec<u8>, PWDuckCoreError> {
+    Ok(fs::read(path)?)
+}
@@ -4,6 +4,7 @@ use std::path::Path;
 use crate::{
     cryptography::fill_random_bytes,
     model::uuid::{self, Uuid},
+    PWDuckCoreError,
 };
 
 /// The directory name of the groups
@@ -12,6 +13,9 @@ pub const GROUPS_DIR: &str = "groups";
 /// The directory name of the entries
 pub const ENTRIES_DIR: &str = "entries";
 
+/// The directory name of the saved searches
+pub const SAVED_SEARCHES_DIR: &str = "searches";
+
 /// The directory name of the entry heads.
 pub const HEAD: &str = "head";
 
@@ -21,29 +25,57 @@ pub const BODY: &str = "body";
 /// The file name of the master key
 pub const MASTER_KEY_NAME: &str = "master_key.pwduck";
 
+/// The file name of the vault settings
+pub const VAULT_SETTINGS_NAME: &str = "settings.pwduck";
+
 /// The directory name of the application settings.
 pub const APPLICATION_SETTINGS_DIR: &str = "PWDuck";
 /// The file name of the application settings.
 pub const APPLICATION_SETTINGS_NAME: &str = "settings.ron";
 
+mod backup;
+pub use backup::*;
+
 mod entry;
 pub use entry::*;
 
 mod group;
 pub use group::*;
 
+mod export;
+pub use export::*;
+
+mod import;
+pub use import::*;
+
 mod key_file;
 pub use key_file::*;
 
 mod master_key;
 pub use master_key::*;
 
+mod saved_search;
+pub use saved_search::*;
+
 mod settings;
 pub use settings::*;
 
 mod vault;
 pub use vault::*;
 
+mod vault_settings;
+pub use vault_settings::*;
+
+/// Hash the content of the file at the given location.
+/// Returns `None` if the file does not exist.
+fn hash_file(file: &Path) -> Result<Option<String>, PWDuckCoreError> {
+    match std::fs::read(file) {
+        Ok(content) => Ok(Some(sha256::digest_bytes(&content))),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
 /// Generate a random UUID for the given path.
 pub fn generate_uuid(path: &Path) -> Uuid {
     let mut uuid = [0_u8; uuid::SIZE];
@@ -54,6 +86,7 @@ pub fn generate_uuid(path: &Path) -> Uuid {
 
         if !path.join(GROUPS_DIR).join(&file_name).exists()
             && !path.join(ENTRIES_DIR).join(&file_name).exists()
+            && !path.join(SAVED_SEARCHES_DIR).join(&file_name).exists()
         {
             break;
         }
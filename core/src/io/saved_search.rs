@@ -0,0 +1,127 @@
+//! The functions to save, load and delete saved searches.
+use std::{fs, path::Path};
+
+use crate::{dto::saved_search::SavedSearch, PWDuckCoreError, Uuid};
+
+use super::SAVED_SEARCHES_DIR;
+
+/// Save the [`SavedSearch`](SavedSearch) to disk.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the [`Vault`](Vault)
+///  - The UUID as the identifier of the [`SavedSearch`](SavedSearch)
+///  - The [`SavedSearch`](SavedSearch) to save
+pub fn save_saved_search(
+    path: &Path,
+    uuid: &Uuid,
+    saved_search: &SavedSearch,
+) -> Result<(), PWDuckCoreError> {
+    let directory = path.join(SAVED_SEARCHES_DIR);
+    // Vaults created before saved searches existed do not contain the directory.
+    fs::create_dir_all(&directory)?;
+    fs::write(
+        directory.join(uuid.base64_hash()),
+        ron::to_string(&saved_search)?,
+    )?;
+    Ok(())
+}
+
+/// Delete the [`SavedSearch`](SavedSearch) from disk.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the [`Vault`](Vault)
+///  - The UUID as the identifier of the [`SavedSearch`](SavedSearch)
+pub fn delete_saved_search(path: &Path, uuid: &Uuid) -> Result<(), PWDuckCoreError> {
+    let saved_search_path = path.join(SAVED_SEARCHES_DIR).join(uuid.base64_hash());
+    if saved_search_path.exists() {
+        fs::remove_file(saved_search_path)?;
+    }
+    Ok(())
+}
+
+/// Load all [`SavedSearch`](SavedSearch)es of a vault.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the [`Vault`](Vault)
+pub fn load_all_saved_searches(path: &Path) -> Result<Vec<SavedSearch>, PWDuckCoreError> {
+    let directory = path.join(SAVED_SEARCHES_DIR);
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+
+    fs::read_dir(directory)?
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .map(|file| fs::read_to_string(file.path()))
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .map(|content| ron::from_str(content).map_err(PWDuckCoreError::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        dto::saved_search::SavedSearch,
+        io::{create_new_vault_dir, SAVED_SEARCHES_DIR},
+        model::uuid,
+        Uuid,
+    };
+
+    use super::{delete_saved_search, load_all_saved_searches, save_saved_search};
+
+    #[test]
+    fn save_load_and_delete_saved_searches() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        create_new_vault_dir(&path).unwrap();
+
+        let mut saved_searches: Vec<(SavedSearch, Uuid)> = (0..=10)
+            .into_iter()
+            .map(|n| {
+                let saved_search = SavedSearch::new(format!("IV: {}", n), format!("Search: {}", n));
+                let uuid: Uuid = [n; uuid::SIZE].into();
+
+                save_saved_search(&path, &uuid, &saved_search)
+                    .expect("Saving a saved search should not fail.");
+
+                (saved_search, uuid)
+            })
+            .collect();
+
+        let mut loaded =
+            load_all_saved_searches(&path).expect("Loading all saved searches should not fail.");
+
+        saved_searches.sort_by(|a, b| a.0.iv().cmp(b.0.iv()));
+        loaded.sort_by(|a, b| a.iv().cmp(b.iv()));
+
+        assert_eq!(saved_searches.len(), loaded.len());
+        saved_searches
+            .iter()
+            .zip(loaded.iter())
+            .for_each(|((a, _), b)| {
+                assert_eq!(a.iv(), b.iv());
+                assert_eq!(a.content(), b.content());
+            });
+
+        let uuid = &saved_searches[0].1;
+        let saved_search_path = path.join(SAVED_SEARCHES_DIR).join(uuid.base64_hash());
+        assert!(saved_search_path.exists());
+        delete_saved_search(&path, uuid).expect("Deleting a saved search should not fail.");
+        assert!(!saved_search_path.exists());
+    }
+
+    #[test]
+    fn load_saved_searches_of_old_vault() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        create_new_vault_dir(&path).unwrap();
+        fs::remove_dir(path.join(SAVED_SEARCHES_DIR)).unwrap();
+
+        assert!(load_all_saved_searches(&path).unwrap().is_empty());
+    }
+}
@@ -3,7 +3,7 @@ use std::{fs, path::Path};
 
 use crate::PWDuckCoreError;
 
-use super::{BODY, ENTRIES_DIR, GROUPS_DIR, HEAD};
+use super::{BODY, ENTRIES_DIR, GROUPS_DIR, HEAD, SAVED_SEARCHES_DIR};
 
 /// Create the directory structure of a new [Vault](Vault) on the given path.
 pub fn create_new_vault_dir(path: &Path) -> Result<(), PWDuckCoreError> {
@@ -12,6 +12,7 @@ pub fn create_new_vault_dir(path: &Path) -> Result<(), PWDuckCoreError> {
     fs::create_dir_all(path.join(ENTRIES_DIR))?;
     fs::create_dir_all(path.join(ENTRIES_DIR).join(HEAD))?;
     fs::create_dir_all(path.join(ENTRIES_DIR).join(BODY))?;
+    fs::create_dir_all(path.join(SAVED_SEARCHES_DIR))?;
     Ok(())
 }
 
@@ -19,7 +20,9 @@ pub fn create_new_vault_dir(path: &Path) -> Result<(), PWDuckCoreError> {
 mod tests {
     use tempfile::tempdir;
 
-    use crate::io::{create_new_vault_dir, BODY, ENTRIES_DIR, GROUPS_DIR, HEAD};
+    use crate::io::{
+        create_new_vault_dir, BODY, ENTRIES_DIR, GROUPS_DIR, HEAD, SAVED_SEARCHES_DIR,
+    };
 
     #[test]
     fn vault_dir_creation() {
@@ -31,12 +34,14 @@ mod tests {
         let expected_entries = expected.join(ENTRIES_DIR);
         let expected_entrie_heads = expected_entries.join(HEAD);
         let expected_entrie_bodies = expected_entries.join(BODY);
+        let expected_saved_searches = expected.join(SAVED_SEARCHES_DIR);
 
         assert!(!expected.exists());
         assert!(!expected_groups.exists());
         assert!(!expected_entries.exists());
         assert!(!expected_entrie_heads.exists());
         assert!(!expected_entrie_bodies.exists());
+        assert!(!expected_saved_searches.exists());
 
         create_new_vault_dir(&expected).expect("Creation of new vault dir should not fail.");
 
@@ -45,5 +50,6 @@ mod tests {
         assert!(expected_entries.exists());
         assert!(expected_entrie_heads.exists());
         assert!(expected_entrie_bodies.exists());
+        assert!(expected_saved_searches.exists());
     }
 }
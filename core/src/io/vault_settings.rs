@@ -0,0 +1,68 @@
+//! The functions to save and load the settings of a vault.
+use std::{fs, path::Path};
+
+use crate::{dto::vault_settings::VaultSettings, PWDuckCoreError};
+
+use super::VAULT_SETTINGS_NAME;
+
+/// Save the [`VaultSettings`](VaultSettings) to disk.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the [`Vault`](Vault)
+///  - The [`VaultSettings`](VaultSettings) to save
+pub fn save_vault_settings(
+    path: &Path,
+    vault_settings: &VaultSettings,
+) -> Result<(), PWDuckCoreError> {
+    fs::write(
+        path.join(VAULT_SETTINGS_NAME),
+        ron::to_string(vault_settings)?,
+    )?;
+    Ok(())
+}
+
+/// Load the [`VaultSettings`](VaultSettings) from disk.
+///
+/// Returns `None` if the vault does not contain any settings yet.
+///
+/// It expects:
+///  - The [`Path`](Path) as the location of the [`Vault`](Vault)
+pub fn load_vault_settings(path: &Path) -> Result<Option<VaultSettings>, PWDuckCoreError> {
+    let settings_path = path.join(VAULT_SETTINGS_NAME);
+    if !settings_path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(settings_path)?;
+    Ok(Some(ron::from_str(&content)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::{dto::vault_settings::VaultSettings, io::create_new_vault_dir};
+
+    use super::{load_vault_settings, save_vault_settings};
+
+    #[test]
+    fn save_and_load_vault_settings() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        create_new_vault_dir(&path).unwrap();
+
+        assert!(load_vault_settings(&path)
+            .expect("Loading missing vault settings should not fail.")
+            .is_none());
+
+        let vault_settings = VaultSettings::new("IV".into(), "CONTENT".into());
+        save_vault_settings(&path, &vault_settings)
+            .expect("Saving vault settings should not fail.");
+
+        let loaded = load_vault_settings(&path)
+            .expect("Loading vault settings should not fail.")
+            .unwrap();
+        assert_eq!(vault_settings.iv(), loaded.iv());
+        assert_eq!(vault_settings.content(), loaded.content());
+    }
+}
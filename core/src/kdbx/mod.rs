@@ -0,0 +1,830 @@
+//! The database format of `KeePass` in version 4 (KDBX 4).
+//!
+//! See: <https://keepass.info/help/kb/kdbx_4.html>
+use std::{collections::HashMap, convert::TryFrom};
+
+use aes::{
+    cipher::generic_array::GenericArray,
+    {Aes256, BlockEncrypt, NewBlockCipher},
+};
+use argon2::Argon2;
+use chacha20::{
+    cipher::{NewCipher, StreamCipher},
+    ChaCha20, Key, Nonce,
+};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::{
+    cryptography::{
+        aes_cbc_decrypt, aes_cbc_encrypt, chacha20_decrypt, chacha20_encrypt, fill_random_bytes,
+        AES_IV_LENGTH, CHACHA20_NONCE_LENGTH,
+    },
+    error::PWDuckCoreError,
+    mem_protection::SecVec,
+};
+
+mod read;
+pub use read::read_database;
+
+mod write;
+pub use write::write_database;
+
+/// The first signature of every `KeePass` database.
+pub const SIGNATURE_1: u32 = 0x9AA2_D903;
+/// The second signature of a `KeePass` 2 database.
+pub const SIGNATURE_2: u32 = 0xB54B_FB67;
+/// The supported major version of the KDBX format.
+pub const MAJOR_VERSION: u16 = 4;
+
+/// The header field marking the end of a header.
+pub const HEADER_END: u8 = 0;
+/// The outer header field containing the UUID of the [`OuterCipher`](OuterCipher).
+pub const HEADER_CIPHER_ID: u8 = 2;
+/// The outer header field containing the compression flags of the payload.
+pub const HEADER_COMPRESSION_FLAGS: u8 = 3;
+/// The outer header field containing the master seed.
+pub const HEADER_MASTER_SEED: u8 = 4;
+/// The outer header field containing the IV of the [`OuterCipher`](OuterCipher).
+pub const HEADER_ENCRYPTION_IV: u8 = 7;
+/// The outer header field containing the parameters of the [`Kdf`](Kdf).
+pub const HEADER_KDF_PARAMETERS: u8 = 11;
+
+/// The inner header field containing the ID of the [`InnerStream`](InnerStream).
+pub const INNER_HEADER_STREAM_ID: u8 = 1;
+/// The inner header field containing the key of the [`InnerStream`](InnerStream).
+pub const INNER_HEADER_STREAM_KEY: u8 = 2;
+
+/// The ID of the `ChaCha20` [`InnerStream`](InnerStream).
+pub const INNER_STREAM_CHACHA20: u32 = 3;
+
+/// The seconds between the first of January of the year 1 and the unix epoch.
+/// The times of the XML document are stored relative to the year 1.
+pub const SECONDS_UNTIL_UNIX_EPOCH: i64 = 62_135_596_800;
+
+/// The UUID of the AES-256 [`OuterCipher`](OuterCipher).
+const CIPHER_AES256: [u8; 16] = [
+    0x31, 0xc1, 0xf2, 0xe6, 0xbf, 0x71, 0x43, 0x50, 0xbe, 0x58, 0x05, 0x21, 0x6a, 0xfc, 0x5a, 0xff,
+];
+/// The UUID of the `ChaCha20` [`OuterCipher`](OuterCipher).
+const CIPHER_CHACHA20: [u8; 16] = [
+    0xd6, 0x03, 0x8a, 0x2b, 0x8b, 0x6f, 0x4c, 0xb5, 0xa5, 0x24, 0x33, 0x9a, 0x31, 0xdb, 0xb5, 0x9a,
+];
+
+/// The UUID of the AES-KDF used by KDBX 3.1.
+const KDF_AES_KDBX3: [u8; 16] = [
+    0xc9, 0xd9, 0xf3, 0x9a, 0x62, 0x8a, 0x44, 0x60, 0xbf, 0x74, 0x0d, 0x08, 0xc1, 0x8a, 0x4f, 0xea,
+];
+/// The UUID of the AES-KDF used by KDBX 4.
+const KDF_AES_KDBX4: [u8; 16] = [
+    0x7c, 0x02, 0xbb, 0x82, 0x79, 0xa7, 0x4a, 0xc0, 0x92, 0x7d, 0x11, 0x4a, 0x00, 0x64, 0x82, 0x38,
+];
+/// The UUID of the Argon2d KDF.
+const KDF_ARGON2D: [u8; 16] = [
+    0xef, 0x63, 0x6d, 0xdf, 0x8c, 0x29, 0x44, 0x4b, 0x91, 0xf7, 0xa9, 0xa4, 0x03, 0xe3, 0x0a, 0x0c,
+];
+/// The UUID of the Argon2id KDF.
+const KDF_ARGON2ID: [u8; 16] = [
+    0x9e, 0x29, 0x8b, 0x19, 0x56, 0xdb, 0x47, 0x73, 0xb2, 0x3d, 0xfc, 0x3e, 0xc6, 0xf0, 0xa1, 0xe6,
+];
+
+/// The length of the keys derived by a [`Kdf`](Kdf).
+const KEY_LENGTH: usize = 32;
+
+/// The cipher used to encrypt the payload of a `KeePass` database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OuterCipher {
+    /// AES-256 in CBC mode.
+    Aes256,
+    /// The `ChaCha20` stream cipher.
+    ChaCha20,
+}
+
+impl OuterCipher {
+    /// Identify the [`OuterCipher`](OuterCipher) by its UUID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the cipher is not supported.
+    pub fn from_uuid(uuid: &[u8]) -> Result<Self, PWDuckCoreError> {
+        if uuid == CIPHER_AES256 {
+            Ok(Self::Aes256)
+        } else if uuid == CIPHER_CHACHA20 {
+            Ok(Self::ChaCha20)
+        } else {
+            Err(PWDuckCoreError::Error(
+                "The KeePass database is encrypted with an unsupported cipher".into(),
+            ))
+        }
+    }
+
+    /// The UUID identifying the [`OuterCipher`](OuterCipher).
+    pub const fn uuid(self) -> [u8; 16] {
+        match self {
+            Self::Aes256 => CIPHER_AES256,
+            Self::ChaCha20 => CIPHER_CHACHA20,
+        }
+    }
+
+    /// Encrypt the payload of a `KeePass` database.
+    ///
+    /// It expects:
+    ///  - The payload to encrypt
+    ///  - The key for the encryption
+    ///  - The IV stored in the header
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the IV has the wrong size or the payload can't be encrypted.
+    pub fn encrypt(self, data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, PWDuckCoreError> {
+        match self {
+            Self::Aes256 if iv.len() == AES_IV_LENGTH => aes_cbc_encrypt(data, key, iv),
+            Self::ChaCha20 if iv.len() == CHACHA20_NONCE_LENGTH => chacha20_encrypt(data, key, iv),
+            _ => Err(PWDuckCoreError::Error(
+                "The KeePass database contains an invalid IV".into(),
+            )),
+        }
+    }
+
+    /// Decrypt the payload of a `KeePass` database.
+    ///
+    /// It expects:
+    ///  - The encrypted payload
+    ///  - The key for the decryption
+    ///  - The IV stored in the header
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the IV has the wrong size or the payload can't be decrypted.
+    pub fn decrypt(
+        self,
+        data: &[u8],
+        key: &[u8],
+        iv: &[u8],
+    ) -> Result<SecVec<u8>, PWDuckCoreError> {
+        match self {
+            Self::Aes256 if iv.len() == AES_IV_LENGTH => aes_cbc_decrypt(data, key, iv),
+            Self::ChaCha20 if iv.len() == CHACHA20_NONCE_LENGTH => chacha20_decrypt(data, key, iv),
+            _ => Err(PWDuckCoreError::Error(
+                "The KeePass database contains an invalid IV".into(),
+            )),
+        }
+    }
+}
+
+/// The key derivation function used to transform the composite key of a `KeePass` database.
+#[derive(Clone, Debug)]
+pub enum Kdf {
+    /// AES-KDF, which encrypts the key repeatedly.
+    Aes {
+        /// The seed used as the key of the encryption.
+        seed: Vec<u8>,
+        /// The number of encryption rounds.
+        rounds: u64,
+    },
+    /// Argon2d or Argon2id.
+    Argon2 {
+        /// The variant of Argon2.
+        algorithm: argon2::Algorithm,
+        /// The version of Argon2.
+        version: argon2::Version,
+        /// The salt.
+        salt: Vec<u8>,
+        /// The memory usage in KiB.
+        memory: u32,
+        /// The number of iterations.
+        iterations: u32,
+        /// The degree of parallelism.
+        parallelism: u32,
+    },
+}
+
+impl Kdf {
+    /// Create the [`Kdf`](Kdf) from the parameters stored in the header.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the KDF is not supported or its parameters are invalid.
+    pub fn from_parameters(parameters: &VariantDictionary) -> Result<Self, PWDuckCoreError> {
+        let uuid = parameters.get_bytes("$UUID")?;
+        if uuid == KDF_AES_KDBX3 || uuid == KDF_AES_KDBX4 {
+            return Ok(Self::Aes {
+                seed: parameters.get_bytes("S")?.to_vec(),
+                rounds: parameters.get_u64("R")?,
+            });
+        }
+
+        let algorithm = if uuid == KDF_ARGON2D {
+            argon2::Algorithm::Argon2d
+        } else if uuid == KDF_ARGON2ID {
+            argon2::Algorithm::Argon2id
+        } else {
+            return Err(PWDuckCoreError::Error(
+                "The KeePass database uses an unsupported key derivation function".into(),
+            ));
+        };
+        let version = match parameters.get_u32("V")? {
+            0x10 => argon2::Version::V0x10,
+            0x13 => argon2::Version::V0x13,
+            _ => return Err(invalid_kdf_parameters()),
+        };
+
+        Ok(Self::Argon2 {
+            algorithm,
+            version,
+            salt: parameters.get_bytes("S")?.to_vec(),
+            memory: u32::try_from(parameters.get_u64("M")? / 1024)
+                .map_err(|_err| invalid_kdf_parameters())?,
+            iterations: u32::try_from(parameters.get_u64("I")?)
+                .map_err(|_err| invalid_kdf_parameters())?,
+            parallelism: parameters.get_u32("P")?,
+        })
+    }
+
+    /// Create a new Argon2id [`Kdf`](Kdf) with the default parameters and a random salt.
+    pub fn new_argon2id() -> Self {
+        let params = argon2::Params::default();
+        let mut salt = vec![0_u8; KEY_LENGTH];
+        fill_random_bytes(&mut salt);
+        Self::Argon2 {
+            algorithm: argon2::Algorithm::Argon2id,
+            version: argon2::Version::V0x13,
+            salt,
+            memory: params.m_cost(),
+            iterations: params.t_cost(),
+            parallelism: params.p_cost(),
+        }
+    }
+
+    /// Store the [`Kdf`](Kdf) as the parameters of the header.
+    pub fn to_parameters(&self) -> VariantDictionary {
+        let mut parameters = VariantDictionary::default();
+        match self {
+            Self::Aes { seed, rounds } => {
+                parameters.insert("$UUID", Variant::Bytes(KDF_AES_KDBX4.to_vec()));
+                parameters.insert("S", Variant::Bytes(seed.clone()));
+                parameters.insert("R", Variant::UInt64(*rounds));
+            }
+            Self::Argon2 {
+                algorithm,
+                version,
+                salt,
+                memory,
+                iterations,
+                parallelism,
+            } => {
+                let uuid = match algorithm {
+                    argon2::Algorithm::Argon2d => KDF_ARGON2D,
+                    _ => KDF_ARGON2ID,
+                };
+                let version = match version {
+                    argon2::Version::V0x10 => 0x10,
+                    argon2::Version::V0x13 => 0x13,
+                };
+                parameters.insert("$UUID", Variant::Bytes(uuid.to_vec()));
+                parameters.insert("V", Variant::UInt32(version));
+                parameters.insert("S", Variant::Bytes(salt.clone()));
+                parameters.insert("M", Variant::UInt64(u64::from(*memory) * 1024));
+                parameters.insert("I", Variant::UInt64(u64::from(*iterations)));
+                parameters.insert("P", Variant::UInt32(*parallelism));
+            }
+        }
+        parameters
+    }
+
+    /// Transform the composite key into the key used to derive the encryption and HMAC keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the parameters of the [`Kdf`](Kdf) are invalid.
+    pub fn transform_key(&self, composite_key: &[u8]) -> Result<SecVec<u8>, PWDuckCoreError> {
+        match self {
+            Self::Aes { seed, rounds } => {
+                if seed.len() != KEY_LENGTH || composite_key.len() != KEY_LENGTH {
+                    return Err(invalid_kdf_parameters());
+                }
+                let cipher = Aes256::new(GenericArray::from_slice(seed));
+                let mut key: SecVec<u8> = composite_key.to_vec().into();
+                for block in key.chunks_exact_mut(16) {
+                    let block = GenericArray::from_mut_slice(block);
+                    for _ in 0..*rounds {
+                        cipher.encrypt_block(block);
+                    }
+                }
+                Ok(Sha256::digest(&key).to_vec().into())
+            }
+            Self::Argon2 {
+                algorithm,
+                version,
+                salt,
+                memory,
+                iterations,
+                parallelism,
+            } => {
+                let params =
+                    argon2::Params::new(*memory, *iterations, *parallelism, Some(KEY_LENGTH))?;
+                let hasher = Argon2::new(*algorithm, *version, params);
+                let mut key: SecVec<u8> = vec![0_u8; KEY_LENGTH].into();
+                hasher.hash_password_into(composite_key, salt, &mut key)?;
+                Ok(key)
+            }
+        }
+    }
+}
+
+/// The error of invalid parameters of a [`Kdf`](Kdf).
+fn invalid_kdf_parameters() -> PWDuckCoreError {
+    PWDuckCoreError::Error("The KeePass database contains invalid KDF parameters".into())
+}
+
+/// The composite key of a `KeePass` database protected by a password only.
+pub fn composite_key(password: &str) -> SecVec<u8> {
+    Sha256::digest(&Sha256::digest(password.as_bytes()))
+        .to_vec()
+        .into()
+}
+
+/// The keys to decrypt and authenticate a `KeePass` database.
+pub struct Keys {
+    /// The key of the [`OuterCipher`](OuterCipher).
+    encryption: SecVec<u8>,
+    /// The base key to derive the keys of the HMACs.
+    hmac: SecVec<u8>,
+}
+
+impl Keys {
+    /// Derive the [`Keys`](Keys) from the master seed and the transformed key.
+    pub fn derive(master_seed: &[u8], transformed_key: &[u8]) -> Self {
+        let encryption = Sha256::new()
+            .chain(master_seed)
+            .chain(transformed_key)
+            .finalize()
+            .to_vec()
+            .into();
+        let hmac = Sha512::new()
+            .chain(master_seed)
+            .chain(transformed_key)
+            .chain([1_u8])
+            .finalize()
+            .to_vec()
+            .into();
+        Self { encryption, hmac }
+    }
+
+    /// The key of the [`OuterCipher`](OuterCipher).
+    pub fn encryption(&self) -> &[u8] {
+        &self.encryption
+    }
+
+    /// The key of the HMAC of the block with the given index.
+    /// The header uses the index `u64::MAX`.
+    pub fn hmac(&self, index: u64) -> SecVec<u8> {
+        Sha512::new()
+            .chain(index.to_le_bytes())
+            .chain(&*self.hmac)
+            .finalize()
+            .to_vec()
+            .into()
+    }
+}
+
+/// The stream cipher protecting the values inside of the XML payload.
+pub struct InnerStream {
+    /// The `ChaCha20` stream cipher.
+    cipher: ChaCha20,
+}
+
+impl InnerStream {
+    /// Create the [`InnerStream`](InnerStream) from the ID and the key stored in the inner header.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the inner stream is not supported.
+    pub fn new(id: u32, key: &[u8]) -> Result<Self, PWDuckCoreError> {
+        if id != INNER_STREAM_CHACHA20 {
+            return Err(PWDuckCoreError::Error(
+                "The KeePass database uses an unsupported inner stream cipher".into(),
+            ));
+        }
+        let hash = Sha512::digest(key);
+        Ok(Self {
+            cipher: ChaCha20::new(
+                Key::from_slice(&hash[..32]),
+                Nonce::from_slice(&hash[32..32 + CHACHA20_NONCE_LENGTH]),
+            ),
+        })
+    }
+
+    /// Create a new `ChaCha20` [`InnerStream`](InnerStream) with a random key.
+    /// The key must be stored in the inner header.
+    pub fn generate() -> (Self, SecVec<u8>) {
+        let mut key: SecVec<u8> = vec![0_u8; 64].into();
+        fill_random_bytes(&mut key);
+        let hash = Sha512::digest(&key);
+        let stream = Self {
+            cipher: ChaCha20::new(
+                Key::from_slice(&hash[..32]),
+                Nonce::from_slice(&hash[32..32 + CHACHA20_NONCE_LENGTH]),
+            ),
+        };
+        (stream, key)
+    }
+
+    /// Apply the next part of the key stream to the data.
+    /// The values must be processed in the order of their appearance in the XML payload.
+    pub fn apply(&mut self, data: &mut [u8]) {
+        self.cipher.apply_keystream(data);
+    }
+}
+
+impl std::fmt::Debug for InnerStream {
+    #[cfg_attr(coverage, no_coverage)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InnerStream").finish_non_exhaustive()
+    }
+}
+
+/// A value of a [`VariantDictionary`](VariantDictionary).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Variant {
+    /// An unsigned 32 bit integer.
+    UInt32(u32),
+    /// An unsigned 64 bit integer.
+    UInt64(u64),
+    /// A boolean.
+    Bool(bool),
+    /// A signed 32 bit integer.
+    Int32(i32),
+    /// A signed 64 bit integer.
+    Int64(i64),
+    /// An UTF-8 encoded string.
+    String(String),
+    /// An array of bytes.
+    Bytes(Vec<u8>),
+}
+
+impl Variant {
+    /// The type code identifying the kind of the [`Variant`](Variant).
+    const fn kind(&self) -> u8 {
+        match self {
+            Self::UInt32(_) => 0x04,
+            Self::UInt64(_) => 0x05,
+            Self::Bool(_) => 0x08,
+            Self::Int32(_) => 0x0C,
+            Self::Int64(_) => 0x0D,
+            Self::String(_) => 0x18,
+            Self::Bytes(_) => 0x42,
+        }
+    }
+
+    /// The little endian encoded value of the [`Variant`](Variant).
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::UInt32(value) => value.to_le_bytes().to_vec(),
+            Self::UInt64(value) => value.to_le_bytes().to_vec(),
+            Self::Bool(value) => vec![u8::from(*value)],
+            Self::Int32(value) => value.to_le_bytes().to_vec(),
+            Self::Int64(value) => value.to_le_bytes().to_vec(),
+            Self::String(value) => value.as_bytes().to_vec(),
+            Self::Bytes(value) => value.clone(),
+        }
+    }
+}
+
+/// A typed key-value dictionary used to store the parameters of the [`Kdf`](Kdf).
+#[derive(Clone, Debug, Default)]
+pub struct VariantDictionary {
+    /// The values identified by their keys.
+    values: HashMap<String, Variant>,
+}
+
+impl VariantDictionary {
+    /// The supported major version of the [`VariantDictionary`](VariantDictionary).
+    const VERSION: u16 = 0x0100;
+
+    /// Parse the [`VariantDictionary`](VariantDictionary).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the data is no valid [`VariantDictionary`](VariantDictionary).
+    pub fn parse(data: &[u8]) -> Result<Self, PWDuckCoreError> {
+        let mut reader = ByteReader::new(data);
+        if reader.read_u16()? & 0xFF00 != Self::VERSION & 0xFF00 {
+            return Err(invalid_kdf_parameters());
+        }
+
+        let mut values = HashMap::new();
+        loop {
+            let kind = reader.read_u8()?;
+            if kind == 0 {
+                break;
+            }
+            let key_length = reader.read_length()?;
+            let key = String::from_utf8(reader.read_bytes(key_length)?.to_vec())?;
+            let value_length = reader.read_length()?;
+            let mut value = ByteReader::new(reader.read_bytes(value_length)?);
+            let value = match kind {
+                0x04 => Variant::UInt32(value.read_u32()?),
+                0x05 => Variant::UInt64(value.read_u64()?),
+                0x08 => Variant::Bool(value.read_u8()? != 0),
+                0x0C => Variant::Int32(value.read_u32()? as i32),
+                0x0D => Variant::Int64(value.read_u64()? as i64),
+                0x18 => Variant::String(String::from_utf8(value.read_to_end().to_vec())?),
+                0x42 => Variant::Bytes(value.read_to_end().to_vec()),
+                _ => return Err(invalid_kdf_parameters()),
+            };
+            drop(values.insert(key, value));
+        }
+
+        Ok(Self { values })
+    }
+
+    /// Insert the value identified by the key.
+    pub fn insert(&mut self, key: &str, value: Variant) {
+        drop(self.values.insert(key.to_owned(), value));
+    }
+
+    /// Serialize the [`VariantDictionary`](VariantDictionary).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if a key or value is too long.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PWDuckCoreError> {
+        let mut writer = ByteWriter::default();
+        writer.write_u16(Self::VERSION);
+        for (key, value) in &self.values {
+            writer.write_u8(value.kind());
+            writer.write_length(key.len())?;
+            writer.write_bytes(key.as_bytes());
+            let value = value.to_bytes();
+            writer.write_length(value.len())?;
+            writer.write_bytes(&value);
+        }
+        writer.write_u8(0);
+        Ok(writer.into_inner())
+    }
+
+    /// Get the bytes identified by the key.
+    fn get_bytes(&self, key: &str) -> Result<&[u8], PWDuckCoreError> {
+        match self.values.get(key) {
+            Some(Variant::Bytes(bytes)) => Ok(bytes),
+            _ => Err(invalid_kdf_parameters()),
+        }
+    }
+
+    /// Get the unsigned 32 bit integer identified by the key.
+    fn get_u32(&self, key: &str) -> Result<u32, PWDuckCoreError> {
+        match self.values.get(key) {
+            Some(Variant::UInt32(value)) => Ok(*value),
+            _ => Err(invalid_kdf_parameters()),
+        }
+    }
+
+    /// Get the unsigned 64 bit integer identified by the key.
+    fn get_u64(&self, key: &str) -> Result<u64, PWDuckCoreError> {
+        match self.values.get(key) {
+            Some(Variant::UInt64(value)) => Ok(*value),
+            _ => Err(invalid_kdf_parameters()),
+        }
+    }
+}
+
+/// A cursor to read the little endian encoded binary data of a `KeePass` database.
+#[derive(Debug)]
+pub struct ByteReader<'a> {
+    /// The data to read.
+    data: &'a [u8],
+    /// The position of the next byte to read.
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    /// Create a new [`ByteReader`](ByteReader) reading the data from the start.
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// The position of the next byte to read.
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Read the next bytes with the given length.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the data ends too early.
+    pub fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], PWDuckCoreError> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| {
+                PWDuckCoreError::Error("The KeePass database ends unexpectedly".into())
+            })?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Read all remaining bytes.
+    pub fn read_to_end(&mut self) -> &'a [u8] {
+        let bytes = &self.data[self.position..];
+        self.position = self.data.len();
+        bytes
+    }
+
+    /// Read the next byte.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the data ends too early.
+    pub fn read_u8(&mut self) -> Result<u8, PWDuckCoreError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    /// Read the next unsigned 16 bit integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the data ends too early.
+    pub fn read_u16(&mut self) -> Result<u16, PWDuckCoreError> {
+        let mut bytes = [0_u8; 2];
+        bytes.copy_from_slice(self.read_bytes(2)?);
+        Ok(u16::from_le_bytes(bytes))
+    }
+
+    /// Read the next unsigned 32 bit integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the data ends too early.
+    pub fn read_u32(&mut self) -> Result<u32, PWDuckCoreError> {
+        let mut bytes = [0_u8; 4];
+        bytes.copy_from_slice(self.read_bytes(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    /// Read the next unsigned 64 bit integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the data ends too early.
+    pub fn read_u64(&mut self) -> Result<u64, PWDuckCoreError> {
+        let mut bytes = [0_u8; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Read the next length stored as an unsigned 32 bit integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the data ends too early.
+    pub fn read_length(&mut self) -> Result<usize, PWDuckCoreError> {
+        Ok(self.read_u32()? as usize)
+    }
+}
+
+/// A buffer to write little endian encoded binary data of a `KeePass` database.
+#[derive(Debug, Default)]
+pub struct ByteWriter {
+    /// The written data.
+    data: Vec<u8>,
+}
+
+impl ByteWriter {
+    /// Write the bytes.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    /// Write a byte.
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    /// Write an unsigned 16 bit integer.
+    pub fn write_u16(&mut self, value: u16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Write an unsigned 32 bit integer.
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Write a length as an unsigned 32 bit integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the length does not fit into an unsigned 32 bit integer.
+    pub fn write_length(&mut self, length: usize) -> Result<(), PWDuckCoreError> {
+        let length = u32::try_from(length).map_err(|_err| {
+            PWDuckCoreError::Error("The data is too large for a KeePass database".into())
+        })?;
+        self.write_u32(length);
+        Ok(())
+    }
+
+    /// Write a header field with the given ID and value.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the value is too long.
+    pub fn write_field(&mut self, id: u8, value: &[u8]) -> Result<(), PWDuckCoreError> {
+        self.write_u8(id);
+        self.write_length(value.len())?;
+        self.write_bytes(value);
+        Ok(())
+    }
+
+    /// The written data.
+    pub const fn data(&self) -> &Vec<u8> {
+        &self.data
+    }
+
+    /// Consume the [`ByteWriter`](ByteWriter) and return the written data.
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ByteReader, ByteWriter, Kdf, Variant, VariantDictionary};
+
+    #[test]
+    fn byte_reader() {
+        let data = [1_u8, 2, 0, 3, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 5];
+        let mut reader = ByteReader::new(&data);
+
+        assert_eq!(reader.read_u8().unwrap(), 1);
+        assert_eq!(reader.read_u16().unwrap(), 2);
+        assert_eq!(reader.read_u32().unwrap(), 3);
+        assert_eq!(reader.read_u64().unwrap(), 4);
+        assert_eq!(reader.position(), 15);
+        assert!(reader.read_u16().is_err());
+        assert_eq!(reader.read_to_end(), &[5]);
+        assert!(reader.read_u8().is_err());
+    }
+
+    #[test]
+    fn parse_variant_dictionary() {
+        let mut data = vec![0x00, 0x01];
+        data.extend_from_slice(&[0x05, 1, 0, 0, 0, b'R', 8, 0, 0, 0]);
+        data.extend_from_slice(&42_u64.to_le_bytes());
+        data.extend_from_slice(&[0x42, 1, 0, 0, 0, b'S', 2, 0, 0, 0, 7, 8]);
+        data.push(0x00);
+
+        let dictionary = VariantDictionary::parse(&data).unwrap();
+        assert_eq!(dictionary.values["R"], Variant::UInt64(42));
+        assert_eq!(dictionary.get_u64("R").unwrap(), 42);
+        assert_eq!(dictionary.get_bytes("S").unwrap(), &[7, 8]);
+        assert!(dictionary.get_u32("R").is_err());
+        assert!(dictionary.get_bytes("missing").is_err());
+
+        // Unsupported versions are rejected.
+        data[1] = 0x02;
+        assert!(VariantDictionary::parse(&data).is_err());
+    }
+
+    #[test]
+    fn byte_writer() {
+        let mut writer = ByteWriter::default();
+        writer.write_u8(1);
+        writer.write_u16(2);
+        writer.write_u32(3);
+        writer.write_field(4, &[5, 6]).unwrap();
+        assert_eq!(writer.data().len(), 14);
+
+        let data = writer.into_inner();
+        let mut reader = ByteReader::new(&data);
+        assert_eq!(reader.read_u8().unwrap(), 1);
+        assert_eq!(reader.read_u16().unwrap(), 2);
+        assert_eq!(reader.read_u32().unwrap(), 3);
+        assert_eq!(reader.read_u8().unwrap(), 4);
+        assert_eq!(reader.read_length().unwrap(), 2);
+        assert_eq!(reader.read_to_end(), &[5, 6]);
+    }
+
+    #[test]
+    fn kdf_parameters_round_trip() {
+        let kdf = Kdf::new_argon2id();
+        let data = kdf.to_parameters().to_bytes().unwrap();
+        let parsed = Kdf::from_parameters(&VariantDictionary::parse(&data).unwrap()).unwrap();
+
+        assert_eq!(
+            kdf.transform_key(&[0_u8; 32]).unwrap().as_slice(),
+            parsed.transform_key(&[0_u8; 32]).unwrap().as_slice()
+        );
+
+        let kdf = Kdf::Aes {
+            seed: vec![1_u8; 32],
+            rounds: 10,
+        };
+        let data = kdf.to_parameters().to_bytes().unwrap();
+        let parsed = Kdf::from_parameters(&VariantDictionary::parse(&data).unwrap()).unwrap();
+        assert!(matches!(parsed, Kdf::Aes { rounds: 10, .. }));
+    }
+}
@@ -0,0 +1,214 @@
+//! Read and decrypt a `KeePass` database.
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::{Digest, Sha256};
+
+use crate::{error::PWDuckCoreError, mem_protection::SecVec};
+
+use super::{
+    composite_key, ByteReader, InnerStream, Kdf, Keys, OuterCipher, VariantDictionary,
+    HEADER_CIPHER_ID, HEADER_COMPRESSION_FLAGS, HEADER_ENCRYPTION_IV, HEADER_END,
+    HEADER_KDF_PARAMETERS, HEADER_MASTER_SEED, INNER_HEADER_STREAM_ID, INNER_HEADER_STREAM_KEY,
+    MAJOR_VERSION, SIGNATURE_1, SIGNATURE_2,
+};
+
+/// The decrypted content of a `KeePass` database.
+#[derive(Debug)]
+pub struct Database {
+    /// The XML document containing the groups and entries.
+    pub xml: String,
+    /// The stream cipher to decrypt the protected values of the XML document.
+    pub inner_stream: InnerStream,
+}
+
+/// The outer header of a `KeePass` database.
+struct OuterHeader {
+    /// The cipher of the payload.
+    cipher: OuterCipher,
+    /// True, if the payload is compressed with gzip.
+    compressed: bool,
+    /// The master seed.
+    master_seed: Vec<u8>,
+    /// The IV of the cipher.
+    iv: Vec<u8>,
+    /// The key derivation function.
+    kdf: Kdf,
+}
+
+/// Read and decrypt the `KeePass` database protected by the given password.
+///
+/// # Errors
+///
+/// Returns `Err` if the database is not a KDBX 4 database, the password is wrong
+/// or the database is corrupted.
+pub fn read_database(data: &[u8], password: &str) -> Result<Database, PWDuckCoreError> {
+    let mut reader = ByteReader::new(data);
+    let header = read_outer_header(&mut reader)?;
+    let header_data = &data[..reader.position()];
+
+    let header_hash = reader.read_bytes(32)?;
+    if Sha256::digest(header_data).as_slice() != header_hash {
+        return Err(PWDuckCoreError::Error(
+            "The header of the KeePass database is corrupted".into(),
+        ));
+    }
+
+    let transformed_key = header.kdf.transform_key(&composite_key(password))?;
+    let keys = Keys::derive(&header.master_seed, &transformed_key);
+
+    let header_hmac = reader.read_bytes(32)?;
+    verify_hmac(&keys.hmac(u64::MAX), &[header_data], header_hmac)?;
+
+    let encrypted = read_blocks(&mut reader, &keys)?;
+    let decrypted = header
+        .cipher
+        .decrypt(&encrypted, keys.encryption(), &header.iv)?;
+
+    let payload: SecVec<u8> = if header.compressed {
+        let mut decompressed = Vec::new();
+        let _ = GzDecoder::new(decrypted.as_slice()).read_to_end(&mut decompressed)?;
+        decompressed.into()
+    } else {
+        decrypted
+    };
+
+    let mut reader = ByteReader::new(&payload);
+    let inner_stream = read_inner_header(&mut reader)?;
+    let xml = String::from_utf8(reader.read_to_end().to_vec())?;
+
+    Ok(Database { xml, inner_stream })
+}
+
+/// Read the outer header of a `KeePass` database.
+fn read_outer_header(reader: &mut ByteReader) -> Result<OuterHeader, PWDuckCoreError> {
+    if reader.read_u32()? != SIGNATURE_1 || reader.read_u32()? != SIGNATURE_2 {
+        return Err(PWDuckCoreError::Error(
+            "The file is not a KeePass database".into(),
+        ));
+    }
+    let _minor_version = reader.read_u16()?;
+    if reader.read_u16()? != MAJOR_VERSION {
+        return Err(PWDuckCoreError::Error(
+            "Only KeePass databases in the format KDBX 4 are supported".into(),
+        ));
+    }
+
+    let mut cipher = None;
+    let mut compressed = false;
+    let mut master_seed = None;
+    let mut iv = None;
+    let mut kdf = None;
+    loop {
+        let id = reader.read_u8()?;
+        let length = reader.read_length()?;
+        let value = reader.read_bytes(length)?;
+        match id {
+            HEADER_END => break,
+            HEADER_CIPHER_ID => cipher = Some(OuterCipher::from_uuid(value)?),
+            HEADER_COMPRESSION_FLAGS => compressed = ByteReader::new(value).read_u32()? == 1,
+            HEADER_MASTER_SEED => master_seed = Some(value.to_vec()),
+            HEADER_ENCRYPTION_IV => iv = Some(value.to_vec()),
+            HEADER_KDF_PARAMETERS => {
+                kdf = Some(Kdf::from_parameters(&VariantDictionary::parse(value)?)?);
+            }
+            _ => {}
+        }
+    }
+
+    match (cipher, master_seed, iv, kdf) {
+        (Some(cipher), Some(master_seed), Some(iv), Some(kdf)) => Ok(OuterHeader {
+            cipher,
+            compressed,
+            master_seed,
+            iv,
+            kdf,
+        }),
+        _ => Err(PWDuckCoreError::Error(
+            "The header of the KeePass database is incomplete".into(),
+        )),
+    }
+}
+
+/// Read the HMAC protected blocks of the encrypted payload.
+fn read_blocks(reader: &mut ByteReader, keys: &Keys) -> Result<Vec<u8>, PWDuckCoreError> {
+    let mut encrypted = Vec::new();
+    for index in 0_u64.. {
+        let hmac = reader.read_bytes(32)?;
+        let length = reader.read_u32()?;
+        let block = reader.read_bytes(length as usize)?;
+        verify_hmac(
+            &keys.hmac(index),
+            &[&index.to_le_bytes(), &length.to_le_bytes(), block],
+            hmac,
+        )?;
+        if length == 0 {
+            break;
+        }
+        encrypted.extend_from_slice(block);
+    }
+    Ok(encrypted)
+}
+
+/// Verify the HMAC-SHA-256 of the data.
+fn verify_hmac(key: &[u8], data: &[&[u8]], hmac: &[u8]) -> Result<(), PWDuckCoreError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)
+        .map_err(|error| PWDuckCoreError::Error(error.to_string()))?;
+    for part in data {
+        mac.update(part);
+    }
+    mac.verify(hmac).map_err(|_error| {
+        PWDuckCoreError::Error("Wrong password or the KeePass database is corrupted".into())
+    })
+}
+
+/// Read the inner header of the decrypted payload.
+fn read_inner_header(reader: &mut ByteReader) -> Result<InnerStream, PWDuckCoreError> {
+    let mut stream_id = None;
+    let mut stream_key = None;
+    loop {
+        let id = reader.read_u8()?;
+        let length = reader.read_length()?;
+        let value = reader.read_bytes(length)?;
+        match id {
+            HEADER_END => break,
+            INNER_HEADER_STREAM_ID => stream_id = Some(ByteReader::new(value).read_u32()?),
+            INNER_HEADER_STREAM_KEY => stream_key = Some(value),
+            // Attachments are not supported.
+            _ => {}
+        }
+    }
+
+    match (stream_id, stream_key) {
+        (Some(id), Some(key)) => InnerStream::new(id, key),
+        _ => Err(PWDuckCoreError::Error(
+            "The inner header of the KeePass database is incomplete".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_database;
+
+    #[test]
+    fn read_argon2id_chacha20() {
+        let data = include_bytes!("../../tests/resources/argon2id_chacha20.kdbx");
+        let database = read_database(data, "password").unwrap();
+        assert!(database.xml.contains("<Value>Mail &amp; Calendar</Value>"));
+
+        assert!(read_database(data, "wrong password").is_err());
+    }
+
+    #[test]
+    fn read_aes_kdf_aes256() {
+        let data = include_bytes!("../../tests/resources/aes_kdf_aes256.kdbx");
+        let database = read_database(data, "password").unwrap();
+        assert!(database.xml.contains("<Value>Database</Value>"));
+
+        assert!(read_database(data, "wrong password").is_err());
+        assert!(read_database(&data[..100], "password").is_err());
+        assert!(read_database(b"no database", "password").is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! Encrypt and write a `KeePass` database.
+use std::{convert::TryFrom, io::Write};
+
+use flate2::{write::GzEncoder, Compression};
+use hmac::{Hmac, Mac, NewMac};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    cryptography::{fill_random_bytes, CHACHA20_NONCE_LENGTH},
+    error::PWDuckCoreError,
+    mem_protection::SecVec,
+};
+
+use super::{
+    composite_key, ByteWriter, Kdf, Keys, OuterCipher, HEADER_CIPHER_ID, HEADER_COMPRESSION_FLAGS,
+    HEADER_ENCRYPTION_IV, HEADER_END, HEADER_KDF_PARAMETERS, HEADER_MASTER_SEED,
+    INNER_HEADER_STREAM_ID, INNER_HEADER_STREAM_KEY, INNER_STREAM_CHACHA20, KEY_LENGTH,
+    MAJOR_VERSION, SIGNATURE_1, SIGNATURE_2,
+};
+
+/// The minor version of the written KDBX format.
+const MINOR_VERSION: u16 = 0;
+
+/// The maximum size of an HMAC protected block of the encrypted payload.
+const BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Encrypt the XML document as a `KeePass` database protected by the given password.
+///
+/// It expects:
+///  - The XML document containing the groups and entries
+///  - The key of the [`InnerStream`](super::InnerStream) used to protect the values of the XML document
+///  - The password of the new `KeePass` database
+///
+/// The database is encrypted with `ChaCha20`, the key is derived with Argon2id.
+///
+/// # Errors
+///
+/// Returns `Err` if the database can't be encrypted.
+pub fn write_database(
+    xml: &str,
+    inner_stream_key: &[u8],
+    password: &str,
+) -> Result<Vec<u8>, PWDuckCoreError> {
+    let cipher = OuterCipher::ChaCha20;
+    let kdf = Kdf::new_argon2id();
+    let mut master_seed = vec![0_u8; KEY_LENGTH];
+    fill_random_bytes(&mut master_seed);
+    let mut iv = vec![0_u8; CHACHA20_NONCE_LENGTH];
+    fill_random_bytes(&mut iv);
+
+    let mut writer = ByteWriter::default();
+    writer.write_u32(SIGNATURE_1);
+    writer.write_u32(SIGNATURE_2);
+    writer.write_u16(MINOR_VERSION);
+    writer.write_u16(MAJOR_VERSION);
+    writer.write_field(HEADER_CIPHER_ID, &cipher.uuid())?;
+    writer.write_field(HEADER_COMPRESSION_FLAGS, &1_u32.to_le_bytes())?;
+    writer.write_field(HEADER_MASTER_SEED, &master_seed)?;
+    writer.write_field(HEADER_ENCRYPTION_IV, &iv)?;
+    writer.write_field(HEADER_KDF_PARAMETERS, &kdf.to_parameters().to_bytes()?)?;
+    writer.write_field(HEADER_END, b"\r\n\r\n")?;
+    let header_data = writer.data().clone();
+
+    let transformed_key = kdf.transform_key(&composite_key(password))?;
+    let keys = Keys::derive(&master_seed, &transformed_key);
+
+    writer.write_bytes(&Sha256::digest(&header_data));
+    writer.write_bytes(&hmac(&keys.hmac(u64::MAX), &[&header_data])?);
+
+    let payload = write_payload(xml, inner_stream_key)?;
+    let encrypted = cipher.encrypt(&payload, keys.encryption(), &iv)?;
+    write_blocks(&mut writer, &encrypted, &keys)?;
+
+    Ok(writer.into_inner())
+}
+
+/// Write the inner header and the XML document as the compressed payload.
+fn write_payload(xml: &str, inner_stream_key: &[u8]) -> Result<SecVec<u8>, PWDuckCoreError> {
+    let mut inner = ByteWriter::default();
+    inner.write_field(INNER_HEADER_STREAM_ID, &INNER_STREAM_CHACHA20.to_le_bytes())?;
+    inner.write_field(INNER_HEADER_STREAM_KEY, inner_stream_key)?;
+    inner.write_field(HEADER_END, &[])?;
+    let inner: SecVec<u8> = inner.into_inner().into();
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&inner)?;
+    encoder.write_all(xml.as_bytes())?;
+    Ok(encoder.finish()?.into())
+}
+
+/// Write the encrypted payload as HMAC protected blocks.
+fn write_blocks(
+    writer: &mut ByteWriter,
+    encrypted: &[u8],
+    keys: &Keys,
+) -> Result<(), PWDuckCoreError> {
+    // The last block is always empty.
+    for (index, block) in encrypted
+        .chunks(BLOCK_SIZE)
+        .chain(std::iter::once(&[][..]))
+        .enumerate()
+    {
+        let index = index as u64;
+        let length = u32::try_from(block.len())
+            .map_err(|_err| PWDuckCoreError::Error("The block is too large".into()))?
+            .to_le_bytes();
+        writer.write_bytes(&hmac(
+            &keys.hmac(index),
+            &[&index.to_le_bytes(), &length, block],
+        )?);
+        writer.write_bytes(&length);
+        writer.write_bytes(block);
+    }
+    Ok(())
+}
+
+/// Calculate the HMAC-SHA-256 of the data.
+fn hmac(key: &[u8], data: &[&[u8]]) -> Result<Vec<u8>, PWDuckCoreError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)
+        .map_err(|error| PWDuckCoreError::Error(error.to_string()))?;
+    for part in data {
+        mac.update(part);
+    }
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::read_database, write_database};
+
+    #[test]
+    fn write_and_read() {
+        let xml = "<KeePassFile><Root><Group><Name>Export</Name></Group></Root></KeePassFile>";
+        let data = write_database(xml, &[7_u8; 64], "password").unwrap();
+
+        let database = read_database(&data, "password").unwrap();
+        assert_eq!(database.xml, xml);
+
+        assert!(read_database(&data, "wrong password").is_err());
+    }
+}
@@ -50,12 +50,33 @@ extern crate pest_derive;
 mod auto_type;
 pub use auto_type::{AutoTypeSequenceParser, Key, Part, Sequence};
 
+mod backup;
+pub use backup::BACKUP_EXTENSION;
+
+#[cfg(unix)]
+mod confirmation;
+#[cfg(unix)]
+pub use confirmation::{
+    request_confirmation, socket_directory, socket_path, vault_id, Confirmation,
+    ConfirmationListener, CONFIRMATION_SOCKET,
+};
+
 mod cryptography;
 
 mod error;
-pub use error::PWDuckCoreError;
+pub use error::{PWDuckCoreError, ReferenceError};
+
+mod export;
+pub use export::{Export, ExportEntry, ExportGroup};
+
+mod import;
+pub use import::{
+    CsvField, CsvFile, CsvMapping, CsvPreset, Import, ImportEntry, ImportGroup, SkippedField,
+};
 
 mod io;
+
+mod kdbx;
 pub use {io::load_application_settings, io::save_application_settings};
 
 mod mem_protection;
@@ -63,16 +84,25 @@ pub use mem_protection::{try_to_prevent_core_dump, MemKey, SecString, SecVec};
 
 mod model;
 pub use model::{
-    entry::{AutoTypeSequence, EntryBody, EntryHead},
+    entry::{Attribute, AutoTypeSequence, EntryBody, EntryHead},
     group::Group,
     master_key::MasterKey,
+    merge::{ExternalChanges, Merge, MergeConflict, MergeItem, MergeSide},
+    saved_search::SavedSearch,
     settings::{theme, ApplicationSettings},
     uuid::{self, Uuid},
     vault::{ItemList, Vault},
+    vault_settings::{SortDirection, SortKey, SortOrder, VaultSettings},
 };
 
+mod reference;
+pub use reference::{FieldReference, ReferenceField};
+
 mod passwords;
 pub use passwords::{generate_password, password_entropy, Symbols};
 pub use pw_entropy::PasswordInfo;
 
+mod watcher;
+pub use watcher::VaultWatcher;
+
 mod dto;
@@ -6,6 +6,7 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
+use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
 use seckey::SecBytes;
@@ -149,7 +150,8 @@ impl<T: Zeroize> From<Vec<T>> for SecVec<T> {
 }
 
 /// Wrapper around a [`String`](String). It zeroizes itself automatically at drop.
-#[derive(Clone, Default, PartialEq, Eq, Zeroize)]
+#[derive(Clone, Default, PartialEq, Eq, Zeroize, Deserialize, Serialize)]
+#[serde(transparent)]
 #[zeroize(drop)]
 #[allow(missing_debug_implementations)]
 pub struct SecString(String);
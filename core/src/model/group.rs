@@ -1,7 +1,7 @@
 //! Decrypted groups stored in memory.
 use std::{collections::HashMap, path::Path};
 
-use getset::{Getters, Setters};
+use getset::{CopyGetters, Getters, Setters};
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
@@ -16,7 +16,7 @@ use super::uuid::Uuid;
 /// The in-memory representation of a group.
 #[derive(Clone, Debug, Deserialize, Serialize, Zeroize)]
 #[zeroize(drop)]
-#[derive(Getters, Setters)]
+#[derive(CopyGetters, Getters, Setters)]
 pub struct Group {
     /// The UUID of this group.
     #[getset(get = "pub")]
@@ -30,6 +30,16 @@ pub struct Group {
     #[getset(get = "pub")]
     title: String,
 
+    /// The time this group was created as seconds since the unix epoch.
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    created: u64,
+
+    /// The time this group was modified the last time as seconds since the unix epoch.
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    last_modified: u64,
+
     /// If the group was modified.
     #[serde(skip)]
     modified: bool,
@@ -38,11 +48,14 @@ pub struct Group {
 impl Group {
     /// Create an new [`Group`](Group).
     #[must_use]
-    pub const fn new(uuid: Uuid, parent: Uuid, title: String) -> Self {
+    pub fn new(uuid: Uuid, parent: Uuid, title: String) -> Self {
+        let now = super::timestamp();
         Self {
             uuid,
             parent: Some(parent),
             title,
+            created: now,
+            last_modified: now,
             modified: true,
         }
     }
@@ -143,10 +156,13 @@ impl Group {
     /// Create a new root group on the given path.
     #[must_use]
     pub fn create_root_for(path: &Path) -> Self {
+        let now = super::timestamp();
         Self {
             uuid: Uuid::new(path),
             parent: None,
             title: String::new(),
+            created: now,
+            last_modified: now,
             modified: true,
         }
     }
@@ -160,10 +176,30 @@ impl Group {
     /// Set the title of this group.
     pub fn set_title(&mut self, title: String) -> &mut Self {
         self.title = title;
+        self.touch();
+        self
+    }
+
+    /// Set the parent of this group.
+    pub fn set_parent(&mut self, parent: Uuid) -> &mut Self {
+        self.parent = Some(parent);
+        self.touch();
+        self
+    }
+
+    /// Mark this group as modified to save it, but keep the time of the last modification,
+    /// e.g. taken over from another copy of the vault.
+    pub(crate) fn mark_modified(&mut self) -> &mut Self {
         self.modified = true;
         self
     }
 
+    /// Mark this group as modified and update the time of the last modification.
+    fn touch(&mut self) {
+        self.last_modified = super::timestamp();
+        self.modified = true;
+    }
+
     /// True, if this group was modified.
     #[must_use]
     pub const fn is_modified(&self) -> bool {
@@ -317,6 +353,19 @@ mod tests {
         assert_eq!(group.title.as_str(), title);
     }
 
+    #[test]
+    fn set_parent() {
+        let parent: Uuid = [84_u8; uuid::SIZE].into();
+
+        let mut group = DEFAULT_GROUP.to_owned();
+        group.modified = false;
+
+        let _ = group.set_parent(parent.clone());
+
+        assert!(group.modified);
+        assert_eq!(group.parent, Some(parent));
+    }
+
     #[test]
     fn is_modified() {
         let mut group = DEFAULT_GROUP.to_owned();
@@ -0,0 +1,263 @@
+//! The undo and redo history of the unsaved changes of a vault.
+use super::{entry::EntryHead, group::Group, vault::Children};
+
+/// The maximum number of [`Command`](Command)s that can be undone.
+pub const MAX_HISTORY_LENGTH: usize = 100;
+
+/// A single change applied to a [`Vault`](crate::Vault).
+///
+/// Each command contains the states of the affected item before and after
+/// the change, so that it can be reverted and re-applied.
+#[derive(Clone, Debug)]
+pub enum Command {
+    /// A new [`Group`](Group) was inserted.
+    InsertGroup(Group),
+    /// An existing [`Group`](Group) was modified.
+    ModifyGroup {
+        /// The [`Group`](Group) before the modification.
+        before: Group,
+        /// The [`Group`](Group) after the modification.
+        after: Group,
+    },
+    /// A [`Group`](Group) was deleted.
+    DeleteGroup {
+        /// The deleted [`Group`](Group).
+        group: Group,
+        /// The children of the deleted [`Group`](Group).
+        children: Children,
+    },
+    /// A [`Group`](Group) was moved into another parent [`Group`](Group).
+    MoveGroup {
+        /// The [`Group`](Group) before it was moved.
+        before: Group,
+        /// The [`Group`](Group) after it was moved.
+        after: Group,
+    },
+    /// A new entry was inserted.
+    InsertEntry(EntrySnapshot),
+    /// An existing entry was modified.
+    ModifyEntry {
+        /// The entry before the modification.
+        before: EntrySnapshot,
+        /// The entry after the modification.
+        after: EntrySnapshot,
+    },
+    /// An entry was deleted.
+    DeleteEntry(EntrySnapshot),
+    /// An entry was moved into another parent [`Group`](Group).
+    MoveEntry {
+        /// The entry before it was moved.
+        before: EntrySnapshot,
+        /// The entry after it was moved.
+        after: EntrySnapshot,
+    },
+    /// Several changes made by a single action, e.g. an import, in the order they were applied.
+    Batch(Vec<Command>),
+}
+
+/// The state of an entry at a specific point in the history.
+#[derive(Clone, Debug)]
+pub struct EntrySnapshot {
+    /// The [`EntryHead`](EntryHead) of the entry.
+    pub head: EntryHead,
+    /// The encrypted data-transfer-object (dto) of the body, if the body was not saved yet.
+    pub body: Option<crate::dto::entry::EntryBody>,
+}
+
+/// The undo and redo stacks of a [`Vault`](crate::Vault).
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    /// The [`Command`](Command)s that can be undone. The last one is the most recent.
+    undo_stack: Vec<Command>,
+    /// The [`Command`](Command)s that can be redone. The last one is the most recently undone.
+    redo_stack: Vec<Command>,
+    /// The [`Command`](Command)s of the running batch, see [`begin_batch`](History::begin_batch).
+    batch: Option<Vec<Command>>,
+}
+
+impl History {
+    /// Record a new [`Command`](Command).
+    ///
+    /// This invalidates all [`Command`](Command)s that could be redone.
+    pub fn record(&mut self, command: Command) {
+        self.redo_stack.clear();
+        match &mut self.batch {
+            Some(batch) => batch.push(command),
+            None => self.push_undo(command),
+        }
+    }
+
+    /// Collect all [`Command`](Command)s recorded from now on into a single [`Batch`](Command::Batch),
+    /// so they are undone and redone in one step.
+    pub fn begin_batch(&mut self) {
+        if self.batch.is_none() {
+            self.batch = Some(Vec::new());
+        }
+    }
+
+    /// Record the [`Command`](Command)s collected since [`begin_batch`](History::begin_batch).
+    pub fn end_batch(&mut self) {
+        if let Some(mut batch) = self.batch.take() {
+            if batch.len() > 1 {
+                self.push_undo(Command::Batch(batch));
+            } else if let Some(command) = batch.pop() {
+                self.push_undo(command);
+            }
+        }
+    }
+
+    /// Take the most recent [`Command`](Command) that can be undone.
+    pub fn pop_undo(&mut self) -> Option<Command> {
+        self.undo_stack.pop()
+    }
+
+    /// Take the most recently undone [`Command`](Command).
+    pub fn pop_redo(&mut self) -> Option<Command> {
+        self.redo_stack.pop()
+    }
+
+    /// Push a [`Command`](Command) that can be undone without invalidating the redo stack.
+    pub fn push_undo(&mut self, command: Command) {
+        if self.undo_stack.len() >= MAX_HISTORY_LENGTH {
+            drop(self.undo_stack.remove(0));
+        }
+        self.undo_stack.push(command);
+    }
+
+    /// Push a [`Command`](Command) that was undone and can be redone.
+    pub fn push_redo(&mut self, command: Command) {
+        self.redo_stack.push(command);
+    }
+
+    /// True, if there is a [`Command`](Command) that can be undone.
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// True, if there is a [`Command`](Command) that can be redone.
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Remove all [`Command`](Command)s from the history.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.batch = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{model::uuid, Group};
+
+    use super::{Command, History, MAX_HISTORY_LENGTH};
+
+    fn command(id: u8) -> Command {
+        Command::InsertGroup(Group::new(
+            [id; uuid::SIZE].into(),
+            [255_u8; uuid::SIZE].into(),
+            format!("Group: {}", id),
+        ))
+    }
+
+    fn title_of(command: &Command) -> Option<&str> {
+        match command {
+            Command::InsertGroup(group) => Some(group.title()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn record_and_pop() {
+        let mut history = History::default();
+        assert!(!history.can_undo());
+        assert!(!history.can_redo());
+
+        history.record(command(1));
+        history.record(command(2));
+        assert!(history.can_undo());
+
+        let undone = history.pop_undo().unwrap();
+        assert_eq!(title_of(&undone), Some("Group: 2"));
+        history.push_redo(undone);
+        assert!(history.can_redo());
+
+        let redone = history.pop_redo().unwrap();
+        assert_eq!(title_of(&redone), Some("Group: 2"));
+        history.push_undo(redone);
+        assert!(!history.can_redo());
+
+        let undone = history.pop_undo().unwrap();
+        history.push_redo(undone);
+        assert!(history.can_redo());
+        history.record(command(3));
+        assert!(!history.can_redo());
+        assert_eq!(title_of(&history.pop_undo().unwrap()), Some("Group: 3"));
+        assert_eq!(title_of(&history.pop_undo().unwrap()), Some("Group: 1"));
+        assert!(history.pop_undo().is_none());
+    }
+
+    #[test]
+    fn limit_history_length() {
+        let mut history = History::default();
+
+        for id in 0..=(MAX_HISTORY_LENGTH as u8) {
+            history.record(command(id));
+        }
+
+        assert_eq!(history.undo_stack.len(), MAX_HISTORY_LENGTH);
+        assert_eq!(title_of(&history.undo_stack[0]), Some("Group: 1"));
+    }
+
+    #[test]
+    fn batch() {
+        let mut history = History::default();
+        history.record(command(1));
+        let undone = history.pop_undo().unwrap();
+        history.push_redo(undone);
+
+        history.begin_batch();
+        for id in 0..=(MAX_HISTORY_LENGTH as u8) {
+            history.record(command(id));
+        }
+        assert!(!history.can_redo());
+        assert!(!history.can_undo());
+        history.end_batch();
+
+        assert!(matches!(
+            history.pop_undo().unwrap(),
+            Command::Batch(commands)
+                if commands.len() == MAX_HISTORY_LENGTH + 1
+                    && title_of(&commands[0]) == Some("Group: 0")
+        ));
+        assert!(!history.can_undo());
+
+        // A batch of a single command is recorded as the command itself.
+        history.begin_batch();
+        history.record(command(2));
+        history.end_batch();
+        assert_eq!(title_of(&history.pop_undo().unwrap()), Some("Group: 2"));
+
+        // An empty batch is not recorded at all.
+        history.begin_batch();
+        history.end_batch();
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn clear() {
+        let mut history = History::default();
+        history.record(command(1));
+        history.record(command(2));
+        let undone = history.pop_undo().unwrap();
+        history.push_redo(undone);
+
+        history.clear();
+
+        assert!(!history.can_undo());
+        assert!(!history.can_redo());
+    }
+}
@@ -0,0 +1,526 @@
+//! Compare the open vault with another copy of it to merge the changes of both
+//! and with the changes made on disk by other programs.
+use std::{collections::HashMap, path::PathBuf};
+
+use getset::{CopyGetters, Getters};
+
+use super::{entry::EntryHead, group::Group, uuid::Uuid};
+
+/// One of the two copies of a merged vault.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeSide {
+    /// The open vault.
+    Local,
+    /// The other copy of the vault.
+    Remote,
+}
+
+impl MergeSide {
+    /// The other copy of the vault.
+    #[must_use]
+    pub const fn other(self) -> Self {
+        match self {
+            Self::Local => Self::Remote,
+            Self::Remote => Self::Local,
+        }
+    }
+}
+
+/// The kind of a merged item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MergeItem {
+    /// A [`Group`](Group).
+    Group,
+    /// An entry.
+    Entry,
+}
+
+/// An item that was changed in both copies since they diverged.
+#[derive(Clone, Debug, CopyGetters, Getters)]
+pub struct MergeConflict {
+    /// The UUID of the item.
+    #[getset(get = "pub")]
+    uuid: Uuid,
+
+    /// The kind of the item.
+    #[getset(get_copy = "pub")]
+    item: MergeItem,
+
+    /// The title of the item in the open vault.
+    #[getset(get = "pub")]
+    local_title: String,
+
+    /// The title of the item in the other copy.
+    #[getset(get = "pub")]
+    remote_title: String,
+
+    /// The time the item was modified in the open vault as seconds since the unix epoch.
+    #[getset(get_copy = "pub")]
+    local_modified: u64,
+
+    /// The time the item was modified in the other copy as seconds since the unix epoch.
+    #[getset(get_copy = "pub")]
+    remote_modified: u64,
+
+    /// The copy whose version of the item is kept.
+    #[getset(get_copy = "pub")]
+    winner: MergeSide,
+}
+
+/// The comparison of the open vault with another copy of it.
+///
+/// Items are matched by their UUID and the version modified last is taken over.
+/// Entries modified at the same time, but with different [`EntryBody`](crate::EntryBody)s,
+/// are reported as conflicts, because it is unknown which copy changed them.
+/// Items that exist only in one copy are kept, because deletions are not recorded.
+/// An item changed in both copies since they diverged is reported as a [`MergeConflict`](MergeConflict),
+/// the version modified last is preselected as the winner.
+/// The copies are assumed to have diverged after the most recent modification of the items
+/// that are still identical in both copies.
+#[derive(Debug, CopyGetters, Getters)]
+pub struct Merge {
+    /// The location of the other copy.
+    #[getset(get = "pub")]
+    path: PathBuf,
+
+    /// The [`Group`](Group)s of the other copy.
+    remote_groups: HashMap<Uuid, Group>,
+
+    /// The [`EntryHead`](EntryHead)s of the other copy.
+    remote_entries: HashMap<Uuid, EntryHead>,
+
+    /// The items of the other copy that are taken over without a conflict.
+    taken: Vec<ItemKey>,
+
+    /// The number of items that only exist in the other copy.
+    #[getset(get_copy = "pub")]
+    added: usize,
+
+    /// The number of items that were changed only in the other copy.
+    #[getset(get_copy = "pub")]
+    updated: usize,
+
+    /// The items that were changed in both copies.
+    #[getset(get = "pub")]
+    conflicts: Vec<MergeConflict>,
+}
+
+/// The groups, entries, saved searches and settings of the open vault that were changed on disk
+/// by another program, e.g. a sync client, since they were loaded or saved.
+#[derive(Debug, Default, CopyGetters, Getters)]
+pub struct ExternalChanges {
+    /// The number of items that were created or changed on disk and reloaded.
+    #[getset(get_copy = "pub")]
+    reloaded: usize,
+
+    /// The number of items that were removed on disk and removed from the open vault.
+    #[getset(get_copy = "pub")]
+    removed: usize,
+
+    /// The items that were changed on disk while they contain unsaved changes.
+    /// An item removed on disk is reported with an empty remote title,
+    /// an item deleted in the open vault with an empty local title.
+    #[getset(get = "pub")]
+    conflicts: Vec<MergeConflict>,
+}
+
+impl ExternalChanges {
+    /// Create the report of the external changes.
+    pub(crate) const fn new(
+        reloaded: usize,
+        removed: usize,
+        conflicts: Vec<MergeConflict>,
+    ) -> Self {
+        Self {
+            reloaded,
+            removed,
+            conflicts,
+        }
+    }
+
+    /// True, if nothing was changed on disk.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.reloaded == 0 && self.removed == 0 && self.conflicts.is_empty()
+    }
+}
+
+/// The identifier of a group or entry stored on disk.
+/// Groups and entries are stored separately, so their UUIDs may overlap.
+pub(crate) type ItemKey = (MergeItem, Uuid);
+
+/// The comparison of the items of the open vault with the items on disk.
+#[derive(Debug, Default)]
+pub(crate) struct DiskComparison {
+    /// The items that were created or changed on disk.
+    pub(crate) changed: Vec<ItemKey>,
+    /// The items that were removed on disk.
+    pub(crate) removed: Vec<ItemKey>,
+    /// The items that were changed on disk while they contain unsaved changes.
+    pub(crate) conflicts: Vec<MergeConflict>,
+}
+
+impl DiskComparison {
+    /// Compare the items of the open vault with the items on disk.
+    ///
+    /// It expects:
+    ///  - The versions of the items of the open vault
+    ///  - The versions of the items on disk
+    ///  - The hashes of the files of the items when they were loaded or saved
+    ///  - The hashes of the files of the items on disk
+    ///  - The items that were deleted, but not saved yet
+    pub(crate) fn compare<'a>(
+        local: impl Iterator<Item = Version<'a>>,
+        disk: impl Iterator<Item = Version<'a>>,
+        disk_versions: &HashMap<ItemKey, String>,
+        disk_hashes: &HashMap<ItemKey, String>,
+        locally_deleted: &[ItemKey],
+    ) -> Self {
+        let local: HashMap<ItemKey, Version> =
+            local.map(|version| (version.key(), version)).collect();
+        let disk: HashMap<ItemKey, Version> =
+            disk.map(|version| (version.key(), version)).collect();
+        let mut comparison = Self::default();
+
+        for (key, disk) in &disk {
+            if disk_versions.get(key) == disk_hashes.get(key) {
+                continue;
+            }
+            match local.get(key) {
+                Some(local) if local.modified => comparison
+                    .conflicts
+                    .push(MergeConflict::external(disk, Some(local), Some(disk))),
+                None if locally_deleted.contains(key) => comparison
+                    .conflicts
+                    .push(MergeConflict::external(disk, None, Some(disk))),
+                _ => comparison.changed.push(key.clone()),
+            }
+        }
+
+        for key in disk_versions.keys().filter(|key| !disk.contains_key(key)) {
+            match local.get(key) {
+                Some(local) if local.modified => comparison
+                    .conflicts
+                    .push(MergeConflict::external(local, Some(local), None)),
+                _ => comparison.removed.push(key.clone()),
+            }
+        }
+
+        comparison
+            .conflicts
+            .sort_by(|a, b| a.local_title.cmp(&b.local_title));
+        comparison
+    }
+}
+
+impl MergeConflict {
+    /// Create the conflict of an item with unsaved changes that was changed on disk.
+    /// The side that does not contain the item anymore is reported with an empty title.
+    /// The unsaved changes are preselected as the winner.
+    ///
+    /// It expects:
+    ///  - Any version of the item
+    ///  - The version of the open vault
+    ///  - The version on disk
+    fn external(item: &Version, local: Option<&Version>, disk: Option<&Version>) -> Self {
+        Self {
+            uuid: item.uuid.clone(),
+            item: item.item,
+            local_title: local.map_or_else(String::new, |local| local.title.to_owned()),
+            remote_title: disk.map_or_else(String::new, |disk| disk.title.to_owned()),
+            local_modified: local.map_or(0, |local| local.last_modified),
+            remote_modified: disk.map_or(0, |disk| disk.last_modified),
+            winner: MergeSide::Local,
+        }
+    }
+}
+
+/// The version of an item in one of the copies.
+pub(crate) struct Version<'a> {
+    /// The kind of the item.
+    item: MergeItem,
+    /// The UUID of the item.
+    uuid: &'a Uuid,
+    /// The title of the item.
+    title: &'a str,
+    /// The time the item was modified the last time.
+    last_modified: u64,
+    /// True, if the item contains unsaved changes.
+    modified: bool,
+}
+
+impl Version<'_> {
+    /// The identifier of the item on disk.
+    fn key(&self) -> ItemKey {
+        (self.item, self.uuid.clone())
+    }
+}
+
+impl<'a> From<&'a Group> for Version<'a> {
+    fn from(group: &'a Group) -> Self {
+        Self {
+            item: MergeItem::Group,
+            uuid: group.uuid(),
+            title: group.title(),
+            last_modified: group.last_modified(),
+            modified: group.is_modified(),
+        }
+    }
+}
+
+impl<'a> From<&'a EntryHead> for Version<'a> {
+    fn from(entry: &'a EntryHead) -> Self {
+        Self {
+            item: MergeItem::Entry,
+            uuid: entry.uuid(),
+            title: entry.title(),
+            last_modified: entry.last_modified(),
+            modified: entry.is_modified(),
+        }
+    }
+}
+
+impl Merge {
+    /// Compare the items of the open vault with the items of the other copy.
+    ///
+    /// It expects:
+    ///  - The location of the other copy
+    ///  - The [`Group`](Group)s of the open vault
+    ///  - The [`EntryHead`](EntryHead)s of the open vault
+    ///  - The [`Group`](Group)s of the other copy
+    ///  - The [`EntryHead`](EntryHead)s of the other copy
+    ///  - The UUIDs of the entries whose [`EntryBody`](crate::EntryBody)s differ in both copies
+    pub(crate) fn compare(
+        path: PathBuf,
+        local_groups: &HashMap<Uuid, Group>,
+        local_entries: &HashMap<Uuid, EntryHead>,
+        remote_groups: HashMap<Uuid, Group>,
+        remote_entries: HashMap<Uuid, EntryHead>,
+        changed_bodies: &[Uuid],
+    ) -> Self {
+        let versions: Vec<(Option<Version>, Version)> = remote_groups
+            .values()
+            .map(|remote| {
+                (
+                    local_groups.get(remote.uuid()).map(Version::from),
+                    remote.into(),
+                )
+            })
+            .chain(remote_entries.values().map(|remote| {
+                (
+                    local_entries.get(remote.uuid()).map(Version::from),
+                    remote.into(),
+                )
+            }))
+            .collect();
+
+        let changed_bodies: Vec<ItemKey> = changed_bodies
+            .iter()
+            .map(|uuid| (MergeItem::Entry, uuid.clone()))
+            .collect();
+        let (taken, added, updated, mut conflicts) = compare_versions(&versions, &changed_bodies);
+        conflicts.sort_by(|a, b| a.local_title.cmp(&b.local_title));
+
+        Self {
+            path,
+            remote_groups,
+            remote_entries,
+            taken,
+            added,
+            updated,
+            conflicts,
+        }
+    }
+
+    /// Keep the version of the given copy for the conflict with the given index.
+    pub fn set_winner(&mut self, index: usize, winner: MergeSide) {
+        if let Some(conflict) = self.conflicts.get_mut(index) {
+            conflict.winner = winner;
+        }
+    }
+
+    /// True, if the merge changes nothing in the open vault.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.taken.is_empty() && self.conflicts.is_empty()
+    }
+
+    /// True, if the version of the other copy of the item is taken over.
+    fn is_taken(&self, key: &ItemKey) -> bool {
+        self.taken.contains(key)
+            || self.conflicts.iter().any(|conflict| {
+                conflict.item == key.0
+                    && conflict.uuid == key.1
+                    && conflict.winner == MergeSide::Remote
+            })
+    }
+
+    /// The [`Group`](Group)s of the other copy that are taken over.
+    /// Parents are listed before their children.
+    pub(crate) fn groups_to_take(&self) -> Vec<&Group> {
+        let depth = |group: &Group| {
+            let mut depth = 0_usize;
+            let mut parent = group.parent().as_ref();
+            while let Some(group) = parent.and_then(|uuid| self.remote_groups.get(uuid)) {
+                depth += 1;
+                // Stop at broken copies containing cycles.
+                if depth > self.remote_groups.len() {
+                    break;
+                }
+                parent = group.parent().as_ref();
+            }
+            depth
+        };
+
+        let mut groups: Vec<(usize, &Group)> = self
+            .remote_groups
+            .values()
+            .filter(|group| self.is_taken(&(MergeItem::Group, group.uuid().clone())))
+            .map(|group| (depth(group), group))
+            .collect();
+        groups.sort_by_key(|(depth, _group)| *depth);
+        groups.into_iter().map(|(_depth, group)| group).collect()
+    }
+
+    /// The [`EntryHead`](EntryHead)s of the other copy that are taken over.
+    pub(crate) fn entries_to_take(&self) -> Vec<&EntryHead> {
+        self.remote_entries
+            .values()
+            .filter(|entry| self.is_taken(&(MergeItem::Entry, entry.uuid().clone())))
+            .collect()
+    }
+}
+
+/// Compare the versions of the items in both copies.
+///
+/// It expects:
+///  - The versions of the items in the open vault, if they exist there, and in the other copy
+///  - The items whose content not covered by the time of the last modification differs in both copies
+///
+/// Returns the items to take over without a conflict,
+/// the number of added and updated items and the conflicts.
+fn compare_versions(
+    versions: &[(Option<Version>, Version)],
+    changed_content: &[ItemKey],
+) -> (Vec<ItemKey>, usize, usize, Vec<MergeConflict>) {
+    let identical = |local: &Version, remote: &Version| {
+        local.last_modified == remote.last_modified && !changed_content.contains(&remote.key())
+    };
+
+    // The most recent modification both copies have in common.
+    let diverged = versions
+        .iter()
+        .filter_map(|(local, remote)| {
+            local
+                .as_ref()
+                .filter(|local| identical(local, remote))
+                .map(|local| local.last_modified)
+        })
+        .max()
+        .unwrap_or_default();
+
+    let mut taken = Vec::new();
+    let mut added = 0;
+    let mut updated = 0;
+    let mut conflicts = Vec::new();
+    for (local, remote) in versions {
+        match local {
+            None => {
+                added += 1;
+                taken.push(remote.key());
+            }
+            Some(local) if identical(local, remote) => {}
+            Some(local)
+                if local.last_modified == remote.last_modified
+                    || (local.last_modified > diverged && remote.last_modified > diverged) =>
+            {
+                conflicts.push(MergeConflict {
+                    uuid: remote.uuid.clone(),
+                    item: remote.item,
+                    local_title: local.title.to_owned(),
+                    remote_title: remote.title.to_owned(),
+                    local_modified: local.last_modified,
+                    remote_modified: remote.last_modified,
+                    winner: if remote.last_modified > local.last_modified {
+                        MergeSide::Remote
+                    } else {
+                        MergeSide::Local
+                    },
+                });
+            }
+            Some(local) if remote.last_modified > local.last_modified => {
+                updated += 1;
+                taken.push(remote.key());
+            }
+            Some(_local) => {}
+        }
+    }
+    (taken, added, updated, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Uuid;
+
+    use super::{compare_versions, MergeItem, MergeSide, Version};
+
+    fn version(uuid: &Uuid, last_modified: u64) -> Version<'_> {
+        Version {
+            item: MergeItem::Entry,
+            uuid,
+            title: "Entry",
+            last_modified,
+            modified: false,
+        }
+    }
+
+    #[test]
+    fn compare() {
+        let uuids: Vec<Uuid> = (0..7_u8).map(|i| [i; crate::uuid::SIZE].into()).collect();
+        let versions = vec![
+            // Unchanged since the copies diverged at 100.
+            (Some(version(&uuids[0], 100)), version(&uuids[0], 100)),
+            // Only in the other copy.
+            (None, version(&uuids[1], 50)),
+            // Changed only in the other copy.
+            (Some(version(&uuids[2], 90)), version(&uuids[2], 150)),
+            // Changed only in the open vault.
+            (Some(version(&uuids[3], 160)), version(&uuids[3], 80)),
+            // Changed in both copies.
+            (Some(version(&uuids[4], 170)), version(&uuids[4], 120)),
+            (Some(version(&uuids[5], 110)), version(&uuids[5], 180)),
+            // Only the body was changed in one of the copies.
+            (Some(version(&uuids[6], 100)), version(&uuids[6], 100)),
+        ];
+
+        let (taken, added, updated, conflicts) =
+            compare_versions(&versions, &[(MergeItem::Entry, uuids[6].clone())]);
+        assert_eq!(
+            taken,
+            vec![
+                (MergeItem::Entry, uuids[1].clone()),
+                (MergeItem::Entry, uuids[2].clone())
+            ]
+        );
+        assert_eq!(added, 1);
+        assert_eq!(updated, 1);
+        assert_eq!(conflicts.len(), 3);
+        assert_eq!(conflicts[0].uuid(), &uuids[4]);
+        assert_eq!(conflicts[0].winner(), MergeSide::Local);
+        assert_eq!(conflicts[0].local_modified(), 170);
+        assert_eq!(conflicts[0].remote_modified(), 120);
+        assert_eq!(conflicts[1].winner(), MergeSide::Remote);
+        assert_eq!(conflicts[2].uuid(), &uuids[6]);
+        assert_eq!(conflicts[2].winner(), MergeSide::Local);
+
+        // Entries with the same body and modification time are unchanged.
+        let (_taken, _added, _updated, conflicts) = compare_versions(&versions, &[]);
+        assert_eq!(conflicts.len(), 2);
+    }
+
+    #[test]
+    fn other_side() {
+        assert_eq!(MergeSide::Local.other(), MergeSide::Remote);
+        assert_eq!(MergeSide::Remote.other(), MergeSide::Local);
+    }
+}
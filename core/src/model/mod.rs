@@ -1,15 +1,36 @@
 //! This module contains all in memory representations that a vault is made of.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(test)]
+use mocktopus::macros::*;
 
 pub mod entry;
 
 pub mod group;
 
+pub mod history;
+
 pub mod key_file;
 
 pub mod master_key;
 
+pub mod merge;
+
+pub mod saved_search;
+
 pub mod uuid;
 
 pub mod vault;
 
+pub mod vault_settings;
+
 pub mod settings;
+
+/// Returns the current time as seconds since the unix epoch.
+#[cfg_attr(test, mockable)]
+#[must_use]
+pub fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
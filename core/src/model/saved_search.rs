@@ -0,0 +1,240 @@
+//! Decrypted saved searches stored in memory.
+//!
+//! A saved search is a named search query that is displayed like a virtual
+//! group. Its content is evaluated live against the [`Vault`](crate::Vault).
+use std::{collections::HashMap, path::Path};
+
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{
+    cryptography::{aes_cbc_decrypt, aes_cbc_encrypt, generate_aes_iv},
+    error::PWDuckCoreError,
+    mem_protection::SecString,
+};
+
+use super::uuid::Uuid;
+
+/// The in-memory representation of a saved search.
+#[derive(Clone, Debug, Deserialize, Serialize, Zeroize)]
+#[zeroize(drop)]
+#[derive(Getters)]
+pub struct SavedSearch {
+    /// The UUID of this saved search.
+    #[getset(get = "pub")]
+    uuid: Uuid,
+
+    /// The title of this saved search.
+    #[getset(get = "pub")]
+    title: String,
+
+    /// The search query. It supports the same syntax as the search of the list view.
+    #[getset(get = "pub")]
+    query: String,
+
+    /// If the saved search was modified.
+    #[serde(skip)]
+    modified: bool,
+}
+
+impl SavedSearch {
+    /// Create a new [`SavedSearch`](SavedSearch).
+    #[must_use]
+    pub const fn new(uuid: Uuid, title: String, query: String) -> Self {
+        Self {
+            uuid,
+            title,
+            query,
+            modified: true,
+        }
+    }
+
+    /// Save the [`SavedSearch`](SavedSearch) to disk.
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the [`Vault`](crate::Vault)
+    ///  - The master key to encrypt the saved search
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - The serialization of the [`SavedSearch`](SavedSearch) fails.
+    /// - The [`SavedSearch`](SavedSearch) can't be encrypted.
+    /// - Writing the encrypted [`SavedSearch`](SavedSearch) to disk failed.
+    pub fn save(&mut self, path: &Path, master_key: &[u8]) -> Result<(), PWDuckCoreError> {
+        let saved_search = self.encrypt(master_key)?;
+        crate::io::save_saved_search(path, &self.uuid, &saved_search)?;
+        self.modified = false;
+        Ok(())
+    }
+
+    /// Encrypt this [`SavedSearch`](SavedSearch) with the given master key.
+    fn encrypt(
+        &self,
+        master_key: &[u8],
+    ) -> Result<crate::dto::saved_search::SavedSearch, PWDuckCoreError> {
+        let iv = generate_aes_iv();
+        let mut content = ron::to_string(self)?;
+        let encrypted_content = aes_cbc_encrypt(content.as_bytes(), master_key, &iv)?;
+        content.zeroize();
+        Ok(crate::dto::saved_search::SavedSearch::new(
+            base64::encode(iv),
+            base64::encode(encrypted_content),
+        ))
+    }
+
+    /// Load all [`SavedSearch`](SavedSearch)es from disk.
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the [`Vault`](crate::Vault)
+    ///  - The master key to decrypt the [`SavedSearch`](SavedSearch)es
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - Reading the encrypted [`SavedSearch`](SavedSearch)es fails.
+    /// - The de-serialization of the [`SavedSearch`](SavedSearch)es fails.
+    /// - The [`SavedSearch`](SavedSearch)es can't be decrypted.
+    /// - The base64 encoded data can't be decoded.
+    pub fn load_all(
+        path: &Path,
+        master_key: &[u8],
+    ) -> Result<HashMap<Uuid, Self>, PWDuckCoreError> {
+        let dtos = crate::io::load_all_saved_searches(path)?;
+
+        let mut results = HashMap::new();
+
+        for dto in dtos {
+            let saved_search = Self::decrypt(&dto, master_key)?;
+            drop(results.insert(saved_search.uuid().clone(), saved_search));
+        }
+
+        Ok(results)
+    }
+
+    /// Decrypt the data-transfer-object (dto) of the [`SavedSearch`](SavedSearch) with the given master key.
+    fn decrypt(
+        dto: &crate::dto::saved_search::SavedSearch,
+        master_key: &[u8],
+    ) -> Result<Self, PWDuckCoreError> {
+        let decrypted_content = aes_cbc_decrypt(
+            &base64::decode(dto.content())?,
+            master_key,
+            &base64::decode(dto.iv())?,
+        )?;
+
+        let content = SecString::from_utf8(decrypted_content)?;
+        let saved_search = ron::from_str(&content)?;
+
+        Ok(saved_search)
+    }
+
+    /// Set the title of this saved search.
+    pub fn set_title(&mut self, title: String) -> &mut Self {
+        self.title = title;
+        self.modified = true;
+        self
+    }
+
+    /// Set the query of this saved search.
+    pub fn set_query(&mut self, query: String) -> &mut Self {
+        self.query = query;
+        self.modified = true;
+        self
+    }
+
+    /// True, if this saved search was modified.
+    #[must_use]
+    pub const fn is_modified(&self) -> bool {
+        self.modified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::{cryptography, io::create_new_vault_dir, model::uuid, Uuid};
+
+    use super::SavedSearch;
+
+    fn default_saved_search() -> SavedSearch {
+        SavedSearch::new(
+            [42_u8; uuid::SIZE].into(),
+            "Databases".into(),
+            "prod db".into(),
+        )
+    }
+
+    fn equal_saved_searches(a: &SavedSearch, b: &SavedSearch) -> bool {
+        a.uuid == b.uuid && a.title == b.title && a.query == b.query
+    }
+
+    #[test]
+    fn new_saved_search() {
+        let uuid: Uuid = [42_u8; uuid::SIZE].into();
+
+        let saved_search = SavedSearch::new(uuid.clone(), "Title".into(), "Query".into());
+
+        assert_eq!(saved_search.uuid, uuid);
+        assert_eq!(saved_search.title.as_str(), "Title");
+        assert_eq!(saved_search.query.as_str(), "Query");
+        assert!(saved_search.modified);
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_saved_search() {
+        let master_key = [21_u8; cryptography::MASTER_KEY_SIZE];
+
+        let saved_search = default_saved_search();
+
+        let encrypted = saved_search
+            .encrypt(&master_key)
+            .expect("Encrypting saved search should not fail.");
+
+        let decrypted = SavedSearch::decrypt(&encrypted, &master_key)
+            .expect("Decrypting saved search should not fail.");
+
+        assert!(equal_saved_searches(&saved_search, &decrypted));
+    }
+
+    #[test]
+    fn save_and_load_all_saved_searches() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        create_new_vault_dir(&path).unwrap();
+
+        let master_key = [21_u8; cryptography::MASTER_KEY_SIZE];
+
+        let mut saved_search = default_saved_search();
+        saved_search
+            .save(&path, &master_key)
+            .expect("Saving saved search should not fail.");
+        assert!(!saved_search.modified);
+
+        let loaded = SavedSearch::load_all(&path, &master_key)
+            .expect("Loading saved searches should not fail.");
+
+        assert_eq!(loaded.len(), 1);
+        assert!(equal_saved_searches(
+            &saved_search,
+            loaded.get(saved_search.uuid()).unwrap()
+        ));
+    }
+
+    #[test]
+    fn set_title_and_query() {
+        let mut saved_search = default_saved_search();
+        saved_search.modified = false;
+
+        let _ = saved_search.set_title("Mail".into());
+        assert!(saved_search.modified);
+        assert_eq!(saved_search.title.as_str(), "Mail");
+
+        saved_search.modified = false;
+        let _ = saved_search.set_query("mail".into());
+        assert!(saved_search.modified);
+        assert_eq!(saved_search.query.as_str(), "mail");
+    }
+}
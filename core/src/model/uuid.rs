@@ -1,5 +1,5 @@
 //! The unique identifiers of each data in a vault.
-use std::{convert::TryFrom, ops::Deref, path::Path};
+use std::{convert::TryFrom, fmt::Display, ops::Deref, path::Path, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
@@ -46,6 +46,43 @@ impl From<[u8; SIZE]> for Uuid {
     }
 }
 
+impl Display for Uuid {
+    /// Format the [`Uuid`](Uuid) as hyphenated lowercase hex digits (8-4-4-4-12).
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, byte) in self.id.iter().enumerate() {
+            if matches!(index, 4 | 6 | 8 | 10) {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = PWDuckCoreError;
+
+    /// Parse a [`Uuid`](Uuid) from hex digits. Hyphens are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits: Option<Vec<u8>> = s
+            .chars()
+            .filter(|c| *c != '-')
+            .map(|c| c.to_digit(16).and_then(|d| u8::try_from(d).ok()))
+            .collect();
+
+        match digits {
+            Some(digits) if digits.len() == SIZE * 2 => {
+                let mut id = [0_u8; SIZE];
+                for (byte, pair) in id.iter_mut().zip(digits.chunks(2)) {
+                    *byte = (pair[0] << 4) | pair[1];
+                }
+                Ok(id.into())
+            }
+            _ => Err(PWDuckCoreError::Error(format!("Not a valid UUID: {}", s))),
+        }
+    }
+}
+
 impl TryFrom<SecVec<u8>> for Uuid {
     type Error = PWDuckCoreError;
 
@@ -74,6 +111,34 @@ mod tests {
 
     use super::{Uuid, SIZE};
 
+    #[test]
+    fn display_and_parse() {
+        let uuid: Uuid = [
+            0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab,
+            0xcd, 0xef,
+        ]
+        .into();
+
+        let text = uuid.to_string();
+        assert_eq!(text, "01234567-89ab-cdef-0123-456789abcdef");
+
+        assert_eq!(text.parse::<Uuid>().unwrap(), uuid);
+        assert_eq!(
+            "0123456789ABCDEF0123456789ABCDEF".parse::<Uuid>().unwrap(),
+            uuid
+        );
+
+        let _ = "0123"
+            .parse::<Uuid>()
+            .expect_err("Too short UUIDs should fail.");
+        let _ = "0123456789abcdef0123456789abcdeg"
+            .parse::<Uuid>()
+            .expect_err("Invalid hex digits should fail.");
+        let _ = "+123456789abcdef0123456789abcdef"
+            .parse::<Uuid>()
+            .expect_err("Signs should fail.");
+    }
+
     #[test]
     fn new_uuid() {
         let dir = tempdir().unwrap();
@@ -1,22 +1,43 @@
 //! The vault in memory.
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use zeroize::Zeroize;
 
 use crate::{
+    backup::{self, Backup},
     cryptography::{
         decrypt_master_key, derive_key_protection, generate_chacha20_nonce, generate_master_key,
         generate_salt, unprotect_master_key,
     },
-    error::PWDuckCoreError,
-    io::{create_new_vault_dir, save_master_key},
-    mem_protection::MemKey,
-    Uuid,
+    error::{PWDuckCoreError, ReferenceError},
+    export::{Export, ExportEntry, ExportGroup},
+    import::{Import, ImportGroup},
+    io::{
+        create_new_vault_dir, load_backup_file, load_master_key, load_vault_files,
+        save_backup_file, save_master_key, save_vault_files,
+    },
+    mem_protection::{MemKey, SecString},
+    reference, Uuid,
 };
 
-use super::{entry::EntryBody, entry::EntryHead, group::Group, master_key::MasterKey};
+use super::{
+    entry::EntryBody,
+    entry::EntryHead,
+    group::Group,
+    history::{Command, EntrySnapshot, History},
+    master_key::MasterKey,
+    merge::{DiskComparison, ExternalChanges, ItemKey, Merge, MergeItem, MergeSide, Version},
+    saved_search::SavedSearch,
+    vault_settings::{SortOrder, VaultSettings},
+};
 use getset::{Getters, MutGetters};
 
+/// The maximum number of recently used entries listed on the start page.
+pub const RECENTLY_USED_LIMIT: usize = 5;
+
 /// The in-memory representation of a vault.
 #[derive(Clone, Debug, Getters, MutGetters)]
 pub struct Vault {
@@ -54,10 +75,27 @@ pub struct Vault {
     #[getset(get = "pub")]
     unsaved_entry_bodies: HashMap<Uuid, crate::dto::entry::EntryBody>,
 
+    /// The [`SavedSearch`](SavedSearch)es of this [`Vault`](Vault).
+    #[getset(get = "pub")]
+    saved_searches: HashMap<Uuid, SavedSearch>,
+
+    /// The [`VaultSettings`](VaultSettings) of this [`Vault`](Vault).
+    #[getset(get = "pub")]
+    settings: VaultSettings,
+
     /// A list of containing all the groups that will be deleted from disk when the [`Vault`](Vault) is saved.
     deleted_groups: Vec<Uuid>,
     /// A list of containing all the entries (head, body) that will be deleted from disk when the [`Vault`](Vault) is saved.
     deleted_entries: Vec<(Uuid, Uuid)>,
+    /// A list of containing all the saved searches that will be deleted from disk when the [`Vault`](Vault) is saved.
+    deleted_saved_searches: Vec<Uuid>,
+
+    /// The undo and redo history of the unsaved changes.
+    history: History,
+
+    /// The hashes of the files of the groups and entries as they were loaded or saved.
+    /// Used to detect the changes made on disk by other programs.
+    disk_versions: HashMap<ItemKey, String>,
 }
 
 impl Vault {
@@ -115,8 +153,13 @@ impl Vault {
             children: HashMap::new(),
             entries: HashMap::new(),
             unsaved_entry_bodies: HashMap::new(),
+            saved_searches: HashMap::new(),
+            settings: VaultSettings::default(),
             deleted_groups: Vec::new(),
             deleted_entries: Vec::new(),
+            deleted_saved_searches: Vec::new(),
+            history: History::default(),
+            disk_versions: HashMap::new(),
         };
 
         let root = Group::create_root_for(vault.path());
@@ -135,6 +178,27 @@ impl Vault {
 
     /// Save the vault to disk.
     ///
+    /// Nothing is saved if the files of the modified or deleted [`Group`](Group)s and [`EntryHead`](EntryHead)s
+    /// were changed on disk by another program since they were loaded or saved.
+    ///
+    /// It expects:
+    ///  - The [`MemKey`](MemKey) to decrypt the in-memory encrypted master key of the [`Vault`](Vault).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - The files were changed on disk. The [`Conflict`](PWDuckCoreError::Conflict) lists the UUIDs of the items.
+    /// - The errors of [`save_overwriting`](Vault::save_overwriting) occur.
+    pub fn save(&mut self, mem_key: &MemKey) -> Result<(), PWDuckCoreError> {
+        let conflicts = self.changed_on_disk()?;
+        if !conflicts.is_empty() {
+            return Err(PWDuckCoreError::Conflict(conflicts));
+        }
+        self.save_overwriting(mem_key)
+    }
+
+    /// Save the vault to disk and overwrite the changes made on disk by other programs.
+    ///
     /// It expects:
     ///  - The [`MemKey`](MemKey) to decrypt the in-memory encrypted master key of the [`Vault`](Vault).
     ///
@@ -146,8 +210,9 @@ impl Vault {
     /// - The [`EntryBody`](EntryBody)s can't be stored on disk.
     /// - The [`Group`](Group)s can't be stored on disk.
     /// - The [`EntryHead`](EntryHead)s can't be stored on disk.
-    /// - The entries and groups that are marked for removing can't be removed from disk.
-    pub fn save(&mut self, mem_key: &MemKey) -> Result<(), PWDuckCoreError> {
+    /// - The [`SavedSearch`](SavedSearch)es can't be stored on disk.
+    /// - The entries, groups and saved searches that are marked for removing can't be removed from disk.
+    pub fn save_overwriting(&mut self, mem_key: &MemKey) -> Result<(), PWDuckCoreError> {
         let path = self.path.clone();
         let mut master_key = unprotect_master_key(
             self.master_key.key(),
@@ -163,17 +228,36 @@ impl Vault {
             self.unsaved_entry_bodies.clear();
         }
 
+        let disk_versions = &mut self.disk_versions;
         let group_result: Result<(), PWDuckCoreError> = self
             .groups
             .iter_mut()
             .filter(|(_, group)| group.is_modified())
-            .try_for_each(|(_, group)| group.save(&path, &master_key));
+            .try_for_each(|(uuid, group)| {
+                group.save(&path, &master_key)?;
+                if let Some(hash) = crate::io::hash_group(&path, uuid)? {
+                    drop(disk_versions.insert((MergeItem::Group, uuid.clone()), hash));
+                }
+                Ok(())
+            });
 
         let entry_result: Result<(), PWDuckCoreError> = self
             .entries
             .iter_mut()
             .filter(|(_, entry)| entry.is_modified())
-            .try_for_each(|(_, entry)| entry.save(&path, &master_key));
+            .try_for_each(|(uuid, entry)| {
+                entry.save(&path, &master_key)?;
+                if let Some(hash) = crate::io::hash_entry(&path, uuid, entry.body())? {
+                    drop(disk_versions.insert((MergeItem::Entry, uuid.clone()), hash));
+                }
+                Ok(())
+            });
+
+        let saved_search_result: Result<(), PWDuckCoreError> = self
+            .saved_searches
+            .iter_mut()
+            .filter(|(_, saved_search)| saved_search.is_modified())
+            .try_for_each(|(_, saved_search)| saved_search.save(&path, &master_key));
 
         master_key.zeroize();
 
@@ -182,6 +266,9 @@ impl Vault {
             .iter()
             .try_for_each(|group| crate::io::delete_group(&path, group));
         if delete_group_result.is_ok() {
+            for uuid in &self.deleted_groups {
+                drop(self.disk_versions.remove(&(MergeItem::Group, uuid.clone())));
+            }
             self.deleted_groups.clear();
         }
 
@@ -190,11 +277,66 @@ impl Vault {
             .iter()
             .try_for_each(|entry| crate::io::delete_entry(&path, &entry.0, &entry.1));
         if delete_entry_result.is_ok() {
+            for (uuid, _body) in &self.deleted_entries {
+                drop(self.disk_versions.remove(&(MergeItem::Entry, uuid.clone())));
+            }
             self.deleted_entries.clear();
         }
 
+        let delete_saved_search_result: Result<(), PWDuckCoreError> = self
+            .deleted_saved_searches
+            .iter()
+            .try_for_each(|saved_search| crate::io::delete_saved_search(&path, saved_search));
+        if delete_saved_search_result.is_ok() {
+            self.deleted_saved_searches.clear();
+        }
+
+        // The disk state changed, the recorded changes can't be reverted anymore.
+        self.history.clear();
+
         unsaved_entry_bodies_result
             .and(group_result.and(entry_result.and(delete_group_result.and(delete_entry_result))))
+            .and(saved_search_result.and(delete_saved_search_result))
+    }
+
+    /// The UUIDs of the modified or deleted [`Group`](Group)s and entries
+    /// whose files were changed on disk since they were loaded or saved.
+    /// The files of an entry are its [`EntryHead`](EntryHead) and its [`EntryBody`](EntryBody).
+    fn changed_on_disk(&self) -> Result<Vec<Uuid>, PWDuckCoreError> {
+        let changed = |item: MergeItem, uuid: &Uuid, hash: Option<String>| {
+            (hash.as_ref() != self.disk_versions.get(&(item, uuid.clone()))).then(|| uuid.clone())
+        };
+        let mut conflicts = Vec::new();
+
+        let groups = self
+            .groups
+            .iter()
+            .filter(|(_, group)| group.is_modified())
+            .map(|(uuid, _)| uuid)
+            .chain(self.deleted_groups.iter());
+        for uuid in groups {
+            conflicts.extend(changed(
+                MergeItem::Group,
+                uuid,
+                crate::io::hash_group(&self.path, uuid)?,
+            ));
+        }
+
+        let entries = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.is_modified())
+            .map(|(uuid, entry)| (uuid, entry.body()))
+            .chain(self.deleted_entries.iter().map(|(head, body)| (head, body)));
+        for (uuid, body) in entries {
+            conflicts.extend(changed(
+                MergeItem::Entry,
+                uuid,
+                crate::io::hash_entry(&self.path, uuid, body)?,
+            ));
+        }
+
+        Ok(conflicts)
     }
 
     /// Load a [`Vault`](Vault) from disk.
@@ -213,6 +355,8 @@ impl Vault {
     /// - The master key can't be decrypted.
     /// - Loading the [`Group`](Group)s failed.
     /// - Loading the [`EntryHead`](EntryHead)s failed.
+    /// - Loading the [`SavedSearch`](SavedSearch)es failed.
+    /// - Loading the [`VaultSettings`](VaultSettings) failed.
     pub fn load<P1, P2>(
         password: &str,
         key_file: Option<P1>,
@@ -243,30 +387,12 @@ impl Vault {
         )?;
         let groups = Group::load_all(&path, &unprotected_master_key)?;
         let entries = EntryHead::load_all(&path, &unprotected_master_key)?;
+        let saved_searches = SavedSearch::load_all(&path, &unprotected_master_key)?;
+        let settings = VaultSettings::load(&path, &unprotected_master_key)?;
         drop(unprotected_master_key);
 
-        let mut children: HashMap<Uuid, Children> = HashMap::new();
-
-        for (uuid, group) in &groups {
-            if !children.contains_key(uuid) {
-                drop(children.insert(uuid.clone(), Children::default()));
-            }
-            if let Some(parent) = group.parent() {
-                children
-                    .entry(parent.clone())
-                    .or_insert_with(Children::default)
-                    .groups_mut()
-                    .push(uuid.clone());
-            }
-        }
-
-        for (uuid, entry) in &entries {
-            children
-                .entry(entry.parent().clone())
-                .or_insert_with(Children::default)
-                .entries_mut()
-                .push(uuid.clone());
-        }
+        let children = collect_children(&groups, &entries);
+        let disk_versions = hash_items(&path, groups.keys(), entries.values())?;
 
         let vault = Self {
             master_key,
@@ -278,8 +404,13 @@ impl Vault {
             children,
             entries,
             unsaved_entry_bodies: HashMap::new(),
+            saved_searches,
+            settings,
             deleted_groups: Vec::new(),
             deleted_entries: Vec::new(),
+            deleted_saved_searches: Vec::new(),
+            history: History::default(),
+            disk_versions,
         };
 
         Ok(vault)
@@ -304,42 +435,76 @@ impl Vault {
     }
 
     /// Insert a new [`Group`](Group) into this [`Vault`](Vault).
+    ///
+    /// If the [`Group`](Group) already exists, it will be replaced.
     pub fn insert_group(&mut self, group: Group) {
-        if !self.groups().contains_key(group.uuid()) {
-            // Insert into parent's children.
-            let _ = group
-                .parent()
-                .as_ref()
-                .and_then(|parent| self.children.get_mut(parent))
-                .filter(|parent| !parent.groups().contains(group.uuid())) // TODO: find better way
-                .map(|parent| parent.groups_mut().push(group.uuid().clone()));
-            // Add own children.
-            drop(
-                self.children
-                    .insert(group.uuid().clone(), Children::default()),
-            );
-        }
-
-        drop(self.groups.insert(group.uuid().clone(), group));
+        let command = self.groups.get(group.uuid()).map_or_else(
+            || Command::InsertGroup(group.clone()),
+            |before| Command::ModifyGroup {
+                before: before.clone(),
+                after: group.clone(),
+            },
+        );
+        self.put_group(group);
+        self.history.record(command);
     }
 
     /// Delete a [`Group`](Group) from this [`Vault`](Vault).
     pub fn delete_group(&mut self, uuid: &Uuid) {
-        if let Some(group) = self.groups.remove(uuid) {
-            // Remove from parent's children.
-            let _ = group
-                .parent()
-                .as_ref()
-                .and_then(|parent| self.children.get_mut(parent))
-                .map(|parent| parent.groups_mut().retain(|e| e != uuid));
-            // Remove own children.
-            drop(self.children.remove(uuid));
+        if let Some((group, children)) = self.take_group(uuid) {
             self.deleted_groups.push(uuid.clone());
+            self.history
+                .record(Command::DeleteGroup { group, children });
+        }
+    }
+
+    /// Move a [`Group`](Group) of this [`Vault`](Vault) into another parent [`Group`](Group).
+    ///
+    /// It expects:
+    ///  - The UUID of the [`Group`](Group) to move
+    ///  - The UUID of the new parent [`Group`](Group)
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - One of the [`Group`](Group)s does not exist.
+    /// - The [`Group`](Group) is the root.
+    /// - The new parent is the [`Group`](Group) itself or one of its descendants.
+    pub fn move_group(&mut self, uuid: &Uuid, parent: &Uuid) -> Result<(), PWDuckCoreError> {
+        let before = self
+            .groups
+            .get(uuid)
+            .ok_or_else(|| PWDuckCoreError::Error("The group does not exist".into()))?
+            .clone();
+        if before.is_root() {
+            return Err(PWDuckCoreError::Error("The root can't be moved".into()));
+        }
+        if !self.groups.contains_key(parent) {
+            return Err(PWDuckCoreError::Error(
+                "The new parent group does not exist".into(),
+            ));
+        }
+        if self.is_descendant_of(parent, uuid) {
+            return Err(PWDuckCoreError::Error(
+                "A group can't be moved into itself".into(),
+            ));
+        }
+        if before.parent().as_ref() == Some(parent) {
+            return Ok(());
         }
+
+        let mut after = before.clone();
+        let _ = after.set_parent(parent.clone());
+        self.put_group(after.clone());
+        self.history.record(Command::MoveGroup { before, after });
+        Ok(())
     }
 
     /// Insert a new entry into this [`Vault`](Vault).
     ///
+    /// If the entry already exists, it will be replaced.
+    /// A modified [`EntryBody`](EntryBody) of an existing entry updates the time of its last modification.
+    ///
     /// It expects:
     ///  - The [`EntryHead`] of the new entry
     ///  - The [`EntryBody`] of the new entry
@@ -355,37 +520,282 @@ impl Vault {
         entry_body: EntryBody,
         master_key: &[u8],
     ) -> Result<(), PWDuckCoreError> {
-        // Insert into parent's children.
-        let _ = self
-            .children
-            .get_mut(entry_head.parent())
-            .filter(|parent| !parent.entries().contains(entry_head.uuid())) // TODO: find better way
-            .map(|parent| parent.entries_mut().push(entry_head.uuid().clone()));
-        drop(self.entries.insert(entry_head.uuid().clone(), entry_head));
+        let mut entry_head = entry_head;
+        if entry_body.is_modified() && self.entries.contains_key(entry_head.uuid()) {
+            entry_head.touch();
+        }
+        let _ = entry_head.set_references(entry_body.references());
+        let after = EntrySnapshot {
+            head: entry_head,
+            body: Some(entry_body.encrypt(master_key)?),
+        };
+        drop(entry_body);
 
-        drop(
-            self.unsaved_entry_bodies
-                .insert(entry_body.uuid().clone(), entry_body.encrypt(master_key)?),
+        let command = self.snapshot_of(after.head.uuid()).map_or_else(
+            || Command::InsertEntry(after.clone()),
+            |before| Command::ModifyEntry {
+                before,
+                after: after.clone(),
+            },
         );
-        drop(entry_body);
+        self.put_entry(after);
+        self.history.record(command);
         Ok(())
     }
 
     /// Delete an entry from this [`Vault`](Vault).
-    pub fn delete_entry(&mut self, uuid: &Uuid) {
-        if let Some(entry_head) = self.entries.remove(uuid) {
-            // Remove from parent's children.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the fields of other entries reference the entry.
+    /// See: [`delete_entry_breaking_references`](Vault::delete_entry_breaking_references).
+    pub fn delete_entry(&mut self, uuid: &Uuid) -> Result<(), PWDuckCoreError> {
+        let referencing = self.get_referencing_entries(uuid);
+        if !referencing.is_empty() {
+            let titles: Vec<&str> = referencing
+                .iter()
+                .map(|entry| entry.title().as_str())
+                .collect();
+            return Err(ReferenceError::Referenced(titles.join(", ")).into());
+        }
+        self.delete_entry_breaking_references(uuid);
+        Ok(())
+    }
+
+    /// Delete an entry from this [`Vault`](Vault), even if the fields of other entries reference it.
+    /// Their references are broken afterwards, see: [`get_broken_references`](Vault::get_broken_references).
+    pub fn delete_entry_breaking_references(&mut self, uuid: &Uuid) {
+        if let Some(snapshot) = self.take_entry(uuid) {
+            self.deleted_entries
+                .push((uuid.clone(), snapshot.head.body().clone()));
+            self.history.record(Command::DeleteEntry(snapshot));
+        }
+    }
+
+    /// Move an entry of this [`Vault`](Vault) into another parent [`Group`](Group).
+    ///
+    /// It expects:
+    ///  - The UUID of the [`EntryHead`](EntryHead) to move
+    ///  - The UUID of the new parent [`Group`](Group)
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the entry or the new parent [`Group`](Group) does not exist.
+    pub fn move_entry(&mut self, uuid: &Uuid, parent: &Uuid) -> Result<(), PWDuckCoreError> {
+        let before = self
+            .snapshot_of(uuid)
+            .ok_or_else(|| PWDuckCoreError::Error("The entry does not exist".into()))?;
+        if !self.groups.contains_key(parent) {
+            return Err(PWDuckCoreError::Error(
+                "The new parent group does not exist".into(),
+            ));
+        }
+        if before.head.parent() == parent {
+            return Ok(());
+        }
+
+        let mut after = before.clone();
+        let _ = after.head.set_parent(parent.clone());
+        self.put_entry(after.clone());
+        self.history.record(Command::MoveEntry { before, after });
+        Ok(())
+    }
+
+    /// Revert the most recent unsaved change of this [`Vault`](Vault).
+    ///
+    /// Returns `false`, if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        if let Some(command) = self.history.pop_undo() {
+            self.revert(&command);
+            self.history.push_redo(command);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Re-apply the most recently undone change of this [`Vault`](Vault).
+    ///
+    /// Returns `false`, if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        if let Some(command) = self.history.pop_redo() {
+            self.apply(&command);
+            self.history.push_undo(command);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Revert the change of the [`Command`](Command) without recording it.
+    fn revert(&mut self, command: &Command) {
+        match command {
+            Command::InsertGroup(group) => drop(self.take_group(group.uuid())),
+            Command::ModifyGroup { before, .. } | Command::MoveGroup { before, .. } => {
+                self.put_group(before.clone());
+            }
+            Command::DeleteGroup { group, children } => {
+                self.put_group(group.clone());
+                drop(self.children.insert(group.uuid().clone(), children.clone()));
+                self.deleted_groups.retain(|uuid| uuid != group.uuid());
+            }
+            Command::InsertEntry(snapshot) => drop(self.take_entry(snapshot.head.uuid())),
+            Command::ModifyEntry { before, .. } | Command::MoveEntry { before, .. } => {
+                self.put_entry(before.clone());
+            }
+            Command::DeleteEntry(snapshot) => {
+                self.put_entry(snapshot.clone());
+                self.deleted_entries
+                    .retain(|(head, _body)| head != snapshot.head.uuid());
+            }
+            Command::Batch(commands) => {
+                for command in commands.iter().rev() {
+                    self.revert(command);
+                }
+            }
+        }
+    }
+
+    /// Re-apply the change of the [`Command`](Command) without recording it.
+    fn apply(&mut self, command: &Command) {
+        match command {
+            Command::InsertGroup(after)
+            | Command::ModifyGroup { after, .. }
+            | Command::MoveGroup { after, .. } => self.put_group(after.clone()),
+            Command::DeleteGroup { group, .. } => {
+                drop(self.take_group(group.uuid()));
+                self.deleted_groups.push(group.uuid().clone());
+            }
+            Command::InsertEntry(after)
+            | Command::ModifyEntry { after, .. }
+            | Command::MoveEntry { after, .. } => self.put_entry(after.clone()),
+            Command::DeleteEntry(snapshot) => {
+                drop(self.take_entry(snapshot.head.uuid()));
+                self.deleted_entries
+                    .push((snapshot.head.uuid().clone(), snapshot.head.body().clone()));
+            }
+            Command::Batch(commands) => {
+                for command in commands {
+                    self.apply(command);
+                }
+            }
+        }
+    }
+
+    /// True, if there is an unsaved change that can be undone.
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    /// True, if there is an undone change that can be redone.
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Put the [`Group`](Group) into this [`Vault`](Vault) and update the children of its parents.
+    fn put_group(&mut self, group: Group) {
+        let old_parent = self
+            .groups
+            .get(group.uuid())
+            .and_then(|old| old.parent().clone());
+        if old_parent.is_some() && &old_parent != group.parent() {
+            // Remove from old parent's children.
+            let _ = old_parent
+                .as_ref()
+                .and_then(|parent| self.children.get_mut(parent))
+                .map(|parent| parent.groups_mut().retain(|e| e != group.uuid()));
+        }
+
+        // Insert into parent's children.
+        let _ = group
+            .parent()
+            .as_ref()
+            .and_then(|parent| self.children.get_mut(parent))
+            .filter(|parent| !parent.groups().contains(group.uuid()))
+            .map(|parent| parent.groups_mut().push(group.uuid().clone()));
+        // Add own children.
+        let _ = self.children.entry(group.uuid().clone()).or_default();
+
+        drop(self.groups.insert(group.uuid().clone(), group));
+    }
+
+    /// Take the [`Group`](Group) and its children out of this [`Vault`](Vault).
+    fn take_group(&mut self, uuid: &Uuid) -> Option<(Group, Children)> {
+        let group = self.groups.remove(uuid)?;
+        // Remove from parent's children.
+        let _ = group
+            .parent()
+            .as_ref()
+            .and_then(|parent| self.children.get_mut(parent))
+            .map(|parent| parent.groups_mut().retain(|e| e != uuid));
+        // Remove own children.
+        let children = self.children.remove(uuid).unwrap_or_default();
+        Some((group, children))
+    }
+
+    /// Put the entry into this [`Vault`](Vault) and update the children of its parents.
+    fn put_entry(&mut self, snapshot: EntrySnapshot) {
+        let EntrySnapshot { mut head, body } = snapshot;
+
+        // The usage is recorded independently of the history and must not be reverted.
+        let old_parent = self.entries.get(head.uuid()).map(|old| {
+            let _ = head.adopt_usage(old);
+            old.parent().clone()
+        });
+        if let Some(old_parent) = old_parent.filter(|old_parent| old_parent != head.parent()) {
+            // Remove from old parent's children.
             let _ = self
                 .children
-                .get_mut(entry_head.parent())
-                .map(|parent| parent.entries_mut().retain(|e| e != uuid));
-            let entry_body = entry_head.body();
+                .get_mut(&old_parent)
+                .map(|parent| parent.entries_mut().retain(|e| e != head.uuid()));
+        }
+
+        // Insert into parent's children.
+        let _ = self
+            .children
+            .get_mut(head.parent())
+            .filter(|parent| !parent.entries().contains(head.uuid()))
+            .map(|parent| parent.entries_mut().push(head.uuid().clone()));
 
-            drop(self.unsaved_entry_bodies.remove(entry_body));
+        match body {
+            Some(body) => drop(self.unsaved_entry_bodies.insert(head.body().clone(), body)),
+            None => drop(self.unsaved_entry_bodies.remove(head.body())),
+        }
+        drop(self.entries.insert(head.uuid().clone(), head));
+    }
 
-            self.deleted_entries
-                .push((uuid.clone(), entry_body.clone()));
+    /// Take the entry out of this [`Vault`](Vault).
+    fn take_entry(&mut self, uuid: &Uuid) -> Option<EntrySnapshot> {
+        let head = self.entries.remove(uuid)?;
+        // Remove from parent's children.
+        let _ = self
+            .children
+            .get_mut(head.parent())
+            .map(|parent| parent.entries_mut().retain(|e| e != uuid));
+        let body = self.unsaved_entry_bodies.remove(head.body());
+        Some(EntrySnapshot { head, body })
+    }
+
+    /// Get the current state of the entry with the given UUID.
+    fn snapshot_of(&self, uuid: &Uuid) -> Option<EntrySnapshot> {
+        self.entries.get(uuid).map(|head| EntrySnapshot {
+            head: head.clone(),
+            body: self.unsaved_entry_bodies.get(head.body()).cloned(),
+        })
+    }
+
+    /// True, if the [`Group`](Group) is the same as or a descendant of the given ancestor.
+    fn is_descendant_of(&self, group: &Uuid, ancestor: &Uuid) -> bool {
+        let mut current = Some(group.clone());
+        while let Some(uuid) = current {
+            if &uuid == ancestor {
+                return true;
+            }
+            current = self.groups.get(&uuid).and_then(|g| g.parent().clone());
         }
+        false
     }
 
     /// Get all [`Group`](Group)s in this [`Vault`] that are the children of the specified parent [`Group`](Group).
@@ -416,6 +826,94 @@ impl Vault {
             })
     }
 
+    /// Load the [`EntryBody`](EntryBody) identified by the UUID. Unsaved
+    /// [`EntryBody`](EntryBody)s are preferred over those stored on disk.
+    ///
+    /// It expects:
+    ///  - The UUID of the [`EntryBody`](EntryBody)
+    ///  - The master key to decrypt the [`EntryBody`](EntryBody)
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the [`EntryBody`](EntryBody) can't be loaded or decrypted.
+    pub fn load_entry_body(
+        &self,
+        uuid: &Uuid,
+        master_key: &[u8],
+    ) -> Result<EntryBody, PWDuckCoreError> {
+        self.unsaved_entry_bodies.get(uuid).map_or_else(
+            || EntryBody::load(&self.path, uuid, master_key),
+            |dto| EntryBody::decrypt(dto, master_key),
+        )
+    }
+
+    /// Replace all field references in the given value by the current values of the referenced fields.
+    ///
+    /// It expects:
+    ///  - The value that may contain references like `{REF:password@<uuid>}`
+    ///  - The master key to decrypt the referenced entries
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - A referenced entry does not exist.
+    /// - The references are cyclic or nested too deeply.
+    /// - A referenced entry can't be loaded.
+    pub fn resolve_references(
+        &self,
+        value: &str,
+        master_key: &[u8],
+    ) -> Result<SecString, PWDuckCoreError> {
+        reference::resolve(value, self, master_key, 0)
+    }
+
+    /// Returns a copy of the [`EntryBody`](EntryBody) with all field references resolved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if a field reference can't be resolved.
+    /// See: [`resolve_references`](Vault::resolve_references).
+    pub fn resolve_entry_body(
+        &self,
+        entry_body: &EntryBody,
+        master_key: &[u8],
+    ) -> Result<EntryBody, PWDuckCoreError> {
+        let mut resolved = entry_body.clone();
+        let _ = resolved
+            .set_username(reference::resolve(entry_body.username(), self, master_key, 0)?.into())
+            .set_password(reference::resolve(entry_body.password(), self, master_key, 0)?.into())
+            .set_email(reference::resolve(entry_body.email(), self, master_key, 0)?.into());
+        Ok(resolved)
+    }
+
+    /// Get all [`EntryHead`](EntryHead)s in this [`Vault`](Vault) whose fields reference the specified entry.
+    #[must_use]
+    pub fn get_referencing_entries(&self, uuid: &Uuid) -> Vec<&EntryHead> {
+        let mut entries: Vec<&EntryHead> = self
+            .entries
+            .values()
+            .filter(|entry| entry.uuid() != uuid && entry.references().contains(uuid))
+            .collect();
+        entries.sort_by(|&a, &b| a.title().cmp(b.title()));
+        entries
+    }
+
+    /// Get all field references in this [`Vault`](Vault) whose referenced entry does not exist.
+    /// Returns the referencing [`EntryHead`](EntryHead) and the UUID of the missing entry.
+    #[must_use]
+    pub fn get_broken_references(&self) -> Vec<(&EntryHead, &Uuid)> {
+        self.entries
+            .values()
+            .flat_map(|entry| {
+                entry
+                    .references()
+                    .iter()
+                    .filter(|target| !self.entries.contains_key(target))
+                    .map(move |target| (entry, target))
+            })
+            .collect()
+    }
+
     /// Trie, if this [`Vault`](Vault) contains unsaved changes.
     #[must_use]
     pub fn contains_unsaved_changes(&self) -> bool {
@@ -427,23 +925,713 @@ impl Vault {
             || !self.unsaved_entry_bodies.is_empty()
             || !self.deleted_entries.is_empty()
             || !self.deleted_groups.is_empty()
+            || self
+                .saved_searches
+                .iter()
+                .any(|(_uuid, saved_search)| saved_search.is_modified())
+            || !self.deleted_saved_searches.is_empty()
     }
 
-    /// Returns the [`ItemList`](ItemList) containing [`Group`](Group)s and [`EntryHead`](EntryHead) based on the given filters.
+    /// Set the [`SortOrder`](SortOrder) of the groups and entries of this [`Vault`](Vault).
+    ///
+    /// In contrast to the content of the [`Vault`](Vault), the settings are stored on disk immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - The key to decrypt the master key can't be derived.
+    /// - The master key can't be decrypted.
+    /// - The [`VaultSettings`](VaultSettings) can't be stored on disk.
+    pub fn set_sort_order(
+        &mut self,
+        sort_order: SortOrder,
+        mem_key: &MemKey,
+    ) -> Result<(), PWDuckCoreError> {
+        let _ = self.settings.set_sort_order(sort_order);
+
+        let mut master_key = unprotect_master_key(
+            self.master_key.key(),
+            &derive_key_protection(mem_key, &self.salt)?,
+            &self.nonce,
+        )?;
+        let result = self.settings.save(&self.path, &master_key);
+        master_key.zeroize();
+
+        result
+    }
+
+    /// Record that the credentials of the entry identified by the UUID were used.
+    ///
+    /// If the entry does not contain unsaved changes, it is stored on disk immediately.
+    /// Otherwise the usage is stored together with the next save of the [`Vault`](Vault).
+    /// If another program changed the entry on disk, the usage is kept as an unsaved change as well,
+    /// so the next save or reload reports the conflict instead of overwriting the change.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - The entry does not exist.
+    /// - The [`EntryHead`](EntryHead) can't be stored on disk.
+    pub fn record_usage(&mut self, uuid: &Uuid, master_key: &[u8]) -> Result<(), PWDuckCoreError> {
+        let entry_head = self
+            .entries
+            .get_mut(uuid)
+            .ok_or_else(|| PWDuckCoreError::Error("The entry does not exist".into()))?;
+        let _ = entry_head.record_usage();
+
+        if entry_head.is_modified() {
+            return Ok(());
+        }
+        let key = (MergeItem::Entry, uuid.clone());
+        if crate::io::hash_entry(&self.path, uuid, entry_head.body())?.as_ref()
+            != self.disk_versions.get(&key)
+        {
+            let _ = entry_head.mark_modified();
+            return Ok(());
+        }
+        entry_head.save(&self.path, master_key)?;
+        // The saved file is the known version on disk, it is neither a conflict nor an external change.
+        if let Some(hash) = crate::io::hash_entry(&self.path, uuid, entry_head.body())? {
+            drop(self.disk_versions.insert(key, hash));
+        }
+        Ok(())
+    }
+
+    /// Get the most recently used [`EntryHead`](EntryHead)s of this [`Vault`](Vault), the most recent first.
     ///
     /// It expects:
-    ///  - The UUID of the current selected [`Group`](Group)
-    ///  - The optional search filter
+    ///  - The maximum number of [`EntryHead`](EntryHead)s to return
     #[must_use]
-    pub fn get_item_list_for<'a>(
-        &'a self,
-        selected_group_uuid: &Uuid,
-        search: Option<&str>,
-    ) -> ItemList<'a> {
-        let (mut groups, mut entries) = search.map_or_else(
-            || {
-                (
-                    self.get_groups_of(selected_group_uuid),
+    pub fn get_recently_used(&self, limit: usize) -> Vec<&EntryHead> {
+        let mut recently_used: Vec<&EntryHead> = self
+            .entries
+            .values()
+            .filter(|entry| entry.last_used() > 0)
+            .collect();
+        recently_used.sort_by(|a, b| {
+            b.last_used()
+                .cmp(&a.last_used())
+                .then_with(|| a.title().cmp(b.title()))
+        });
+        recently_used.truncate(limit);
+        recently_used
+    }
+
+    /// Get all favourite [`EntryHead`](EntryHead)s of this [`Vault`](Vault) sorted by the current [`SortOrder`](SortOrder).
+    #[must_use]
+    pub fn get_favourites(&self) -> Vec<&EntryHead> {
+        let sort_order = self.settings.sort_order();
+        let mut favourites: Vec<&EntryHead> = self
+            .entries
+            .values()
+            .filter(|entry| entry.favourite())
+            .collect();
+        favourites.sort_by(|&a, &b| sort_order.compare_entries(a, b));
+        favourites
+    }
+
+    /// Import the groups and entries read from the database of another password manager.
+    ///
+    /// The content of the imported root is inserted directly into the parent [`Group`](Group).
+    /// Existing groups with the same title as an imported group are reused.
+    ///
+    /// It expects:
+    ///  - The [`Import`](Import) containing the groups and entries
+    ///  - The UUID of the parent [`Group`](Group)
+    ///  - The master key to encrypt the [`EntryBody`](EntryBody)s
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - The parent [`Group`](Group) does not exist.
+    /// - An [`EntryBody`](EntryBody) can't be encrypted.
+    pub fn import(
+        &mut self,
+        import: &Import,
+        parent: &Uuid,
+        master_key: &[u8],
+    ) -> Result<(), PWDuckCoreError> {
+        if !self.groups.contains_key(parent) {
+            return Err(PWDuckCoreError::Error(
+                "The parent group does not exist".into(),
+            ));
+        }
+        // The whole import is undone in one step, including the items imported before a failure.
+        self.history.begin_batch();
+        let result = self.import_group(import.root(), parent, master_key);
+        self.history.end_batch();
+        result
+    }
+
+    /// Insert the content of the imported group into the parent [`Group`](Group).
+    fn import_group(
+        &mut self,
+        import_group: &ImportGroup,
+        parent: &Uuid,
+        master_key: &[u8],
+    ) -> Result<(), PWDuckCoreError> {
+        for import_entry in import_group.entries() {
+            let body_uuid = Uuid::new(&self.path);
+            let mut entry_body = EntryBody::new(
+                body_uuid.clone(),
+                import_entry.username().as_str().to_owned(),
+                import_entry.password().as_str().to_owned(),
+            );
+            let _ = entry_body.set_email(import_entry.email().as_str().to_owned());
+
+            let mut entry_head = EntryHead::new(
+                Uuid::new(&self.path),
+                parent.clone(),
+                import_entry.title().clone(),
+                body_uuid,
+            );
+            let _ = entry_head
+                .set_web_address(import_entry.web_address().clone())
+                .set_favourite(import_entry.favourite())
+                .set_timestamps(import_entry.created(), import_entry.last_modified());
+
+            self.insert_entry(entry_head, entry_body, master_key)?;
+        }
+
+        for child in import_group.groups() {
+            // Existing groups with the same title are reused.
+            let existing = self
+                .get_groups_of(parent)
+                .into_iter()
+                .find(|group| group.title() == child.title())
+                .map(|group| group.uuid().clone());
+            let uuid = if let Some(uuid) = existing {
+                uuid
+            } else {
+                let group =
+                    Group::new(Uuid::new(&self.path), parent.clone(), child.title().clone());
+                let uuid = group.uuid().clone();
+                self.insert_group(group);
+                uuid
+            };
+            self.import_group(child, &uuid, master_key)?;
+        }
+
+        Ok(())
+    }
+
+    /// Export the [`Group`](Group) identified by the UUID with all of its descendants.
+    /// The field references of the entries are replaced by their current values.
+    ///
+    /// It expects:
+    ///  - The UUID of the [`Group`](Group) to export
+    ///  - The master key to decrypt the entries
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the group does not exist or an entry can't be decrypted.
+    pub fn export(&self, uuid: &Uuid, master_key: &[u8]) -> Result<Export, PWDuckCoreError> {
+        let group = self
+            .groups
+            .get(uuid)
+            .ok_or_else(|| PWDuckCoreError::Error("The group does not exist".into()))?;
+        let title = if group.is_root() {
+            self.get_name().to_owned()
+        } else {
+            group.title().clone()
+        };
+        Ok(Export::new(self.export_group(uuid, title, master_key)?))
+    }
+
+    /// Export the content of the [`Group`](Group) identified by the UUID.
+    fn export_group(
+        &self,
+        uuid: &Uuid,
+        title: String,
+        master_key: &[u8],
+    ) -> Result<ExportGroup, PWDuckCoreError> {
+        let entries = self
+            .get_entries_of(uuid)
+            .into_iter()
+            .map(|entry_head| self.export_entry(entry_head, master_key))
+            .collect::<Result<Vec<_>, PWDuckCoreError>>()?;
+
+        let groups = self
+            .get_groups_of(uuid)
+            .into_iter()
+            .map(|group| self.export_group(group.uuid(), group.title().clone(), master_key))
+            .collect::<Result<Vec<_>, PWDuckCoreError>>()?;
+
+        Ok(ExportGroup::new(title, groups, entries))
+    }
+
+    /// Export the [`EntryHead`](EntryHead) together with its decrypted and resolved [`EntryBody`](EntryBody).
+    fn export_entry(
+        &self,
+        entry_head: &EntryHead,
+        master_key: &[u8],
+    ) -> Result<ExportEntry, PWDuckCoreError> {
+        let entry_body = self.load_entry_body(entry_head.body(), master_key)?;
+        let entry_body = self.resolve_entry_body(&entry_body, master_key)?;
+        Ok(ExportEntry::new(entry_head, &entry_body))
+    }
+
+    /// Export the content of the [`ItemList`](ItemList), e.g. the result of a search.
+    /// The listed [`Group`](Group)s are exported with all of their descendants and the listed entries
+    /// are placed directly into the exported root named after this [`Vault`](Vault).
+    /// The recently used entries are skipped, because they are duplicates of other entries.
+    ///
+    /// It expects:
+    ///  - The [`ItemList`](ItemList) to export
+    ///  - The master key to decrypt the entries
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an entry can't be decrypted.
+    pub fn export_item_list(
+        &self,
+        item_list: &ItemList,
+        master_key: &[u8],
+    ) -> Result<Export, PWDuckCoreError> {
+        let groups = item_list
+            .groups()
+            .iter()
+            .map(|group| self.export_group(group.uuid(), group.title().clone(), master_key))
+            .collect::<Result<Vec<_>, PWDuckCoreError>>()?;
+
+        let mut exported: Vec<&Uuid> = Vec::new();
+        let mut entries = Vec::new();
+        for entry_head in item_list.favourites().iter().chain(item_list.entries()) {
+            if !exported.contains(&entry_head.uuid()) {
+                exported.push(entry_head.uuid());
+                entries.push(self.export_entry(entry_head, master_key)?);
+            }
+        }
+
+        Ok(Export::new(ExportGroup::new(
+            self.get_name().to_owned(),
+            groups,
+            entries,
+        )))
+    }
+
+    /// True, if the password unlocks the master key of this [`Vault`](Vault).
+    /// The master key is loaded from disk again and, if configured, the key file is used.
+    /// This is used to confirm critical actions like an unencrypted export.
+    ///
+    /// It expects:
+    ///  - The password to check
+    ///  - The [`MemKey`](MemKey) used to protect the master key in memory
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the master key can't be loaded from disk.
+    pub fn verify_password(
+        &self,
+        password: &str,
+        mem_key: &MemKey,
+    ) -> Result<bool, PWDuckCoreError> {
+        let master_key_dto = load_master_key(&self.path)?;
+        let master_key = decrypt_master_key(
+            &master_key_dto,
+            password,
+            self.key_file.as_deref(),
+            &derive_key_protection(mem_key, &self.salt)?,
+            &self.nonce,
+        );
+        Ok(master_key.map_or(false, |master_key| {
+            master_key.key() == self.master_key.key()
+        }))
+    }
+
+    /// Write the whole directory of this [`Vault`](Vault) into a single backup archive.
+    /// The archive is versioned and authenticated with a key derived from the master key.
+    /// Only the state on disk is written, unsaved changes must be saved before.
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the archive
+    ///  - The master key to authenticate the archive
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the files of this [`Vault`](Vault) can't be read or the archive can't be written.
+    pub fn export_backup(&self, path: &Path, master_key: &[u8]) -> Result<(), PWDuckCoreError> {
+        let files = load_vault_files(&self.path)?;
+        let archive = backup::write_backup(&files, master_key)?;
+        save_backup_file(path, &archive)
+    }
+
+    /// Validate the backup archive and unpack it as a new [`Vault`](Vault) at the given location.
+    /// The restored [`Vault`](Vault) is unlocked by the same password and key file as the backed up one.
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the archive
+    ///  - The password of the backed up [`Vault`](Vault)
+    ///  - The location of the optional key file
+    ///  - The [`MemKey`](MemKey) used to protect the master key in memory
+    ///  - The [`Path`](Path) as the location of the new [`Vault`](Vault). It must not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - The archive can't be read or is not a backup of a supported version.
+    /// - The password or the key file is wrong or the archive was modified.
+    /// - The location of the new [`Vault`](Vault) already exists or the files can't be written.
+    pub fn restore_backup(
+        backup: &Path,
+        password: &str,
+        key_file: Option<&Path>,
+        mem_key: &MemKey,
+        path: &Path,
+    ) -> Result<(), PWDuckCoreError> {
+        let archive = load_backup_file(backup)?;
+        let backup = Backup::parse(&archive)?;
+
+        let master_key_dto = ron::from_str(&String::from_utf8_lossy(backup.master_key()))?;
+
+        let salt = generate_salt();
+        let nonce = generate_chacha20_nonce()?;
+        let key_protection = derive_key_protection(mem_key, &salt)?;
+        let master_key =
+            decrypt_master_key(&master_key_dto, password, key_file, &key_protection, &nonce)
+                .map_err(|_err| {
+                    PWDuckCoreError::Error("Wrong password or the backup is corrupted".into())
+                })?;
+        let master_key = unprotect_master_key(master_key.key(), &key_protection, &nonce)?;
+
+        let files = backup.verify(&master_key)?;
+        drop(master_key);
+        save_vault_files(path, &files)
+    }
+
+    /// Compare this [`Vault`](Vault) with another copy of it, e.g. diverged by syncing two devices.
+    /// The returned [`Merge`](Merge) reports the conflicts and is applied by [`merge`](Vault::merge).
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the other copy
+    ///  - The master key to decrypt the other copy
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the directory is not a copy of this [`Vault`](Vault) or can't be decrypted.
+    pub fn prepare_merge(&self, path: &Path, master_key: &[u8]) -> Result<Merge, PWDuckCoreError> {
+        let not_a_copy = || {
+            PWDuckCoreError::Error(format!(
+                "The directory {} does not contain a copy of this vault",
+                path.display()
+            ))
+        };
+        let remote_groups = Group::load_all(path, master_key).map_err(|_err| not_a_copy())?;
+        let remote_entries = EntryHead::load_all(path, master_key).map_err(|_err| not_a_copy())?;
+
+        let remote_root = remote_groups
+            .values()
+            .find(|group| group.is_root())
+            .map(|group| group.uuid().clone());
+        if remote_root.is_none() || remote_root != self.get_root_uuid() {
+            return Err(not_a_copy());
+        }
+
+        // Bodies changed without a new time of the last modification are only found by their values.
+        let mut changed_bodies = Vec::new();
+        for remote in remote_entries.values() {
+            let local = self
+                .entries
+                .get(remote.uuid())
+                .filter(|local| local.last_modified() == remote.last_modified());
+            if let Some(local) = local {
+                let local_body = self.load_entry_body(local.body(), master_key)?;
+                let remote_body = EntryBody::load(path, remote.body(), master_key)?;
+                if !local_body.has_same_values(&remote_body) {
+                    changed_bodies.push(remote.uuid().clone());
+                }
+            }
+        }
+
+        Ok(Merge::compare(
+            path.to_path_buf(),
+            &self.groups,
+            &self.entries,
+            remote_groups,
+            remote_entries,
+            &changed_bodies,
+        ))
+    }
+
+    /// Take over the groups and entries of the other copy as selected by the [`Merge`](Merge).
+    /// The changes are recorded like any other change and must be saved afterwards.
+    /// Merging the [`Vault`](Vault) with its own location takes over the changes made on disk by other programs,
+    /// so saving the merged changes does not conflict with them anymore.
+    ///
+    /// It expects:
+    ///  - The [`Merge`](Merge) created by [`prepare_merge`](Vault::prepare_merge)
+    ///  - The master key to decrypt the [`EntryBody`](EntryBody)s of the other copy
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if an [`EntryBody`](EntryBody) of the other copy can't be loaded or encrypted.
+    pub fn merge(&mut self, merge: &Merge, master_key: &[u8]) -> Result<(), PWDuckCoreError> {
+        // The whole merge is undone in one step, including the items taken before a failure.
+        self.history.begin_batch();
+        let result = self.take_merged(merge, master_key);
+        self.history.end_batch();
+        result?;
+
+        if merge.path() == &self.path {
+            self.disk_versions = hash_items(&self.path, self.groups.keys(), self.entries.values())?;
+        }
+        Ok(())
+    }
+
+    /// Insert the groups and entries of the other copy selected by the [`Merge`](Merge).
+    fn take_merged(&mut self, merge: &Merge, master_key: &[u8]) -> Result<(), PWDuckCoreError> {
+        for group in merge.groups_to_take() {
+            let mut group = group.clone();
+            let _ = group.mark_modified();
+            self.insert_group(group);
+        }
+
+        for entry_head in merge.entries_to_take() {
+            let entry_body = EntryBody::load(merge.path(), entry_head.body(), master_key)?;
+            let mut entry_head = entry_head.clone();
+            let _ = entry_head.mark_modified();
+            self.insert_entry(entry_head, entry_body, master_key)?;
+        }
+        Ok(())
+    }
+
+    /// Reload the [`Group`](Group)s and [`EntryHead`](EntryHead)s that were created, changed or removed
+    /// on disk by another program, e.g. a sync client, since they were loaded or saved.
+    /// Changes are detected by the hashes of the files of the items, an entry also changes with its [`EntryBody`](EntryBody).
+    /// The [`SavedSearch`](SavedSearch)es without unsaved changes and the [`VaultSettings`](VaultSettings) are reloaded as well.
+    ///
+    /// Items with unsaved changes are not reloaded, but reported as conflicts.
+    /// Reloading items clears the undo and redo history, like saving the [`Vault`](Vault).
+    /// A conflict is resolved by passing the side to keep:
+    ///  - [`Local`](MergeSide::Local) keeps the unsaved changes, they overwrite the changes on disk when saved.
+    ///  - [`Remote`](MergeSide::Remote) discards the unsaved changes and reloads the items from disk.
+    ///
+    /// It expects:
+    ///  - The master key to decrypt the items on disk
+    ///  - The side to keep for conflicting items or `None` to report them
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the items can't be loaded from disk.
+    pub fn reload_external_changes(
+        &mut self,
+        master_key: &[u8],
+        resolution: Option<MergeSide>,
+    ) -> Result<ExternalChanges, PWDuckCoreError> {
+        let mut disk_groups = Group::load_all(&self.path, master_key)?;
+        let mut disk_entries = EntryHead::load_all(&self.path, master_key)?;
+        let disk_hashes = hash_items(&self.path, disk_groups.keys(), disk_entries.values())?;
+
+        let locally_deleted: Vec<ItemKey> = self
+            .deleted_groups
+            .iter()
+            .map(|uuid| (MergeItem::Group, uuid.clone()))
+            .chain(
+                self.deleted_entries
+                    .iter()
+                    .map(|(head, _body)| (MergeItem::Entry, head.clone())),
+            )
+            .collect();
+        let comparison = DiskComparison::compare(
+            self.groups
+                .values()
+                .map(Version::from)
+                .chain(self.entries.values().map(Version::from)),
+            disk_groups
+                .values()
+                .map(Version::from)
+                .chain(disk_entries.values().map(Version::from)),
+            &self.disk_versions,
+            &disk_hashes,
+            &locally_deleted,
+        );
+
+        let DiskComparison {
+            mut changed,
+            mut removed,
+            conflicts,
+        } = comparison;
+        let conflicts = match resolution {
+            None => conflicts,
+            Some(MergeSide::Remote) => {
+                for conflict in conflicts {
+                    let key = (conflict.item(), conflict.uuid().clone());
+                    if disk_hashes.contains_key(&key) {
+                        changed.push(key);
+                    } else {
+                        removed.push(key);
+                    }
+                }
+                Vec::new()
+            }
+            Some(MergeSide::Local) => {
+                for conflict in conflicts {
+                    let key = (conflict.item(), conflict.uuid().clone());
+                    match disk_hashes.get(&key) {
+                        Some(hash) => drop(self.disk_versions.insert(key, hash.clone())),
+                        None => drop(self.disk_versions.remove(&key)),
+                    }
+                }
+                Vec::new()
+            }
+        };
+
+        for key in &changed {
+            if let Some(hash) = disk_hashes.get(key) {
+                drop(self.disk_versions.insert(key.clone(), hash.clone()));
+            }
+            let (item, uuid) = key;
+            match item {
+                MergeItem::Group => {
+                    if let Some(group) = disk_groups.remove(uuid) {
+                        self.deleted_groups.retain(|deleted| deleted != uuid);
+                        drop(self.groups.insert(uuid.clone(), group));
+                    }
+                }
+                MergeItem::Entry => {
+                    if let Some(entry) = disk_entries.remove(uuid) {
+                        self.deleted_entries
+                            .retain(|(deleted, _body)| deleted != uuid);
+                        if let Some(old) = self.entries.insert(uuid.clone(), entry) {
+                            drop(self.unsaved_entry_bodies.remove(old.body()));
+                        }
+                    }
+                }
+            }
+        }
+
+        for key in &removed {
+            self.forget_removed(key);
+        }
+
+        if !changed.is_empty() || !removed.is_empty() {
+            self.children = collect_children(&self.groups, &self.entries);
+            // The recorded changes would revert the reloaded items to their outdated versions.
+            self.history.clear();
+        }
+
+        let (other_reloaded, other_removed) =
+            self.reload_saved_searches_and_settings(master_key)?;
+
+        Ok(ExternalChanges::new(
+            changed.len() + other_reloaded,
+            removed.len() + other_removed,
+            conflicts,
+        ))
+    }
+
+    /// Reload the [`SavedSearch`](SavedSearch)es and the [`VaultSettings`](VaultSettings) that differ on disk.
+    /// Saved searches with unsaved changes are kept.
+    ///
+    /// Returns the number of reloaded and removed saved searches and settings.
+    fn reload_saved_searches_and_settings(
+        &mut self,
+        master_key: &[u8],
+    ) -> Result<(usize, usize), PWDuckCoreError> {
+        let disk_searches = SavedSearch::load_all(&self.path, master_key)?;
+        let settings = VaultSettings::load(&self.path, master_key)?;
+
+        let count = self.saved_searches.len();
+        self.saved_searches.retain(|uuid, saved_search| {
+            saved_search.is_modified() || disk_searches.contains_key(uuid)
+        });
+        let removed = count - self.saved_searches.len();
+
+        let mut reloaded = 0;
+        for (uuid, disk_search) in disk_searches {
+            let changed = !self.deleted_saved_searches.contains(&uuid)
+                && self.saved_searches.get(&uuid).map_or(true, |saved_search| {
+                    !saved_search.is_modified()
+                        && (saved_search.title() != disk_search.title()
+                            || saved_search.query() != disk_search.query())
+                });
+            if changed {
+                drop(self.saved_searches.insert(uuid, disk_search));
+                reloaded += 1;
+            }
+        }
+
+        if settings != self.settings {
+            self.settings = settings;
+            reloaded += 1;
+        }
+        Ok((reloaded, removed))
+    }
+
+    /// Remove the item that was removed on disk without recording the removal.
+    fn forget_removed(&mut self, key: &ItemKey) {
+        drop(self.disk_versions.remove(key));
+        let (item, uuid) = key;
+        match item {
+            MergeItem::Group => {
+                drop(self.groups.remove(uuid));
+                self.deleted_groups.retain(|deleted| deleted != uuid);
+            }
+            MergeItem::Entry => {
+                if let Some(old) = self.entries.remove(uuid) {
+                    drop(self.unsaved_entry_bodies.remove(old.body()));
+                }
+                self.deleted_entries
+                    .retain(|(deleted, _body)| deleted != uuid);
+            }
+        }
+    }
+
+    /// Insert a new [`SavedSearch`](SavedSearch) into this [`Vault`](Vault).
+    ///
+    /// If the [`SavedSearch`](SavedSearch) already exists, it will be replaced.
+    pub fn insert_saved_search(&mut self, saved_search: SavedSearch) {
+        self.deleted_saved_searches
+            .retain(|uuid| uuid != saved_search.uuid());
+        drop(
+            self.saved_searches
+                .insert(saved_search.uuid().clone(), saved_search),
+        );
+    }
+
+    /// Delete the [`SavedSearch`](SavedSearch) identified by the UUID from this [`Vault`](Vault).
+    pub fn delete_saved_search(&mut self, uuid: &Uuid) {
+        if self.saved_searches.remove(uuid).is_some() {
+            self.deleted_saved_searches.push(uuid.clone());
+        }
+    }
+
+    /// Returns all [`SavedSearch`](SavedSearch)es of this [`Vault`](Vault) sorted by their titles.
+    #[must_use]
+    pub fn get_saved_searches(&self) -> Vec<&SavedSearch> {
+        let mut saved_searches: Vec<&SavedSearch> = self.saved_searches.values().collect();
+        saved_searches.sort_by(|a, b| a.title().cmp(b.title()));
+        saved_searches
+    }
+
+    /// Returns the [`ItemList`](ItemList) with the current results of the [`SavedSearch`](SavedSearch) identified by the UUID.
+    ///
+    /// Returns `None`, if the [`SavedSearch`](SavedSearch) does not exist.
+    #[must_use]
+    pub fn get_item_list_for_saved_search(&self, uuid: &Uuid) -> Option<ItemList<'_>> {
+        self.saved_searches.get(uuid).map(|saved_search| {
+            // The selected group is ignored while searching.
+            self.get_item_list_for(uuid, Some(saved_search.query()))
+        })
+    }
+
+    /// Returns the [`ItemList`](ItemList) containing [`Group`](Group)s and [`EntryHead`](EntryHead) based on the given filters.
+    /// The items are sorted by the current [`SortOrder`](SortOrder). The favourites are pinned at the top of the root
+    /// and the recently used entries are listed above them.
+    ///
+    /// It expects:
+    ///  - The UUID of the current selected [`Group`](Group)
+    ///  - The optional search filter
+    #[must_use]
+    pub fn get_item_list_for<'a>(
+        &'a self,
+        selected_group_uuid: &Uuid,
+        search: Option<&str>,
+    ) -> ItemList<'a> {
+        let (mut groups, mut entries) = search.map_or_else(
+            || {
+                (
+                    self.get_groups_of(selected_group_uuid),
                     self.get_entries_of(selected_group_uuid),
                 )
             },
@@ -464,10 +1652,30 @@ impl Vault {
             },
         );
 
-        groups.sort_by(|&a, &b| a.title().cmp(b.title()));
-        entries.sort_by(|&a, &b| a.title().cmp(b.title()));
+        let is_root = self
+            .groups
+            .get(selected_group_uuid)
+            .map_or(false, Group::is_root);
+        let (recently_used, favourites) = if search.is_none() && is_root {
+            entries.retain(|entry| !entry.favourite());
+            (
+                self.get_recently_used(RECENTLY_USED_LIMIT),
+                self.get_favourites(),
+            )
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        let sort_order = self.settings.sort_order();
+        groups.sort_by(|&a, &b| sort_order.compare_groups(a, b));
+        entries.sort_by(|&a, &b| sort_order.compare_entries(a, b));
 
-        ItemList { groups, entries }
+        ItemList {
+            recently_used,
+            favourites,
+            groups,
+            entries,
+        }
     }
 }
 
@@ -482,9 +1690,69 @@ pub struct Children {
     entries: Vec<Uuid>,
 }
 
+/// Hash the files of the [`Group`](Group)s with the given UUIDs and of the given entries.
+/// Items that do not exist on disk are left out.
+fn hash_items<'a>(
+    path: &Path,
+    groups: impl Iterator<Item = &'a Uuid>,
+    entries: impl Iterator<Item = &'a EntryHead>,
+) -> Result<HashMap<ItemKey, String>, PWDuckCoreError> {
+    let mut hashes = HashMap::new();
+    for uuid in groups {
+        if let Some(hash) = crate::io::hash_group(path, uuid)? {
+            drop(hashes.insert((MergeItem::Group, uuid.clone()), hash));
+        }
+    }
+    for entry in entries {
+        if let Some(hash) = crate::io::hash_entry(path, entry.uuid(), entry.body())? {
+            drop(hashes.insert((MergeItem::Entry, entry.uuid().clone()), hash));
+        }
+    }
+    Ok(hashes)
+}
+
+/// Collect the children of all [`Group`](Group)s.
+fn collect_children(
+    groups: &HashMap<Uuid, Group>,
+    entries: &HashMap<Uuid, EntryHead>,
+) -> HashMap<Uuid, Children> {
+    let mut children: HashMap<Uuid, Children> = HashMap::new();
+
+    for (uuid, group) in groups {
+        if !children.contains_key(uuid) {
+            drop(children.insert(uuid.clone(), Children::default()));
+        }
+        if let Some(parent) = group.parent() {
+            children
+                .entry(parent.clone())
+                .or_insert_with(Children::default)
+                .groups_mut()
+                .push(uuid.clone());
+        }
+    }
+
+    for (uuid, entry) in entries {
+        children
+            .entry(entry.parent().clone())
+            .or_insert_with(Children::default)
+            .entries_mut()
+            .push(uuid.clone());
+    }
+
+    children
+}
+
 /// Filtered collection of [`Group`](Group)s and [`EntryHead`](EntryHead)s.
 #[derive(Debug, Getters)]
 pub struct ItemList<'a> {
+    /// Collection of the recently used [`EntryHead`](EntryHead)s listed on the start page.
+    #[getset(get = "pub")]
+    recently_used: Vec<&'a EntryHead>,
+
+    /// Collection of the favourite [`EntryHead`](EntryHead)s pinned at the top.
+    #[getset(get = "pub")]
+    favourites: Vec<&'a EntryHead>,
+
     /// Collection of [`Group`](Group)s.
     #[getset(get = "pub")]
     groups: Vec<&'a Group>,
@@ -498,21 +1766,35 @@ impl<'a> ItemList<'a> {
     /// Trie, if this [`ItemList`](ItemList) is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.groups.is_empty() && self.entries.is_empty()
+        self.recently_used.is_empty()
+            && self.favourites.is_empty()
+            && self.groups.is_empty()
+            && self.entries.is_empty()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::path::Path;
+    use std::{
+        convert::TryFrom,
+        path::Path,
+        sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
+    };
 
     use mocktopus::mocking::*;
     use seckey::SecBytes;
     use tempfile::tempdir;
 
-    use crate::{cryptography, model::uuid, EntryBody, EntryHead, Group, MemKey, Uuid};
+    use crate::{
+        cryptography,
+        error::ReferenceError,
+        io::{ENTRIES_DIR, HEAD},
+        model::uuid,
+        EntryBody, EntryHead, Group, Import, MemKey, MergeSide, PWDuckCoreError, SavedSearch,
+        SortDirection, SortKey, SortOrder, Uuid,
+    };
 
-    use super::{ItemList, Vault};
+    use super::{ItemList, Vault, RECENTLY_USED_LIMIT};
 
     const PASSWORD: &str = "This is a totally secure password";
     const VAULT_NAME: &str = "Default Vault";
@@ -652,7 +1934,7 @@ mod tests {
             .iter()
             .take(delete_entry_count)
             .for_each(|(entry, _)| {
-                vault.delete_entry(entry.uuid());
+                vault.delete_entry(entry.uuid()).unwrap();
             });
         vault.save(&mem_key).expect("Should not fail");
 
@@ -844,6 +2126,34 @@ mod tests {
         assert!(root_children.entries.contains(&head_uuid));
 
         assert!(vault.unsaved_entry_bodies.contains_key(&body_uuid));
+
+        // Changing only the password modifies the head, too.
+        vault.save(&mem_key).unwrap();
+        let _ = vault
+            .entries
+            .get_mut(&head_uuid)
+            .unwrap()
+            .set_timestamps(1, 1);
+        let head = vault.entries.get(&head_uuid).unwrap().clone();
+        assert!(!head.is_modified());
+        assert_eq!(head.last_modified(), 1);
+
+        let mut body = vault.load_entry_body(&body_uuid, &master_key).unwrap();
+        let _ = body.set_password("Changed".into());
+        vault.insert_entry(head, body, &master_key).unwrap();
+        let modified = vault.entries.get(&head_uuid).unwrap();
+        assert!(modified.is_modified());
+        assert!(modified.last_modified() > 1);
+        vault.save(&mem_key).unwrap();
+        assert!(!vault.contains_unsaved_changes());
+        assert_eq!(
+            vault
+                .load_entry_body(&body_uuid, &master_key)
+                .unwrap()
+                .password()
+                .as_str(),
+            "Changed"
+        );
     }
 
     #[test]
@@ -889,7 +2199,7 @@ mod tests {
             .deleted_entries
             .contains(&(head_uuid.clone(), body_uuid.clone())));
 
-        vault.delete_entry(&head_uuid);
+        vault.delete_entry(&head_uuid).unwrap();
 
         assert!(!vault.entries.contains_key(&head_uuid));
         assert!(!vault
@@ -903,7 +2213,7 @@ mod tests {
     }
 
     #[test]
-    fn get_groups_of() {
+    fn move_group() {
         let dir = tempdir().unwrap();
         let path = dir.path();
         let mem_key = default_mem_key();
@@ -911,7 +2221,396 @@ mod tests {
         let mut vault = default_vault(&path, &mem_key);
 
         let root = vault.get_root_uuid().unwrap();
-        let mut expected: Vec<Group> = (0..=10)
+        let parent: Uuid = [21_u8; uuid::SIZE].into();
+        let uuid: Uuid = [42_u8; uuid::SIZE].into();
+        vault.insert_group(Group::new(parent.clone(), root.clone(), "Parent".into()));
+        vault.insert_group(Group::new(uuid.clone(), root.clone(), "Child".into()));
+
+        vault
+            .move_group(&uuid, &parent)
+            .expect("Moving the group should not fail.");
+
+        assert_eq!(
+            vault.groups.get(&uuid).unwrap().parent(),
+            &Some(parent.clone())
+        );
+        assert!(!vault.children.get(&root).unwrap().groups.contains(&uuid));
+        assert!(vault.children.get(&parent).unwrap().groups.contains(&uuid));
+
+        assert!(vault.move_group(&root, &parent).is_err());
+        assert!(vault.move_group(&parent, &uuid).is_err());
+        assert!(vault.move_group(&parent, &parent).is_err());
+        assert!(vault.move_group(&uuid, &[1_u8; uuid::SIZE].into()).is_err());
+    }
+
+    #[test]
+    fn move_entry() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+
+        let group: Uuid = [21_u8; uuid::SIZE].into();
+        let head_uuid: Uuid = [42_u8; uuid::SIZE].into();
+        let body_uuid: Uuid = [84_u8; uuid::SIZE].into();
+        vault.insert_group(Group::new(group.clone(), root.clone(), "Group".into()));
+        vault
+            .insert_entry(
+                EntryHead::new(
+                    head_uuid.clone(),
+                    root.clone(),
+                    "Title".into(),
+                    body_uuid.clone(),
+                ),
+                EntryBody::new(body_uuid.clone(), "username".into(), "password".into()),
+                &master_key,
+            )
+            .unwrap();
+
+        vault
+            .move_entry(&head_uuid, &group)
+            .expect("Moving the entry should not fail.");
+
+        assert_eq!(vault.entries.get(&head_uuid).unwrap().parent(), &group);
+        assert!(!vault
+            .children
+            .get(&root)
+            .unwrap()
+            .entries
+            .contains(&head_uuid));
+        assert!(vault
+            .children
+            .get(&group)
+            .unwrap()
+            .entries
+            .contains(&head_uuid));
+        assert!(vault.unsaved_entry_bodies.contains_key(&body_uuid));
+
+        assert!(vault
+            .move_entry(&head_uuid, &[1_u8; uuid::SIZE].into())
+            .is_err());
+        assert!(vault.move_entry(&[1_u8; uuid::SIZE].into(), &root).is_err());
+    }
+
+    #[test]
+    fn undo_and_redo_groups() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+        assert!(!vault.can_undo());
+        assert!(!vault.can_redo());
+
+        let root = vault.get_root_uuid().unwrap();
+        let parent: Uuid = [21_u8; uuid::SIZE].into();
+        let uuid: Uuid = [42_u8; uuid::SIZE].into();
+
+        // Undo and redo an insertion.
+        vault.insert_group(Group::new(uuid.clone(), root.clone(), "Title".into()));
+        assert!(vault.undo());
+        assert!(!vault.groups.contains_key(&uuid));
+        assert!(!vault.children.get(&root).unwrap().groups.contains(&uuid));
+        assert!(!vault.contains_unsaved_changes());
+        assert!(vault.redo());
+        assert!(vault.groups.contains_key(&uuid));
+        assert!(vault.children.get(&root).unwrap().groups.contains(&uuid));
+        assert!(!vault.redo());
+
+        vault.save(&mem_key).unwrap();
+        assert!(!vault.can_undo());
+
+        // Undo and redo a modification.
+        let mut modified = vault.groups.get(&uuid).unwrap().clone();
+        let _ = modified.set_title("Modified".into());
+        vault.insert_group(modified);
+        assert!(vault.undo());
+        assert_eq!(vault.groups.get(&uuid).unwrap().title(), "Title");
+        assert!(!vault.contains_unsaved_changes());
+        assert!(vault.redo());
+        assert_eq!(vault.groups.get(&uuid).unwrap().title(), "Modified");
+
+        // Undo and redo a move.
+        vault.insert_group(Group::new(parent.clone(), root.clone(), "Parent".into()));
+        vault.move_group(&uuid, &parent).unwrap();
+        assert!(vault.undo());
+        assert_eq!(
+            vault.groups.get(&uuid).unwrap().parent(),
+            &Some(root.clone())
+        );
+        assert!(vault.children.get(&root).unwrap().groups.contains(&uuid));
+        assert!(!vault.children.get(&parent).unwrap().groups.contains(&uuid));
+        assert!(vault.redo());
+        assert_eq!(
+            vault.groups.get(&uuid).unwrap().parent(),
+            &Some(parent.clone())
+        );
+        assert!(!vault.children.get(&root).unwrap().groups.contains(&uuid));
+        assert!(vault.children.get(&parent).unwrap().groups.contains(&uuid));
+
+        // Undo and redo a deletion.
+        vault.delete_group(&uuid);
+        assert!(vault.undo());
+        assert!(vault.groups.contains_key(&uuid));
+        assert!(vault.children.contains_key(&uuid));
+        assert!(!vault.deleted_groups.contains(&uuid));
+        assert!(vault.redo());
+        assert!(!vault.groups.contains_key(&uuid));
+        assert!(vault.deleted_groups.contains(&uuid));
+
+        // A new change invalidates the redo stack.
+        assert!(vault.undo());
+        assert!(vault.can_redo());
+        vault.insert_group(Group::new([1_u8; uuid::SIZE].into(), root, "New".into()));
+        assert!(!vault.can_redo());
+    }
+
+    #[test]
+    fn undo_and_redo_entries() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+
+        let group: Uuid = [21_u8; uuid::SIZE].into();
+        let head_uuid: Uuid = [42_u8; uuid::SIZE].into();
+        let body_uuid: Uuid = [84_u8; uuid::SIZE].into();
+        let head = EntryHead::new(
+            head_uuid.clone(),
+            root.clone(),
+            "Title".into(),
+            body_uuid.clone(),
+        );
+        let body = EntryBody::new(body_uuid.clone(), "username".into(), "password".into());
+
+        // Undo and redo an insertion.
+        vault
+            .insert_entry(head.clone(), body.clone(), &master_key)
+            .unwrap();
+        assert!(vault.undo());
+        assert!(!vault.entries.contains_key(&head_uuid));
+        assert!(!vault.unsaved_entry_bodies.contains_key(&body_uuid));
+        assert!(!vault
+            .children
+            .get(&root)
+            .unwrap()
+            .entries
+            .contains(&head_uuid));
+        assert!(vault.redo());
+        assert!(vault.entries.contains_key(&head_uuid));
+        assert!(vault.unsaved_entry_bodies.contains_key(&body_uuid));
+        assert!(vault
+            .children
+            .get(&root)
+            .unwrap()
+            .entries
+            .contains(&head_uuid));
+
+        vault.save(&mem_key).unwrap();
+
+        // Undo and redo a modification.
+        let mut modified_head = head.clone();
+        let _ = modified_head.set_title("Modified".into());
+        let mut modified_body = body.clone();
+        let _ = modified_body.set_password("Modified".into());
+        vault
+            .insert_entry(modified_head, modified_body, &master_key)
+            .unwrap();
+        assert!(vault.undo());
+        assert_eq!(vault.entries.get(&head_uuid).unwrap().title(), "Title");
+        assert!(!vault.unsaved_entry_bodies.contains_key(&body_uuid));
+        assert!(!vault.contains_unsaved_changes());
+        assert!(vault.redo());
+        assert_eq!(vault.entries.get(&head_uuid).unwrap().title(), "Modified");
+        let restored = EntryBody::decrypt(
+            vault.unsaved_entry_bodies.get(&body_uuid).unwrap(),
+            &master_key,
+        )
+        .unwrap();
+        assert_eq!(restored.password().as_str(), "Modified");
+
+        // Undo and redo a move.
+        vault.insert_group(Group::new(group.clone(), root.clone(), "Group".into()));
+        vault.move_entry(&head_uuid, &group).unwrap();
+        assert!(vault.undo());
+        assert_eq!(vault.entries.get(&head_uuid).unwrap().parent(), &root);
+        assert!(vault
+            .children
+            .get(&root)
+            .unwrap()
+            .entries
+            .contains(&head_uuid));
+        assert!(!vault
+            .children
+            .get(&group)
+            .unwrap()
+            .entries
+            .contains(&head_uuid));
+        assert!(vault.redo());
+        assert_eq!(vault.entries.get(&head_uuid).unwrap().parent(), &group);
+
+        // Undo and redo a deletion.
+        vault.delete_entry(&head_uuid).unwrap();
+        assert!(vault.undo());
+        assert!(vault.entries.contains_key(&head_uuid));
+        assert!(vault.unsaved_entry_bodies.contains_key(&body_uuid));
+        assert!(vault.deleted_entries.is_empty());
+        assert!(vault.redo());
+        assert!(!vault.entries.contains_key(&head_uuid));
+        assert!(vault
+            .deleted_entries
+            .contains(&(head_uuid.clone(), body_uuid.clone())));
+
+        // Undo everything.
+        while vault.undo() {}
+        assert!(!vault.contains_unsaved_changes());
+    }
+
+    #[test]
+    fn resolve_references() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+
+        let group: Uuid = [21_u8; uuid::SIZE].into();
+        let target: Uuid = [42_u8; uuid::SIZE].into();
+        let source: Uuid = [43_u8; uuid::SIZE].into();
+        vault.insert_group(Group::new(group.clone(), root.clone(), "Group".into()));
+        vault
+            .insert_entry(
+                EntryHead::new(
+                    target.clone(),
+                    root.clone(),
+                    "Target".into(),
+                    [84_u8; uuid::SIZE].into(),
+                ),
+                EntryBody::new(
+                    [84_u8; uuid::SIZE].into(),
+                    "username".into(),
+                    "password".into(),
+                ),
+                &master_key,
+            )
+            .unwrap();
+        vault
+            .insert_entry(
+                EntryHead::new(
+                    source.clone(),
+                    root.clone(),
+                    "Source".into(),
+                    [85_u8; uuid::SIZE].into(),
+                ),
+                EntryBody::new(
+                    [85_u8; uuid::SIZE].into(),
+                    format!("{{REF:username@{}}}", target),
+                    format!("{{REF:password@{}}}", target),
+                ),
+                &master_key,
+            )
+            .unwrap();
+
+        assert_eq!(
+            vault.entries.get(&source).unwrap().references(),
+            &vec![target.clone()]
+        );
+        let referencing: Vec<&Uuid> = vault
+            .get_referencing_entries(&target)
+            .iter()
+            .map(|entry| entry.uuid())
+            .collect();
+        assert_eq!(referencing, vec![&source]);
+        assert!(vault.get_referencing_entries(&source).is_empty());
+
+        let value = format!("{{REF:title@{}}}: {{REF:password@{}}}", target, target);
+        assert_eq!(
+            vault
+                .resolve_references(&value, &master_key)
+                .expect("Resolving references should not fail.")
+                .as_str(),
+            "Target: password"
+        );
+
+        // References are still valid after the target was moved.
+        vault.move_entry(&target, &group).unwrap();
+        vault.move_group(&group, &root).unwrap();
+        let body = vault
+            .load_entry_body(&[85_u8; uuid::SIZE].into(), &master_key)
+            .unwrap();
+        let resolved = vault
+            .resolve_entry_body(&body, &master_key)
+            .expect("Resolving the body should not fail.");
+        assert_eq!(resolved.username().as_str(), "username");
+        assert_eq!(resolved.password().as_str(), "password");
+        assert!(vault.get_broken_references().is_empty());
+
+        // Cyclic references fail.
+        let cyclic: Uuid = [44_u8; uuid::SIZE].into();
+        let reference = format!("{{REF:username@{}}}", cyclic);
+        vault
+            .insert_entry(
+                EntryHead::new(
+                    cyclic.clone(),
+                    root.clone(),
+                    "Cyclic".into(),
+                    [86_u8; uuid::SIZE].into(),
+                ),
+                EntryBody::new([86_u8; uuid::SIZE].into(), reference.clone(), "".into()),
+                &master_key,
+            )
+            .unwrap();
+        assert!(vault.resolve_references(&reference, &master_key).is_err());
+        vault.delete_entry(&cyclic).unwrap();
+
+        // Referenced entries are only deleted if the references may break.
+        assert!(matches!(
+            vault.delete_entry(&target),
+            Err(PWDuckCoreError::Reference(ReferenceError::Referenced(titles))) if titles == "Source"
+        ));
+        assert!(vault.entries().contains_key(&target));
+
+        // References to deleted entries fail.
+        vault.delete_entry_breaking_references(&target);
+        assert!(vault.resolve_references(&value, &master_key).is_err());
+        let broken: Vec<(&Uuid, &Uuid)> = vault
+            .get_broken_references()
+            .into_iter()
+            .map(|(entry, missing)| (entry.uuid(), missing))
+            .collect();
+        assert_eq!(broken, vec![(&source, &target)]);
+    }
+
+    #[test]
+    fn get_groups_of() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+
+        let root = vault.get_root_uuid().unwrap();
+        let mut expected: Vec<Group> = (0..=10)
             .into_iter()
             .map(|next| {
                 let uuid: Uuid = [next; uuid::SIZE].into();
@@ -1065,7 +2764,7 @@ mod tests {
             .unwrap();
         vault.save(&mem_key).unwrap();
         assert!(!vault.contains_unsaved_changes());
-        vault.delete_entry(&head_uuid);
+        vault.delete_entry(&head_uuid).unwrap();
         assert!(vault.contains_unsaved_changes());
     }
 
@@ -1168,28 +2867,110 @@ mod tests {
     }
 
     #[test]
-    fn item_list_is_empty() {
-        let item_list = ItemList {
-            groups: vec![],
-            entries: vec![],
-        };
-
-        assert!(item_list.is_empty());
+    fn saved_searches() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
 
-        let group = Group::new(
-            [42_u8; uuid::SIZE].into(),
-            [21_u8; uuid::SIZE].into(),
-            "Title".into(),
-        );
+        let mut vault = default_vault(&path, &mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        vault.insert_group(Group::new(
+            [1_u8; uuid::SIZE].into(),
+            root.clone(),
+            "Production".into(),
+        ));
+        vault.insert_group(Group::new(
+            [2_u8; uuid::SIZE].into(),
+            root,
+            "Staging".into(),
+        ));
+        vault.save(&mem_key).unwrap();
 
-        let item_list = ItemList {
-            groups: vec![&group],
-            entries: vec![],
-        };
+        let prod: Uuid = [42_u8; uuid::SIZE].into();
+        let all: Uuid = [21_u8; uuid::SIZE].into();
+        vault.insert_saved_search(SavedSearch::new(prod.clone(), "Prod".into(), "prod".into()));
+        vault.insert_saved_search(SavedSearch::new(all.clone(), "All".into(), "".into()));
+        assert!(vault.contains_unsaved_changes());
 
-        assert!(!item_list.is_empty());
+        let titles: Vec<&String> = vault
+            .get_saved_searches()
+            .iter()
+            .map(|saved_search| saved_search.title())
+            .collect();
+        assert_eq!(titles, vec!["All", "Prod"]);
 
-        let entry = EntryHead::new(
+        // The results are evaluated live.
+        let item_list = vault.get_item_list_for_saved_search(&prod).unwrap();
+        assert_eq!(item_list.groups.len(), 1);
+        vault.insert_group(Group::new(
+            [3_u8; uuid::SIZE].into(),
+            vault.get_root_uuid().unwrap(),
+            "Reproduction".into(),
+        ));
+        let item_list = vault.get_item_list_for_saved_search(&prod).unwrap();
+        assert_eq!(item_list.groups.len(), 2);
+        assert!(vault
+            .get_item_list_for_saved_search(&[0_u8; uuid::SIZE].into())
+            .is_none());
+
+        vault.save(&mem_key).unwrap();
+        assert!(!vault.contains_unsaved_changes());
+
+        let mut loaded_vault = Vault::load(
+            PASSWORD,
+            Option::<String>::None,
+            &mem_key,
+            &path.join(VAULT_NAME),
+        )
+        .expect("Should not fail");
+        assert_eq!(loaded_vault.saved_searches().len(), 2);
+        assert_eq!(
+            loaded_vault.saved_searches().get(&prod).unwrap().query(),
+            "prod"
+        );
+
+        loaded_vault.delete_saved_search(&prod);
+        assert!(loaded_vault.contains_unsaved_changes());
+        loaded_vault.save(&mem_key).unwrap();
+
+        let loaded_vault = Vault::load(
+            PASSWORD,
+            Option::<String>::None,
+            &mem_key,
+            &path.join(VAULT_NAME),
+        )
+        .expect("Should not fail");
+        assert_eq!(loaded_vault.saved_searches().len(), 1);
+        assert!(loaded_vault.saved_searches().contains_key(&all));
+    }
+
+    #[test]
+    fn item_list_is_empty() {
+        let item_list = ItemList {
+            recently_used: vec![],
+            favourites: vec![],
+            groups: vec![],
+            entries: vec![],
+        };
+
+        assert!(item_list.is_empty());
+
+        let group = Group::new(
+            [42_u8; uuid::SIZE].into(),
+            [21_u8; uuid::SIZE].into(),
+            "Title".into(),
+        );
+
+        let item_list = ItemList {
+            recently_used: vec![],
+            favourites: vec![],
+            groups: vec![&group],
+            entries: vec![],
+        };
+
+        assert!(!item_list.is_empty());
+
+        let entry = EntryHead::new(
             [42_u8; uuid::SIZE].into(),
             [21_u8; uuid::SIZE].into(),
             "title".into(),
@@ -1197,6 +2978,8 @@ mod tests {
         );
 
         let item_list = ItemList {
+            recently_used: vec![],
+            favourites: vec![],
             groups: vec![],
             entries: vec![&entry],
         };
@@ -1204,10 +2987,944 @@ mod tests {
         assert!(!item_list.is_empty());
 
         let item_list = ItemList {
+            recently_used: vec![],
+            favourites: vec![],
             groups: vec![&group],
             entries: vec![&entry],
         };
 
         assert!(!item_list.is_empty());
+
+        let item_list = ItemList {
+            recently_used: vec![],
+            favourites: vec![&entry],
+            groups: vec![],
+            entries: vec![],
+        };
+
+        assert!(!item_list.is_empty());
+    }
+
+    #[test]
+    fn sort_order_and_favourites() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+
+        let group: Uuid = [1_u8; uuid::SIZE].into();
+        vault.insert_group(Group::new(group.clone(), root.clone(), "Group".into()));
+
+        // The entries are created in reverse order of their titles.
+        let clock = AtomicU64::new(1_000);
+        crate::model::timestamp
+            .mock_safe(move || MockResult::Return(clock.fetch_add(1, AtomicOrdering::SeqCst)));
+        for (index, title) in ["C", "B", "A"].iter().enumerate() {
+            let index = u8::try_from(index).unwrap();
+            let parent = if *title == "B" { &group } else { &root };
+            vault
+                .insert_entry(
+                    EntryHead::new(
+                        [10 + index; uuid::SIZE].into(),
+                        parent.clone(),
+                        (*title).into(),
+                        [20 + index; uuid::SIZE].into(),
+                    ),
+                    EntryBody::new(
+                        [20 + index; uuid::SIZE].into(),
+                        "username".into(),
+                        "password".into(),
+                    ),
+                    &master_key,
+                )
+                .unwrap();
+        }
+
+        let titles = |item_list: &ItemList| -> Vec<String> {
+            item_list
+                .entries()
+                .iter()
+                .map(|entry| entry.title().clone())
+                .collect()
+        };
+
+        assert_eq!(
+            titles(&vault.get_item_list_for(&root, None)),
+            vec!["A", "C"]
+        );
+
+        vault
+            .set_sort_order(
+                SortOrder::new(SortKey::Created, SortDirection::Ascending),
+                &mem_key,
+            )
+            .unwrap();
+        assert_eq!(
+            titles(&vault.get_item_list_for(&root, None)),
+            vec!["C", "A"]
+        );
+
+        vault
+            .set_sort_order(
+                SortOrder::new(SortKey::Title, SortDirection::Descending),
+                &mem_key,
+            )
+            .unwrap();
+        assert_eq!(
+            titles(&vault.get_item_list_for(&root, None)),
+            vec!["C", "A"]
+        );
+
+        // Favourites are pinned at the top of the root, but not inside of a search.
+        let mut favourite = vault.entries()[&[11_u8; uuid::SIZE].into()].clone();
+        let _ = favourite.set_favourite(true);
+        let _ = vault.entries.insert(favourite.uuid().clone(), favourite);
+        let mut favourite = vault.entries()[&[12_u8; uuid::SIZE].into()].clone();
+        let _ = favourite.set_favourite(true);
+        let _ = vault.entries.insert(favourite.uuid().clone(), favourite);
+
+        let item_list = vault.get_item_list_for(&root, None);
+        let favourites: Vec<&String> = item_list
+            .favourites()
+            .iter()
+            .map(|entry| entry.title())
+            .collect();
+        assert_eq!(favourites, vec!["B", "A"]);
+        assert_eq!(titles(&item_list), vec!["C"]);
+
+        let item_list = vault.get_item_list_for(&group, None);
+        assert!(item_list.favourites().is_empty());
+        assert_eq!(titles(&item_list), vec!["B"]);
+
+        let item_list = vault.get_item_list_for(&root, Some("A"));
+        assert!(item_list.favourites().is_empty());
+        assert_eq!(titles(&item_list), vec!["A"]);
+
+        // The sort order is remembered by the vault.
+        let loaded_vault = Vault::load(
+            PASSWORD,
+            Option::<String>::None,
+            &mem_key,
+            &path.join(VAULT_NAME),
+        )
+        .expect("Should not fail");
+        assert_eq!(
+            loaded_vault.settings().sort_order(),
+            SortOrder::new(SortKey::Title, SortDirection::Descending)
+        );
+    }
+
+    #[test]
+    fn record_usage() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+
+        for (index, title) in ["A", "B", "C"].iter().enumerate() {
+            let index = u8::try_from(index).unwrap();
+            vault
+                .insert_entry(
+                    EntryHead::new(
+                        [10 + index; uuid::SIZE].into(),
+                        root.clone(),
+                        (*title).into(),
+                        [20 + index; uuid::SIZE].into(),
+                    ),
+                    EntryBody::new(
+                        [20 + index; uuid::SIZE].into(),
+                        "username".into(),
+                        "password".into(),
+                    ),
+                    &master_key,
+                )
+                .unwrap();
+        }
+        vault.save(&mem_key).unwrap();
+
+        let a: Uuid = [10_u8; uuid::SIZE].into();
+        let b: Uuid = [11_u8; uuid::SIZE].into();
+        let stale_a = vault.entries()[&a].clone();
+
+        let clock = AtomicU64::new(1_000);
+        crate::model::timestamp
+            .mock_safe(move || MockResult::Return(clock.fetch_add(1, AtomicOrdering::SeqCst)));
+        assert!(vault.get_recently_used(RECENTLY_USED_LIMIT).is_empty());
+        vault.record_usage(&b, &master_key).unwrap();
+        vault.record_usage(&a, &master_key).unwrap();
+        vault.record_usage(&a, &master_key).unwrap();
+        assert!(vault
+            .record_usage(&[99_u8; uuid::SIZE].into(), &master_key)
+            .is_err());
+
+        // Using an entry does not create unsaved changes.
+        assert!(!vault.contains_unsaved_changes());
+        assert_eq!(vault.entries()[&a].usage_count(), 2);
+
+        let titles = |entries: Vec<&EntryHead>| -> Vec<String> {
+            entries.iter().map(|entry| entry.title().clone()).collect()
+        };
+        assert_eq!(
+            titles(vault.get_recently_used(RECENTLY_USED_LIMIT)),
+            vec!["A", "B"]
+        );
+        assert_eq!(titles(vault.get_recently_used(1)), vec!["A"]);
+        assert_eq!(
+            titles(vault.get_item_list_for(&root, None).recently_used().clone()),
+            vec!["A", "B"]
+        );
+        assert!(vault
+            .get_item_list_for(&root, Some("A"))
+            .recently_used()
+            .is_empty());
+
+        // The usage survives modifications based on an outdated state and their undo.
+        let mut modified_a = stale_a;
+        let _ = modified_a.set_title("Modified".into());
+        vault
+            .insert_entry(
+                modified_a,
+                EntryBody::new(
+                    [20_u8; uuid::SIZE].into(),
+                    "username".into(),
+                    "password".into(),
+                ),
+                &master_key,
+            )
+            .unwrap();
+        assert_eq!(vault.entries()[&a].usage_count(), 2);
+        assert!(vault.undo());
+        assert_eq!(vault.entries()[&a].title(), "A");
+        assert_eq!(vault.entries()[&a].usage_count(), 2);
+
+        // The usage is stored on disk.
+        let loaded_vault = Vault::load(
+            PASSWORD,
+            Option::<String>::None,
+            &mem_key,
+            &path.join(VAULT_NAME),
+        )
+        .expect("Should not fail");
+        assert_eq!(loaded_vault.entries()[&a].usage_count(), 2);
+        assert_eq!(loaded_vault.entries()[&b].usage_count(), 1);
+
+        // The stored usage is neither an external change nor a conflict of the next save.
+        vault.record_usage(&b, &master_key).unwrap();
+        assert!(vault
+            .reload_external_changes(&master_key, None)
+            .unwrap()
+            .is_empty());
+        let mut modified_b = vault.entries()[&b].clone();
+        let _ = modified_b.set_title("Modified".into());
+        vault
+            .insert_entry(
+                modified_b,
+                EntryBody::new(
+                    [21_u8; uuid::SIZE].into(),
+                    "username".into(),
+                    "password".into(),
+                ),
+                &master_key,
+            )
+            .unwrap();
+        vault.save(&mem_key).unwrap();
+        assert_eq!(vault.entries()[&b].usage_count(), 2);
+
+        // A usage of an entry changed on disk by another program does not overwrite the change.
+        let c: Uuid = [12_u8; uuid::SIZE].into();
+        let mut other = Vault::load(
+            PASSWORD,
+            Option::<String>::None,
+            &mem_key,
+            &path.join(VAULT_NAME),
+        )
+        .unwrap();
+        let mut other_c = other.entries()[&c].clone();
+        let body = other.load_entry_body(other_c.body(), &master_key).unwrap();
+        let _ = other_c.set_title("Other".into());
+        other.insert_entry(other_c, body, &master_key).unwrap();
+        other.save(&mem_key).unwrap();
+
+        vault.record_usage(&c, &master_key).unwrap();
+        assert!(vault.contains_unsaved_changes());
+        let on_disk = EntryHead::load_all(&vault.path, &master_key).unwrap();
+        assert_eq!(on_disk[&c].title(), "Other");
+        assert_eq!(on_disk[&c].usage_count(), 0);
+        assert!(matches!(
+            vault.save(&mem_key),
+            Err(PWDuckCoreError::Conflict(uuids)) if uuids == vec![c.clone()]
+        ));
+        let changes = vault.reload_external_changes(&master_key, None).unwrap();
+        assert_eq!(changes.conflicts().len(), 1);
+        assert_eq!(changes.conflicts()[0].uuid(), &c);
+    }
+
+    #[test]
+    fn import() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+
+        let import = Import::from_kdbx(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources/aes_kdf_aes256.kdbx"),
+            "password",
+        )
+        .unwrap();
+
+        assert!(vault
+            .import(&import, &[99_u8; uuid::SIZE].into(), &master_key)
+            .is_err());
+        vault.import(&import, &root, &master_key).unwrap();
+        assert!(vault.contains_unsaved_changes());
+
+        let entries = vault.get_entries_of(&root);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title(), "Mail & Calendar");
+        assert_eq!(entries[0].web_address(), "https://mail.example.com");
+        assert_eq!(entries[0].created(), 1_614_834_367);
+        let body = vault
+            .load_entry_body(entries[0].body(), &master_key)
+            .unwrap();
+        assert_eq!(body.username().as_str(), "alice");
+        assert_eq!(body.password().as_str(), "s3cr3t <pw>");
+        assert_eq!(body.email().as_str(), "alice@example.com");
+
+        let groups = vault.get_groups_of(&root);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].title(), "Servers");
+        let servers = groups[0].uuid().clone();
+        assert_eq!(vault.get_groups_of(&servers)[0].title(), "Empty");
+        assert_eq!(vault.get_entries_of(&servers)[0].title(), "Database");
+
+        // The whole import is undone and redone in one step.
+        assert!(vault.undo());
+        assert!(!vault.can_undo());
+        assert!(vault.get_groups_of(&root).is_empty());
+        assert!(vault.get_entries_of(&root).is_empty());
+        assert!(!vault.contains_unsaved_changes());
+        assert!(vault.redo());
+        assert_eq!(vault.get_entries_of(&root).len(), 1);
+        assert_eq!(vault.get_entries_of(&servers)[0].title(), "Database");
+
+        // A second import reuses the existing groups.
+        vault.import(&import, &root, &master_key).unwrap();
+        assert_eq!(vault.get_groups_of(&root).len(), 1);
+        assert_eq!(vault.get_groups_of(&servers).len(), 1);
+        assert_eq!(vault.get_entries_of(&servers).len(), 2);
+        assert!(vault.get_favourites().is_empty());
+
+        // Favourites are kept.
+        let import = Import::from_bitwarden(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources/bitwarden.json"),
+        )
+        .unwrap();
+        vault.import(&import, &root, &master_key).unwrap();
+        let favourites = vault.get_favourites();
+        assert_eq!(favourites.len(), 1);
+        assert_eq!(favourites[0].title(), "Mail & Calendar");
+
+        vault.save(&mem_key).unwrap();
+        assert!(!vault.contains_unsaved_changes());
+    }
+
+    #[test]
+    fn export() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+
+        let import = Import::from_kdbx(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources/aes_kdf_aes256.kdbx"),
+            "password",
+        )
+        .unwrap();
+        vault.import(&import, &root, &master_key).unwrap();
+
+        assert!(vault
+            .export(&[99_u8; uuid::SIZE].into(), &master_key)
+            .is_err());
+
+        let export = vault.export(&root, &master_key).unwrap();
+        assert_eq!(export.root().title(), VAULT_NAME);
+        assert_eq!(export.group_count(), 2);
+        assert_eq!(export.entry_count(), 2);
+        let entry = &export.root().entries()[0];
+        assert_eq!(entry.title(), "Mail & Calendar");
+        assert_eq!(entry.password().as_str(), "s3cr3t <pw>");
+        assert_eq!(entry.created(), 1_614_834_367);
+
+        let servers = vault.get_groups_of(&root)[0].uuid().clone();
+        let export = vault.export(&servers, &master_key).unwrap();
+        assert_eq!(export.root().title(), "Servers");
+        assert_eq!(export.group_count(), 1);
+        assert_eq!(export.entry_count(), 1);
+        assert_eq!(export.root().entries()[0].username().as_str(), "root");
+    }
+
+    #[test]
+    fn export_item_list() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+
+        let import = Import::from_kdbx(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources/aes_kdf_aes256.kdbx"),
+            "password",
+        )
+        .unwrap();
+        vault.import(&import, &root, &master_key).unwrap();
+
+        let item_list = vault.get_item_list_for(&root, Some("a"));
+        assert_eq!(item_list.entries().len(), 2);
+        let export = vault.export_item_list(&item_list, &master_key).unwrap();
+        assert_eq!(export.root().title(), VAULT_NAME);
+        assert_eq!(export.entry_count(), 2);
+
+        let item_list = vault.get_item_list_for(&root, Some("servers"));
+        let export = vault.export_item_list(&item_list, &master_key).unwrap();
+        assert!(export.root().entries().is_empty());
+        assert_eq!(export.root().groups()[0].title(), "Servers");
+        assert_eq!(export.group_count(), 2);
+        assert_eq!(export.entry_count(), 1);
+    }
+
+    #[test]
+    fn verify_password() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let vault = default_vault(&path, &mem_key);
+        assert!(vault.verify_password(PASSWORD, &mem_key).unwrap());
+        assert!(!vault.verify_password("wrong password", &mem_key).unwrap());
+        assert!(!vault.verify_password("", &mem_key).unwrap());
+    }
+
+    #[test]
+    fn backup() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+
+        let import = Import::from_kdbx(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources/aes_kdf_aes256.kdbx"),
+            "password",
+        )
+        .unwrap();
+        vault.import(&import, &root, &master_key).unwrap();
+        vault.save(&mem_key).unwrap();
+
+        let archive = path.join("vault.pwduck-backup");
+        vault.export_backup(&archive, &master_key).unwrap();
+        assert!(archive.exists());
+
+        let restored = path.join("Restored");
+        assert!(
+            Vault::restore_backup(&archive, "wrong password", None, &mem_key, &restored).is_err()
+        );
+        assert!(!restored.exists());
+        assert!(Vault::restore_backup(&archive, PASSWORD, None, &mem_key, &vault.path).is_err());
+
+        Vault::restore_backup(&archive, PASSWORD, None, &mem_key, &restored).unwrap();
+        let restored = Vault::load(PASSWORD, Option::<String>::None, &mem_key, restored).unwrap();
+        assert_eq!(restored.groups().len(), vault.groups().len());
+        assert_eq!(restored.entries().len(), vault.entries().len());
+        assert_eq!(restored.get_root_uuid(), vault.get_root_uuid());
+
+        let mut modified = std::fs::read(&archive).unwrap();
+        let last = modified.len() - 1;
+        modified[last] ^= 1;
+        std::fs::write(&archive, modified).unwrap();
+        assert!(
+            Vault::restore_backup(&archive, PASSWORD, None, &mem_key, &path.join("Modified"))
+                .is_err()
+        );
+        assert!(!path.join("Modified").exists());
+    }
+
+    #[test]
+    fn merge() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+        let base = vault.groups()[&root].last_modified() + 1000;
+
+        let new_entry = |vault: &Vault, title: &str, modified: u64| {
+            let body = EntryBody::new(Uuid::new(&vault.path), "user".into(), "secret".into());
+            let mut head = EntryHead::new(
+                Uuid::new(&vault.path),
+                root.clone(),
+                title.into(),
+                body.uuid().clone(),
+            );
+            let _ = head.set_timestamps(modified, modified);
+            (head, body)
+        };
+        let modify_entry = |vault: &mut Vault, uuid: &Uuid, title: &str, modified: u64| {
+            let mut head = vault.entries()[uuid].clone();
+            let body = EntryBody::load(&vault.path, head.body(), &master_key).unwrap();
+            let _ = head.set_title(title.into()).set_timestamps(0, modified);
+            vault.insert_entry(head, body, &master_key).unwrap();
+        };
+
+        let mut uuids = Vec::new();
+        for title in ["A", "B", "C"] {
+            let (head, body) = new_entry(&vault, title, base);
+            uuids.push(head.uuid().clone());
+            vault.insert_entry(head, body, &master_key).unwrap();
+        }
+        // The last change before the copies diverged.
+        let (head, body) = new_entry(&vault, "E", base + 50);
+        vault.insert_entry(head, body, &master_key).unwrap();
+        vault.save(&mem_key).unwrap();
+
+        let copy_path = path.join("Copy");
+        crate::io::save_vault_files(
+            &copy_path,
+            &crate::io::load_vault_files(&vault.path).unwrap(),
+        )
+        .unwrap();
+        let mut copy = Vault::load(PASSWORD, Option::<String>::None, &mem_key, &copy_path).unwrap();
+        modify_entry(&mut copy, &uuids[0], "A remote", base + 100);
+        modify_entry(&mut copy, &uuids[2], "C remote", base + 300);
+        let (head, body) = new_entry(&copy, "D", base + 100);
+        let added = head.uuid().clone();
+        copy.insert_entry(head, body, &master_key).unwrap();
+        copy.save(&mem_key).unwrap();
+
+        modify_entry(&mut vault, &uuids[1], "B local", base + 200);
+        modify_entry(&mut vault, &uuids[2], "C local", base + 200);
+        vault.save(&mem_key).unwrap();
+
+        assert!(vault
+            .prepare_merge(&path.join("Unknown"), &master_key)
+            .is_err());
+
+        let mut merge = vault.prepare_merge(&copy_path, &master_key).unwrap();
+        assert_eq!(merge.added(), 1);
+        assert_eq!(merge.updated(), 1);
+        assert_eq!(merge.conflicts().len(), 1);
+        assert_eq!(merge.conflicts()[0].uuid(), &uuids[2]);
+        assert_eq!(merge.conflicts()[0].local_title(), "C local");
+        assert_eq!(merge.conflicts()[0].remote_title(), "C remote");
+        assert_eq!(merge.conflicts()[0].winner(), MergeSide::Remote);
+        merge.set_winner(0, MergeSide::Local);
+
+        vault.merge(&merge, &master_key).unwrap();
+        assert!(vault.contains_unsaved_changes());
+        vault.save(&mem_key).unwrap();
+
+        let vault = Vault::load(PASSWORD, Option::<String>::None, &mem_key, &vault.path).unwrap();
+        assert_eq!(vault.entries()[&uuids[0]].title(), "A remote");
+        assert_eq!(vault.entries()[&uuids[0]].last_modified(), base + 100);
+        assert_eq!(vault.entries()[&uuids[1]].title(), "B local");
+        assert_eq!(vault.entries()[&uuids[2]].title(), "C local");
+        assert_eq!(vault.entries()[&added].title(), "D");
+        let body = EntryBody::load(&vault.path, vault.entries()[&added].body(), &master_key);
+        assert_eq!(body.unwrap().password().as_str(), "secret");
+
+        let merge = vault.prepare_merge(&copy_path, &master_key).unwrap();
+        assert_eq!(merge.conflicts().len(), 1);
+        assert!(!merge.is_empty());
+    }
+
+    #[test]
+    fn merge_changed_password() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+
+        let body = EntryBody::new(Uuid::new(&vault.path), "user".into(), "secret".into());
+        let head = EntryHead::new(
+            Uuid::new(&vault.path),
+            root,
+            "Entry".into(),
+            body.uuid().clone(),
+        );
+        let uuid = head.uuid().clone();
+        vault.insert_entry(head, body, &master_key).unwrap();
+        // An unchanged entry marks when the copies diverged.
+        let body = EntryBody::new(Uuid::new(&vault.path), "other".into(), "other".into());
+        let head = EntryHead::new(
+            Uuid::new(&vault.path),
+            vault.get_root_uuid().unwrap(),
+            "Other".into(),
+            body.uuid().clone(),
+        );
+        vault.insert_entry(head, body, &master_key).unwrap();
+        vault.save(&mem_key).unwrap();
+
+        let copy_path = path.join("Copy");
+        crate::io::save_vault_files(
+            &copy_path,
+            &crate::io::load_vault_files(&vault.path).unwrap(),
+        )
+        .unwrap();
+        let mut copy = Vault::load(PASSWORD, Option::<String>::None, &mem_key, &copy_path).unwrap();
+
+        let change_password = |vault: &mut Vault, password: &str, modified: u64| {
+            let head = vault.entries()[&uuid].clone();
+            let mut body = EntryBody::load(&vault.path, head.body(), &master_key).unwrap();
+            let _ = body.set_password(password.into());
+            vault.insert_entry(head, body, &master_key).unwrap();
+            let _ = vault
+                .entries
+                .get_mut(&uuid)
+                .unwrap()
+                .set_timestamps(0, modified);
+            vault.save(&mem_key).unwrap();
+        };
+        let password = |vault: &Vault| {
+            let body = vault
+                .load_entry_body(vault.entries()[&uuid].body(), &master_key)
+                .unwrap();
+            body.password().as_str().to_owned()
+        };
+
+        // Only the other copy changed the password.
+        let last_modified = vault.entries()[&uuid].last_modified();
+        change_password(&mut copy, "remote secret", last_modified + 100);
+        let merge = vault.prepare_merge(&copy_path, &master_key).unwrap();
+        assert_eq!(merge.updated(), 1);
+        assert!(merge.conflicts().is_empty());
+        vault.merge(&merge, &master_key).unwrap();
+        assert_eq!(password(&vault), "remote secret");
+        vault.save(&mem_key).unwrap();
+
+        // A password changed without a new time of the last modification is a conflict.
+        let last_modified = vault.entries()[&uuid].last_modified();
+        change_password(&mut copy, "older client", last_modified);
+        let merge = vault.prepare_merge(&copy_path, &master_key).unwrap();
+        assert_eq!(merge.updated(), 0);
+        assert_eq!(merge.conflicts().len(), 1);
+        assert_eq!(merge.conflicts()[0].uuid(), &uuid);
+        assert_eq!(merge.conflicts()[0].winner(), MergeSide::Local);
+    }
+
+    #[test]
+    fn reload_external_changes() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+        let base = vault.groups()[&root].last_modified();
+
+        let modify_entry = |vault: &mut Vault, uuid: &Uuid, title: &str, modified: u64| {
+            let mut head = vault.entries()[uuid].clone();
+            let body = EntryBody::load(&vault.path, head.body(), &master_key).unwrap();
+            let _ = head.set_title(title.into()).set_timestamps(0, modified);
+            vault.insert_entry(head, body, &master_key).unwrap();
+        };
+
+        let mut uuids = Vec::new();
+        for title in ["A", "B", "C"] {
+            let body = EntryBody::new(Uuid::new(&vault.path), "user".into(), "secret".into());
+            let head = EntryHead::new(
+                Uuid::new(&vault.path),
+                root.clone(),
+                title.into(),
+                body.uuid().clone(),
+            );
+            uuids.push(head.uuid().clone());
+            vault.insert_entry(head, body, &master_key).unwrap();
+        }
+        let group = Group::new(Uuid::new(&vault.path), root.clone(), "Group".into());
+        let group_uuid = group.uuid().clone();
+        vault.insert_group(group);
+        vault.save(&mem_key).unwrap();
+
+        // Nothing changed yet, the own changes are known.
+        let changes = vault.reload_external_changes(&master_key, None).unwrap();
+        assert!(changes.is_empty());
+
+        // Another program changes the vault on disk.
+        let mut other =
+            Vault::load(PASSWORD, Option::<String>::None, &mem_key, &vault.path).unwrap();
+        modify_entry(&mut other, &uuids[0], "A changed", base + 100);
+        other.delete_entry(&uuids[1]).unwrap();
+        other.delete_group(&group_uuid);
+        let group = Group::new(Uuid::new(&other.path), root.clone(), "New group".into());
+        let new_group = group.uuid().clone();
+        other.insert_group(group);
+        other.save(&mem_key).unwrap();
+
+        let changes = vault.reload_external_changes(&master_key, None).unwrap();
+        assert_eq!(changes.reloaded(), 2);
+        assert_eq!(changes.removed(), 2);
+        assert!(changes.conflicts().is_empty());
+        assert!(!vault.contains_unsaved_changes());
+        assert_eq!(vault.entries()[&uuids[0]].title(), "A changed");
+        assert!(!vault.entries().contains_key(&uuids[1]));
+        assert!(!vault.groups().contains_key(&group_uuid));
+        let mut groups: Vec<&str> = vault
+            .get_groups_of(&root)
+            .iter()
+            .map(|group| group.title().as_str())
+            .collect();
+        groups.sort_unstable();
+        assert_eq!(groups, ["New group"]);
+        assert_eq!(vault.get_entries_of(&root).len(), 2);
+        assert!(vault.groups().contains_key(&new_group));
+
+        // Changed on disk while it contains unsaved changes.
+        modify_entry(&mut vault, &uuids[2], "C local", base + 200);
+        modify_entry(&mut other, &uuids[2], "C other", base + 300);
+        other.save(&mem_key).unwrap();
+
+        let changes = vault.reload_external_changes(&master_key, None).unwrap();
+        assert_eq!(changes.conflicts().len(), 1);
+        assert_eq!(changes.conflicts()[0].uuid(), &uuids[2]);
+        assert_eq!(changes.conflicts()[0].local_title(), "C local");
+        assert_eq!(changes.conflicts()[0].remote_title(), "C other");
+        assert_eq!(vault.entries()[&uuids[2]].title(), "C local");
+
+        // Keep the unsaved changes.
+        let changes = vault
+            .reload_external_changes(&master_key, Some(MergeSide::Local))
+            .unwrap();
+        assert!(changes.conflicts().is_empty());
+        assert_eq!(vault.entries()[&uuids[2]].title(), "C local");
+        assert!(vault
+            .reload_external_changes(&master_key, None)
+            .unwrap()
+            .is_empty());
+
+        // Discard the unsaved changes, they can't be restored by undo.
+        modify_entry(&mut other, &uuids[2], "C other again", base + 400);
+        other.save(&mem_key).unwrap();
+        assert!(vault.can_undo());
+        let changes = vault
+            .reload_external_changes(&master_key, Some(MergeSide::Remote))
+            .unwrap();
+        assert_eq!(changes.reloaded(), 1);
+        assert_eq!(vault.entries()[&uuids[2]].title(), "C other again");
+        assert!(!vault.contains_unsaved_changes());
+        assert!(!vault.can_undo());
+        assert!(!vault.undo());
+        assert_eq!(vault.entries()[&uuids[2]].title(), "C other again");
+
+        // Only the body of an entry is changed on disk.
+        let head = other.entries()[&uuids[0]].clone();
+        let mut body = EntryBody::load(&other.path, head.body(), &master_key).unwrap();
+        let _ = body.set_password("changed on disk".into());
+        other.insert_entry(head, body, &master_key).unwrap();
+        other.save(&mem_key).unwrap();
+        let changes = vault.reload_external_changes(&master_key, None).unwrap();
+        assert_eq!(changes.reloaded(), 1);
+        let body = vault
+            .load_entry_body(vault.entries()[&uuids[0]].body(), &master_key)
+            .unwrap();
+        assert_eq!(body.password().as_str(), "changed on disk");
+
+        // Saved searches and settings are changed on disk.
+        let saved_search = SavedSearch::new(Uuid::new(&other.path), "Mail".into(), "mail".into());
+        let saved_search_uuid = saved_search.uuid().clone();
+        other.insert_saved_search(saved_search);
+        other.save(&mem_key).unwrap();
+        other
+            .set_sort_order(
+                SortOrder::new(SortKey::Created, SortDirection::Descending),
+                &mem_key,
+            )
+            .unwrap();
+        let changes = vault.reload_external_changes(&master_key, None).unwrap();
+        assert_eq!(changes.reloaded(), 2);
+        assert_eq!(vault.saved_searches()[&saved_search_uuid].title(), "Mail");
+        assert_eq!(
+            vault.settings().sort_order(),
+            SortOrder::new(SortKey::Created, SortDirection::Descending)
+        );
+        assert!(vault
+            .reload_external_changes(&master_key, None)
+            .unwrap()
+            .is_empty());
+
+        // A saved search with unsaved changes is kept.
+        let mut saved_search = vault.saved_searches()[&saved_search_uuid].clone();
+        let _ = saved_search.set_query("mail local".into());
+        vault.insert_saved_search(saved_search);
+        other.delete_saved_search(&saved_search_uuid);
+        other.save(&mem_key).unwrap();
+        let changes = vault.reload_external_changes(&master_key, None).unwrap();
+        assert!(changes.is_empty());
+        assert_eq!(
+            vault.saved_searches()[&saved_search_uuid].query(),
+            "mail local"
+        );
+    }
+
+    #[test]
+    fn save_conflict() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let mem_key = default_mem_key();
+
+        let mut vault = default_vault(&path, &mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key
+            .as_unprotected(&mem_key, &vault.salt, &vault.nonce)
+            .unwrap();
+
+        let rename_entry = |vault: &mut Vault, uuid: &Uuid, title: &str| {
+            let mut head = vault.entries()[uuid].clone();
+            let body = EntryBody::load(&vault.path, head.body(), &master_key).unwrap();
+            let _ = head.set_title(title.into());
+            vault.insert_entry(head, body, &master_key).unwrap();
+        };
+
+        let body = EntryBody::new(Uuid::new(&vault.path), "user".into(), "secret".into());
+        let head = EntryHead::new(
+            Uuid::new(&vault.path),
+            root.clone(),
+            "Entry".into(),
+            body.uuid().clone(),
+        );
+        let uuid = head.uuid().clone();
+        let body_uuid = body.uuid().clone();
+        vault.insert_entry(head, body, &master_key).unwrap();
+        vault.save(&mem_key).unwrap();
+
+        // Another program changes the entry on disk.
+        let mut other =
+            Vault::load(PASSWORD, Option::<String>::None, &mem_key, &vault.path).unwrap();
+        rename_entry(&mut other, &uuid, "Other");
+        other.save(&mem_key).unwrap();
+
+        // Unrelated changes can still be saved.
+        let group = Group::new(Uuid::new(&vault.path), root.clone(), "Group".into());
+        vault.insert_group(group);
+        vault.save(&mem_key).unwrap();
+
+        // The changed entry is not overwritten.
+        rename_entry(&mut vault, &uuid, "Local");
+        assert!(matches!(
+            vault.save(&mem_key),
+            Err(PWDuckCoreError::Conflict(uuids)) if uuids == vec![uuid.clone()]
+        ));
+        assert!(vault.contains_unsaved_changes());
+        let loaded = Vault::load(PASSWORD, Option::<String>::None, &mem_key, &vault.path).unwrap();
+        assert_eq!(loaded.entries()[&uuid].title(), "Other");
+
+        // Overwrite the changes on disk.
+        vault.save_overwriting(&mem_key).unwrap();
+        let loaded = Vault::load(PASSWORD, Option::<String>::None, &mem_key, &vault.path).unwrap();
+        assert_eq!(loaded.entries()[&uuid].title(), "Local");
+
+        // A body changed on disk is not overwritten either.
+        let mut changed_body = EntryBody::load(&vault.path, &body_uuid, &master_key).unwrap();
+        let _ = changed_body.set_password("changed on disk".into());
+        crate::io::save_entry_body(
+            &vault.path,
+            &body_uuid,
+            &changed_body.encrypt(&master_key).unwrap(),
+        )
+        .unwrap();
+        rename_entry(&mut vault, &uuid, "Local again");
+        assert!(matches!(
+            vault.save(&mem_key),
+            Err(PWDuckCoreError::Conflict(uuids)) if uuids == vec![uuid.clone()]
+        ));
+        vault.save_overwriting(&mem_key).unwrap();
+
+        // Deleting an entry changed on disk is refused as well.
+        rename_entry(&mut other, &uuid, "Other again");
+        assert!(matches!(
+            other.save(&mem_key),
+            Err(PWDuckCoreError::Conflict(_))
+        ));
+        other.save_overwriting(&mem_key).unwrap();
+        vault.delete_entry(&uuid).unwrap();
+        assert!(matches!(
+            vault.save(&mem_key),
+            Err(PWDuckCoreError::Conflict(_))
+        ));
+
+        // Keeping the local changes after a reload allows to save them.
+        let _ = vault
+            .reload_external_changes(&master_key, Some(MergeSide::Local))
+            .unwrap();
+        vault.save(&mem_key).unwrap();
+        assert!(!vault
+            .path
+            .join(ENTRIES_DIR)
+            .join(HEAD)
+            .join(uuid.base64_hash())
+            .exists());
+
+        // Merging the changes on disk allows to save the merged changes.
+        let group = Group::new(Uuid::new(&other.path), root.clone(), "Other group".into());
+        let group_uuid = group.uuid().clone();
+        other.insert_group(group);
+        other.save(&mem_key).unwrap();
+        let group = Group::new(group_uuid.clone(), root, "Local group".into());
+        vault.insert_group(group);
+        assert!(matches!(
+            vault.save(&mem_key),
+            Err(PWDuckCoreError::Conflict(_))
+        ));
+        let merge = vault.prepare_merge(&vault.path, &master_key).unwrap();
+        vault.merge(&merge, &master_key).unwrap();
+        vault.save(&mem_key).unwrap();
     }
 }
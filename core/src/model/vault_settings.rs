@@ -0,0 +1,331 @@
+//! The settings of a single vault.
+//!
+//! In contrast to the [`ApplicationSettings`](crate::ApplicationSettings), these
+//! settings are stored encrypted inside of the [`Vault`](crate::Vault).
+use std::{cmp::Ordering, fmt::Display, path::Path};
+
+use getset::{CopyGetters, Setters};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{
+    cryptography::{aes_cbc_decrypt, aes_cbc_encrypt, generate_aes_iv},
+    error::PWDuckCoreError,
+    mem_protection::SecString,
+    EntryHead, Group,
+};
+
+/// The settings of a [`Vault`](crate::Vault).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, CopyGetters, Setters)]
+pub struct VaultSettings {
+    /// The order of the groups and entries in the list view.
+    #[getset(get_copy = "pub", set = "pub")]
+    #[serde(default)]
+    sort_order: SortOrder,
+}
+
+impl VaultSettings {
+    /// Save the [`VaultSettings`](VaultSettings) to disk.
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the [`Vault`](crate::Vault)
+    ///  - The master key to encrypt the settings
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - The serialization of the [`VaultSettings`](VaultSettings) fails.
+    /// - The [`VaultSettings`](VaultSettings) can't be encrypted.
+    /// - Writing the encrypted [`VaultSettings`](VaultSettings) to disk failed.
+    pub fn save(&self, path: &Path, master_key: &[u8]) -> Result<(), PWDuckCoreError> {
+        let vault_settings = self.encrypt(master_key)?;
+        crate::io::save_vault_settings(path, &vault_settings)
+    }
+
+    /// Encrypt these [`VaultSettings`](VaultSettings) with the given master key.
+    fn encrypt(
+        &self,
+        master_key: &[u8],
+    ) -> Result<crate::dto::vault_settings::VaultSettings, PWDuckCoreError> {
+        let iv = generate_aes_iv();
+        let mut content = ron::to_string(self)?;
+        let encrypted_content = aes_cbc_encrypt(content.as_bytes(), master_key, &iv)?;
+        content.zeroize();
+        Ok(crate::dto::vault_settings::VaultSettings::new(
+            base64::encode(iv),
+            base64::encode(encrypted_content),
+        ))
+    }
+
+    /// Load the [`VaultSettings`](VaultSettings) from disk.
+    /// If the vault does not contain any settings, the default settings are returned.
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the [`Vault`](crate::Vault)
+    ///  - The master key to decrypt the settings
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    /// - Reading the encrypted [`VaultSettings`](VaultSettings) fails.
+    /// - The de-serialization of the [`VaultSettings`](VaultSettings) fails.
+    /// - The [`VaultSettings`](VaultSettings) can't be decrypted.
+    /// - The base64 encoded data can't be decoded.
+    pub fn load(path: &Path, master_key: &[u8]) -> Result<Self, PWDuckCoreError> {
+        crate::io::load_vault_settings(path)?.map_or_else(
+            || Ok(Self::default()),
+            |dto| Self::decrypt(&dto, master_key),
+        )
+    }
+
+    /// Decrypt the data-transfer-object (dto) of the [`VaultSettings`](VaultSettings) with the given master key.
+    fn decrypt(
+        dto: &crate::dto::vault_settings::VaultSettings,
+        master_key: &[u8],
+    ) -> Result<Self, PWDuckCoreError> {
+        let decrypted_content = aes_cbc_decrypt(
+            &base64::decode(dto.content())?,
+            master_key,
+            &base64::decode(dto.iv())?,
+        )?;
+
+        let content = SecString::from_utf8(decrypted_content)?;
+        let vault_settings = ron::from_str(&content)?;
+
+        Ok(vault_settings)
+    }
+}
+
+/// The order of the [`Group`](Group)s and [`EntryHead`](EntryHead)s in the list view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, CopyGetters)]
+pub struct SortOrder {
+    /// The property to sort by.
+    #[getset(get_copy = "pub")]
+    key: SortKey,
+
+    /// The direction to sort in.
+    #[getset(get_copy = "pub")]
+    direction: SortDirection,
+}
+
+impl SortOrder {
+    /// Create a new [`SortOrder`](SortOrder).
+    #[must_use]
+    pub const fn new(key: SortKey, direction: SortDirection) -> Self {
+        Self { key, direction }
+    }
+
+    /// Compare two [`Group`](Group)s by this order.
+    ///
+    /// Groups are never used, so they are sorted by their titles if sorted by usage.
+    #[must_use]
+    pub fn compare_groups(self, a: &Group, b: &Group) -> Ordering {
+        let ordering = match self.key {
+            SortKey::Created => a.created().cmp(&b.created()),
+            SortKey::LastModified => a.last_modified().cmp(&b.last_modified()),
+            SortKey::Title | SortKey::LastUsed | SortKey::UsageCount => Ordering::Equal,
+        }
+        .then_with(|| a.title().cmp(b.title()));
+
+        self.direction.apply(ordering)
+    }
+
+    /// Compare two [`EntryHead`](EntryHead)s by this order.
+    #[must_use]
+    pub fn compare_entries(self, a: &EntryHead, b: &EntryHead) -> Ordering {
+        let ordering = match self.key {
+            SortKey::Title => Ordering::Equal,
+            SortKey::Created => a.created().cmp(&b.created()),
+            SortKey::LastModified => a.last_modified().cmp(&b.last_modified()),
+            SortKey::LastUsed => a.last_used().cmp(&b.last_used()),
+            SortKey::UsageCount => a.usage_count().cmp(&b.usage_count()),
+        }
+        .then_with(|| a.title().cmp(b.title()));
+
+        self.direction.apply(ordering)
+    }
+}
+
+/// The property to sort the [`Group`](Group)s and [`EntryHead`](EntryHead)s by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SortKey {
+    /// Sort by the title.
+    Title,
+    /// Sort by the creation date.
+    Created,
+    /// Sort by the date of the last modification.
+    LastModified,
+    /// Sort by the date of the last usage.
+    LastUsed,
+    /// Sort by the number of usages.
+    UsageCount,
+}
+
+impl SortKey {
+    /// All available [`SortKey`](SortKey)s.
+    pub const ALL: [Self; 5] = [
+        Self::Title,
+        Self::Created,
+        Self::LastModified,
+        Self::LastUsed,
+        Self::UsageCount,
+    ];
+
+    /// The human readable name of this [`SortKey`](SortKey).
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Title => "Title",
+            Self::Created => "Creation date",
+            Self::LastModified => "Last modified",
+            Self::LastUsed => "Last used",
+            Self::UsageCount => "Usage count",
+        }
+    }
+
+    /// Returns the next [`SortKey`](SortKey). The last one is followed by the first one.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Title => Self::Created,
+            Self::Created => Self::LastModified,
+            Self::LastModified => Self::LastUsed,
+            Self::LastUsed => Self::UsageCount,
+            Self::UsageCount => Self::Title,
+        }
+    }
+}
+
+impl Default for SortKey {
+    fn default() -> Self {
+        Self::Title
+    }
+}
+
+impl Display for SortKey {
+    #[cfg_attr(coverage, no_coverage)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The direction to sort the [`Group`](Group)s and [`EntryHead`](EntryHead)s in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SortDirection {
+    /// Sort in ascending order.
+    Ascending,
+    /// Sort in descending order.
+    Descending,
+}
+
+impl SortDirection {
+    /// Apply this direction to the given ascending [`Ordering`](Ordering).
+    #[must_use]
+    pub const fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            Self::Ascending => ordering,
+            Self::Descending => ordering.reverse(),
+        }
+    }
+
+    /// Returns the opposite direction.
+    #[must_use]
+    pub const fn toggle(self) -> Self {
+        match self {
+            Self::Ascending => Self::Descending,
+            Self::Descending => Self::Ascending,
+        }
+    }
+}
+
+impl Default for SortDirection {
+    fn default() -> Self {
+        Self::Ascending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use mocktopus::mocking::*;
+    use tempfile::tempdir;
+
+    use crate::{cryptography, io::create_new_vault_dir, model::uuid, EntryHead};
+
+    use super::{SortDirection, SortKey, SortOrder, VaultSettings};
+
+    #[test]
+    fn save_and_load_vault_settings() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        create_new_vault_dir(&path).unwrap();
+
+        let master_key = [21_u8; cryptography::MASTER_KEY_SIZE];
+
+        let loaded = VaultSettings::load(&path, &master_key)
+            .expect("Loading missing vault settings should not fail.");
+        assert_eq!(loaded.sort_order(), SortOrder::default());
+
+        let mut vault_settings = VaultSettings::default();
+        let _ = vault_settings.set_sort_order(SortOrder::new(
+            SortKey::UsageCount,
+            SortDirection::Descending,
+        ));
+        vault_settings
+            .save(&path, &master_key)
+            .expect("Saving vault settings should not fail.");
+
+        let loaded = VaultSettings::load(&path, &master_key)
+            .expect("Loading vault settings should not fail.");
+        assert_eq!(loaded.sort_order(), vault_settings.sort_order());
+    }
+
+    #[test]
+    fn compare_entries() {
+        crate::model::timestamp.mock_safe(|| MockResult::Return(2_000));
+        let a = EntryHead::new(
+            [1_u8; uuid::SIZE].into(),
+            [0_u8; uuid::SIZE].into(),
+            "A".into(),
+            [2_u8; uuid::SIZE].into(),
+        );
+        crate::model::timestamp.mock_safe(|| MockResult::Return(1_000));
+        let b = EntryHead::new(
+            [3_u8; uuid::SIZE].into(),
+            [0_u8; uuid::SIZE].into(),
+            "B".into(),
+            [4_u8; uuid::SIZE].into(),
+        );
+
+        let by_title = SortOrder::new(SortKey::Title, SortDirection::Ascending);
+        assert_eq!(by_title.compare_entries(&a, &b), Ordering::Less);
+        let by_title = SortOrder::new(SortKey::Title, SortDirection::Descending);
+        assert_eq!(by_title.compare_entries(&a, &b), Ordering::Greater);
+
+        // Equal usage counts fall back to the title.
+        let by_usage = SortOrder::new(SortKey::UsageCount, SortDirection::Ascending);
+        assert_eq!(by_usage.compare_entries(&a, &b), Ordering::Less);
+
+        let by_creation = SortOrder::new(SortKey::Created, SortDirection::Ascending);
+        assert_eq!(by_creation.compare_entries(&a, &b), Ordering::Greater);
+        let by_creation = SortOrder::new(SortKey::Created, SortDirection::Descending);
+        assert_eq!(by_creation.compare_entries(&a, &b), Ordering::Less);
+    }
+
+    #[test]
+    fn next_key() {
+        let mut key = SortKey::Title;
+        for expected in SortKey::ALL.iter().skip(1) {
+            key = key.next();
+            assert_eq!(key, *expected);
+        }
+        assert_eq!(key.next(), SortKey::Title);
+    }
+
+    #[test]
+    fn toggle_direction() {
+        assert_eq!(SortDirection::Ascending.toggle(), SortDirection::Descending);
+        assert_eq!(SortDirection::Descending.toggle(), SortDirection::Ascending);
+    }
+}
@@ -0,0 +1,250 @@
+//! This module contains the references to the fields of other entries.
+//!
+//! A reference has the form `{REF:<field>@<uuid>}` and is resolved to the
+//! current value of the referenced field whenever the value is used.
+use std::{fmt::Display, str::FromStr};
+
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ReferenceError, PWDuckCoreError, SecString, Uuid, Vault};
+
+/// The prefix of a reference.
+pub const REFERENCE_PREFIX: &str = "{REF:";
+
+/// The suffix of a reference.
+pub const REFERENCE_SUFFIX: &str = "}";
+
+/// The maximum depth of nested references. Deeper references are considered cyclic.
+pub const MAX_REFERENCE_DEPTH: usize = 8;
+
+/// The field of an entry that can be referenced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceField {
+    /// The title of the entry.
+    Title,
+    /// The username of the entry.
+    Username,
+    /// The password of the entry.
+    Password,
+    /// The email of the entry.
+    Email,
+}
+
+impl ReferenceField {
+    /// The name of the field used inside of a reference.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Username => "username",
+            Self::Password => "password",
+            Self::Email => "email",
+        }
+    }
+}
+
+impl FromStr for ReferenceField {
+    type Err = PWDuckCoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(Self::Title),
+            "username" => Ok(Self::Username),
+            "password" => Ok(Self::Password),
+            "email" => Ok(Self::Email),
+            _ => Err(PWDuckCoreError::Error(format!(
+                "Not a valid reference field: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// A reference to the field of another entry.
+#[derive(Clone, Debug, PartialEq, Eq, Getters)]
+pub struct FieldReference {
+    /// The referenced field.
+    #[getset(get = "pub")]
+    field: ReferenceField,
+
+    /// The UUID of the [`EntryHead`](crate::EntryHead) of the referenced entry.
+    #[getset(get = "pub")]
+    target: Uuid,
+}
+
+impl FieldReference {
+    /// Create a new [`FieldReference`](FieldReference).
+    #[must_use]
+    pub const fn new(field: ReferenceField, target: Uuid) -> Self {
+        Self { field, target }
+    }
+
+    /// Parse a [`FieldReference`](FieldReference) from the given value.
+    /// The value must consist of exactly one reference.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let inner = value
+            .strip_prefix(REFERENCE_PREFIX)?
+            .strip_suffix(REFERENCE_SUFFIX)?;
+        let (field, target) = inner.split_once('@')?;
+
+        Some(Self {
+            field: field.parse().ok()?,
+            target: target.parse().ok()?,
+        })
+    }
+
+    /// Find all [`FieldReference`](FieldReference)s contained in the given value.
+    #[must_use]
+    pub fn find_all(value: &str) -> Vec<Self> {
+        Self::locate(value)
+            .into_iter()
+            .map(|(_, _, reference)| reference)
+            .collect()
+    }
+
+    /// Locate all [`FieldReference`](FieldReference)s contained in the given value.
+    /// Returns the start and the end of each reference in the value.
+    fn locate(value: &str) -> Vec<(usize, usize, Self)> {
+        let mut references = Vec::new();
+        let mut offset = 0;
+
+        while let Some(start) = value[offset..].find(REFERENCE_PREFIX).map(|i| i + offset) {
+            let end = match value[start..].find(REFERENCE_SUFFIX) {
+                Some(end) => start + end + REFERENCE_SUFFIX.len(),
+                None => break,
+            };
+
+            if let Some(reference) = Self::parse(&value[start..end]) {
+                references.push((start, end, reference));
+                offset = end;
+            } else {
+                offset = start + REFERENCE_PREFIX.len();
+            }
+        }
+
+        references
+    }
+}
+
+impl Display for FieldReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}@{}{}",
+            REFERENCE_PREFIX,
+            self.field.name(),
+            self.target,
+            REFERENCE_SUFFIX
+        )
+    }
+}
+
+/// Replace all [`FieldReference`](FieldReference)s in the given value by the values of the referenced fields.
+///
+/// It expects:
+///  - The value that may contain references
+///  - The [`Vault`](Vault) containing the referenced entries
+///  - The master key to decrypt the referenced entries
+///  - The current depth of the nested references
+///
+/// # Errors
+///
+/// Returns `Err` if:
+/// - A referenced entry does not exist.
+/// - The references are cyclic or nested deeper than [`MAX_REFERENCE_DEPTH`](MAX_REFERENCE_DEPTH).
+/// - A referenced entry can't be loaded.
+pub fn resolve(
+    value: &str,
+    vault: &Vault,
+    master_key: &[u8],
+    depth: usize,
+) -> Result<SecString, PWDuckCoreError> {
+    let references = FieldReference::locate(value);
+    if references.is_empty() {
+        return Ok(value.into());
+    }
+    if depth >= MAX_REFERENCE_DEPTH {
+        return Err(ReferenceError::TooDeep.into());
+    }
+
+    let mut resolved = SecString::new();
+    let mut last = 0;
+    for (start, end, reference) in references {
+        resolved.push_str(&value[last..start]);
+
+        let head = vault
+            .entries()
+            .get(reference.target())
+            .ok_or_else(|| ReferenceError::MissingTarget(reference.target().to_string()))?;
+
+        let field = match reference.field() {
+            ReferenceField::Title => SecString::from(head.title().as_str()),
+            ReferenceField::Username => vault
+                .load_entry_body(head.body(), master_key)?
+                .username()
+                .clone(),
+            ReferenceField::Password => vault
+                .load_entry_body(head.body(), master_key)?
+                .password()
+                .clone(),
+            ReferenceField::Email => vault
+                .load_entry_body(head.body(), master_key)?
+                .email()
+                .clone(),
+        };
+        resolved.push_str(&resolve(&field, vault, master_key, depth + 1)?);
+
+        last = end;
+    }
+    resolved.push_str(&value[last..]);
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{model::uuid, Uuid};
+
+    use super::{FieldReference, ReferenceField};
+
+    fn target() -> Uuid {
+        [0xab_u8; uuid::SIZE].into()
+    }
+
+    #[test]
+    fn display_and_parse() {
+        let reference = FieldReference::new(ReferenceField::Password, target());
+        let text = reference.to_string();
+
+        assert_eq!(text, "{REF:password@abababab-abab-abab-abab-abababababab}");
+        assert_eq!(FieldReference::parse(&text), Some(reference));
+
+        assert!(FieldReference::parse("{REF:password}").is_none());
+        assert!(FieldReference::parse("{REF:pin@abababab-abab-abab-abab-abababababab}").is_none());
+        assert!(FieldReference::parse("{REF:password@abab}").is_none());
+        assert!(FieldReference::parse("password").is_none());
+    }
+
+    #[test]
+    fn find_all() {
+        let value = format!(
+            "prefix {}{{REF:}}{} {{REF:username@nope}} suffix {{REF:",
+            FieldReference::new(ReferenceField::Username, target()),
+            FieldReference::new(ReferenceField::Email, target()),
+        );
+
+        let references = FieldReference::find_all(&value);
+
+        assert_eq!(
+            references,
+            vec![
+                FieldReference::new(ReferenceField::Username, target()),
+                FieldReference::new(ReferenceField::Email, target()),
+            ]
+        );
+        assert!(FieldReference::find_all("no references").is_empty());
+    }
+}
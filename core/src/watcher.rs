@@ -0,0 +1,190 @@
+//! Watch the directory of an open vault for changes made by other programs, e.g. a sync client.
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{
+    io::{ENTRIES_DIR, GROUPS_DIR, SAVED_SEARCHES_DIR, VAULT_SETTINGS_NAME},
+    PWDuckCoreError,
+};
+
+/// The delay to collect the events of a change, e.g. all files written by a sync client.
+const DEBOUNCE_DELAY: Duration = Duration::from_secs(1);
+
+/// Watch the groups, entries, saved searches and settings of a vault on disk.
+///
+/// Changes are also reported for files written by the vault itself.
+/// The vault must compare its items with the files to ignore them.
+pub struct VaultWatcher {
+    /// The watcher of the file system. It stops watching when dropped.
+    _watcher: RecommendedWatcher,
+    /// The receiver of the events of the watcher.
+    receiver: Receiver<DebouncedEvent>,
+    /// The location of the vault as reported in the events.
+    path: PathBuf,
+}
+
+impl VaultWatcher {
+    /// Start watching the [`Vault`](crate::Vault).
+    ///
+    /// It expects:
+    ///  - The [`Path`](Path) as the location of the [`Vault`](crate::Vault)
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the directory of the [`Vault`](crate::Vault) can't be watched.
+    pub fn new(path: &Path) -> Result<Self, PWDuckCoreError> {
+        let path = path.canonicalize()?;
+        let (sender, receiver) = channel();
+        let mut watcher = notify::watcher(sender, DEBOUNCE_DELAY)?;
+        // The whole directory is watched, because the saved searches may be created later.
+        watcher.watch(&path, RecursiveMode::Recursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver,
+            path,
+        })
+    }
+
+    /// Wait until groups, entries, saved searches or the settings were created, changed or removed on disk.
+    /// All changes collected so far are consumed.
+    ///
+    /// Returns `false` if nothing changed within the timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the watcher failed.
+    pub fn wait_for_change(&self, timeout: Duration) -> Result<bool, PWDuckCoreError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            // Notices are send immediately, the change itself after the debounce delay.
+            let event = match self.receiver.recv_timeout(remaining) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => return Ok(false),
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(PWDuckCoreError::Error(
+                        "The watcher of the vault stopped".into(),
+                    ))
+                }
+            };
+            if self.is_change(event)? {
+                for event in self.receiver.try_iter() {
+                    let _ = self.is_change(event)?;
+                }
+                return Ok(true);
+            }
+        }
+    }
+
+    /// True, if the event reports a created, changed or removed file of the vault.
+    fn is_change(&self, event: DebouncedEvent) -> Result<bool, PWDuckCoreError> {
+        match event {
+            DebouncedEvent::Create(path)
+            | DebouncedEvent::Write(path)
+            | DebouncedEvent::Remove(path) => Ok(self.is_vault_file(&path)),
+            DebouncedEvent::Rename(from, to) => {
+                Ok(self.is_vault_file(&from) || self.is_vault_file(&to))
+            }
+            DebouncedEvent::Rescan => Ok(true),
+            DebouncedEvent::NoticeWrite(_)
+            | DebouncedEvent::NoticeRemove(_)
+            | DebouncedEvent::Chmod(_) => Ok(false),
+            DebouncedEvent::Error(error, _path) => Err(error.into()),
+        }
+    }
+
+    /// True, if the file contains groups, entries, saved searches or the settings of the vault.
+    fn is_vault_file(&self, path: &Path) -> bool {
+        path.strip_prefix(&self.path).map_or(false, |relative| {
+            relative == Path::new(VAULT_SETTINGS_NAME)
+                || [GROUPS_DIR, ENTRIES_DIR, SAVED_SEARCHES_DIR]
+                    .iter()
+                    .any(|directory| relative.starts_with(directory))
+        })
+    }
+}
+
+impl Debug for VaultWatcher {
+    #[cfg_attr(coverage, no_coverage)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultWatcher").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use tempfile::tempdir;
+
+    use crate::io::{
+        create_new_vault_dir, BODY, ENTRIES_DIR, GROUPS_DIR, HEAD, MASTER_KEY_NAME,
+        SAVED_SEARCHES_DIR, VAULT_SETTINGS_NAME,
+    };
+
+    use super::VaultWatcher;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn wait_for_change() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("Vault");
+        create_new_vault_dir(&path).unwrap();
+        let watcher = VaultWatcher::new(&path).unwrap();
+
+        assert!(!watcher.wait_for_change(Duration::from_millis(100)).unwrap());
+
+        fs::write(path.join(GROUPS_DIR).join("group"), "GROUP").unwrap();
+        assert!(watcher.wait_for_change(TIMEOUT).unwrap());
+
+        fs::write(path.join(ENTRIES_DIR).join(HEAD).join("head"), "HEAD").unwrap();
+        assert!(watcher.wait_for_change(TIMEOUT).unwrap());
+
+        fs::remove_file(path.join(GROUPS_DIR).join("group")).unwrap();
+        assert!(watcher.wait_for_change(TIMEOUT).unwrap());
+
+        fs::write(path.join(ENTRIES_DIR).join(BODY).join("body"), "BODY").unwrap();
+        assert!(watcher.wait_for_change(TIMEOUT).unwrap());
+
+        fs::write(path.join(SAVED_SEARCHES_DIR).join("search"), "SEARCH").unwrap();
+        assert!(watcher.wait_for_change(TIMEOUT).unwrap());
+
+        fs::write(path.join(VAULT_SETTINGS_NAME), "SETTINGS").unwrap();
+        assert!(watcher.wait_for_change(TIMEOUT).unwrap());
+
+        // Other files next to the vault are ignored.
+        fs::write(path.join(MASTER_KEY_NAME), "KEY").unwrap();
+        fs::write(path.join("notes.txt"), "NOTES").unwrap();
+        assert!(!watcher.wait_for_change(Duration::from_secs(2)).unwrap());
+    }
+
+    #[test]
+    fn watch_created_saved_searches() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("Vault");
+        create_new_vault_dir(&path).unwrap();
+        // Vaults created before saved searches existed do not contain the directory.
+        fs::remove_dir(path.join(SAVED_SEARCHES_DIR)).unwrap();
+        let watcher = VaultWatcher::new(&path).unwrap();
+
+        fs::create_dir(path.join(SAVED_SEARCHES_DIR)).unwrap();
+        assert!(watcher.wait_for_change(TIMEOUT).unwrap());
+
+        fs::write(path.join(SAVED_SEARCHES_DIR).join("search"), "SEARCH").unwrap();
+        assert!(watcher.wait_for_change(TIMEOUT).unwrap());
+    }
+
+    #[test]
+    fn watch_missing_vault() {
+        let dir = tempdir().unwrap();
+        assert!(VaultWatcher::new(&dir.path().join("Missing")).is_err());
+    }
+}
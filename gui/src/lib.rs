@@ -136,6 +136,10 @@ pub struct PWDuckGui<P: Platform + 'static> {
 
     /// The settings of this application.
     application_settings: pwduck_core::ApplicationSettings,
+
+    /// The confirmations requested by other programs, waiting to be shown.
+    #[cfg(unix)]
+    confirmations: std::collections::VecDeque<vault::confirmation::ConfirmationRequest>,
 }
 
 /// The state of the error dialog.
@@ -295,6 +299,74 @@ impl<P: Platform + 'static> PWDuckGui<P> {
             .map(move |cmd| cmd.map(move |msg| Message::VaultTab(index, msg)))
     }
 
+    /// Forward the message to the vault container of the selected tab, if it contains an unlocked vault.
+    fn update_selected_vault_container(
+        &mut self,
+        message: VaultContainerMessage,
+        clipboard: &mut iced::Clipboard,
+    ) -> Result<iced::Command<Message>, PWDuckGuiError> {
+        let index = self.tabs.selected();
+        if self.tabs.get(index).map_or(false, VaultTab::is_open) {
+            self.update_vault_tab(index, VaultTabMessage::Container(message), clipboard)
+        } else {
+            Ok(Command::none())
+        }
+    }
+
+    /// Reload the changes made on disk into the tab of the vault at the given location.
+    fn reload_vault(
+        &mut self,
+        path: &std::path::Path,
+        clipboard: &mut iced::Clipboard,
+    ) -> Result<iced::Command<Message>, PWDuckGuiError> {
+        match self
+            .tabs
+            .iter()
+            .position(|tab| tab.vault_path().map_or(false, |vault| vault == path))
+        {
+            Some(index) => {
+                let cmd = self.update_vault_tab(
+                    index,
+                    VaultTabMessage::Container(VaultContainerMessage::VaultChanged),
+                    clipboard,
+                );
+                // The modal always belongs to the selected tab.
+                if let ModalState::ExternalChanges(_) = self.modal_state.inner() {
+                    self.tabs.select(index);
+                }
+                cmd
+            }
+            None => Ok(Command::none()),
+        }
+    }
+
+    /// Answer the confirmation shown in the modal.
+    #[cfg(unix)]
+    fn answer_confirmation(
+        &mut self,
+        message: &vault::confirmation::ConfirmationModalMessage,
+    ) -> Result<Command<Message>, PWDuckGuiError> {
+        if let ModalState::Confirmation(confirmation_modal) = self.modal_state.inner() {
+            let result = confirmation_modal.answer(message);
+            self.modal_state = modal::State::default();
+            result?;
+        }
+        Ok(Command::none())
+    }
+
+    /// Show the next confirmation requested by another program, unless another modal is shown.
+    #[cfg(unix)]
+    fn show_next_confirmation(&mut self) {
+        if matches!(self.modal_state.inner(), ModalState::None) {
+            if let Some(request) = self.confirmations.pop_front() {
+                self.modal_state = modal::State::new(ModalState::Confirmation(
+                    vault::confirmation::ConfirmationModal::new(request),
+                ));
+                self.modal_state.show(true);
+            }
+        }
+    }
+
     /// Open the settings tab.
     fn open_settings(&mut self) -> Command<Message> {
         let mut settings_tab = VaultTab::new(());
@@ -327,6 +399,18 @@ pub enum Message {
     OpenSettings,
     /// Request focus in the given direction.
     Focus(iced_focus::Direction),
+    /// Undo the most recent unsaved change of the selected vault.
+    Undo,
+    /// Redo the most recently undone change of the selected vault.
+    Redo,
+    /// The vault at the given location was changed on disk by another program.
+    VaultChanged(PathBuf),
+    /// Another program requested a confirmation of the user.
+    #[cfg(unix)]
+    ConfirmationRequested(vault::confirmation::ConfirmationRequest),
+    /// Messages related to the confirmation of a request of another program.
+    #[cfg(unix)]
+    Confirmation(vault::confirmation::ConfirmationModalMessage),
 }
 
 impl<P: Platform + 'static> Application for PWDuckGui<P> {
@@ -348,6 +432,9 @@ impl<P: Platform + 'static> Application for PWDuckGui<P> {
                 phantom: PhantomData,
 
                 application_settings: flags,
+
+                #[cfg(unix)]
+                confirmations: std::collections::VecDeque::new(),
             },
             Command::none(),
         )
@@ -418,9 +505,28 @@ impl<P: Platform + 'static> Application for PWDuckGui<P> {
                 let _ = self.focus(direction);
                 Ok(Command::none())
             }
+
+            Message::Undo => {
+                self.update_selected_vault_container(VaultContainerMessage::Undo, clipboard)
+            }
+
+            Message::Redo => {
+                self.update_selected_vault_container(VaultContainerMessage::Redo, clipboard)
+            }
+
+            Message::VaultChanged(path) => self.reload_vault(&path, clipboard),
+
+            #[cfg(unix)]
+            Message::ConfirmationRequested(request) => {
+                self.confirmations.push_back(request);
+                Ok(Command::none())
+            }
+
+            #[cfg(unix)]
+            Message::Confirmation(message) => self.answer_confirmation(&message),
         };
 
-        match cmd {
+        let cmd = match cmd {
             Ok(cmd) => cmd,
             Err(error) => {
                 println!("{:?}", error);
@@ -432,7 +538,13 @@ impl<P: Platform + 'static> Application for PWDuckGui<P> {
 
                 Command::none()
             }
-        }
+        };
+
+        // Confirmations wait until the user closed the other modals.
+        #[cfg(unix)]
+        self.show_next_confirmation();
+
+        cmd
     }
 
     #[cfg_attr(coverage, no_coverage)]
@@ -507,7 +619,13 @@ impl<P: Platform + 'static> Application for PWDuckGui<P> {
         let content: Element<_> = Column::new().push(top_row).push(tab).into();
 
         let modal_style = match self.modal_state.inner() {
-            ModalState::Password(_) => theme.modal(),
+            ModalState::Export(crate::vault::container::ExportModal::Plain { .. }) => {
+                theme.modal_warning()
+            }
+            ModalState::Password(_)
+            | ModalState::Import(_)
+            | ModalState::Export(_)
+            | ModalState::Merge(_) => theme.modal(),
             _ => theme.modal_warning(),
         };
         Container::new(
@@ -524,21 +642,37 @@ impl<P: Platform + 'static> Application for PWDuckGui<P> {
 
     #[cfg_attr(coverage, no_coverage)]
     fn subscription(&self) -> Subscription<Self::Message> {
-        iced_native::subscription::events_with(|event, _status| {
-            if let iced_native::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+        let watchers = self
+            .tabs
+            .iter()
+            .filter_map(VaultTab::vault_path)
+            .map(|path| vault::watcher::watch(path.clone()).map(Message::VaultChanged));
+        #[cfg(unix)]
+        let watchers = watchers.chain(self.tabs.iter().filter_map(VaultTab::vault_path).map(
+            |path| vault::confirmation::listen(path.clone()).map(Message::ConfirmationRequested),
+        ));
+
+        let events = iced_native::subscription::events_with(|event, _status| match event {
+            iced_native::Event::Keyboard(iced::keyboard::Event::KeyPressed {
                 key_code: iced_native::keyboard::KeyCode::Tab,
                 modifiers,
-            }) = event
-            {
-                Some(Message::Focus(if modifiers.shift {
-                    iced_focus::Direction::Backwards
-                } else {
-                    iced_focus::Direction::Forwards
-                }))
+            }) => Some(Message::Focus(if modifiers.shift {
+                iced_focus::Direction::Backwards
             } else {
-                Some(Message::IcedEvent(event))
-            }
-        })
+                iced_focus::Direction::Forwards
+            })),
+            iced_native::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                key_code: iced_native::keyboard::KeyCode::Z,
+                modifiers,
+            }) if modifiers.control => Some(if modifiers.shift {
+                Message::Redo
+            } else {
+                Message::Undo
+            }),
+            _ => Some(Message::IcedEvent(event)),
+        });
+
+        Subscription::batch(std::iter::once(events).chain(watchers))
     }
 
     #[cfg_attr(coverage, no_coverage)]
@@ -591,6 +725,15 @@ pub trait Platform {
     /// If file name is some a file save dialoge is used, else a file open dialog.
     async fn nfd_choose_key_file(file_name: Option<String>) -> Result<PathBuf, NfdError>;
 
+    /// Open the choose file dialog of the native file dialog on this [`Platform`](Platform)
+    /// for files with the given extensions.
+    /// If file name is some a file save dialoge is used, else a file open dialog.
+    async fn nfd_choose_file(
+        filter_name: String,
+        extensions: Vec<String>,
+        file_name: Option<String>,
+    ) -> Result<PathBuf, NfdError>;
+
     /// True, if the system supports to open an URL in the default browser.
     fn is_open_in_browser_available() -> bool;
 
@@ -626,6 +769,15 @@ impl Platform for TestPlatform {
         Ok(PathBuf::from("this/is/a/path"))
     }
 
+    #[cfg_attr(coverage, no_coverage)]
+    async fn nfd_choose_file(
+        _filter_name: String,
+        _extensions: Vec<String>,
+        _file_name: Option<String>,
+    ) -> Result<PathBuf, NfdError> {
+        Ok(PathBuf::from("this/is/a/path"))
+    }
+
     #[cfg_attr(coverage, no_coverage)]
     fn is_open_in_browser_available() -> bool {
         false
@@ -658,6 +810,19 @@ pub enum ModalState {
     ModifyGroup(crate::vault::container::ModifyGroupModal),
     /// The state of the entry modifier modal.
     ModifyEntry(crate::vault::container::ModifyEntryModal),
+    /// The state of the import modal.
+    Import(crate::vault::container::ImportModal),
+    /// The state of the export modal.
+    Export(crate::vault::container::ExportModal),
+    /// The state of the merge modal.
+    Merge(Box<crate::vault::container::MergeModal>),
+    /// The state of the modal to resolve the conflicts with the changes on disk.
+    ExternalChanges(crate::vault::container::ExternalChangesModal),
+    /// The state of the modal shown when saving conflicts with the changes on disk.
+    SaveConflict(crate::vault::container::SaveConflictModal),
+    /// The state of the modal asking to confirm the request of another program.
+    #[cfg(unix)]
+    Confirmation(crate::vault::confirmation::ConfirmationModal),
     /// The modal is empty.
     None,
 }
@@ -697,6 +862,44 @@ impl ModalState {
                     )
                 })
             }
+            ModalState::Import(import_modal) => import_modal.view(theme).map(move |msg| {
+                Message::VaultTab(
+                    index,
+                    VaultTabMessage::Container(VaultContainerMessage::Import(msg)),
+                )
+            }),
+            ModalState::Export(export_modal) => export_modal.view(theme).map(move |msg| {
+                Message::VaultTab(
+                    index,
+                    VaultTabMessage::Container(VaultContainerMessage::Export(msg)),
+                )
+            }),
+            ModalState::Merge(merge_modal) => merge_modal.view(theme).map(move |msg| {
+                Message::VaultTab(
+                    index,
+                    VaultTabMessage::Container(VaultContainerMessage::Merge(msg)),
+                )
+            }),
+            ModalState::ExternalChanges(external_changes_modal) => {
+                external_changes_modal.view(theme).map(move |msg| {
+                    Message::VaultTab(
+                        index,
+                        VaultTabMessage::Container(VaultContainerMessage::ExternalChanges(msg)),
+                    )
+                })
+            }
+            ModalState::SaveConflict(save_conflict_modal) => {
+                save_conflict_modal.view(theme).map(move |msg| {
+                    Message::VaultTab(
+                        index,
+                        VaultTabMessage::Container(VaultContainerMessage::SaveConflict(msg)),
+                    )
+                })
+            }
+            #[cfg(unix)]
+            ModalState::Confirmation(confirmation_modal) => {
+                confirmation_modal.view(theme).map(Message::Confirmation)
+            }
             ModalState::None => Text::new("This is a bug and should never be visible!").into(),
         }
     }
@@ -1249,6 +1452,14 @@ mod tests {
                 &mut clipboard,
             );
 
+            // Undo and redo are ignored if the selected tab contains no unlocked vault.
+            let _ = gui.update(Message::Undo, &mut clipboard);
+            let _ = gui.update(Message::Redo, &mut clipboard);
+            assert_eq!(
+                call_map.borrow()[&PWDuckGui::<TestPlatform>::update_vault_tab.type_id()],
+                1
+            );
+
             //assert!(call_map.borrow().values().all(|v| *v == 1))
 
             // Check correct error handling.
@@ -0,0 +1,202 @@
+//! The confirmations requested by other programs for an open vault, e.g. by the native messaging host of a browser.
+use std::{
+    hash::Hash,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use iced::{
+    button,
+    futures::{channel::mpsc, stream::BoxStream},
+    Column, Element, Row, Subscription, Text,
+};
+use iced_aw::Card;
+use pwduck_core::{Confirmation, ConfirmationListener};
+
+use crate::{
+    error::PWDuckGuiError,
+    icons::Icon,
+    theme::Theme,
+    utils::{icon_button, ButtonData, ButtonKind},
+    DEFAULT_COLUMN_SPACING, DEFAULT_MAX_WIDTH, DEFAULT_ROW_SPACING,
+};
+
+/// The interval to check if the subscription was dropped.
+const CLOSE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Listen for the confirmations requested for the vault at the given location.
+pub fn listen(path: PathBuf) -> Subscription<ConfirmationRequest> {
+    Subscription::from_recipe(ConfirmationListen { path })
+}
+
+/// A confirmation requested by another program, answered by the user.
+/// The request is refused if it is dropped unanswered.
+#[derive(Clone, Debug)]
+pub struct ConfirmationRequest {
+    /// The location of the vault.
+    vault: PathBuf,
+    /// The question shown to the user.
+    prompt: String,
+    /// The confirmation waiting for the answer. It is taken by the answer.
+    confirmation: Arc<Mutex<Option<Confirmation>>>,
+}
+
+impl ConfirmationRequest {
+    /// Create a new request of the confirmation for the vault.
+    fn new(vault: PathBuf, confirmation: Confirmation) -> Self {
+        Self {
+            vault,
+            prompt: confirmation.prompt().to_owned(),
+            confirmation: Arc::new(Mutex::new(Some(confirmation))),
+        }
+    }
+
+    /// Send the answer of the user to the requesting program.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the program closed the connection.
+    pub fn answer(&self, confirmed: bool) -> Result<(), PWDuckGuiError> {
+        if let Some(confirmation) = self.confirmation.lock()?.take() {
+            confirmation.answer(confirmed)?;
+        }
+        Ok(())
+    }
+}
+
+/// The recipe of the subscription listening for the confirmations of a vault.
+#[derive(Debug)]
+struct ConfirmationListen {
+    /// The location of the vault.
+    path: PathBuf,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for ConfirmationListen
+where
+    H: std::hash::Hasher,
+{
+    type Output = ConfirmationRequest;
+
+    fn hash(&self, state: &mut H) {
+        std::any::TypeId::of::<Self>().hash(state);
+        self.path.hash(state);
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        let (sender, receiver) = mpsc::unbounded();
+        let path = self.path;
+
+        // The listener blocks while waiting, so it must not run on the executor.
+        drop(std::thread::spawn(move || {
+            // Another GUI showing the same vault answers its confirmations.
+            let listener = match ConfirmationListener::bind(&path) {
+                Ok(listener) => listener,
+                Err(_err) => return,
+            };
+            loop {
+                match listener.accept(CLOSE_CHECK_INTERVAL) {
+                    Ok(Some(confirmation)) => {
+                        let request = ConfirmationRequest::new(path.clone(), confirmation);
+                        if sender.unbounded_send(request).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) if !sender.is_closed() => {}
+                    _ => break,
+                }
+            }
+        }));
+
+        Box::pin(receiver)
+    }
+}
+
+/// The state of the modal asking the user to confirm the request of another program.
+#[derive(Debug)]
+pub struct ConfirmationModal {
+    /// The request to confirm.
+    request: ConfirmationRequest,
+    /// The state of the [`Button`](iced::Button) to allow the request.
+    allow_button_state: button::State,
+    /// The state of the [`Button`](iced::Button) to deny the request.
+    deny_button_state: button::State,
+}
+
+/// The message send by the modal asking to confirm the request of another program.
+#[derive(Clone, Debug)]
+pub enum ConfirmationModalMessage {
+    /// Allow the request.
+    Allow,
+    /// Deny the request.
+    Deny,
+}
+
+impl ConfirmationModal {
+    /// Create the modal asking to confirm the request.
+    pub fn new(request: ConfirmationRequest) -> Self {
+        Self {
+            request,
+            allow_button_state: button::State::new(),
+            deny_button_state: button::State::new(),
+        }
+    }
+
+    /// Send the answer of the user to the requesting program.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the program closed the connection.
+    pub fn answer(&self, message: &ConfirmationModalMessage) -> Result<(), PWDuckGuiError> {
+        self.request
+            .answer(matches!(message, ConfirmationModalMessage::Allow))
+    }
+
+    /// Create the view of the modal.
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn view(&mut self, theme: &dyn Theme) -> Element<'_, ConfirmationModalMessage> {
+        Card::new(
+            Text::new("Another program asks for your confirmation"),
+            Column::new()
+                .spacing(DEFAULT_COLUMN_SPACING)
+                .push(Text::new(self.request.prompt.clone()))
+                .push(Text::new(format!(
+                    "Vault: {}",
+                    self.request.vault.display()
+                ))),
+        )
+        .foot(
+            Row::new()
+                .spacing(DEFAULT_ROW_SPACING)
+                .push(icon_button(
+                    ButtonData {
+                        state: &mut self.deny_button_state,
+                        icon: Icon::XSquare,
+                        text: "Deny",
+                        kind: ButtonKind::Normal,
+                        on_press: Some(ConfirmationModalMessage::Deny),
+                    },
+                    "Refuse the request of the program",
+                    false,
+                    theme,
+                ))
+                .push(icon_button(
+                    ButtonData {
+                        state: &mut self.allow_button_state,
+                        icon: Icon::Unlock,
+                        text: "Allow",
+                        kind: ButtonKind::Warning,
+                        on_press: Some(ConfirmationModalMessage::Allow),
+                    },
+                    "Allow the program to proceed",
+                    false,
+                    theme,
+                )),
+        )
+        .on_close(ConfirmationModalMessage::Deny)
+        .style(theme.card_warning())
+        .max_width(DEFAULT_MAX_WIDTH)
+        .into()
+    }
+}
@@ -0,0 +1,545 @@
+//! The modal to export groups and entries into the databases of other password managers
+//! or into unencrypted CSV files and JSON documents.
+use std::path::PathBuf;
+
+use iced::{button, text_input, Column, Element, Row, Text};
+use iced_aw::Card;
+use pwduck_core::{Export, SecString};
+
+use crate::{
+    icons::Icon,
+    theme::Theme,
+    utils::{default_text_input, icon_button, password_toggle, ButtonData, ButtonKind, SomeIf},
+    DEFAULT_COLUMN_SPACING, DEFAULT_MAX_WIDTH, DEFAULT_ROW_SPACING,
+};
+
+/// The state of the export modal.
+#[derive(Debug)]
+pub enum ExportModal {
+    /// Enter the password of the new `KeePass` database.
+    Password {
+        /// The location of the new database.
+        path: PathBuf,
+        /// The password of the new database.
+        password: SecString,
+        /// The state of the [`TextInput`](iced::TextInput) for the password.
+        password_state: text_input::State,
+        /// The confirmation of the password.
+        password_confirm: SecString,
+        /// The state of the [`TextInput`](iced::TextInput) for the password confirmation.
+        password_confirm_state: text_input::State,
+        /// The visibility of the password and its confirmation.
+        password_show: bool,
+        /// The state of the [`Button`](iced::Button) to toggle the visibility.
+        password_show_state: button::State,
+        /// The state of the cancel [`Button`](iced::Button).
+        cancel_button_state: button::State,
+        /// The state of the submit [`Button`](iced::Button).
+        submit_button_state: button::State,
+    },
+    /// Warn about the unencrypted export and confirm it with the master password.
+    Plain {
+        /// The location of the unencrypted file.
+        path: PathBuf,
+        /// The master password of the vault.
+        password: SecString,
+        /// The state of the [`TextInput`](iced::TextInput) for the master password.
+        password_state: text_input::State,
+        /// The visibility of the master password.
+        password_show: bool,
+        /// The state of the [`Button`](iced::Button) to toggle the visibility.
+        password_show_state: button::State,
+        /// True, if the submitted master password was wrong.
+        password_wrong: bool,
+        /// The state of the cancel [`Button`](iced::Button).
+        cancel_button_state: button::State,
+        /// The state of the submit [`Button`](iced::Button).
+        submit_button_state: button::State,
+    },
+    /// Report the result of the export.
+    Report {
+        /// The location of the new database.
+        path: PathBuf,
+        /// The number of exported groups.
+        group_count: usize,
+        /// The number of exported entries.
+        entry_count: usize,
+        /// The state of the close [`Button`](iced::Button).
+        close_button_state: button::State,
+    },
+    /// Report the location of the written backup.
+    Backup {
+        /// The location of the backup.
+        path: PathBuf,
+        /// The state of the close [`Button`](iced::Button).
+        close_button_state: button::State,
+    },
+    /// No modal.
+    None,
+}
+
+/// The message send by the export modal.
+#[derive(Clone, Debug)]
+pub enum ExportModalMessage {
+    /// Change the password to the new value.
+    PasswordInput(String),
+    /// Change the password confirmation to the new value.
+    PasswordConfirmInput(String),
+    /// Toggle the visibility of the password.
+    PasswordShow,
+    /// Close the modal.
+    Close,
+    /// Submit the export.
+    Submit,
+}
+impl SomeIf for ExportModalMessage {}
+
+impl ExportModal {
+    /// Create the modal to enter the password of the database that will be created at the given path.
+    pub fn password(path: PathBuf) -> Self {
+        let mut password_state = text_input::State::new();
+        password_state.focus();
+
+        Self::Password {
+            path,
+            password: SecString::default(),
+            password_state,
+            password_confirm: SecString::default(),
+            password_confirm_state: text_input::State::new(),
+            password_show: false,
+            password_show_state: button::State::new(),
+            cancel_button_state: button::State::new(),
+            submit_button_state: button::State::new(),
+        }
+    }
+
+    /// Create the modal to confirm the unencrypted export to the given path with the master password.
+    pub fn plain(path: PathBuf) -> Self {
+        let mut password_state = text_input::State::new();
+        password_state.focus();
+
+        Self::Plain {
+            path,
+            password: SecString::default(),
+            password_state,
+            password_show: false,
+            password_show_state: button::State::new(),
+            password_wrong: false,
+            cancel_button_state: button::State::new(),
+            submit_button_state: button::State::new(),
+        }
+    }
+
+    /// Create the modal to report the result of the given [`Export`](Export).
+    pub fn report(path: PathBuf, export: &Export) -> Self {
+        Self::Report {
+            path,
+            group_count: export.group_count(),
+            entry_count: export.entry_count(),
+            close_button_state: button::State::new(),
+        }
+    }
+
+    /// Create the modal to report the backup written to the given path.
+    pub fn backup(path: PathBuf) -> Self {
+        Self::Backup {
+            path,
+            close_button_state: button::State::new(),
+        }
+    }
+
+    /// Update the password and replace it with the given value.
+    pub fn update_password(&mut self, value: String) {
+        match self {
+            Self::Password { password, .. } => *password = value.into(),
+            Self::Plain {
+                password,
+                password_wrong,
+                ..
+            } => {
+                *password = value.into();
+                *password_wrong = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// Mark the submitted master password of the unencrypted export as wrong.
+    pub fn set_password_wrong(&mut self) {
+        if let Self::Plain { password_wrong, .. } = self {
+            *password_wrong = true;
+        }
+    }
+
+    /// Update the password confirmation and replace it with the given value.
+    pub fn update_password_confirm(&mut self, value: String) {
+        if let Self::Password {
+            password_confirm, ..
+        } = self
+        {
+            *password_confirm = value.into();
+        }
+    }
+
+    /// Toggle the visibility of the password.
+    pub fn toggle_password_visibility(&mut self) {
+        if let Self::Password { password_show, .. } | Self::Plain { password_show, .. } = self {
+            *password_show = !*password_show;
+        }
+    }
+
+    /// True, if the password is not empty and equals the password confirmation.
+    /// The master password of the unencrypted export only needs to be non-empty.
+    pub fn is_password_valid(&self) -> bool {
+        match self {
+            Self::Password {
+                password,
+                password_confirm,
+                ..
+            } => !password.is_empty() && password == password_confirm,
+            Self::Plain { password, .. } => !password.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Create the view of the modal.
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn view(&mut self, theme: &dyn Theme) -> Element<'_, ExportModalMessage> {
+        let password_valid = self.is_password_valid();
+        match self {
+            ExportModal::Password {
+                path,
+                password,
+                password_state,
+                password_confirm,
+                password_confirm_state,
+                password_show,
+                password_show_state,
+                cancel_button_state,
+                submit_button_state,
+            } => {
+                let mut password_input = default_text_input(
+                    password_state,
+                    "Enter the password of the new KeePass database",
+                    password,
+                    ExportModalMessage::PasswordInput,
+                )
+                .on_submit(ExportModalMessage::Submit)
+                .style(theme.text_input());
+                let mut password_confirm_input = default_text_input(
+                    password_confirm_state,
+                    "Confirm the password",
+                    password_confirm,
+                    ExportModalMessage::PasswordConfirmInput,
+                )
+                .on_submit(ExportModalMessage::Submit)
+                .style(if password.is_empty() || password_valid {
+                    theme.text_input()
+                } else {
+                    theme.password_missmatch()
+                });
+                if !*password_show {
+                    password_input = password_input.password();
+                    password_confirm_input = password_confirm_input.password();
+                }
+
+                Card::new(
+                    Text::new("Export KeePass database"),
+                    Column::new()
+                        .spacing(DEFAULT_COLUMN_SPACING)
+                        .push(Text::new(path.to_str().unwrap_or("Invalid path")))
+                        .push(
+                            Row::new()
+                                .spacing(DEFAULT_ROW_SPACING)
+                                .push(password_input)
+                                .push(password_toggle(
+                                    password_show_state,
+                                    *password_show,
+                                    ExportModalMessage::PasswordShow,
+                                    theme,
+                                )),
+                        )
+                        .push(password_confirm_input),
+                )
+                .foot(
+                    Row::new()
+                        .spacing(DEFAULT_ROW_SPACING)
+                        .push(icon_button(
+                            ButtonData {
+                                state: cancel_button_state,
+                                icon: Icon::XSquare,
+                                text: "Cancel",
+                                kind: ButtonKind::Normal,
+                                on_press: Some(ExportModalMessage::Close),
+                            },
+                            "Cancel the export",
+                            false,
+                            theme,
+                        ))
+                        .push(icon_button(
+                            ButtonData {
+                                state: submit_button_state,
+                                icon: Icon::Lock,
+                                text: "Export",
+                                kind: ButtonKind::Primary,
+                                on_press: ExportModalMessage::Submit.some_if(password_valid),
+                            },
+                            "Export the groups and entries into the database",
+                            false,
+                            theme,
+                        )),
+                )
+                .style(theme.card())
+                .max_width(DEFAULT_MAX_WIDTH)
+                .into()
+            }
+            ExportModal::Plain {
+                path,
+                password,
+                password_state,
+                password_show,
+                password_show_state,
+                password_wrong,
+                cancel_button_state,
+                submit_button_state,
+            } => {
+                let mut password_input = default_text_input(
+                    password_state,
+                    "Enter the master password to confirm the export",
+                    password,
+                    ExportModalMessage::PasswordInput,
+                )
+                .on_submit(ExportModalMessage::Submit)
+                .style(if *password_wrong {
+                    theme.password_missmatch()
+                } else {
+                    theme.text_input()
+                });
+                if !*password_show {
+                    password_input = password_input.password();
+                }
+
+                let mut body = Column::new()
+                    .spacing(DEFAULT_COLUMN_SPACING)
+                    .push(Text::new(
+                        "The passwords and all other fields will be written unencrypted \
+                        to the following file. Everyone with access to this file can read them, \
+                        so delete it as soon as it is no longer needed.",
+                    ))
+                    .push(Text::new(path.to_str().unwrap_or("Invalid path")))
+                    .push(
+                        Row::new()
+                            .spacing(DEFAULT_ROW_SPACING)
+                            .push(password_input)
+                            .push(password_toggle(
+                                password_show_state,
+                                *password_show,
+                                ExportModalMessage::PasswordShow,
+                                theme,
+                            )),
+                    );
+                if *password_wrong {
+                    body = body.push(Text::new("The master password is wrong."));
+                }
+
+                Card::new(Text::new("Unencrypted export"), body)
+                    .foot(
+                        Row::new()
+                            .spacing(DEFAULT_ROW_SPACING)
+                            .push(icon_button(
+                                ButtonData {
+                                    state: cancel_button_state,
+                                    icon: Icon::XSquare,
+                                    text: "Cancel",
+                                    kind: ButtonKind::Normal,
+                                    on_press: Some(ExportModalMessage::Close),
+                                },
+                                "Cancel the export",
+                                false,
+                                theme,
+                            ))
+                            .push(icon_button(
+                                ButtonData {
+                                    state: submit_button_state,
+                                    icon: Icon::FileEarmarkPerson,
+                                    text: "Export",
+                                    kind: ButtonKind::Warning,
+                                    on_press: ExportModalMessage::Submit.some_if(password_valid),
+                                },
+                                "Export the groups and entries unencrypted into the file",
+                                false,
+                                theme,
+                            )),
+                    )
+                    .style(theme.card_warning())
+                    .max_width(DEFAULT_MAX_WIDTH)
+                    .into()
+            }
+            ExportModal::Report {
+                path,
+                group_count,
+                entry_count,
+                close_button_state,
+            } => Card::new(
+                Text::new("Export finished"),
+                Column::new()
+                    .spacing(DEFAULT_COLUMN_SPACING)
+                    .push(Text::new(format!(
+                        "Exported {} groups and {} entries.",
+                        group_count, entry_count
+                    )))
+                    .push(Text::new(path.to_str().unwrap_or("Invalid path"))),
+            )
+            .foot(Row::new().spacing(DEFAULT_ROW_SPACING).push(icon_button(
+                ButtonData {
+                    state: close_button_state,
+                    icon: Icon::XSquare,
+                    text: "Close",
+                    kind: ButtonKind::Normal,
+                    on_press: Some(ExportModalMessage::Close),
+                },
+                "Close the report of the export",
+                false,
+                theme,
+            )))
+            .style(theme.card())
+            .max_width(DEFAULT_MAX_WIDTH)
+            .into(),
+            ExportModal::Backup {
+                path,
+                close_button_state,
+            } => Card::new(
+                Text::new("Backup finished"),
+                Column::new()
+                    .spacing(DEFAULT_COLUMN_SPACING)
+                    .push(Text::new(
+                        "The saved state of the vault was written to the following file. \
+                        It can be restored with the password and key file of the vault.",
+                    ))
+                    .push(Text::new(path.to_str().unwrap_or("Invalid path"))),
+            )
+            .foot(Row::new().spacing(DEFAULT_ROW_SPACING).push(icon_button(
+                ButtonData {
+                    state: close_button_state,
+                    icon: Icon::XSquare,
+                    text: "Close",
+                    kind: ButtonKind::Normal,
+                    on_press: Some(ExportModalMessage::Close),
+                },
+                "Close the report of the backup",
+                false,
+                theme,
+            )))
+            .style(theme.card())
+            .max_width(DEFAULT_MAX_WIDTH)
+            .into(),
+            ExportModal::None => Text::new("This message should never appear!").into(),
+        }
+    }
+}
+
+impl Default for ExportModal {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::ExportModal;
+
+    #[test]
+    fn password() {
+        let mut modal = ExportModal::password(PathBuf::from("export.kdbx"));
+        assert!(!modal.is_password_valid());
+
+        modal.update_password("password".into());
+        assert!(!modal.is_password_valid());
+        modal.update_password_confirm("password".into());
+        assert!(modal.is_password_valid());
+        modal.toggle_password_visibility();
+
+        if let ExportModal::Password {
+            path,
+            password,
+            password_confirm,
+            password_state,
+            password_show,
+            ..
+        } = &modal
+        {
+            assert_eq!(path, &PathBuf::from("export.kdbx"));
+            assert_eq!(password.as_str(), "password");
+            assert_eq!(password_confirm.as_str(), "password");
+            assert!(password_state.is_focused());
+            assert!(password_show);
+        } else {
+            panic!("Modal should be the password modal");
+        }
+
+        modal.update_password_confirm("different".into());
+        assert!(!modal.is_password_valid());
+    }
+
+    #[test]
+    fn plain() {
+        let mut modal = ExportModal::plain(PathBuf::from("export.csv"));
+        assert!(!modal.is_password_valid());
+
+        modal.update_password("password".into());
+        assert!(modal.is_password_valid());
+        modal.update_password_confirm("different".into());
+        assert!(modal.is_password_valid());
+        modal.toggle_password_visibility();
+        modal.set_password_wrong();
+
+        if let ExportModal::Plain {
+            path,
+            password,
+            password_state,
+            password_show,
+            password_wrong,
+            ..
+        } = &modal
+        {
+            assert_eq!(path, &PathBuf::from("export.csv"));
+            assert_eq!(password.as_str(), "password");
+            assert!(password_state.is_focused());
+            assert!(password_show);
+            assert!(password_wrong);
+        } else {
+            panic!("Modal should be the plain export modal");
+        }
+
+        modal.update_password("another password".into());
+        assert!(matches!(
+            modal,
+            ExportModal::Plain {
+                password_wrong: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn backup() {
+        let mut modal = ExportModal::backup(PathBuf::from("vault.pwduck-backup"));
+        assert!(!modal.is_password_valid());
+        modal.update_password("password".into());
+        assert!(!modal.is_password_valid());
+
+        if let ExportModal::Backup { path, .. } = &modal {
+            assert_eq!(path, &PathBuf::from("vault.pwduck-backup"));
+        } else {
+            panic!("Modal should be the backup modal");
+        }
+    }
+
+    #[test]
+    fn default() {
+        assert!(matches!(ExportModal::default(), ExportModal::None));
+        assert!(!ExportModal::default().is_password_valid());
+    }
+}
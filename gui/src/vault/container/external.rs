@@ -0,0 +1,117 @@
+//! The modal to resolve the conflicts between unsaved changes and the changes made on disk by other programs.
+use iced::{button, scrollable, Column, Element, Length, Row, Scrollable, Text};
+use iced_aw::Card;
+use pwduck_core::{MergeConflict, MergeItem};
+
+use crate::{
+    icons::Icon,
+    theme::Theme,
+    utils::{icon_button, ButtonData, ButtonKind},
+    DEFAULT_COLUMN_SPACING, DEFAULT_MAX_WIDTH, DEFAULT_ROW_SPACING,
+};
+
+/// The maximum height of the list of conflicts.
+const CONFLICTS_MAX_HEIGHT: u32 = 200;
+
+/// The state of the modal to resolve the conflicts with the changes on disk.
+#[derive(Debug)]
+pub struct ExternalChangesModal {
+    /// The items with unsaved changes that were changed on disk.
+    conflicts: Vec<MergeConflict>,
+    /// The state of the [`Scrollable`](iced::Scrollable) of the conflicts.
+    scroll_state: scrollable::State,
+    /// The state of the [`Button`](iced::Button) to keep the unsaved changes.
+    keep_button_state: button::State,
+    /// The state of the [`Button`](iced::Button) to reload the changes from disk.
+    reload_button_state: button::State,
+}
+
+/// The message send by the modal to resolve the conflicts with the changes on disk.
+#[derive(Clone, Debug)]
+pub enum ExternalChangesModalMessage {
+    /// Keep the unsaved changes, they overwrite the changes on disk when saved.
+    Keep,
+    /// Discard the unsaved changes and reload the items from disk.
+    Reload,
+}
+
+impl ExternalChangesModal {
+    /// Create the modal to resolve the given conflicts.
+    pub fn new(conflicts: Vec<MergeConflict>) -> Self {
+        Self {
+            conflicts,
+            scroll_state: scrollable::State::new(),
+            keep_button_state: button::State::new(),
+            reload_button_state: button::State::new(),
+        }
+    }
+
+    /// Create the view of the modal.
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn view(&mut self, theme: &dyn Theme) -> Element<'_, ExternalChangesModalMessage> {
+        let conflicts = self.conflicts.iter().fold(
+            Scrollable::new(&mut self.scroll_state)
+                .spacing(DEFAULT_COLUMN_SPACING)
+                .max_height(CONFLICTS_MAX_HEIGHT)
+                .width(Length::Fill),
+            |list, conflict| list.push(Text::new(conflict_description(conflict))),
+        );
+
+        Card::new(
+            Text::new("The vault was changed on disk"),
+            Column::new()
+                .spacing(DEFAULT_COLUMN_SPACING)
+                .push(Text::new(
+                    "Another program changed the following items \
+                    while they contain unsaved changes:",
+                ))
+                .push(conflicts),
+        )
+        .foot(
+            Row::new()
+                .spacing(DEFAULT_ROW_SPACING)
+                .push(icon_button(
+                    ButtonData {
+                        state: &mut self.keep_button_state,
+                        icon: Icon::Save,
+                        text: "Keep my changes",
+                        kind: ButtonKind::Normal,
+                        on_press: Some(ExternalChangesModalMessage::Keep),
+                    },
+                    "Keep the unsaved changes, they overwrite the changes on disk when saved",
+                    false,
+                    theme,
+                ))
+                .push(icon_button(
+                    ButtonData {
+                        state: &mut self.reload_button_state,
+                        icon: Icon::ArrowClockwise,
+                        text: "Reload",
+                        kind: ButtonKind::Warning,
+                        on_press: Some(ExternalChangesModalMessage::Reload),
+                    },
+                    "Discard the unsaved changes of these items and reload them from disk",
+                    false,
+                    theme,
+                )),
+        )
+        .style(theme.card_warning())
+        .max_width(DEFAULT_MAX_WIDTH)
+        .into()
+    }
+}
+
+/// The description of the conflicting item.
+fn conflict_description(conflict: &MergeConflict) -> String {
+    let kind = match conflict.item() {
+        MergeItem::Group => "Group",
+        MergeItem::Entry => "Entry",
+    };
+    if conflict.remote_title().is_empty() {
+        format!("{}: {} (removed on disk)", kind, conflict.local_title())
+    } else if conflict.local_title().is_empty() {
+        format!("{}: {} (deleted here)", kind, conflict.remote_title())
+    } else {
+        format!("{}: {}", kind, conflict.local_title())
+    }
+}
@@ -0,0 +1,713 @@
+//! The modal to import groups and entries from the databases of other password managers.
+use std::path::PathBuf;
+
+use iced::{button, scrollable, text_input, Column, Element, Length, Row, Scrollable, Text};
+use iced_aw::Card;
+use pwduck_core::{CsvField, CsvFile, CsvMapping, CsvPreset, Import, ImportGroup, SecString};
+
+use crate::{
+    icons::Icon,
+    theme::Theme,
+    utils::{
+        default_text_input, icon_button, icon_button_with_width, password_toggle, ButtonData,
+        ButtonKind, SomeIf,
+    },
+    DEFAULT_COLUMN_SPACING, DEFAULT_MAX_WIDTH, DEFAULT_ROW_SPACING,
+};
+
+/// The maximum height of the list of skipped fields.
+const SKIPPED_FIELDS_MAX_HEIGHT: u32 = 200;
+
+/// The maximum height of the column mapping and the preview of an import.
+const LIST_MAX_HEIGHT: u32 = 200;
+
+/// The state of the import modal.
+#[derive(Debug)]
+pub enum ImportModal {
+    /// Enter the password of the `KeePass` database to import.
+    Password {
+        /// The location of the database to import.
+        path: PathBuf,
+        /// The password of the database.
+        password: SecString,
+        /// The state of the [`TextInput`](iced::TextInput) for the password.
+        password_state: text_input::State,
+        /// The visibility of the password.
+        password_show: bool,
+        /// The state of the [`Button`](iced::Button) to toggle the visibility.
+        password_show_state: button::State,
+        /// The state of the cancel [`Button`](iced::Button).
+        cancel_button_state: button::State,
+        /// The state of the submit [`Button`](iced::Button).
+        submit_button_state: button::State,
+    },
+    /// Map the columns of a CSV file and preview the result.
+    Csv {
+        /// The location of the CSV file.
+        path: PathBuf,
+        /// The content of the CSV file.
+        file: CsvFile,
+        /// The mapping of the columns to the fields of the entries.
+        mapping: CsvMapping,
+        /// The groups and entries that will be imported.
+        preview: Import,
+        /// The paths of the entries that will be imported.
+        preview_lines: Vec<String>,
+        /// The states of the [`Button`](iced::Button)s to select a [`CsvPreset`](CsvPreset).
+        preset_states: Vec<button::State>,
+        /// The states of the [`Button`](iced::Button)s to change the field of a column.
+        column_states: Vec<button::State>,
+        /// The state of the [`Scrollable`](iced::Scrollable) of the columns.
+        mapping_scroll_state: scrollable::State,
+        /// The state of the [`Scrollable`](iced::Scrollable) of the preview.
+        preview_scroll_state: scrollable::State,
+        /// The state of the cancel [`Button`](iced::Button).
+        cancel_button_state: button::State,
+        /// The state of the submit [`Button`](iced::Button).
+        submit_button_state: button::State,
+    },
+    /// Preview the groups and entries of a file that needs no further input.
+    Preview {
+        /// The title of the modal naming the kind of the file.
+        title: &'static str,
+        /// The location of the file.
+        path: PathBuf,
+        /// The groups and entries that will be imported.
+        preview: Import,
+        /// The paths of the entries that will be imported.
+        preview_lines: Vec<String>,
+        /// The state of the [`Scrollable`](iced::Scrollable) of the preview.
+        preview_scroll_state: scrollable::State,
+        /// The state of the cancel [`Button`](iced::Button).
+        cancel_button_state: button::State,
+        /// The state of the submit [`Button`](iced::Button).
+        submit_button_state: button::State,
+    },
+    /// Report the result of the import.
+    Report {
+        /// The number of imported groups.
+        group_count: usize,
+        /// The number of imported entries.
+        entry_count: usize,
+        /// The fields which could not be imported.
+        skipped: Vec<String>,
+        /// The state of the [`Scrollable`](iced::Scrollable) of the skipped fields.
+        scroll_state: scrollable::State,
+        /// The state of the close [`Button`](iced::Button).
+        close_button_state: button::State,
+    },
+    /// No modal.
+    None,
+}
+
+/// The message send by the import modal.
+#[derive(Clone, Debug)]
+pub enum ImportModalMessage {
+    /// Change the password to the new value.
+    PasswordInput(String),
+    /// Toggle the visibility of the password.
+    PasswordShow,
+    /// Map the columns of the CSV file as defined by the preset.
+    PresetSelected(CsvPreset),
+    /// Map the column of the CSV file to the next field.
+    CycleColumn(usize),
+    /// Close the modal.
+    Close,
+    /// Submit the import.
+    Submit,
+}
+impl SomeIf for ImportModalMessage {}
+
+impl ImportModal {
+    /// Create the modal to enter the password of the database located at the given path.
+    pub fn password(path: PathBuf) -> Self {
+        let mut password_state = text_input::State::new();
+        password_state.focus();
+
+        Self::Password {
+            path,
+            password: SecString::default(),
+            password_state,
+            password_show: false,
+            password_show_state: button::State::new(),
+            cancel_button_state: button::State::new(),
+            submit_button_state: button::State::new(),
+        }
+    }
+
+    /// Create the modal to map the columns of the given [`CsvFile`](CsvFile).
+    /// The columns of known exports are mapped automatically.
+    pub fn csv(path: PathBuf, file: CsvFile) -> Self {
+        let mapping = CsvMapping::detect(file.headers());
+        let mut modal = Self::Csv {
+            path,
+            preset_states: CsvPreset::ALL
+                .iter()
+                .map(|_| button::State::new())
+                .collect(),
+            column_states: file
+                .headers()
+                .iter()
+                .map(|_| button::State::new())
+                .collect(),
+            file,
+            mapping,
+            preview: Import::default(),
+            preview_lines: Vec::new(),
+            mapping_scroll_state: scrollable::State::new(),
+            preview_scroll_state: scrollable::State::new(),
+            cancel_button_state: button::State::new(),
+            submit_button_state: button::State::new(),
+        };
+        modal.update_preview();
+        modal
+    }
+
+    /// Map the columns of the CSV file as defined by the [`CsvPreset`](CsvPreset).
+    pub fn select_preset(&mut self, preset: CsvPreset) {
+        if let Self::Csv { file, mapping, .. } = self {
+            *mapping = CsvMapping::from_preset(preset, file.headers());
+        }
+        self.update_preview();
+    }
+
+    /// Map the column of the CSV file to the next [`CsvField`](CsvField).
+    pub fn cycle_column(&mut self, column: usize) {
+        if let Self::Csv { mapping, .. } = self {
+            let field = mapping.field(column).next();
+            mapping.set(column, field);
+        }
+        self.update_preview();
+    }
+
+    /// Map the rows of the CSV file again to update the preview.
+    fn update_preview(&mut self) {
+        if let Self::Csv {
+            file,
+            mapping,
+            preview,
+            preview_lines,
+            ..
+        } = self
+        {
+            *preview = Import::from_csv(file, mapping);
+            preview_lines.clear();
+            collect_preview_lines(preview.root(), "", preview_lines);
+        }
+    }
+
+    /// Create the modal to preview the given [`Import`](Import) read from the file located at the given path.
+    pub fn preview(title: &'static str, path: PathBuf, preview: Import) -> Self {
+        let mut preview_lines = Vec::new();
+        collect_preview_lines(preview.root(), "", &mut preview_lines);
+
+        Self::Preview {
+            title,
+            path,
+            preview,
+            preview_lines,
+            preview_scroll_state: scrollable::State::new(),
+            cancel_button_state: button::State::new(),
+            submit_button_state: button::State::new(),
+        }
+    }
+
+    /// Create the modal to report the result of the given [`Import`](Import).
+    pub fn report(import: &Import) -> Self {
+        Self::Report {
+            group_count: import.group_count(),
+            entry_count: import.entry_count(),
+            skipped: import.skipped().iter().map(ToString::to_string).collect(),
+            scroll_state: scrollable::State::new(),
+            close_button_state: button::State::new(),
+        }
+    }
+
+    /// Update the password and replace it with the given value.
+    pub fn update_password(&mut self, value: String) {
+        if let Self::Password { password, .. } = self {
+            *password = value.into();
+        }
+    }
+
+    /// Toggle the visibility of the password.
+    pub fn toggle_password_visibility(&mut self) {
+        if let Self::Password { password_show, .. } = self {
+            *password_show = !*password_show;
+        }
+    }
+
+    /// Create the view of the modal.
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn view(&mut self, theme: &dyn Theme) -> Element<'_, ImportModalMessage> {
+        match self {
+            ImportModal::Password {
+                path,
+                password,
+                password_state,
+                password_show,
+                password_show_state,
+                cancel_button_state,
+                submit_button_state,
+            } => {
+                let mut password_input = default_text_input(
+                    password_state,
+                    "Enter the password of the KeePass database",
+                    password,
+                    ImportModalMessage::PasswordInput,
+                )
+                .on_submit(ImportModalMessage::Submit)
+                .style(theme.text_input());
+                if !*password_show {
+                    password_input = password_input.password();
+                }
+
+                Card::new(
+                    Text::new("Import KeePass database"),
+                    Column::new()
+                        .spacing(DEFAULT_COLUMN_SPACING)
+                        .push(Text::new(path.to_str().unwrap_or("Invalid path")))
+                        .push(
+                            Row::new()
+                                .spacing(DEFAULT_ROW_SPACING)
+                                .push(password_input)
+                                .push(password_toggle(
+                                    password_show_state,
+                                    *password_show,
+                                    ImportModalMessage::PasswordShow,
+                                    theme,
+                                )),
+                        ),
+                )
+                .foot(
+                    Row::new()
+                        .spacing(DEFAULT_ROW_SPACING)
+                        .push(icon_button(
+                            ButtonData {
+                                state: cancel_button_state,
+                                icon: Icon::XSquare,
+                                text: "Cancel",
+                                kind: ButtonKind::Normal,
+                                on_press: Some(ImportModalMessage::Close),
+                            },
+                            "Cancel the import",
+                            false,
+                            theme,
+                        ))
+                        .push(icon_button(
+                            ButtonData {
+                                state: submit_button_state,
+                                icon: Icon::Unlock,
+                                text: "Import",
+                                kind: ButtonKind::Primary,
+                                on_press: ImportModalMessage::Submit
+                                    .some_if_not(password.is_empty()),
+                            },
+                            "Import the groups and entries of the database",
+                            false,
+                            theme,
+                        )),
+                )
+                .style(theme.card())
+                .max_width(DEFAULT_MAX_WIDTH)
+                .into()
+            }
+            ImportModal::Csv {
+                path,
+                file,
+                mapping,
+                preview,
+                preview_lines,
+                preset_states,
+                column_states,
+                mapping_scroll_state,
+                preview_scroll_state,
+                cancel_button_state,
+                submit_button_state,
+            } => {
+                let presets = CsvPreset::ALL.iter().zip(preset_states.iter_mut()).fold(
+                    Row::new().spacing(DEFAULT_ROW_SPACING),
+                    |row, (preset, state)| {
+                        row.push(icon_button(
+                            ButtonData {
+                                state,
+                                icon: Icon::FileEarmarkPerson,
+                                text: preset.name(),
+                                kind: if CsvMapping::from_preset(*preset, file.headers())
+                                    == *mapping
+                                {
+                                    ButtonKind::Primary
+                                } else {
+                                    ButtonKind::Normal
+                                },
+                                on_press: Some(ImportModalMessage::PresetSelected(*preset)),
+                            },
+                            "Map the columns of this export",
+                            false,
+                            theme,
+                        ))
+                    },
+                );
+
+                let columns = file
+                    .headers()
+                    .iter()
+                    .zip(column_states.iter_mut())
+                    .enumerate()
+                    .fold(
+                        Scrollable::new(mapping_scroll_state)
+                            .spacing(DEFAULT_COLUMN_SPACING)
+                            .max_height(LIST_MAX_HEIGHT)
+                            .width(Length::Fill),
+                        |list, (column, (header, state))| {
+                            list.push(
+                                Row::new()
+                                    .spacing(DEFAULT_ROW_SPACING)
+                                    .align_items(iced::Align::Center)
+                                    .push(Text::new(header.as_str()).width(Length::Fill))
+                                    .push(icon_button_with_width(
+                                        ButtonData {
+                                            state,
+                                            icon: Icon::List,
+                                            text: mapping.field(column).name(),
+                                            kind: ButtonKind::Normal,
+                                            on_press: Some(ImportModalMessage::CycleColumn(column)),
+                                        },
+                                        "Change the field of this column",
+                                        Length::Fill,
+                                        theme,
+                                    )),
+                            )
+                        },
+                    );
+
+                let preview_list = preview_lines.iter().fold(
+                    Scrollable::new(preview_scroll_state)
+                        .max_height(LIST_MAX_HEIGHT)
+                        .width(Length::Fill),
+                    |list, line| list.push(Text::new(line.as_str())),
+                );
+
+                Card::new(
+                    Text::new("Import CSV file"),
+                    Column::new()
+                        .spacing(DEFAULT_COLUMN_SPACING)
+                        .push(Text::new(path.to_str().unwrap_or("Invalid path")))
+                        .push(presets)
+                        .push(columns)
+                        .push(Text::new(format!(
+                            "Preview: {} groups and {} entries will be imported.",
+                            preview.group_count(),
+                            preview.entry_count()
+                        )))
+                        .push(preview_list),
+                )
+                .foot(
+                    Row::new()
+                        .spacing(DEFAULT_ROW_SPACING)
+                        .push(icon_button(
+                            ButtonData {
+                                state: cancel_button_state,
+                                icon: Icon::XSquare,
+                                text: "Cancel",
+                                kind: ButtonKind::Normal,
+                                on_press: Some(ImportModalMessage::Close),
+                            },
+                            "Cancel the import",
+                            false,
+                            theme,
+                        ))
+                        .push(icon_button(
+                            ButtonData {
+                                state: submit_button_state,
+                                icon: Icon::Unlock,
+                                text: "Import",
+                                kind: ButtonKind::Primary,
+                                on_press: ImportModalMessage::Submit
+                                    .some_if(preview.entry_count() > 0),
+                            },
+                            "Import the previewed groups and entries",
+                            false,
+                            theme,
+                        )),
+                )
+                .style(theme.card())
+                .max_width(DEFAULT_MAX_WIDTH)
+                .into()
+            }
+            ImportModal::Preview {
+                title,
+                path,
+                preview,
+                preview_lines,
+                preview_scroll_state,
+                cancel_button_state,
+                submit_button_state,
+            } => {
+                let preview_list = preview_lines.iter().fold(
+                    Scrollable::new(preview_scroll_state)
+                        .max_height(LIST_MAX_HEIGHT)
+                        .width(Length::Fill),
+                    |list, line| list.push(Text::new(line.as_str())),
+                );
+
+                Card::new(
+                    Text::new(*title),
+                    Column::new()
+                        .spacing(DEFAULT_COLUMN_SPACING)
+                        .push(Text::new(path.to_str().unwrap_or("Invalid path")))
+                        .push(Text::new(format!(
+                            "Preview: {} groups and {} entries, {} fields will be skipped.",
+                            preview.group_count(),
+                            preview.entry_count(),
+                            preview.skipped().len()
+                        )))
+                        .push(preview_list),
+                )
+                .foot(
+                    Row::new()
+                        .spacing(DEFAULT_ROW_SPACING)
+                        .push(icon_button(
+                            ButtonData {
+                                state: cancel_button_state,
+                                icon: Icon::XSquare,
+                                text: "Cancel",
+                                kind: ButtonKind::Normal,
+                                on_press: Some(ImportModalMessage::Close),
+                            },
+                            "Cancel the import",
+                            false,
+                            theme,
+                        ))
+                        .push(icon_button(
+                            ButtonData {
+                                state: submit_button_state,
+                                icon: Icon::Unlock,
+                                text: "Import",
+                                kind: ButtonKind::Primary,
+                                on_press: ImportModalMessage::Submit
+                                    .some_if(preview.entry_count() > 0),
+                            },
+                            "Import the previewed groups and entries",
+                            false,
+                            theme,
+                        )),
+                )
+                .style(theme.card())
+                .max_width(DEFAULT_MAX_WIDTH)
+                .into()
+            }
+            ImportModal::Report {
+                group_count,
+                entry_count,
+                skipped,
+                scroll_state,
+                close_button_state,
+            } => {
+                let mut body = Column::new()
+                    .spacing(DEFAULT_COLUMN_SPACING)
+                    .push(Text::new(format!(
+                        "Imported {} groups and {} entries.",
+                        group_count, entry_count
+                    )));
+                if !skipped.is_empty() {
+                    body = body
+                        .push(Text::new(
+                            "The following fields can't be represented in PWDuck and were skipped:",
+                        ))
+                        .push(
+                            skipped.iter().fold(
+                                Scrollable::new(scroll_state)
+                                    .max_height(SKIPPED_FIELDS_MAX_HEIGHT)
+                                    .width(Length::Fill),
+                                |list, field| list.push(Text::new(field.as_str())),
+                            ),
+                        );
+                }
+
+                Card::new(Text::new("Import finished"), body)
+                    .foot(Row::new().spacing(DEFAULT_ROW_SPACING).push(icon_button(
+                        ButtonData {
+                            state: close_button_state,
+                            icon: Icon::XSquare,
+                            text: "Close",
+                            kind: ButtonKind::Normal,
+                            on_press: Some(ImportModalMessage::Close),
+                        },
+                        "Close the report of the import",
+                        false,
+                        theme,
+                    )))
+                    .style(theme.card())
+                    .max_width(DEFAULT_MAX_WIDTH)
+                    .into()
+            }
+            ImportModal::None => Text::new("This message should never appear!").into(),
+        }
+    }
+}
+
+/// Collect the paths of all entries of the group and its descendants.
+fn collect_preview_lines(group: &ImportGroup, path: &str, lines: &mut Vec<String>) {
+    for entry in group.entries() {
+        lines.push(format!("{}{}", path, entry.title()));
+    }
+    for child in group.groups() {
+        collect_preview_lines(child, &format!("{}{}/", path, child.title()), lines);
+    }
+}
+
+impl Default for ImportModal {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pwduck_core::{CsvField, CsvFile, CsvPreset, Import};
+
+    use super::ImportModal;
+
+    #[test]
+    fn password() {
+        let mut modal = ImportModal::password(PathBuf::from("database.kdbx"));
+
+        modal.update_password("password".into());
+        modal.toggle_password_visibility();
+
+        if let ImportModal::Password {
+            path,
+            password,
+            password_state,
+            password_show,
+            ..
+        } = &modal
+        {
+            assert_eq!(path, &PathBuf::from("database.kdbx"));
+            assert_eq!(password.as_str(), "password");
+            assert!(password_state.is_focused());
+            assert!(password_show);
+        } else {
+            panic!("Modal should be the password modal");
+        }
+
+        modal.toggle_password_visibility();
+        if let ImportModal::Password { password_show, .. } = &modal {
+            assert!(!password_show);
+        }
+    }
+
+    #[test]
+    fn preview() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../core/tests/resources/bitwarden.json");
+        let import = Import::from_bitwarden(&path).unwrap();
+        let modal = ImportModal::preview("Import Bitwarden export", path.clone(), import);
+
+        if let ImportModal::Preview {
+            title,
+            path: preview_path,
+            preview,
+            preview_lines,
+            ..
+        } = &modal
+        {
+            assert_eq!(*title, "Import Bitwarden export");
+            assert_eq!(preview_path, &path);
+            assert_eq!(preview.entry_count(), 3);
+            assert_eq!(
+                preview_lines,
+                &vec![
+                    "Forum".to_owned(),
+                    "Work/Mail & Calendar".to_owned(),
+                    "Work/Servers/Database".to_owned()
+                ]
+            );
+        } else {
+            panic!("Modal should be the preview modal");
+        }
+    }
+
+    #[test]
+    fn csv() {
+        let file = CsvFile::parse(
+            b"name,url,username,password\nDatabase,https://db.example.com,root,hunter2\n",
+        )
+        .unwrap();
+        let mut modal = ImportModal::csv(PathBuf::from("passwords.csv"), file);
+
+        // The Chrome export is detected.
+        if let ImportModal::Csv {
+            mapping,
+            preview_lines,
+            column_states,
+            ..
+        } = &modal
+        {
+            assert_eq!(mapping.field(0), CsvField::Title);
+            assert_eq!(column_states.len(), 4);
+            assert_eq!(preview_lines, &vec!["Database".to_owned()]);
+        } else {
+            panic!("Modal should be the CSV modal");
+        }
+
+        // Map the username column to the group path.
+        modal.cycle_column(2);
+        modal.cycle_column(2);
+        modal.cycle_column(2);
+        modal.cycle_column(2);
+        if let ImportModal::Csv {
+            mapping,
+            preview,
+            preview_lines,
+            ..
+        } = &modal
+        {
+            assert_eq!(mapping.field(2), CsvField::GroupPath);
+            assert_eq!(preview.group_count(), 1);
+            assert_eq!(preview_lines, &vec!["root/Database".to_owned()]);
+        } else {
+            panic!("Modal should be the CSV modal");
+        }
+
+        modal.select_preset(CsvPreset::Firefox);
+        if let ImportModal::Csv {
+            mapping,
+            preview_lines,
+            ..
+        } = &modal
+        {
+            assert_eq!(mapping.field(0), CsvField::Ignore);
+            assert_eq!(preview_lines, &vec!["https://db.example.com".to_owned()]);
+        } else {
+            panic!("Modal should be the CSV modal");
+        }
+    }
+
+    #[test]
+    fn report() {
+        let modal = ImportModal::report(&pwduck_core::Import::default());
+
+        if let ImportModal::Report {
+            group_count,
+            entry_count,
+            skipped,
+            ..
+        } = &modal
+        {
+            assert_eq!(*group_count, 0);
+            assert_eq!(*entry_count, 0);
+            assert!(skipped.is_empty());
+        } else {
+            panic!("Modal should be the report modal");
+        }
+    }
+
+    #[test]
+    fn default() {
+        assert!(matches!(ImportModal::default(), ImportModal::None));
+    }
+}
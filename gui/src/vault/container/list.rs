@@ -7,7 +7,7 @@ use iced::{
 };
 use iced_aw::{split, Split};
 use iced_focus::Focus;
-use pwduck_core::{EntryHead, Group, Uuid, Vault};
+use pwduck_core::{EntryHead, Group, SavedSearch, SortDirection, Uuid, Vault};
 
 use crate::{
     error::PWDuckGuiError,
@@ -32,6 +32,14 @@ pub struct ListView {
     /// The UUID of the selected group.
     #[getset(get = "pub", get_mut = "pub", set = "pub")]
     selected_group_uuid: Uuid,
+    /// The recently used entries listed at the top of the root.
+    #[getset(get)]
+    #[focus(enable)]
+    recently_used_items: Vec<ListEntryItem>,
+    /// The favourite entries pinned at the top of the root.
+    #[getset(get)]
+    #[focus(enable)]
+    favourite_items: Vec<ListEntryItem>,
     /// The sub-groups of the selected group.
     #[getset(get)]
     #[focus(enable)]
@@ -48,6 +56,23 @@ pub struct ListView {
     #[focus(enable)]
     search_state: text_input::State,
 
+    /// The state of the [`Button`](Button) to change the property to sort by.
+    sort_key_state: button::State,
+    /// The state of the [`Button`](Button) to change the direction to sort in.
+    sort_direction_state: button::State,
+
+    /// The name of the saved search that will be created from the current search.
+    #[getset(get = "pub", get_mut = "pub", set = "pub")]
+    saved_search_name: String,
+    /// The state of the [`TextInput`](TextInput) of the name of the saved search.
+    saved_search_name_state: text_input::State,
+    /// The state of the save search [`Button`](Button).
+    save_search_state: button::State,
+    /// The saved searches displayed below the group tree.
+    saved_search_items: Vec<SavedSearchItem>,
+    /// The states of the [`Button`](Button)s of the favourites displayed below the group tree.
+    favourite_tree_states: Vec<button::State>,
+
     /// The state of the back [`Button`](Button).
     back_state: button::State,
     /// The state of the edit [`Button`](Button)
@@ -73,6 +98,10 @@ pub struct ListView {
 pub enum ListMessage {
     /// Change the search to the new value.
     SearchInput(String),
+    /// Sort by the next property.
+    CycleSortKey,
+    /// Toggle the direction to sort in.
+    ToggleSortDirection,
     /// Go pack to the parent group.
     Back,
     /// Edit the currently selected group.
@@ -83,6 +112,12 @@ pub enum ListMessage {
     SplitResize(u16),
     /// A message send by the group tree.
     GroupTreeMessage(GroupTreeMessage),
+    /// Change the name of the saved search to the new value.
+    SavedSearchNameInput(String),
+    /// Save the current search as a saved search.
+    SaveSearch,
+    /// A message send by the saved searches.
+    SavedSearchMessage(SavedSearchMessage),
 }
 impl SomeIf for ListMessage {}
 
@@ -92,12 +127,14 @@ impl ListView {
     ///
     /// It expects:
     ///  - The UUID of the root group of the vault
-    ///  - The number of sub-groups in the root group.
-    ///  - The number of entries in the root group.
+    ///  - The vault to count the recently used entries, favourites, sub-groups and entries of the root group.
     pub fn new(root_uuid: Uuid, vault: &Vault) -> Self {
-        let (group_count, entry_count) = (
-            vault.get_groups_of(&root_uuid).len(),
-            vault.get_entries_of(&root_uuid).len(),
+        let items = vault.get_item_list_for(&root_uuid, None);
+        let (recently_used_count, favourite_count, group_count, entry_count) = (
+            items.recently_used().len(),
+            items.favourites().len(),
+            items.groups().len(),
+            items.entries().len(),
         );
 
         let mut group_tree = GroupTree::new(root_uuid.clone(), vault);
@@ -105,12 +142,23 @@ impl ListView {
 
         Self {
             selected_group_uuid: root_uuid,
+            recently_used_items: vec![ListEntryItem::default(); recently_used_count],
+            favourite_items: vec![ListEntryItem::default(); favourite_count],
             group_items: vec![ListGroupItem::default(); group_count],
             entry_items: vec![ListEntryItem::default(); entry_count],
 
             search: String::new(),
             search_state: text_input::State::focused(),
 
+            sort_key_state: button::State::new(),
+            sort_direction_state: button::State::new(),
+
+            saved_search_name: String::new(),
+            saved_search_name_state: text_input::State::new(),
+            save_search_state: button::State::new(),
+            saved_search_items: vec![SavedSearchItem::default(); vault.saved_searches().len()],
+            favourite_tree_states: vec![button::State::new(); vault.get_favourites().len()],
+
             back_state: button::State::new(),
             edit_group_state: button::State::new(),
 
@@ -122,7 +170,7 @@ impl ListView {
         }
     }
 
-    /// Resize the number of sub-groups and entries to the current configuration.
+    /// Resize the number of recently used entries, favourites, sub-groups, entries and saved searches to the current configuration.
     pub fn resize(&mut self, vault: &Vault) {
         let search = if self.search().is_empty() {
             None
@@ -130,11 +178,17 @@ impl ListView {
             Some(self.search().as_str())
         };
         let items = vault.get_item_list_for(&self.selected_group_uuid, search);
+        let new_recently_used_count = items.recently_used().len();
+        let new_favourite_count = items.favourites().len();
         let new_group_count = items.groups().len();
         let new_entry_count = items.entries().len();
 
+        self.recently_used_items = vec![ListEntryItem::default(); new_recently_used_count];
+        self.favourite_items = vec![ListEntryItem::default(); new_favourite_count];
         self.group_items = vec![ListGroupItem::default(); new_group_count];
         self.entry_items = vec![ListEntryItem::default(); new_entry_count];
+        self.saved_search_items = vec![SavedSearchItem::default(); vault.saved_searches().len()];
+        self.favourite_tree_states = vec![button::State::new(); vault.get_favourites().len()];
     }
 
     /// Create the view of the [`ListView`](ListView).
@@ -154,6 +208,74 @@ impl ListView {
         .style(theme.text_input())
         .padding(DEFAULT_TEXT_INPUT_PADDING);
 
+        let sort_order = vault.settings().sort_order();
+        let search_row = Row::new()
+            .spacing(DEFAULT_ROW_SPACING)
+            .align_items(iced::Align::Center)
+            .push(search_bar)
+            .push(icon_button_with_width(
+                ButtonData {
+                    state: &mut self.sort_key_state,
+                    icon: Icon::List,
+                    text: sort_order.key().name(),
+                    kind: ButtonKind::Normal,
+                    on_press: Some(ListMessage::CycleSortKey),
+                },
+                "Change the property to sort by",
+                Length::Shrink,
+                theme,
+            ))
+            .push(icon_button_with_width(
+                ButtonData {
+                    state: &mut self.sort_direction_state,
+                    icon: match sort_order.direction() {
+                        SortDirection::Ascending => Icon::CaretRight,
+                        SortDirection::Descending => Icon::CaretDown,
+                    },
+                    text: match sort_order.direction() {
+                        SortDirection::Ascending => "Ascending",
+                        SortDirection::Descending => "Descending",
+                    },
+                    kind: ButtonKind::Normal,
+                    on_press: Some(ListMessage::ToggleSortDirection),
+                },
+                "Change the direction to sort in",
+                Length::Shrink,
+                theme,
+            ));
+
+        let save_search: Element<_> = if self.search.is_empty() {
+            Space::new(Length::Shrink, Length::Shrink).into()
+        } else {
+            Row::new()
+                .spacing(DEFAULT_ROW_SPACING)
+                .align_items(iced::Align::Center)
+                .push(
+                    TextInput::new(
+                        &mut self.saved_search_name_state,
+                        "Name of the saved search",
+                        &self.saved_search_name,
+                        ListMessage::SavedSearchNameInput,
+                    )
+                    .style(theme.text_input())
+                    .padding(DEFAULT_TEXT_INPUT_PADDING),
+                )
+                .push(icon_button_with_width(
+                    ButtonData {
+                        state: &mut self.save_search_state,
+                        icon: Icon::Save,
+                        text: "Save search",
+                        kind: ButtonKind::Normal,
+                        on_press: ListMessage::SaveSearch
+                            .some_if_not(self.saved_search_name.trim().is_empty()),
+                    },
+                    "Save this search as a virtual group",
+                    Length::Shrink,
+                    theme,
+                ))
+                .into()
+        };
+
         let hide_group_tree = viewport.width < 600;
 
         let group_view = group_view(
@@ -163,6 +285,8 @@ impl ListView {
             &mut self.back_state,
             &mut self.edit_group_state,
             &mut self.item_scroll_state,
+            &mut self.recently_used_items,
+            &mut self.favourite_items,
             &mut self.group_items,
             &mut self.entry_items,
             theme,
@@ -186,6 +310,8 @@ impl ListView {
                 vault,
                 &mut self.tree_scroll_state,
                 &mut self.group_tree,
+                &mut self.saved_search_items,
+                &mut self.favourite_tree_states,
                 theme,
             );
 
@@ -204,8 +330,10 @@ impl ListView {
 
         Container::new(
             Column::new()
-                .push(search_bar)
-                .push(vertical_space(2))
+                .push(search_row)
+                .push(vertical_space(1))
+                .push(save_search)
+                .push(vertical_space(1))
                 .push(content),
         )
         .style(theme.container())
@@ -215,28 +343,72 @@ impl ListView {
     }
 }
 
-/// Create the view of the group tree.
+/// Create the view of the group tree followed by the saved searches and the favourites.
 #[cfg_attr(coverage, no_coverage)]
 fn tree_view<'a>(
     vault: &'a Vault,
     scroll_state: &'a mut scrollable::State,
     group_tree: &'a mut GroupTree,
+    saved_search_items: &'a mut [SavedSearchItem],
+    favourite_tree_states: &'a mut [button::State],
     theme: &dyn Theme,
 ) -> Element<'a, ListMessage> {
-    Container::new(
-        Scrollable::new(scroll_state)
-            .push(
-                group_tree
-                    .view(0, vault, theme)
-                    .map(ListMessage::GroupTreeMessage),
-            )
-            .width(Length::Fill)
-            .height(Length::Fill),
-    )
-    .style(theme.container_accent())
-    .width(Length::Fill)
-    .height(Length::Fill)
-    .into()
+    let mut tree = Scrollable::new(scroll_state).push(
+        group_tree
+            .view(0, vault, theme)
+            .map(ListMessage::GroupTreeMessage),
+    );
+
+    let saved_searches = vault.get_saved_searches();
+    if !saved_searches.is_empty() {
+        tree = tree
+            .push(default_vertical_space())
+            .push(Text::new("Saved searches"));
+
+        tree = saved_search_items
+            .iter_mut()
+            .zip(saved_searches.into_iter())
+            .fold(tree, |tree, (item, saved_search)| {
+                tree.push(
+                    item.view(saved_search, theme)
+                        .map(ListMessage::SavedSearchMessage),
+                )
+            });
+    }
+
+    let favourites = vault.get_favourites();
+    if !favourites.is_empty() {
+        tree = tree
+            .push(default_vertical_space())
+            .push(Text::new("Favourites"));
+
+        tree = favourite_tree_states
+            .iter_mut()
+            .zip(favourites.into_iter())
+            .fold(tree, |tree, (state, entry)| {
+                tree.push(
+                    Button::new(
+                        state,
+                        Row::new()
+                            .spacing(DEFAULT_ROW_SPACING)
+                            .align_items(iced::Align::Center)
+                            .push(icon_text(Icon::Person))
+                            .push(Text::new(entry.title())),
+                    )
+                    .width(Length::Fill)
+                    .on_press(ListMessage::ListItemMessage(
+                        ListItemMessage::EntrySelected(entry.uuid().clone()),
+                    ))
+                    .style(theme.tree_node()),
+                )
+            });
+    }
+
+    Container::new(tree.width(Length::Fill).height(Length::Fill))
+        .style(theme.container_accent())
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
 }
 
 /// Create the view of the group entries.
@@ -249,6 +421,8 @@ fn group_view<'a>(
     back_state: &'a mut button::State,
     edit_group_state: &'a mut button::State,
     scroll_state: &'a mut scrollable::State,
+    recently_used_items: &'a mut [ListEntryItem],
+    favourite_items: &'a mut [ListEntryItem],
     group_items: &'a mut [ListGroupItem],
     entry_items: &'a mut [ListEntryItem],
     theme: &dyn Theme,
@@ -324,6 +498,32 @@ fn group_view<'a>(
     } else {
         let mut list = Scrollable::new(scroll_state).spacing(DEFAULT_COLUMN_SPACING);
 
+        if !current_item_list.recently_used().is_empty() {
+            list = recently_used_items
+                .iter_mut()
+                .zip(current_item_list.recently_used().iter())
+                .fold(
+                    list.push(Text::new("Recently used")),
+                    |list, (item, entry)| {
+                        list.push(
+                            item.view(entry, icon_only, no_buttons, theme)
+                                .map(ListMessage::ListItemMessage),
+                        )
+                    },
+                )
+                .push(Text::new("All items"));
+        }
+
+        list = favourite_items
+            .iter_mut()
+            .zip(current_item_list.favourites().iter())
+            .fold(list, |list, (item, entry)| {
+                list.push(
+                    item.view(entry, icon_only, no_buttons, theme)
+                        .map(ListMessage::ListItemMessage),
+                )
+            });
+
         list = group_items
             .iter_mut()
             .zip(current_item_list.groups().iter())
@@ -425,7 +625,7 @@ impl ListEntryItem {
                                 icon: Icon::FileEarmarkPerson,
                                 text: "Username",
                                 kind: ButtonKind::Normal,
-                                on_press: Some(ListItemMessage::CopyUsername(entry.body().clone())),
+                                on_press: Some(ListItemMessage::CopyUsername(entry.uuid().clone())),
                             },
                             "Copy username to clipboard",
                             icon_only,
@@ -437,7 +637,7 @@ impl ListEntryItem {
                                 icon: Icon::FileEarmarkLock,
                                 text: "Password",
                                 kind: ButtonKind::Normal,
-                                on_press: Some(ListItemMessage::CopyPassword(entry.body().clone())),
+                                on_press: Some(ListItemMessage::CopyPassword(entry.uuid().clone())),
                             },
                             "Copy password to clipboard",
                             icon_only,
@@ -472,14 +672,66 @@ pub enum ListItemMessage {
     GroupSelected(Uuid),
     /// Select the entry identified by it's UUID.
     EntrySelected(Uuid),
-    /// Copy the username from the entry body identified by it's UUID.
+    /// Copy the username from the entry identified by it's UUID.
     CopyUsername(Uuid),
-    /// Copy the password from the entry body identified by it's UUID.
+    /// Copy the password from the entry identified by it's UUID.
     CopyPassword(Uuid),
-    /// Autofill credentials from the entry body identified by it's UUID  to the target.
+    /// Autofill credentials from the entry identified by it's UUID  to the target.
     Autofill(Uuid),
 }
 
+/// The state of a saved search displayed below the group tree.
+#[derive(Clone, Debug, Default)]
+struct SavedSearchItem {
+    /// The state of the [`Button`](Button) to select the saved search.
+    select_state: button::State,
+    /// The state of the [`Button`](Button) to delete the saved search.
+    delete_state: button::State,
+}
+
+impl SavedSearchItem {
+    /// Create the view of the [`SavedSearchItem`](SavedSearchItem).
+    #[cfg_attr(coverage, no_coverage)]
+    fn view<'a>(
+        &'a mut self,
+        saved_search: &'a SavedSearch,
+        theme: &dyn Theme,
+    ) -> Element<'a, SavedSearchMessage> {
+        Row::new()
+            .spacing(DEFAULT_ROW_SPACING)
+            .align_items(iced::Align::Center)
+            .push(
+                Button::new(
+                    &mut self.select_state,
+                    Row::new()
+                        .spacing(DEFAULT_ROW_SPACING)
+                        .align_items(iced::Align::Center)
+                        .push(icon_text(Icon::Folder))
+                        .push(Text::new(saved_search.title())),
+                )
+                .width(Length::Fill)
+                .on_press(SavedSearchMessage::Selected(saved_search.uuid().clone()))
+                .style(theme.tree_node()),
+            )
+            .push(
+                Button::new(&mut self.delete_state, icon_text(Icon::Trash))
+                    .on_press(SavedSearchMessage::Delete(saved_search.uuid().clone()))
+                    .padding(0)
+                    .style(theme.tree_expand_button()),
+            )
+            .into()
+    }
+}
+
+/// The message that is send by the saved searches.
+#[derive(Clone, Debug)]
+pub enum SavedSearchMessage {
+    /// Select the saved search identified by it's UUID.
+    Selected(Uuid),
+    /// Delete the saved search identified by it's UUID.
+    Delete(Uuid),
+}
+
 /// A tree view of the group.
 #[derive(Debug)]
 pub struct GroupTree {
@@ -687,7 +939,7 @@ impl GroupTree {
 #[cfg(test)]
 mod tests {
 
-    use pwduck_core::{uuid, MemKey, Uuid, Vault};
+    use pwduck_core::{uuid, MemKey, SavedSearch, Uuid, Vault};
     use tempfile::{tempdir, TempDir};
 
     use std::{
@@ -769,6 +1021,11 @@ mod tests {
         );
         assert!(list_view.search().is_empty());
         assert!(list_view.search_state.is_focused());
+        assert!(list_view.saved_search_name().is_empty());
+        assert!(list_view.saved_search_items.is_empty());
+        assert!(list_view.favourite_items.is_empty());
+        assert!(list_view.favourite_tree_states.is_empty());
+        assert!(list_view.recently_used_items.is_empty());
     }
 
     #[test]
@@ -914,6 +1171,63 @@ mod tests {
             DEFAULT_GROUP_COUNT as usize + 3
         );
         assert_eq!(list_view.entry_items.len(), 0);
+
+        // Add saved searches
+        assert!(list_view.saved_search_items.is_empty());
+        for i in 0..2 {
+            vault.insert_saved_search(SavedSearch::new(
+                [i; uuid::SIZE].into(),
+                format!("Search: {}", i),
+                "Group".into(),
+            ));
+        }
+        list_view.resize(&vault);
+        assert_eq!(list_view.saved_search_items.len(), 2);
+
+        // Add a favourite to some group
+        let mut head = pwduck_core::EntryHead::new(
+            [100; uuid::SIZE].into(),
+            some_group_uuid.clone(),
+            "Favourite".into(),
+            [100; uuid::SIZE].into(),
+        );
+        let _ = head.set_favourite(true);
+        let body = pwduck_core::EntryBody::new(
+            [100; uuid::SIZE].into(),
+            "username".into(),
+            "password".into(),
+        );
+        vault.insert_entry(head, body, &master_key).unwrap();
+
+        list_view.search = "".into();
+        list_view.resize(&vault);
+        assert!(list_view.favourite_items.is_empty());
+        assert_eq!(list_view.favourite_tree_states.len(), 1);
+
+        // The favourite is pinned at the top of the root.
+        list_view.selected_group_uuid = root.clone();
+        list_view.resize(&vault);
+        assert_eq!(list_view.favourite_items.len(), 1);
+        assert_eq!(
+            list_view.entry_items.len(),
+            vault.get_entries_of(&root).len()
+        );
+
+        // The recently used entries are listed at the top of the root, but not inside of a search.
+        assert!(list_view.recently_used_items.is_empty());
+        vault
+            .record_usage(&[0; uuid::SIZE].into(), &master_key)
+            .unwrap();
+        vault
+            .record_usage(&[100; uuid::SIZE].into(), &master_key)
+            .unwrap();
+        list_view.resize(&vault);
+        assert_eq!(list_view.recently_used_items.len(), 2);
+        assert_eq!(list_view.favourite_items.len(), 1);
+
+        list_view.search = "Entry".into();
+        list_view.resize(&vault);
+        assert!(list_view.recently_used_items.is_empty());
     }
 
     #[test]
@@ -0,0 +1,210 @@
+//! The modal to merge another copy of the vault and to resolve the conflicts between both copies.
+use getset::Getters;
+use iced::{button, scrollable, Column, Element, Length, Row, Scrollable, Text};
+use iced_aw::Card;
+use pwduck_core::{Merge, MergeConflict, MergeItem, MergeSide};
+
+use crate::{
+    icons::Icon,
+    theme::Theme,
+    utils::{icon_button, icon_button_with_width, ButtonData, ButtonKind, SomeIf},
+    DEFAULT_COLUMN_SPACING, DEFAULT_MAX_WIDTH, DEFAULT_ROW_SPACING,
+};
+
+/// The maximum height of the list of conflicts.
+const CONFLICTS_MAX_HEIGHT: u32 = 300;
+
+/// The state of the merge modal.
+#[derive(Debug, Getters)]
+pub struct MergeModal {
+    /// The comparison of the vault with the other copy.
+    #[getset(get = "pub")]
+    merge: Merge,
+    /// The states of the [`Button`](iced::Button)s to choose the winner of each conflict.
+    conflict_states: Vec<button::State>,
+    /// The state of the [`Scrollable`](iced::Scrollable) of the conflicts.
+    scroll_state: scrollable::State,
+    /// The state of the cancel [`Button`](iced::Button).
+    cancel_button_state: button::State,
+    /// The state of the submit [`Button`](iced::Button).
+    submit_button_state: button::State,
+}
+
+/// The message send by the merge modal.
+#[derive(Clone, Debug)]
+pub enum MergeModalMessage {
+    /// Keep the version of the other copy for the conflict with the given index.
+    ToggleWinner(usize),
+    /// Close the modal.
+    Close,
+    /// Submit the merge.
+    Submit,
+}
+impl SomeIf for MergeModalMessage {}
+
+impl MergeModal {
+    /// Create the modal to review the given [`Merge`](Merge).
+    pub fn new(merge: Merge) -> Self {
+        let conflict_states = merge
+            .conflicts()
+            .iter()
+            .map(|_conflict| button::State::new())
+            .collect();
+
+        Self {
+            merge,
+            conflict_states,
+            scroll_state: scrollable::State::new(),
+            cancel_button_state: button::State::new(),
+            submit_button_state: button::State::new(),
+        }
+    }
+
+    /// Switch the copy whose version is kept for the conflict with the given index.
+    pub fn toggle_winner(&mut self, index: usize) {
+        if let Some(winner) = self.merge.conflicts().get(index).map(MergeConflict::winner) {
+            self.merge.set_winner(index, winner.other());
+        }
+    }
+
+    /// Create the view of the modal.
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn view(&mut self, theme: &dyn Theme) -> Element<'_, MergeModalMessage> {
+        let mut body = Column::new()
+            .spacing(DEFAULT_COLUMN_SPACING)
+            .push(Text::new(
+                self.merge.path().to_str().unwrap_or("Invalid path"),
+            ))
+            .push(Text::new(format!(
+                "{} items will be added and {} items will be updated from the other copy.",
+                self.merge.added(),
+                self.merge.updated()
+            )));
+
+        if !self.merge.conflicts().is_empty() {
+            let conflicts = self
+                .merge
+                .conflicts()
+                .iter()
+                .zip(self.conflict_states.iter_mut())
+                .enumerate()
+                .fold(
+                    Scrollable::new(&mut self.scroll_state)
+                        .spacing(DEFAULT_COLUMN_SPACING)
+                        .max_height(CONFLICTS_MAX_HEIGHT)
+                        .width(Length::Fill),
+                    |list, (index, (conflict, state))| {
+                        list.push(
+                            Row::new()
+                                .spacing(DEFAULT_ROW_SPACING)
+                                .align_items(iced::Align::Center)
+                                .push(Text::new(conflict_title(conflict)).width(Length::Fill))
+                                .push(icon_button_with_width(
+                                    ButtonData {
+                                        state,
+                                        icon: Icon::List,
+                                        text: match conflict.winner() {
+                                            MergeSide::Local => "Keep this vault",
+                                            MergeSide::Remote => "Keep other copy",
+                                        },
+                                        kind: ButtonKind::Normal,
+                                        on_press: Some(MergeModalMessage::ToggleWinner(index)),
+                                    },
+                                    "Change the version of this item to keep",
+                                    Length::Fill,
+                                    theme,
+                                )),
+                        )
+                    },
+                );
+
+            body = body
+                .push(Text::new(
+                    "The following items were changed in both copies. \
+                    The version changed last is kept by default:",
+                ))
+                .push(conflicts);
+        }
+
+        Card::new(Text::new("Merge another copy of the vault"), body)
+            .foot(
+                Row::new()
+                    .spacing(DEFAULT_ROW_SPACING)
+                    .push(icon_button(
+                        ButtonData {
+                            state: &mut self.cancel_button_state,
+                            icon: Icon::XSquare,
+                            text: "Cancel",
+                            kind: ButtonKind::Normal,
+                            on_press: Some(MergeModalMessage::Close),
+                        },
+                        "Cancel the merge",
+                        false,
+                        theme,
+                    ))
+                    .push(icon_button(
+                        ButtonData {
+                            state: &mut self.submit_button_state,
+                            icon: Icon::ArrowClockwise,
+                            text: "Merge",
+                            kind: ButtonKind::Primary,
+                            on_press: MergeModalMessage::Submit.some_if_not(self.merge.is_empty()),
+                        },
+                        "Take over the changes of the other copy",
+                        false,
+                        theme,
+                    )),
+            )
+            .style(theme.card())
+            .max_width(DEFAULT_MAX_WIDTH)
+            .into()
+    }
+}
+
+/// The title of the conflicting item in both copies.
+fn conflict_title(conflict: &MergeConflict) -> String {
+    let kind = match conflict.item() {
+        MergeItem::Group => "Group",
+        MergeItem::Entry => "Entry",
+    };
+    if conflict.local_title() == conflict.remote_title() {
+        format!("{}: {}", kind, conflict.local_title())
+    } else {
+        format!(
+            "{}: {} / {}",
+            kind,
+            conflict.local_title(),
+            conflict.remote_title()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pwduck_core::{MemKey, Vault};
+    use tempfile::tempdir;
+
+    use super::MergeModal;
+
+    #[test]
+    fn new() {
+        let mem_key = MemKey::with_length(1);
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("TempVault");
+        let vault = Vault::generate("password", Option::<String>::None, &mem_key, &path).unwrap();
+        let master_key = vault
+            .master_key()
+            .as_unprotected(&mem_key, vault.salt(), vault.nonce())
+            .unwrap();
+
+        // The vault compared with itself.
+        let merge = vault.prepare_merge(&path, &master_key).unwrap();
+        let mut modal = MergeModal::new(merge);
+        assert!(modal.merge().is_empty());
+        assert!(modal.conflict_states.is_empty());
+
+        // Unknown conflicts are ignored.
+        modal.toggle_winner(0);
+        assert!(modal.merge().conflicts().is_empty());
+    }
+}
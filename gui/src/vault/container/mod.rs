@@ -1,12 +1,31 @@
 //! The view of the content of a vault.
-use std::sync::MutexGuard;
+use std::{path::PathBuf, sync::MutexGuard};
 
 use iced::{Column, Command, Container, Length};
+use iced_aw::modal;
 use iced_focus::Focus;
-use pwduck_core::{AutoTypeSequenceParser, EntryBody, EntryHead, Group, MemKey, Uuid, Vault};
+use pwduck_core::{
+    AutoTypeSequenceParser, CsvFile, EntryBody, EntryHead, Group, Import, MemKey, MergeSide,
+    PWDuckCoreError, SavedSearch, SortOrder, Uuid, Vault, BACKUP_EXTENSION,
+};
+
+mod export;
+pub use export::{ExportModal, ExportModalMessage};
+
+mod external;
+pub use external::{ExternalChangesModal, ExternalChangesModalMessage};
+
+mod save_conflict;
+pub use save_conflict::{SaveConflictModal, SaveConflictModalMessage};
+
+mod import;
+pub use import::{ImportModal, ImportModalMessage};
 
 mod list;
-use list::{ListMessage, ListView};
+use list::{ListMessage, ListView, SavedSearchMessage};
+
+mod merge;
+pub use merge::{MergeModal, MergeModalMessage};
 
 mod modify_entry;
 use modify_entry::ModifyEntryView;
@@ -23,8 +42,10 @@ use toolbar::ToolBar;
 pub use toolbar::ToolBarMessage;
 
 use crate::{
-    error::PWDuckGuiError, theme::Theme, utils::default_vertical_space, Component, Platform,
-    Viewport, DEFAULT_COLUMN_PADDING, DEFAULT_COLUMN_SPACING,
+    error::{NfdError, PWDuckGuiError},
+    theme::Theme,
+    utils::default_vertical_space,
+    Component, ModalState, Platform, Viewport, DEFAULT_COLUMN_PADDING, DEFAULT_COLUMN_SPACING,
 };
 
 use self::list::ListItemMessage;
@@ -92,13 +113,74 @@ impl VaultContainer {
     }
 
     /// Save the vault to disk.
+    /// If other programs changed the items on disk, nothing is saved and the user is asked how to continue.
     fn save(
         &mut self,
         mem_key: &MutexGuard<MemKey>,
     ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
         // TODO: find a way to do this async
-        self.vault.save(mem_key)?;
-        Ok(Command::none())
+        match self.vault.save(mem_key) {
+            Err(PWDuckCoreError::Conflict(uuids)) => Ok(Command::perform(
+                async move { uuids },
+                VaultContainerMessage::SaveRefused,
+            )),
+            result => Ok(result.map(|()| Command::none())?),
+        }
+    }
+
+    /// Open the [`SaveConflictModal`](SaveConflictModal) listing the items with the given UUIDs.
+    fn open_save_conflict(
+        &self,
+        uuids: &[Uuid],
+        modal_state: &mut modal::State<ModalState>,
+    ) -> Command<VaultContainerMessage> {
+        let titles = uuids
+            .iter()
+            .map(|uuid| {
+                self.vault
+                    .groups()
+                    .get(uuid)
+                    .map(|group| group.title().clone())
+                    .or_else(|| {
+                        self.vault
+                            .entries()
+                            .get(uuid)
+                            .map(|entry| entry.title().clone())
+                    })
+                    .unwrap_or_else(|| "Deleted item".into())
+            })
+            .collect();
+
+        *modal_state = modal::State::new(ModalState::SaveConflict(SaveConflictModal::new(titles)));
+        modal_state.show(true);
+        Command::none()
+    }
+
+    /// Handle the message that was send by the [`SaveConflictModal`](SaveConflictModal).
+    fn update_save_conflict(
+        &mut self,
+        message: &SaveConflictModalMessage,
+        modal_state: &mut modal::State<ModalState>,
+        mem_key: &MutexGuard<MemKey>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        match message {
+            SaveConflictModalMessage::Close => {
+                *modal_state = modal::State::default();
+                Ok(Command::none())
+            }
+            SaveConflictModalMessage::Reload => {
+                self.reload_external_changes(Some(MergeSide::Remote), modal_state, mem_key)
+            }
+            SaveConflictModalMessage::Merge => {
+                let path = self.vault.path().clone();
+                self.open_merge(&path, modal_state, mem_key)
+            }
+            SaveConflictModalMessage::Overwrite => {
+                self.vault.save_overwriting(mem_key)?;
+                *modal_state = modal::State::default();
+                Ok(Command::none())
+            }
+        }
     }
 
     /// Create a new group and switch to the [`ModifyGroupView`](ModifyGroupView) as the current view.
@@ -141,10 +223,10 @@ impl VaultContainer {
         Command::none()
     }
 
-    /// Copy the username to the clipboard.
+    /// Copy the username of the entry identified by it's UUID to the clipboard and record the usage of the entry.
     #[cfg_attr(coverage, no_coverage)]
     fn copy_username(
-        &self,
+        &mut self,
         uuid: &Uuid,
         mem_key: &MutexGuard<MemKey>,
         clipboard: &mut iced::Clipboard,
@@ -155,20 +237,28 @@ impl VaultContainer {
             self.vault.nonce(),
         )?;
 
-        let entry_body = self.vault.unsaved_entry_bodies().get(uuid).map_or_else(
-            || pwduck_core::EntryBody::load(self.vault.path(), uuid, &master_key),
-            |dto| pwduck_core::EntryBody::decrypt(dto, &master_key),
-        )?;
+        let entry_head = self
+            .vault
+            .entries()
+            .get(uuid)
+            .ok_or(PWDuckGuiError::Option)?;
+        let entry_body = self.vault.load_entry_body(entry_head.body(), &master_key)?;
+        let username = self
+            .vault
+            .resolve_references(entry_body.username(), &master_key)?;
 
-        clipboard.write(entry_body.username().to_string());
+        clipboard.write(username.to_string());
+
+        self.vault.record_usage(uuid, &master_key)?;
+        self.list_view.resize(&self.vault);
 
         Ok(Command::none())
     }
 
-    /// Copy the password to the clipboard.
+    /// Copy the password of the entry identified by it's UUID to the clipboard and record the usage of the entry.
     #[cfg_attr(coverage, no_coverage)]
     fn copy_password(
-        &self,
+        &mut self,
         uuid: &Uuid,
         mem_key: &MutexGuard<MemKey>,
         clipboard: &mut iced::Clipboard,
@@ -179,12 +269,20 @@ impl VaultContainer {
             self.vault.nonce(),
         )?;
 
-        let entry_body = self.vault.unsaved_entry_bodies().get(uuid).map_or_else(
-            || pwduck_core::EntryBody::load(self.vault.path(), uuid, &master_key),
-            |dto| pwduck_core::EntryBody::decrypt(dto, &master_key),
-        )?;
+        let entry_head = self
+            .vault
+            .entries()
+            .get(uuid)
+            .ok_or(PWDuckGuiError::Option)?;
+        let entry_body = self.vault.load_entry_body(entry_head.body(), &master_key)?;
+        let password = self
+            .vault
+            .resolve_references(entry_body.password(), &master_key)?;
+
+        clipboard.write(password.to_string());
 
-        clipboard.write(entry_body.password().to_string());
+        self.vault.record_usage(uuid, &master_key)?;
+        self.list_view.resize(&self.vault);
 
         Ok(Command::none())
     }
@@ -199,16 +297,429 @@ impl VaultContainer {
             ToolBarMessage::Save => self.save(&crate::MEM_KEY.lock()?),
             ToolBarMessage::NewGroup => Ok(self.create_group()),
             ToolBarMessage::NewEntry => Ok(self.create_entry()),
-            ToolBarMessage::AutoFill => self.modify_entry_view.as_ref().map_or_else(
-                || Ok(Command::none()),
-                |view| self.auto_fill::<P>(view.entry_head().uuid(), &crate::MEM_KEY.lock()?),
-            ),
+            ToolBarMessage::Import => Ok(Command::perform(
+                P::nfd_choose_file(
+                    "KeePass database or XML export, CSV file or Bitwarden export".into(),
+                    vec!["kdbx".into(), "xml".into(), "csv".into(), "json".into()],
+                    None,
+                ),
+                VaultContainerMessage::ImportFileSelected,
+            )),
+            ToolBarMessage::Export => Ok(Command::perform(
+                P::nfd_choose_file(
+                    "KeePass database".into(),
+                    vec!["kdbx".into()],
+                    Some(format!("{}.kdbx", self.vault.get_name())),
+                ),
+                VaultContainerMessage::ExportFileSelected,
+            )),
+            ToolBarMessage::PlainExport => Ok(Command::perform(
+                P::nfd_choose_file(
+                    "CSV file or JSON document".into(),
+                    vec!["csv".into(), "json".into()],
+                    Some(format!("{}.csv", self.vault.get_name())),
+                ),
+                VaultContainerMessage::PlainExportFileSelected,
+            )),
+            ToolBarMessage::Backup => Ok(Command::perform(
+                P::nfd_choose_file(
+                    "PWDuck backup".into(),
+                    vec![BACKUP_EXTENSION.into()],
+                    Some(format!("{}.{}", self.vault.get_name(), BACKUP_EXTENSION)),
+                ),
+                VaultContainerMessage::BackupFileSelected,
+            )),
+            ToolBarMessage::Merge => Ok(Command::perform(
+                P::nfd_choose_folder(),
+                VaultContainerMessage::MergeDirectorySelected,
+            )),
+            ToolBarMessage::AutoFill => self
+                .modify_entry_view
+                .as_ref()
+                .map(|view| view.entry_head().uuid().clone())
+                .map_or_else(
+                    || Ok(Command::none()),
+                    |uuid| self.auto_fill::<P>(&uuid, &crate::MEM_KEY.lock()?),
+                ),
             ToolBarMessage::LockVault => {
                 PWDuckGuiError::Unreachable("ToolBarMessage".into()).into()
             }
         }
     }
 
+    /// Open the modal to map the columns of a CSV file, to preview a `KeePass` XML or `Bitwarden` export
+    /// or to enter the password of the database to import.
+    fn open_import(
+        path: PathBuf,
+        modal_state: &mut modal::State<ModalState>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        let extension = path
+            .extension()
+            .and_then(std::ffi::OsStr::to_str)
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+        let modal = match extension.as_str() {
+            "csv" => {
+                let file = CsvFile::from_path(&path)?;
+                ImportModal::csv(path, file)
+            }
+            "xml" => {
+                let import = Import::from_keepass_xml(&path)?;
+                ImportModal::preview("Import KeePass XML export", path, import)
+            }
+            "json" => {
+                let import = Import::from_bitwarden(&path)?;
+                ImportModal::preview("Import Bitwarden export", path, import)
+            }
+            _ => ImportModal::password(path),
+        };
+        *modal_state = modal::State::new(ModalState::Import(modal));
+        modal_state.show(true);
+        Ok(Command::none())
+    }
+
+    /// Import the database into the currently selected group and report the result.
+    fn submit_import(
+        &mut self,
+        modal_state: &mut modal::State<ModalState>,
+        mem_key: &MutexGuard<MemKey>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        let import = match modal_state.inner() {
+            ModalState::Import(ImportModal::Password { path, password, .. }) => {
+                Import::from_kdbx(path, password)?
+            }
+            ModalState::Import(
+                ImportModal::Csv { preview, .. } | ImportModal::Preview { preview, .. },
+            ) => preview.clone(),
+            _ => return Ok(Command::none()),
+        };
+
+        let master_key = self.vault.master_key().as_unprotected(
+            mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        self.vault
+            .import(&import, self.list_view.selected_group_uuid(), &master_key)?;
+        self.refresh_list_view();
+
+        *modal_state = modal::State::new(ModalState::Import(ImportModal::report(&import)));
+        modal_state.show(true);
+        Ok(Command::none())
+    }
+
+    /// Handle the message that was send by the [`ImportModal`](ImportModal).
+    fn update_import(
+        &mut self,
+        message: ImportModalMessage,
+        modal_state: &mut modal::State<ModalState>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        match message {
+            ImportModalMessage::PasswordInput(password) => {
+                if let ModalState::Import(modal) = modal_state.inner_mut() {
+                    modal.update_password(password);
+                }
+                Ok(Command::none())
+            }
+            ImportModalMessage::PasswordShow => {
+                if let ModalState::Import(modal) = modal_state.inner_mut() {
+                    modal.toggle_password_visibility();
+                }
+                Ok(Command::none())
+            }
+            ImportModalMessage::PresetSelected(preset) => {
+                if let ModalState::Import(modal) = modal_state.inner_mut() {
+                    modal.select_preset(preset);
+                }
+                Ok(Command::none())
+            }
+            ImportModalMessage::CycleColumn(column) => {
+                if let ModalState::Import(modal) = modal_state.inner_mut() {
+                    modal.cycle_column(column);
+                }
+                Ok(Command::none())
+            }
+            ImportModalMessage::Close => {
+                *modal_state = modal::State::default();
+                Ok(Command::none())
+            }
+            ImportModalMessage::Submit => self.submit_import(modal_state, &crate::MEM_KEY.lock()?),
+        }
+    }
+
+    /// Open the modal to enter the password of the database to export.
+    fn open_export(
+        path: PathBuf,
+        modal_state: &mut modal::State<ModalState>,
+    ) -> Command<VaultContainerMessage> {
+        *modal_state = modal::State::new(ModalState::Export(ExportModal::password(path)));
+        modal_state.show(true);
+        Command::none()
+    }
+
+    /// Open the modal to warn about the unencrypted export and to confirm it with the master password.
+    fn open_plain_export(
+        path: PathBuf,
+        modal_state: &mut modal::State<ModalState>,
+    ) -> Command<VaultContainerMessage> {
+        *modal_state = modal::State::new(ModalState::Export(ExportModal::plain(path)));
+        modal_state.show(true);
+        Command::none()
+    }
+
+    /// Export the currently listed groups and entries unencrypted and report the result.
+    /// The search result is exported while searching, else the selected group with all of its descendants.
+    /// A file with the extension `json` is written as a JSON document, every other file as CSV.
+    fn submit_plain_export(
+        &self,
+        modal_state: &mut modal::State<ModalState>,
+        mem_key: &MutexGuard<MemKey>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        let confirmed = match modal_state.inner() {
+            ModalState::Export(modal) if !modal.is_password_valid() => return Ok(Command::none()),
+            ModalState::Export(ExportModal::Plain { path, password, .. }) => self
+                .vault
+                .verify_password(password, mem_key)?
+                .then(|| path.clone()),
+            _ => return Ok(Command::none()),
+        };
+        let path = match confirmed {
+            Some(path) => path,
+            None => {
+                if let ModalState::Export(modal) = modal_state.inner_mut() {
+                    modal.set_password_wrong();
+                }
+                return Ok(Command::none());
+            }
+        };
+
+        let master_key = self.vault.master_key().as_unprotected(
+            mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        let selected_group_uuid = self.list_view.selected_group_uuid();
+        let export = if self.list_view.search().is_empty() {
+            self.vault.export(selected_group_uuid, &master_key)?
+        } else {
+            let item_list = self
+                .vault
+                .get_item_list_for(selected_group_uuid, Some(self.list_view.search()));
+            self.vault.export_item_list(&item_list, &master_key)?
+        };
+
+        let is_json = path
+            .extension()
+            .map_or(false, |extension| extension.eq_ignore_ascii_case("json"));
+        if is_json {
+            export.to_json(&path)?;
+        } else {
+            export.to_csv(&path)?;
+        }
+
+        *modal_state = modal::State::new(ModalState::Export(ExportModal::report(path, &export)));
+        modal_state.show(true);
+        Ok(Command::none())
+    }
+
+    /// Export the currently selected group into a new database and report the result.
+    fn submit_export(
+        &self,
+        modal_state: &mut modal::State<ModalState>,
+        mem_key: &MutexGuard<MemKey>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        let (path, export) = match modal_state.inner() {
+            ModalState::Export(modal) if !modal.is_password_valid() => return Ok(Command::none()),
+            ModalState::Export(ExportModal::Password { path, password, .. }) => {
+                let master_key = self.vault.master_key().as_unprotected(
+                    mem_key,
+                    self.vault.salt(),
+                    self.vault.nonce(),
+                )?;
+                let export = self
+                    .vault
+                    .export(self.list_view.selected_group_uuid(), &master_key)?;
+                export.to_kdbx(path, password)?;
+                (path.clone(), export)
+            }
+            _ => return Ok(Command::none()),
+        };
+
+        *modal_state = modal::State::new(ModalState::Export(ExportModal::report(path, &export)));
+        modal_state.show(true);
+        Ok(Command::none())
+    }
+
+    /// Back up the saved state of the vault into the file and report the location of the backup.
+    fn export_backup(
+        &self,
+        path: PathBuf,
+        modal_state: &mut modal::State<ModalState>,
+        mem_key: &MutexGuard<MemKey>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        let master_key = self.vault.master_key().as_unprotected(
+            mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        self.vault.export_backup(&path, &master_key)?;
+
+        *modal_state = modal::State::new(ModalState::Export(ExportModal::backup(path)));
+        modal_state.show(true);
+        Ok(Command::none())
+    }
+
+    /// Compare the vault with the copy in the directory and open the modal to review the merge.
+    fn open_merge(
+        &self,
+        path: &std::path::Path,
+        modal_state: &mut modal::State<ModalState>,
+        mem_key: &MutexGuard<MemKey>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        let master_key = self.vault.master_key().as_unprotected(
+            mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        let merge = self.vault.prepare_merge(path, &master_key)?;
+
+        *modal_state = modal::State::new(ModalState::Merge(Box::new(MergeModal::new(merge))));
+        modal_state.show(true);
+        Ok(Command::none())
+    }
+
+    /// Take over the changes of the other copy as reviewed in the [`MergeModal`](MergeModal).
+    /// The merged changes are unsaved until the vault is saved.
+    fn submit_merge(
+        &mut self,
+        modal_state: &mut modal::State<ModalState>,
+        mem_key: &MutexGuard<MemKey>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        if let ModalState::Merge(modal) = modal_state.inner() {
+            let master_key = self.vault.master_key().as_unprotected(
+                mem_key,
+                self.vault.salt(),
+                self.vault.nonce(),
+            )?;
+            self.vault.merge(modal.merge(), &master_key)?;
+            self.refresh_list_view();
+        }
+
+        *modal_state = modal::State::default();
+        Ok(Command::none())
+    }
+
+    /// Reload the groups, entries, saved searches and settings that were changed on disk by another program.
+    /// The modal to resolve the conflicts with unsaved changes is opened, if no other modal is open.
+    ///
+    /// It expects:
+    ///  - The side to keep for conflicting items or `None` to ask the user
+    fn reload_external_changes(
+        &mut self,
+        resolution: Option<MergeSide>,
+        modal_state: &mut modal::State<ModalState>,
+        mem_key: &MutexGuard<MemKey>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        let master_key = self.vault.master_key().as_unprotected(
+            mem_key,
+            self.vault.salt(),
+            self.vault.nonce(),
+        )?;
+        let changes = self
+            .vault
+            .reload_external_changes(&master_key, resolution)?;
+        if !changes.is_empty() {
+            self.refresh_list_view();
+        }
+
+        if resolution.is_some() {
+            *modal_state = modal::State::default();
+        } else if !changes.conflicts().is_empty() && matches!(modal_state.inner(), ModalState::None)
+        {
+            *modal_state = modal::State::new(ModalState::ExternalChanges(
+                ExternalChangesModal::new(changes.conflicts().clone()),
+            ));
+            modal_state.show(true);
+        }
+        Ok(Command::none())
+    }
+
+    /// Handle the message that was send by the [`ExternalChangesModal`](ExternalChangesModal).
+    fn update_external_changes(
+        &mut self,
+        message: &ExternalChangesModalMessage,
+        modal_state: &mut modal::State<ModalState>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        let resolution = match message {
+            ExternalChangesModalMessage::Keep => MergeSide::Local,
+            ExternalChangesModalMessage::Reload => MergeSide::Remote,
+        };
+        self.reload_external_changes(Some(resolution), modal_state, &crate::MEM_KEY.lock()?)
+    }
+
+    /// Handle the message that was send by the [`MergeModal`](MergeModal).
+    fn update_merge(
+        &mut self,
+        message: MergeModalMessage,
+        modal_state: &mut modal::State<ModalState>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        match message {
+            MergeModalMessage::ToggleWinner(index) => {
+                if let ModalState::Merge(modal) = modal_state.inner_mut() {
+                    modal.toggle_winner(index);
+                }
+                Ok(Command::none())
+            }
+            MergeModalMessage::Close => {
+                *modal_state = modal::State::default();
+                Ok(Command::none())
+            }
+            MergeModalMessage::Submit => self.submit_merge(modal_state, &crate::MEM_KEY.lock()?),
+        }
+    }
+
+    /// Handle the message that was send by the [`ExportModal`](ExportModal).
+    fn update_export(
+        &self,
+        message: ExportModalMessage,
+        modal_state: &mut modal::State<ModalState>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        match message {
+            ExportModalMessage::PasswordInput(password) => {
+                if let ModalState::Export(modal) = modal_state.inner_mut() {
+                    modal.update_password(password);
+                }
+                Ok(Command::none())
+            }
+            ExportModalMessage::PasswordConfirmInput(password) => {
+                if let ModalState::Export(modal) = modal_state.inner_mut() {
+                    modal.update_password_confirm(password);
+                }
+                Ok(Command::none())
+            }
+            ExportModalMessage::PasswordShow => {
+                if let ModalState::Export(modal) = modal_state.inner_mut() {
+                    modal.toggle_password_visibility();
+                }
+                Ok(Command::none())
+            }
+            ExportModalMessage::Close => {
+                *modal_state = modal::State::default();
+                Ok(Command::none())
+            }
+            ExportModalMessage::Submit => {
+                if matches!(
+                    modal_state.inner(),
+                    ModalState::Export(ExportModal::Plain { .. })
+                ) {
+                    self.submit_plain_export(modal_state, &crate::MEM_KEY.lock()?)
+                } else {
+                    self.submit_export(modal_state, &crate::MEM_KEY.lock()?)
+                }
+            }
+        }
+    }
+
     /// Update the search and replace it with the given value. The [`ListView`](ListView) will be resized.
     fn update_search(&mut self, search: String) -> Command<VaultContainerMessage> {
         let _ = self.list_view.set_search(search);
@@ -216,6 +727,64 @@ impl VaultContainer {
         Command::none()
     }
 
+    /// Change the [`SortOrder`](SortOrder) of the unlocked vault. The [`ListView`](ListView) will be resized.
+    fn update_sort_order(
+        &mut self,
+        sort_order: SortOrder,
+        mem_key: &MutexGuard<MemKey>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        self.vault.set_sort_order(sort_order, mem_key)?;
+        self.list_view.resize(&self.vault);
+        Ok(Command::none())
+    }
+
+    /// Update the name of the saved search and replace it with the given value.
+    fn update_saved_search_name(&mut self, name: String) -> Command<VaultContainerMessage> {
+        let _ = self.list_view.set_saved_search_name(name);
+        Command::none()
+    }
+
+    /// Save the current search as a new [`SavedSearch`](SavedSearch) of the unlocked vault.
+    fn save_search(&mut self) -> Command<VaultContainerMessage> {
+        let title = self.list_view.saved_search_name().trim().to_owned();
+        if title.is_empty() || self.list_view.search().is_empty() {
+            return Command::none();
+        }
+
+        self.vault.insert_saved_search(SavedSearch::new(
+            Uuid::new(self.vault.path()),
+            title,
+            self.list_view.search().clone(),
+        ));
+        self.list_view.saved_search_name_mut().clear();
+        self.list_view.resize(&self.vault);
+        Command::none()
+    }
+
+    /// Select the [`SavedSearch`](SavedSearch) identified by the UUID.
+    /// Its query replaces the current search, so the results are evaluated live.
+    fn select_saved_search(
+        &mut self,
+        uuid: &Uuid,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        let query = self
+            .vault
+            .saved_searches()
+            .get(uuid)
+            .ok_or(PWDuckGuiError::Option)?
+            .query()
+            .clone();
+
+        Ok(self.update_search(query))
+    }
+
+    /// Delete the [`SavedSearch`](SavedSearch) identified by the UUID.
+    fn delete_saved_search(&mut self, uuid: &Uuid) -> Command<VaultContainerMessage> {
+        self.vault.delete_saved_search(uuid);
+        self.list_view.resize(&self.vault);
+        Command::none()
+    }
+
     /// Go back to the parent group of the currently selected group of the unlocked vault.
     fn go_to_parent_group(&mut self) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
         let group = self
@@ -283,14 +852,7 @@ impl VaultContainer {
         )?;
 
         // Load body from cache if exists, otherwise load from file system.
-        let entry_body = self
-            .vault
-            .unsaved_entry_bodies()
-            .get(entry_head.body())
-            .map_or_else(
-                || pwduck_core::EntryBody::load(self.vault.path(), entry_head.body(), &master_key),
-                |dto| pwduck_core::EntryBody::decrypt(dto, &master_key),
-            )?;
+        let entry_body = self.vault.load_entry_body(entry_head.body(), &master_key)?;
 
         self.modify_entry_view = Some(Box::new(ModifyEntryView::with(
             modify_entry::State::Modify,
@@ -301,17 +863,39 @@ impl VaultContainer {
         Ok(Command::none())
     }
 
-    /// Autotype the credentials of the entry identified by it's UUID.
-    fn auto_fill<P: Platform + 'static>(
-        &self,
+    /// Open the entry referenced by a field of the currently modified entry.
+    ///
+    /// This is only possible if the currently modified entry contains no unsaved changes.
+    fn open_reference(
+        &mut self,
         uuid: &Uuid,
         mem_key: &MutexGuard<MemKey>,
     ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
-        let entry_head = self
-            .vault
-            .entries()
-            .get(uuid)
-            .ok_or(PWDuckGuiError::Option)?;
+        if self
+            .modify_entry_view
+            .as_ref()
+            .map_or(false, |view| view.contains_unsaved_changes())
+        {
+            return Err(PWDuckGuiError::String(
+                "Submit or cancel the changes of this entry before opening the referenced entry"
+                    .into(),
+            ));
+        }
+
+        self.select_entry(uuid, mem_key)
+    }
+
+    /// Autotype the credentials of the entry identified by it's UUID and record the usage of the entry.
+    fn auto_fill<P: Platform + 'static>(
+        &mut self,
+        uuid: &Uuid,
+        mem_key: &MutexGuard<MemKey>,
+    ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        let entry_head = self
+            .vault
+            .entries()
+            .get(uuid)
+            .ok_or(PWDuckGuiError::Option)?;
 
         let master_key = self.vault.master_key().as_unprotected(
             mem_key,
@@ -319,14 +903,8 @@ impl VaultContainer {
             self.vault.nonce(),
         )?;
 
-        let entry_body = self
-            .vault
-            .unsaved_entry_bodies()
-            .get(entry_head.body())
-            .map_or_else(
-                || pwduck_core::EntryBody::load(self.vault.path(), entry_head.body(), &master_key),
-                |dto| pwduck_core::EntryBody::decrypt(dto, &master_key),
-            )?;
+        let entry_body = self.vault.load_entry_body(entry_head.body(), &master_key)?;
+        let entry_body = self.vault.resolve_entry_body(&entry_body, &master_key)?;
 
         let sequence = AutoTypeSequenceParser::parse_sequence(
             entry_head.auto_type_sequence(),
@@ -334,6 +912,9 @@ impl VaultContainer {
             &entry_body,
         )?;
 
+        self.vault.record_usage(uuid, &master_key)?;
+        self.list_view.resize(&self.vault);
+
         Ok(Command::perform(
             P::auto_type(sequence),
             VaultContainerMessage::AutoTypeResult,
@@ -348,6 +929,43 @@ impl VaultContainer {
         Command::none()
     }
 
+    /// Undo the most recent unsaved change of the vault.
+    ///
+    /// This is only possible while the [`ListView`](ListView) is displayed.
+    fn undo(&mut self) -> Command<VaultContainerMessage> {
+        if self.current_view == CurrentView::ListView && self.vault.undo() {
+            self.refresh_list_view();
+        }
+        Command::none()
+    }
+
+    /// Redo the most recently undone change of the vault.
+    ///
+    /// This is only possible while the [`ListView`](ListView) is displayed.
+    fn redo(&mut self) -> Command<VaultContainerMessage> {
+        if self.current_view == CurrentView::ListView && self.vault.redo() {
+            self.refresh_list_view();
+        }
+        Command::none()
+    }
+
+    /// Refresh the [`ListView`](ListView) after the content of the vault was changed.
+    ///
+    /// If the selected group no longer exists, the root will be selected.
+    fn refresh_list_view(&mut self) {
+        if !self
+            .vault
+            .groups()
+            .contains_key(self.list_view.selected_group_uuid())
+        {
+            if let Some(root) = self.vault.get_root_uuid() {
+                let _ = self.list_view.set_selected_group_uuid(root);
+            }
+        }
+        self.list_view.resize(&self.vault);
+        self.list_view.group_tree_mut().refresh(&self.vault);
+    }
+
     /// Handle the message that was send by the group tree.
     fn update_group_tree(
         &mut self,
@@ -394,6 +1012,20 @@ impl VaultContainer {
     ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
         match message {
             ListMessage::SearchInput(search) => Ok(self.update_search(search)),
+            ListMessage::CycleSortKey => {
+                let sort_order = self.vault.settings().sort_order();
+                self.update_sort_order(
+                    SortOrder::new(sort_order.key().next(), sort_order.direction()),
+                    &crate::MEM_KEY.lock()?,
+                )
+            }
+            ListMessage::ToggleSortDirection => {
+                let sort_order = self.vault.settings().sort_order();
+                self.update_sort_order(
+                    SortOrder::new(sort_order.key(), sort_order.direction().toggle()),
+                    &crate::MEM_KEY.lock()?,
+                )
+            }
             ListMessage::Back => self.go_to_parent_group(),
             ListMessage::EditGroup => self.edit_group(),
             ListMessage::ListItemMessage(message) => {
@@ -404,6 +1036,14 @@ impl VaultContainer {
                 .update_group_tree(message)?
                 .map(ListMessage::GroupTreeMessage)
                 .map(VaultContainerMessage::List)),
+            ListMessage::SavedSearchNameInput(name) => Ok(self.update_saved_search_name(name)),
+            ListMessage::SaveSearch => Ok(self.save_search()),
+            ListMessage::SavedSearchMessage(SavedSearchMessage::Selected(uuid)) => {
+                self.select_saved_search(&uuid)
+            }
+            ListMessage::SavedSearchMessage(SavedSearchMessage::Delete(uuid)) => {
+                Ok(self.delete_saved_search(&uuid))
+            }
         }
     }
 
@@ -456,6 +1096,10 @@ impl VaultContainer {
         modal_state: &mut iced_aw::modal::State<crate::ModalState>,
         clipboard: &mut iced::Clipboard,
     ) -> Result<Command<VaultContainerMessage>, PWDuckGuiError> {
+        if let ModifyEntryMessage::OpenReference(uuid) = message {
+            return self.open_reference(uuid, &crate::MEM_KEY.lock()?);
+        }
+
         let vault = &mut self.vault;
         let cmd = self
             .modify_entry_view
@@ -505,6 +1149,34 @@ pub enum VaultContainerMessage {
     ModifyEntry(ModifyEntryMessage),
     /// The result of the autotyper.
     AutoTypeResult(Result<(), PWDuckGuiError>),
+    /// The database to import was selected by the native file dialog.
+    ImportFileSelected(Result<PathBuf, NfdError>),
+    /// The message that is send by the ImportModal.
+    Import(ImportModalMessage),
+    /// The location of the database to export was selected by the native file dialog.
+    ExportFileSelected(Result<PathBuf, NfdError>),
+    /// The location of the unencrypted export was selected.
+    PlainExportFileSelected(Result<PathBuf, NfdError>),
+    /// The location of the backup was selected by the native file dialog.
+    BackupFileSelected(Result<PathBuf, NfdError>),
+    /// The message that is send by the ExportModal.
+    Export(ExportModalMessage),
+    /// The directory of the copy to merge was selected by the native file dialog.
+    MergeDirectorySelected(Result<PathBuf, NfdError>),
+    /// The message that is send by the MergeModal.
+    Merge(MergeModalMessage),
+    /// Groups or entries were changed on disk by another program.
+    VaultChanged,
+    /// The message that is send by the ExternalChangesModal.
+    ExternalChanges(ExternalChangesModalMessage),
+    /// Saving was refused, because the items with the UUIDs were changed on disk by another program.
+    SaveRefused(Vec<Uuid>),
+    /// The message that is send by the SaveConflictModal.
+    SaveConflict(SaveConflictModalMessage),
+    /// Undo the most recent unsaved change.
+    Undo,
+    /// Redo the most recently undone change.
+    Redo,
 }
 
 #[cfg_attr(test, mockable)]
@@ -562,6 +1234,62 @@ impl Component for VaultContainer {
                 result?;
                 Ok(Command::none())
             }
+
+            VaultContainerMessage::ImportFileSelected(Ok(path)) => {
+                Self::open_import(path, modal_state)
+            }
+
+            VaultContainerMessage::ImportFileSelected(Err(_err)) => Ok(Command::none()),
+
+            VaultContainerMessage::Import(message) => self.update_import(message, modal_state),
+
+            VaultContainerMessage::ExportFileSelected(Ok(path)) => {
+                Ok(Self::open_export(path, modal_state))
+            }
+
+            VaultContainerMessage::ExportFileSelected(Err(_err)) => Ok(Command::none()),
+
+            VaultContainerMessage::PlainExportFileSelected(Ok(path)) => {
+                Ok(Self::open_plain_export(path, modal_state))
+            }
+
+            VaultContainerMessage::PlainExportFileSelected(Err(_err)) => Ok(Command::none()),
+
+            VaultContainerMessage::BackupFileSelected(Ok(path)) => {
+                self.export_backup(path, modal_state, &crate::MEM_KEY.lock()?)
+            }
+
+            VaultContainerMessage::BackupFileSelected(Err(_err)) => Ok(Command::none()),
+
+            VaultContainerMessage::Export(message) => self.update_export(message, modal_state),
+
+            VaultContainerMessage::MergeDirectorySelected(Ok(path)) => {
+                self.open_merge(&path, modal_state, &crate::MEM_KEY.lock()?)
+            }
+
+            VaultContainerMessage::MergeDirectorySelected(Err(_err)) => Ok(Command::none()),
+
+            VaultContainerMessage::Merge(message) => self.update_merge(message, modal_state),
+
+            VaultContainerMessage::VaultChanged => {
+                self.reload_external_changes(None, modal_state, &crate::MEM_KEY.lock()?)
+            }
+
+            VaultContainerMessage::ExternalChanges(message) => {
+                self.update_external_changes(&message, modal_state)
+            }
+
+            VaultContainerMessage::SaveRefused(uuids) => {
+                Ok(self.open_save_conflict(&uuids, modal_state))
+            }
+
+            VaultContainerMessage::SaveConflict(message) => {
+                self.update_save_conflict(&message, modal_state, &crate::MEM_KEY.lock()?)
+            }
+
+            VaultContainerMessage::Undo => Ok(self.undo()),
+
+            VaultContainerMessage::Redo => Ok(self.redo()),
         }
     }
 
@@ -607,7 +1335,7 @@ impl Component for VaultContainer {
 
             CurrentView::ModifyEntry => match &mut self.modify_entry_view {
                 Some(modify_enty_view) => modify_enty_view
-                    .view::<P>(self.list_view.selected_group_uuid(), theme)
+                    .view::<P>(&self.vault, self.list_view.selected_group_uuid(), theme)
                     .map(VaultContainerMessage::ModifyEntry),
                 None => unreachable!(),
             },
@@ -634,6 +1362,7 @@ mod tests {
         any::{Any, TypeId},
         cell::RefCell,
         collections::HashMap,
+        path::PathBuf,
         sync::Mutex,
     };
 
@@ -641,16 +1370,22 @@ mod tests {
     use mocktopus::mocking::*;
     use modify_entry::{ModifyEntryMessage, ModifyEntryModalMessage};
     use modify_group::{ModifyGroupMessage, ModifyGroupModalMessage};
-    use pwduck_core::{uuid, EntryHead, MemKey, Vault};
+    use pwduck_core::{
+        uuid, EntryHead, MemKey, MergeSide, SortDirection, SortKey, SortOrder, Vault,
+    };
     use tempfile::{tempdir, TempDir};
 
     use crate::{error::PWDuckGuiError, Component, TestPlatform};
 
     use super::{
-        list::{GroupTree, GroupTreeMessage, ListItemMessage, ListMessage, ListView},
+        list::{
+            GroupTree, GroupTreeMessage, ListItemMessage, ListMessage, ListView, SavedSearchMessage,
+        },
         modify_entry::{self, ModifyEntryView},
         modify_group::{self, ModifyGroupView},
-        CurrentView, ToolBarMessage, VaultContainer, VaultContainerMessage,
+        CurrentView, ExportModal, ExportModalMessage, ExternalChangesModal, ImportModal,
+        ImportModalMessage, MergeModalMessage, SaveConflictModalMessage, ToolBarMessage,
+        VaultContainer, VaultContainerMessage,
     };
 
     thread_local! {
@@ -918,69 +1653,670 @@ mod tests {
                 .update_toolbar::<TestPlatform>(&ToolBarMessage::Save, &mut clipboard);
             assert_eq!(call_map.borrow()[&VaultContainer::save.type_id()], 1);
 
-            // New group
-            assert_eq!(
-                call_map.borrow()[&VaultContainer::create_group.type_id()],
-                0
-            );
-            let _ = vault_container
-                .update_toolbar::<TestPlatform>(&ToolBarMessage::NewGroup, &mut clipboard);
-            assert_eq!(
-                call_map.borrow()[&VaultContainer::create_group.type_id()],
-                1
-            );
+            // New group
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::create_group.type_id()],
+                0
+            );
+            let _ = vault_container
+                .update_toolbar::<TestPlatform>(&ToolBarMessage::NewGroup, &mut clipboard);
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::create_group.type_id()],
+                1
+            );
+
+            // New entry
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::create_entry.type_id()],
+                0
+            );
+            let _ = vault_container
+                .update_toolbar::<TestPlatform>(&ToolBarMessage::NewEntry, &mut clipboard);
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::create_entry.type_id()],
+                1
+            );
+
+            // Auto fill
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::auto_fill::<TestPlatform>.type_id()],
+                0
+            );
+            // A non existent ModifyEntryView can not auto fill.
+            let _ = vault_container
+                .update_toolbar::<TestPlatform>(&ToolBarMessage::AutoFill, &mut clipboard);
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::auto_fill::<TestPlatform>.type_id()],
+                0
+            );
+            // An existent ModifyEntryView can auto fill.
+            vault_container.modify_entry_view = Some(Box::new(ModifyEntryView::with(
+                modify_entry::State::Create,
+                pwduck_core::EntryHead::new(
+                    [1; uuid::SIZE].into(),
+                    root.clone(),
+                    "".into(),
+                    [2; uuid::SIZE].into(),
+                ),
+                pwduck_core::EntryBody::new([2; uuid::SIZE].into(), "".into(), "".into()),
+            )));
+            let _ = vault_container
+                .update_toolbar::<TestPlatform>(&ToolBarMessage::AutoFill, &mut clipboard);
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::auto_fill::<TestPlatform>.type_id()],
+                1
+            );
+
+            // Import
+            assert!(vault_container
+                .update_toolbar::<TestPlatform>(&ToolBarMessage::Import, &mut clipboard)
+                .is_ok());
+
+            // Export
+            assert!(vault_container
+                .update_toolbar::<TestPlatform>(&ToolBarMessage::Export, &mut clipboard)
+                .is_ok());
+
+            // Plain export
+            assert!(vault_container
+                .update_toolbar::<TestPlatform>(&ToolBarMessage::PlainExport, &mut clipboard)
+                .is_ok());
+
+            // Merge
+            assert!(vault_container
+                .update_toolbar::<TestPlatform>(&ToolBarMessage::Merge, &mut clipboard)
+                .is_ok());
+
+            // Lock vault
+            let res = vault_container
+                .update_toolbar::<TestPlatform>(&ToolBarMessage::LockVault, &mut clipboard)
+                .expect_err("Should fail");
+            match res {
+                PWDuckGuiError::Unreachable(_) => {}
+                _ => panic!("Should contain unreachable warning."),
+            }
+        })
+    }
+
+    #[test]
+    fn import() {
+        let mem_key = MemKey::with_length(1);
+        let (_dir, vault) = default_vault(&mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let mut modal_state = iced_aw::modal::State::new(crate::ModalState::default());
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../core/tests/resources/argon2id_chacha20.kdbx");
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+        let group_count = vault_container.vault.get_groups_of(&root).len();
+        let entry_count = vault_container.vault.get_entries_of(&root).len();
+
+        // Selecting a file opens the password modal.
+        let _ = VaultContainer::open_import(path, &mut modal_state).unwrap();
+        assert!(matches!(
+            modal_state.inner(),
+            crate::ModalState::Import(ImportModal::Password { .. })
+        ));
+
+        let _ = vault_container
+            .update_import(
+                ImportModalMessage::PasswordInput("password".into()),
+                &mut modal_state,
+            )
+            .unwrap();
+        let _ = vault_container
+            .update_import(ImportModalMessage::PasswordShow, &mut modal_state)
+            .unwrap();
+        if let crate::ModalState::Import(ImportModal::Password {
+            password,
+            password_show,
+            ..
+        }) = modal_state.inner()
+        {
+            assert_eq!(password.as_str(), "password");
+            assert!(password_show);
+        } else {
+            panic!("Modal should be the password modal");
+        }
+
+        let mutex_mem_key = Mutex::new(mem_key);
+        let _ = vault_container
+            .submit_import(&mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+
+        // The content of the database is imported into the selected group.
+        assert_eq!(
+            vault_container.vault.get_groups_of(&root).len(),
+            group_count + 1
+        );
+        assert_eq!(
+            vault_container.vault.get_entries_of(&root).len(),
+            entry_count + 1
+        );
+        if let crate::ModalState::Import(ImportModal::Report {
+            group_count,
+            entry_count,
+            skipped,
+            ..
+        }) = modal_state.inner()
+        {
+            assert_eq!(*group_count, 2);
+            assert_eq!(*entry_count, 2);
+            assert!(!skipped.is_empty());
+        } else {
+            panic!("Modal should be the report modal");
+        }
+
+        let _ = vault_container
+            .update_import(ImportModalMessage::Close, &mut modal_state)
+            .unwrap();
+        assert!(matches!(modal_state.inner(), crate::ModalState::None));
+    }
+
+    #[test]
+    fn csv_import() {
+        let mem_key = MemKey::with_length(1);
+        let (dir, vault) = default_vault(&mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let mut modal_state = iced_aw::modal::State::new(crate::ModalState::default());
+        let path = dir.path().join("passwords.csv");
+        std::fs::write(
+            &path,
+            "folder,name,login_uri,login_username,login_password\n\
+             Work/Servers,Database,https://db.example.com,root,hunter2\n",
+        )
+        .unwrap();
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+        let group_count = vault_container.vault.get_groups_of(&root).len();
+
+        // Selecting a CSV file opens the column mapping.
+        let _ = VaultContainer::open_import(path, &mut modal_state).unwrap();
+        assert!(matches!(
+            modal_state.inner(),
+            crate::ModalState::Import(ImportModal::Csv { .. })
+        ));
+
+        let mutex_mem_key = Mutex::new(mem_key);
+        let _ = vault_container
+            .submit_import(&mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+
+        // The group path is created as nested groups.
+        let groups = vault_container.vault.get_groups_of(&root);
+        assert_eq!(groups.len(), group_count + 1);
+        let work = groups
+            .iter()
+            .find(|group| group.title() == "Work")
+            .unwrap()
+            .uuid()
+            .clone();
+        let servers = vault_container.vault.get_groups_of(&work)[0].uuid().clone();
+        assert_eq!(
+            vault_container.vault.get_entries_of(&servers)[0].title(),
+            "Database"
+        );
+        assert!(matches!(
+            modal_state.inner(),
+            crate::ModalState::Import(ImportModal::Report { .. })
+        ));
+    }
+
+    #[test]
+    fn bitwarden_import() {
+        let mem_key = MemKey::with_length(1);
+        let (_dir, vault) = default_vault(&mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let mut modal_state = iced_aw::modal::State::new(crate::ModalState::default());
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../core/tests/resources/bitwarden.json");
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+        let entry_count = vault_container.vault.get_entries_of(&root).len();
+
+        // Selecting a JSON file opens the preview of the Bitwarden export.
+        let _ = VaultContainer::open_import(path, &mut modal_state).unwrap();
+        assert!(matches!(
+            modal_state.inner(),
+            crate::ModalState::Import(ImportModal::Preview { .. })
+        ));
+
+        let mutex_mem_key = Mutex::new(mem_key);
+        let _ = vault_container
+            .submit_import(&mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+
+        let entries = vault_container.vault.get_entries_of(&root);
+        assert_eq!(entries.len(), entry_count + 1);
+        assert!(entries.iter().any(|entry| entry.title() == "Forum"));
+        if let crate::ModalState::Import(ImportModal::Report {
+            entry_count,
+            skipped,
+            ..
+        }) = modal_state.inner()
+        {
+            assert_eq!(*entry_count, 3);
+            assert!(skipped.contains(&"Credit card: Card".to_owned()));
+        } else {
+            panic!("Modal should be the report modal");
+        }
+    }
+
+    #[test]
+    fn keepass_xml_import() {
+        let mem_key = MemKey::with_length(1);
+        let (_dir, vault) = default_vault(&mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let mut modal_state = iced_aw::modal::State::new(crate::ModalState::default());
+        let path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../core/tests/resources/keepass.xml");
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+        let group_count = vault_container.vault.get_groups_of(&root).len();
+
+        // Selecting an XML file opens the preview of the KeePass export.
+        let _ = VaultContainer::open_import(path, &mut modal_state).unwrap();
+        if let crate::ModalState::Import(ImportModal::Preview { title, preview, .. }) =
+            modal_state.inner()
+        {
+            assert_eq!(*title, "Import KeePass XML export");
+            assert_eq!(preview.entry_count(), 2);
+        } else {
+            panic!("Modal should be the preview modal");
+        }
+
+        let mutex_mem_key = Mutex::new(mem_key);
+        let _ = vault_container
+            .submit_import(&mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+
+        let groups = vault_container.vault.get_groups_of(&root);
+        assert_eq!(groups.len(), group_count + 1);
+        assert!(groups.iter().any(|group| group.title() == "Servers"));
+        assert!(matches!(
+            modal_state.inner(),
+            crate::ModalState::Import(ImportModal::Report { .. })
+        ));
+    }
+
+    #[test]
+    fn export() {
+        let mem_key = MemKey::with_length(1);
+        let (dir, vault) = default_vault(&mem_key);
+        let mut modal_state = iced_aw::modal::State::new(crate::ModalState::default());
+        let path = dir.path().join("export.kdbx");
+
+        let vault_container = VaultContainer::new(Box::new(vault));
+
+        // Selecting a file opens the password modal.
+        let _ = VaultContainer::open_export(path.clone(), &mut modal_state);
+        assert!(matches!(
+            modal_state.inner(),
+            crate::ModalState::Export(ExportModal::Password { .. })
+        ));
+
+        let _ = vault_container
+            .update_export(
+                ExportModalMessage::PasswordInput("password".into()),
+                &mut modal_state,
+            )
+            .unwrap();
+        let _ = vault_container
+            .update_export(ExportModalMessage::PasswordShow, &mut modal_state)
+            .unwrap();
+
+        // The export is not submitted until the password is confirmed.
+        let mutex_mem_key = Mutex::new(mem_key);
+        let _ = vault_container
+            .submit_export(&mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+        assert!(!path.exists());
+
+        let _ = vault_container
+            .update_export(
+                ExportModalMessage::PasswordConfirmInput("password".into()),
+                &mut modal_state,
+            )
+            .unwrap();
+        let _ = vault_container
+            .submit_export(&mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+        assert!(path.exists());
+
+        if let crate::ModalState::Export(ExportModal::Report {
+            group_count,
+            entry_count,
+            ..
+        }) = modal_state.inner()
+        {
+            assert_eq!(*group_count, DEFAULT_GROUP_COUNT as usize);
+            assert_eq!(*entry_count, DEFAULT_ENTRY_COUNT as usize);
+        } else {
+            panic!("Modal should be the report modal");
+        }
+
+        let import = pwduck_core::Import::from_kdbx(&path, "password").unwrap();
+        assert_eq!(import.group_count(), DEFAULT_GROUP_COUNT as usize);
+        assert_eq!(import.entry_count(), DEFAULT_ENTRY_COUNT as usize);
+
+        let _ = vault_container
+            .update_export(ExportModalMessage::Close, &mut modal_state)
+            .unwrap();
+        assert!(matches!(modal_state.inner(), crate::ModalState::None));
+    }
+
+    #[test]
+    fn plain_export() {
+        let mem_key = MemKey::with_length(1);
+        let (dir, vault) = default_vault(&mem_key);
+        let mut modal_state = iced_aw::modal::State::new(crate::ModalState::default());
+        let path = dir.path().join("export.json");
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+
+        // Selecting a file opens the warning modal.
+        let _ = VaultContainer::open_plain_export(path.clone(), &mut modal_state);
+        assert!(matches!(
+            modal_state.inner(),
+            crate::ModalState::Export(ExportModal::Plain { .. })
+        ));
+
+        // The export is not submitted with a wrong master password.
+        let mutex_mem_key = Mutex::new(mem_key);
+        let _ = vault_container
+            .update_export(
+                ExportModalMessage::PasswordInput("wrong password".into()),
+                &mut modal_state,
+            )
+            .unwrap();
+        let _ = vault_container
+            .submit_plain_export(&mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+        assert!(!path.exists());
+        assert!(matches!(
+            modal_state.inner(),
+            crate::ModalState::Export(ExportModal::Plain {
+                password_wrong: true,
+                ..
+            })
+        ));
+
+        // Only the search result is exported while searching.
+        let _ = vault_container.list_view.set_search("Entry: 7".into());
+        let _ = vault_container
+            .update_export(
+                ExportModalMessage::PasswordInput(PASSWORD.into()),
+                &mut modal_state,
+            )
+            .unwrap();
+        let _ = vault_container
+            .submit_plain_export(&mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+        assert!(path.exists());
+
+        if let crate::ModalState::Export(ExportModal::Report {
+            group_count,
+            entry_count,
+            ..
+        }) = modal_state.inner()
+        {
+            assert_eq!(*group_count, 0);
+            assert_eq!(*entry_count, 1);
+        } else {
+            panic!("Modal should be the report modal");
+        }
+
+        let json = std::fs::read_to_string(&path).unwrap();
+        assert!(json.contains("\"title\": \"Entry: 7\""));
+        assert!(json.contains("\"password\": \"password\""));
+
+        let _ = vault_container
+            .update_export(ExportModalMessage::Close, &mut modal_state)
+            .unwrap();
+        assert!(matches!(modal_state.inner(), crate::ModalState::None));
+    }
+
+    #[test]
+    fn backup() {
+        let mem_key = MemKey::with_length(1);
+        let (dir, mut vault) = default_vault(&mem_key);
+        vault.save(&mem_key).unwrap();
+        let mut modal_state = iced_aw::modal::State::new(crate::ModalState::default());
+        let path = dir.path().join("TempVault.pwduck-backup");
+
+        let vault_container = VaultContainer::new(Box::new(vault));
+
+        let mutex_mem_key = Mutex::new(mem_key);
+        let _ = vault_container
+            .export_backup(
+                path.clone(),
+                &mut modal_state,
+                &mutex_mem_key.lock().unwrap(),
+            )
+            .unwrap();
+        assert!(path.exists());
+        assert!(matches!(
+            modal_state.inner(),
+            crate::ModalState::Export(ExportModal::Backup { .. })
+        ));
+
+        let restored = dir.path().join("Restored");
+        pwduck_core::Vault::restore_backup(
+            &path,
+            PASSWORD,
+            None,
+            &mutex_mem_key.lock().unwrap(),
+            &restored,
+        )
+        .unwrap();
+        let restored = pwduck_core::Vault::load(
+            PASSWORD,
+            Option::<String>::None,
+            &mutex_mem_key.lock().unwrap(),
+            restored,
+        )
+        .unwrap();
+        assert_eq!(
+            restored.entries().len(),
+            vault_container.vault.entries().len()
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let mem_key = MemKey::with_length(1);
+        let (dir, mut vault) = default_vault(&mem_key);
+        vault.save(&mem_key).unwrap();
+        let master_key = vault
+            .master_key()
+            .as_unprotected(&mem_key, vault.salt(), vault.nonce())
+            .unwrap();
+
+        // Create another copy of the vault and add an entry to it.
+        let archive = dir.path().join("TempVault.pwduck-backup");
+        vault.export_backup(&archive, &master_key).unwrap();
+        let copy_path = dir.path().join("Copy");
+        Vault::restore_backup(&archive, PASSWORD, None, &mem_key, &copy_path).unwrap();
+        let mut copy = Vault::load(PASSWORD, Option::<String>::None, &mem_key, &copy_path).unwrap();
+        let head = EntryHead::new(
+            [255; uuid::SIZE].into(),
+            copy.get_root_uuid().unwrap(),
+            "Added".into(),
+            [255; uuid::SIZE].into(),
+        );
+        let body = pwduck_core::EntryBody::new(
+            [255; uuid::SIZE].into(),
+            "username".into(),
+            "password".into(),
+        );
+        copy.insert_entry(head, body, &master_key).unwrap();
+        copy.save(&mem_key).unwrap();
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+        let mut modal_state = iced_aw::modal::State::new(crate::ModalState::default());
+        let mutex_mem_key = Mutex::new(mem_key);
+
+        // Not a copy of the vault.
+        assert!(vault_container
+            .open_merge(
+                &dir.path().join("Unknown"),
+                &mut modal_state,
+                &mutex_mem_key.lock().unwrap()
+            )
+            .is_err());
+
+        let _ = vault_container
+            .open_merge(&copy_path, &mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+        if let crate::ModalState::Merge(modal) = modal_state.inner() {
+            assert_eq!(modal.merge().added(), 1);
+            assert_eq!(modal.merge().updated(), 0);
+            assert!(modal.merge().conflicts().is_empty());
+        } else {
+            panic!("Modal should be the merge modal");
+        }
+
+        // Cancel
+        let _ = vault_container
+            .update_merge(MergeModalMessage::Close, &mut modal_state)
+            .unwrap();
+        assert!(matches!(modal_state.inner(), crate::ModalState::None));
+        assert!(!vault_container.contains_unsaved_changes());
+
+        // Submit
+        let _ = vault_container
+            .open_merge(&copy_path, &mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+        let _ = vault_container
+            .update_merge(MergeModalMessage::ToggleWinner(0), &mut modal_state)
+            .unwrap();
+        let _ = vault_container
+            .submit_merge(&mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+        assert!(matches!(modal_state.inner(), crate::ModalState::None));
+        assert!(vault_container.contains_unsaved_changes());
+        let added: pwduck_core::Uuid = [255; uuid::SIZE].into();
+        assert_eq!(vault_container.vault.entries()[&added].title(), "Added");
+    }
+
+    #[test]
+    fn reload_external_changes() {
+        let mem_key = MemKey::with_length(1);
+        let (_dir, mut vault) = default_vault(&mem_key);
+        vault.save(&mem_key).unwrap();
+        let master_key = vault
+            .master_key()
+            .as_unprotected(&mem_key, vault.salt(), vault.nonce())
+            .unwrap();
+
+        // Another program adds an entry to the vault on disk.
+        let mut other =
+            Vault::load(PASSWORD, Option::<String>::None, &mem_key, vault.path()).unwrap();
+        let head = EntryHead::new(
+            [255; uuid::SIZE].into(),
+            other.get_root_uuid().unwrap(),
+            "Added".into(),
+            [255; uuid::SIZE].into(),
+        );
+        let body = pwduck_core::EntryBody::new(
+            [255; uuid::SIZE].into(),
+            "username".into(),
+            "password".into(),
+        );
+        other.insert_entry(head, body, &master_key).unwrap();
+        other.save(&mem_key).unwrap();
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+        let mut modal_state = iced_aw::modal::State::new(crate::ModalState::default());
+        let mutex_mem_key = Mutex::new(mem_key);
+
+        let _ = vault_container
+            .reload_external_changes(None, &mut modal_state, &mutex_mem_key.lock().unwrap())
+            .unwrap();
+        assert!(matches!(modal_state.inner(), crate::ModalState::None));
+        assert!(!vault_container.contains_unsaved_changes());
+        let added: pwduck_core::Uuid = [255; uuid::SIZE].into();
+        assert_eq!(vault_container.vault.entries()[&added].title(), "Added");
+
+        // A resolution closes the modal.
+        modal_state = iced_aw::modal::State::new(crate::ModalState::ExternalChanges(
+            ExternalChangesModal::new(Vec::new()),
+        ));
+        let _ = vault_container
+            .reload_external_changes(
+                Some(MergeSide::Local),
+                &mut modal_state,
+                &mutex_mem_key.lock().unwrap(),
+            )
+            .unwrap();
+        assert!(matches!(modal_state.inner(), crate::ModalState::None));
+    }
+
+    #[test]
+    fn save_conflict() {
+        let mem_key = MemKey::with_length(1);
+        let (_dir, mut vault) = default_vault(&mem_key);
+        vault.save(&mem_key).unwrap();
+        let root = vault.get_root_uuid().unwrap();
+
+        // Another program renames the root group on disk.
+        let mut other =
+            Vault::load(PASSWORD, Option::<String>::None, &mem_key, vault.path()).unwrap();
+        let mut group = other.groups()[&root].clone();
+        let _ = group.set_title("Other".into());
+        other.insert_group(group);
+        other.save(&mem_key).unwrap();
+
+        let mut group = vault.groups()[&root].clone();
+        let _ = group.set_title("Local".into());
+        vault.insert_group(group);
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+        let mut modal_state = iced_aw::modal::State::new(crate::ModalState::default());
+        let mutex_mem_key = Mutex::new(mem_key);
 
-            // New entry
-            assert_eq!(
-                call_map.borrow()[&VaultContainer::create_entry.type_id()],
-                0
-            );
-            let _ = vault_container
-                .update_toolbar::<TestPlatform>(&ToolBarMessage::NewEntry, &mut clipboard);
-            assert_eq!(
-                call_map.borrow()[&VaultContainer::create_entry.type_id()],
-                1
-            );
+        // Saving is refused.
+        let _ = vault_container
+            .save(&mutex_mem_key.lock().unwrap())
+            .unwrap();
+        assert!(vault_container.contains_unsaved_changes());
 
-            // Auto fill
-            assert_eq!(
-                call_map.borrow()[&VaultContainer::auto_fill::<TestPlatform>.type_id()],
-                0
-            );
-            // A non existent ModifyEntryView can not auto fill.
-            let _ = vault_container
-                .update_toolbar::<TestPlatform>(&ToolBarMessage::AutoFill, &mut clipboard);
-            assert_eq!(
-                call_map.borrow()[&VaultContainer::auto_fill::<TestPlatform>.type_id()],
-                0
-            );
-            // An existent ModifyEntryView can auto fill.
-            vault_container.modify_entry_view = Some(Box::new(ModifyEntryView::with(
-                modify_entry::State::Create,
-                pwduck_core::EntryHead::new(
-                    [1; uuid::SIZE].into(),
-                    root.clone(),
-                    "".into(),
-                    [2; uuid::SIZE].into(),
-                ),
-                pwduck_core::EntryBody::new([2; uuid::SIZE].into(), "".into(), "".into()),
-            )));
-            let _ = vault_container
-                .update_toolbar::<TestPlatform>(&ToolBarMessage::AutoFill, &mut clipboard);
-            assert_eq!(
-                call_map.borrow()[&VaultContainer::auto_fill::<TestPlatform>.type_id()],
-                1
-            );
+        let _ = vault_container.open_save_conflict(&[root.clone()], &mut modal_state);
+        assert!(matches!(
+            modal_state.inner(),
+            crate::ModalState::SaveConflict(_)
+        ));
 
-            // Lock vault
-            let res = vault_container
-                .update_toolbar::<TestPlatform>(&ToolBarMessage::LockVault, &mut clipboard)
-                .expect_err("Should fail");
-            match res {
-                PWDuckGuiError::Unreachable(_) => {}
-                _ => panic!("Should contain unreachable warning."),
-            }
-        })
+        // Cancel
+        let _ = vault_container
+            .update_save_conflict(
+                &SaveConflictModalMessage::Close,
+                &mut modal_state,
+                &mutex_mem_key.lock().unwrap(),
+            )
+            .unwrap();
+        assert!(matches!(modal_state.inner(), crate::ModalState::None));
+        assert!(vault_container.contains_unsaved_changes());
+
+        // Overwrite
+        let _ = vault_container.open_save_conflict(&[root.clone()], &mut modal_state);
+        let _ = vault_container
+            .update_save_conflict(
+                &SaveConflictModalMessage::Overwrite,
+                &mut modal_state,
+                &mutex_mem_key.lock().unwrap(),
+            )
+            .unwrap();
+        assert!(matches!(modal_state.inner(), crate::ModalState::None));
+        assert!(!vault_container.contains_unsaved_changes());
+        let loaded = Vault::load(
+            PASSWORD,
+            Option::<String>::None,
+            &mutex_mem_key.lock().unwrap(),
+            vault_container.vault.path(),
+        )
+        .unwrap();
+        assert_eq!(loaded.groups()[&root].title(), "Local");
     }
 
     #[test]
@@ -1011,6 +2347,93 @@ mod tests {
         });
     }
 
+    #[test]
+    fn update_sort_order() {
+        let mem_key = MemKey::with_length(1);
+        let (_dir, vault) = default_vault(&mem_key);
+        let root = vault.get_root_uuid().unwrap();
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+        let mutex_mem_key = Mutex::new(mem_key);
+
+        let titles = |vault: &Vault| -> Vec<String> {
+            vault
+                .get_item_list_for(&root, None)
+                .entries()
+                .iter()
+                .map(|entry| entry.title().clone())
+                .collect()
+        };
+        let ascending = titles(&vault_container.vault);
+
+        let sort_order = SortOrder::new(SortKey::Title, SortDirection::Descending);
+        let _ = vault_container
+            .update_sort_order(sort_order, &mutex_mem_key.lock().unwrap())
+            .expect("Should not fail");
+        assert_eq!(vault_container.vault.settings().sort_order(), sort_order);
+
+        let mut descending = titles(&vault_container.vault);
+        descending.reverse();
+        assert_eq!(ascending, descending);
+
+        // Changing the sort order is no unsaved change of the vault.
+        vault_container
+            .vault
+            .save(&mutex_mem_key.lock().unwrap())
+            .unwrap();
+        let _ = vault_container
+            .update_sort_order(SortOrder::default(), &mutex_mem_key.lock().unwrap())
+            .expect("Should not fail");
+        assert!(!vault_container.vault.contains_unsaved_changes());
+    }
+
+    #[test]
+    fn saved_searches() {
+        let mem_key = MemKey::with_length(1);
+        let (_dir, vault) = default_vault(&mem_key);
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+
+        // Nothing to save without a search or a name.
+        let _ = vault_container.update_saved_search_name("Name".into());
+        let _ = vault_container.save_search();
+        assert!(vault_container.vault.saved_searches().is_empty());
+
+        let _ = vault_container.update_search("1".into());
+        let _ = vault_container.update_saved_search_name("  ".into());
+        let _ = vault_container.save_search();
+        assert!(vault_container.vault.saved_searches().is_empty());
+
+        let _ = vault_container.update_saved_search_name("Ones".into());
+        let _ = vault_container.save_search();
+        assert_eq!(vault_container.vault.saved_searches().len(), 1);
+        assert!(vault_container.list_view.saved_search_name().is_empty());
+        assert!(vault_container.vault.contains_unsaved_changes());
+
+        let saved_search = vault_container
+            .vault
+            .get_saved_searches()
+            .first()
+            .map(|saved_search| (*saved_search).clone())
+            .unwrap();
+        assert_eq!(saved_search.title().as_str(), "Ones");
+        assert_eq!(saved_search.query().as_str(), "1");
+
+        // Selecting the saved search replaces the current search.
+        let _ = vault_container.update_search(String::new());
+        let _ = vault_container
+            .select_saved_search(saved_search.uuid())
+            .expect("Should not fail");
+        assert_eq!(vault_container.list_view.search().as_str(), "1");
+
+        let _ = vault_container
+            .select_saved_search(&[255; uuid::SIZE].into())
+            .expect_err("Should fail");
+
+        let _ = vault_container.delete_saved_search(saved_search.uuid());
+        assert!(vault_container.vault.saved_searches().is_empty());
+    }
+
     #[test]
     fn go_to_parent_group() {
         let mem_key = MemKey::with_length(1);
@@ -1197,6 +2620,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn open_reference() {
+        let mem_key = MemKey::with_length(1);
+        let (_dir, vault) = default_vault(&mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let mut modal_state = iced_aw::modal::State::new(crate::ModalState::default());
+        // WARNING: This is highly unsafe!
+        #[allow(deref_nullptr)]
+        let mut clipboard: &mut iced::Clipboard = unsafe { &mut *(std::ptr::null_mut()) };
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+        let entries: Vec<EntryHead> = vault_container
+            .vault
+            .get_entries_of(&root)
+            .iter()
+            .map(|entry| (*entry).clone())
+            .collect();
+
+        let mutex_mem_key = Mutex::new(mem_key);
+        let _ = vault_container
+            .select_entry(entries[3].uuid(), &mutex_mem_key.lock().unwrap())
+            .expect("Should not fail");
+
+        // Open the referenced entry.
+        let _ = vault_container
+            .open_reference(entries[2].uuid(), &mutex_mem_key.lock().unwrap())
+            .expect("Should not fail");
+        assert_eq!(vault_container.current_view, CurrentView::ModifyEntry);
+        assert_eq!(
+            vault_container
+                .modify_entry_view()
+                .as_ref()
+                .unwrap()
+                .entry_head()
+                .uuid(),
+            entries[2].uuid()
+        );
+
+        // Unsaved changes must not be discarded.
+        let _ = vault_container
+            .update_modify_entry::<TestPlatform>(
+                &ModifyEntryMessage::TitleInput("title".into()),
+                &mut modal_state,
+                &mut clipboard,
+            )
+            .expect("Should not fail");
+        let _ = vault_container
+            .open_reference(entries[3].uuid(), &mutex_mem_key.lock().unwrap())
+            .expect_err("Should fail");
+        assert_eq!(
+            vault_container
+                .modify_entry_view()
+                .as_ref()
+                .unwrap()
+                .entry_head()
+                .uuid(),
+            entries[2].uuid()
+        );
+    }
+
     #[test]
     fn split_resize() {
         let mem_key = MemKey::with_length(1);
@@ -1215,6 +2698,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn undo_and_redo() {
+        let mem_key = MemKey::with_length(1);
+        let (_dir, vault) = default_vault(&mem_key);
+        let root = vault.get_root_uuid().unwrap();
+        let change_count = DEFAULT_GROUP_COUNT + DEFAULT_ENTRY_COUNT;
+
+        let mut vault_container = VaultContainer::new(Box::new(vault));
+        let _ = vault_container.select_group([0; uuid::SIZE].into());
+
+        // Undo is not possible outside of the list view.
+        vault_container.current_view = CurrentView::ModifyGroup;
+        let _ = vault_container.undo();
+        assert_eq!(
+            vault_container.vault.get_entries_of(&root).len(),
+            DEFAULT_ENTRY_COUNT as usize
+        );
+        vault_container.current_view = CurrentView::ListView;
+
+        for _ in 0..change_count {
+            let _ = vault_container.undo();
+        }
+        assert!(vault_container.vault.get_groups_of(&root).is_empty());
+        assert!(vault_container.vault.get_entries_of(&root).is_empty());
+        assert!(!vault_container.vault.contains_unsaved_changes());
+        // The selected group was removed, so the root should be selected.
+        assert_eq!(vault_container.list_view.selected_group_uuid(), &root);
+
+        for _ in 0..change_count {
+            let _ = vault_container.redo();
+        }
+        assert_eq!(
+            vault_container.vault.get_groups_of(&root).len(),
+            DEFAULT_GROUP_COUNT as usize
+        );
+        assert_eq!(
+            vault_container.vault.get_entries_of(&root).len(),
+            DEFAULT_ENTRY_COUNT as usize
+        );
+        assert!(!vault_container.vault.can_redo());
+    }
+
     #[test]
     fn update_group_tree() {
         let mem_key = MemKey::with_length(1);
@@ -1438,6 +2963,18 @@ mod tests {
             call_map
                 .borrow_mut()
                 .insert(VaultContainer::update_group_tree.type_id(), 0);
+            call_map
+                .borrow_mut()
+                .insert(VaultContainer::update_saved_search_name.type_id(), 0);
+            call_map
+                .borrow_mut()
+                .insert(VaultContainer::save_search.type_id(), 0);
+            call_map
+                .borrow_mut()
+                .insert(VaultContainer::select_saved_search.type_id(), 0);
+            call_map
+                .borrow_mut()
+                .insert(VaultContainer::delete_saved_search.type_id(), 0);
 
             VaultContainer::update_search.mock_raw(|_self, _search| {
                 call_map
@@ -1483,6 +3020,34 @@ mod tests {
                     .map(|c| *c += 1);
                 MockResult::Return(Ok(Command::none()))
             });
+            VaultContainer::update_saved_search_name.mock_raw(|_self, _name| {
+                call_map
+                    .borrow_mut()
+                    .get_mut(&VaultContainer::update_saved_search_name.type_id())
+                    .map(|c| *c += 1);
+                MockResult::Return(Command::none())
+            });
+            VaultContainer::save_search.mock_raw(|_self| {
+                call_map
+                    .borrow_mut()
+                    .get_mut(&VaultContainer::save_search.type_id())
+                    .map(|c| *c += 1);
+                MockResult::Return(Command::none())
+            });
+            VaultContainer::select_saved_search.mock_raw(|_self, _uuid| {
+                call_map
+                    .borrow_mut()
+                    .get_mut(&VaultContainer::select_saved_search.type_id())
+                    .map(|c| *c += 1);
+                MockResult::Return(Ok(Command::none()))
+            });
+            VaultContainer::delete_saved_search.mock_raw(|_self, _uuid| {
+                call_map
+                    .borrow_mut()
+                    .get_mut(&VaultContainer::delete_saved_search.type_id())
+                    .map(|c| *c += 1);
+                MockResult::Return(Command::none())
+            });
 
             // Update search
             assert_eq!(
@@ -1559,6 +3124,56 @@ mod tests {
                 1
             );
 
+            // Update saved search name
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::update_saved_search_name.type_id()],
+                0
+            );
+            let _ = vault_container.update_list::<TestPlatform>(
+                ListMessage::SavedSearchNameInput("Name".into()),
+                &mut clipboard,
+            );
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::update_saved_search_name.type_id()],
+                1
+            );
+
+            // Save search
+            assert_eq!(call_map.borrow()[&VaultContainer::save_search.type_id()], 0);
+            let _ = vault_container
+                .update_list::<TestPlatform>(ListMessage::SaveSearch, &mut clipboard);
+            assert_eq!(call_map.borrow()[&VaultContainer::save_search.type_id()], 1);
+
+            // Select saved search
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::select_saved_search.type_id()],
+                0
+            );
+            let _ = vault_container.update_list::<TestPlatform>(
+                ListMessage::SavedSearchMessage(SavedSearchMessage::Selected(
+                    [1; uuid::SIZE].into(),
+                )),
+                &mut clipboard,
+            );
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::select_saved_search.type_id()],
+                1
+            );
+
+            // Delete saved search
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::delete_saved_search.type_id()],
+                0
+            );
+            let _ = vault_container.update_list::<TestPlatform>(
+                ListMessage::SavedSearchMessage(SavedSearchMessage::Delete([1; uuid::SIZE].into())),
+                &mut clipboard,
+            );
+            assert_eq!(
+                call_map.borrow()[&VaultContainer::delete_saved_search.type_id()],
+                1
+            );
+
             assert!(call_map.borrow().values().all(|v| *v == 1));
         })
     }
@@ -2053,6 +3668,24 @@ mod tests {
                 )
                 .expect_err("Should fail");
 
+            // Undo and redo
+            let _ = vault_container
+                .update::<TestPlatform>(
+                    VaultContainerMessage::Undo,
+                    &mut application_settings,
+                    &mut modal_state,
+                    &mut clipboard,
+                )
+                .expect("Should not fail");
+            let _ = vault_container
+                .update::<TestPlatform>(
+                    VaultContainerMessage::Redo,
+                    &mut application_settings,
+                    &mut modal_state,
+                    &mut clipboard,
+                )
+                .expect("Should not fail");
+
             assert!(call_map.borrow().values().all(|v| *v == 1));
         })
     }
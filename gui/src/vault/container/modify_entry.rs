@@ -3,12 +3,15 @@ use std::sync::MutexGuard;
 
 use getset::{CopyGetters, Getters, MutGetters, Setters};
 use iced::{
-    button, scrollable, text_input, Button, Column, Command, Element, Length, Row, Scrollable,
-    Space, Text,
+    button, scrollable, text_input, Button, Checkbox, Column, Command, Element, Length, Row,
+    Scrollable, Space, Text,
 };
 use iced_aw::{modal, Card};
 use iced_focus::Focus;
-use pwduck_core::{EntryBody, EntryHead, MemKey, PWDuckCoreError, PasswordInfo, Uuid, Vault};
+use pwduck_core::{
+    EntryBody, EntryHead, FieldReference, MemKey, PWDuckCoreError, PasswordInfo, ReferenceField,
+    Uuid, Vault,
+};
 
 use crate::{
     error::PWDuckGuiError,
@@ -17,7 +20,8 @@ use crate::{
     theme::Theme,
     utils::{
         centered_container_with_column, default_text_input, default_vertical_space,
-        estimate_password_strength, icon_button, password_toggle, ButtonData, ButtonKind, SomeIf,
+        estimate_password_strength, icon_button, icon_text, password_toggle, ButtonData,
+        ButtonKind, SomeIf,
     },
     Platform, DEFAULT_COLUMN_PADDING, DEFAULT_COLUMN_SPACING, DEFAULT_MAX_WIDTH,
     DEFAULT_ROW_SPACING,
@@ -69,10 +73,14 @@ pub struct ModifyEntryView {
     #[focus(enable)]
     email_state: text_input::State,
 
+    /// The states of the [`Button`](iced::Button)s to open the referenced entries.
+    reference_states: Vec<button::State>,
+
     /// The estimated password score.
     password_score: Option<PasswordScore>,
 
     /// Whether the entry was modified or not.
+    #[getset(get_copy = "pub")]
     is_modified: bool,
 
     /// The state of the cancel [`Button`](iced::Button).
@@ -98,6 +106,8 @@ pub struct ModifyEntryView {
 pub enum ModifyEntryMessage {
     /// Change the title to the new value.
     TitleInput(String),
+    /// Pin or unpin the entry as a favourite.
+    FavouriteToggle(bool),
     /// Change the username to the new value.
     UsernameInput(String),
     /// Copy the username.
@@ -120,6 +130,9 @@ pub enum ModifyEntryMessage {
     /// Change the email to the new value.
     EmailInput(String),
 
+    /// Open the referenced entry identified by the UUID.
+    OpenReference(Uuid),
+
     /// Set the password score tho the new value.
     PasswordScore(Result<PasswordInfo, PWDuckCoreError>),
 
@@ -170,6 +183,8 @@ impl ModifyEntryView {
             open_in_browser_state: button::State::new(),
             email_state: text_input::State::new(),
 
+            reference_states: Vec::new(),
+
             password_score: Option::None,
 
             is_modified: false,
@@ -198,6 +213,13 @@ impl ModifyEntryView {
         Command::none()
     }
 
+    /// Pin or unpin the entry as a favourite.
+    fn update_favourite(&mut self, favourite: bool) -> Command<ModifyEntryMessage> {
+        let _ = self.entry_head_mut().set_favourite(favourite);
+        self.is_modified = true;
+        Command::none()
+    }
+
     /// Update the username and replace it with the given value.
     fn update_username(&mut self, username: String) -> Command<ModifyEntryMessage> {
         let _ = self.entry_body_mut().set_username(username);
@@ -205,10 +227,29 @@ impl ModifyEntryView {
         Command::none()
     }
 
-    /// Copy the username to clipboard.
-    fn copy_username(&self, clipboard: &mut iced::Clipboard) -> Command<ModifyEntryMessage> {
-        clipboard.write(self.entry_body().username().to_string());
-        Command::none()
+    /// Copy the username to clipboard. References to other entries will be resolved.
+    fn copy_username(
+        &self,
+        vault: &mut Vault,
+        mem_key: &MutexGuard<MemKey>,
+        clipboard: &mut iced::Clipboard,
+    ) -> Result<Command<ModifyEntryMessage>, PWDuckGuiError> {
+        let master_key = vault
+            .master_key()
+            .as_unprotected(mem_key, vault.salt(), vault.nonce())?;
+
+        let username = vault.resolve_references(self.entry_body().username(), &master_key)?;
+        clipboard.write(username.to_string());
+        self.record_usage(vault, &master_key)?;
+        Ok(Command::none())
+    }
+
+    /// Record the usage of this entry, if it already exists inside of the [`Vault`](Vault).
+    fn record_usage(&self, vault: &mut Vault, master_key: &[u8]) -> Result<(), PWDuckGuiError> {
+        if vault.entries().contains_key(self.entry_head.uuid()) {
+            vault.record_usage(self.entry_head.uuid(), master_key)?;
+        }
+        Ok(())
     }
 
     /// Update the password and replace it with the given value.
@@ -246,10 +287,21 @@ impl ModifyEntryView {
         Command::none()
     }
 
-    /// Copy the password to the clipboard.
-    fn copy_password(&self, clipboard: &mut iced::Clipboard) -> Command<ModifyEntryMessage> {
-        clipboard.write(self.entry_body().password().to_string());
-        Command::none()
+    /// Copy the password to the clipboard. References to other entries will be resolved.
+    fn copy_password(
+        &self,
+        vault: &mut Vault,
+        mem_key: &MutexGuard<MemKey>,
+        clipboard: &mut iced::Clipboard,
+    ) -> Result<Command<ModifyEntryMessage>, PWDuckGuiError> {
+        let master_key = vault
+            .master_key()
+            .as_unprotected(mem_key, vault.salt(), vault.nonce())?;
+
+        let password = vault.resolve_references(self.entry_body().password(), &master_key)?;
+        clipboard.write(password.to_string());
+        self.record_usage(vault, &master_key)?;
+        Ok(Command::none())
     }
 
     /// Toggle the visibility of the advanced area.
@@ -296,13 +348,20 @@ impl ModifyEntryView {
     }
 
     /// Request the deletion of the entry.
-    #[allow(clippy::unused_self)]
+    ///
+    /// The titles of all entries referencing this entry will be listed in the confirmation.
     fn request_entry_deletion(
         &mut self,
+        vault: &Vault,
         modal_state: &mut iced_aw::modal::State<crate::ModalState>,
     ) -> Command<ModifyEntryMessage> {
+        let referencing_titles = vault
+            .get_referencing_entries(self.entry_head.uuid())
+            .iter()
+            .map(|entry| entry.title().clone())
+            .collect();
         *modal_state = modal::State::new(crate::ModalState::ModifyEntry(
-            ModifyEntryModal::delete_request(),
+            ModifyEntryModal::delete_request(referencing_titles),
         ));
         modal_state.show(true);
         Command::none()
@@ -320,17 +379,32 @@ impl ModifyEntryView {
         Command::none()
     }
 
+    /// Copy a reference to the given field of this entry to the clipboard.
+    fn copy_reference(
+        &self,
+        field: ReferenceField,
+        clipboard: &mut iced::Clipboard,
+    ) -> Command<ModifyEntryMessage> {
+        clipboard.write(FieldReference::new(field, self.entry_head.uuid().clone()).to_string());
+        Command::none()
+    }
+
     /// Update the advanced state with the given message.
     fn update_advanced<P: Platform + 'static>(
         &mut self,
         message: AdvancedStateMessage,
+        vault: &Vault,
         modal_state: &mut iced_aw::modal::State<crate::ModalState>,
+        clipboard: &mut iced::Clipboard,
     ) -> Command<ModifyEntryMessage> {
         match message {
-            AdvancedStateMessage::DeleteEntryRequest => self.request_entry_deletion(modal_state),
+            AdvancedStateMessage::DeleteEntryRequest => {
+                self.request_entry_deletion(vault, modal_state)
+            }
             AdvancedStateMessage::AutoTypeInput(auto_type_sequence) => {
                 self.update_auto_type_sequence(auto_type_sequence)
             }
+            AdvancedStateMessage::CopyReference(field) => self.copy_reference(field, clipboard),
         }
     }
 
@@ -344,9 +418,9 @@ impl ModifyEntryView {
         Command::none()
     }
 
-    /// Delete the entry from the vault.
+    /// Delete the entry from the vault. The user already confirmed to break the references to it.
     fn delete_entry(&mut self, vault: &mut Vault) -> Command<ModifyEntryMessage> {
-        vault.delete_entry(self.entry_head.uuid());
+        vault.delete_entry_breaking_references(self.entry_head.uuid());
         Command::none()
     }
 
@@ -375,11 +449,16 @@ impl ModifyEntryView {
     ) -> Result<Command<ModifyEntryMessage>, PWDuckGuiError> {
         match message {
             ModifyEntryMessage::TitleInput(title) => Ok(self.update_title(title)),
+            ModifyEntryMessage::FavouriteToggle(favourite) => Ok(self.update_favourite(favourite)),
             ModifyEntryMessage::UsernameInput(username) => Ok(self.update_username(username)),
-            ModifyEntryMessage::UsernameCopy => Ok(self.copy_username(clipboard)),
+            ModifyEntryMessage::UsernameCopy => {
+                self.copy_username(vault, &crate::MEM_KEY.lock()?, clipboard)
+            }
             ModifyEntryMessage::PasswordInput(password) => Ok(self.update_password(password)),
             ModifyEntryMessage::PasswordShow => Ok(self.toggle_password_visibility()),
-            ModifyEntryMessage::PasswordCopy => Ok(self.copy_password(clipboard)),
+            ModifyEntryMessage::PasswordCopy => {
+                self.copy_password(vault, &crate::MEM_KEY.lock()?, clipboard)
+            }
             ModifyEntryMessage::WebAddressInput(web_address) => {
                 Ok(self.update_web_address(web_address))
             }
@@ -394,14 +473,14 @@ impl ModifyEntryView {
             }
             ModifyEntryMessage::ToggleAdvanced => Ok(self.toggle_advanced_visibility()),
             ModifyEntryMessage::Advanced(message) => {
-                Ok(self.update_advanced::<P>(message, modal_state))
+                Ok(self.update_advanced::<P>(message, vault, modal_state, clipboard))
             }
             ModifyEntryMessage::Modal(message) => {
                 Ok(self.update_modal(&message, vault, modal_state))
             }
             ModifyEntryMessage::Cancel => Ok(Command::none()),
             ModifyEntryMessage::Submit => self.submit(vault, &crate::MEM_KEY.lock()?),
-            ModifyEntryMessage::PasswordGenerate => {
+            ModifyEntryMessage::PasswordGenerate | ModifyEntryMessage::OpenReference(_) => {
                 PWDuckGuiError::Unreachable("ModifyEntryMessage".into()).into()
             }
         }
@@ -411,10 +490,17 @@ impl ModifyEntryView {
     #[cfg_attr(coverage, no_coverage)]
     pub fn view<P: Platform + 'static>(
         &mut self,
+        vault: &Vault,
         _selected_group_uuid: &Uuid,
         theme: &dyn Theme,
     ) -> Element<ModifyEntryMessage> {
         let title = title_text_input(&mut self.title_state, self.entry_head.title(), theme);
+        let favourite = Checkbox::new(
+            self.entry_head.favourite(),
+            "Pin as favourite",
+            ModifyEntryMessage::FavouriteToggle,
+        )
+        .style(theme.checkbox());
         let username = username_row(
             &mut self.username_state,
             self.entry_body.username(),
@@ -439,6 +525,9 @@ impl ModifyEntryView {
         );
         let email = email_text_input(&mut self.email_state, self.entry_body.email(), theme);
 
+        let references =
+            references_column(&mut self.reference_states, vault, &self.entry_body, theme);
+
         let control_row = control_button_row(
             &mut self.cancel_state,
             &mut self.submit_state,
@@ -464,12 +553,14 @@ impl ModifyEntryView {
                 State::Modify => "Edit entry:",
             }))
             .push(title)
+            .push(favourite)
             .push(default_vertical_space())
             .push(username)
             .push(password)
             .push(default_vertical_space())
             .push(web_address)
             .push(email)
+            .push(references)
             .push(default_vertical_space())
             .push(control_row)
             .push(default_vertical_space())
@@ -658,6 +749,67 @@ fn email_text_input<'a>(
     .into()
 }
 
+/// Create the column containing the links to the entries referenced by the fields of this entry.
+#[cfg_attr(coverage, no_coverage)]
+fn references_column<'a>(
+    button_states: &'a mut Vec<button::State>,
+    vault: &Vault,
+    entry_body: &EntryBody,
+    theme: &dyn Theme,
+) -> Element<'a, ModifyEntryMessage> {
+    let references: Vec<FieldReference> = [
+        entry_body.username(),
+        entry_body.password(),
+        entry_body.email(),
+    ]
+    .iter()
+    .flat_map(|field| FieldReference::find_all(field))
+    .collect();
+
+    if references.is_empty() {
+        return Space::new(Length::Fill, Length::Shrink).into();
+    }
+    button_states.resize_with(references.len(), button::State::new);
+
+    references
+        .iter()
+        .zip(button_states.iter_mut())
+        .fold(
+            Column::new()
+                .spacing(DEFAULT_COLUMN_SPACING)
+                .push(default_vertical_space())
+                .push(Text::new("References:")),
+            |column, (reference, state)| {
+                let link: Element<_> = match vault.entries().get(reference.target()) {
+                    Some(target) => Button::new(
+                        state,
+                        Row::new()
+                            .spacing(DEFAULT_ROW_SPACING)
+                            .push(icon_text(Icon::Key))
+                            .push(Text::new(format!(
+                                "{} of {}",
+                                reference.field().name(),
+                                target.title()
+                            ))),
+                    )
+                    .style(theme.button())
+                    .on_press(ModifyEntryMessage::OpenReference(
+                        reference.target().clone(),
+                    ))
+                    .into(),
+                    None => Text::new(format!(
+                        "{} of a missing entry ({})",
+                        reference.field().name(),
+                        reference.target()
+                    ))
+                    .into(),
+                };
+                column.push(link)
+            },
+        )
+        .into()
+}
+
 /// Create the control row containing the cancel and submit buttons.
 #[cfg_attr(coverage, no_coverage)]
 fn control_button_row<'a>(
@@ -763,6 +915,10 @@ pub enum State {
 pub struct AdvancedState {
     /// The state of the [`Button`](iced::Button) to delete the entry.
     delete: button::State,
+    /// The state of the [`Button`](iced::Button) to copy a reference to the username.
+    copy_username_reference: button::State,
+    /// The state of the [`Button`](iced::Button) to copy a reference to the password.
+    copy_password_reference: button::State,
     /// The state of the [`TextInput`](iced::TextInput) of the entry's auto type value.
     #[focus(enable)]
     auto_type: text_input::State,
@@ -775,6 +931,8 @@ pub enum AdvancedStateMessage {
     DeleteEntryRequest,
     /// Change the auto type to the new value.
     AutoTypeInput(String),
+    /// Copy a reference to the field of the entry.
+    CopyReference(ReferenceField),
 }
 
 impl AdvancedState {
@@ -782,6 +940,8 @@ impl AdvancedState {
     pub fn new() -> Self {
         Self {
             delete: button::State::new(),
+            copy_username_reference: button::State::new(),
+            copy_password_reference: button::State::new(),
             auto_type: text_input::State::new(),
         }
     }
@@ -812,6 +972,42 @@ impl AdvancedState {
             default_vertical_space().into()
         };
 
+        let copy_references: Element<_> = if state == State::Modify {
+            Row::new()
+                .spacing(DEFAULT_ROW_SPACING)
+                .push(icon_button(
+                    ButtonData {
+                        state: &mut self.copy_username_reference,
+                        icon: Icon::FileEarmarkPerson,
+                        text: "Copy username reference",
+                        kind: ButtonKind::Normal,
+                        on_press: Some(AdvancedStateMessage::CopyReference(
+                            ReferenceField::Username,
+                        )),
+                    },
+                    "Copy a reference to the username of this entry",
+                    false,
+                    theme,
+                ))
+                .push(icon_button(
+                    ButtonData {
+                        state: &mut self.copy_password_reference,
+                        icon: Icon::FileEarmarkLock,
+                        text: "Copy password reference",
+                        kind: ButtonKind::Normal,
+                        on_press: Some(AdvancedStateMessage::CopyReference(
+                            ReferenceField::Password,
+                        )),
+                    },
+                    "Copy a reference to the password of this entry",
+                    false,
+                    theme,
+                ))
+                .into()
+        } else {
+            default_vertical_space().into()
+        };
+
         let auto_type_label = Text::new("AutoType sequence:");
 
         let auto_type = default_text_input(
@@ -826,6 +1022,7 @@ impl AdvancedState {
             .spacing(DEFAULT_COLUMN_SPACING)
             .push(default_vertical_space())
             .push(delete)
+            .push(copy_references)
             .push(default_vertical_space())
             .push(auto_type_label)
             .push(auto_type)
@@ -842,6 +1039,8 @@ pub enum ModifyEntryModal {
         cancel_button_state: button::State,
         /// The state of the submit [`Button`](iced::Button).
         submit_button_state: button::State,
+        /// The titles of the entries referencing the entry to delete.
+        referencing_titles: Vec<String>,
     },
     /// No modal.
     None,
@@ -858,10 +1057,13 @@ pub enum ModifyEntryModalMessage {
 
 impl ModifyEntryModal {
     /// Create the modal to confirm the entry deletion.
-    fn delete_request() -> Self {
+    ///
+    /// It expects the titles of the entries referencing the entry to delete.
+    fn delete_request(referencing_titles: Vec<String>) -> Self {
         Self::DeleteRequest {
             cancel_button_state: button::State::new(),
             submit_button_state: button::State::new(),
+            referencing_titles,
         }
     }
 
@@ -872,9 +1074,19 @@ impl ModifyEntryModal {
             ModifyEntryModal::DeleteRequest {
                 cancel_button_state,
                 submit_button_state,
+                referencing_titles,
             } => Card::new(
                 Text::new("Confirm deletion"),
-                Text::new("Do you really want to delete this entry? This cannot be undone!"),
+                if referencing_titles.is_empty() {
+                    Text::new("Do you really want to delete this entry?")
+                } else {
+                    Text::new(format!(
+                        "Do you really want to delete this entry? \
+                        It is referenced by the following entries, \
+                        whose references will be broken: {}",
+                        referencing_titles.join(", ")
+                    ))
+                },
             )
             .foot(
                 Row::new()
@@ -1046,6 +1258,19 @@ mod tests {
         assert!(mev.is_modified);
     }
 
+    #[test]
+    fn update_favourite() {
+        let mut mev = default_mev();
+
+        assert!(!mev.entry_head().favourite());
+        assert!(!mev.is_modified);
+
+        let _ = mev.update_favourite(true);
+
+        assert!(mev.entry_head().favourite());
+        assert!(mev.is_modified);
+    }
+
     #[test]
     fn update_username() {
         let mut mev = default_mev();
@@ -1190,7 +1415,22 @@ mod tests {
 
     #[test]
     fn request_entry_deletion() {
-        let mut mev = default_mev();
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("TempVault");
+
+        let password = "this_is_a_password";
+        let mem_key = pwduck_core::MemKey::with_length(1);
+
+        let mut vault =
+            pwduck_core::Vault::generate(password, Option::<String>::None, &mem_key, &path)
+                .unwrap();
+        let root = vault.get_root_uuid().unwrap();
+        let master_key = vault
+            .master_key()
+            .as_unprotected(&mem_key, vault.salt(), vault.nonce())
+            .unwrap();
+
+        let mut mev = default_mev_with_parent(root.clone());
         let mut modal_state = iced_aw::modal::State::new(crate::ModalState::None);
 
         if let crate::ModalState::None = modal_state.inner() {
@@ -1198,11 +1438,43 @@ mod tests {
             panic!("Modal state should be None");
         }
 
-        let _ = mev.request_entry_deletion(&mut modal_state);
+        let _ = mev.request_entry_deletion(&vault, &mut modal_state);
 
-        if let crate::ModalState::ModifyEntry(ModifyEntryModal::DeleteRequest { .. }) =
-            modal_state.inner()
+        if let crate::ModalState::ModifyEntry(ModifyEntryModal::DeleteRequest {
+            referencing_titles,
+            ..
+        }) = modal_state.inner()
+        {
+            assert!(referencing_titles.is_empty());
+        } else {
+            panic!("Modal state should be an delete request");
+        }
+
+        vault
+            .insert_entry(
+                pwduck_core::EntryHead::new(
+                    [3; uuid::SIZE].into(),
+                    root,
+                    "Referencing".into(),
+                    [4; uuid::SIZE].into(),
+                ),
+                pwduck_core::EntryBody::new(
+                    [4; uuid::SIZE].into(),
+                    "username".into(),
+                    format!("{{REF:password@{}}}", mev.entry_head().uuid()),
+                ),
+                &master_key,
+            )
+            .unwrap();
+
+        let _ = mev.request_entry_deletion(&vault, &mut modal_state);
+
+        if let crate::ModalState::ModifyEntry(ModifyEntryModal::DeleteRequest {
+            referencing_titles,
+            ..
+        }) = modal_state.inner()
         {
+            assert_eq!(referencing_titles, &vec![String::from("Referencing")]);
         } else {
             panic!("Modal state should be an delete request");
         }
@@ -1227,6 +1499,21 @@ mod tests {
         let mut mev = default_mev();
         let mut modal_state = iced_aw::modal::State::new(crate::ModalState::None);
 
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("TempVault");
+        let mem_key = pwduck_core::MemKey::with_length(1);
+        let vault = pwduck_core::Vault::generate(
+            "this_is_a_password",
+            Option::<String>::None,
+            &mem_key,
+            &path,
+        )
+        .unwrap();
+
+        // WARNING: This is highly unsafe!
+        #[allow(deref_nullptr)]
+        let mut clipboard: &mut iced::Clipboard = unsafe { &mut *(std::ptr::null_mut()) };
+
         CALL_MAP.with(|call_map| unsafe {
             call_map
                 .borrow_mut()
@@ -1234,8 +1521,11 @@ mod tests {
             call_map
                 .borrow_mut()
                 .insert(ModifyEntryView::update_auto_type_sequence.type_id(), 0);
+            call_map
+                .borrow_mut()
+                .insert(ModifyEntryView::copy_reference.type_id(), 0);
 
-            ModifyEntryView::request_entry_deletion.mock_raw(|_self, _state| {
+            ModifyEntryView::request_entry_deletion.mock_raw(|_self, _vault, _state| {
                 call_map
                     .borrow_mut()
                     .get_mut(&ModifyEntryView::request_entry_deletion.type_id())
@@ -1249,6 +1539,13 @@ mod tests {
                     .map(|c| *c += 1);
                 MockResult::Return(Command::none())
             });
+            ModifyEntryView::copy_reference.mock_raw(|_self, _field, _clipboard| {
+                call_map
+                    .borrow_mut()
+                    .get_mut(&ModifyEntryView::copy_reference.type_id())
+                    .map(|c| *c += 1);
+                MockResult::Return(Command::none())
+            });
 
             // Request entry deletion
             assert_eq!(
@@ -1257,7 +1554,9 @@ mod tests {
             );
             let _ = mev.update_advanced::<TestPlatform>(
                 AdvancedStateMessage::DeleteEntryRequest,
+                &vault,
                 &mut modal_state,
+                &mut clipboard,
             );
             assert_eq!(
                 call_map.borrow()[&ModifyEntryView::request_entry_deletion.type_id()],
@@ -1271,13 +1570,31 @@ mod tests {
             );
             let _ = mev.update_advanced::<TestPlatform>(
                 AdvancedStateMessage::AutoTypeInput("Autotype".into()),
+                &vault,
                 &mut modal_state,
+                &mut clipboard,
             );
             assert_eq!(
                 call_map.borrow()[&ModifyEntryView::update_auto_type_sequence.type_id()],
                 1
             );
 
+            // Copy reference.
+            assert_eq!(
+                call_map.borrow()[&ModifyEntryView::copy_reference.type_id()],
+                0
+            );
+            let _ = mev.update_advanced::<TestPlatform>(
+                AdvancedStateMessage::CopyReference(pwduck_core::ReferenceField::Password),
+                &vault,
+                &mut modal_state,
+                &mut clipboard,
+            );
+            assert_eq!(
+                call_map.borrow()[&ModifyEntryView::copy_reference.type_id()],
+                1
+            );
+
             assert!(call_map.borrow().values().all(|v| *v == 1));
         })
     }
@@ -1287,7 +1604,7 @@ mod tests {
         let mut mev = default_mev();
 
         let mut modal_state = iced_aw::modal::State::new(crate::ModalState::ModifyEntry(
-            ModifyEntryModal::delete_request(),
+            ModifyEntryModal::delete_request(Vec::new()),
         ));
 
         if let crate::ModalState::ModifyEntry(ModifyEntryModal::DeleteRequest { .. }) =
@@ -1349,7 +1666,7 @@ mod tests {
         let mut mev = default_mev();
 
         let mut modal_state = iced_aw::modal::State::new(crate::ModalState::ModifyEntry(
-            ModifyEntryModal::delete_request(),
+            ModifyEntryModal::delete_request(Vec::new()),
         ));
 
         let dir = tempdir().unwrap();
@@ -1426,7 +1743,7 @@ mod tests {
         let mut mev = default_mev();
 
         let mut modal_state = iced_aw::modal::State::new(crate::ModalState::ModifyEntry(
-            ModifyEntryModal::delete_request(),
+            ModifyEntryModal::delete_request(Vec::new()),
         ));
 
         let dir = tempdir().unwrap();
@@ -1447,6 +1764,9 @@ mod tests {
             call_map
                 .borrow_mut()
                 .insert(ModifyEntryView::update_title.type_id(), 0);
+            call_map
+                .borrow_mut()
+                .insert(ModifyEntryView::update_favourite.type_id(), 0);
             call_map
                 .borrow_mut()
                 .insert(ModifyEntryView::update_username.type_id(), 0);
@@ -1496,6 +1816,13 @@ mod tests {
                     .map(|c| *c += 1);
                 MockResult::Return(Command::none())
             });
+            ModifyEntryView::update_favourite.mock_raw(|_self, _value| {
+                call_map
+                    .borrow_mut()
+                    .get_mut(&ModifyEntryView::update_favourite.type_id())
+                    .map(|c| *c += 1);
+                MockResult::Return(Command::none())
+            });
             ModifyEntryView::update_username.mock_raw(|_self, _value| {
                 call_map
                     .borrow_mut()
@@ -1503,12 +1830,12 @@ mod tests {
                     .map(|c| *c += 1);
                 MockResult::Return(Command::none())
             });
-            ModifyEntryView::copy_username.mock_raw(|_self, _clipboard| {
+            ModifyEntryView::copy_username.mock_raw(|_self, _vault, _mem_key, _clipboard| {
                 call_map
                     .borrow_mut()
                     .get_mut(&ModifyEntryView::copy_username.type_id())
                     .map(|c| *c += 1);
-                MockResult::Return(Command::none())
+                MockResult::Return(Ok(Command::none()))
             });
             ModifyEntryView::update_password.mock_raw(|_self, _value| {
                 call_map
@@ -1524,12 +1851,12 @@ mod tests {
                     .map(|c| *c += 1);
                 MockResult::Return(Command::none())
             });
-            ModifyEntryView::copy_password.mock_raw(|_self, _clipboard| {
+            ModifyEntryView::copy_password.mock_raw(|_self, _vault, _mem_key, _clipboard| {
                 call_map
                     .borrow_mut()
                     .get_mut(&ModifyEntryView::copy_password.type_id())
                     .map(|c| *c += 1);
-                MockResult::Return(Command::none())
+                MockResult::Return(Ok(Command::none()))
             });
             ModifyEntryView::update_web_address.mock_raw(|_self, _value| {
                 call_map
@@ -1566,13 +1893,15 @@ mod tests {
                     .map(|c| *c += 1);
                 MockResult::Return(Command::none())
             });
-            ModifyEntryView::update_advanced::<TestPlatform>.mock_raw(|_self, _message, _state| {
-                call_map
-                    .borrow_mut()
-                    .get_mut(&ModifyEntryView::update_advanced::<TestPlatform>.type_id())
-                    .map(|c| *c += 1);
-                MockResult::Return(Command::none())
-            });
+            ModifyEntryView::update_advanced::<TestPlatform>.mock_raw(
+                |_self, _message, _vault, _state, _clipboard| {
+                    call_map
+                        .borrow_mut()
+                        .get_mut(&ModifyEntryView::update_advanced::<TestPlatform>.type_id())
+                        .map(|c| *c += 1);
+                    MockResult::Return(Command::none())
+                },
+            );
             ModifyEntryView::update_modal.mock_raw(|_self, _message, _vault, _state| {
                 call_map
                     .borrow_mut()
@@ -1734,6 +2063,22 @@ mod tests {
                 )
                 .expect_err("Should fail");
 
+            // Update favourite
+            assert_eq!(
+                call_map.borrow()[&ModifyEntryView::update_favourite.type_id()],
+                0
+            );
+            let _ = mev.update::<TestPlatform>(
+                ModifyEntryMessage::FavouriteToggle(true),
+                &mut vault,
+                &mut modal_state,
+                &mut clipboard,
+            );
+            assert_eq!(
+                call_map.borrow()[&ModifyEntryView::update_favourite.type_id()],
+                1
+            );
+
             // Update email
             assert_eq!(
                 call_map.borrow()[&ModifyEntryView::update_email.type_id()],
@@ -1863,7 +2208,7 @@ mod tests {
 
     #[test]
     fn new_delete_request() {
-        let modal_state = ModifyEntryModal::delete_request();
+        let modal_state = ModifyEntryModal::delete_request(Vec::new());
 
         if let ModifyEntryModal::DeleteRequest { .. } = modal_state {
         } else {
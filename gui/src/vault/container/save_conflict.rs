@@ -0,0 +1,135 @@
+//! The modal shown when saving was refused, because other programs changed the vault on disk.
+use iced::{button, scrollable, Column, Element, Length, Row, Scrollable, Text};
+use iced_aw::Card;
+
+use crate::{
+    icons::Icon,
+    theme::Theme,
+    utils::{icon_button, ButtonData, ButtonKind},
+    DEFAULT_COLUMN_SPACING, DEFAULT_MAX_WIDTH, DEFAULT_ROW_SPACING,
+};
+
+/// The maximum height of the list of conflicting items.
+const CONFLICTS_MAX_HEIGHT: u32 = 200;
+
+/// The state of the modal to resolve the conflicts that prevented saving.
+#[derive(Debug)]
+pub struct SaveConflictModal {
+    /// The titles of the items that were changed on disk.
+    titles: Vec<String>,
+    /// The state of the [`Scrollable`](iced::Scrollable) of the titles.
+    scroll_state: scrollable::State,
+    /// The state of the cancel [`Button`](iced::Button).
+    cancel_button_state: button::State,
+    /// The state of the [`Button`](iced::Button) to reload the items from disk.
+    reload_button_state: button::State,
+    /// The state of the [`Button`](iced::Button) to merge the changes on disk.
+    merge_button_state: button::State,
+    /// The state of the [`Button`](iced::Button) to overwrite the changes on disk.
+    overwrite_button_state: button::State,
+}
+
+/// The message send by the modal to resolve the conflicts that prevented saving.
+#[derive(Clone, Debug)]
+pub enum SaveConflictModalMessage {
+    /// Close the modal without saving.
+    Close,
+    /// Discard the unsaved changes of the conflicting items and reload them from disk.
+    Reload,
+    /// Review the changes on disk in the merge modal.
+    Merge,
+    /// Save the vault and overwrite the changes on disk.
+    Overwrite,
+}
+
+impl SaveConflictModal {
+    /// Create the modal listing the given titles of the conflicting items.
+    pub fn new(titles: Vec<String>) -> Self {
+        Self {
+            titles,
+            scroll_state: scrollable::State::new(),
+            cancel_button_state: button::State::new(),
+            reload_button_state: button::State::new(),
+            merge_button_state: button::State::new(),
+            overwrite_button_state: button::State::new(),
+        }
+    }
+
+    /// Create the view of the modal.
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn view(&mut self, theme: &dyn Theme) -> Element<'_, SaveConflictModalMessage> {
+        let titles = self.titles.iter().fold(
+            Scrollable::new(&mut self.scroll_state)
+                .spacing(DEFAULT_COLUMN_SPACING)
+                .max_height(CONFLICTS_MAX_HEIGHT)
+                .width(Length::Fill),
+            |list, title| list.push(Text::new(title)),
+        );
+
+        Card::new(
+            Text::new("The vault was not saved"),
+            Column::new()
+                .spacing(DEFAULT_COLUMN_SPACING)
+                .push(Text::new(
+                    "Another program changed the following items on disk \
+                    since they were loaded or saved:",
+                ))
+                .push(titles),
+        )
+        .foot(
+            Row::new()
+                .spacing(DEFAULT_ROW_SPACING)
+                .push(icon_button(
+                    ButtonData {
+                        state: &mut self.cancel_button_state,
+                        icon: Icon::XSquare,
+                        text: "Cancel",
+                        kind: ButtonKind::Normal,
+                        on_press: Some(SaveConflictModalMessage::Close),
+                    },
+                    "Close without saving",
+                    false,
+                    theme,
+                ))
+                .push(icon_button(
+                    ButtonData {
+                        state: &mut self.reload_button_state,
+                        icon: Icon::ArrowClockwise,
+                        text: "Reload",
+                        kind: ButtonKind::Normal,
+                        on_press: Some(SaveConflictModalMessage::Reload),
+                    },
+                    "Discard the unsaved changes of these items and reload them from disk",
+                    false,
+                    theme,
+                ))
+                .push(icon_button(
+                    ButtonData {
+                        state: &mut self.merge_button_state,
+                        icon: Icon::List,
+                        text: "Merge",
+                        kind: ButtonKind::Primary,
+                        on_press: Some(SaveConflictModalMessage::Merge),
+                    },
+                    "Choose the version to keep for each item",
+                    false,
+                    theme,
+                ))
+                .push(icon_button(
+                    ButtonData {
+                        state: &mut self.overwrite_button_state,
+                        icon: Icon::Save,
+                        text: "Overwrite",
+                        kind: ButtonKind::Warning,
+                        on_press: Some(SaveConflictModalMessage::Overwrite),
+                    },
+                    "Save the vault and overwrite the changes on disk",
+                    false,
+                    theme,
+                )),
+        )
+        .style(theme.card_warning())
+        .max_width(DEFAULT_MAX_WIDTH)
+        .into()
+    }
+}
@@ -19,6 +19,16 @@ pub struct ToolBar {
     new_group_state: button::State,
     /// The state of the [`Button`](iced:Button) to create a new entry.
     new_entry_state: button::State,
+    /// The state of the [`Button`](iced::Button) to import a database.
+    import_state: button::State,
+    /// The state of the [`Button`](iced::Button) to export a database.
+    export_state: button::State,
+    /// The state of the [`Button`](iced::Button) to export an unencrypted file.
+    plain_export_state: button::State,
+    /// The state of the [`Button`](iced::Button) to back up the vault.
+    backup_state: button::State,
+    /// The state of the [`Button`](iced::Button) to merge another copy of the vault.
+    merge_state: button::State,
     /// The state of the autofill [`Button`](Button)
     auto_fill: button::State,
     /// The state of the lock [`Button`](iced::Button).
@@ -34,6 +44,16 @@ pub enum ToolBarMessage {
     NewGroup,
     /// Create a new entry.
     NewEntry,
+    /// Import the database of another password manager.
+    Import,
+    /// Export into the database of another password manager.
+    Export,
+    /// Export into an unencrypted CSV file or JSON document.
+    PlainExport,
+    /// Back up the vault into a single file.
+    Backup,
+    /// Merge another copy of the vault.
+    Merge,
     /// Autofill the credentials.
     AutoFill,
     /// Lock the vault.
@@ -95,6 +115,85 @@ impl ToolBar {
             theme,
         );
 
+        let import = icon_button(
+            ButtonData {
+                state: &mut self.import_state,
+                icon: Icon::Safe,
+                text: "Import",
+                kind: ButtonKind::Normal,
+                on_press: ToolBarMessage::Import.some_if_not(flags.intersects(
+                    Flags::MODIFY_GROUP_VIEW_IS_SOME | Flags::MODIFY_ENTRY_VIEW_IS_SOME,
+                )),
+            },
+            "Import a database or export of another password manager into the selected group",
+            flags.contains(Flags::HIDE_TOOLBAR_LABELS),
+            theme,
+        );
+
+        let export = icon_button(
+            ButtonData {
+                state: &mut self.export_state,
+                icon: Icon::FileEarmarkLock,
+                text: "Export",
+                kind: ButtonKind::Normal,
+                on_press: ToolBarMessage::Export.some_if_not(flags.intersects(
+                    Flags::MODIFY_GROUP_VIEW_IS_SOME | Flags::MODIFY_ENTRY_VIEW_IS_SOME,
+                )),
+            },
+            "Export the selected group into a KeePass database",
+            flags.contains(Flags::HIDE_TOOLBAR_LABELS),
+            theme,
+        );
+
+        let plain_export = icon_button(
+            ButtonData {
+                state: &mut self.plain_export_state,
+                icon: Icon::FileEarmarkPerson,
+                text: "Plain export",
+                kind: ButtonKind::Normal,
+                on_press: ToolBarMessage::PlainExport.some_if_not(flags.intersects(
+                    Flags::MODIFY_GROUP_VIEW_IS_SOME | Flags::MODIFY_ENTRY_VIEW_IS_SOME,
+                )),
+            },
+            "Export the listed groups and entries unencrypted into a CSV or JSON file",
+            flags.contains(Flags::HIDE_TOOLBAR_LABELS),
+            theme,
+        );
+
+        let backup = icon_button(
+            ButtonData {
+                state: &mut self.backup_state,
+                icon: Icon::Safe,
+                text: "Backup",
+                kind: ButtonKind::Normal,
+                on_press: ToolBarMessage::Backup.some_if_not(flags.intersects(
+                    Flags::VAULT_CONTAINS_UNSAVED_CHANGES
+                        | Flags::MODIFY_GROUP_VIEW_IS_SOME
+                        | Flags::MODIFY_ENTRY_VIEW_IS_SOME,
+                )),
+            },
+            "Back up the saved vault into a single file",
+            flags.contains(Flags::HIDE_TOOLBAR_LABELS),
+            theme,
+        );
+
+        let merge = icon_button(
+            ButtonData {
+                state: &mut self.merge_state,
+                icon: Icon::ArrowClockwise,
+                text: "Merge",
+                kind: ButtonKind::Normal,
+                on_press: ToolBarMessage::Merge.some_if_not(flags.intersects(
+                    Flags::VAULT_CONTAINS_UNSAVED_CHANGES
+                        | Flags::MODIFY_GROUP_VIEW_IS_SOME
+                        | Flags::MODIFY_ENTRY_VIEW_IS_SOME,
+                )),
+            },
+            "Merge the changes of another copy of the vault, e.g. from another device",
+            flags.contains(Flags::HIDE_TOOLBAR_LABELS),
+            theme,
+        );
+
         let autofill = icon_button(
             ButtonData {
                 state: &mut self.auto_fill,
@@ -126,10 +225,21 @@ impl ToolBar {
             theme,
         );
 
-        Row::with_children(vec![save, new_group, new_entry, autofill, lock_vault])
-            .spacing(DEFAULT_ROW_SPACING)
-            .width(Length::Fill)
-            .into()
+        Row::with_children(vec![
+            save,
+            new_group,
+            new_entry,
+            import,
+            export,
+            plain_export,
+            backup,
+            merge,
+            autofill,
+            lock_vault,
+        ])
+        .spacing(DEFAULT_ROW_SPACING)
+        .width(Length::Fill)
+        .into()
     }
 }
 
@@ -1,9 +1,9 @@
 //! The view of the vault loader
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use iced::{button, text_input, Checkbox, Column, Command, Element, Row, Text};
 use iced_focus::Focus;
-use pwduck_core::{PWDuckCoreError, SecString, Vault};
+use pwduck_core::{PWDuckCoreError, SecString, Vault, BACKUP_EXTENSION};
 use zeroize::Zeroize;
 
 use crate::{
@@ -51,6 +51,11 @@ pub struct VaultLoader {
     /// Use a key file
     use_key_file: bool,
 
+    /// The backup to restore as a new vault at the path.
+    backup: Option<PathBuf>,
+    /// The state of the [`Button`](iced::Button) to restore or to stop restoring a backup.
+    restore_state: button::State,
+
     /// The state of the [`Button`](iced::Button) to show the vault creator.
     create_state: button::State,
     /// The state of teh [`Button`](iced::Button) to submit the loading of the vault.
@@ -92,7 +97,25 @@ impl VaultLoader {
         Command::none()
     }
 
+    /// Restore the given backup. The path is replaced by a new directory next to the backup.
+    fn select_backup(&mut self, backup: PathBuf) -> Command<VaultLoaderMessage> {
+        let parent = backup.parent().map(Path::to_path_buf).unwrap_or_default();
+        self.path = restore_path(&parent, &backup)
+            .to_string_lossy()
+            .into_owned();
+        self.backup = Some(backup);
+        Command::none()
+    }
+
+    /// Stop restoring the backup and load an existing vault again.
+    fn cancel_restore(&mut self) -> Command<VaultLoaderMessage> {
+        self.backup = None;
+        self.path.clear();
+        Command::none()
+    }
+
     /// Submit the loading of the vault.
+    /// If a backup is selected, it is restored to the path first.
     fn submit(&mut self) -> Command<VaultLoaderMessage> {
         if self.path.is_empty() || self.password.is_empty() {
             return Command::none();
@@ -111,8 +134,19 @@ impl VaultLoader {
                     None
                 };
 
+                let backup = self.backup.clone();
+
                 async move {
                     let mem_key = crate::MEM_KEY.lock()?;
+                    if let Some(backup) = backup {
+                        pwduck_core::Vault::restore_backup(
+                            &backup,
+                            &password,
+                            key_file.as_deref().map(Path::new),
+                            &mem_key,
+                            &path,
+                        )?;
+                    }
                     let vault = pwduck_core::Vault::load(&password, key_file, &mem_key, path);
 
                     vault.map(Box::new)
@@ -134,6 +168,22 @@ impl VaultLoader {
             VaultLoaderMessage::KeyFileSelected,
         )
     }
+
+    /// Open the native file dialog of the [`Platform`](Platform) to choose the backup to restore.
+    fn open_file_dialog_backup<P: Platform + 'static>() -> Command<VaultLoaderMessage> {
+        Command::perform(
+            P::nfd_choose_file("PWDuck backup".into(), vec![BACKUP_EXTENSION.into()], None),
+            VaultLoaderMessage::BackupSelected,
+        )
+    }
+}
+
+/// The location of the new vault restored from the backup inside of the given directory.
+fn restore_path(parent: &Path, backup: &Path) -> PathBuf {
+    let name = backup
+        .file_stem()
+        .map_or_else(|| "Vault".into(), |stem| stem.to_string_lossy());
+    parent.join(format!("{} (restored)", name))
 }
 
 /// The message created by the vault loader.
@@ -162,6 +212,10 @@ pub enum VaultLoaderMessage {
 
     /// Show the vault creator.
     Create,
+    /// Choose a backup to restore or stop restoring the selected backup.
+    Restore,
+    /// The backup was selected by the native file dialog.
+    BackupSelected(Result<PathBuf, NfdError>),
     /// Submit the loading of the vault.
     Submit,
 
@@ -191,6 +245,9 @@ impl Component for VaultLoader {
             key_file_open_fd_state: button::State::new(),
             use_key_file: false,
 
+            backup: None,
+            restore_state: button::State::new(),
+
             create_state: button::State::new(),
             submit_state: button::State::new(),
         }
@@ -214,6 +271,10 @@ impl Component for VaultLoader {
             VaultLoaderMessage::OpenFileDialog => Self::open_file_dialog_path::<P>(),
 
             VaultLoaderMessage::PathSelected(Ok(path)) => {
+                let path = match &self.backup {
+                    Some(backup) => restore_path(&path, backup),
+                    None => path,
+                };
                 let cmd = self.update_path(path.to_str().ok_or(PWDuckGuiError::Option)?.to_owned());
                 self.path_state.unfocus();
                 self.password_state.focus();
@@ -240,6 +301,23 @@ impl Component for VaultLoader {
 
             VaultLoaderMessage::KeyFileSelected(Err(_err)) => Command::none(),
 
+            VaultLoaderMessage::Restore => {
+                if self.backup.is_some() {
+                    self.cancel_restore()
+                } else {
+                    Self::open_file_dialog_backup::<P>()
+                }
+            }
+
+            VaultLoaderMessage::BackupSelected(Ok(backup)) => {
+                let cmd = self.select_backup(backup);
+                self.path_state.unfocus();
+                self.password_state.focus();
+                cmd
+            }
+
+            VaultLoaderMessage::BackupSelected(Err(_err)) => Command::none(),
+
             VaultLoaderMessage::Submit => self.submit(),
 
             VaultLoaderMessage::Create | VaultLoaderMessage::Loaded(_) => {
@@ -260,6 +338,7 @@ impl Component for VaultLoader {
             &mut self.path_state,
             &self.path,
             &mut self.path_open_fd_state,
+            self.backup.is_some(),
             theme,
         );
 
@@ -279,18 +358,26 @@ impl Component for VaultLoader {
             theme,
         );
 
-        let button_row = button_row(
+        let button_row = button_row::<P>(
             &mut self.create_state,
+            &mut self.restore_state,
             &mut self.submit_state,
+            self.backup.is_some(),
             !(self.path.is_empty() || self.password.is_empty()),
             theme,
         );
 
+        let header = match &self.backup {
+            Some(backup) => format!(
+                "Restore backup {}:",
+                backup.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            None => "Open existing Vault:".into(),
+        };
+
         centered_container_with_column(
             vec![
-                Text::new("Open existing Vault:")
-                    .size(DEFAULT_HEADER_SIZE)
-                    .into(),
+                Text::new(header).size(DEFAULT_HEADER_SIZE).into(),
                 path_row,
                 password_row,
                 default_vertical_space().into(),
@@ -310,11 +397,13 @@ impl Component for VaultLoader {
 ///  - The state of the [`TextInput`](iced::TextInput)
 ///  - The value of the path
 ///  - The state of the [`Button`](iced::Button) to open the native file dialog
+///  - True, if the path is the location of a restored backup
 #[cfg_attr(coverage, no_coverage)]
 fn path_row<'a, P: Platform + 'static>(
     path_state: &'a mut text_input::State,
     path: &'a str,
     path_open_fd_state: &'a mut button::State,
+    restore: bool,
     theme: &dyn Theme,
 ) -> Element<'a, VaultLoaderMessage> {
     let path_fd_button = icon_button(
@@ -325,14 +414,22 @@ fn path_row<'a, P: Platform + 'static>(
             kind: ButtonKind::Normal,
             on_press: VaultLoaderMessage::OpenFileDialog.some_if(P::is_nfd_available()),
         },
-        "Select the directory of the vault",
+        if restore {
+            "Select the directory to restore the backup into"
+        } else {
+            "Select the directory of the vault"
+        },
         true,
         theme,
     );
 
     let mut vault_path = default_text_input(
         path_state,
-        "Choose a Vault",
+        if restore {
+            "Choose the location of the restored Vault"
+        } else {
+            "Choose a Vault"
+        },
         path,
         VaultLoaderMessage::PathInput,
     )
@@ -458,11 +555,15 @@ fn key_file_row<'a, P: Platform + 'static>(
 ///
 /// It expects:
 ///  - The state of the create [`Button`](iced::Button)
+///  - The state of the restore [`Button`](iced::Button)
 ///  - The state of the submit [`Button`](iced::Button)
+///  - True, if a backup is restored
 ///  - True, if the creation can be submitted.
-fn button_row<'a>(
+fn button_row<'a, P: Platform + 'static>(
     create_state: &'a mut button::State,
+    restore_state: &'a mut button::State,
     submit_state: &'a mut button::State,
+    restore: bool,
     can_submit: bool,
     theme: &dyn Theme,
 ) -> Element<'a, VaultLoaderMessage> {
@@ -479,15 +580,47 @@ fn button_row<'a>(
         theme,
     );
 
+    let restore_backup = if restore {
+        icon_button(
+            ButtonData {
+                state: restore_state,
+                icon: Icon::XSquare,
+                text: "Cancel restore",
+                kind: ButtonKind::Normal,
+                on_press: Some(VaultLoaderMessage::Restore),
+            },
+            "Open an existing vault instead of restoring the backup",
+            false,
+            theme,
+        )
+    } else {
+        icon_button(
+            ButtonData {
+                state: restore_state,
+                icon: Icon::ArrowClockwise,
+                text: "Restore",
+                kind: ButtonKind::Normal,
+                on_press: VaultLoaderMessage::Restore.some_if(P::is_nfd_available()),
+            },
+            "Restore a vault from a backup",
+            false,
+            theme,
+        )
+    };
+
     let unlock_vault = icon_button(
         ButtonData {
             state: submit_state,
             icon: Icon::Unlock,
-            text: "Unlock",
+            text: if restore { "Restore" } else { "Unlock" },
             kind: ButtonKind::Primary,
             on_press: VaultLoaderMessage::Submit.some_if(can_submit),
         },
-        "Unlock vault",
+        if restore {
+            "Restore the backup as a new vault and unlock it"
+        } else {
+            "Unlock vault"
+        },
         false,
         theme,
     );
@@ -495,6 +628,7 @@ fn button_row<'a>(
     Row::new()
         .spacing(DEFAULT_ROW_SPACING)
         .push(create)
+        .push(restore_backup)
         .push(unlock_vault)
         .into()
 }
@@ -506,6 +640,7 @@ mod tests {
         any::{Any, TypeId},
         cell::RefCell,
         collections::HashMap,
+        path::PathBuf,
     };
 
     use iced::Command;
@@ -604,6 +739,36 @@ mod tests {
         assert!(cmd.futures().is_empty());
     }
 
+    #[test]
+    fn select_backup() {
+        let mut vault_loader = VaultLoader::new(());
+        assert!(vault_loader.backup.is_none());
+
+        let _cmd = vault_loader.select_backup(PathBuf::from("backups/Vault.pwduck-backup"));
+        assert_eq!(
+            vault_loader.backup,
+            Some(PathBuf::from("backups/Vault.pwduck-backup"))
+        );
+        assert_eq!(
+            PathBuf::from(&vault_loader.path),
+            PathBuf::from("backups/Vault (restored)")
+        );
+
+        let _cmd = vault_loader.update_password("password".into());
+        let cmd = vault_loader.submit();
+        assert!(!cmd.futures().is_empty());
+
+        let _cmd = vault_loader.cancel_restore();
+        assert!(vault_loader.backup.is_none());
+        assert!(vault_loader.path.is_empty());
+    }
+
+    #[test]
+    fn open_file_dialog_backup() {
+        let cmd = VaultLoader::open_file_dialog_backup::<TestPlatform>();
+        assert!(!cmd.futures().is_empty());
+    }
+
     #[test]
     fn open_file_dialog() {
         let cmd = VaultLoader::open_file_dialog_path::<TestPlatform>();
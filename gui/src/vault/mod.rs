@@ -1,9 +1,12 @@
 //! The views to create, load, unlock and manage the vault.
 //! It also contains the view of the settings.
 
+#[cfg(unix)]
+pub mod confirmation;
 pub mod container;
 pub mod creator;
 pub mod loader;
 pub mod settings;
 pub mod tab;
 pub mod unlock;
+pub mod watcher;
@@ -41,6 +41,21 @@ impl VaultTab {
         }
     }
 
+    /// True, if the tab contains an unlocked vault.
+    #[must_use]
+    pub const fn is_open(&self) -> bool {
+        matches!(self.state, VaultTabState::Open(_))
+    }
+
+    /// The location of the unlocked vault of the tab.
+    #[must_use]
+    pub fn vault_path(&self) -> Option<&PathBuf> {
+        match &self.state {
+            VaultTabState::Open(container) => Some(container.vault().path()),
+            _ => None,
+        }
+    }
+
     /// Change the content of the tab to the [`VaultCreator`](VaultCreator).
     fn change_to_create_state(&mut self) -> Command<VaultTabMessage> {
         self.state = VaultTabState::Create(Box::new(VaultCreator::new(())));
@@ -344,6 +359,27 @@ mod tests {
         assert!(!vault_tab.contains_unsaved_changes());
     }
 
+    #[test]
+    fn is_open() {
+        let mut vault_tab = VaultTab::new(());
+        assert!(!vault_tab.is_open());
+
+        let _ = vault_tab.change_to_create_state();
+        assert!(!vault_tab.is_open());
+
+        let mem_key = pwduck_core::MemKey::with_length(1);
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("TempVault");
+        let vault =
+            pwduck_core::Vault::generate("password", Option::<String>::None, &mem_key, &path)
+                .unwrap();
+        let _ = vault_tab.change_to_open_state(Box::new(vault));
+        assert!(vault_tab.is_open());
+
+        let _ = vault_tab.change_to_unlock_state(path.into(), None);
+        assert!(!vault_tab.is_open());
+    }
+
     #[test]
     fn change_to_create_state() {
         let mut vaul_tab = VaultTab::new(());
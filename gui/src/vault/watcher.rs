@@ -0,0 +1,59 @@
+//! The subscription to the changes of an open vault made on disk by other programs.
+use std::{hash::Hash, path::PathBuf, time::Duration};
+
+use iced::{
+    futures::{channel::mpsc, stream::BoxStream},
+    Subscription,
+};
+use pwduck_core::VaultWatcher;
+
+/// The interval to check if the subscription was dropped.
+const CLOSE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Watch the vault at the given location.
+/// The location is send every time groups, entries, saved searches or the settings were changed on disk.
+pub fn watch(path: PathBuf) -> Subscription<PathBuf> {
+    Subscription::from_recipe(VaultWatch { path })
+}
+
+/// The recipe of the subscription watching a vault.
+#[derive(Debug)]
+struct VaultWatch {
+    /// The location of the vault.
+    path: PathBuf,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for VaultWatch
+where
+    H: std::hash::Hasher,
+{
+    type Output = PathBuf;
+
+    fn hash(&self, state: &mut H) {
+        std::any::TypeId::of::<Self>().hash(state);
+        self.path.hash(state);
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        let (sender, receiver) = mpsc::unbounded();
+        let path = self.path;
+
+        // The watcher blocks while waiting, so it must not run on the executor.
+        drop(std::thread::spawn(move || {
+            let watcher = match VaultWatcher::new(&path) {
+                Ok(watcher) => watcher,
+                Err(_err) => return,
+            };
+            loop {
+                match watcher.wait_for_change(CLOSE_CHECK_INTERVAL) {
+                    Ok(true) if sender.unbounded_send(path.clone()).is_ok() => {}
+                    Ok(false) if !sender.is_closed() => {}
+                    _ => break,
+                }
+            }
+        }));
+
+        Box::pin(receiver)
+    }
+}
@@ -108,6 +108,29 @@ mod desktop {
             Ok(key_file.path().into())
         }
 
+        async fn nfd_choose_file(
+            filter_name: String,
+            extensions: Vec<String>,
+            file_name: Option<String>,
+        ) -> Result<PathBuf, pwduck_gui::error::NfdError> {
+            let extensions: Vec<&str> = extensions.iter().map(String::as_str).collect();
+            let file = AsyncFileDialog::new()
+                .set_directory(
+                    dirs::document_dir()
+                        .unwrap_or_else(|| dirs::home_dir().unwrap_or_else(|| "".into())),
+                )
+                .add_filter(&filter_name, &extensions);
+
+            let file = if let Some(file_name) = file_name {
+                file.set_file_name(&file_name).save_file().await
+            } else {
+                file.pick_file().await
+            }
+            .ok_or(NfdError::Null)?;
+
+            Ok(file.path().into())
+        }
+
         fn is_open_in_browser_available() -> bool {
             true
         }